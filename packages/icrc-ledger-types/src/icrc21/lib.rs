@@ -1,9 +1,16 @@
-use super::responses::ConsentMessage;
+use super::errors::ErrorInfo;
+use super::requests::{ConsentMessageMetadata, ConsentMessageRequest};
+use super::responses::{ConsentInfo, ConsentMessage};
 use super::{requests::DisplayMessageType, responses::LineDisplayPage};
 use crate::icrc1::account::Account;
+use crate::icrc1::transfer::TransferArg;
+use crate::icrc2::approve::ApproveArgs;
+use crate::icrc2::transfer_from::TransferFromArgs;
 use crate::icrc21::errors::Icrc21Error;
-use candid::Nat;
+use candid::{Decode, Nat, Principal};
 use itertools::Itertools;
+use num_bigint::BigUint;
+use num_traits::Zero;
 use serde_bytes::ByteBuf;
 use strum;
 use strum::EnumString;
@@ -34,6 +41,7 @@ pub struct ConsentMessageBuilder {
     receiver: Option<Account>,
     amount: Option<Nat>,
     token_symbol: Option<String>,
+    decimals: Option<u8>,
     fee_set: Option<Nat>,
     ledger_fee: Option<Nat>,
     memo: Option<ByteBuf>,
@@ -61,6 +69,7 @@ impl ConsentMessageBuilder {
             receiver: None,
             amount: None,
             token_symbol: None,
+            decimals: None,
             fee_set: None,
             ledger_fee: None,
             memo: None,
@@ -100,6 +109,11 @@ impl ConsentMessageBuilder {
         self
     }
 
+    pub fn with_decimals(mut self, decimals: u8) -> Self {
+        self.decimals = Some(decimals);
+        self
+    }
+
     pub fn with_fee_set(mut self, fee_set: Nat) -> Self {
         self.fee_set = Some(fee_set);
         self
@@ -136,6 +150,12 @@ impl ConsentMessageBuilder {
     }
 
     pub fn build(self) -> Result<ConsentMessage, Icrc21Error> {
+        // Without decimals, amounts are rendered in the smallest unit of the token.
+        let decimals = self.decimals;
+        let format_tokens = |tokens: Nat| match decimals {
+            Some(decimals) => convert_tokens_to_string_representation(tokens, decimals),
+            None => tokens.to_string(),
+        };
         let mut message = match self.function {
             Icrc21Function::Transfer => ICRC1_TRANSFER_DISPLAY_MESSAGE
                 .replace(
@@ -213,13 +233,10 @@ impl ConsentMessageBuilder {
         }
         .replace(
             "{AMOUNT}",
-            &self
-                .amount
-                .ok_or(Icrc21Error::GenericError {
-                    error_code: Nat::from(500u64),
-                    description: "Amount has to be specified.".to_owned(),
-                })?
-                .to_string(),
+            &format_tokens(self.amount.ok_or(Icrc21Error::GenericError {
+                error_code: Nat::from(500u64),
+                description: "Amount has to be specified.".to_owned(),
+            })?),
         )
         .replace(
             "{TOKEN_SYMBOL}",
@@ -230,13 +247,10 @@ impl ConsentMessageBuilder {
         )
         .replace(
             "{LEDGER_FEE}",
-            &self
-                .ledger_fee
-                .ok_or(Icrc21Error::GenericError {
-                    error_code: Nat::from(500u64),
-                    description: "Ledger Fee must be specified.".to_owned(),
-                })?
-                .to_string(),
+            &format_tokens(self.ledger_fee.ok_or(Icrc21Error::GenericError {
+                error_code: Nat::from(500u64),
+                description: "Ledger Fee must be specified.".to_owned(),
+            })?),
         );
 
         match self.display_type {
@@ -257,7 +271,7 @@ impl ConsentMessageBuilder {
                 if let Some(expected_allowance) = self.expected_allowance {
                     message.push_str(&format!(
                         "\n*The expected allowance before approving the requested amount is: {}",
-                        expected_allowance
+                        format_tokens(expected_allowance)
                     ));
                 }
                 if let Some(expires_at) = self.expires_at {
@@ -279,6 +293,196 @@ impl ConsentMessageBuilder {
     }
 }
 
+/// Renders a token amount given in the smallest unit of the token as a
+/// decimal number, e.g., `150_000_000` with 8 decimals is rendered as `1.5`.
+pub fn convert_tokens_to_string_representation(tokens: Nat, decimals: u8) -> String {
+    let base = BigUint::from(10u32).pow(decimals as u32);
+    let whole = &tokens.0 / &base;
+    let fraction = &tokens.0 % &base;
+    if fraction.is_zero() {
+        return whole.to_string();
+    }
+    let fraction = format!(
+        "{:0>width$}",
+        fraction.to_string(),
+        width = decimals as usize
+    );
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+/// Builds the ICRC-21 consent info for the `icrc1_transfer`, `icrc2_approve`
+/// and `icrc2_transfer_from` endpoints of a ledger. The ledger fee is expected
+/// in the smallest unit of the token. Amounts are rendered using `decimals` if
+/// given, and in the smallest unit of the token otherwise.
+pub fn build_icrc21_consent_info_for_icrc1_and_icrc2_endpoints(
+    consent_msg_request: ConsentMessageRequest,
+    caller_principal: Principal,
+    ledger_fee: Nat,
+    token_symbol: String,
+    decimals: Option<u8>,
+) -> Result<ConsentInfo, Icrc21Error> {
+    if consent_msg_request.arg.len() > MAX_CONSENT_MESSAGE_ARG_SIZE_BYTES as usize {
+        return Err(Icrc21Error::UnsupportedCanisterCall(ErrorInfo {
+            description: format!(
+                "The argument size is too large. The maximum allowed size is {} bytes.",
+                MAX_CONSENT_MESSAGE_ARG_SIZE_BYTES
+            ),
+        }));
+    }
+
+    // only English supported
+    let metadata = ConsentMessageMetadata {
+        language: "en".to_string(),
+    };
+
+    let mut display_message_builder = ConsentMessageBuilder::new(&consent_msg_request.method)?
+        .with_ledger_fee(ledger_fee)
+        .with_token_symbol(token_symbol);
+    if let Some(decimals) = decimals {
+        display_message_builder = display_message_builder.with_decimals(decimals);
+    }
+    if let Some(display_type) = consent_msg_request.user_preferences.device_spec {
+        if let DisplayMessageType::LineDisplay {
+            lines_per_page,
+            characters_per_line,
+        } = display_type
+        {
+            if lines_per_page == 0 || characters_per_line == 0 {
+                return Err(Icrc21Error::UnsupportedCanisterCall(ErrorInfo {
+                    description: "Invalid display type. Lines per page and characters per line must be greater than 0.".to_string()
+                }));
+            }
+        }
+        display_message_builder = display_message_builder.with_display_type(display_type);
+    }
+
+    let consent_message = match consent_msg_request.method.as_str() {
+        "icrc1_transfer" => {
+            let TransferArg {
+                memo,
+                amount,
+                fee,
+                from_subaccount,
+                to,
+                created_at_time,
+            } = Decode!(&consent_msg_request.arg, TransferArg).map_err(|e| {
+                Icrc21Error::UnsupportedCanisterCall(ErrorInfo {
+                    description: format!("Failed to decode TransferArg: {}", e),
+                })
+            })?;
+            let sender = Account {
+                owner: caller_principal,
+                subaccount: from_subaccount,
+            };
+            display_message_builder = display_message_builder
+                .with_amount(amount)
+                .with_receiver(to)
+                .with_sender(sender);
+
+            if let Some(memo) = memo {
+                display_message_builder = display_message_builder.with_memo(memo.0);
+            }
+            if let Some(created_at_time) = created_at_time {
+                display_message_builder =
+                    display_message_builder.with_created_at_time(created_at_time);
+            }
+            if let Some(fee) = fee {
+                display_message_builder = display_message_builder.with_fee_set(fee);
+            }
+            display_message_builder.build()
+        }
+        "icrc2_transfer_from" => {
+            let TransferFromArgs {
+                memo,
+                amount,
+                fee,
+                from,
+                to,
+                spender_subaccount,
+                created_at_time,
+            } = Decode!(&consent_msg_request.arg, TransferFromArgs).map_err(|e| {
+                Icrc21Error::UnsupportedCanisterCall(ErrorInfo {
+                    description: format!("Failed to decode TransferFromArgs: {}", e),
+                })
+            })?;
+            let spender = Account {
+                owner: caller_principal,
+                subaccount: spender_subaccount,
+            };
+            display_message_builder = display_message_builder
+                .with_amount(amount)
+                .with_receiver(to)
+                .with_sender(from)
+                .with_spender(spender);
+
+            if let Some(memo) = memo {
+                display_message_builder = display_message_builder.with_memo(memo.0);
+            }
+            if let Some(created_at_time) = created_at_time {
+                display_message_builder =
+                    display_message_builder.with_created_at_time(created_at_time);
+            }
+            if let Some(fee) = fee {
+                display_message_builder = display_message_builder.with_fee_set(fee);
+            }
+            display_message_builder.build()
+        }
+        "icrc2_approve" => {
+            let ApproveArgs {
+                memo,
+                amount,
+                fee,
+                from_subaccount,
+                spender,
+                created_at_time,
+                expires_at,
+                expected_allowance,
+            } = Decode!(&consent_msg_request.arg, ApproveArgs).map_err(|e| {
+                Icrc21Error::UnsupportedCanisterCall(ErrorInfo {
+                    description: format!("Failed to decode ApproveArgs: {}", e),
+                })
+            })?;
+            let approver = Account {
+                owner: caller_principal,
+                subaccount: from_subaccount,
+            };
+            display_message_builder = display_message_builder
+                .with_amount(amount)
+                .with_approver(approver)
+                .with_spender(spender);
+
+            if let Some(memo) = memo {
+                display_message_builder = display_message_builder.with_memo(memo.0);
+            }
+            if let Some(created_at_time) = created_at_time {
+                display_message_builder =
+                    display_message_builder.with_created_at_time(created_at_time);
+            }
+            if let Some(expires_at) = expires_at {
+                display_message_builder = display_message_builder.with_expires_at(expires_at);
+            }
+            if let Some(expected_allowance) = expected_allowance {
+                display_message_builder =
+                    display_message_builder.with_expected_allowance(expected_allowance);
+            }
+            if let Some(fee) = fee {
+                display_message_builder = display_message_builder.with_fee_set(fee);
+            }
+            display_message_builder.build()
+        }
+        method => {
+            return Err(Icrc21Error::UnsupportedCanisterCall(ErrorInfo {
+                description: format!("Unsupported method: {}", method),
+            }))
+        }
+    }?;
+
+    Ok(ConsentInfo {
+        metadata,
+        consent_message,
+    })
+}
+
 /// This function was taken from the reference implementation: https://github.com/dfinity/wg-identity-authentication/blob/3ed140225b283c0a1cc88344d0cfb9912aec73cd/reference-implementations/ICRC-21/src/lib.rs#L73
 pub fn consent_msg_text_pages(
     message: &str,
@@ -327,3 +531,74 @@ pub fn consent_msg_text_pages(
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{convert_tokens_to_string_representation, ConsentMessageBuilder};
+    use crate::icrc1::account::Account;
+    use crate::icrc21::responses::ConsentMessage;
+    use candid::{Nat, Principal};
+
+    fn transfer_consent_message(decimals: Option<u8>) -> String {
+        let mut builder = ConsentMessageBuilder::new("icrc1_transfer")
+            .unwrap()
+            .with_sender(Account::from(Principal::anonymous()))
+            .with_receiver(Account::from(Principal::management_canister()))
+            .with_amount(Nat::from(150u64))
+            .with_ledger_fee(Nat::from(10u64))
+            .with_token_symbol("XTK".to_string());
+        if let Some(decimals) = decimals {
+            builder = builder.with_decimals(decimals);
+        }
+        match builder.build().unwrap() {
+            ConsentMessage::GenericDisplayMessage(message) => message,
+            ConsentMessage::LineDisplayMessage { .. } => panic!("expected a generic message"),
+        }
+    }
+
+    #[test]
+    fn test_consent_message_amounts_without_decimals() {
+        let message = transfer_consent_message(None);
+        assert!(
+            message.contains("Transfers 150 XTK"),
+            "Message: {}",
+            message
+        );
+        assert!(message.contains("is 10 XTK"), "Message: {}", message);
+    }
+
+    #[test]
+    fn test_consent_message_amounts_with_decimals() {
+        let message = transfer_consent_message(Some(2));
+        assert!(
+            message.contains("Transfers 1.5 XTK"),
+            "Message: {}",
+            message
+        );
+        assert!(message.contains("is 0.1 XTK"), "Message: {}", message);
+    }
+
+    #[test]
+    fn test_convert_tokens_to_string_representation() {
+        assert_eq!(
+            convert_tokens_to_string_representation(Nat::from(0u64), 8),
+            "0"
+        );
+        assert_eq!(
+            convert_tokens_to_string_representation(Nat::from(150_000_000u64), 8),
+            "1.5"
+        );
+        assert_eq!(
+            convert_tokens_to_string_representation(Nat::from(10_000u64), 8),
+            "0.0001"
+        );
+        assert_eq!(
+            convert_tokens_to_string_representation(Nat::from(1_000_000u64), 0),
+            "1000000"
+        );
+        assert_eq!(
+            convert_tokens_to_string_representation(Nat::from(1_000_000_000_000_000_001u64), 18),
+            "1.000000000000000001"
+        );
+    }
+}
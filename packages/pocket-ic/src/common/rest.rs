@@ -9,7 +9,7 @@ use reqwest::blocking::Response;
 use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::path::PathBuf;
 
 pub type InstanceId = usize;
@@ -122,6 +122,11 @@ pub enum RawWasmResult {
     Reject(String),
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawCheckpoint {
+    pub label: String,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct RawSetStableMemory {
     #[serde(deserialize_with = "base64::deserialize")]
@@ -272,6 +277,136 @@ pub struct RawVerifyCanisterSigArg {
     pub root_pubkey: Vec<u8>,
 }
 
+#[derive(
+    Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum CanisterHttpMethod {
+    GET,
+    POST,
+    HEAD,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema)]
+pub struct CanisterHttpHeader {
+    pub name: String,
+    pub value: String,
+}
+
+/// A pending canister HTTP outcall as seen by the PocketIC server.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RawCanisterHttpRequest {
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub subnet_id: Vec<u8>,
+    pub request_id: u64,
+    pub http_method: CanisterHttpMethod,
+    pub url: String,
+    pub headers: Vec<CanisterHttpHeader>,
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub body: Vec<u8>,
+    pub max_response_bytes: Option<u64>,
+}
+
+/// A pending canister HTTP outcall made by a canister on the given subnet.
+/// The pair (`subnet_id`, `request_id`) identifies the outcall when mocking its response.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct CanisterHttpRequest {
+    pub subnet_id: SubnetId,
+    pub request_id: u64,
+    pub http_method: CanisterHttpMethod,
+    pub url: String,
+    pub headers: Vec<CanisterHttpHeader>,
+    pub body: Vec<u8>,
+    pub max_response_bytes: Option<u64>,
+}
+
+impl From<RawCanisterHttpRequest> for CanisterHttpRequest {
+    fn from(raw: RawCanisterHttpRequest) -> Self {
+        Self {
+            subnet_id: Principal::from_slice(&raw.subnet_id),
+            request_id: raw.request_id,
+            http_method: raw.http_method,
+            url: raw.url,
+            headers: raw.headers,
+            body: raw.body,
+            max_response_bytes: raw.max_response_bytes,
+        }
+    }
+}
+
+impl From<CanisterHttpRequest> for RawCanisterHttpRequest {
+    fn from(request: CanisterHttpRequest) -> Self {
+        Self {
+            subnet_id: request.subnet_id.as_slice().to_vec(),
+            request_id: request.request_id,
+            http_method: request.http_method,
+            url: request.url,
+            headers: request.headers,
+            body: request.body,
+            max_response_bytes: request.max_response_bytes,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct CanisterHttpReply {
+    pub status: u16,
+    pub headers: Vec<CanisterHttpHeader>,
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub body: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
+pub struct CanisterHttpReject {
+    pub reject_code: u64,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum CanisterHttpResponse {
+    CanisterHttpReply(CanisterHttpReply),
+    CanisterHttpReject(CanisterHttpReject),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RawMockCanisterHttpResponse {
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub subnet_id: Vec<u8>,
+    pub request_id: u64,
+    pub response: CanisterHttpResponse,
+    pub additional_responses: Vec<CanisterHttpResponse>,
+}
+
+/// A mock response to a pending canister HTTP outcall.
+///
+/// If `additional_responses` is empty, `response` is delivered (after applying
+/// the transform function of the outcall, if any) as if all replicas of the
+/// subnet observed it. Otherwise, `response` and `additional_responses` are
+/// the responses observed by the individual replicas of the subnet and their
+/// total number must be equal to the subnet size. The outcall then only succeeds
+/// if enough replicas agree on the same transformed response.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MockCanisterHttpResponse {
+    pub subnet_id: SubnetId,
+    pub request_id: u64,
+    pub response: CanisterHttpResponse,
+    pub additional_responses: Vec<CanisterHttpResponse>,
+}
+
+impl From<MockCanisterHttpResponse> for RawMockCanisterHttpResponse {
+    fn from(mock: MockCanisterHttpResponse) -> Self {
+        Self {
+            subnet_id: mock.subnet_id.as_slice().to_vec(),
+            request_id: mock.request_id,
+            response: mock.response,
+            additional_responses: mock.additional_responses,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct BlobId(
     #[serde(deserialize_with = "base64::deserialize")]
@@ -418,6 +553,8 @@ pub struct SubnetSpec {
     state_config: SubnetStateConfig,
     instruction_config: SubnetInstructionConfig,
     dts_flag: DtsFlag,
+    #[serde(default)]
+    schnorr_keys: Vec<SchnorrKeyId>,
 }
 
 impl SubnetSpec {
@@ -441,6 +578,14 @@ impl SubnetSpec {
         self
     }
 
+    /// Makes the subnet hold the given threshold Schnorr key.
+    /// The subnet then serves `schnorr_public_key` and `sign_with_schnorr`
+    /// requests for this key from canisters on all subnets.
+    pub fn with_schnorr_key(mut self, key: SchnorrKeyId) -> SubnetSpec {
+        self.schnorr_keys.push(key);
+        self
+    }
+
     pub fn get_state_path(&self) -> Option<PathBuf> {
         self.state_config.get_path()
     }
@@ -449,6 +594,10 @@ impl SubnetSpec {
         self.dts_flag
     }
 
+    pub fn get_schnorr_keys(&self) -> Vec<SchnorrKeyId> {
+        self.schnorr_keys.clone()
+    }
+
     pub fn get_subnet_id(&self) -> Option<RawSubnetId> {
         match &self.state_config {
             SubnetStateConfig::New => None,
@@ -476,6 +625,7 @@ impl Default for SubnetSpec {
             state_config: SubnetStateConfig::New,
             instruction_config: SubnetInstructionConfig::Production,
             dts_flag: DtsFlag::Enabled,
+            schnorr_keys: vec![],
         }
     }
 }
@@ -496,6 +646,24 @@ pub enum DtsFlag {
     Disabled,
 }
 
+/// Signature scheme of a threshold Schnorr key.
+#[derive(
+    Debug, Clone, Copy, Eq, Hash, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum SchnorrAlgorithm {
+    Bip340Secp256k1,
+    Ed25519,
+}
+
+/// Identifies a threshold Schnorr key as in `sign_with_schnorr` requests.
+#[derive(
+    Debug, Clone, Eq, Hash, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub struct SchnorrKeyId {
+    pub algorithm: SchnorrAlgorithm,
+    pub name: String,
+}
+
 /// Specifies whether the subnet should be created from scratch or loaded
 /// from a path.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -536,6 +704,7 @@ impl ExtendedSubnetConfigSet {
         Option<PathBuf>,
         SubnetInstructionConfig,
         DtsFlag,
+        Vec<SchnorrKeyId>,
     )> {
         use SubnetKind::*;
         vec![
@@ -554,12 +723,35 @@ impl ExtendedSubnetConfigSet {
                 spec.get_state_path(),
                 spec.get_instruction_config(),
                 spec.get_dts_flag(),
+                spec.get_schnorr_keys(),
             )
         })
         .collect()
     }
 
     pub fn validate(&self) -> Result<(), String> {
+        let specs = [
+            &self.nns,
+            &self.sns,
+            &self.ii,
+            &self.fiduciary,
+            &self.bitcoin,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(self.system.iter())
+        .chain(self.application.iter());
+        let mut schnorr_keys = BTreeSet::new();
+        for spec in specs {
+            for key in &spec.schnorr_keys {
+                if !schnorr_keys.insert(key) {
+                    return Err(format!(
+                        "Schnorr key {:?} is configured on multiple subnets",
+                        key
+                    ));
+                }
+            }
+        }
         if !self.system.is_empty()
             || !self.application.is_empty()
             || self.nns.is_some()
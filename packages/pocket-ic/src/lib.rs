@@ -34,12 +34,13 @@
 //! For more information, see the [README](https://crates.io/crates/pocket-ic).
 //!
 use crate::common::rest::{
-    ApiResponse, BlobCompression, BlobId, CreateHttpGatewayResponse, CreateInstanceResponse,
-    DtsFlag, ExtendedSubnetConfigSet, HttpGatewayBackend, HttpGatewayConfig, HttpGatewayInfo,
-    InstanceId, RawAddCycles, RawCanisterCall, RawCanisterId, RawCanisterResult, RawCycles,
-    RawEffectivePrincipal, RawMessageId, RawSetStableMemory, RawStableMemory,
-    RawSubmitIngressResult, RawSubnetId, RawTime, RawVerifyCanisterSigArg, RawWasmResult, SubnetId,
-    SubnetSpec, Topology,
+    ApiResponse, BlobCompression, BlobId, CanisterHttpRequest, CreateHttpGatewayResponse,
+    CreateInstanceResponse, DtsFlag, ExtendedSubnetConfigSet, HttpGatewayBackend,
+    HttpGatewayConfig, HttpGatewayInfo, InstanceId, MockCanisterHttpResponse, RawAddCycles,
+    RawCanisterCall, RawCanisterHttpRequest, RawCanisterId, RawCanisterResult, RawCheckpoint,
+    RawCycles, RawEffectivePrincipal, RawMessageId, RawMockCanisterHttpResponse,
+    RawSetStableMemory, RawStableMemory, RawSubmitIngressResult, RawSubnetId, RawTime,
+    RawVerifyCanisterSigArg, RawWasmResult, SchnorrKeyId, SubnetId, SubnetSpec, Topology,
 };
 use candid::{
     decode_args, encode_args,
@@ -211,6 +212,14 @@ impl PocketIcBuilder {
         self.config = self.config.with_dts_flag(dts_flag);
         self
     }
+
+    /// Add a threshold Schnorr key held by the fiduciary subnet (as on mainnet).
+    /// An empty fiduciary subnet is added if none has been configured yet.
+    pub fn with_schnorr_key(mut self, key: SchnorrKeyId) -> Self {
+        let fiduciary = self.config.fiduciary.take().unwrap_or_default();
+        self.config.fiduciary = Some(fiduciary.with_schnorr_key(key));
+        self
+    }
 }
 /// Main entry point for interacting with PocketIC.
 pub struct PocketIc {
@@ -246,13 +255,37 @@ impl PocketIc {
         let config = config.into();
         config.validate().unwrap();
 
+        Self::create_instance(server_url, "instances", config)
+    }
+
+    /// Creates a new PocketIC instance from a checkpoint created by [PocketIc::checkpoint]
+    /// on an instance of the same PocketIC server.
+    pub fn from_checkpoint(label: &str) -> Self {
+        let server_url = crate::start_or_reuse_server();
+        Self::from_checkpoint_and_server_url(label, server_url)
+    }
+
+    /// Creates a new PocketIC instance from a checkpoint with the specified label on the
+    /// PocketIC server with the specified url.
+    /// This function is intended for advanced users who start the server manually.
+    pub fn from_checkpoint_and_server_url(label: &str, server_url: Url) -> Self {
+        Self::create_instance(
+            server_url,
+            "instances/restore",
+            RawCheckpoint {
+                label: label.to_string(),
+            },
+        )
+    }
+
+    fn create_instance(server_url: Url, endpoint: &str, body: impl Serialize) -> Self {
         let parent_pid = std::os::unix::process::parent_id();
         let log_guard = setup_tracing(parent_pid);
 
         let reqwest_client = reqwest::blocking::Client::new();
         let (instance_id, topology) = match reqwest_client
-            .post(server_url.join("instances").unwrap())
-            .json(&config)
+            .post(server_url.join(endpoint).unwrap())
+            .json(&body)
             .send()
             .expect("Failed to get result")
             .json::<CreateInstanceResponse>()
@@ -381,6 +414,36 @@ impl PocketIc {
         self.post::<(), _>(endpoint, "");
     }
 
+    /// Saves the full state of this PocketIC instance under the given label
+    /// (replacing any previous checkpoint with that label).
+    /// New instances starting from this state can then be created by [PocketIc::from_checkpoint]
+    /// and instances with the same topology can be rewound to this state by [PocketIc::restore].
+    /// Note that this executes a round on every subnet.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, label = %label))]
+    pub fn checkpoint(&self, label: &str) {
+        let endpoint = "update/checkpoint";
+        self.post::<(), _>(
+            endpoint,
+            RawCheckpoint {
+                label: label.to_string(),
+            },
+        );
+    }
+
+    /// Replaces the state of this PocketIC instance by the state saved under the given label
+    /// by [PocketIc::checkpoint]. The checkpoint must have been created on an instance
+    /// with the same topology as this instance.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, label = %label))]
+    pub fn restore(&self, label: &str) {
+        let endpoint = "update/restore";
+        self.post::<(), _>(
+            endpoint,
+            RawCheckpoint {
+                label: label.to_string(),
+            },
+        );
+    }
+
     /// Configures the IC to make progress automatically,
     /// i.e., periodically update the time of the IC
     /// to the real time and execute rounds on the subnets.
@@ -925,6 +988,28 @@ impl PocketIc {
         result.map(|RawSubnetId { subnet_id }| SubnetId::from_slice(&subnet_id))
     }
 
+    /// Get the pending canister HTTP outcalls made by canisters on all subnets.
+    /// Use `mock_canister_http_response` to respond to them.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub fn get_canister_http(&self) -> Vec<CanisterHttpRequest> {
+        let endpoint = "read/get_canister_http";
+        let res: Vec<RawCanisterHttpRequest> = self.get(endpoint);
+        res.into_iter().map(|r| r.into()).collect()
+    }
+
+    /// Mock a response to a pending canister HTTP outcall
+    /// and deliver it to the canister in a new round on the subnet of the outcall.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub fn mock_canister_http_response(
+        &self,
+        mock_canister_http_response: MockCanisterHttpResponse,
+    ) {
+        let endpoint = "update/mock_canister_http";
+        let raw_mock_canister_http_response: RawMockCanisterHttpResponse =
+            mock_canister_http_response.into();
+        self.post::<(), _>(endpoint, raw_mock_canister_http_response);
+    }
+
     fn instance_url(&self) -> Url {
         self.server_url
             .join("/instances/")
@@ -1,5 +1,8 @@
-use candid::{decode_one, encode_one, Principal};
+use candid::{decode_one, encode_one, CandidType, Principal};
 use ic_base_types::PrincipalId;
+use ic_cdk::api::management_canister::http_request::{
+    CanisterHttpRequestArgument, HttpMethod, HttpResponse,
+};
 use ic_cdk::api::management_canister::provisional::CanisterId;
 use ic_universal_canister::{wasm, CallArgs, UNIVERSAL_CANISTER_WASM};
 use icp_ledger::{
@@ -7,9 +10,14 @@ use icp_ledger::{
     Symbol, Tokens, TransferArgs, TransferError,
 };
 use pocket_ic::{
-    common::rest::{BlobCompression, DtsFlag, SubnetConfigSet, SubnetKind},
+    common::rest::{
+        BlobCompression, CanisterHttpMethod, CanisterHttpReply, CanisterHttpResponse, DtsFlag,
+        MockCanisterHttpResponse, RawMessageId, SchnorrAlgorithm, SchnorrKeyId, SubnetConfigSet,
+        SubnetKind,
+    },
     PocketIc, PocketIcBuilder, UserError, WasmResult,
 };
+use serde::Deserialize;
 use std::{
     collections::HashMap,
     io::Read,
@@ -43,6 +51,116 @@ fn test_counter_canister() {
     assert_eq!(reply, WasmResult::Reply(vec![2, 0, 0, 0]));
 }
 
+#[test]
+fn test_checkpoint_and_restore() {
+    let pic = PocketIcBuilder::new()
+        .with_nns_subnet()
+        .with_application_subnet()
+        .build();
+
+    let can_id = pic.create_canister();
+    pic.add_cycles(can_id, INIT_CYCLES);
+    pic.install_canister(can_id, counter_wasm(), vec![], None);
+    let reply = call_counter_can(&pic, can_id, "write");
+    assert_eq!(reply, WasmResult::Reply(vec![1, 0, 0, 0]));
+
+    pic.checkpoint("counter_1");
+    let time = pic.get_time();
+
+    // Changes after the checkpoint do not affect the checkpoint.
+    let reply = call_counter_can(&pic, can_id, "write");
+    assert_eq!(reply, WasmResult::Reply(vec![2, 0, 0, 0]));
+
+    let restored_pic = PocketIc::from_checkpoint("counter_1");
+    assert_eq!(restored_pic.topology(), pic.topology());
+    assert!(restored_pic.get_time() >= time);
+    let reply = call_counter_can(&restored_pic, can_id, "read");
+    assert_eq!(reply, WasmResult::Reply(vec![1, 0, 0, 0]));
+
+    // Instances restored from the same checkpoint are independent of each other.
+    let reply = call_counter_can(&restored_pic, can_id, "write");
+    assert_eq!(reply, WasmResult::Reply(vec![2, 0, 0, 0]));
+    let another_restored_pic = PocketIc::from_checkpoint("counter_1");
+    let reply = call_counter_can(&another_restored_pic, can_id, "write");
+    assert_eq!(reply, WasmResult::Reply(vec![2, 0, 0, 0]));
+
+    // New canisters can be created on restored instances.
+    let new_can_id = restored_pic.create_canister();
+    assert_ne!(new_can_id, can_id);
+}
+
+#[test]
+fn test_checkpoint_preserves_subnet_selection() {
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_application_subnet()
+        .with_application_subnet()
+        .build();
+    for _ in 0..5 {
+        pic.create_canister();
+    }
+
+    pic.checkpoint("subnet_selection");
+    let restored_pic = PocketIc::from_checkpoint("subnet_selection");
+
+    // Canisters created without a specified subnet are placed on the same
+    // subnets as on the original instance after the checkpoint.
+    for _ in 0..10 {
+        let can_id = pic.create_canister();
+        let restored_can_id = restored_pic.create_canister();
+        assert_eq!(can_id, restored_can_id);
+        assert_eq!(
+            pic.get_subnet(can_id).unwrap(),
+            restored_pic.get_subnet(restored_can_id).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_restore_into_existing_instance() {
+    let pic = PocketIcBuilder::new().with_application_subnet().build();
+
+    let can_id = pic.create_canister();
+    pic.add_cycles(can_id, INIT_CYCLES);
+    pic.install_canister(can_id, counter_wasm(), vec![], None);
+    let reply = call_counter_can(&pic, can_id, "write");
+    assert_eq!(reply, WasmResult::Reply(vec![1, 0, 0, 0]));
+
+    pic.checkpoint("rewind_1");
+    let reply = call_counter_can(&pic, can_id, "write");
+    assert_eq!(reply, WasmResult::Reply(vec![2, 0, 0, 0]));
+
+    // Rewind the instance to the checkpoint.
+    pic.restore("rewind_1");
+    let reply = call_counter_can(&pic, can_id, "read");
+    assert_eq!(reply, WasmResult::Reply(vec![1, 0, 0, 0]));
+
+    // Another instance with the same topology can be restored from the checkpoint, too.
+    let other_pic = PocketIcBuilder::new().with_application_subnet().build();
+    other_pic.restore("rewind_1");
+    let reply = call_counter_can(&other_pic, can_id, "read");
+    assert_eq!(reply, WasmResult::Reply(vec![1, 0, 0, 0]));
+}
+
+#[test]
+#[should_panic(expected = "CheckpointTopologyMismatch")]
+fn test_restore_checkpoint_with_different_topology() {
+    let pic = PocketIcBuilder::new().with_application_subnet().build();
+    pic.checkpoint("single_subnet");
+
+    let other_pic = PocketIcBuilder::new()
+        .with_nns_subnet()
+        .with_application_subnet()
+        .build();
+    other_pic.restore("single_subnet");
+}
+
+#[test]
+#[should_panic(expected = "Checkpoint unknown_label not found")]
+fn test_restore_unknown_checkpoint() {
+    let _pic = PocketIc::from_checkpoint("unknown_label");
+}
+
 fn counter_wasm() -> Vec<u8> {
     let wasm_path = std::env::var_os("COUNTER_WASM").expect("Missing counter wasm file");
     std::fs::read(wasm_path).unwrap()
@@ -1014,3 +1132,276 @@ fn test_too_large_call() {
     )
     .unwrap_err();
 }
+
+fn submit_canister_http_request(pic: &PocketIc, canister_id: CanisterId) -> RawMessageId {
+    let arg = CanisterHttpRequestArgument {
+        url: "https://example.com".to_string(),
+        max_response_bytes: None,
+        method: HttpMethod::GET,
+        headers: vec![],
+        body: None,
+        transform: None,
+    };
+    pic.submit_call(
+        canister_id,
+        Principal::anonymous(),
+        "update",
+        wasm()
+            .call_with_cycles(
+                Principal::management_canister(),
+                "http_request",
+                CallArgs::default().other_side(encode_one(arg).unwrap()),
+                100_000_000_000_u128.into(),
+            )
+            .build(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_canister_http() {
+    let pic = PocketIc::new();
+    let canister_id = pic.create_canister();
+    pic.add_cycles(canister_id, INIT_CYCLES);
+    pic.install_canister(canister_id, UNIVERSAL_CANISTER_WASM.to_vec(), vec![], None);
+
+    let msg_id = submit_canister_http_request(&pic, canister_id);
+    // Execute a round so that the canister makes the HTTP outcall.
+    pic.tick();
+
+    let canister_http_requests = pic.get_canister_http();
+    assert_eq!(canister_http_requests.len(), 1);
+    let canister_http_request = &canister_http_requests[0];
+    assert_eq!(canister_http_request.url, "https://example.com");
+    assert_eq!(canister_http_request.http_method, CanisterHttpMethod::GET);
+
+    let mock_canister_http_response = MockCanisterHttpResponse {
+        subnet_id: canister_http_request.subnet_id,
+        request_id: canister_http_request.request_id,
+        response: CanisterHttpResponse::CanisterHttpReply(CanisterHttpReply {
+            status: 200,
+            headers: vec![],
+            body: b"hello".to_vec(),
+        }),
+        additional_responses: vec![],
+    };
+    pic.mock_canister_http_response(mock_canister_http_response);
+    assert!(pic.get_canister_http().is_empty());
+
+    let WasmResult::Reply(reply) = pic.await_call(msg_id).unwrap() else {
+        panic!("Expected the canister HTTP outcall to succeed");
+    };
+    let http_response: HttpResponse = decode_one(&reply).unwrap();
+    assert_eq!(http_response.status, candid::Nat::from(200_u64));
+    assert_eq!(http_response.body, b"hello".to_vec());
+}
+
+#[test]
+fn test_canister_http_with_diverging_responses() {
+    let pic = PocketIc::new();
+    let canister_id = pic.create_canister();
+    pic.add_cycles(canister_id, INIT_CYCLES);
+    pic.install_canister(canister_id, UNIVERSAL_CANISTER_WASM.to_vec(), vec![], None);
+    let subnet_id = pic.get_subnet(canister_id).unwrap();
+    let subnet_size = pic.topology().0.get(&subnet_id).unwrap().size as usize;
+
+    let reply = |body: &[u8]| {
+        CanisterHttpResponse::CanisterHttpReply(CanisterHttpReply {
+            status: 200,
+            headers: vec![],
+            body: body.to_vec(),
+        })
+    };
+
+    // Every replica observes a different response: no consensus can be reached.
+    let msg_id = submit_canister_http_request(&pic, canister_id);
+    pic.tick();
+    let canister_http_request = pic.get_canister_http().pop().unwrap();
+    let responses: Vec<_> = (0..subnet_size)
+        .map(|i| reply(format!("hello {}", i).as_bytes()))
+        .collect();
+    pic.mock_canister_http_response(MockCanisterHttpResponse {
+        subnet_id,
+        request_id: canister_http_request.request_id,
+        response: responses[0].clone(),
+        additional_responses: responses[1..].to_vec(),
+    });
+    assert!(matches!(
+        pic.await_call(msg_id).unwrap(),
+        WasmResult::Reject(_)
+    ));
+
+    // Only a minority of the replicas observes a different response.
+    let msg_id = submit_canister_http_request(&pic, canister_id);
+    pic.tick();
+    let canister_http_request = pic.get_canister_http().pop().unwrap();
+    let faulty = (subnet_size - 1) / 3;
+    let responses: Vec<_> = (0..subnet_size)
+        .map(|i| {
+            if i < faulty {
+                reply(b"bye")
+            } else {
+                reply(b"hello")
+            }
+        })
+        .collect();
+    pic.mock_canister_http_response(MockCanisterHttpResponse {
+        subnet_id,
+        request_id: canister_http_request.request_id,
+        response: responses[0].clone(),
+        additional_responses: responses[1..].to_vec(),
+    });
+    let WasmResult::Reply(reply) = pic.await_call(msg_id).unwrap() else {
+        panic!("Expected the canister HTTP outcall to succeed");
+    };
+    let http_response: HttpResponse = decode_one(&reply).unwrap();
+    assert_eq!(http_response.body, b"hello".to_vec());
+}
+
+#[derive(CandidType)]
+enum CandidSchnorrAlgorithm {
+    #[serde(rename = "bip340secp256k1")]
+    Bip340Secp256k1,
+    #[serde(rename = "ed25519")]
+    Ed25519,
+}
+
+#[derive(CandidType)]
+struct CandidSchnorrKeyId {
+    algorithm: CandidSchnorrAlgorithm,
+    name: String,
+}
+
+impl From<&SchnorrKeyId> for CandidSchnorrKeyId {
+    fn from(key_id: &SchnorrKeyId) -> Self {
+        let algorithm = match key_id.algorithm {
+            SchnorrAlgorithm::Bip340Secp256k1 => CandidSchnorrAlgorithm::Bip340Secp256k1,
+            SchnorrAlgorithm::Ed25519 => CandidSchnorrAlgorithm::Ed25519,
+        };
+        Self {
+            algorithm,
+            name: key_id.name.clone(),
+        }
+    }
+}
+
+#[derive(CandidType)]
+struct SchnorrPublicKeyArgs {
+    canister_id: Option<Principal>,
+    derivation_path: Vec<Vec<u8>>,
+    key_id: CandidSchnorrKeyId,
+}
+
+#[derive(CandidType, Deserialize)]
+struct SchnorrPublicKeyResponse {
+    #[serde(with = "serde_bytes")]
+    public_key: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    chain_code: Vec<u8>,
+}
+
+#[derive(CandidType)]
+struct SignWithSchnorrArgs {
+    #[serde(with = "serde_bytes")]
+    message: Vec<u8>,
+    derivation_path: Vec<Vec<u8>>,
+    key_id: CandidSchnorrKeyId,
+}
+
+#[derive(CandidType, Deserialize)]
+struct SignWithSchnorrResponse {
+    #[serde(with = "serde_bytes")]
+    signature: Vec<u8>,
+}
+
+fn call_management_canister(
+    pic: &PocketIc,
+    canister_id: CanisterId,
+    method: &str,
+    arg: Vec<u8>,
+) -> Vec<u8> {
+    let res = pic
+        .update_call(
+            canister_id,
+            Principal::anonymous(),
+            "update",
+            wasm()
+                .call_with_cycles(
+                    Principal::management_canister(),
+                    method,
+                    CallArgs::default().other_side(arg),
+                    100_000_000_000_u128.into(),
+                )
+                .build(),
+        )
+        .unwrap();
+    match res {
+        WasmResult::Reply(reply) => reply,
+        WasmResult::Reject(msg) => panic!("Unexpected reject: {}", msg),
+    }
+}
+
+#[test]
+fn test_schnorr() {
+    let bip340_key_id = SchnorrKeyId {
+        algorithm: SchnorrAlgorithm::Bip340Secp256k1,
+        name: "test_key_bip340".to_string(),
+    };
+    let ed25519_key_id = SchnorrKeyId {
+        algorithm: SchnorrAlgorithm::Ed25519,
+        name: "test_key_ed25519".to_string(),
+    };
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_schnorr_key(bip340_key_id.clone())
+        .with_schnorr_key(ed25519_key_id.clone())
+        .build();
+
+    // The canister lives on the application subnet while the keys are held by the fiduciary subnet.
+    let canister_id = pic.create_canister();
+    pic.add_cycles(canister_id, INIT_CYCLES);
+    pic.install_canister(canister_id, UNIVERSAL_CANISTER_WASM.to_vec(), vec![], None);
+
+    let derivation_path = vec![b"derivation".to_vec(), vec![42]];
+    let message = b"Hello, Schnorr!".to_vec();
+
+    for (key_id, public_key_len) in [(bip340_key_id, 33), (ed25519_key_id, 32)] {
+        let public_key_args = SchnorrPublicKeyArgs {
+            canister_id: None,
+            derivation_path: derivation_path.clone(),
+            key_id: (&key_id).into(),
+        };
+        let reply = call_management_canister(
+            &pic,
+            canister_id,
+            "schnorr_public_key",
+            encode_one(public_key_args).unwrap(),
+        );
+        let public_key_response: SchnorrPublicKeyResponse = decode_one(&reply).unwrap();
+        assert_eq!(public_key_response.public_key.len(), public_key_len);
+        assert_eq!(public_key_response.chain_code.len(), 32);
+
+        let sign_args = SignWithSchnorrArgs {
+            message: message.clone(),
+            derivation_path: derivation_path.clone(),
+            key_id: (&key_id).into(),
+        };
+        let reply = call_management_canister(
+            &pic,
+            canister_id,
+            "sign_with_schnorr",
+            encode_one(sign_args).unwrap(),
+        );
+        let sign_response: SignWithSchnorrResponse = decode_one(&reply).unwrap();
+        assert_eq!(sign_response.signature.len(), 64);
+
+        if key_id.algorithm == SchnorrAlgorithm::Ed25519 {
+            let public_key =
+                ic_crypto_ed25519::PublicKey::deserialize_raw(&public_key_response.public_key)
+                    .unwrap();
+            public_key
+                .verify_signature(&message, &sign_response.signature)
+                .unwrap();
+        }
+    }
+}
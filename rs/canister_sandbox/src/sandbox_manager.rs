@@ -144,6 +144,7 @@ impl Execution {
                 instance_stats,
                 system_api_call_counters,
                 canister_log,
+                execution_profile,
             },
             deltas,
             instance_or_system_api,
@@ -211,6 +212,7 @@ impl Execution {
                     instance_stats,
                     system_api_call_counters,
                     canister_log,
+                    execution_profile,
                 };
                 self.sandbox_manager.controller.execution_finished(
                     protocol::ctlsvc::ExecutionFinishedRequest {
@@ -238,6 +240,7 @@ impl Execution {
                     instance_stats,
                     system_api_call_counters,
                     canister_log,
+                    execution_profile,
                 };
 
                 self.sandbox_manager.controller.execution_finished(
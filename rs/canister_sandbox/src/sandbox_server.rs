@@ -220,10 +220,13 @@ mod tests {
             0,
             ic00_aliases,
             SMALL_APP_SUBNET_MAX_SIZE,
+            BTreeMap::new(),
             SchedulerConfig::application_subnet().dirty_page_overhead,
             CanisterTimer::Inactive,
             0,
             BTreeSet::from([controller]),
+            BTreeMap::new(),
+            false,
             RequestMetadata::new(0, Time::from_nanos_since_unix_epoch(0)),
             caller,
             0,
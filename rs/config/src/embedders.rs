@@ -93,6 +93,9 @@ pub struct FeatureFlags {
     // TODO(IC-1674): remove this flag once the feature is enabled by default.
     /// Indicates whether the best-effort responses feature is enabled.
     pub best_effort_responses: FlagStatus,
+    /// Indicates whether Wasm backtraces are captured on traps, so that they
+    /// can be reported for canisters that opt into them via their settings.
+    pub canister_backtrace: FlagStatus,
 }
 
 impl FeatureFlags {
@@ -104,6 +107,7 @@ impl FeatureFlags {
             canister_logging: FlagStatus::Enabled,
             wasm64: FlagStatus::Disabled,
             best_effort_responses: FlagStatus::Disabled,
+            canister_backtrace: FlagStatus::Disabled,
         }
     }
 }
@@ -202,6 +206,12 @@ pub struct Config {
     /// entry with the number of executed instructions and the duration.
     pub trace_execution: FlagStatus,
 
+    /// If this flag is enabled, then the embedder records a profile of every
+    /// execution with the system API calls, the instructions per Wasm call
+    /// stack, and the number of dirty pages. Only meant for local tools such
+    /// as `drun` that run canisters without sandboxing.
+    pub execution_profiling: FlagStatus,
+
     /// The maximum number of pages that a message dirties without optimizing dirty
     /// page copying by triggering a new execution slice for copying and using prefaulting.
     pub max_dirty_pages_without_optimization: usize,
@@ -235,6 +245,7 @@ impl Config {
             subnet_type: SubnetType::Application,
             dirty_page_overhead: NumInstructions::new(0),
             trace_execution: FlagStatus::Disabled,
+            execution_profiling: FlagStatus::Disabled,
             max_dirty_pages_without_optimization: DEFAULT_MAX_DIRTY_PAGES_WITHOUT_OPTIMIZATION,
             dirty_page_copy_overhead: DIRTY_PAGE_COPY_OVERHEAD,
         }
@@ -4,13 +4,18 @@
 #![forbid(missing_docs)]
 
 //! A crate with handling of ECDSA keys over the secp256k1 curve
+//!
+//! BIP340 Schnorr signatures and the key derivation used by the Internet
+//! Computer for threshold ECDSA and threshold Schnorr are also supported.
 
 use k256::{
     elliptic_curve::{
         generic_array::{typenum::Unsigned, GenericArray},
-        Curve,
+        ops::Reduce,
+        sec1::ToEncodedPoint,
+        Curve, PrimeField,
     },
-    Secp256k1,
+    AffinePoint, FieldBytes, ProjectivePoint, Scalar, Secp256k1, U256,
 };
 use rand::{CryptoRng, RngCore};
 use zeroize::ZeroizeOnDrop;
@@ -34,6 +39,21 @@ impl std::fmt::Display for KeyDecodingError {
 
 impl std::error::Error for KeyDecodingError {}
 
+/// An error indicating that a BIP341 Taproot tree hash was invalid
+///
+/// The tree hash must be either empty (for a key with no script paths) or
+/// exactly 32 bytes long.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidTaprootHash;
+
+impl std::fmt::Display for InvalidTaprootHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for InvalidTaprootHash {}
+
 lazy_static::lazy_static! {
 
     /// See RFC 3279 section 2.3.5
@@ -200,6 +220,121 @@ fn pem_encode(raw: &[u8], label: &'static str) -> String {
     })
 }
 
+/// BIP340 tagged hash: SHA-256(SHA-256(tag) || SHA-256(tag) || inputs)
+fn bip340_tagged_hash(tag: &str, inputs: &[&[u8]]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    let tag_hash = Sha256::digest(tag.as_bytes());
+    let mut sha256 = Sha256::new();
+    sha256.update(tag_hash);
+    sha256.update(tag_hash);
+    for input in inputs {
+        sha256.update(input);
+    }
+    sha256.finalize().into()
+}
+
+/// Interpret a 32-byte big-endian integer as a scalar, reducing it modulo the
+/// group order
+fn reduce_to_scalar(bytes: &[u8; 32]) -> Scalar {
+    <Scalar as Reduce<U256>>::reduce_bytes(&FieldBytes::from(*bytes))
+}
+
+/// Interpret a 32-byte big-endian integer as a scalar, returning None if it
+/// is not less than the group order
+fn scalar_from_bytes(bytes: &[u8]) -> Option<Scalar> {
+    Scalar::from_repr(FieldBytes::clone_from_slice(bytes)).into()
+}
+
+/// Return the x-coordinate of a point, and whether its y-coordinate is odd
+///
+/// The point must not be the identity
+fn x_and_y_is_odd(pt: &AffinePoint) -> ([u8; 32], bool) {
+    let encoded = pt.to_encoded_point(true);
+    let bytes = encoded.as_bytes();
+    let mut x = [0u8; 32];
+    x.copy_from_slice(&bytes[1..]);
+    (x, bytes[0] == 0x03)
+}
+
+/// Compute the BIP341 Taproot tweak of the x-only public key `x`
+fn bip341_tweak(x: &[u8; 32], taproot_tree_hash: &[u8]) -> Result<Scalar, InvalidTaprootHash> {
+    if !taproot_tree_hash.is_empty() && taproot_tree_hash.len() != 32 {
+        return Err(InvalidTaprootHash);
+    }
+
+    let tweak = bip340_tagged_hash("TapTweak", &[x, taproot_tree_hash]);
+    // Fails only if the tweak is not less than the group order, which occurs
+    // with negligible probability
+    scalar_from_bytes(&tweak).ok_or(InvalidTaprootHash)
+}
+
+/// Create a BIP340 signature of `message` with the secret scalar `sk`
+///
+/// See <https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki#default-signing>
+fn bip340_sign(sk: Scalar, message: &[u8], aux_rand: &[u8; 32]) -> [u8; 64] {
+    let pk = (ProjectivePoint::GENERATOR * sk).to_affine();
+    let (pk_x, pk_y_is_odd) = x_and_y_is_odd(&pk);
+    let sk = if pk_y_is_odd { -sk } else { sk };
+
+    let mut t = [0u8; 32];
+    let aux_hash = bip340_tagged_hash("BIP0340/aux", &[aux_rand]);
+    for (t, (sk, aux)) in t.iter_mut().zip(sk.to_bytes().iter().zip(aux_hash.iter())) {
+        *t = sk ^ aux;
+    }
+
+    let nonce = reduce_to_scalar(&bip340_tagged_hash("BIP0340/nonce", &[&t, &pk_x, message]));
+    // A zero nonce occurs with negligible probability
+    assert!(!bool::from(nonce.is_zero()), "BIP340 nonce is zero");
+
+    let r = (ProjectivePoint::GENERATOR * nonce).to_affine();
+    let (r_x, r_y_is_odd) = x_and_y_is_odd(&r);
+    let nonce = if r_y_is_odd { -nonce } else { nonce };
+
+    let e = reduce_to_scalar(&bip340_tagged_hash(
+        "BIP0340/challenge",
+        &[&r_x, &pk_x, message],
+    ));
+    let s = nonce + e * sk;
+
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(&r_x);
+    signature[32..].copy_from_slice(&s.to_bytes());
+    signature
+}
+
+/// Verify a BIP340 signature of `message` against the x-coordinate of `pk`
+///
+/// See <https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki#verification>
+fn bip340_verify(pk: &ProjectivePoint, message: &[u8], signature: &[u8]) -> bool {
+    if signature.len() != 64 {
+        return false;
+    }
+
+    // BIP340 public keys are x-only, and implicitly have an even y-coordinate
+    let (pk_x, pk_y_is_odd) = x_and_y_is_odd(&pk.to_affine());
+    let pk = if pk_y_is_odd { -*pk } else { *pk };
+
+    let s = match scalar_from_bytes(&signature[32..]) {
+        Some(s) => s,
+        None => return false,
+    };
+    let e = reduce_to_scalar(&bip340_tagged_hash(
+        "BIP0340/challenge",
+        &[&signature[..32], &pk_x, message],
+    ));
+
+    let r = ProjectivePoint::GENERATOR * s - pk * e;
+    if r == ProjectivePoint::IDENTITY {
+        return false;
+    }
+
+    // No point has an x-coordinate encoding that is not less than the field
+    // prime, so this comparison also rejects out of range r values
+    let (r_x, r_y_is_odd) = x_and_y_is_odd(&r.to_affine());
+    !r_y_is_odd && r_x == signature[..32]
+}
+
 /// An ECDSA private key
 #[derive(Clone, ZeroizeOnDrop)]
 pub struct PrivateKey {
@@ -324,11 +459,92 @@ impl PrivateKey {
         Some(sig.to_bytes().into())
     }
 
+    /// Sign a message with BIP340 Schnorr
+    ///
+    /// The message is signed as is, without hashing it first; BIP340 allows
+    /// messages of any length. This matches the threshold BIP340 signatures
+    /// produced by the Internet Computer.
+    pub fn sign_message_with_bip340<R: RngCore + CryptoRng>(
+        &self,
+        message: &[u8],
+        rng: &mut R,
+    ) -> [u8; 64] {
+        let mut aux_rand = [0u8; 32];
+        rng.fill_bytes(&mut aux_rand);
+        bip340_sign(self.scalar(), message, &aux_rand)
+    }
+
+    /// Sign a message with BIP340 Schnorr, without using an RNG
+    ///
+    /// The auxiliary randomness is set to all zeros. BIP340 allows this, but
+    /// recommends using fresh randomness as a countermeasure against side
+    /// channel attacks, so prefer [`Self::sign_message_with_bip340`].
+    pub fn sign_message_with_bip340_no_rng(&self, message: &[u8]) -> [u8; 64] {
+        bip340_sign(self.scalar(), message, &[0u8; 32])
+    }
+
+    /// Sign a message with BIP340 Schnorr using the BIP341 Taproot tweak
+    ///
+    /// The `taproot_tree_hash` is the Merkle root of the script tree, or empty
+    /// if the key has no script paths. The signature verifies against the
+    /// Taproot output key, see [`PublicKey::verify_bip341_signature`].
+    pub fn sign_message_with_bip341<R: RngCore + CryptoRng>(
+        &self,
+        message: &[u8],
+        rng: &mut R,
+        taproot_tree_hash: &[u8],
+    ) -> Result<[u8; 64], InvalidTaprootHash> {
+        let sk = self.scalar();
+        let (pk_x, pk_y_is_odd) = x_and_y_is_odd(&(ProjectivePoint::GENERATOR * sk).to_affine());
+        let sk = if pk_y_is_odd { -sk } else { sk };
+        let tweak = bip341_tweak(&pk_x, taproot_tree_hash)?;
+
+        let mut aux_rand = [0u8; 32];
+        rng.fill_bytes(&mut aux_rand);
+        Ok(bip340_sign(sk + tweak, message, &aux_rand))
+    }
+
+    /// Derive a private key from this private key using a derivation path
+    ///
+    /// This is the same derivation system used by the Internet Computer when
+    /// deriving subkeys for threshold ECDSA and threshold BIP340 Schnorr over
+    /// secp256k1
+    pub fn derive_subkey(&self, derivation_path: &DerivationPath) -> (Self, [u8; 32]) {
+        let chain_code = [0u8; 32];
+        self.derive_subkey_with_chain_code(derivation_path, &chain_code)
+    }
+
+    /// Derive a private key from this private key using a derivation path
+    /// and chain code
+    ///
+    /// This is the same derivation system used by the Internet Computer when
+    /// deriving subkeys for threshold ECDSA and threshold BIP340 Schnorr over
+    /// secp256k1
+    pub fn derive_subkey_with_chain_code(
+        &self,
+        derivation_path: &DerivationPath,
+        chain_code: &[u8; 32],
+    ) -> (Self, [u8; 32]) {
+        let sk = self.scalar();
+        let pt = ProjectivePoint::GENERATOR * sk;
+
+        let (_pt, offset, chain_code) = derivation_path.derive_offset(pt, chain_code);
+
+        let derived_scalar = sk + offset;
+        let key = k256::ecdsa::SigningKey::from_bytes(&derived_scalar.to_bytes())
+            .expect("Derived private key is never zero");
+        (Self { key }, chain_code)
+    }
+
     /// Return the public key corresponding to this private key
     pub fn public_key(&self) -> PublicKey {
         let key = self.key.verifying_key();
         PublicKey { key: *key }
     }
+
+    fn scalar(&self) -> Scalar {
+        *self.key.as_nonzero_scalar().as_ref()
+    }
 }
 
 /// An ECDSA public key
@@ -494,6 +710,73 @@ impl PublicKey {
         }
     }
 
+    /// Verify a BIP340 Schnorr signature
+    ///
+    /// BIP340 public keys are x-only; the y-coordinate of this key is ignored.
+    pub fn verify_bip340_signature(&self, message: &[u8], signature: &[u8]) -> bool {
+        bip340_verify(&self.point(), message, signature)
+    }
+
+    /// Verify a BIP340 Schnorr signature against the BIP341 Taproot output key
+    ///
+    /// The output key is this key tweaked with `taproot_tree_hash`, which is
+    /// the Merkle root of the script tree, or empty if there are no script
+    /// paths. Returns false if `taproot_tree_hash` is invalid.
+    pub fn verify_bip341_signature(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        taproot_tree_hash: &[u8],
+    ) -> bool {
+        let pt = self.point();
+        let (pk_x, pk_y_is_odd) = x_and_y_is_odd(&pt.to_affine());
+        let pt = if pk_y_is_odd { -pt } else { pt };
+        let tweak = match bip341_tweak(&pk_x, taproot_tree_hash) {
+            Ok(tweak) => tweak,
+            Err(_) => return false,
+        };
+
+        let output_key = pt + ProjectivePoint::GENERATOR * tweak;
+        if output_key == ProjectivePoint::IDENTITY {
+            return false;
+        }
+        bip340_verify(&output_key, message, signature)
+    }
+
+    /// Derive a public key from this public key using a derivation path
+    ///
+    /// This is the same derivation system used by the Internet Computer when
+    /// deriving subkeys for threshold ECDSA and threshold BIP340 Schnorr over
+    /// secp256k1. To reproduce the key returned by `ecdsa_public_key` or
+    /// `schnorr_public_key`, the first element of the path is the canister
+    /// ID, followed by the caller-provided derivation path.
+    pub fn derive_subkey(&self, derivation_path: &DerivationPath) -> (Self, [u8; 32]) {
+        let chain_code = [0u8; 32];
+        self.derive_subkey_with_chain_code(derivation_path, &chain_code)
+    }
+
+    /// Derive a public key from this public key using a derivation path
+    /// and chain code
+    ///
+    /// This is the same derivation system used by the Internet Computer when
+    /// deriving subkeys for threshold ECDSA and threshold BIP340 Schnorr over
+    /// secp256k1
+    pub fn derive_subkey_with_chain_code(
+        &self,
+        derivation_path: &DerivationPath,
+        chain_code: &[u8; 32],
+    ) -> (Self, [u8; 32]) {
+        let (pt, _offset, chain_code) = derivation_path.derive_offset(self.point(), chain_code);
+
+        let key = k256::ecdsa::VerifyingKey::from_affine(pt.to_affine())
+            .expect("Derived public key is never the identity");
+        (Self { key }, chain_code)
+    }
+
+    fn point(&self) -> ProjectivePoint {
+        ProjectivePoint::from(*self.key.as_affine())
+    }
+
     /// Determines the [`RecoveryId`] for a given public key, digest and signature.
     ///
     /// The recovery cannot fail if the parameters are correct, meaning that
@@ -579,3 +862,112 @@ impl RecoveryId {
         self.recid.to_byte()
     }
 }
+
+/// A component of a derivation path
+#[derive(Debug, Clone)]
+pub struct DerivationIndex(pub Vec<u8>);
+
+/// Derivation Path
+///
+/// A derivation path is simply a sequence of DerivationIndex
+#[derive(Debug, Clone)]
+pub struct DerivationPath {
+    path: Vec<DerivationIndex>,
+}
+
+impl DerivationPath {
+    /// Create a BIP32-style derivation path
+    pub fn new_bip32(bip32: &[u32]) -> Self {
+        let mut path = Vec::with_capacity(bip32.len());
+        for n in bip32 {
+            path.push(DerivationIndex(n.to_be_bytes().to_vec()));
+        }
+        Self::new(path)
+    }
+
+    /// Create a free-form derivation path
+    pub fn new(path: Vec<DerivationIndex>) -> Self {
+        Self { path }
+    }
+
+    /// Return the length of this path
+    pub fn len(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Return if this path is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the components of the derivation path
+    pub fn path(&self) -> &[DerivationIndex] {
+        &self.path
+    }
+
+    /// BIP32 CKDpub, extended to support indices of arbitrary length
+    ///
+    /// The exceptional cases (an HMAC output that is not less than the group
+    /// order, or a derived key that is the identity) are handled following
+    /// SLIP-0010, as the Internet Computer does.
+    fn ckd(
+        idx: &[u8],
+        pt: ProjectivePoint,
+        chain_code: &[u8; 32],
+    ) -> (ProjectivePoint, Scalar, [u8; 32]) {
+        let mut ckd_input = pt.to_affine().to_encoded_point(true).as_bytes().to_vec();
+
+        loop {
+            let (offset, next_chain_code) = Self::ckd_offset(&ckd_input, idx, chain_code);
+
+            let next_pt = pt + ProjectivePoint::GENERATOR * offset;
+            if next_pt != ProjectivePoint::IDENTITY {
+                return (next_pt, offset, next_chain_code);
+            }
+
+            ckd_input[0] = 0x01;
+            ckd_input[1..].copy_from_slice(&next_chain_code);
+        }
+    }
+
+    fn ckd_offset(key_input: &[u8], idx: &[u8], chain_code: &[u8; 32]) -> (Scalar, [u8; 32]) {
+        let mut ikm = key_input.to_vec();
+
+        loop {
+            ikm.extend_from_slice(idx);
+
+            // HKDF-Extract is HMAC keyed with the salt, so this is
+            // HMAC-SHA512(chain_code, key_input || idx)
+            let (hmac_output, _) = hkdf::Hkdf::<sha2::Sha512>::extract(Some(chain_code), &ikm);
+
+            let mut next_chain_code = [0u8; 32];
+            next_chain_code.copy_from_slice(&hmac_output[32..]);
+
+            if let Some(offset) = scalar_from_bytes(&hmac_output[..32]) {
+                return (offset, next_chain_code);
+            }
+
+            ikm.clear();
+            ikm.push(0x01);
+            ikm.extend_from_slice(&next_chain_code);
+        }
+    }
+
+    fn derive_offset(
+        &self,
+        mut pt: ProjectivePoint,
+        chain_code: &[u8; 32],
+    ) -> (ProjectivePoint, Scalar, [u8; 32]) {
+        let mut chain_code = *chain_code;
+        let mut sum = Scalar::ZERO;
+
+        for idx in self.path() {
+            let (next_pt, offset, next_chain_code) = Self::ckd(&idx.0, pt, &chain_code);
+            pt = next_pt;
+            sum += offset;
+            chain_code = next_chain_code;
+        }
+
+        (pt, sum, chain_code)
+    }
+}
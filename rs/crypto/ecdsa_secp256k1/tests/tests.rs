@@ -1,4 +1,7 @@
-use ic_crypto_ecdsa_secp256k1::{KeyDecodingError, PrivateKey, PublicKey};
+use hex_literal::hex;
+use ic_crypto_ecdsa_secp256k1::{
+    DerivationIndex, DerivationPath, InvalidTaprootHash, KeyDecodingError, PrivateKey, PublicKey,
+};
 use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
 
 #[test]
@@ -259,6 +262,177 @@ i389XZmdlKFbsLkUI9dDQgMP98YnUA==
     );
 }
 
+#[test]
+fn should_produce_expected_derived_public_keys() {
+    // Test vectors generated using the IC's threshold ECDSA key derivation
+    let master_key = PublicKey::deserialize_sec1(&hex!(
+        "034313d67f176fcb486756322e2cca9cd0aa4504e7c8128222a7b54bf02f1742c1"
+    ))
+    .expect("Invalid key");
+
+    fn check_derivation(
+        master_key: &PublicKey,
+        path: &DerivationPath,
+        expected_key: [u8; 33],
+        expected_chain_code: [u8; 32],
+    ) {
+        let (dk, chain_code) = master_key.derive_subkey(path);
+
+        assert_eq!(
+            hex::encode(dk.serialize_sec1(true)),
+            hex::encode(expected_key)
+        );
+        assert_eq!(hex::encode(chain_code), hex::encode(expected_chain_code));
+    }
+
+    let index1 = DerivationIndex(vec![1, 2, 3, 4, 5]);
+    let index2 = DerivationIndex(vec![8, 0, 2, 8, 0, 2]);
+
+    check_derivation(
+        &master_key,
+        &DerivationPath::new(vec![index1.clone()]),
+        hex!("02cf5b8b78e5663d2de5f34b15fae61096335674bae520a0989a1cb19f47a0b1c7"),
+        hex!("701de4dca971413c3388039fe838ae9ec000cedcebda8c0b31388d10d959d31a"),
+    );
+
+    check_derivation(
+        &master_key,
+        &DerivationPath::new(vec![index2.clone()]),
+        hex!("03a8299cdbc7fc2e61e56d5dd11c5c933fb32ffbefbf4d451f601f60d0a9f6a1d4"),
+        hex!("96420bd99dea768cc5d7bb85ec85753ff1c39e47e24ffb43cfab236832e017e3"),
+    );
+
+    check_derivation(
+        &master_key,
+        &DerivationPath::new(vec![index1, index2]),
+        hex!("0292879f7c52e7389600602b32f47aa9a694c31370b8d8131f91342952bf6667e9"),
+        hex!("545054338b6149de7b098112c41277b7c8db56802c27a6cef51bc0fe0ac589df"),
+    );
+}
+
+#[test]
+fn should_derive_matching_private_and_public_keys() {
+    use rand::Rng;
+
+    let rng = &mut reproducible_rng();
+
+    let master_sk = PrivateKey::generate_using_rng(rng);
+    let master_pk = master_sk.public_key();
+
+    for path_len in 0..5 {
+        let path = DerivationPath::new(
+            (0..path_len)
+                .map(|_| DerivationIndex(rng.gen::<[u8; 8]>().to_vec()))
+                .collect(),
+        );
+        let chain_code = rng.gen::<[u8; 32]>();
+
+        let (derived_pk, cc_pk) = master_pk.derive_subkey_with_chain_code(&path, &chain_code);
+        let (derived_sk, cc_sk) = master_sk.derive_subkey_with_chain_code(&path, &chain_code);
+
+        assert_eq!(derived_pk, derived_sk.public_key());
+        assert_eq!(cc_pk, cc_sk);
+
+        let msg = rng.gen::<[u8; 32]>();
+        assert!(derived_pk.verify_signature(&msg, &derived_sk.sign_message(&msg)));
+        assert!(derived_pk
+            .verify_bip340_signature(&msg, &derived_sk.sign_message_with_bip340(&msg, rng)));
+    }
+}
+
+#[test]
+fn should_match_bip340_test_vector() {
+    // Test vector 0 from
+    // https://github.com/bitcoin/bips/blob/master/bip-0340/test-vectors.csv
+    let sk = PrivateKey::deserialize_sec1(&hex!(
+        "0000000000000000000000000000000000000000000000000000000000000003"
+    ))
+    .expect("Valid key");
+    let msg = [0u8; 32];
+    let expected_sig = hex!("e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca821525f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0");
+
+    let sig = sk.sign_message_with_bip340_no_rng(&msg);
+    assert_eq!(hex::encode(sig), hex::encode(expected_sig));
+    assert!(sk.public_key().verify_bip340_signature(&msg, &sig));
+}
+
+#[test]
+fn should_accept_bip340_signatures_that_we_generate() {
+    use rand::{Rng, RngCore};
+
+    let rng = &mut reproducible_rng();
+
+    let sk = PrivateKey::generate_using_rng(rng);
+    let pk = sk.public_key();
+
+    for m in 0..100 {
+        let mut msg = vec![0u8; m];
+        rng.fill_bytes(&mut msg);
+        let sig = sk.sign_message_with_bip340(&msg, rng);
+
+        assert!(pk.verify_bip340_signature(&msg, &sig));
+        assert!(pk.verify_bip340_signature(&msg, &sk.sign_message_with_bip340_no_rng(&msg)));
+
+        let mut bad_sig = sig;
+        bad_sig[rng.gen_range(0..64)] ^= 1;
+        assert!(!pk.verify_bip340_signature(&msg, &bad_sig));
+        assert!(!pk.verify_bip340_signature(&msg, &sig[..63]));
+
+        let mut bad_msg = msg.clone();
+        bad_msg.push(0);
+        assert!(!pk.verify_bip340_signature(&bad_msg, &sig));
+    }
+}
+
+#[test]
+fn should_accept_bip341_signatures_that_we_generate() {
+    use rand::Rng;
+
+    let rng = &mut reproducible_rng();
+
+    let sk = PrivateKey::generate_using_rng(rng);
+    let pk = sk.public_key();
+
+    let msg = rng.gen::<[u8; 32]>();
+
+    for ttr in [vec![], rng.gen::<[u8; 32]>().to_vec()] {
+        let sig = sk
+            .sign_message_with_bip341(&msg, rng, &ttr)
+            .expect("Valid taproot tree hash");
+
+        assert!(pk.verify_bip341_signature(&msg, &sig, &ttr));
+        // The signature is for the tweaked key, not the internal key
+        assert!(!pk.verify_bip340_signature(&msg, &sig));
+
+        let mut other_ttr = ttr.clone();
+        other_ttr.resize(32, 0);
+        other_ttr[0] ^= 1;
+        assert!(!pk.verify_bip341_signature(&msg, &sig, &other_ttr));
+    }
+}
+
+#[test]
+fn should_reject_invalid_taproot_tree_hash() {
+    let rng = &mut reproducible_rng();
+
+    let sk = PrivateKey::generate_using_rng(rng);
+    let pk = sk.public_key();
+    let msg = b"message";
+
+    let valid_sig = sk
+        .sign_message_with_bip341(msg, rng, &[])
+        .expect("Valid taproot tree hash");
+
+    for len in [1, 31, 33, 64] {
+        let ttr = vec![0x42; len];
+        assert_eq!(
+            sk.sign_message_with_bip341(msg, rng, &ttr),
+            Err(InvalidTaprootHash)
+        );
+        assert!(!pk.verify_bip341_signature(msg, &valid_sig, &ttr));
+    }
+}
+
 mod try_recovery_from_digest {
     use ic_crypto_ecdsa_secp256k1::{PrivateKey, PublicKey, RecoveryError};
     use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
@@ -634,6 +634,12 @@ impl CyclesAccountManager {
         self.scale_cost(self.config.ecdsa_signature_fee, subnet_size)
     }
 
+    /// Amount to charge for a Schnorr signature.
+    pub fn schnorr_signature_fee(&self, subnet_size: usize) -> Cycles {
+        // TODO(EXC-1629): introduce a dedicated Schnorr fee.
+        self.ecdsa_signature_fee(subnet_size)
+    }
+
     ////////////////////////////////////////////////////////////////////////////
     //
     // Storage
@@ -775,6 +781,18 @@ impl CyclesAccountManager {
         ]))
     }
 
+    /// Returns the amount of cycles withdrawn when sending a request with a
+    /// payload (method name and argument) of the given size: the transmission
+    /// fees for the request and the prepayments for the largest possible
+    /// response.
+    pub fn xnet_call_total_fee(&self, payload_size: NumBytes, subnet_size: usize) -> Cycles {
+        self.scale_cost(
+            self.config.xnet_call_fee + self.config.xnet_byte_transmission_fee * payload_size.get(),
+            subnet_size,
+        ) + self.prepayment_for_response_transmission(subnet_size)
+            + self.prepayment_for_response_execution(subnet_size)
+    }
+
     /// Returns the amount of cycles required for executing the longest-running
     /// response callback.
     pub fn prepayment_for_response_execution(&self, subnet_size: usize) -> Cycles {
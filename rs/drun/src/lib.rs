@@ -1,9 +1,10 @@
 //! Standalone interface for testing application canisters.
 
 use crate::message::{msg_stream_from_file, Message};
+use crate::trace::Tracer;
 use futures::future::join_all;
 use hex::encode;
-use ic_config::{subnet_config::SubnetConfig, Config};
+use ic_config::{flag_status::FlagStatus, subnet_config::SubnetConfig, Config};
 use ic_crypto_test_utils_ni_dkg::dummy_initial_dkg_transcript_with_master_key;
 use ic_cycles_account_manager::CyclesAccountManager;
 use ic_error_types::{ErrorCode, UserError};
@@ -56,6 +57,9 @@ use std::{thread::sleep, time::Duration};
 use tower::util::ServiceExt;
 
 mod message;
+mod trace;
+
+pub use trace::TraceFormat;
 
 // drun will panic if it takes more than this many batches
 // until a response for a message is received
@@ -70,6 +74,8 @@ pub struct DrunOptions {
     pub log_file: Option<PathBuf>,
     pub instruction_limit: Option<u64>,
     pub subnet_type: SubnetType,
+    pub trace_file: Option<PathBuf>,
+    pub trace_format: TraceFormat,
 }
 
 /// Deliver a single message to the Message Routing layer
@@ -169,6 +175,8 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
         log_file,
         instruction_limit,
         subnet_type,
+        trace_file,
+        trace_format,
     } = uo;
     // Hardcoded magic values to create a ReplicaConfig that parses.
     let mut subnet_config = SubnetConfig::new(subnet_type);
@@ -186,6 +194,16 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
         cfg.hypervisor.max_query_call_graph_instructions = instruction_limit;
     }
 
+    // Without time slicing, each profile covers a whole message.
+    let tracer = match trace_file {
+        Some(trace_file) => {
+            cfg.hypervisor.deterministic_time_slicing = FlagStatus::Disabled;
+            cfg.hypervisor.embedders_config.execution_profiling = FlagStatus::Enabled;
+            Some(Mutex::new(Tracer::new(&trace_file, trace_format)?))
+        }
+        None => None,
+    };
+
     let subnet_id = SubnetId::from(PrincipalId::new_subnet_test_id(0));
     let root_subnet_id = SubnetId::from(PrincipalId::new_subnet_test_id(1));
     let replica_config = ReplicaConfig {
@@ -225,19 +243,20 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
         None,
         ic_types::malicious_flags::MaliciousFlags::default(),
     ));
+    let execution_services = ExecutionServices::setup_execution(
+        log.clone().into(),
+        &metrics_registry,
+        replica_config.subnet_id,
+        subnet_type,
+        subnet_config.scheduler_config,
+        cfg.hypervisor.clone(),
+        Arc::clone(&cycles_account_manager),
+        Arc::clone(&state_manager) as Arc<_>,
+        state_manager.get_fd_factory(),
+    );
+    let execution_profiles = execution_services.execution_profiles.clone();
     let (_, ingress_history_writer, ingress_hist_reader, query_handler, scheduler) =
-        ExecutionServices::setup_execution(
-            log.clone().into(),
-            &metrics_registry,
-            replica_config.subnet_id,
-            subnet_type,
-            subnet_config.scheduler_config,
-            cfg.hypervisor.clone(),
-            Arc::clone(&cycles_account_manager),
-            Arc::clone(&state_manager) as Arc<_>,
-            state_manager.get_fd_factory(),
-        )
-        .into_parts();
+        execution_services.into_parts();
 
     let runtime = tokio::runtime::Handle::current();
     let _metrics_endpoint =
@@ -257,16 +276,27 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
         MaliciousFlags::default(),
     );
 
+    let write_trace = || match &tracer {
+        Some(tracer) => tracer
+            .lock()
+            .unwrap()
+            .write_profiles(execution_profiles.take()),
+        None => Ok(()),
+    };
+
     join_all(msg_stream.map(|parse_result| async {
         match parse_result {
             Ok(Message::Install(msg)) => {
+                if let Some(tracer) = &tracer {
+                    tracer.lock().unwrap().register_install(&msg);
+                }
                 deliver_message(
                     msg,
                     &message_routing,
                     ingress_hist_reader.as_ref(),
                     extra_batches,
                 );
-                Ok(())
+                write_trace()
             }
 
             Ok(Message::Query(q)) => {
@@ -284,7 +314,7 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
                     }
                 };
                 print_query_result(query_result);
-                Ok(())
+                write_trace()
             }
 
             Ok(Message::Ingress(msg)) => {
@@ -294,7 +324,7 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
                     ingress_hist_reader.as_ref(),
                     extra_batches,
                 );
-                Ok(())
+                write_trace()
             }
 
             Ok(Message::Create(msg)) => {
@@ -304,7 +334,7 @@ pub async fn run_drun(uo: DrunOptions) -> Result<(), String> {
                     ingress_hist_reader.as_ref(),
                     extra_batches,
                 );
-                Ok(())
+                write_trace()
             }
 
             Err(e) => Err(e),
@@ -5,7 +5,7 @@ use ic_canister_sandbox_backend_lib::{
     RUN_AS_SANDBOX_LAUNCHER_FLAG,
 };
 use ic_config::{flag_status::FlagStatus, Config, ConfigSource};
-use ic_drun::{run_drun, DrunOptions};
+use ic_drun::{run_drun, DrunOptions, TraceFormat};
 use ic_registry_subnet_type::SubnetType;
 use std::path::PathBuf;
 
@@ -17,6 +17,8 @@ const ARG_MESSAGES: &str = "messages";
 const ARG_EXTRA_BATCHES: &str = "extra-batches";
 const ARG_INSTRUCTION_LIMIT: &str = "instruction-limit";
 const ARG_SUBNET_TYPE: &str = "subnet-type";
+const ARG_TRACE: &str = "trace";
+const ARG_TRACE_FORMAT: &str = "trace-format";
 
 fn main() -> Result<(), String> {
     // Check if `drun` is running in the canister sandbox mode where it waits
@@ -89,6 +91,18 @@ async fn drun_main() -> Result<(), String> {
             })
             .unwrap_or(SubnetType::System);
 
+        let trace_file = matches.value_of(ARG_TRACE).map(PathBuf::from);
+
+        let trace_format = matches
+            .value_of(ARG_TRACE_FORMAT)
+            .map(|arg| {
+                arg.parse().unwrap_or_else(|err| {
+                    eprintln!("Failed to parse {}\n  {}", ARG_TRACE_FORMAT, err);
+                    std::process::exit(1);
+                })
+            })
+            .unwrap_or(TraceFormat::Json);
+
         let uo = DrunOptions {
             msg_filename: matches.value_of(ARG_MESSAGES).unwrap().to_string(),
             cfg,
@@ -96,6 +110,8 @@ async fn drun_main() -> Result<(), String> {
             log_file,
             instruction_limit,
             subnet_type,
+            trace_file,
+            trace_format,
         };
         run_drun(uo).await
     })
@@ -159,5 +175,27 @@ fn get_arg_matches() -> ArgMatches {
                 .value_name("Subnet Type")
                 .takes_value(true),
         )
+        .arg(
+            Arg::new(ARG_TRACE)
+                .long(ARG_TRACE)
+                .value_name("trace_file")
+                .help(
+                    "Profile every execution and write the system API calls, the \
+                     instructions per function, and the dirty pages to the given file. \
+                     Disables deterministic time slicing.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new(ARG_TRACE_FORMAT)
+                .long(ARG_TRACE_FORMAT)
+                .value_name("json|folded")
+                .help(
+                    "Format of the trace file: JSON lines or folded stacks for \
+                     flamegraph tools (default: json).",
+                )
+                .requires(ARG_TRACE)
+                .takes_value(true),
+        )
         .get_matches()
 }
@@ -0,0 +1,173 @@
+//! Writes the execution profiles recorded by the embedder in `--trace` mode.
+
+use ic_interfaces::execution_environment::{ExecutionProfile, SystemApiCall};
+use ic_management_canister_types::{InstallCodeArgsV2, Payload};
+use ic_types::{messages::SignedIngress, CanisterId};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// One JSON object per execution.
+    Json,
+    /// Folded stacks as consumed by `flamegraph.pl` and `inferno`.
+    Folded,
+}
+
+impl FromStr for TraceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(TraceFormat::Json),
+            "folded" => Ok(TraceFormat::Folded),
+            _ => Err(format!("Unknown trace format: {}", s)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StackRecord {
+    frames: Vec<String>,
+    instructions: u64,
+}
+
+#[derive(Serialize)]
+struct ProfileRecord<'a> {
+    canister_id: String,
+    method: &'a str,
+    instructions_executed: u64,
+    wasm_dirty_pages: usize,
+    stable_dirty_pages: usize,
+    system_api_calls: &'a [SystemApiCall],
+    /// Instructions executed while the function was on top of the stack.
+    functions: BTreeMap<String, u64>,
+    stacks: Vec<StackRecord>,
+}
+
+pub(crate) struct Tracer {
+    format: TraceFormat,
+    out: BufWriter<File>,
+    function_names: BTreeMap<CanisterId, BTreeMap<u32, String>>,
+}
+
+impl Tracer {
+    pub(crate) fn new(trace_file: &Path, format: TraceFormat) -> Result<Self, String> {
+        let file = File::create(trace_file).map_err(|e| {
+            format!(
+                "Could not create trace file: {} - Error: {}",
+                trace_file.display(),
+                e
+            )
+        })?;
+        Ok(Self {
+            format,
+            out: BufWriter::new(file),
+            function_names: BTreeMap::new(),
+        })
+    }
+
+    /// Remembers the function names of the module installed by `msg` so that
+    /// stack frames can be symbolized.
+    pub(crate) fn register_install(&mut self, msg: &SignedIngress) {
+        if let Ok(args) = InstallCodeArgsV2::decode(msg.content().arg()) {
+            if let Ok(canister_id) = CanisterId::try_from(args.canister_id) {
+                self.function_names
+                    .insert(canister_id, function_names(&args.wasm_module));
+            }
+        }
+    }
+
+    /// Writes the given execution profiles.
+    pub(crate) fn write_profiles(&mut self, profiles: Vec<ExecutionProfile>) -> Result<(), String> {
+        for profile in profiles {
+            match self.format {
+                TraceFormat::Json => self.write_json(&profile),
+                TraceFormat::Folded => self.write_folded(&profile),
+            }
+            .map_err(|e| format!("Failed to write trace: {}", e))?;
+        }
+        self.out
+            .flush()
+            .map_err(|e| format!("Failed to write trace: {}", e))
+    }
+
+    fn frame_names(&self, profile: &ExecutionProfile, frames: &[u32]) -> Vec<String> {
+        let names = self.function_names.get(&profile.canister_id);
+        frames
+            .iter()
+            .map(|index| match names.and_then(|names| names.get(index)) {
+                Some(name) => name.clone(),
+                None => format!("func[{}]", index),
+            })
+            .collect()
+    }
+
+    fn write_json(&mut self, profile: &ExecutionProfile) -> std::io::Result<()> {
+        let mut functions = BTreeMap::new();
+        let mut stacks = vec![];
+        for sample in &profile.stacks {
+            let frames = self.frame_names(profile, &sample.frames);
+            let function = frames
+                .last()
+                .cloned()
+                .unwrap_or_else(|| profile.method.clone());
+            *functions.entry(function).or_default() += sample.instructions;
+            stacks.push(StackRecord {
+                frames,
+                instructions: sample.instructions,
+            });
+        }
+        let record = ProfileRecord {
+            canister_id: profile.canister_id.to_string(),
+            method: &profile.method,
+            instructions_executed: profile.instructions_executed,
+            wasm_dirty_pages: profile.wasm_dirty_pages,
+            stable_dirty_pages: profile.stable_dirty_pages,
+            system_api_calls: &profile.system_api_calls,
+            functions,
+            stacks,
+        };
+        serde_json::to_writer(&mut self.out, &record)?;
+        writeln!(self.out)
+    }
+
+    fn write_folded(&mut self, profile: &ExecutionProfile) -> std::io::Result<()> {
+        for sample in &profile.stacks {
+            let mut stack = vec![profile.canister_id.to_string(), profile.method.clone()];
+            stack.extend(self.frame_names(profile, &sample.frames));
+            writeln!(self.out, "{} {}", stack.join(";"), sample.instructions)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the function names from the `name` custom section of `wasm`, if
+/// there is one. Compressed modules are not supported.
+fn function_names(wasm: &[u8]) -> BTreeMap<u32, String> {
+    use wasmparser::{Name, NameSectionReader, Parser, Payload::CustomSection};
+
+    let mut names = BTreeMap::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload {
+            Ok(CustomSection(reader)) if reader.name() == "name" => {
+                for subsection in NameSectionReader::new(reader.data(), reader.data_offset()) {
+                    if let Ok(Name::Function(map)) = subsection {
+                        for naming in map.into_iter().flatten() {
+                            names.insert(naming.index, naming.name.to_string());
+                        }
+                    }
+                }
+            }
+            Ok(_) => (),
+            Err(_) => break,
+        }
+    }
+    names
+}
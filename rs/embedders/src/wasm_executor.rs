@@ -456,6 +456,7 @@ pub fn wasm_execution_error(
             instance_stats: InstanceStats::default(),
             system_api_call_counters: SystemApiCallCounters::default(),
             canister_log: Default::default(),
+            execution_profile: None,
         },
         None,
     )
@@ -622,6 +623,7 @@ pub fn process(
                     instance_stats: InstanceStats::default(),
                     system_api_call_counters: SystemApiCallCounters::default(),
                     canister_log: Default::default(),
+                    execution_profile: None,
                 },
                 None,
                 Err(system_api.unwrap()), // should be safe because we've passed Some(api) to new_instance
@@ -632,6 +634,13 @@ pub fn process(
     // Set the instruction limit for the first slice.
     instance.set_instruction_counter(first_slice_instruction_limit.get() as i64);
 
+    // The execution profile needs to know which function was executed.
+    let profiled_func_ref = instance
+        .store_data()
+        .execution_profiler
+        .is_some()
+        .then(|| func_ref.clone());
+
     // Execute Wasm code until it finishes or exceeds the message instruction
     // limit. With deterministic time slicing, this call may execute multiple
     // slices before it returns.
@@ -640,6 +649,7 @@ pub fn process(
     // Get the executed/remaining instructions for the message and the slice.
     let instruction_counter = instance.instruction_counter();
     let instance_stats = instance.get_stats();
+    let execution_profiler = instance.store_data_mut().execution_profiler.take();
     //unwrap should not fail, because we have passed Some(system_api) to the instance above
     let system_api = instance.store_data_mut().system_api_mut().unwrap();
     let system_api_call_counters = system_api.call_counters();
@@ -658,6 +668,16 @@ pub fn process(
         .min(message_instruction_limit);
     let message_instructions_left = message_instruction_limit - message_instructions_executed;
 
+    let execution_profile = match (execution_profiler, profiled_func_ref) {
+        (Some(profiler), Some(func_ref)) => Some(profiler.finish(
+            canister_id,
+            &func_ref,
+            message_instructions_executed,
+            &instance_stats,
+        )),
+        _ => None,
+    };
+
     // In case the message dirtied too many pages, as a performance optimization we will
     // yield the control to the replica and then resume copying dirty pages in a new execution slice.
     let num_dirty_pages = if let Ok(ref res) = run_result {
@@ -681,6 +701,7 @@ pub fn process(
                         instance_stats,
                         system_api_call_counters,
                         canister_log,
+                        execution_profile,
                     },
                     None,
                     Ok(instance),
@@ -762,14 +783,21 @@ pub fn process(
             }
         }
         Err(err) => {
-            if let Some(log_message) = match err {
-                HypervisorError::Trapped(trap_code) => Some(format!("[TRAP]: {}", trap_code)),
-                HypervisorError::CalledTrap(text) if text.is_empty() => {
-                    Some("[TRAP]: (no message)".to_string())
+            let trap = match err {
+                HypervisorError::Trapped {
+                    trap_code,
+                    backtrace,
+                } => Some((trap_code.to_string(), backtrace)),
+                HypervisorError::CalledTrap { message, backtrace } if message.is_empty() => {
+                    Some(("(no message)".to_string(), backtrace))
                 }
-                HypervisorError::CalledTrap(text) => Some(format!("[TRAP]: {}", text)),
+                HypervisorError::CalledTrap { message, backtrace } => Some((message, backtrace)),
                 _ => None,
-            } {
+            };
+            if let Some(log_message) = trap.map(|(message, backtrace)| match backtrace {
+                Some(backtrace) => format!("[TRAP]: {}\n{}", message, backtrace),
+                None => format!("[TRAP]: {}", message),
+            }) {
                 canister_log.add_record(
                     embedder.config().feature_flags.canister_logging == FlagStatus::Enabled,
                     timestamp_nanos,
@@ -802,6 +830,7 @@ pub fn process(
             instance_stats,
             system_api_call_counters,
             canister_log,
+            execution_profile,
         },
         wasm_state_changes,
         Ok(instance),
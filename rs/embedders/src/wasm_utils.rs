@@ -206,6 +206,7 @@ fn validate_and_instrument(
         config.cost_to_compile_wasm_instruction,
         config.feature_flags.write_barrier,
         config.feature_flags.wasm_native_stable_memory,
+        config.feature_flags.canister_backtrace,
         config.metering_type,
         config.subnet_type,
         config.dirty_page_overhead,
@@ -128,8 +128,9 @@ use crate::wasmtime_embedder::{
     WASM_HEAP_MEMORY_NAME,
 };
 use ic_wasm_transform::{self, Global, Module};
+use wasm_encoder::Encode;
 use wasmparser::{
-    BlockType, CompositeType, Export, ExternalKind, FuncType, GlobalType, Import, MemoryType,
+    BlockType, CompositeType, Export, ExternalKind, FuncType, GlobalType, Import, MemoryType, Name,
     Operator, SubType, TypeRef, ValType,
 };
 
@@ -162,7 +163,7 @@ pub(crate) enum InjectedImports {
 }
 
 impl InjectedImports {
-    fn count(wasm_native_stable_memory: FlagStatus) -> usize {
+    pub(crate) fn count(wasm_native_stable_memory: FlagStatus) -> usize {
         if wasm_native_stable_memory == FlagStatus::Enabled {
             5
         } else {
@@ -891,6 +892,54 @@ fn inject_helper_functions(
     module
 }
 
+/// Rewrites the `name` custom section so that the function names and the local
+/// and label names of functions refer to the function indices after
+/// `injected_imports` imports have been prepended. This keeps the names that
+/// wasmtime reports in backtraces aligned with the instrumented module.
+///
+/// Returns `None` if the section cannot be parsed.
+fn shift_name_section(data: &[u8], injected_imports: u32) -> Option<wasm_encoder::NameSection> {
+    fn name_map(map: wasmparser::NameMap, shift: u32) -> Option<wasm_encoder::NameMap> {
+        let mut result = wasm_encoder::NameMap::new();
+        for naming in map {
+            let naming = naming.ok()?;
+            result.append(naming.index + shift, naming.name);
+        }
+        Some(result)
+    }
+
+    fn indirect_name_map(
+        map: wasmparser::IndirectNameMap,
+        shift: u32,
+    ) -> Option<wasm_encoder::IndirectNameMap> {
+        let mut result = wasm_encoder::IndirectNameMap::new();
+        for naming in map {
+            let naming = naming.ok()?;
+            result.append(naming.index + shift, &name_map(naming.names, 0)?);
+        }
+        Some(result)
+    }
+
+    let mut section = wasm_encoder::NameSection::new();
+    for subsection in wasmparser::NameSectionReader::new(data, 0) {
+        match subsection.ok()? {
+            Name::Module { name, .. } => section.module(name),
+            Name::Function(map) => section.functions(&name_map(map, injected_imports)?),
+            Name::Local(map) => section.locals(&indirect_name_map(map, injected_imports)?),
+            Name::Label(map) => section.labels(&indirect_name_map(map, injected_imports)?),
+            Name::Type(map) => section.types(&name_map(map, 0)?),
+            Name::Table(map) => section.tables(&name_map(map, 0)?),
+            Name::Memory(map) => section.memories(&name_map(map, 0)?),
+            Name::Global(map) => section.globals(&name_map(map, 0)?),
+            Name::Element(map) => section.elements(&name_map(map, 0)?),
+            Name::Data(map) => section.data(&name_map(map, 0)?),
+            // Tag names and unknown subsections are dropped.
+            Name::Tag(_) | Name::Unknown { .. } => {}
+        }
+    }
+    Some(section)
+}
+
 /// Indices of functions, globals, etc that will be need in the later parts of
 /// instrumentation.
 #[derive(Default)]
@@ -914,6 +963,7 @@ pub(super) fn instrument(
     cost_to_compile_wasm_instruction: NumInstructions,
     write_barrier: FlagStatus,
     wasm_native_stable_memory: FlagStatus,
+    canister_backtrace: FlagStatus,
     metering_type: MeteringType,
     subnet_type: SubnetType,
     dirty_page_overhead: NumInstructions,
@@ -1071,9 +1121,30 @@ pub(super) fn instrument(
         wasm_instruction_count += 2;
     }
 
-    let result = module.encode().map_err(|err| {
+    // Backtraces are only captured with the `canister_backtrace` flag, so the
+    // name section is left untouched otherwise.
+    let name_section = module
+        .custom_sections
+        .iter()
+        .position(|(name, _)| *name == "name")
+        .filter(|_| canister_backtrace == FlagStatus::Enabled)
+        .and_then(|index| {
+            let (_, data) = module.custom_sections.remove(index);
+            shift_name_section(
+                data,
+                InjectedImports::count(wasm_native_stable_memory) as u32,
+            )
+        });
+
+    let mut result = module.encode().map_err(|err| {
         WasmInstrumentationError::WasmSerializeError(WasmError::new(err.to_string()))
     })?;
+    // Custom sections are encoded last, so appending the name section keeps
+    // the order of the remaining sections intact.
+    if let Some(name_section) = name_section {
+        result.push(wasm_encoder::SectionId::Custom as u8);
+        name_section.encode(&mut result);
+    }
 
     Ok(InstrumentationOutput {
         exported_functions,
@@ -1,3 +1,4 @@
+pub mod execution_profiler;
 pub mod host_memory;
 mod signal_stack;
 mod system_api;
@@ -20,7 +21,7 @@ use wasmtime::{
 pub use host_memory::WasmtimeMemoryCreator;
 use ic_config::{embedders::Config as EmbeddersConfig, flag_status::FlagStatus};
 use ic_interfaces::execution_environment::{
-    HypervisorError, HypervisorResult, InstanceStats, SystemApi, TrapCode,
+    CanisterBacktrace, HypervisorError, HypervisorResult, InstanceStats, SystemApi, TrapCode,
 };
 use ic_logger::{debug, error, fatal, ReplicaLogger};
 use ic_replicated_state::{
@@ -37,8 +38,8 @@ use memory_tracker::{DirtyPageTracking, PageBitmap, SigsegvMemoryTracker};
 use signal_stack::WasmtimeSignalStack;
 
 use crate::wasm_utils::instrumentation::{
-    WasmMemoryType, ACCESSED_PAGES_COUNTER_GLOBAL_NAME, DIRTY_PAGES_COUNTER_GLOBAL_NAME,
-    INSTRUCTIONS_COUNTER_GLOBAL_NAME,
+    InjectedImports, WasmMemoryType, ACCESSED_PAGES_COUNTER_GLOBAL_NAME,
+    DIRTY_PAGES_COUNTER_GLOBAL_NAME, INSTRUCTIONS_COUNTER_GLOBAL_NAME,
 };
 use crate::{
     serialized_module::SerializedModuleBytes, wasm_utils::validation::wasmtime_validation_config,
@@ -46,6 +47,7 @@ use crate::{
 
 use super::InstanceRunResult;
 
+use self::execution_profiler::ExecutionProfiler;
 use self::host_memory::{MemoryPageSize, MemoryStart};
 
 #[cfg(test)]
@@ -90,27 +92,67 @@ fn wasmtime_error_to_hypervisor_error(err: anyhow::Error) -> HypervisorError {
                     error: BAD_SIGNATURE_MESSAGE.to_string(),
                 };
             }
-            HypervisorError::Trapped(TrapCode::Other)
+            HypervisorError::Trapped {
+                trap_code: TrapCode::Other,
+                backtrace: None,
+            }
         }
     }
 }
 
+/// Converts the Wasm call stack that wasmtime attached to `err` into a
+/// `CanisterBacktrace`. The function indices are mapped back to the module
+/// installed by the user, i.e. before `injected_imports` were prepended during
+/// instrumentation. The names come from the `name` section, which the
+/// instrumentation keeps aligned with the instrumented module.
+fn canister_backtrace(err: &anyhow::Error, injected_imports: u32) -> Option<CanisterBacktrace> {
+    let backtrace = err.downcast_ref::<wasmtime::WasmBacktrace>()?;
+    Some(CanisterBacktrace(
+        backtrace
+            .frames()
+            .iter()
+            .map(|frame| {
+                (
+                    frame.func_index().saturating_sub(injected_imports),
+                    frame.func_name().map(str::to_string),
+                )
+            })
+            .collect(),
+    ))
+}
+
 fn trap_code_to_hypervisor_error(trap: wasmtime::Trap) -> HypervisorError {
     match trap {
-        wasmtime::Trap::StackOverflow => HypervisorError::Trapped(TrapCode::StackOverflow),
-        wasmtime::Trap::MemoryOutOfBounds => HypervisorError::Trapped(TrapCode::HeapOutOfBounds),
-        wasmtime::Trap::TableOutOfBounds => HypervisorError::Trapped(TrapCode::TableOutOfBounds),
+        wasmtime::Trap::StackOverflow => HypervisorError::Trapped {
+            trap_code: TrapCode::StackOverflow,
+            backtrace: None,
+        },
+        wasmtime::Trap::MemoryOutOfBounds => HypervisorError::Trapped {
+            trap_code: TrapCode::HeapOutOfBounds,
+            backtrace: None,
+        },
+        wasmtime::Trap::TableOutOfBounds => HypervisorError::Trapped {
+            trap_code: TrapCode::TableOutOfBounds,
+            backtrace: None,
+        },
         wasmtime::Trap::BadSignature => HypervisorError::ToolchainContractViolation {
             error: BAD_SIGNATURE_MESSAGE.to_string(),
         },
-        wasmtime::Trap::IntegerDivisionByZero => {
-            HypervisorError::Trapped(TrapCode::IntegerDivByZero)
-        }
-        wasmtime::Trap::UnreachableCodeReached => HypervisorError::Trapped(TrapCode::Unreachable),
+        wasmtime::Trap::IntegerDivisionByZero => HypervisorError::Trapped {
+            trap_code: TrapCode::IntegerDivByZero,
+            backtrace: None,
+        },
+        wasmtime::Trap::UnreachableCodeReached => HypervisorError::Trapped {
+            trap_code: TrapCode::Unreachable,
+            backtrace: None,
+        },
         _ => {
             // The `wasmtime::TrapCode` enum is marked as #[non_exhaustive]
             // so we have to use the wildcard matching here.
-            HypervisorError::Trapped(TrapCode::Other)
+            HypervisorError::Trapped {
+                trap_code: TrapCode::Other,
+                backtrace: None,
+            }
         }
     }
 }
@@ -204,6 +246,12 @@ impl WasmtimeEmbedder {
         if embedder_config.feature_flags.wasm_native_stable_memory == FlagStatus::Enabled {
             config.wasm_memory64(true);
         }
+        // Capturing a backtrace walks the Wasm stack whenever a trap or a host
+        // function error occurs, so it only adds cost to failing executions.
+        // Symbol names come from the `name` section, so no DWARF info is needed.
+        if embedder_config.feature_flags.canister_backtrace == FlagStatus::Enabled {
+            config.wasm_backtrace(true);
+        }
         config
     }
 
@@ -247,6 +295,7 @@ impl WasmtimeEmbedder {
             self.config.stable_memory_dirty_page_limit,
             self.config.stable_memory_accessed_page_limit,
             main_memory_type,
+            self.config.execution_profiling,
         );
 
         let instance_pre = linker.instantiate_pre(module).map_err(|e| {
@@ -365,6 +414,16 @@ impl WasmtimeEmbedder {
             None => self.config.stable_memory_dirty_page_limit.message,
         };
 
+        // There is nothing to profile if the canister is not running.
+        let execution_profiler = match (&system_api, self.config.execution_profiling) {
+            (Some(_), FlagStatus::Enabled) => {
+                let injected_imports =
+                    InjectedImports::count(self.config.feature_flags.wasm_native_stable_memory);
+                Some(ExecutionProfiler::new(injected_imports as u32))
+            }
+            _ => None,
+        };
+
         let mut store = Store::new(
             instance_pre.module().engine(),
             StoreData {
@@ -372,6 +431,7 @@ impl WasmtimeEmbedder {
                 num_instructions_global: None,
                 log: self.log.clone(),
                 num_stable_dirty_pages_from_non_native_writes: NumOsPages::from(0),
+                execution_profiler,
                 limits: StoreLimitsBuilder::new()
                     .memory_size(MAX_STABLE_MEMORY_IN_BYTES as usize)
                     .tables(MAX_STORE_TABLES)
@@ -677,6 +737,8 @@ pub struct StoreData {
     pub log: ReplicaLogger,
     /// Tracks the number of dirty pages in stable memory in non-native stable mode
     pub num_stable_dirty_pages_from_non_native_writes: NumOsPages,
+    /// Collects the execution profile if execution profiling is enabled.
+    pub execution_profiler: Option<ExecutionProfiler>,
     pub limits: StoreLimits,
 }
 
@@ -757,7 +819,7 @@ impl WasmtimeInstance {
         self.store.data()
     }
 
-    fn invoke_export(&mut self, export: &str, args: &[Val]) -> HypervisorResult<()> {
+    fn exported_func(&mut self, export: &str) -> HypervisorResult<wasmtime::Func> {
         self.instance
             .get_export(&mut self.store, export)
             .ok_or_else(|| {
@@ -766,9 +828,7 @@ impl WasmtimeInstance {
             .into_func()
             .ok_or_else(|| HypervisorError::ToolchainContractViolation {
                 error: "export is not a function".to_string(),
-            })?
-            .call(&mut self.store, args, &mut [])
-            .map_err(wasmtime_error_to_hypervisor_error)
+            })
     }
 
     fn page_accesses(&mut self) -> HypervisorResult<PageAccessResults> {
@@ -956,8 +1016,10 @@ impl WasmtimeInstance {
     pub fn run(&mut self, func_ref: FuncRef) -> HypervisorResult<InstanceRunResult> {
         let _alt_sig_stack = unsafe { self.signal_stack.register() };
 
-        let result = match &func_ref {
-            FuncRef::Method(wasm_method) => self.invoke_export(&wasm_method.to_string(), &[]),
+        let func_and_args = match &func_ref {
+            FuncRef::Method(wasm_method) => self
+                .exported_func(&wasm_method.to_string())
+                .map(|func| (func, vec![])),
             FuncRef::QueryClosure(closure) | FuncRef::UpdateClosure(closure) => {
                 // As the 64-bit `env` values will be supported in `wasm64`,
                 // which is disabled for now, the conversion should always succeed.
@@ -966,7 +1028,8 @@ impl WasmtimeInstance {
                         error: format!("error converting additional value {} to u32", closure.env),
                     });
                 };
-                self.instance
+                let func = self
+                    .instance
                     .get_export(&mut self.store, "table")
                     .ok_or_else(|| HypervisorError::ToolchainContractViolation {
                         error: "table not found".to_string(),
@@ -983,12 +1046,29 @@ impl WasmtimeInstance {
                     })?
                     .ok_or_else(|| HypervisorError::ToolchainContractViolation {
                         error: "unexpected null function reference".to_string(),
-                    })?
-                    .call(&mut self.store, &[Val::I32(env32 as i32)], &mut [])
-                    .map_err(wasmtime_error_to_hypervisor_error)
+                    })?;
+                Ok((func, vec![Val::I32(env32 as i32)]))
             }
-        }
-        .map_err(|e| {
+        };
+
+        let backtraces_enabled = self
+            .store
+            .data()
+            .system_api
+            .as_ref()
+            .map_or(false, |api| api.wasm_backtraces_enabled());
+        let injected_imports = InjectedImports::count(self.wasm_native_stable_memory) as u32;
+        let mut backtrace = None;
+        let result = func_and_args.and_then(|(func, args)| {
+            func.call(&mut self.store, &args, &mut []).map_err(|err| {
+                if backtraces_enabled {
+                    backtrace = canister_backtrace(&err, injected_imports);
+                }
+                wasmtime_error_to_hypervisor_error(err)
+            })
+        });
+
+        let result = result.map_err(|e| {
             let exec_err = self
                 .store
                 .data()
@@ -1006,6 +1086,12 @@ impl WasmtimeInstance {
                 Err(_) => e,
             }
         });
+        // The error set by the system API replaces the wasmtime error above,
+        // so the backtrace is attached only afterwards.
+        let result = result.map_err(|err| match backtrace {
+            Some(backtrace) => err.with_backtrace(backtrace),
+            None => err,
+        });
 
         if let Err(HypervisorError::Aborted) = result {
             // The replica process has aborted the execution and the memory may
@@ -0,0 +1,107 @@
+//! Per-message execution profiles for local canister development.
+//!
+//! When `execution_profiling` is enabled in the embedders config, each
+//! `ic0` system API call samples the Wasm call stack together with the number
+//! of instructions the message has executed so far. The instructions executed
+//! since the previous sample are attributed to the sampled stack.
+//!
+//! The profile therefore has system API call granularity: it is not a
+//! sampling profiler and does not observe Wasm code between two `ic0` calls.
+//! A function that runs a long computation without calling the system API is
+//! not visible on its own; its instructions are attributed to the stack of
+//! the next system API call, or to the empty stack if there is none.
+//!
+//! The profiler lives in the [`StoreData`](super::StoreData) of the execution
+//! and the finished profile is returned in the
+//! [`WasmExecutionOutput`](ic_interfaces::execution_environment::WasmExecutionOutput)
+//! of the execution.
+
+use std::collections::BTreeMap;
+
+use ic_interfaces::execution_environment::{
+    ExecutionProfile, InstanceStats, StackSample, SystemApiCall,
+};
+use ic_types::{methods::FuncRef, CanisterId, NumInstructions};
+use wasmtime::WasmBacktrace;
+
+/// Collects the samples of a single execution.
+pub struct ExecutionProfiler {
+    injected_imports: u32,
+    last_instructions: u64,
+    system_api_calls: Vec<SystemApiCall>,
+    stacks: BTreeMap<Vec<u32>, u64>,
+}
+
+impl ExecutionProfiler {
+    /// Creates a profiler for a module that got `injected_imports` additional
+    /// function imports during instrumentation.
+    pub(crate) fn new(injected_imports: u32) -> Self {
+        Self {
+            injected_imports,
+            last_instructions: 0,
+            system_api_calls: vec![],
+            stacks: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn record_system_api_call(
+        &mut self,
+        name: &str,
+        instructions: NumInstructions,
+        backtrace: &WasmBacktrace,
+    ) {
+        // Wasmtime reports the innermost frame first.
+        let frames = backtrace
+            .frames()
+            .iter()
+            .rev()
+            .map(|frame| frame.func_index().saturating_sub(self.injected_imports))
+            .collect();
+        self.attribute(frames, instructions);
+        self.system_api_calls.push(SystemApiCall {
+            name: name.to_string(),
+            instructions: instructions.get(),
+        });
+    }
+
+    fn attribute(&mut self, frames: Vec<u32>, instructions: NumInstructions) {
+        let delta = instructions.get().saturating_sub(self.last_instructions);
+        self.last_instructions = self.last_instructions.max(instructions.get());
+        if delta > 0 {
+            *self.stacks.entry(frames).or_default() += delta;
+        }
+    }
+
+    /// Completes the profile once the execution has finished.
+    pub fn finish(
+        mut self,
+        canister_id: CanisterId,
+        func_ref: &FuncRef,
+        instructions_executed: NumInstructions,
+        instance_stats: &InstanceStats,
+    ) -> ExecutionProfile {
+        self.attribute(vec![], instructions_executed);
+        let method = match func_ref {
+            FuncRef::Method(method) => method.to_string(),
+            FuncRef::UpdateClosure(closure) | FuncRef::QueryClosure(closure) => {
+                format!("closure {}", closure.func_idx)
+            }
+        };
+        ExecutionProfile {
+            canister_id,
+            method,
+            instructions_executed: instructions_executed.get(),
+            system_api_calls: self.system_api_calls,
+            stacks: self
+                .stacks
+                .into_iter()
+                .map(|(frames, instructions)| StackSample {
+                    frames,
+                    instructions,
+                })
+                .collect(),
+            wasm_dirty_pages: instance_stats.wasm_dirty_pages,
+            stable_dirty_pages: instance_stats.stable_dirty_pages,
+        }
+    }
+}
@@ -17,7 +17,7 @@ use ic_sys::PAGE_SIZE;
 use ic_types::{Cycles, NumBytes, NumInstructions, NumOsPages, Time};
 use ic_wasm_types::WasmEngineError;
 
-use wasmtime::{AsContextMut, Caller, Global, Linker, Val};
+use wasmtime::{AsContextMut, Caller, Global, Linker, Val, WasmBacktrace};
 
 use crate::InternalErrorCode;
 use std::convert::TryFrom;
@@ -299,12 +299,51 @@ fn ic0_performance_counter_helper(
     }
 }
 
+/// Adds a sample for the given system API call to the execution profile.
+/// This is a no-op unless `execution_profiling` is enabled in the config.
+#[inline(always)]
+fn profile_system_api_call(
+    execution_profiling: FlagStatus,
+    caller: &mut Caller<'_, StoreData>,
+    name: &'static str,
+) -> Result<(), anyhow::Error> {
+    match execution_profiling {
+        FlagStatus::Disabled => Ok(()),
+        FlagStatus::Enabled => {
+            sample_system_api_call(caller, name).map_err(|e| process_err(caller, e))
+        }
+    }
+}
+
+#[inline(never)]
+fn sample_system_api_call(
+    caller: &mut Caller<'_, StoreData>,
+    name: &'static str,
+) -> HypervisorResult<()> {
+    // There is no profiler if the execution has no system API.
+    if caller.data().execution_profiler.is_none() {
+        return Ok(());
+    }
+    let num_instructions_global = get_num_instructions_global(caller)?;
+    let instruction_counter = load_value(&num_instructions_global, caller)?;
+    let instructions = caller
+        .data()
+        .system_api()?
+        .message_instructions_executed(instruction_counter);
+    let backtrace = WasmBacktrace::force_capture(&*caller);
+    if let Some(profiler) = caller.data_mut().execution_profiler.as_mut() {
+        profiler.record_system_api_call(name, instructions, &backtrace);
+    }
+    Ok(())
+}
+
 pub(crate) fn syscalls(
     linker: &mut Linker<StoreData>,
     feature_flags: FeatureFlags,
     stable_memory_dirty_page_limit: StableMemoryDirtyPageLimit,
     stable_memory_access_page_limit: NumOsPages,
     main_memory_type: WasmMemoryType,
+    execution_profiling: FlagStatus,
 ) {
     fn with_system_api<T>(
         mut caller: &mut Caller<'_, StoreData>,
@@ -385,6 +424,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_caller_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: u32, offset: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_caller_copy")?;
                 let (dst, offset, size) = (dst as usize, offset as usize, size as usize);
                 charge_for_cpu_and_mem(&mut caller, overhead::MSG_CALLER_COPY, size)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -402,6 +442,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_caller_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_caller_size")?;
                 charge_for_cpu(&mut caller, overhead::MSG_CALLER_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_caller_size()).and_then(|s| {
                     i32::try_from(s).map_err(|e| {
@@ -415,6 +456,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_arg_data_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_arg_data_size")?;
                 charge_for_cpu(&mut caller, overhead::MSG_ARG_DATA_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_arg_data_size()).and_then(|s| {
                     i32::try_from(s).map_err(|e| {
@@ -428,6 +470,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_arg_data_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: u32, offset: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_arg_data_copy")?;
                 let (dst, offset, size) = (dst as usize, offset as usize, size as usize);
                 charge_for_cpu_and_mem(&mut caller, overhead::MSG_ARG_DATA_COPY, size)?;
                 with_memory_and_system_api(&mut caller, |system_api, mem| {
@@ -445,6 +488,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_method_name_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_method_name_size")?;
                 charge_for_cpu(&mut caller, overhead::MSG_METHOD_NAME_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_method_name_size()).and_then(|s| {
                     i32::try_from(s).map_err(|e| {
@@ -458,6 +502,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_method_name_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: u32, offset: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_method_name_copy")?;
                 let (dst, offset, size) = (dst as usize, offset as usize, size as usize);
                 charge_for_cpu_and_mem(&mut caller, overhead::MSG_METHOD_NAME_COPY, size)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -475,6 +520,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "accept_message", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "accept_message")?;
                 charge_for_cpu(&mut caller, overhead::ACCEPT_MESSAGE)?;
                 with_system_api(&mut caller, |s| s.ic0_accept_message())
             }
@@ -484,6 +530,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_reply_data_append", {
             move |mut caller: Caller<'_, StoreData>, src: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_reply_data_append")?;
                 let (src, size) = (src as usize, size as usize);
                 charge_for_cpu_and_mem(
                     &mut caller,
@@ -500,6 +547,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_reply", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_reply")?;
                 charge_for_cpu(&mut caller, overhead::MSG_REPLY)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_reply())
             }
@@ -509,6 +557,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_reject_code", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_reject_code")?;
                 charge_for_cpu(&mut caller, overhead::MSG_REJECT_CODE)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_reject_code())
             }
@@ -518,6 +567,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_reject", {
             move |mut caller: Caller<'_, StoreData>, src: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_reject")?;
                 let (src, size) = (src as usize, size as usize);
                 charge_for_cpu_and_mem(
                     &mut caller,
@@ -534,6 +584,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_reject_msg_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_reject_msg_size")?;
                 charge_for_cpu(&mut caller, overhead::MSG_REJECT_MSG_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_reject_msg_size()).and_then(|s| {
                     i32::try_from(s).map_err(|e| {
@@ -547,6 +598,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_reject_msg_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: u32, offset: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_reject_msg_copy")?;
                 let (dst, offset, size) = (dst as usize, offset as usize, size as usize);
                 charge_for_cpu_and_mem(&mut caller, overhead::MSG_REJECT_MSG_COPY, size)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -564,6 +616,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "canister_self_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "canister_self_size")?;
                 charge_for_cpu(&mut caller, overhead::CANISTER_SELF_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_canister_self_size()).and_then(|s| {
                     i32::try_from(s).map_err(|e| {
@@ -577,6 +630,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "canister_self_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: u32, offset: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "canister_self_copy")?;
                 let (dst, offset, size) = (dst as usize, offset as usize, size as usize);
                 charge_for_cpu_and_mem(&mut caller, overhead::CANISTER_SELF_COPY, size)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -594,6 +648,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "debug_print", {
             move |mut caller: Caller<'_, StoreData>, offset: u32, length: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "debug_print")?;
                 let mut num_bytes = 0;
                 let canister_logging_is_enabled =
                     feature_flags.canister_logging == FlagStatus::Enabled;
@@ -626,6 +681,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "trap", {
             move |mut caller: Caller<'_, StoreData>, offset: u32, length: u32| -> Result<(), _> {
+                profile_system_api_call(execution_profiling, &mut caller, "trap")?;
                 let (offset, length) = (offset as usize, length as usize);
                 charge_for_cpu_and_mem(&mut caller, overhead::TRAP, length)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -646,6 +702,7 @@ pub(crate) fn syscalls(
                   reply_env: u32,
                   reject_fun: u32,
                   reject_env: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "call_new")?;
                 let (callee_src, callee_size) = (callee_src as usize, callee_size as usize);
                 let (name_src, name_len) = (name_src as usize, name_len as usize);
                 charge_for_cpu_and_mem(
@@ -673,6 +730,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "call_data_append", {
             move |mut caller: Caller<'_, StoreData>, src: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "call_data_append")?;
                 let (src, size) = (src as usize, size as usize);
                 charge_for_cpu_and_mem(
                     &mut caller,
@@ -689,6 +747,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "call_on_cleanup", {
             move |mut caller: Caller<'_, StoreData>, fun: u32, env: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "call_on_cleanup")?;
                 charge_for_cpu(&mut caller, overhead::CALL_ON_CLEANUP)?;
                 with_system_api(&mut caller, |s| s.ic0_call_on_cleanup(fun, env))
             }
@@ -698,6 +757,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "call_cycles_add", {
             move |mut caller: Caller<'_, StoreData>, amount: u64| {
+                profile_system_api_call(execution_profiling, &mut caller, "call_cycles_add")?;
                 charge_for_cpu(&mut caller, overhead::CALL_CYCLES_ADD)?;
                 with_system_api(&mut caller, |s| s.ic0_call_cycles_add(amount))
             }
@@ -707,6 +767,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "call_cycles_add128", {
             move |mut caller: Caller<'_, StoreData>, amount_high: u64, amount_low: u64| {
+                profile_system_api_call(execution_profiling, &mut caller, "call_cycles_add128")?;
                 charge_for_cpu(&mut caller, overhead::CALL_CYCLES_ADD128)?;
                 with_system_api(&mut caller, |s| {
                     s.ic0_call_cycles_add128(Cycles::from_parts(amount_high, amount_low))
@@ -718,6 +779,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "call_perform", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "call_perform")?;
                 charge_for_cpu(&mut caller, overhead::CALL_PERFORM)?;
                 with_system_api(&mut caller, |s| s.ic0_call_perform())
             }
@@ -727,6 +789,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "stable_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "stable_size")?;
                 charge_for_cpu(&mut caller, overhead::STABLE_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_stable_size()).and_then(|s| {
                     i32::try_from(s)
@@ -739,6 +802,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "stable_grow", {
             move |mut caller: Caller<'_, StoreData>, additional_pages: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "stable_grow")?;
                 charge_for_cpu(&mut caller, overhead::STABLE_GROW)?;
                 with_system_api(&mut caller, |s| s.ic0_stable_grow(additional_pages))
             }
@@ -748,6 +812,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "stable_read", {
             move |mut caller: Caller<'_, StoreData>, dst: u32, offset: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "stable_read")?;
                 charge_for_cpu_and_mem(&mut caller, overhead::STABLE_READ, size as usize)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
                     system_api.ic0_stable_read(dst, offset, size, memory)
@@ -764,6 +829,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "stable_write", {
             move |mut caller: Caller<'_, StoreData>, offset: u32, src: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "stable_write")?;
                 charge_for_stable_write(
                     &mut caller,
                     overhead::STABLE_WRITE,
@@ -783,6 +849,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "stable64_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "stable64_size")?;
                 charge_for_cpu(&mut caller, overhead::STABLE64_SIZE)?;
                 with_system_api(&mut caller, |s| s.ic0_stable64_size()).and_then(|s| {
                     i64::try_from(s)
@@ -795,6 +862,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "stable64_grow", {
             move |mut caller: Caller<'_, StoreData>, additional_pages: u64| {
+                profile_system_api_call(execution_profiling, &mut caller, "stable64_grow")?;
                 charge_for_cpu(&mut caller, overhead::STABLE64_GROW)?;
                 with_system_api(&mut caller, |s| s.ic0_stable64_grow(additional_pages))
             }
@@ -819,6 +887,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "stable64_read", {
             move |mut caller: Caller<'_, StoreData>, dst: u64, offset: u64, size: u64| {
+                profile_system_api_call(execution_profiling, &mut caller, "stable64_read")?;
                 charge_for_cpu_and_mem(&mut caller, overhead::STABLE64_READ, size as usize)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
                     system_api.ic0_stable64_read(dst, offset, size, memory)
@@ -835,6 +904,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "stable64_write", {
             move |mut caller: Caller<'_, StoreData>, offset: u64, src: u64, size: u64| {
+                profile_system_api_call(execution_profiling, &mut caller, "stable64_write")?;
                 charge_for_stable_write(
                     &mut caller,
                     overhead::STABLE64_WRITE,
@@ -854,6 +924,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "time", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "time")?;
                 charge_for_cpu(&mut caller, overhead::TIME)?;
                 with_system_api(&mut caller, |s| s.ic0_time())
                     .map(|s| s.as_nanos_since_unix_epoch())
@@ -864,6 +935,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "global_timer_set", {
             move |mut caller: Caller<'_, StoreData>, time: u64| {
+                profile_system_api_call(execution_profiling, &mut caller, "global_timer_set")?;
                 charge_for_cpu(&mut caller, overhead::GLOBAL_TIMER_SET)?;
                 with_system_api(&mut caller, |s| {
                     s.ic0_global_timer_set(Time::from_nanos_since_unix_epoch(time))
@@ -876,6 +948,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "performance_counter", {
             move |mut caller: Caller<'_, StoreData>, counter_type: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "performance_counter")?;
                 charge_for_cpu(&mut caller, overhead::PERFORMANCE_COUNTER)?;
                 ic0_performance_counter_helper(&mut caller, counter_type)
                     .map_err(|e| process_err(&mut caller, e))
@@ -886,6 +959,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "canister_version", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "canister_version")?;
                 charge_for_cpu(&mut caller, overhead::CANISTER_VERSION)?;
                 with_system_api(&mut caller, |s| s.ic0_canister_version())
             }
@@ -895,6 +969,11 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "canister_cycle_balance", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(
+                    execution_profiling,
+                    &mut caller,
+                    "canister_cycle_balance",
+                )?;
                 charge_for_cpu(&mut caller, overhead::CANISTER_CYCLE_BALANCE)?;
                 with_system_api(&mut caller, |s| s.ic0_canister_cycle_balance()).and_then(|s| {
                     i64::try_from(s).map_err(|e| {
@@ -908,6 +987,11 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "canister_cycle_balance128", {
             move |mut caller: Caller<'_, StoreData>, dst: u32| {
+                profile_system_api_call(
+                    execution_profiling,
+                    &mut caller,
+                    "canister_cycle_balance128",
+                )?;
                 let dst = dst as usize;
                 charge_for_cpu(&mut caller, overhead::CANISTER_CYCLE_BALANCE128)?;
                 with_memory_and_system_api(&mut caller, |s, memory| {
@@ -925,6 +1009,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_cycles_available", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_cycles_available")?;
                 charge_for_cpu(&mut caller, overhead::MSG_CYCLES_AVAILABLE)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_cycles_available()).and_then(|s| {
                     i64::try_from(s).map_err(|e| {
@@ -938,6 +1023,11 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_cycles_available128", {
             move |mut caller: Caller<'_, StoreData>, dst: u32| {
+                profile_system_api_call(
+                    execution_profiling,
+                    &mut caller,
+                    "msg_cycles_available128",
+                )?;
                 let dst = dst as usize;
                 charge_for_cpu(&mut caller, overhead::MSG_CYCLES_AVAILABLE128)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -955,6 +1045,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_cycles_refunded", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_cycles_refunded")?;
                 charge_for_cpu(&mut caller, overhead::MSG_CYCLES_REFUNDED)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_cycles_refunded()).and_then(|s| {
                     i64::try_from(s).map_err(|e| {
@@ -968,6 +1059,11 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_cycles_refunded128", {
             move |mut caller: Caller<'_, StoreData>, dst: u32| {
+                profile_system_api_call(
+                    execution_profiling,
+                    &mut caller,
+                    "msg_cycles_refunded128",
+                )?;
                 let dst = dst as usize;
                 charge_for_cpu(&mut caller, overhead::MSG_CYCLES_REFUNDED128)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -985,6 +1081,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_cycles_accept", {
             move |mut caller: Caller<'_, StoreData>, amount: u64| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_cycles_accept")?;
                 charge_for_cpu(&mut caller, overhead::MSG_CYCLES_ACCEPT)?;
                 with_system_api(&mut caller, |s| s.ic0_msg_cycles_accept(amount))
             }
@@ -994,6 +1091,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_cycles_accept128", {
             move |mut caller: Caller<'_, StoreData>, amount_high: u64, amount_low: u64, dst: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_cycles_accept128")?;
                 let dst = dst as usize;
                 charge_for_cpu(&mut caller, overhead::MSG_CYCLES_ACCEPT128)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -1075,7 +1173,10 @@ pub(crate) fn syscalls(
                         additional_pages as u64,
                         stable_memory_api
                             .try_into()
-                            .map_err(|()| HypervisorError::Trapped(TrapCode::Other))?,
+                            .map_err(|()| HypervisorError::Trapped {
+                                trap_code: TrapCode::Other,
+                                backtrace: None,
+                            })?,
                     )? {
                         StableGrowOutcome::Success => Ok(current_size),
                         StableGrowOutcome::Failure => Ok(-1),
@@ -1088,6 +1189,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "canister_status", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "canister_status")?;
                 charge_for_cpu(&mut caller, overhead::CANISTER_STATUS)?;
                 with_system_api(&mut caller, |s| s.ic0_canister_status())
             }
@@ -1097,6 +1199,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "certified_data_set", {
             move |mut caller: Caller<'_, StoreData>, src: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "certified_data_set")?;
                 let (src, size) = (src as usize, size as usize);
                 charge_for_cpu_and_mem(&mut caller, overhead::CERTIFIED_DATA_SET, size)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -1109,6 +1212,11 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "data_certificate_present", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(
+                    execution_profiling,
+                    &mut caller,
+                    "data_certificate_present",
+                )?;
                 charge_for_cpu(&mut caller, overhead::DATA_CERTIFICATE_PRESENT)?;
                 with_system_api(&mut caller, |s| s.ic0_data_certificate_present())
             }
@@ -1118,6 +1226,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "data_certificate_size", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "data_certificate_size")?;
                 charge_for_cpu(&mut caller, overhead::DATA_CERTIFICATE_SIZE)?;
                 with_system_api(&mut caller, |s| {
                     s.ic0_data_certificate_size().map(|x| x as u32)
@@ -1129,6 +1238,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "is_controller", {
             move |mut caller: Caller<'_, StoreData>, src: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "is_controller")?;
                 let (src, size) = (src as usize, size as usize);
                 charge_for_cpu_and_mem(&mut caller, overhead::IS_CONTROLLER, size)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -1141,6 +1251,11 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "in_replicated_execution", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(
+                    execution_profiling,
+                    &mut caller,
+                    "in_replicated_execution",
+                )?;
                 charge_for_cpu(&mut caller, overhead::IN_REPLICATED_EXECUTION)?;
                 with_system_api(&mut caller, |s| s.ic0_in_replicated_execution())
             }
@@ -1150,6 +1265,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "data_certificate_copy", {
             move |mut caller: Caller<'_, StoreData>, dst: u32, offset: u32, size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "data_certificate_copy")?;
                 let (dst, offset, size) = (dst as usize, offset as usize, size as usize);
                 charge_for_cpu_and_mem(&mut caller, overhead::DATA_CERTIFICATE_COPY, size)?;
                 with_memory_and_system_api(&mut caller, |system_api, memory| {
@@ -1167,6 +1283,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "mint_cycles", {
             move |mut caller: Caller<'_, StoreData>, amount: u64| {
+                profile_system_api_call(execution_profiling, &mut caller, "mint_cycles")?;
                 with_system_api(&mut caller, |s| s.ic0_mint_cycles(amount)).and_then(|s| {
                     i64::try_from(s)
                         .map_err(|e| anyhow::Error::msg(format!("ic0_mint_cycles failed: {}", e)))
@@ -1178,6 +1295,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "cycles_burn128", {
             move |mut caller: Caller<'_, StoreData>, amount_high: u64, amount_low: u64, dst: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "cycles_burn128")?;
                 let dst = dst as usize;
                 with_memory_and_system_api(&mut caller, |s, memory| {
                     s.ic0_cycles_burn128(Cycles::from_parts(amount_high, amount_low), dst, memory)
@@ -1187,9 +1305,228 @@ pub(crate) fn syscalls(
         })
         .unwrap();
 
+    linker
+        .func_wrap("ic0", "cost_call", {
+            move |mut caller: Caller<'_, StoreData>,
+                  method_name_size: u64,
+                  payload_size: u64,
+                  dst: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "cost_call")?;
+                let dst = dst as usize;
+                charge_for_cpu(&mut caller, overhead::COST_CALL)?;
+                with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api.ic0_cost_call(method_name_size, payload_size, dst, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, 16)
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "cost_create_canister", {
+            move |mut caller: Caller<'_, StoreData>, dst: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "cost_create_canister")?;
+                let dst = dst as usize;
+                charge_for_cpu(&mut caller, overhead::COST_CREATE_CANISTER)?;
+                with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api.ic0_cost_create_canister(dst, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, 16)
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "cost_http_request", {
+            move |mut caller: Caller<'_, StoreData>,
+                  request_size: u64,
+                  max_res_bytes: u64,
+                  dst: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "cost_http_request")?;
+                let dst = dst as usize;
+                charge_for_cpu(&mut caller, overhead::COST_HTTP_REQUEST)?;
+                with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api.ic0_cost_http_request(request_size, max_res_bytes, dst, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, 16)
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "cost_sign_with_ecdsa", {
+            move |mut caller: Caller<'_, StoreData>, src: u32, size: u32, curve: u32, dst: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "cost_sign_with_ecdsa")?;
+                let (src, size, dst) = (src as usize, size as usize, dst as usize);
+                charge_for_cpu_and_mem(&mut caller, overhead::COST_SIGN_WITH_ECDSA, size)?;
+                let result = with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api.ic0_cost_sign_with_ecdsa(src, size, curve, dst, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, 16)?;
+                }
+                Ok(result)
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "cost_sign_with_schnorr", {
+            move |mut caller: Caller<'_, StoreData>,
+                  src: u32,
+                  size: u32,
+                  algorithm: u32,
+                  dst: u32| {
+                profile_system_api_call(
+                    execution_profiling,
+                    &mut caller,
+                    "cost_sign_with_schnorr",
+                )?;
+                let (src, size, dst) = (src as usize, size as usize, dst as usize);
+                charge_for_cpu_and_mem(&mut caller, overhead::COST_SIGN_WITH_SCHNORR, size)?;
+                let result = with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api.ic0_cost_sign_with_schnorr(src, size, algorithm, dst, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, 16)?;
+                }
+                Ok(result)
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "env_var_count", {
+            move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "env_var_count")?;
+                charge_for_cpu(&mut caller, overhead::ENV_VAR_COUNT)?;
+                with_system_api(&mut caller, |s| s.ic0_env_var_count()).and_then(|s| {
+                    i32::try_from(s)
+                        .map_err(|e| anyhow::Error::msg(format!("ic0_env_var_count failed: {}", e)))
+                })
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "env_var_name_size", {
+            move |mut caller: Caller<'_, StoreData>, index: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "env_var_name_size")?;
+                charge_for_cpu(&mut caller, overhead::ENV_VAR_NAME_SIZE)?;
+                with_system_api(&mut caller, |s| s.ic0_env_var_name_size(index as usize)).and_then(
+                    |s| {
+                        i32::try_from(s).map_err(|e| {
+                            anyhow::Error::msg(format!("ic0_env_var_name_size failed: {}", e))
+                        })
+                    },
+                )
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "env_var_name_copy", {
+            move |mut caller: Caller<'_, StoreData>,
+                  index: u32,
+                  dst: u32,
+                  offset: u32,
+                  size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "env_var_name_copy")?;
+                let (index, dst, offset, size) =
+                    (index as usize, dst as usize, offset as usize, size as usize);
+                charge_for_cpu_and_mem(&mut caller, overhead::ENV_VAR_NAME_COPY, size)?;
+                with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api.ic0_env_var_name_copy(index, dst, offset, size, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, size)
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "env_var_name_exists", {
+            move |mut caller: Caller<'_, StoreData>, name_src: u32, name_size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "env_var_name_exists")?;
+                let (name_src, name_size) = (name_src as usize, name_size as usize);
+                charge_for_cpu_and_mem(&mut caller, overhead::ENV_VAR_NAME_EXISTS, name_size)?;
+                with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api.ic0_env_var_name_exists(name_src, name_size, memory)
+                })
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "env_var_value_size", {
+            move |mut caller: Caller<'_, StoreData>, name_src: u32, name_size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "env_var_value_size")?;
+                let (name_src, name_size) = (name_src as usize, name_size as usize);
+                charge_for_cpu_and_mem(&mut caller, overhead::ENV_VAR_VALUE_SIZE, name_size)?;
+                with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api.ic0_env_var_value_size(name_src, name_size, memory)
+                })
+                .and_then(|s| {
+                    i32::try_from(s).map_err(|e| {
+                        anyhow::Error::msg(format!("ic0_env_var_value_size failed: {}", e))
+                    })
+                })
+            }
+        })
+        .unwrap();
+
+    linker
+        .func_wrap("ic0", "env_var_value_copy", {
+            move |mut caller: Caller<'_, StoreData>,
+                  name_src: u32,
+                  name_size: u32,
+                  dst: u32,
+                  offset: u32,
+                  size: u32| {
+                profile_system_api_call(execution_profiling, &mut caller, "env_var_value_copy")?;
+                let (name_src, name_size) = (name_src as usize, name_size as usize);
+                let (dst, offset, size) = (dst as usize, offset as usize, size as usize);
+                charge_for_cpu_and_mem(
+                    &mut caller,
+                    overhead::ENV_VAR_VALUE_COPY,
+                    name_size.saturating_add(size),
+                )?;
+                with_memory_and_system_api(&mut caller, |system_api, memory| {
+                    system_api
+                        .ic0_env_var_value_copy(name_src, name_size, dst, offset, size, memory)
+                })?;
+                if feature_flags.write_barrier == FlagStatus::Enabled {
+                    mark_writes_on_bytemap(&mut caller, dst, size)
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .unwrap();
+
     linker
         .func_wrap("ic0", "call_with_best_effort_response", {
             move |mut caller: Caller<'_, StoreData>, timeout_seconds: u32| {
+                profile_system_api_call(
+                    execution_profiling,
+                    &mut caller,
+                    "call_with_best_effort_response",
+                )?;
                 charge_for_cpu(&mut caller, overhead::CALL_WITH_BEST_EFFORT_RESPONSE)?;
                 if feature_flags.best_effort_responses == FlagStatus::Enabled {
                     with_system_api(&mut caller, |system_api| {
@@ -1210,6 +1547,7 @@ pub(crate) fn syscalls(
     linker
         .func_wrap("ic0", "msg_deadline", {
             move |mut caller: Caller<'_, StoreData>| {
+                profile_system_api_call(execution_profiling, &mut caller, "msg_deadline")?;
                 charge_for_cpu(&mut caller, overhead::MSG_DEADLINE)?;
                 if feature_flags.best_effort_responses == FlagStatus::Enabled {
                     with_system_api(&mut caller, |system_api| system_api.ic0_msg_deadline())
@@ -1230,13 +1568,13 @@ pub(crate) fn syscalls(
             move |mut caller: Caller<'_, StoreData>, err_code: i32| -> Result<(), _> {
                 let err = match InternalErrorCode::from_i32(err_code) {
                     InternalErrorCode::HeapOutOfBounds => {
-                        HypervisorError::Trapped(TrapCode::HeapOutOfBounds)
+                        HypervisorError::Trapped { trap_code: TrapCode::HeapOutOfBounds, backtrace: None }
                     }
                     InternalErrorCode::StableMemoryOutOfBounds => {
-                        HypervisorError::Trapped(TrapCode::StableMemoryOutOfBounds)
+                        HypervisorError::Trapped { trap_code: TrapCode::StableMemoryOutOfBounds, backtrace: None }
                     }
                     InternalErrorCode::StableMemoryTooBigFor32Bit => {
-                        HypervisorError::Trapped(TrapCode::StableMemoryTooBigFor32Bit)
+                        HypervisorError::Trapped { trap_code: TrapCode::StableMemoryTooBigFor32Bit, backtrace: None }
                     }
                     InternalErrorCode::MemoryWriteLimitExceeded => {
                         HypervisorError::MemoryAccessLimitExceeded(
@@ -1253,12 +1591,15 @@ pub(crate) fn syscalls(
                         )
                     }
                     InternalErrorCode::StableGrowFailed => {
-                        HypervisorError::CalledTrap("Internal error: `memory.grow` instruction failed to grow stable memory".to_string())
+                        HypervisorError::CalledTrap {
+                            message: "Internal error: `memory.grow` instruction failed to grow stable memory".to_string(),
+                            backtrace: None,
+                        }
                     }
-                    InternalErrorCode::Unknown => HypervisorError::CalledTrap(format!(
-                        "Trapped with internal error code: {}",
-                        err_code
-                    )),
+                    InternalErrorCode::Unknown => HypervisorError::CalledTrap {
+                        message: format!("Trapped with internal error code: {}", err_code),
+                        backtrace: None,
+                    },
                 };
                 Err(process_err(&mut caller, err))
             }
@@ -33,12 +33,23 @@ pub mod overhead {
     pub const CANISTER_STATUS: NumInstructions = NumInstructions::new(500);
     pub const CANISTER_VERSION: NumInstructions = NumInstructions::new(500);
     pub const CERTIFIED_DATA_SET: NumInstructions = NumInstructions::new(500);
+    pub const COST_CALL: NumInstructions = NumInstructions::new(500);
+    pub const COST_CREATE_CANISTER: NumInstructions = NumInstructions::new(500);
+    pub const COST_HTTP_REQUEST: NumInstructions = NumInstructions::new(500);
+    pub const COST_SIGN_WITH_ECDSA: NumInstructions = NumInstructions::new(500);
+    pub const COST_SIGN_WITH_SCHNORR: NumInstructions = NumInstructions::new(500);
     pub const CONTROLLER_COPY: NumInstructions = NumInstructions::new(500);
     pub const CONTROLLER_SIZE: NumInstructions = NumInstructions::new(500);
     pub const DATA_CERTIFICATE_COPY: NumInstructions = NumInstructions::new(500);
     pub const DATA_CERTIFICATE_PRESENT: NumInstructions = NumInstructions::new(500);
     pub const DATA_CERTIFICATE_SIZE: NumInstructions = NumInstructions::new(500);
     pub const DEBUG_PRINT: NumInstructions = NumInstructions::new(100);
+    pub const ENV_VAR_COUNT: NumInstructions = NumInstructions::new(500);
+    pub const ENV_VAR_NAME_COPY: NumInstructions = NumInstructions::new(500);
+    pub const ENV_VAR_NAME_EXISTS: NumInstructions = NumInstructions::new(500);
+    pub const ENV_VAR_NAME_SIZE: NumInstructions = NumInstructions::new(500);
+    pub const ENV_VAR_VALUE_COPY: NumInstructions = NumInstructions::new(500);
+    pub const ENV_VAR_VALUE_SIZE: NumInstructions = NumInstructions::new(500);
     pub const GLOBAL_TIMER_SET: NumInstructions = NumInstructions::new(500);
     pub const IS_CONTROLLER: NumInstructions = NumInstructions::new(1_000);
     pub const IN_REPLICATED_EXECUTION: NumInstructions = NumInstructions::new(500);
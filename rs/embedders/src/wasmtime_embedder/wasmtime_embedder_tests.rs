@@ -101,6 +101,7 @@ fn test_wasmtime_system_api() {
             num_instructions_global: None,
             log: no_op_logger(),
             num_stable_dirty_pages_from_non_native_writes: ic_types::NumOsPages::from(0),
+            execution_profiler: None,
             limits: StoreLimits::default(),
         },
     );
@@ -137,6 +138,7 @@ fn test_wasmtime_system_api() {
         config.stable_memory_dirty_page_limit,
         config.stable_memory_accessed_page_limit,
         crate::wasmtime_embedder::WasmMemoryType::Wasm32,
+        config.execution_profiling,
     );
     let instance = linker
         .instantiate(&mut store, &module)
@@ -7,7 +7,7 @@ use ic_interfaces::execution_environment::{HypervisorError, SystemApi, TrapCode}
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{canister_state::WASM_PAGE_SIZE_IN_BYTES, Global};
 use ic_test_utilities_embedders::{WasmtimeInstanceBuilder, DEFAULT_NUM_INSTRUCTIONS};
-use ic_test_utilities_types::ids::user_test_id;
+use ic_test_utilities_types::ids::{canister_test_id, user_test_id};
 use ic_types::{
     methods::{FuncRef, WasmClosure, WasmMethod},
     time::UNIX_EPOCH,
@@ -17,6 +17,13 @@ use ic_types::{
 #[cfg(target_os = "linux")]
 use ic_types::{Cycles, PrincipalId};
 
+fn trapped(trap_code: TrapCode) -> HypervisorError {
+    HypervisorError::Trapped {
+        trap_code,
+        backtrace: None,
+    }
+}
+
 /// Ensures that attempts to execute messages on wasm modules that do not
 /// define memory fails.
 #[test]
@@ -285,9 +292,10 @@ fn stack_overflow_traps() {
             assert_eq!(
                 result.err(),
                 Some(
-                    ic_interfaces::execution_environment::HypervisorError::Trapped(
-                        ic_interfaces::execution_environment::TrapCode::StackOverflow
-                    )
+                    ic_interfaces::execution_environment::HypervisorError::Trapped {
+                        trap_code: ic_interfaces::execution_environment::TrapCode::StackOverflow,
+                        backtrace: None
+                    }
                 )
             );
         })
@@ -527,7 +535,10 @@ fn calling_function_by_index() {
         .unwrap_err();
     assert_eq!(
         err,
-        HypervisorError::CalledTrap(std::str::from_utf8(&[0; 6]).unwrap().to_string())
+        HypervisorError::CalledTrap {
+            message: std::str::from_utf8(&[0; 6]).unwrap().to_string(),
+            backtrace: None,
+        }
     );
 }
 
@@ -548,7 +559,10 @@ fn zero_size_memory() {
         .unwrap_err();
     assert_eq!(
         err,
-        HypervisorError::CalledTrap(std::str::from_utf8(&[0; 0]).unwrap().to_string())
+        HypervisorError::CalledTrap {
+            message: std::str::from_utf8(&[0; 0]).unwrap().to_string(),
+            backtrace: None,
+        }
     );
 }
 
@@ -648,7 +662,13 @@ fn stable_write_and_read() {
     let err = instance
         .run(FuncRef::Method(WasmMethod::Update("test".to_string())))
         .unwrap_err();
-    assert_eq!(err, HypervisorError::CalledTrap("Hello".to_string()));
+    assert_eq!(
+        err,
+        HypervisorError::CalledTrap {
+            message: "Hello".to_string(),
+            backtrace: None,
+        }
+    );
 }
 
 #[test]
@@ -689,7 +709,13 @@ fn stable64_write_and_read() {
     let err = instance
         .run(FuncRef::Method(WasmMethod::Update("test".to_string())))
         .unwrap_err();
-    assert_eq!(err, HypervisorError::CalledTrap("Hello".to_string()));
+    assert_eq!(
+        err,
+        HypervisorError::CalledTrap {
+            message: "Hello".to_string(),
+            backtrace: None,
+        }
+    );
 }
 
 #[test]
@@ -1014,29 +1040,28 @@ fn stable_read_out_of_bounds() {
             (memory 2 2)
         )"#;
 
-    use HypervisorError::*;
     use TrapCode::*;
 
     // Host stable memory
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_src")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_dst")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len_heap")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len_stable")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len_both")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     // native stable memory
     let mut config = Config::default();
@@ -1047,35 +1072,35 @@ fn stable_read_out_of_bounds() {
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_src")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_dst")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len_heap")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len_stable")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config)
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len_both")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 }
 
 #[test]
@@ -1129,33 +1154,32 @@ fn stable64_read_out_of_bounds() {
             (memory 2 2)
         )"#;
 
-    use HypervisorError::*;
     use TrapCode::*;
 
     // Host stable memory
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_src")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_dst")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len_heap")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len_stable")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len_both")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     // Native stable memory
     let mut config = Config::default();
@@ -1166,42 +1190,42 @@ fn stable64_read_out_of_bounds() {
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_src")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_dst")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len_heap")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len_stable")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config)
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len_both")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 }
 
 #[test]
@@ -1248,29 +1272,28 @@ fn stable_write_out_of_bounds() {
             (memory 2 2)
         )"#;
 
-    use HypervisorError::*;
     use TrapCode::*;
 
     // Host stable memory
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_src")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_dst")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len_heap")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len_stable")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len_both")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     // native stable memory
     let mut config = Config::default();
@@ -1281,35 +1304,35 @@ fn stable_write_out_of_bounds() {
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_src")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_dst")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len_heap")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len_stable")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config)
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len_both")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 }
 
 #[test]
@@ -1363,33 +1386,32 @@ fn stable64_write_out_of_bounds() {
             (memory 2 2)
         )"#;
 
-    use HypervisorError::*;
     use TrapCode::*;
 
     // Host stable memory
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_src")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_dst")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len_heap")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len_stable")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new().with_wat(wat).build();
     let err = instance.run(func_ref("test_len_both")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     // native stable memory
     let mut config = Config::default();
@@ -1399,42 +1421,42 @@ fn stable64_write_out_of_bounds() {
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_src")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_dst")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len_heap")).unwrap_err();
-    assert_eq!(err, Trapped(HeapOutOfBounds));
+    assert_eq!(err, trapped(HeapOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config.clone())
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len_stable")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 
     let mut instance = WasmtimeInstanceBuilder::new()
         .with_config(config)
         .with_wat(wat)
         .build();
     let err = instance.run(func_ref("test_len_both")).unwrap_err();
-    assert_eq!(err, Trapped(StableMemoryOutOfBounds));
+    assert_eq!(err, trapped(StableMemoryOutOfBounds));
 }
 
 /// Test that stable memory access past the normal 32-bit range (including
@@ -1497,7 +1519,7 @@ fn wasm_heap_oob_access() {
     let err = instance
         .run(FuncRef::Method(WasmMethod::Update("test".to_string())))
         .unwrap_err();
-    assert_eq!(err, HypervisorError::Trapped(TrapCode::HeapOutOfBounds));
+    assert_eq!(err, trapped(TrapCode::HeapOutOfBounds));
 }
 
 #[test]
@@ -1817,3 +1839,71 @@ fn wasm64_handles_memory_grow_failure_test() {
     assert_eq!(res.exported_globals[0], Global::I64(-1));
     assert_eq!(res.exported_globals[1], Global::I64(137));
 }
+
+#[test]
+fn execution_profile_attributes_instructions_to_call_stacks() {
+    let wat = r#"
+    (module
+        (import "ic0" "msg_arg_data_size" (func $msg_arg_data_size (result i32)))
+        (import "ic0" "debug_print" (func $debug_print (param i32 i32)))
+        (func $inner
+            (drop (call $msg_arg_data_size))
+        )
+        (func $test (export "canister_update test")
+            (call $inner)
+            (call $debug_print (i32.const 0) (i32.const 1))
+        )
+        (memory (export "memory") 1)
+    )"#;
+
+    let mut config = Config::default();
+    config.execution_profiling = FlagStatus::Enabled;
+    let mut instance = WasmtimeInstanceBuilder::new()
+        .with_config(config)
+        .with_wat(wat)
+        .build();
+    let func_ref = FuncRef::Method(WasmMethod::Update("test".to_string()));
+    instance.run(func_ref.clone()).unwrap();
+
+    let instruction_counter = instance.instruction_counter();
+    let instance_stats = instance.get_stats();
+    let store_data = instance.into_store_data();
+    let instructions_executed = store_data
+        .system_api()
+        .unwrap()
+        .message_instructions_executed(instruction_counter);
+    let profile = store_data.execution_profiler.unwrap().finish(
+        canister_test_id(0),
+        &func_ref,
+        instructions_executed,
+        &instance_stats,
+    );
+
+    assert_eq!(profile.method, "canister_update test");
+    assert_eq!(
+        profile
+            .system_api_calls
+            .iter()
+            .map(|call| call.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["msg_arg_data_size", "debug_print"]
+    );
+    // The two imports take the function indices 0 and 1, so `$inner` is 2
+    // and `$test` is 3 regardless of the imports injected by instrumentation.
+    assert_eq!(
+        profile
+            .stacks
+            .iter()
+            .map(|sample| sample.frames.clone())
+            .collect::<Vec<_>>(),
+        vec![vec![], vec![3], vec![3, 2]]
+    );
+    assert_eq!(
+        profile
+            .stacks
+            .iter()
+            .map(|sample| sample.instructions)
+            .sum::<u64>(),
+        instructions_executed.get()
+    );
+}
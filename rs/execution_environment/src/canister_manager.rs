@@ -14,33 +14,38 @@ use crate::{
 use ic_base_types::NumSeconds;
 use ic_config::flag_status::FlagStatus;
 use ic_cycles_account_manager::{CyclesAccountManager, ResourceSaturation};
+use ic_embedders::wasm_utils::decoding::MAX_WASM_MODULE_SIZE_BYTES;
 use ic_error_types::{ErrorCode, RejectCode, UserError};
 use ic_interfaces::execution_environment::{
     CanisterOutOfCyclesError, HypervisorError, IngressHistoryWriter, SubnetAvailableMemory,
 };
 use ic_logger::{error, fatal, info, ReplicaLogger};
 use ic_management_canister_types::{
-    CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallModeV2, CanisterSnapshotResponse,
-    CanisterStatusResultV2, CanisterStatusType, ChunkHash, InstallChunkedCodeArgs,
-    InstallCodeArgsV2, Method as Ic00Method, StoredChunksReply, UploadChunkReply,
+    CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallModeV2, CanisterSnapshotDataKind,
+    CanisterSnapshotDataOffset, CanisterSnapshotResponse, CanisterStatusResultV2,
+    CanisterStatusType, ChunkHash, CyclesBurned, EnvironmentVariable, InstallChunkedCodeArgs,
+    InstallCodeArgsV2, MemoryMetrics, Method as Ic00Method, ReadCanisterSnapshotDataResponse,
+    ReadCanisterSnapshotMetadataResponse, SnapshotSource, StoredChunksReply,
+    UploadCanisterSnapshotDataArgs, UploadCanisterSnapshotMetadataArgs,
+    UploadCanisterSnapshotMetadataResponse, UploadChunkReply,
 };
 use ic_registry_provisional_whitelist::ProvisionalWhitelist;
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::canister_state::system_state::ReservationError;
 use ic_replicated_state::{
-    canister_snapshots::CanisterSnapshot,
+    canister_snapshots::{CanisterSnapshot, ExecutionStateSnapshot, PageMemory},
     canister_state::{
-        execution_state::Memory,
+        execution_state::{Global, Memory},
         system_state::{
-            wasm_chunk_store::{self, WasmChunkStore},
+            wasm_chunk_store::{self, WasmChunkHash, WasmChunkStore},
             CyclesUseCase,
         },
-        NextExecution,
+        NextExecution, WASM_PAGE_SIZE_IN_BYTES,
     },
     metadata_state::subnet_call_context_manager::InstallCodeCallId,
-    page_map::PageAllocatorFileDescriptor,
-    CallOrigin, CanisterState, CanisterStatus, NetworkTopology, ReplicatedState, SchedulerState,
-    SystemState,
+    page_map::{Buffer, PageAllocatorFileDescriptor},
+    CallOrigin, CanisterState, CanisterStatus, NetworkTopology, NumWasmPages, PageMap,
+    ReplicatedState, SchedulerState, SystemState,
 };
 use ic_system_api::ExecutionParameters;
 use ic_types::{
@@ -52,7 +57,7 @@ use ic_types::{
     nominal_cycles::NominalCycles,
     CanisterId, CanisterTimer, ComputeAllocation, Cycles, InvalidComputeAllocationError,
     InvalidMemoryAllocationError, MemoryAllocation, NumBytes, NumInstructions, PrincipalId,
-    SnapshotId, SubnetId, Time,
+    SnapshotId, SubnetId, Time, MAX_STABLE_MEMORY_IN_BYTES, MAX_WASM_MEMORY_IN_BYTES,
 };
 use ic_wasm_types::{AsErrorHelp, CanisterModule, ErrorHelp, WasmHash};
 use num_traits::cast::ToPrimitive;
@@ -492,7 +497,11 @@ impl CanisterManager {
             | Ok(Ic00Method::TakeCanisterSnapshot)
             | Ok(Ic00Method::LoadCanisterSnapshot)
             | Ok(Ic00Method::ListCanisterSnapshots)
-            | Ok(Ic00Method::DeleteCanisterSnapshot) => {
+            | Ok(Ic00Method::DeleteCanisterSnapshot)
+            | Ok(Ic00Method::ReadCanisterSnapshotMetadata)
+            | Ok(Ic00Method::ReadCanisterSnapshotData)
+            | Ok(Ic00Method::UploadCanisterSnapshotMetadata)
+            | Ok(Ic00Method::UploadCanisterSnapshotData) => {
                 // Reject large install methods if the flag is not enabled, or
                 // they are not implemented.
                 match method {
@@ -640,11 +649,17 @@ impl CanisterManager {
             canister.system_state.freeze_threshold = freezing_threshold;
         }
         if let Some(log_visibility) = settings.log_visibility() {
-            canister.system_state.log_visibility = log_visibility;
+            canister.system_state.log_visibility = log_visibility.clone();
         }
         if let Some(wasm_memory_limit) = settings.wasm_memory_limit() {
             canister.system_state.wasm_memory_limit = Some(wasm_memory_limit);
         }
+        if let Some(environment_variables) = settings.environment_variables() {
+            canister.system_state.environment_variables = environment_variables.clone();
+        }
+        if let Some(wasm_backtraces) = settings.wasm_backtraces() {
+            canister.system_state.wasm_backtraces = wasm_backtraces;
+        }
     }
 
     /// Tries to apply the requested settings on the canister identified by
@@ -1158,6 +1173,7 @@ impl CanisterManager {
         &self,
         sender: PrincipalId,
         canister: &mut CanisterState,
+        snapshots_memory_usage: NumBytes,
         subnet_size: usize,
     ) -> Result<CanisterStatusResultV2, CanisterManagerError> {
         // Skip the controller check if the canister itself is requesting its
@@ -1179,8 +1195,49 @@ impl CanisterManager {
         let memory_allocation = canister.memory_allocation();
         let freeze_threshold = canister.system_state.freeze_threshold;
         let reserved_cycles_limit = canister.system_state.reserved_balance_limit();
-        let log_visibility = canister.system_state.log_visibility;
+        let log_visibility = canister.system_state.log_visibility.clone();
         let wasm_memory_limit = canister.system_state.wasm_memory_limit;
+        let environment_variables = canister
+            .system_state
+            .environment_variables
+            .iter()
+            .map(|(name, value)| EnvironmentVariable::new(name, value))
+            .collect();
+        let memory_metrics = MemoryMetrics::new(
+            canister.wasm_memory_usage(),
+            canister.stable_memory_usage(),
+            canister.globals_memory_usage(),
+            canister.wasm_binary_memory_usage(),
+            canister.wasm_custom_sections_memory_usage(),
+            canister.canister_history_memory_usage(),
+            canister.wasm_chunk_store_memory_usage(),
+            snapshots_memory_usage,
+            canister_message_memory_usage,
+        );
+        let consumed_cycles = canister
+            .system_state
+            .canister_metrics
+            .get_consumed_cycles_since_replica_started_by_use_cases();
+        let cycles_burned_by = |use_case| {
+            consumed_cycles
+                .get(&use_case)
+                .map_or(0, |cycles| cycles.get())
+        };
+        let cycles_burned = CyclesBurned::new(
+            cycles_burned_by(CyclesUseCase::Memory),
+            cycles_burned_by(CyclesUseCase::ComputeAllocation),
+            cycles_burned_by(CyclesUseCase::IngressInduction),
+            cycles_burned_by(CyclesUseCase::Instructions),
+            cycles_burned_by(CyclesUseCase::RequestAndResponseTransmission),
+            cycles_burned_by(CyclesUseCase::HTTPOutcalls),
+            cycles_burned_by(CyclesUseCase::ECDSAOutcalls),
+            cycles_burned_by(CyclesUseCase::SchnorrOutcalls),
+            cycles_burned_by(CyclesUseCase::Uninstall),
+            cycles_burned_by(CyclesUseCase::CanisterCreation),
+            cycles_burned_by(CyclesUseCase::DeletedCanisters),
+            cycles_burned_by(CyclesUseCase::NonConsumed),
+            cycles_burned_by(CyclesUseCase::BurnedCycles),
+        );
 
         Ok(CanisterStatusResultV2::new(
             canister.status(),
@@ -1218,6 +1275,10 @@ impl CanisterManager {
                 .total_query_stats
                 .egress_payload_size,
             wasm_memory_limit.map(|x| x.get()),
+            environment_variables,
+            canister.system_state.wasm_backtraces,
+            memory_metrics,
+            cycles_burned,
         ))
     }
 
@@ -1795,6 +1856,93 @@ impl CanisterManager {
         Ok(StoredChunksReply(keys))
     }
 
+    /// Checks that `bytes` of additional snapshot memory can be stored and
+    /// deducts them from the subnet available memory.
+    ///
+    /// Runs the following checks on memory usage and returns an error
+    /// if any fails:
+    /// 1. Check new usage will not freeze canister
+    /// 2. Check subnet has available memory
+    /// 3. Reserve cycles on canister
+    /// 4. Actually deduct memory from subnet (asserting it won't fail)
+    fn reserve_snapshot_memory(
+        &self,
+        subnet_size: usize,
+        canister: &mut CanisterState,
+        bytes: NumBytes,
+        round_limits: &mut RoundLimits,
+        resource_saturation: &ResourceSaturation,
+    ) -> Result<(), CanisterManagerError> {
+        // Calculate if any cycles will need to be reserved.
+        let reservation_cycles = self.cycles_account_manager.storage_reservation_cycles(
+            bytes,
+            resource_saturation,
+            subnet_size,
+        );
+
+        // Memory usage will increase by the snapshot size.
+        // Check that it doesn't bump the canister over the freezing threshold.
+        let threshold = self.cycles_account_manager.freeze_threshold_cycles(
+            canister.system_state.freeze_threshold,
+            canister.memory_allocation(),
+            canister.memory_usage() + bytes,
+            canister.message_memory_usage(),
+            canister.compute_allocation(),
+            subnet_size,
+            canister.system_state.reserved_balance(),
+        );
+
+        if canister.system_state.balance() < threshold + reservation_cycles {
+            return Err(CanisterManagerError::InsufficientCyclesInMemoryGrow {
+                bytes,
+                available: canister.system_state.balance(),
+                threshold,
+            });
+        }
+        // Verify that the subnet has enough memory.
+        round_limits
+            .subnet_available_memory
+            .check_available_memory(bytes, NumBytes::from(0), NumBytes::from(0))
+            .map_err(
+                |_| CanisterManagerError::SubnetMemoryCapacityOverSubscribed {
+                    requested: bytes,
+                    available: NumBytes::from(
+                        round_limits
+                            .subnet_available_memory
+                            .get_execution_memory()
+                            .max(0) as u64,
+                    ),
+                },
+            )?;
+        // Reserve needed cycles if the subnet is becoming saturated.
+        canister
+            .system_state
+            .reserve_cycles(reservation_cycles)
+            .map_err(|err| match err {
+                ReservationError::InsufficientCycles {
+                    requested,
+                    available,
+                } => CanisterManagerError::InsufficientCyclesInMemoryGrow {
+                    bytes,
+                    available,
+                    threshold: requested,
+                },
+                ReservationError::ReservedLimitExceed { requested, limit } => {
+                    CanisterManagerError::ReservedCyclesLimitExceededInMemoryGrow {
+                        bytes,
+                        requested,
+                        limit,
+                    }
+                }
+            })?;
+        // Actually deduct memory from the subnet. It's safe to unwrap
+        // here because we already checked the available memory above.
+        round_limits.subnet_available_memory
+            .try_decrement(bytes, NumBytes::from(0), NumBytes::from(0))
+            .expect("Error: Cannot fail to decrement SubnetAvailableMemory after checking for availability");
+        Ok(())
+    }
+
     /// Creates a new canister snapshot.
     ///
     /// A canister snapshot can only be initiated by the controllers.
@@ -1850,83 +1998,13 @@ impl CanisterManager {
         }
 
         let new_snapshot_size = canister.snapshot_memory_usage();
-
-        {
-            // Run the following checks on memory usage and return an error
-            // if any fails:
-            // 1. Check new usage will not freeze canister
-            // 2. Check subnet has available memory
-            // 3. Reserve cycles on canister
-            // 4. Actually deduct memory from subnet (asserting it won't fail)
-
-            // Calculate if any cycles will need to be reserved.
-            let reservation_cycles = self.cycles_account_manager.storage_reservation_cycles(
-                new_snapshot_size,
-                resource_saturation,
-                subnet_size,
-            );
-
-            // Memory usage will increase by the snapshot size.
-            // Check that it doesn't bump the canister over the freezing threshold.
-            let threshold = self.cycles_account_manager.freeze_threshold_cycles(
-                canister.system_state.freeze_threshold,
-                canister.memory_allocation(),
-                canister.memory_usage() + new_snapshot_size,
-                canister.message_memory_usage(),
-                canister.compute_allocation(),
-                subnet_size,
-                canister.system_state.reserved_balance(),
-            );
-
-            if canister.system_state.balance() < threshold + reservation_cycles {
-                return Err(CanisterManagerError::InsufficientCyclesInMemoryGrow {
-                    bytes: new_snapshot_size,
-                    available: canister.system_state.balance(),
-                    threshold,
-                });
-            }
-            // Verify that the subnet has enough memory.
-            round_limits
-                .subnet_available_memory
-                .check_available_memory(new_snapshot_size, NumBytes::from(0), NumBytes::from(0))
-                .map_err(
-                    |_| CanisterManagerError::SubnetMemoryCapacityOverSubscribed {
-                        requested: new_snapshot_size,
-                        available: NumBytes::from(
-                            round_limits
-                                .subnet_available_memory
-                                .get_execution_memory()
-                                .max(0) as u64,
-                        ),
-                    },
-                )?;
-            // Reserve needed cycles if the subnet is becoming saturated.
-            canister
-                .system_state
-                .reserve_cycles(reservation_cycles)
-                .map_err(|err| match err {
-                    ReservationError::InsufficientCycles {
-                        requested,
-                        available,
-                    } => CanisterManagerError::InsufficientCyclesInMemoryGrow {
-                        bytes: new_snapshot_size,
-                        available,
-                        threshold: requested,
-                    },
-                    ReservationError::ReservedLimitExceed { requested, limit } => {
-                        CanisterManagerError::ReservedCyclesLimitExceededInMemoryGrow {
-                            bytes: new_snapshot_size,
-                            requested,
-                            limit,
-                        }
-                    }
-                })?;
-            // Actually deduct memory from the subnet. It's safe to unwrap
-            // here because we already checked the available memory above.
-            round_limits.subnet_available_memory
-                        .try_decrement(new_snapshot_size, NumBytes::from(0), NumBytes::from(0))
-                        .expect("Error: Cannot fail to decrement SubnetAvailableMemory after checking for availability");
-        }
+        self.reserve_snapshot_memory(
+            subnet_size,
+            canister,
+            new_snapshot_size,
+            round_limits,
+            resource_saturation,
+        )?;
 
         // Create new snapshot.
         let new_snapshot = CanisterSnapshot::from(canister, state.time());
@@ -2061,8 +2139,26 @@ impl CanisterManager {
                     }
                 };
 
+                // The globals of an uploaded snapshot are not guaranteed to
+                // match the ones exported by its Wasm module.
+                if !globals_match(
+                    &new_execution_state.exported_globals,
+                    &execution_snapshot.exported_globals,
+                ) {
+                    return (
+                        instructions_used,
+                        Err(CanisterManagerError::InvalidSnapshotMetadata {
+                            message: format!(
+                                "The exported globals of snapshot {} do not match its Wasm module",
+                                snapshot_id
+                            ),
+                        }),
+                    );
+                }
+
                 new_execution_state.stable_memory = Memory::from(&execution_snapshot.stable_memory);
                 new_execution_state.wasm_memory = Memory::from(&execution_snapshot.wasm_memory);
+                new_execution_state.exported_globals = execution_snapshot.exported_globals.clone();
                 (instructions_used, Some(new_execution_state))
             }
             None => (NumInstructions::new(0), None),
@@ -2166,6 +2262,404 @@ impl CanisterManager {
         state.canister_snapshots.remove(delete_snapshot_id);
         Ok(())
     }
+
+    /// Returns the metadata of the specified canister snapshot.
+    ///
+    /// Reading the snapshot metadata can only be initiated by the controllers.
+    pub(crate) fn read_snapshot_metadata(
+        &self,
+        sender: PrincipalId,
+        canister: &CanisterState,
+        snapshot_id: SnapshotId,
+        state: &ReplicatedState,
+    ) -> Result<ReadCanisterSnapshotMetadataResponse, CanisterManagerError> {
+        // Check sender is a controller.
+        validate_controller(canister, &sender)?;
+        let snapshot = get_owned_snapshot(canister.canister_id(), snapshot_id, state)?;
+
+        Ok(ReadCanisterSnapshotMetadataResponse {
+            source: snapshot.source(),
+            taken_at_timestamp: snapshot.taken_at_timestamp().as_nanos_since_unix_epoch(),
+            wasm_module_size: snapshot
+                .canister_module()
+                .map_or(0, |module| module.len() as u64),
+            exported_globals: snapshot
+                .exported_globals()
+                .map(|globals| globals.iter().map(|global| global.into()).collect())
+                .unwrap_or_default(),
+            wasm_memory_size: snapshot.wasm_memory().map_or(0, memory_size_in_bytes),
+            stable_memory_size: snapshot.stable_memory().map_or(0, memory_size_in_bytes),
+            wasm_chunk_store: snapshot
+                .chunk_store()
+                .keys()
+                .map(|hash| ChunkHash {
+                    hash: hash.to_vec(),
+                })
+                .collect(),
+            canister_version: snapshot.canister_version(),
+            certified_data: snapshot.certified_data().clone(),
+        })
+    }
+
+    /// Returns a slice of the specified canister snapshot data.
+    ///
+    /// Reading the snapshot data can only be initiated by the controllers.
+    pub(crate) fn read_snapshot_data(
+        &self,
+        sender: PrincipalId,
+        canister: &CanisterState,
+        snapshot_id: SnapshotId,
+        kind: CanisterSnapshotDataKind,
+        state: &ReplicatedState,
+    ) -> Result<ReadCanisterSnapshotDataResponse, CanisterManagerError> {
+        // Check sender is a controller.
+        validate_controller(canister, &sender)?;
+        let snapshot = get_owned_snapshot(canister.canister_id(), snapshot_id, state)?;
+
+        let chunk = match kind {
+            CanisterSnapshotDataKind::WasmModule { offset, size } => {
+                let module = snapshot.canister_module().ok_or(
+                    CanisterManagerError::CanisterSnapshotExecutionStateNotFound { snapshot_id },
+                )?;
+                let range = validate_slice(offset, size, module.len() as u64)?;
+                module.as_slice()[range].to_vec()
+            }
+            CanisterSnapshotDataKind::MainMemory { offset, size } => {
+                let memory = snapshot.wasm_memory().ok_or(
+                    CanisterManagerError::CanisterSnapshotExecutionStateNotFound { snapshot_id },
+                )?;
+                read_page_memory(memory, offset, size)?
+            }
+            CanisterSnapshotDataKind::StableMemory { offset, size } => {
+                let memory = snapshot.stable_memory().ok_or(
+                    CanisterManagerError::CanisterSnapshotExecutionStateNotFound { snapshot_id },
+                )?;
+                read_page_memory(memory, offset, size)?
+            }
+            CanisterSnapshotDataKind::WasmChunk { hash } => {
+                let hash: WasmChunkHash =
+                    hash.try_into()
+                        .map_err(|_| CanisterManagerError::WasmChunkStoreError {
+                            message: "Chunk hash must be 32 bytes long".to_string(),
+                        })?;
+                snapshot
+                    .chunk_store()
+                    .get_chunk_data(&hash)
+                    .ok_or_else(|| CanisterManagerError::WasmChunkStoreError {
+                        message: format!("Chunk {} not found in the snapshot", hex::encode(hash)),
+                    })?
+                    .flat_map(|bytes| bytes.iter().copied())
+                    .collect()
+            }
+        };
+        Ok(ReadCanisterSnapshotDataResponse::new(chunk))
+    }
+
+    /// Creates a new, empty canister snapshot from the uploaded metadata.
+    /// The contents of the snapshot are filled in with subsequent calls to
+    /// `upload_snapshot_data`.
+    ///
+    /// Uploading a canister snapshot can only be initiated by the controllers.
+    /// In addition, if the `replace_snapshot` parameter is `Some`, the
+    /// identified snapshot is deleted before creating the new one.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn upload_snapshot_metadata(
+        &self,
+        subnet_size: usize,
+        sender: PrincipalId,
+        canister: &mut CanisterState,
+        args: UploadCanisterSnapshotMetadataArgs,
+        state: &mut ReplicatedState,
+        round_limits: &mut RoundLimits,
+        resource_saturation: &ResourceSaturation,
+    ) -> Result<UploadCanisterSnapshotMetadataResponse, CanisterManagerError> {
+        // Check sender is a controller.
+        validate_controller(canister, &sender)?;
+
+        let replace_snapshot = args.replace_snapshot();
+        if let Some(replace_snapshot) = replace_snapshot {
+            get_owned_snapshot(canister.canister_id(), replace_snapshot, state)?;
+        }
+
+        let wasm_memory_size = num_wasm_pages("wasm_memory_size", args.wasm_memory_size)?;
+        let stable_memory_size = num_wasm_pages("stable_memory_size", args.stable_memory_size)?;
+        if args.wasm_memory_size > MAX_WASM_MEMORY_IN_BYTES {
+            return Err(CanisterManagerError::InvalidSnapshotMetadata {
+                message: format!(
+                    "Wasm memory size {} exceeds the maximum of {} bytes",
+                    args.wasm_memory_size, MAX_WASM_MEMORY_IN_BYTES
+                ),
+            });
+        }
+        if args.stable_memory_size > MAX_STABLE_MEMORY_IN_BYTES {
+            return Err(CanisterManagerError::InvalidSnapshotMetadata {
+                message: format!(
+                    "Stable memory size {} exceeds the maximum of {} bytes",
+                    args.stable_memory_size, MAX_STABLE_MEMORY_IN_BYTES
+                ),
+            });
+        }
+        if args.wasm_module_size > MAX_WASM_MODULE_SIZE_BYTES.get() {
+            return Err(CanisterManagerError::InvalidSnapshotMetadata {
+                message: format!(
+                    "Wasm module size {} exceeds the maximum of {} bytes",
+                    args.wasm_module_size, MAX_WASM_MODULE_SIZE_BYTES
+                ),
+            });
+        }
+
+        if self.config.rate_limiting_of_heap_delta == FlagStatus::Enabled
+            && canister.scheduler_state.heap_delta_debit >= self.config.heap_delta_rate_limit
+        {
+            return Err(CanisterManagerError::CanisterHeapDeltaRateLimited {
+                canister_id: canister.canister_id(),
+                value: canister.scheduler_state.heap_delta_debit,
+                limit: self.config.heap_delta_rate_limit,
+            });
+        }
+
+        let exported_globals: Vec<Global> =
+            args.exported_globals.iter().map(Global::from).collect();
+        let new_snapshot_size = NumBytes::from(
+            args.wasm_module_size
+                + args.wasm_memory_size
+                + args.stable_memory_size
+                + (exported_globals.len() * std::mem::size_of::<Global>()) as u64
+                + args.certified_data.len() as u64,
+        );
+        self.reserve_snapshot_memory(
+            subnet_size,
+            canister,
+            new_snapshot_size,
+            round_limits,
+            resource_saturation,
+        )?;
+
+        let execution_snapshot = ExecutionStateSnapshot {
+            wasm_binary: CanisterModule::new(vec![0; args.wasm_module_size as usize]),
+            exported_globals,
+            stable_memory: PageMemory {
+                page_map: PageMap::new(Arc::clone(&self.fd_factory)),
+                size: stable_memory_size,
+            },
+            wasm_memory: PageMemory {
+                page_map: PageMap::new(Arc::clone(&self.fd_factory)),
+                size: wasm_memory_size,
+            },
+        };
+        let new_snapshot = CanisterSnapshot::new(
+            canister.canister_id(),
+            SnapshotSource::MetadataUpload,
+            state.time(),
+            canister.system_state.canister_version,
+            args.certified_data,
+            WasmChunkStore::new(Arc::clone(&self.fd_factory)),
+            Some(execution_snapshot),
+            new_snapshot_size,
+        );
+
+        // Delete old snapshot identified by `replace_snapshot` ID.
+        if let Some(replace_snapshot) = replace_snapshot {
+            // Already confirmed that `replace_snapshot` exists.
+            let is_removed = state.canister_snapshots.remove(replace_snapshot);
+            debug_assert!(is_removed.is_some());
+        }
+
+        let snapshot_id =
+            SnapshotId::from((canister.canister_id(), canister.new_local_snapshot_id()));
+        state
+            .canister_snapshots
+            .push(snapshot_id, Arc::new(new_snapshot));
+        Ok(UploadCanisterSnapshotMetadataResponse::new(&snapshot_id))
+    }
+
+    /// Writes a slice of data into a snapshot created by `upload_snapshot_metadata`.
+    ///
+    /// Returns the number of bytes written, which must be accounted as heap delta.
+    /// Uploading snapshot data can only be initiated by the controllers.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn upload_snapshot_data(
+        &self,
+        subnet_size: usize,
+        sender: PrincipalId,
+        canister: &mut CanisterState,
+        args: UploadCanisterSnapshotDataArgs,
+        state: &mut ReplicatedState,
+        round_limits: &mut RoundLimits,
+        resource_saturation: &ResourceSaturation,
+    ) -> Result<NumBytes, CanisterManagerError> {
+        // Check sender is a controller.
+        validate_controller(canister, &sender)?;
+
+        let snapshot_id = args.get_snapshot_id();
+        let snapshot = get_owned_snapshot(canister.canister_id(), snapshot_id, state)?;
+        if snapshot.source() != SnapshotSource::MetadataUpload {
+            return Err(CanisterManagerError::CanisterSnapshotImmutable { snapshot_id });
+        }
+
+        if self.config.rate_limiting_of_heap_delta == FlagStatus::Enabled
+            && canister.scheduler_state.heap_delta_debit >= self.config.heap_delta_rate_limit
+        {
+            return Err(CanisterManagerError::CanisterHeapDeltaRateLimited {
+                canister_id: canister.canister_id(),
+                value: canister.scheduler_state.heap_delta_debit,
+                limit: self.config.heap_delta_rate_limit,
+            });
+        }
+
+        // A new chunk in the chunk store increases the snapshot size. Uploading a
+        // chunk that is already present is a no-op and must not be charged again.
+        let is_new_chunk = args.kind == CanisterSnapshotDataOffset::WasmChunk
+            && snapshot
+                .chunk_store()
+                .get_chunk_data(&ic_crypto_sha2::Sha256::hash(&args.chunk))
+                .is_none();
+        if is_new_chunk {
+            snapshot
+                .chunk_store()
+                .can_insert_chunk(&args.chunk)
+                .map_err(|err| CanisterManagerError::WasmChunkStoreError { message: err })?;
+            self.reserve_snapshot_memory(
+                subnet_size,
+                canister,
+                wasm_chunk_store::chunk_size(),
+                round_limits,
+                resource_saturation,
+            )?;
+        }
+
+        // Already confirmed that the snapshot exists.
+        let snapshot = state.canister_snapshots.get_mut(snapshot_id).unwrap();
+        match args.kind {
+            CanisterSnapshotDataOffset::WasmModule { offset } => {
+                let execution_snapshot = snapshot.execution_snapshot_mut().ok_or(
+                    CanisterManagerError::CanisterSnapshotExecutionStateNotFound { snapshot_id },
+                )?;
+                let module_size = execution_snapshot.wasm_binary.len() as u64;
+                let range = validate_slice(offset, args.chunk.len() as u64, module_size)?;
+                let mut module = execution_snapshot.wasm_binary.as_slice().to_vec();
+                module[range].copy_from_slice(&args.chunk);
+                execution_snapshot.wasm_binary = CanisterModule::new(module);
+            }
+            CanisterSnapshotDataOffset::MainMemory { offset } => {
+                let execution_snapshot = snapshot.execution_snapshot_mut().ok_or(
+                    CanisterManagerError::CanisterSnapshotExecutionStateNotFound { snapshot_id },
+                )?;
+                write_page_memory(&mut execution_snapshot.wasm_memory, offset, &args.chunk)?;
+            }
+            CanisterSnapshotDataOffset::StableMemory { offset } => {
+                let execution_snapshot = snapshot.execution_snapshot_mut().ok_or(
+                    CanisterManagerError::CanisterSnapshotExecutionStateNotFound { snapshot_id },
+                )?;
+                write_page_memory(&mut execution_snapshot.stable_memory, offset, &args.chunk)?;
+            }
+            CanisterSnapshotDataOffset::WasmChunk => {
+                if !is_new_chunk {
+                    return Ok(NumBytes::from(0));
+                }
+                // Already checked above that the chunk can be inserted.
+                snapshot
+                    .chunk_store_mut()
+                    .insert_chunk(&args.chunk)
+                    .map_err(|err| CanisterManagerError::WasmChunkStoreError { message: err })?;
+                snapshot.add_size(wasm_chunk_store::chunk_size());
+            }
+        }
+
+        let heap_delta = NumBytes::from(args.chunk.len() as u64);
+        if self.config.rate_limiting_of_heap_delta == FlagStatus::Enabled {
+            canister.scheduler_state.heap_delta_debit += heap_delta;
+        }
+        Ok(heap_delta)
+    }
+}
+
+/// Returns the snapshot identified by `snapshot_id` if it exists and
+/// belongs to the canister identified by `canister_id`.
+fn get_owned_snapshot(
+    canister_id: CanisterId,
+    snapshot_id: SnapshotId,
+    state: &ReplicatedState,
+) -> Result<&Arc<CanisterSnapshot>, CanisterManagerError> {
+    match state.canister_snapshots.get(snapshot_id) {
+        None => Err(CanisterManagerError::CanisterSnapshotNotFound {
+            canister_id,
+            snapshot_id,
+        }),
+        Some(snapshot) if snapshot.canister_id() != canister_id => {
+            Err(CanisterManagerError::CanisterSnapshotInvalidOwnership {
+                canister_id,
+                snapshot_id,
+            })
+        }
+        Some(snapshot) => Ok(snapshot),
+    }
+}
+
+/// Checks that the slice `[offset, offset + size)` lies within `len` bytes.
+fn validate_slice(
+    offset: u64,
+    size: u64,
+    len: u64,
+) -> Result<std::ops::Range<usize>, CanisterManagerError> {
+    match offset.checked_add(size) {
+        Some(end) if end <= len => Ok(offset as usize..end as usize),
+        _ => Err(CanisterManagerError::InvalidSlice { offset, size }),
+    }
+}
+
+fn memory_size_in_bytes(memory: &PageMemory) -> u64 {
+    (memory.size.get() * WASM_PAGE_SIZE_IN_BYTES) as u64
+}
+
+fn read_page_memory(
+    memory: &PageMemory,
+    offset: u64,
+    size: u64,
+) -> Result<Vec<u8>, CanisterManagerError> {
+    let range = validate_slice(offset, size, memory_size_in_bytes(memory))?;
+    let mut chunk = vec![0; range.len()];
+    Buffer::new(memory.page_map.clone()).read(&mut chunk, range.start);
+    Ok(chunk)
+}
+
+fn write_page_memory(
+    memory: &mut PageMemory,
+    offset: u64,
+    chunk: &[u8],
+) -> Result<(), CanisterManagerError> {
+    let range = validate_slice(offset, chunk.len() as u64, memory_size_in_bytes(memory))?;
+    let mut buffer = Buffer::new(memory.page_map.clone());
+    buffer.write(chunk, range.start);
+    memory
+        .page_map
+        .update(&buffer.dirty_pages().collect::<Vec<_>>());
+    Ok(())
+}
+
+/// Converts a memory size in bytes into Wasm pages, failing if the size
+/// is not a multiple of the Wasm page size.
+fn num_wasm_pages(field: &str, bytes: u64) -> Result<NumWasmPages, CanisterManagerError> {
+    if bytes % WASM_PAGE_SIZE_IN_BYTES as u64 != 0 {
+        return Err(CanisterManagerError::InvalidSnapshotMetadata {
+            message: format!(
+                "{} {} is not a multiple of the Wasm page size {}",
+                field, bytes, WASM_PAGE_SIZE_IN_BYTES
+            ),
+        });
+    }
+    Ok(NumWasmPages::new(
+        (bytes / WASM_PAGE_SIZE_IN_BYTES as u64) as usize,
+    ))
+}
+
+/// Returns true if both lists contain globals of the same types.
+fn globals_match(expected: &[Global], actual: &[Global]) -> bool {
+    expected.len() == actual.len()
+        && expected
+            .iter()
+            .zip(actual.iter())
+            .all(|(expected, actual)| expected.type_name() == actual.type_name())
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -2262,6 +2756,19 @@ pub(crate) enum CanisterManagerError {
     LongExecutionAlreadyInProgress {
         canister_id: CanisterId,
     },
+    CanisterSnapshotExecutionStateNotFound {
+        snapshot_id: SnapshotId,
+    },
+    CanisterSnapshotImmutable {
+        snapshot_id: SnapshotId,
+    },
+    InvalidSnapshotMetadata {
+        message: String,
+    },
+    InvalidSlice {
+        offset: u64,
+        size: u64,
+    },
     MissingUpgradeOptionError {
         message: String,
     },
@@ -2305,6 +2812,10 @@ impl AsErrorHelp for CanisterManagerError {
             | CanisterManagerError::CanisterHeapDeltaRateLimited { .. }
             | CanisterManagerError::CanisterSnapshotInvalidOwnership { .. }
             | CanisterManagerError::LongExecutionAlreadyInProgress { .. }
+            | CanisterManagerError::CanisterSnapshotExecutionStateNotFound { .. }
+            | CanisterManagerError::CanisterSnapshotImmutable { .. }
+            | CanisterManagerError::InvalidSnapshotMetadata { .. }
+            | CanisterManagerError::InvalidSlice { .. }
             | CanisterManagerError::MissingUpgradeOptionError { .. }
             | CanisterManagerError::InvalidUpgradeOptionError { .. } => ErrorHelp::UserError {
                 suggestion: "".to_string(),
@@ -2585,6 +3096,38 @@ impl From<CanisterManagerError> for UserError {
                     )
                 )
             }
+            CanisterSnapshotExecutionStateNotFound { snapshot_id } => {
+                Self::new(
+                    ErrorCode::CanisterRejectedMessage,
+                    format!(
+                        "The snapshot {} does not contain an execution state.{additional_help}", snapshot_id,
+                    )
+                )
+            }
+            CanisterSnapshotImmutable { snapshot_id } => {
+                Self::new(
+                    ErrorCode::CanisterRejectedMessage,
+                    format!(
+                        "The snapshot {} was not created by a metadata upload and cannot be modified.{additional_help}", snapshot_id,
+                    )
+                )
+            }
+            InvalidSnapshotMetadata { message } => {
+                Self::new(
+                    ErrorCode::InvalidManagementPayload,
+                    format!(
+                        "Invalid snapshot metadata: {}{additional_help}", message
+                    )
+                )
+            }
+            InvalidSlice { offset, size } => {
+                Self::new(
+                    ErrorCode::InvalidManagementPayload,
+                    format!(
+                        "Invalid slice: offset {} and size {} are out of bounds.{additional_help}", offset, size,
+                    )
+                )
+            }
             MissingUpgradeOptionError { message } => {
                 Self::new(
                     ErrorCode::CanisterContractViolation,
@@ -2077,7 +2077,12 @@ fn get_canister_status_with_incorrect_controller() {
         let other_sender = user_test_id(1).get();
         let canister = state.canister_state_mut(&canister_id).unwrap();
         assert_eq!(
-            canister_manager.get_canister_status(other_sender, canister, SMALL_APP_SUBNET_MAX_SIZE),
+            canister_manager.get_canister_status(
+                other_sender,
+                canister,
+                NumBytes::new(0),
+                SMALL_APP_SUBNET_MAX_SIZE
+            ),
             Err(CanisterManagerError::CanisterInvalidController {
                 canister_id,
                 controllers_expected: btreeset! {sender},
@@ -2115,7 +2120,12 @@ fn get_canister_status_of_running_canister() {
 
         let canister = state.canister_state_mut(&canister_id).unwrap();
         let status = canister_manager
-            .get_canister_status(sender, canister, SMALL_APP_SUBNET_MAX_SIZE)
+            .get_canister_status(
+                sender,
+                canister,
+                NumBytes::new(0),
+                SMALL_APP_SUBNET_MAX_SIZE,
+            )
             .unwrap()
             .status();
         assert_eq!(status, CanisterStatusType::Running);
@@ -2150,7 +2160,12 @@ fn get_canister_status_of_self() {
 
         let canister = state.canister_state_mut(&canister_id).unwrap();
         let status = canister_manager
-            .get_canister_status(canister_id.get(), canister, SMALL_APP_SUBNET_MAX_SIZE)
+            .get_canister_status(
+                canister_id.get(),
+                canister,
+                NumBytes::new(0),
+                SMALL_APP_SUBNET_MAX_SIZE,
+            )
             .unwrap()
             .status();
         assert_eq!(status, CanisterStatusType::Running);
@@ -2167,7 +2182,12 @@ fn get_canister_status_of_stopped_canister() {
 
         let canister = state.canister_state_mut(&canister_id).unwrap();
         let status = canister_manager
-            .get_canister_status(sender, canister, SMALL_APP_SUBNET_MAX_SIZE)
+            .get_canister_status(
+                sender,
+                canister,
+                NumBytes::new(0),
+                SMALL_APP_SUBNET_MAX_SIZE,
+            )
             .unwrap()
             .status();
         assert_eq!(status, CanisterStatusType::Stopped);
@@ -2184,7 +2204,12 @@ fn get_canister_status_of_stopping_canister() {
 
         let canister = state.canister_state_mut(&canister_id).unwrap();
         let status = canister_manager
-            .get_canister_status(sender, canister, SMALL_APP_SUBNET_MAX_SIZE)
+            .get_canister_status(
+                sender,
+                canister,
+                NumBytes::new(0),
+                SMALL_APP_SUBNET_MAX_SIZE,
+            )
             .unwrap()
             .status();
         assert_eq!(status, CanisterStatusType::Stopping);
@@ -2579,7 +2604,7 @@ fn can_get_canister_balance() {
 
         let canister = state.canister_state_mut(&canister_id).unwrap();
         assert_matches!(
-            canister_manager.get_canister_status( sender, canister, SMALL_APP_SUBNET_MAX_SIZE),
+            canister_manager.get_canister_status(sender, canister, NumBytes::new(0), SMALL_APP_SUBNET_MAX_SIZE),
             Ok(res) if res.cycles() == cycles.get()
         );
     });
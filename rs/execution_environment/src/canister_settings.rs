@@ -2,12 +2,13 @@ use ic_base_types::{NumBytes, NumSeconds};
 use ic_cycles_account_manager::{CyclesAccountManager, ResourceSaturation};
 use ic_error_types::{ErrorCode, UserError};
 use ic_interfaces::execution_environment::SubnetAvailableMemory;
-use ic_management_canister_types::{CanisterSettingsArgs, LogVisibility};
+use ic_management_canister_types::{CanisterSettingsArgs, EnvironmentVariable, LogVisibility};
 use ic_types::{
     ComputeAllocation, Cycles, InvalidComputeAllocationError, InvalidMemoryAllocationError,
     MemoryAllocation, PrincipalId,
 };
 use num_traits::cast::ToPrimitive;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 
 use crate::canister_manager::CanisterManagerError;
@@ -15,6 +16,15 @@ use crate::canister_manager::CanisterManagerError;
 /// These limit comes from the spec and is not expected to change,
 /// which is why it is not part of the replica config.
 const MAX_WASM_MEMORY_LIMIT: u64 = 1 << 48;
+/// The maximum number of environment variables of a canister.
+const MAX_ENVIRONMENT_VARIABLES: usize = 20;
+/// The maximum length in bytes of the name of an environment variable.
+const MAX_ENVIRONMENT_VARIABLE_NAME_LENGTH: usize = 128;
+/// The maximum length in bytes of the value of an environment variable.
+const MAX_ENVIRONMENT_VARIABLE_VALUE_LENGTH: usize = 128;
+/// The maximum number of principals that can be allowed to view the logs of a
+/// canister in addition to its controllers.
+const MAX_ALLOWED_LOG_VIEWERS: usize = 10;
 /// Struct used for decoding CanisterSettingsArgs
 #[derive(Default)]
 pub(crate) struct CanisterSettings {
@@ -26,6 +36,8 @@ pub(crate) struct CanisterSettings {
     pub(crate) reserved_cycles_limit: Option<Cycles>,
     pub(crate) log_visibility: Option<LogVisibility>,
     pub(crate) wasm_memory_limit: Option<NumBytes>,
+    pub(crate) environment_variables: Option<BTreeMap<String, String>>,
+    pub(crate) wasm_backtraces: Option<bool>,
 }
 
 impl CanisterSettings {
@@ -38,6 +50,8 @@ impl CanisterSettings {
         reserved_cycles_limit: Option<Cycles>,
         log_visibility: Option<LogVisibility>,
         wasm_memory_limit: Option<NumBytes>,
+        environment_variables: Option<BTreeMap<String, String>>,
+        wasm_backtraces: Option<bool>,
     ) -> Self {
         Self {
             controller,
@@ -48,6 +62,8 @@ impl CanisterSettings {
             reserved_cycles_limit,
             log_visibility,
             wasm_memory_limit,
+            environment_variables,
+            wasm_backtraces,
         }
     }
 
@@ -75,13 +91,21 @@ impl CanisterSettings {
         self.reserved_cycles_limit
     }
 
-    pub fn log_visibility(&self) -> Option<LogVisibility> {
-        self.log_visibility
+    pub fn log_visibility(&self) -> Option<&LogVisibility> {
+        self.log_visibility.as_ref()
     }
 
     pub fn wasm_memory_limit(&self) -> Option<NumBytes> {
         self.wasm_memory_limit
     }
+
+    pub fn environment_variables(&self) -> Option<&BTreeMap<String, String>> {
+        self.environment_variables.as_ref()
+    }
+
+    pub fn wasm_backtraces(&self) -> Option<bool> {
+        self.wasm_backtraces
+    }
 }
 
 impl TryFrom<CanisterSettingsArgs> for CanisterSettings {
@@ -135,6 +159,19 @@ impl TryFrom<CanisterSettingsArgs> for CanisterSettings {
             None => None,
         };
 
+        if let Some(LogVisibility::AllowedViewers(principals)) = &input.log_visibility {
+            if principals.len() > MAX_ALLOWED_LOG_VIEWERS {
+                return Err(UpdateSettingsError::TooManyAllowedLogViewers {
+                    provided: principals.len(),
+                });
+            }
+        }
+
+        let environment_variables = match input.environment_variables {
+            Some(variables) => Some(validate_environment_variables(variables)?),
+            None => None,
+        };
+
         Ok(CanisterSettings::new(
             controller,
             input
@@ -146,6 +183,8 @@ impl TryFrom<CanisterSettingsArgs> for CanisterSettings {
             reserved_cycles_limit,
             input.log_visibility,
             wasm_memory_limit,
+            environment_variables,
+            input.wasm_backtraces,
         ))
     }
 }
@@ -170,6 +209,8 @@ pub(crate) struct CanisterSettingsBuilder {
     reserved_cycles_limit: Option<Cycles>,
     log_visibility: Option<LogVisibility>,
     wasm_memory_limit: Option<NumBytes>,
+    environment_variables: Option<BTreeMap<String, String>>,
+    wasm_backtraces: Option<bool>,
 }
 
 #[allow(dead_code)]
@@ -184,6 +225,8 @@ impl CanisterSettingsBuilder {
             reserved_cycles_limit: None,
             log_visibility: None,
             wasm_memory_limit: None,
+            environment_variables: None,
+            wasm_backtraces: None,
         }
     }
 
@@ -197,6 +240,8 @@ impl CanisterSettingsBuilder {
             reserved_cycles_limit: self.reserved_cycles_limit,
             log_visibility: self.log_visibility,
             wasm_memory_limit: self.wasm_memory_limit,
+            environment_variables: self.environment_variables,
+            wasm_backtraces: self.wasm_backtraces,
         }
     }
 
@@ -255,6 +300,23 @@ impl CanisterSettingsBuilder {
             ..self
         }
     }
+
+    pub fn with_environment_variables(
+        self,
+        environment_variables: BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            environment_variables: Some(environment_variables),
+            ..self
+        }
+    }
+
+    pub fn with_wasm_backtraces(self, wasm_backtraces: bool) -> Self {
+        Self {
+            wasm_backtraces: Some(wasm_backtraces),
+            ..self
+        }
+    }
 }
 
 pub enum UpdateSettingsError {
@@ -263,6 +325,11 @@ pub enum UpdateSettingsError {
     FreezingThresholdOutOfRange { provided: candid::Nat },
     ReservedCyclesLimitOutOfRange { provided: candid::Nat },
     WasmMemoryLimitOutOfRange { provided: candid::Nat },
+    TooManyEnvironmentVariables { provided: usize },
+    EnvironmentVariableNameTooLong { name: String },
+    EnvironmentVariableValueTooLong { name: String },
+    DuplicateEnvironmentVariable { name: String },
+    TooManyAllowedLogViewers { provided: usize },
 }
 
 impl From<UpdateSettingsError> for UserError {
@@ -305,10 +372,70 @@ impl From<UpdateSettingsError> for UserError {
                     provided
                 ),
             ),
+            UpdateSettingsError::TooManyEnvironmentVariables { provided } => UserError::new(
+                ErrorCode::CanisterContractViolation,
+                format!(
+                    "At most {} environment variables can be set, got {}",
+                    MAX_ENVIRONMENT_VARIABLES, provided
+                ),
+            ),
+            UpdateSettingsError::EnvironmentVariableNameTooLong { name } => UserError::new(
+                ErrorCode::CanisterContractViolation,
+                format!(
+                    "Environment variable names can be at most {} bytes long, got {} bytes for '{}'",
+                    MAX_ENVIRONMENT_VARIABLE_NAME_LENGTH,
+                    name.len(),
+                    name
+                ),
+            ),
+            UpdateSettingsError::EnvironmentVariableValueTooLong { name } => UserError::new(
+                ErrorCode::CanisterContractViolation,
+                format!(
+                    "Environment variable values can be at most {} bytes long, got a longer value for '{}'",
+                    MAX_ENVIRONMENT_VARIABLE_VALUE_LENGTH, name
+                ),
+            ),
+            UpdateSettingsError::DuplicateEnvironmentVariable { name } => UserError::new(
+                ErrorCode::CanisterContractViolation,
+                format!("Environment variable '{}' is set more than once", name),
+            ),
+            UpdateSettingsError::TooManyAllowedLogViewers { provided } => UserError::new(
+                ErrorCode::CanisterContractViolation,
+                format!(
+                    "At most {} principals can be allowed to view the logs, got {}",
+                    MAX_ALLOWED_LOG_VIEWERS, provided
+                ),
+            ),
         }
     }
 }
 
+/// Checks the limits on the environment variables and converts them into a map
+/// from name to value.
+fn validate_environment_variables(
+    variables: Vec<EnvironmentVariable>,
+) -> Result<BTreeMap<String, String>, UpdateSettingsError> {
+    if variables.len() > MAX_ENVIRONMENT_VARIABLES {
+        return Err(UpdateSettingsError::TooManyEnvironmentVariables {
+            provided: variables.len(),
+        });
+    }
+    let mut result = BTreeMap::new();
+    for EnvironmentVariable { name, value } in variables {
+        if name.len() > MAX_ENVIRONMENT_VARIABLE_NAME_LENGTH {
+            return Err(UpdateSettingsError::EnvironmentVariableNameTooLong { name });
+        }
+        if value.len() > MAX_ENVIRONMENT_VARIABLE_VALUE_LENGTH {
+            return Err(UpdateSettingsError::EnvironmentVariableValueTooLong { name });
+        }
+        if result.contains_key(&name) {
+            return Err(UpdateSettingsError::DuplicateEnvironmentVariable { name });
+        }
+        result.insert(name, value);
+    }
+    Ok(result)
+}
+
 impl From<InvalidComputeAllocationError> for UpdateSettingsError {
     fn from(err: InvalidComputeAllocationError) -> Self {
         Self::ComputeAllocation(err)
@@ -331,6 +458,8 @@ pub(crate) struct ValidatedCanisterSettings {
     reservation_cycles: Cycles,
     log_visibility: Option<LogVisibility>,
     wasm_memory_limit: Option<NumBytes>,
+    environment_variables: Option<BTreeMap<String, String>>,
+    wasm_backtraces: Option<bool>,
 }
 
 impl ValidatedCanisterSettings {
@@ -362,13 +491,21 @@ impl ValidatedCanisterSettings {
         self.reservation_cycles
     }
 
-    pub fn log_visibility(&self) -> Option<LogVisibility> {
-        self.log_visibility
+    pub fn log_visibility(&self) -> Option<&LogVisibility> {
+        self.log_visibility.as_ref()
     }
 
     pub fn wasm_memory_limit(&self) -> Option<NumBytes> {
         self.wasm_memory_limit
     }
+
+    pub fn environment_variables(&self) -> Option<&BTreeMap<String, String>> {
+        self.environment_variables.as_ref()
+    }
+
+    pub fn wasm_backtraces(&self) -> Option<bool> {
+        self.wasm_backtraces
+    }
 }
 
 /// Validates the new canisters settings:
@@ -565,7 +702,9 @@ pub(crate) fn validate_canister_settings(
         freezing_threshold: settings.freezing_threshold(),
         reserved_cycles_limit: settings.reserved_cycles_limit(),
         reservation_cycles,
-        log_visibility: settings.log_visibility(),
+        log_visibility: settings.log_visibility().cloned(),
         wasm_memory_limit: settings.wasm_memory_limit(),
+        environment_variables: settings.environment_variables().cloned(),
+        wasm_backtraces: settings.wasm_backtraces(),
     })
 }
@@ -487,6 +487,8 @@ impl InstallCodeHelper {
                 reserved_cycles_limit: None,
                 log_visibility: None,
                 wasm_memory_limit: None,
+                environment_variables: None,
+                wasm_backtraces: None,
             },
             self.canister.memory_usage(),
             self.canister.message_memory_usage(),
@@ -39,9 +39,11 @@ use ic_management_canister_types::{
     EcdsaKeyId, EmptyBlob, InstallChunkedCodeArgs, InstallCodeArgsV2, ListCanisterSnapshotArgs,
     LoadCanisterSnapshotArgs, MasterPublicKeyId, Method as Ic00Method, NodeMetricsHistoryArgs,
     Payload as Ic00Payload, ProvisionalCreateCanisterWithCyclesArgs, ProvisionalTopUpCanisterArgs,
-    SchnorrKeyId, SchnorrPublicKeyArgs, SchnorrPublicKeyResponse, SetupInitialDKGArgs,
-    SignWithECDSAArgs, SignWithSchnorrArgs, StoredChunksArgs, TakeCanisterSnapshotArgs,
-    UninstallCodeArgs, UpdateSettingsArgs, UploadChunkArgs, IC_00,
+    ReadCanisterSnapshotDataArgs, ReadCanisterSnapshotMetadataArgs, SchnorrKeyId,
+    SchnorrPublicKeyArgs, SchnorrPublicKeyResponse, SetupInitialDKGArgs, SignWithECDSAArgs,
+    SignWithSchnorrArgs, StoredChunksArgs, TakeCanisterSnapshotArgs, UninstallCodeArgs,
+    UpdateSettingsArgs, UploadCanisterSnapshotDataArgs, UploadCanisterSnapshotMetadataArgs,
+    UploadChunkArgs, IC_00,
 };
 use ic_metrics::MetricsRegistry;
 use ic_registry_provisional_whitelist::ProvisionalWhitelist;
@@ -1530,6 +1532,66 @@ impl ExecutionEnvironment {
                 }
             },
 
+            Ok(Ic00Method::ReadCanisterSnapshotMetadata) => {
+                let res = self
+                    .check_canister_snapshots_enabled()
+                    .and_then(|()| ReadCanisterSnapshotMetadataArgs::decode(payload))
+                    .and_then(|args| self.read_snapshot_metadata(*msg.sender(), &state, args));
+                ExecuteSubnetMessageResult::Finished {
+                    response: res,
+                    refund: msg.take_cycles(),
+                }
+            }
+
+            Ok(Ic00Method::ReadCanisterSnapshotData) => {
+                let res = self
+                    .check_canister_snapshots_enabled()
+                    .and_then(|()| ReadCanisterSnapshotDataArgs::decode(payload))
+                    .and_then(|args| self.read_snapshot_data(*msg.sender(), &state, args));
+                ExecuteSubnetMessageResult::Finished {
+                    response: res,
+                    refund: msg.take_cycles(),
+                }
+            }
+
+            Ok(Ic00Method::UploadCanisterSnapshotMetadata) => {
+                let res = self
+                    .check_canister_snapshots_enabled()
+                    .and_then(|()| UploadCanisterSnapshotMetadataArgs::decode(payload))
+                    .and_then(|args| {
+                        self.upload_snapshot_metadata(
+                            *msg.sender(),
+                            &mut state,
+                            args,
+                            registry_settings.subnet_size,
+                            round_limits,
+                        )
+                    });
+                ExecuteSubnetMessageResult::Finished {
+                    response: res,
+                    refund: msg.take_cycles(),
+                }
+            }
+
+            Ok(Ic00Method::UploadCanisterSnapshotData) => {
+                let res = self
+                    .check_canister_snapshots_enabled()
+                    .and_then(|()| UploadCanisterSnapshotDataArgs::decode(payload))
+                    .and_then(|args| {
+                        self.upload_snapshot_data(
+                            *msg.sender(),
+                            &mut state,
+                            args,
+                            registry_settings.subnet_size,
+                            round_limits,
+                        )
+                    });
+                ExecuteSubnetMessageResult::Finished {
+                    response: res,
+                    refund: msg.take_cycles(),
+                }
+            }
+
             Err(ParseError::VariantNotFound) => {
                 let res = Err(UserError::new(
                     ErrorCode::CanisterMethodNotFound,
@@ -1549,6 +1611,17 @@ impl ExecutionEnvironment {
         (state, Some(NumInstructions::from(0)))
     }
 
+    /// Returns an error if canister snapshots are not enabled on this subnet.
+    fn check_canister_snapshots_enabled(&self) -> Result<(), UserError> {
+        match self.config.canister_snapshots {
+            FlagStatus::Enabled => Ok(()),
+            FlagStatus::Disabled => Err(UserError::new(
+                ErrorCode::CanisterContractViolation,
+                "This API is not enabled on this subnet".to_string(),
+            )),
+        }
+    }
+
     // Rejects message because API is not implemented.
     fn reject_due_to_api_not_implemented(msg: &mut CanisterCall) -> ExecuteSubnetMessageResult {
         ExecuteSubnetMessageResult::Finished {
@@ -1937,10 +2010,16 @@ impl ExecutionEnvironment {
         state: &mut ReplicatedState,
         subnet_size: usize,
     ) -> Result<Vec<u8>, UserError> {
+        let snapshots_memory_usage: NumBytes = state
+            .canister_snapshots
+            .list_snapshots(canister_id)
+            .iter()
+            .map(|(_, snapshot)| snapshot.size())
+            .sum();
         let canister = get_canister_mut(canister_id, state)?;
 
         self.canister_manager
-            .get_canister_status(sender, canister, subnet_size)
+            .get_canister_status(sender, canister, snapshots_memory_usage, subnet_size)
             .map(|status| status.encode())
             .map_err(|err| err.into())
     }
@@ -2221,6 +2300,120 @@ impl ExecutionEnvironment {
         result
     }
 
+    /// Returns the metadata of the specified canister snapshot.
+    fn read_snapshot_metadata(
+        &self,
+        sender: PrincipalId,
+        state: &ReplicatedState,
+        args: ReadCanisterSnapshotMetadataArgs,
+    ) -> Result<Vec<u8>, UserError> {
+        let canister = get_canister(args.get_canister_id(), state)?;
+        self.canister_manager
+            .read_snapshot_metadata(sender, canister, args.get_snapshot_id(), state)
+            .map(|response| response.encode())
+            .map_err(UserError::from)
+    }
+
+    /// Returns a slice of the data of the specified canister snapshot.
+    fn read_snapshot_data(
+        &self,
+        sender: PrincipalId,
+        state: &ReplicatedState,
+        args: ReadCanisterSnapshotDataArgs,
+    ) -> Result<Vec<u8>, UserError> {
+        let canister = get_canister(args.get_canister_id(), state)?;
+        let snapshot_id = args.get_snapshot_id();
+        self.canister_manager
+            .read_snapshot_data(sender, canister, snapshot_id, args.kind, state)
+            .map(|response| response.encode())
+            .map_err(UserError::from)
+    }
+
+    /// Creates a new canister snapshot from the uploaded metadata.
+    fn upload_snapshot_metadata(
+        &self,
+        sender: PrincipalId,
+        state: &mut ReplicatedState,
+        args: UploadCanisterSnapshotMetadataArgs,
+        subnet_size: usize,
+        round_limits: &mut RoundLimits,
+    ) -> Result<Vec<u8>, UserError> {
+        let canister_id = args.get_canister_id();
+        // Take canister out.
+        let mut canister = match state.take_canister_state(&canister_id) {
+            None => {
+                return Err(UserError::new(
+                    ErrorCode::CanisterNotFound,
+                    format!("Canister {} not found.", &canister_id),
+                ))
+            }
+            Some(canister) => canister,
+        };
+
+        let resource_saturation =
+            self.subnet_memory_saturation(&round_limits.subnet_available_memory);
+        let result = self
+            .canister_manager
+            .upload_snapshot_metadata(
+                subnet_size,
+                sender,
+                &mut canister,
+                args,
+                state,
+                round_limits,
+                &resource_saturation,
+            )
+            .map(|response| response.encode())
+            .map_err(|err| err.into());
+        // Put canister back.
+        state.put_canister_state(canister);
+        result
+    }
+
+    /// Writes a slice of data into a canister snapshot created by a metadata upload.
+    fn upload_snapshot_data(
+        &self,
+        sender: PrincipalId,
+        state: &mut ReplicatedState,
+        args: UploadCanisterSnapshotDataArgs,
+        subnet_size: usize,
+        round_limits: &mut RoundLimits,
+    ) -> Result<Vec<u8>, UserError> {
+        let canister_id = args.get_canister_id();
+        // Take canister out.
+        let mut canister = match state.take_canister_state(&canister_id) {
+            None => {
+                return Err(UserError::new(
+                    ErrorCode::CanisterNotFound,
+                    format!("Canister {} not found.", &canister_id),
+                ))
+            }
+            Some(canister) => canister,
+        };
+
+        let resource_saturation =
+            self.subnet_memory_saturation(&round_limits.subnet_available_memory);
+        let result = self
+            .canister_manager
+            .upload_snapshot_data(
+                subnet_size,
+                sender,
+                &mut canister,
+                args,
+                state,
+                round_limits,
+                &resource_saturation,
+            )
+            .map(|heap_delta| {
+                state.metadata.heap_delta_estimate += heap_delta;
+                EmptyBlob.encode()
+            })
+            .map_err(|err| err.into());
+        // Put canister back.
+        state.put_canister_state(canister);
+        result
+    }
+
     fn node_metrics_history(
         &self,
         state: &ReplicatedState,
@@ -2746,8 +2939,9 @@ impl ExecutionEnvironment {
         // If the request isn't from the NNS, then we need to charge for it.
         let source_subnet = topology.routing_table.route(request.sender.get());
         if source_subnet != Some(state.metadata.network_topology.nns_subnet_id) {
-            // TODO(EXC-1629): implement schnorr fee.
-            let signature_fee = self.cycles_account_manager.ecdsa_signature_fee(subnet_size);
+            let signature_fee = self
+                .cycles_account_manager
+                .schnorr_signature_fee(subnet_size);
             if request.payment < signature_fee {
                 return Err(UserError::new(
                     ErrorCode::CanisterRejectedMessage,
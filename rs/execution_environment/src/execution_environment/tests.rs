@@ -6,10 +6,10 @@ use ic_error_types::{ErrorCode, RejectCode, UserError};
 use ic_management_canister_types::{
     self as ic00, BitcoinGetUtxosArgs, BitcoinNetwork, BoundedHttpHeaders, CanisterChange,
     CanisterHttpRequestArgs, CanisterIdRecord, CanisterStatusResultV2, CanisterStatusType,
-    DerivationPath, EcdsaCurve, EcdsaKeyId, EmptyBlob, FetchCanisterLogsRequest, HttpMethod,
-    LogVisibility, MasterPublicKeyId, Method, Payload as Ic00Payload,
-    ProvisionalCreateCanisterWithCyclesArgs, ProvisionalTopUpCanisterArgs, SchnorrAlgorithm,
-    SchnorrKeyId, TransformContext, TransformFunc, IC_00,
+    DerivationPath, EcdsaCurve, EcdsaKeyId, EmptyBlob, EnvironmentVariable,
+    FetchCanisterLogsRequest, HttpMethod, LogVisibility, MasterPublicKeyId, Method,
+    Payload as Ic00Payload, ProvisionalCreateCanisterWithCyclesArgs, ProvisionalTopUpCanisterArgs,
+    SchnorrAlgorithm, SchnorrKeyId, TransformContext, TransformFunc, IC_00,
 };
 use ic_registry_routing_table::{canister_id_into_u64, CanisterIdRange, RoutingTable};
 use ic_registry_subnet_type::SubnetType;
@@ -792,6 +792,93 @@ fn get_canister_status_from_another_canister_when_memory_low() {
     );
 }
 
+#[test]
+fn canister_status_reports_memory_metrics() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister = test.universal_canister().unwrap();
+    test.ingress(canister, "update", wasm().stable64_grow(1).reply().build())
+        .unwrap();
+    let csr = CanisterStatusResultV2::decode(&get_reply(test.canister_status(canister))).unwrap();
+    let memory_metrics = csr.memory_metrics();
+    let execution_state = test.execution_state(canister);
+    assert_eq!(
+        memory_metrics.wasm_memory_size(),
+        execution_state.wasm_memory_usage()
+    );
+    assert_eq!(
+        memory_metrics.stable_memory_size(),
+        NumBytes::from(WASM_PAGE_SIZE_IN_BYTES as u64)
+    );
+    assert_eq!(
+        memory_metrics.global_memory_size(),
+        execution_state.globals_memory_usage()
+    );
+    assert_eq!(
+        memory_metrics.wasm_binary_size(),
+        NumBytes::from(UNIVERSAL_CANISTER_WASM.len() as u64)
+    );
+    assert_eq!(
+        memory_metrics.custom_sections_size(),
+        execution_state.metadata.memory_usage()
+    );
+    assert_eq!(
+        memory_metrics.canister_history_size(),
+        test.canister_state(canister)
+            .canister_history_memory_usage()
+    );
+    assert_eq!(memory_metrics.snapshots_size(), NumBytes::from(0));
+    // The breakdown adds up to the total memory usage of the canister.
+    assert_eq!(
+        csr.memory_size(),
+        memory_metrics.wasm_memory_size()
+            + memory_metrics.stable_memory_size()
+            + memory_metrics.global_memory_size()
+            + memory_metrics.wasm_binary_size()
+            + memory_metrics.custom_sections_size()
+            + memory_metrics.canister_history_size()
+            + memory_metrics.wasm_chunk_store_size()
+    );
+}
+
+#[test]
+fn canister_status_reports_cycles_burned_by_use_case() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister = test.universal_canister().unwrap();
+    test.ingress(canister, "update", wasm().reply().build())
+        .unwrap();
+    let csr = CanisterStatusResultV2::decode(&get_reply(test.canister_status(canister))).unwrap();
+    let consumed_cycles = test
+        .canister_state(canister)
+        .system_state
+        .canister_metrics
+        .get_consumed_cycles_since_replica_started_by_use_cases()
+        .clone();
+    assert!(csr.cycles_burned().instructions() > 0);
+    assert_eq!(
+        csr.cycles_burned().instructions(),
+        consumed_cycles[&CyclesUseCase::Instructions].get()
+    );
+    assert_eq!(csr.cycles_burned().http_outcalls(), 0);
+    assert_eq!(csr.cycles_burned().ecdsa_outcalls(), 0);
+}
+
+#[test]
+fn canister_status_reports_burned_cycles() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister = test.universal_canister().unwrap();
+    let amount = Cycles::new(1_000_000);
+    test.ingress(
+        canister,
+        "update",
+        wasm().cycles_burn128(amount).reply().build(),
+    )
+    .unwrap();
+    let csr = CanisterStatusResultV2::decode(&get_reply(test.canister_status(canister))).unwrap();
+    assert_eq!(csr.cycles_burned().burned_cycles(), amount.get());
+    assert_eq!(csr.cycles_burned().deleted_canisters(), 0);
+    assert_eq!(csr.cycles_burned().non_consumed(), 0);
+}
+
 #[test]
 fn get_stopped_canister_status_from_another_canister() {
     let mut test = ExecutionTestBuilder::new().build();
@@ -3471,6 +3558,103 @@ fn test_canister_settings_log_visibility_set_to_public() {
     );
 }
 
+#[test]
+fn test_canister_settings_environment_variables() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister_id = test.create_canister(Cycles::new(1_000_000_000));
+    test.set_environment_variables(
+        canister_id,
+        vec![
+            EnvironmentVariable::new("MODE", "staging"),
+            EnvironmentVariable::new("ENDPOINT", "https://staging.example.com"),
+        ],
+    )
+    .unwrap();
+    let result = test.canister_status(canister_id);
+    let canister_status = CanisterStatusResultV2::decode(&get_reply(result)).unwrap();
+    // The variables are returned sorted by name.
+    assert_eq!(
+        canister_status.settings().environment_variables(),
+        &[
+            EnvironmentVariable::new("ENDPOINT", "https://staging.example.com"),
+            EnvironmentVariable::new("MODE", "staging"),
+        ]
+    );
+
+    // Updating the settings replaces all variables.
+    test.set_environment_variables(canister_id, vec![]).unwrap();
+    let result = test.canister_status(canister_id);
+    let canister_status = CanisterStatusResultV2::decode(&get_reply(result)).unwrap();
+    assert_eq!(canister_status.settings().environment_variables(), &[]);
+}
+
+#[test]
+fn test_canister_settings_environment_variables_are_validated() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister_id = test.create_canister(Cycles::new(1_000_000_000));
+    let too_many = (0..21)
+        .map(|i| EnvironmentVariable::new(format!("VAR_{}", i), "value"))
+        .collect();
+    let invalid = [
+        too_many,
+        vec![EnvironmentVariable::new("A".repeat(129), "value")],
+        vec![EnvironmentVariable::new("NAME", "A".repeat(129))],
+        vec![
+            EnvironmentVariable::new("NAME", "first"),
+            EnvironmentVariable::new("NAME", "second"),
+        ],
+    ];
+    for environment_variables in invalid {
+        let err = test
+            .set_environment_variables(canister_id, environment_variables)
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::CanisterContractViolation);
+    }
+    assert!(test
+        .canister_state(canister_id)
+        .system_state
+        .environment_variables
+        .is_empty());
+}
+
+#[test]
+fn canister_reads_environment_variables() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let wat = r#"
+        (module
+            (import "ic0" "env_var_value_size"
+                (func $env_var_value_size (param i32 i32) (result i32)))
+            (import "ic0" "env_var_value_copy"
+                (func $env_var_value_copy (param i32 i32 i32 i32 i32)))
+            (import "ic0" "msg_reply_data_append"
+                (func $msg_reply_data_append (param i32 i32)))
+            (import "ic0" "msg_reply" (func $msg_reply))
+            (func (export "canister_update get_mode")
+                (local $size i32)
+                (local.set $size (call $env_var_value_size (i32.const 0) (i32.const 4)))
+                (call $env_var_value_copy
+                    (i32.const 0) (i32.const 4) (i32.const 100) (i32.const 0) (local.get $size))
+                (call $msg_reply_data_append (i32.const 100) (local.get $size))
+                (call $msg_reply)
+            )
+            (memory 1)
+            (data (i32.const 0) "MODE")
+        )"#;
+    let canister_id = test.canister_from_wat(wat).unwrap();
+
+    // The variable is not set yet.
+    let err = test.ingress(canister_id, "get_mode", vec![]).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::CanisterContractViolation);
+
+    test.set_environment_variables(
+        canister_id,
+        vec![EnvironmentVariable::new("MODE", "production")],
+    )
+    .unwrap();
+    let result = test.ingress(canister_id, "get_mode", vec![]).unwrap();
+    assert_eq!(result, WasmResult::Reply(b"production".to_vec()));
+}
+
 #[test]
 fn test_fetch_canister_logs_should_accept_ingress_message_disabled() {
     // Arrange.
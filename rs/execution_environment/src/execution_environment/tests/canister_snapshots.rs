@@ -3,12 +3,15 @@ use candid::{Decode, Encode};
 use ic_base_types::NumBytes;
 use ic_config::flag_status::FlagStatus;
 use ic_cycles_account_manager::ResourceSaturation;
-use ic_error_types::{ErrorCode, RejectCode};
+use ic_error_types::{ErrorCode, RejectCode, UserError};
 use ic_management_canister_types::{
-    self as ic00, CanisterChange, CanisterChangeDetails, CanisterSnapshotResponse,
-    ClearChunkStoreArgs, DeleteCanisterSnapshotArgs, ListCanisterSnapshotArgs,
-    LoadCanisterSnapshotArgs, Method, Payload as Ic00Payload, TakeCanisterSnapshotArgs,
-    UploadChunkArgs,
+    self as ic00, CanisterChange, CanisterChangeDetails, CanisterSnapshotDataKind,
+    CanisterSnapshotDataOffset, CanisterSnapshotResponse, ClearChunkStoreArgs,
+    DeleteCanisterSnapshotArgs, ListCanisterSnapshotArgs, LoadCanisterSnapshotArgs, Method,
+    Payload as Ic00Payload, ReadCanisterSnapshotDataArgs, ReadCanisterSnapshotDataResponse,
+    ReadCanisterSnapshotMetadataArgs, ReadCanisterSnapshotMetadataResponse, SnapshotSource,
+    TakeCanisterSnapshotArgs, UploadCanisterSnapshotDataArgs, UploadCanisterSnapshotMetadataArgs,
+    UploadCanisterSnapshotMetadataResponse, UploadChunkArgs,
 };
 use ic_replicated_state::{
     canister_snapshots::SnapshotOperation, canister_state::system_state::CyclesUseCase,
@@ -1267,3 +1270,376 @@ fn snapshot_is_deleted_with_canister_delete() {
     assert!(test.state().canister_state(&canister_id).is_none());
     assert!(test.state().canister_snapshots.get(snapshot_id).is_none());
 }
+
+fn read_snapshot_metadata(
+    test: &mut ExecutionTest,
+    canister_id: CanisterId,
+    snapshot_id: SnapshotId,
+) -> ReadCanisterSnapshotMetadataResponse {
+    let args = ReadCanisterSnapshotMetadataArgs::new(canister_id, snapshot_id);
+    let result = test
+        .subnet_message("read_canister_snapshot_metadata", args.encode())
+        .unwrap();
+    ReadCanisterSnapshotMetadataResponse::decode(&result.bytes()).unwrap()
+}
+
+fn read_snapshot_data(
+    test: &mut ExecutionTest,
+    canister_id: CanisterId,
+    snapshot_id: SnapshotId,
+    kind: CanisterSnapshotDataKind,
+) -> Result<Vec<u8>, UserError> {
+    let args = ReadCanisterSnapshotDataArgs::new(canister_id, snapshot_id, kind);
+    test.subnet_message("read_canister_snapshot_data", args.encode())
+        .map(|result| {
+            ReadCanisterSnapshotDataResponse::decode(&result.bytes())
+                .unwrap()
+                .chunk
+        })
+}
+
+#[test]
+fn read_canister_snapshot_metadata_and_data_succeeds() {
+    const CYCLES: Cycles = Cycles::new(1_000_000_000_000);
+    let own_subnet = subnet_test_id(1);
+    let caller_canister = canister_test_id(1);
+    let mut test = ExecutionTestBuilder::new()
+        .with_own_subnet_id(own_subnet)
+        .with_snapshots(FlagStatus::Enabled)
+        .with_caller(own_subnet, caller_canister)
+        .build();
+
+    let canister_id = test
+        .canister_from_cycles_and_binary(CYCLES, UNIVERSAL_CANISTER_WASM.into())
+        .unwrap();
+
+    let args: TakeCanisterSnapshotArgs = TakeCanisterSnapshotArgs::new(canister_id, None);
+    let result = test.subnet_message("take_canister_snapshot", args.encode());
+    let snapshot_id = CanisterSnapshotResponse::decode(&result.unwrap().bytes())
+        .unwrap()
+        .snapshot_id();
+
+    let metadata = read_snapshot_metadata(&mut test, canister_id, snapshot_id);
+    assert_eq!(metadata.source, SnapshotSource::TakenFromCanister);
+    assert_eq!(
+        metadata.wasm_module_size,
+        UNIVERSAL_CANISTER_WASM.len() as u64
+    );
+    assert_eq!(
+        metadata.canister_version,
+        test.canister_state(canister_id)
+            .system_state
+            .canister_version
+    );
+
+    // Download the Wasm module in slices.
+    let mut module = vec![];
+    let slice_size = 10_000;
+    while (module.len() as u64) < metadata.wasm_module_size {
+        let offset = module.len() as u64;
+        let size = slice_size.min(metadata.wasm_module_size - offset);
+        let chunk = read_snapshot_data(
+            &mut test,
+            canister_id,
+            snapshot_id,
+            CanisterSnapshotDataKind::WasmModule { offset, size },
+        )
+        .unwrap();
+        module.extend(chunk);
+    }
+    assert_eq!(module, UNIVERSAL_CANISTER_WASM);
+
+    // Reading beyond the end of the memory fails.
+    let error = read_snapshot_data(
+        &mut test,
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataKind::MainMemory {
+            offset: metadata.wasm_memory_size,
+            size: 1,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InvalidManagementPayload);
+}
+
+#[test]
+fn upload_canister_snapshot_round_trip_succeeds() {
+    const CYCLES: Cycles = Cycles::new(1_000_000_000_000);
+    let own_subnet = subnet_test_id(1);
+    let caller_canister = canister_test_id(1);
+    let mut test = ExecutionTestBuilder::new()
+        .with_own_subnet_id(own_subnet)
+        .with_snapshots(FlagStatus::Enabled)
+        .with_caller(own_subnet, caller_canister)
+        .build();
+
+    let canister_id = test
+        .canister_from_cycles_and_binary(CYCLES, UNIVERSAL_CANISTER_WASM.into())
+        .unwrap();
+    let chunk_hash = test
+        .subnet_message(
+            "upload_chunk",
+            UploadChunkArgs {
+                canister_id: canister_id.into(),
+                chunk: vec![1, 2, 3, 4, 5],
+            }
+            .encode(),
+        )
+        .unwrap();
+    let chunk_hash = Decode!(&chunk_hash.bytes(), ic00::ChunkHash).unwrap();
+
+    let args: TakeCanisterSnapshotArgs = TakeCanisterSnapshotArgs::new(canister_id, None);
+    let result = test.subnet_message("take_canister_snapshot", args.encode());
+    let snapshot_id = CanisterSnapshotResponse::decode(&result.unwrap().bytes())
+        .unwrap()
+        .snapshot_id();
+
+    // Download the snapshot.
+    let metadata = read_snapshot_metadata(&mut test, canister_id, snapshot_id);
+    let module = read_snapshot_data(
+        &mut test,
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataKind::WasmModule {
+            offset: 0,
+            size: metadata.wasm_module_size,
+        },
+    )
+    .unwrap();
+    let wasm_memory = read_snapshot_data(
+        &mut test,
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataKind::MainMemory {
+            offset: 0,
+            size: metadata.wasm_memory_size,
+        },
+    )
+    .unwrap();
+    let chunk = read_snapshot_data(
+        &mut test,
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataKind::WasmChunk {
+            hash: chunk_hash.hash.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(chunk, vec![1, 2, 3, 4, 5]);
+
+    // Upload it as a new snapshot.
+    let args = UploadCanisterSnapshotMetadataArgs::new(
+        canister_id,
+        None,
+        metadata.wasm_module_size,
+        metadata.exported_globals.clone(),
+        metadata.wasm_memory_size,
+        metadata.stable_memory_size,
+        metadata.certified_data.clone(),
+    );
+    let result = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap();
+    let uploaded_snapshot_id = UploadCanisterSnapshotMetadataResponse::decode(&result.bytes())
+        .unwrap()
+        .get_snapshot_id();
+    assert_ne!(uploaded_snapshot_id, snapshot_id);
+
+    for (kind, data) in [
+        (CanisterSnapshotDataOffset::WasmModule { offset: 0 }, module),
+        (
+            CanisterSnapshotDataOffset::MainMemory { offset: 0 },
+            wasm_memory,
+        ),
+        (CanisterSnapshotDataOffset::WasmChunk, chunk),
+    ] {
+        let args =
+            UploadCanisterSnapshotDataArgs::new(canister_id, uploaded_snapshot_id, kind, data);
+        test.subnet_message("upload_canister_snapshot_data", args.encode())
+            .unwrap();
+    }
+
+    let uploaded_metadata = read_snapshot_metadata(&mut test, canister_id, uploaded_snapshot_id);
+    assert_eq!(uploaded_metadata.source, SnapshotSource::MetadataUpload);
+    assert_eq!(
+        uploaded_metadata.wasm_module_size,
+        metadata.wasm_module_size
+    );
+    assert_eq!(
+        uploaded_metadata.exported_globals,
+        metadata.exported_globals
+    );
+    assert_eq!(
+        uploaded_metadata.wasm_memory_size,
+        metadata.wasm_memory_size
+    );
+    assert_eq!(
+        uploaded_metadata.wasm_chunk_store,
+        metadata.wasm_chunk_store
+    );
+
+    // The uploaded snapshot can be loaded.
+    let args = LoadCanisterSnapshotArgs::new(canister_id, uploaded_snapshot_id, None);
+    test.subnet_message("load_canister_snapshot", args.encode())
+        .unwrap();
+    assert_eq!(
+        test.canister_state(canister_id)
+            .execution_state
+            .as_ref()
+            .unwrap()
+            .wasm_binary
+            .binary
+            .as_slice(),
+        UNIVERSAL_CANISTER_WASM
+    );
+}
+
+#[test]
+fn upload_canister_snapshot_data_fails_for_taken_snapshot() {
+    const CYCLES: Cycles = Cycles::new(1_000_000_000_000);
+    let own_subnet = subnet_test_id(1);
+    let caller_canister = canister_test_id(1);
+    let mut test = ExecutionTestBuilder::new()
+        .with_own_subnet_id(own_subnet)
+        .with_snapshots(FlagStatus::Enabled)
+        .with_caller(own_subnet, caller_canister)
+        .build();
+
+    let canister_id = test
+        .canister_from_cycles_and_binary(CYCLES, UNIVERSAL_CANISTER_WASM.into())
+        .unwrap();
+
+    let args: TakeCanisterSnapshotArgs = TakeCanisterSnapshotArgs::new(canister_id, None);
+    let result = test.subnet_message("take_canister_snapshot", args.encode());
+    let snapshot_id = CanisterSnapshotResponse::decode(&result.unwrap().bytes())
+        .unwrap()
+        .snapshot_id();
+
+    let args = UploadCanisterSnapshotDataArgs::new(
+        canister_id,
+        snapshot_id,
+        CanisterSnapshotDataOffset::WasmModule { offset: 0 },
+        vec![0, 1, 2],
+    );
+    let error = test
+        .subnet_message("upload_canister_snapshot_data", args.encode())
+        .unwrap_err();
+    assert_eq!(error.code(), ErrorCode::CanisterRejectedMessage);
+    assert!(error
+        .description()
+        .contains("was not created by a metadata upload"));
+}
+
+#[test]
+fn upload_canister_snapshot_data_duplicate_chunk_is_not_charged() {
+    const CYCLES: Cycles = Cycles::new(1_000_000_000_000);
+    let own_subnet = subnet_test_id(1);
+    let caller_canister = canister_test_id(1);
+    let mut test = ExecutionTestBuilder::new()
+        .with_own_subnet_id(own_subnet)
+        .with_snapshots(FlagStatus::Enabled)
+        .with_caller(own_subnet, caller_canister)
+        .build();
+
+    let canister_id = test
+        .canister_from_cycles_and_binary(CYCLES, UNIVERSAL_CANISTER_WASM.into())
+        .unwrap();
+
+    let args = UploadCanisterSnapshotMetadataArgs::new(
+        canister_id,
+        None,
+        UNIVERSAL_CANISTER_WASM.len() as u64,
+        vec![],
+        0,
+        0,
+        vec![],
+    );
+    let result = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap();
+    let snapshot_id = UploadCanisterSnapshotMetadataResponse::decode(&result.bytes())
+        .unwrap()
+        .get_snapshot_id();
+
+    let upload_chunk = |test: &mut ExecutionTest| {
+        let args = UploadCanisterSnapshotDataArgs::new(
+            canister_id,
+            snapshot_id,
+            CanisterSnapshotDataOffset::WasmChunk,
+            vec![1, 2, 3, 4, 5],
+        );
+        test.subnet_message("upload_canister_snapshot_data", args.encode())
+            .unwrap();
+    };
+
+    upload_chunk(&mut test);
+    let snapshot_size = test
+        .state()
+        .canister_snapshots
+        .get(snapshot_id)
+        .unwrap()
+        .size();
+    let subnet_available_memory = test.subnet_available_memory();
+    let heap_delta_debit = test
+        .canister_state(canister_id)
+        .scheduler_state
+        .heap_delta_debit;
+
+    // Uploading the same chunk again neither grows the snapshot nor
+    // reserves additional subnet memory.
+    upload_chunk(&mut test);
+    assert_eq!(
+        test.state()
+            .canister_snapshots
+            .get(snapshot_id)
+            .unwrap()
+            .size(),
+        snapshot_size
+    );
+    assert_eq!(test.subnet_available_memory(), subnet_available_memory);
+    assert_eq!(
+        test.canister_state(canister_id)
+            .scheduler_state
+            .heap_delta_debit,
+        heap_delta_debit
+    );
+    assert_eq!(
+        test.state()
+            .canister_snapshots
+            .get(snapshot_id)
+            .unwrap()
+            .chunk_store()
+            .keys()
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn upload_canister_snapshot_metadata_fails_unaligned_memory_size() {
+    const CYCLES: Cycles = Cycles::new(1_000_000_000_000);
+    let own_subnet = subnet_test_id(1);
+    let caller_canister = canister_test_id(1);
+    let mut test = ExecutionTestBuilder::new()
+        .with_own_subnet_id(own_subnet)
+        .with_snapshots(FlagStatus::Enabled)
+        .with_caller(own_subnet, caller_canister)
+        .build();
+
+    let canister_id = test
+        .canister_from_cycles_and_binary(CYCLES, UNIVERSAL_CANISTER_WASM.into())
+        .unwrap();
+
+    let args =
+        UploadCanisterSnapshotMetadataArgs::new(canister_id, None, 10, vec![], 1000, 0, vec![]);
+    let error = test
+        .subnet_message("upload_canister_snapshot_metadata", args.encode())
+        .unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InvalidManagementPayload);
+    assert!(test
+        .state()
+        .canister_snapshots
+        .list_snapshots(canister_id)
+        .is_empty());
+}
@@ -6,7 +6,9 @@ use ic_embedders::wasm_executor::{WasmExecutionResult, WasmExecutor};
 use ic_embedders::wasm_utils::decoding::decoded_wasm_size;
 use ic_embedders::{wasm_executor::WasmExecutorImpl, WasmExecutionInput, WasmtimeEmbedder};
 use ic_embedders::{CompilationCache, CompilationResult};
-use ic_interfaces::execution_environment::{HypervisorResult, WasmExecutionOutput};
+use ic_interfaces::execution_environment::{
+    ExecutionProfile, HypervisorResult, WasmExecutionOutput,
+};
 use ic_logger::ReplicaLogger;
 use ic_metrics::buckets::decimal_buckets_with_zero;
 use ic_metrics::{buckets::exponential_buckets, MetricsRegistry};
@@ -21,7 +23,10 @@ use ic_types::{
 };
 use ic_wasm_types::CanisterModule;
 use prometheus::{Histogram, HistogramVec, IntCounter, IntGauge};
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
 use crate::execution::common::{apply_canister_state_changes, update_round_limits};
 use crate::execution_environment::{as_round_instructions, CompilationCostHandling, RoundLimits};
@@ -192,6 +197,22 @@ impl HypervisorMetrics {
     }
 }
 
+/// Collects the profiles of finished executions if `execution_profiling` is
+/// enabled in the embedders config. All clones share the same profiles.
+#[derive(Clone, Default)]
+pub struct ExecutionProfiles(Arc<Mutex<Vec<ExecutionProfile>>>);
+
+impl ExecutionProfiles {
+    fn push(&self, profile: ExecutionProfile) {
+        self.0.lock().unwrap().push(profile);
+    }
+
+    /// Returns the profiles of all executions that finished since the last call.
+    pub fn take(&self) -> Vec<ExecutionProfile> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
 #[doc(hidden)]
 pub struct Hypervisor {
     wasm_executor: Arc<dyn WasmExecutor>,
@@ -204,6 +225,7 @@ pub struct Hypervisor {
     deterministic_time_slicing: FlagStatus,
     cost_to_compile_wasm_instruction: NumInstructions,
     dirty_page_overhead: NumInstructions,
+    execution_profiles: ExecutionProfiles,
 }
 
 impl Hypervisor {
@@ -215,6 +237,11 @@ impl Hypervisor {
         self.own_subnet_type
     }
 
+    /// Returns a handle to the profiles of finished executions.
+    pub fn execution_profiles(&self) -> ExecutionProfiles {
+        self.execution_profiles.clone()
+    }
+
     pub fn create_execution_state(
         &self,
         canister_module: CanisterModule,
@@ -313,6 +340,7 @@ impl Hypervisor {
                 .embedders_config
                 .cost_to_compile_wasm_instruction,
             dirty_page_overhead,
+            execution_profiles: ExecutionProfiles::default(),
         }
     }
 
@@ -339,6 +367,7 @@ impl Hypervisor {
             deterministic_time_slicing,
             cost_to_compile_wasm_instruction,
             dirty_page_overhead,
+            execution_profiles: ExecutionProfiles::default(),
         }
     }
 
@@ -445,7 +474,7 @@ impl Hypervisor {
             api_type.call_context_id(),
         );
         let api_type_str = api_type.as_str();
-        let (compilation_result, execution_result) = Arc::clone(&self.wasm_executor).execute(
+        let (compilation_result, mut execution_result) = Arc::clone(&self.wasm_executor).execute(
             WasmExecutionInput {
                 api_type,
                 sandbox_safe_system_state: static_system_state,
@@ -463,6 +492,11 @@ impl Hypervisor {
                 .observe_compilation_metrics(&compilation_result);
         }
         self.metrics.observe(&execution_result, api_type_str);
+        if let WasmExecutionResult::Finished(_, output, _) = &mut execution_result {
+            if let Some(profile) = output.execution_profile.take() {
+                self.execution_profiles.push(profile);
+            }
+        }
         execution_result
     }
 
@@ -68,6 +68,54 @@ fn ic0_canister_status_works() {
     assert_empty_reply(result);
 }
 
+const PROFILED_WAT: &str = r#"
+    (module
+        (import "ic0" "msg_reply" (func $msg_reply))
+        (func (export "canister_update test")
+            (call $msg_reply)
+        )
+    )"#;
+
+#[test]
+fn execution_profiles_are_collected_if_execution_profiling_is_enabled() {
+    let mut test = ExecutionTestBuilder::new()
+        .with_execution_profiling(FlagStatus::Enabled)
+        .build();
+    let canister_id = test.canister_from_wat(PROFILED_WAT).unwrap();
+    let execution_profiles = test.hypervisor_deprecated().execution_profiles();
+    execution_profiles.take();
+
+    let result = test.ingress(canister_id, "test", vec![]);
+    assert_empty_reply(result);
+
+    let profiles = execution_profiles.take();
+    assert_eq!(profiles.len(), 1);
+    assert_eq!(profiles[0].canister_id, canister_id);
+    assert_eq!(profiles[0].method, "canister_update test");
+    assert_eq!(
+        profiles[0]
+            .system_api_calls
+            .iter()
+            .map(|call| call.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["msg_reply"]
+    );
+    assert!(execution_profiles.take().is_empty());
+}
+
+#[test]
+fn execution_profiles_are_not_collected_by_default() {
+    let mut test = ExecutionTestBuilder::new().build();
+    let canister_id = test.canister_from_wat(PROFILED_WAT).unwrap();
+    let result = test.ingress(canister_id, "test", vec![]);
+    assert_empty_reply(result);
+    assert!(test
+        .hypervisor_deprecated()
+        .execution_profiles()
+        .take()
+        .is_empty());
+}
+
 #[test]
 fn ic0_msg_arg_data_size_works() {
     let mut test = ExecutionTestBuilder::new().build();
@@ -20,7 +20,7 @@ pub use execution_environment::{
     ExecuteMessageResult, ExecutionEnvironment, ExecutionResponse, RoundInstructions, RoundLimits,
 };
 pub use history::{IngressHistoryReaderImpl, IngressHistoryWriterImpl};
-pub use hypervisor::{Hypervisor, HypervisorMetrics};
+pub use hypervisor::{ExecutionProfiles, Hypervisor, HypervisorMetrics};
 use ic_base_types::PrincipalId;
 use ic_config::{execution_environment::Config, subnet_config::SchedulerConfig};
 use ic_cycles_account_manager::CyclesAccountManager;
@@ -82,6 +82,7 @@ pub struct ExecutionServices {
     pub query_execution_service: QueryExecutionService,
     pub scheduler: Box<dyn Scheduler<State = ReplicatedState>>,
     pub query_stats_payload_builder: QueryStatsPayloadBuilderParams,
+    pub execution_profiles: ExecutionProfiles,
 }
 
 impl ExecutionServices {
@@ -110,6 +111,8 @@ impl ExecutionServices {
             Arc::clone(&fd_factory),
         ));
 
+        let execution_profiles = hypervisor.execution_profiles();
+
         let ingress_history_writer = Arc::new(IngressHistoryWriterImpl::new(
             config.clone(),
             logger.clone(),
@@ -193,6 +196,7 @@ impl ExecutionServices {
             query_execution_service,
             scheduler,
             query_stats_payload_builder,
+            execution_profiles,
         }
     }
 
@@ -37,6 +37,7 @@ use ic_types::{
     CanisterId, NumInstructions, PrincipalId,
 };
 use prometheus::Histogram;
+use regex::bytes::{Regex, RegexBuilder};
 use serde::Serialize;
 use std::convert::Infallible;
 use std::str::FromStr;
@@ -51,7 +52,7 @@ use tower::{util::BoxCloneService, Service};
 
 pub(crate) use self::query_scheduler::{QueryScheduler, QuerySchedulerFlag};
 use ic_management_canister_types::{
-    BitcoinGetBalanceArgs, BitcoinGetUtxosArgs, FetchCanisterLogsRequest,
+    BitcoinGetBalanceArgs, BitcoinGetUtxosArgs, FetchCanisterLogsFilter, FetchCanisterLogsRequest,
     FetchCanisterLogsResponse, LogVisibility, Payload, QueryMethod,
 };
 use ic_replicated_state::NetworkTopology;
@@ -330,6 +331,29 @@ fn route_bitcoin_message(
     Ok(canister_id)
 }
 
+/// Upper bound on the size of a compiled regex in a `fetch_canister_logs`
+/// filter, so that a query cannot make the replica compile huge automata.
+const MAX_LOG_FILTER_REGEX_SIZE: usize = 1 << 20;
+
+enum LogContentFilter<'a> {
+    Substring(&'a [u8]),
+    Regex(Regex),
+}
+
+impl LogContentFilter<'_> {
+    fn matches(&self, content: &[u8]) -> bool {
+        match self {
+            LogContentFilter::Substring(substring) => {
+                substring.is_empty()
+                    || content
+                        .windows(substring.len())
+                        .any(|window| window == *substring)
+            }
+            LogContentFilter::Regex(regex) => regex.is_match(content),
+        }
+    }
+}
+
 fn fetch_canister_logs(
     sender: PrincipalId,
     state: &ReplicatedState,
@@ -343,18 +367,42 @@ fn fetch_canister_logs(
         )
     })?;
 
-    match canister.log_visibility() {
-        LogVisibility::Public => Ok(()),
-        LogVisibility::Controllers if canister.controllers().contains(&sender) => Ok(()),
-        LogVisibility::Controllers => Err(UserError::new(
+    let is_allowed = match canister.log_visibility() {
+        LogVisibility::Public => true,
+        LogVisibility::Controllers => canister.controllers().contains(&sender),
+        LogVisibility::AllowedViewers(principals) => {
+            canister.controllers().contains(&sender) || principals.contains(&sender)
+        }
+    };
+    if !is_allowed {
+        return Err(UserError::new(
             ErrorCode::CanisterRejectedMessage,
             format!(
                 "Caller {} is not allowed to query ic00 method {}",
                 sender,
                 QueryMethod::FetchCanisterLogs
             ),
-        )),
-    }?;
+        ));
+    }
+
+    let content_filter = match &args.content_filter {
+        None => None,
+        Some(FetchCanisterLogsFilter::Substring(substring)) => {
+            Some(LogContentFilter::Substring(substring.as_bytes()))
+        }
+        Some(FetchCanisterLogsFilter::Regex(pattern)) => {
+            let regex = RegexBuilder::new(pattern)
+                .size_limit(MAX_LOG_FILTER_REGEX_SIZE)
+                .build()
+                .map_err(|err| {
+                    UserError::new(
+                        ErrorCode::InvalidManagementPayload,
+                        format!("Invalid regex in canister log filter: {}", err),
+                    )
+                })?;
+            Some(LogContentFilter::Regex(regex))
+        }
+    };
 
     let response = FetchCanisterLogsResponse {
         canister_log_records: canister
@@ -362,6 +410,16 @@ fn fetch_canister_logs(
             .canister_log
             .records()
             .iter()
+            .filter(|record| {
+                args.idx_range
+                    .map_or(true, |range| range.contains(record.idx))
+                    && args
+                        .timestamp_nanos_range
+                        .map_or(true, |range| range.contains(record.timestamp_nanos))
+                    && content_filter
+                        .as_ref()
+                        .map_or(true, |filter| filter.matches(&record.content))
+            })
             .cloned()
             .collect(),
     };
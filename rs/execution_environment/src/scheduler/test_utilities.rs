@@ -1093,6 +1093,7 @@ impl TestWasmExecutorCore {
                 instance_stats: InstanceStats::default(),
                 system_api_call_counters: SystemApiCallCounters::default(),
                 canister_log: Default::default(),
+                execution_profile: None,
             };
             self.schedule
                 .push((self.round, canister_id, instructions_to_execute));
@@ -1135,6 +1136,7 @@ impl TestWasmExecutorCore {
             instance_stats,
             system_api_call_counters: SystemApiCallCounters::default(),
             canister_log: Default::default(),
+            execution_profile: None,
         };
         self.schedule
             .push((self.round, canister_id, instructions_to_execute));
@@ -4,8 +4,8 @@ use ic_config::flag_status::FlagStatus;
 use ic_config::subnet_config::SubnetConfig;
 use ic_management_canister_types::{
     self as ic00, CanisterIdRecord, CanisterInstallMode, CanisterLogRecord, CanisterSettingsArgs,
-    CanisterSettingsArgsBuilder, DataSize, EmptyBlob, FetchCanisterLogsRequest,
-    FetchCanisterLogsResponse, LogVisibility, Payload,
+    CanisterSettingsArgsBuilder, DataSize, EmptyBlob, FetchCanisterLogsFilter,
+    FetchCanisterLogsRequest, FetchCanisterLogsResponse, LogVisibility, Payload,
 };
 use ic_registry_subnet_type::SubnetType;
 use ic_state_machine_tests::{
@@ -64,16 +64,23 @@ fn default_config_with_canister_logging(canister_logging: FlagStatus) -> Executi
 fn setup(
     canister_logging: FlagStatus,
     settings: CanisterSettingsArgs,
+) -> (StateMachine, CanisterId) {
+    setup_with_config(
+        default_config_with_canister_logging(canister_logging),
+        settings,
+    )
+}
+
+fn setup_with_config(
+    execution_config: ExecutionConfig,
+    settings: CanisterSettingsArgs,
 ) -> (StateMachine, CanisterId) {
     let subnet_type = SubnetType::Application;
     let mut subnet_config = SubnetConfig::new(subnet_type);
     subnet_config.scheduler_config.max_instructions_per_round = MAX_INSTRUCTIONS_PER_ROUND;
     subnet_config.scheduler_config.max_instructions_per_message = MAX_INSTRUCTIONS_PER_MESSAGE;
     subnet_config.scheduler_config.max_instructions_per_slice = MAX_INSTRUCTIONS_PER_SLICE;
-    let config = StateMachineConfig::new(
-        subnet_config,
-        default_config_with_canister_logging(canister_logging),
-    );
+    let config = StateMachineConfig::new(subnet_config, execution_config);
     let env = StateMachineBuilder::new()
         .with_config(Some(config))
         .with_subnet_type(subnet_type)
@@ -238,6 +245,7 @@ fn test_log_visibility_of_fetch_canister_logs() {
     // Test combinations of log_visibility and sender for fetch_canister_logs API call.
     let controller = PrincipalId::new_user_test_id(27);
     let not_a_controller = PrincipalId::new_user_test_id(42);
+    let allowed_viewer = PrincipalId::new_user_test_id(56);
     let ok = Ok(WasmResult::Reply(
         FetchCanisterLogsResponse {
             canister_log_records: vec![],
@@ -254,8 +262,23 @@ fn test_log_visibility_of_fetch_canister_logs() {
         // (log_visibility, sender, expected_result)
         (LogVisibility::Public, controller, ok.clone()),
         (LogVisibility::Public, not_a_controller, ok.clone()),
-        (LogVisibility::Controllers, controller, ok),
-        (LogVisibility::Controllers, not_a_controller, error),
+        (LogVisibility::Controllers, controller, ok.clone()),
+        (LogVisibility::Controllers, not_a_controller, error.clone()),
+        (
+            LogVisibility::AllowedViewers(vec![allowed_viewer]),
+            controller,
+            ok.clone(),
+        ),
+        (
+            LogVisibility::AllowedViewers(vec![allowed_viewer]),
+            allowed_viewer,
+            ok,
+        ),
+        (
+            LogVisibility::AllowedViewers(vec![allowed_viewer]),
+            not_a_controller,
+            error,
+        ),
     ];
     for (log_visibility, sender, expected_result) in test_cases {
         let (env, canister_id) = setup_and_install_wasm(
@@ -349,6 +372,121 @@ fn test_appending_logs_in_trapped_replicated_query_call(#[strategy("\\PC*")] mes
     );
 }
 
+#[test]
+fn test_too_many_allowed_log_viewers_are_rejected() {
+    let (env, canister_id, _controller) =
+        setup_with_controller(FlagStatus::Enabled, wat_canister().build_wasm());
+    let allowed_viewers = (0..11).map(PrincipalId::new_user_test_id).collect();
+    let result = env.update_settings(
+        &canister_id,
+        CanisterSettingsArgsBuilder::new()
+            .with_log_visibility(LogVisibility::AllowedViewers(allowed_viewers))
+            .build(),
+    );
+    assert_eq!(
+        result.unwrap_err().code(),
+        ErrorCode::CanisterContractViolation
+    );
+}
+
+fn fetch_canister_logs_with_request(
+    env: &StateMachine,
+    sender: PrincipalId,
+    request: FetchCanisterLogsRequest,
+) -> Result<WasmResult, UserError> {
+    env.query_as(
+        sender,
+        CanisterId::ic_00(),
+        "fetch_canister_logs",
+        request.encode(),
+    )
+}
+
+#[test]
+fn test_fetch_canister_logs_with_filters() {
+    let (env, canister_id, controller) = setup_with_controller(
+        FlagStatus::Enabled,
+        wat_canister()
+            .update(
+                "test1",
+                wat_fn()
+                    .debug_print(b"info: started")
+                    .debug_print(b"error: 42"),
+            )
+            .update(
+                "test2",
+                wat_fn().debug_print(b"info: done").debug_print(b"error: 7"),
+            )
+            .build_wasm(),
+    );
+    let timestamp_01 = system_time_to_nanos(env.time_of_next_round());
+    let _ = env.execute_ingress(canister_id, "test1", vec![]);
+    env.advance_time(Duration::from_nanos(123_456));
+    let timestamp_23 = system_time_to_nanos(env.time_of_next_round());
+    let _ = env.execute_ingress(canister_id, "test2", vec![]);
+
+    let fetch = |request: FetchCanisterLogsRequest| {
+        FetchCanisterLogsResponse::decode(&get_reply(fetch_canister_logs_with_request(
+            &env, controller, request,
+        )))
+        .unwrap()
+    };
+
+    // Index range, e.g. to fetch the logs page by page.
+    assert_eq!(
+        fetch(FetchCanisterLogsRequest::new(canister_id).with_idx_range(1, 3)),
+        canister_log_response(vec![
+            (1, timestamp_01, b"error: 42".to_vec()),
+            (2, timestamp_23, b"info: done".to_vec()),
+        ])
+    );
+    // Timestamp range.
+    assert_eq!(
+        fetch(
+            FetchCanisterLogsRequest::new(canister_id)
+                .with_timestamp_nanos_range(timestamp_23, timestamp_23 + 1)
+        ),
+        canister_log_response(vec![
+            (2, timestamp_23, b"info: done".to_vec()),
+            (3, timestamp_23, b"error: 7".to_vec()),
+        ])
+    );
+    // Substring.
+    assert_eq!(
+        fetch(
+            FetchCanisterLogsRequest::new(canister_id)
+                .with_content_filter(FetchCanisterLogsFilter::Substring("error".to_string()))
+        ),
+        canister_log_response(vec![
+            (1, timestamp_01, b"error: 42".to_vec()),
+            (3, timestamp_23, b"error: 7".to_vec()),
+        ])
+    );
+    // Regex combined with an index range.
+    assert_eq!(
+        fetch(
+            FetchCanisterLogsRequest::new(canister_id)
+                .with_idx_range(0, 2)
+                .with_content_filter(FetchCanisterLogsFilter::Regex(
+                    "^error: [0-9]{2}$".to_string()
+                ))
+        ),
+        canister_log_response(vec![(1, timestamp_01, b"error: 42".to_vec())])
+    );
+
+    // An invalid regex is rejected.
+    let result = fetch_canister_logs_with_request(
+        &env,
+        controller,
+        FetchCanisterLogsRequest::new(canister_id)
+            .with_content_filter(FetchCanisterLogsFilter::Regex("(".to_string())),
+    );
+    assert_eq!(
+        result.unwrap_err().code(),
+        ErrorCode::InvalidManagementPayload
+    );
+}
+
 #[test]
 fn test_canister_log_record_index_increment_for_different_calls() {
     // Test that the index of the log records is incremented for each log message,
@@ -486,6 +624,82 @@ fn test_logging_explicit_canister_trap_with_message() {
     );
 }
 
+const NESTED_TRAP_WAT: &str = r#"
+    (module
+        (func $inner (unreachable))
+        (func $outer (call $inner))
+        (func $test (call $outer))
+        (export "canister_update test" (func $test))
+        (memory 1)
+    )"#;
+
+fn setup_and_install_nested_trap_wasm(
+    canister_backtrace: FlagStatus,
+    wasm_backtraces: bool,
+) -> (StateMachine, CanisterId) {
+    let mut config = default_config_with_canister_logging(FlagStatus::Enabled);
+    config.embedders_config.feature_flags.canister_backtrace = canister_backtrace;
+    let (env, canister_id) = setup_with_config(
+        config,
+        CanisterSettingsArgsBuilder::new()
+            .with_log_visibility(LogVisibility::Public)
+            .with_wasm_backtraces(wasm_backtraces)
+            .build(),
+    );
+    env.install_wasm_in_mode(
+        canister_id,
+        CanisterInstallMode::Install,
+        wat::parse_str(NESTED_TRAP_WAT).unwrap(),
+        vec![],
+    )
+    .unwrap();
+    (env, canister_id)
+}
+
+#[test]
+fn test_logging_trap_with_wasm_backtrace() {
+    let (env, canister_id) = setup_and_install_nested_trap_wasm(FlagStatus::Enabled, true);
+    let timestamp = system_time_to_nanos(env.time_of_next_round());
+    let err = env
+        .execute_ingress(canister_id, "test", vec![])
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::CanisterTrapped);
+    assert!(err
+        .description()
+        .contains("Canister trapped: unreachable\nCanister Backtrace:\ninner\nouter\ntest"));
+    let result = fetch_canister_logs(&env, PrincipalId::new_anonymous(), canister_id);
+    assert_eq!(
+        FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
+        canister_log_response(vec![(
+            0,
+            timestamp,
+            b"[TRAP]: unreachable\nCanister Backtrace:\ninner\nouter\ntest".to_vec()
+        )])
+    );
+}
+
+#[test]
+fn test_logging_trap_without_wasm_backtrace() {
+    // Backtraces are reported only if both the replica flag and the canister
+    // setting are enabled.
+    for (canister_backtrace, wasm_backtraces) in
+        [(FlagStatus::Enabled, false), (FlagStatus::Disabled, true)]
+    {
+        let (env, canister_id) =
+            setup_and_install_nested_trap_wasm(canister_backtrace, wasm_backtraces);
+        let timestamp = system_time_to_nanos(env.time_of_next_round());
+        let err = env
+            .execute_ingress(canister_id, "test", vec![])
+            .unwrap_err();
+        assert!(!err.description().contains("Canister Backtrace"));
+        let result = fetch_canister_logs(&env, PrincipalId::new_anonymous(), canister_id);
+        assert_eq!(
+            FetchCanisterLogsResponse::decode(&get_reply(result)).unwrap(),
+            canister_log_response(vec![(0, timestamp, b"[TRAP]: unreachable".to_vec())])
+        );
+    }
+}
+
 #[test]
 fn test_canister_log_stays_within_limit() {
     // Test that the total size of canister log records stays within the limit
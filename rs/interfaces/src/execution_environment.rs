@@ -1,7 +1,7 @@
 //! The execution environment public interface.
 mod errors;
 
-pub use errors::{CanisterOutOfCyclesError, HypervisorError, TrapCode};
+pub use errors::{CanisterBacktrace, CanisterOutOfCyclesError, HypervisorError, TrapCode};
 use ic_base_types::NumBytes;
 use ic_error_types::UserError;
 use ic_management_canister_types::MasterPublicKeyId;
@@ -13,7 +13,8 @@ use ic_types::{
     crypto::canister_threshold_sig::MasterPublicKey,
     ingress::{IngressStatus, WasmResult},
     messages::{CertificateDelegation, MessageId, Query, SignedIngressContent},
-    CanisterLog, Cycles, ExecutionRound, Height, NumInstructions, NumOsPages, Randomness, Time,
+    CanisterId, CanisterLog, Cycles, ExecutionRound, Height, NumInstructions, NumOsPages,
+    Randomness, Time,
 };
 use serde::{Deserialize, Serialize};
 use std::{
@@ -155,6 +156,16 @@ pub enum SystemApiCallId {
     CanisterVersion,
     /// Tracker for `ic0.certified_data_set()`
     CertifiedDataSet,
+    /// Tracker for `ic0.cost_call()`
+    CostCall,
+    /// Tracker for `ic0.cost_create_canister()`
+    CostCreateCanister,
+    /// Tracker for `ic0.cost_http_request()`
+    CostHttpRequest,
+    /// Tracker for `ic0.cost_sign_with_ecdsa()`
+    CostSignWithEcdsa,
+    /// Tracker for `ic0.cost_sign_with_schnorr()`
+    CostSignWithSchnorr,
     /// Tracker for `ic0.cycles_burn128()`
     CyclesBurn128,
     /// Tracker for `ic0.data_certificate_copy()`
@@ -165,6 +176,18 @@ pub enum SystemApiCallId {
     DataCertificateSize,
     /// Tracker for `ic0.debug_print()`
     DebugPrint,
+    /// Tracker for `ic0.env_var_count()`
+    EnvVarCount,
+    /// Tracker for `ic0.env_var_name_copy()`
+    EnvVarNameCopy,
+    /// Tracker for `ic0.env_var_name_exists()`
+    EnvVarNameExists,
+    /// Tracker for `ic0.env_var_name_size()`
+    EnvVarNameSize,
+    /// Tracker for `ic0.env_var_value_copy()`
+    EnvVarValueCopy,
+    /// Tracker for `ic0.env_var_value_size()`
+    EnvVarValueSize,
     /// Tracker for `ic0.global_timer_set()`
     GlobalTimerSet,
     /// Tracker for `ic0.in_replicated_execution()`
@@ -268,6 +291,43 @@ impl SystemApiCallCounters {
     }
 }
 
+/// A single `ic0` system API call made during a profiled execution.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SystemApiCall {
+    /// The name of the import without the `ic0` module, e.g. `msg_reply`.
+    pub name: String,
+    /// The number of instructions the message executed before the call.
+    pub instructions: u64,
+}
+
+/// The number of instructions attributed to a Wasm call stack.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StackSample {
+    /// Indices of the functions on the stack, outermost first. The indices
+    /// refer to the module as installed by the user, i.e. before
+    /// instrumentation.
+    pub frames: Vec<u32>,
+    pub instructions: u64,
+}
+
+/// The profile of a single execution of a canister method or closure.
+///
+/// The Wasm call stack is only sampled at `ic0` system API calls, so the
+/// profile has system API call granularity rather than instruction granularity.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ExecutionProfile {
+    pub canister_id: CanisterId,
+    /// The exported function that was executed, e.g. `canister_update foo`.
+    pub method: String,
+    pub instructions_executed: u64,
+    pub system_api_calls: Vec<SystemApiCall>,
+    /// Instructions executed after the last system API call, including the
+    /// charge for dirty pages, are attributed to the empty stack.
+    pub stacks: Vec<StackSample>,
+    pub wasm_dirty_pages: usize,
+    pub stable_dirty_pages: usize,
+}
+
 /// Tracks the available memory on a subnet. The main idea is to separately track
 /// the execution available memory, the message available memory and the wasm custom
 /// sections available memory. The different flavors of memory are independent of each
@@ -1151,6 +1211,111 @@ pub trait SystemApi {
         dst: usize,
         heap: &mut [u8],
     ) -> HypervisorResult<()>;
+
+    /// Returns the amount of cycles that sending an inter-canister call with
+    /// the given method name and argument sizes would withdraw, including
+    /// the prepayment for the largest possible response.
+    ///
+    /// The amount of cycles is represented by a 128-bit value
+    /// and is copied in the canister memory starting
+    /// at the location `dst`.
+    fn ic0_cost_call(
+        &self,
+        method_name_size: u64,
+        payload_size: u64,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
+
+    /// Returns the fee for creating a canister on this subnet as a 128-bit
+    /// value at the location `dst` in the canister memory.
+    fn ic0_cost_create_canister(&self, dst: usize, heap: &mut [u8]) -> HypervisorResult<()>;
+
+    /// Returns the cost of an HTTP outcall with a request of `request_size`
+    /// bytes and a response limit of `max_res_bytes` bytes as a 128-bit value
+    /// at the location `dst` in the canister memory.
+    fn ic0_cost_http_request(
+        &self,
+        request_size: u64,
+        max_res_bytes: u64,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
+
+    /// Returns the fee of a `sign_with_ecdsa` call with the key given by the
+    /// name at `src`/`size` and `curve` as a 128-bit value at the location
+    /// `dst` in the canister memory.
+    ///
+    /// Returns `0` on success, `1` if the curve is unknown, and `2` if no
+    /// subnet signs with the key. Nothing is copied in the error cases.
+    fn ic0_cost_sign_with_ecdsa(
+        &self,
+        src: usize,
+        size: usize,
+        curve: u32,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32>;
+
+    /// Same as `ic0_cost_sign_with_ecdsa`, but for a `sign_with_schnorr` call
+    /// with the given `algorithm`.
+    fn ic0_cost_sign_with_schnorr(
+        &self,
+        src: usize,
+        size: usize,
+        algorithm: u32,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32>;
+
+    /// Returns the number of environment variables of the canister.
+    fn ic0_env_var_count(&self) -> HypervisorResult<usize>;
+
+    /// Returns the size of the name of the environment variable at `index`.
+    /// The variables are sorted by name.
+    fn ic0_env_var_name_size(&self, index: usize) -> HypervisorResult<usize>;
+
+    /// Copies `size` bytes starting from `offset` in the name of the
+    /// environment variable at `index` to heap[dst..dst+size].
+    fn ic0_env_var_name_copy(
+        &self,
+        index: usize,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
+
+    /// Returns 1 if an environment variable with the name given by
+    /// heap[name_src..name_src+name_size] exists and 0 otherwise.
+    fn ic0_env_var_name_exists(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<u32>;
+
+    /// Returns the size of the value of the environment variable with the
+    /// name given by heap[name_src..name_src+name_size].
+    fn ic0_env_var_value_size(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<usize>;
+
+    /// Copies `size` bytes starting from `offset` in the value of the
+    /// environment variable with the name given by
+    /// heap[name_src..name_src+name_size] to heap[dst..dst+size].
+    fn ic0_env_var_value_copy(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()>;
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -1250,6 +1415,9 @@ pub struct WasmExecutionOutput {
     /// How many times each tracked System API call was invoked.
     pub system_api_call_counters: SystemApiCallCounters,
     pub canister_log: CanisterLog,
+    /// The profile of the execution if execution profiling is enabled in the
+    /// embedders config.
+    pub execution_profile: Option<ExecutionProfile>,
 }
 
 impl fmt::Display for WasmExecutionOutput {
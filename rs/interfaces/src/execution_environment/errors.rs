@@ -41,6 +41,25 @@ impl std::fmt::Display for TrapCode {
     }
 }
 
+/// The Wasm call stack of a canister at the time it trapped, innermost frame
+/// first. Each frame holds the index of the function in the module installed
+/// by the user and its name from the `name` custom section, if there is one.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanisterBacktrace(pub Vec<(u32, Option<String>)>);
+
+impl std::fmt::Display for CanisterBacktrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Canister Backtrace:")?;
+        for (index, name) in &self.0 {
+            match name {
+                Some(name) => write!(f, "\n{}", name)?,
+                None => write!(f, "\n_wasm_function[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Error when a canister's balance is too low compared to its freezing
 /// threshold and cannot perform the requested action.
 ///
@@ -99,10 +118,17 @@ pub enum HypervisorError {
     /// We could not instrument the wasm module
     InstrumentationFailed(WasmInstrumentationError),
     /// Canister Wasm trapped (e.g. by executing the `unreachable`
-    /// instruction or dividing by zero).
-    Trapped(TrapCode),
+    /// instruction or dividing by zero). The backtrace is only captured if
+    /// the canister enabled Wasm backtraces in its settings.
+    Trapped {
+        trap_code: TrapCode,
+        backtrace: Option<CanisterBacktrace>,
+    },
     /// Canister explicitly called `ic.trap`.
-    CalledTrap(String),
+    CalledTrap {
+        message: String,
+        backtrace: Option<CanisterBacktrace>,
+    },
     /// An attempt was made to execute a message on a canister that does not
     /// contain a Wasm module.
     WasmModuleNotFound,
@@ -217,9 +243,22 @@ impl std::fmt::Display for HypervisorError {
             Self::InstrumentationFailed(err) => {
                 write!(f, "Could not instrument wasm module of canister: {}", err)
             }
-            Self::Trapped(code) => write!(f, "Canister trapped: {}", code),
-            Self::CalledTrap(msg) => {
-                write!(f, "Canister called `ic0.trap` with message: {}", msg)
+            Self::Trapped {
+                trap_code,
+                backtrace,
+            } => {
+                write!(f, "Canister trapped: {}", trap_code)?;
+                if let Some(backtrace) = backtrace {
+                    write!(f, "\n{}", backtrace)?;
+                }
+                Ok(())
+            }
+            Self::CalledTrap { message, backtrace } => {
+                write!(f, "Canister called `ic0.trap` with message: {}", message)?;
+                if let Some(backtrace) = backtrace {
+                    write!(f, "\n{}", backtrace)?;
+                }
+                Ok(())
             }
             Self::WasmModuleNotFound => write!(
                 f,
@@ -355,8 +394,8 @@ impl AsErrorHelp for HypervisorError {
                 doc_link: "http://internetcomputer.org/docs/current/references/execution-errors#method-not-found".to_string(),
             },
             Self::InstructionLimitExceeded(_)
-            | Self::Trapped(_)
-            | Self::CalledTrap(_)
+            | Self::Trapped { .. }
+            | Self::CalledTrap { .. }
             | Self::WasmModuleNotFound
             | Self::OutOfMemory
             | Self::InvalidPrincipalId(_)
@@ -405,8 +444,8 @@ impl HypervisorError {
             Self::InstructionLimitExceeded(_) => E::CanisterInstructionLimitExceeded,
             Self::InvalidWasm(_) => E::CanisterInvalidWasm,
             Self::InstrumentationFailed(_) => E::CanisterInvalidWasm,
-            Self::Trapped(_) => E::CanisterTrapped,
-            Self::CalledTrap(_) => E::CanisterCalledTrap,
+            Self::Trapped { .. } => E::CanisterTrapped,
+            Self::CalledTrap { .. } => E::CanisterCalledTrap,
             Self::WasmModuleNotFound => E::CanisterWasmModuleNotFound,
             Self::OutOfMemory => E::CanisterOutOfMemory,
             Self::InvalidPrincipalId(_) => E::CanisterContractViolation,
@@ -446,8 +485,8 @@ impl HypervisorError {
             HypervisorError::InstructionLimitExceeded(_) => "InstructionLimitExceeded",
             HypervisorError::InvalidWasm(_) => "InvalidWasm",
             HypervisorError::InstrumentationFailed(_) => "InstrumentationFailed",
-            HypervisorError::Trapped(_) => "Trapped",
-            HypervisorError::CalledTrap(_) => "CalledTrap",
+            HypervisorError::Trapped { .. } => "Trapped",
+            HypervisorError::CalledTrap { .. } => "CalledTrap",
             HypervisorError::WasmModuleNotFound => "WasmModuleNotFound",
             HypervisorError::OutOfMemory => "OutOfMemory",
             HypervisorError::InvalidPrincipalId(_) => "InvalidPrincipalId",
@@ -471,4 +510,20 @@ impl HypervisorError {
             HypervisorError::WasmMemoryLimitExceeded { .. } => "WasmMemoryLimitExceeded",
         }
     }
+
+    /// Attaches the backtrace to the error if the canister trapped. Other
+    /// errors are returned unchanged.
+    pub fn with_backtrace(self, backtrace: CanisterBacktrace) -> Self {
+        match self {
+            Self::Trapped { trap_code, .. } => Self::Trapped {
+                trap_code,
+                backtrace: Some(backtrace),
+            },
+            Self::CalledTrap { message, .. } => Self::CalledTrap {
+                message,
+                backtrace: Some(backtrace),
+            },
+            err => err,
+        }
+    }
 }
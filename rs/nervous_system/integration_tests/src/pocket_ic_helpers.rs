@@ -656,6 +656,7 @@ pub mod nns {
                     Encode!(&ListNeurons {
                         neuron_ids: vec![],
                         include_neurons_readable_by_caller: true,
+                        ..Default::default()
                     })
                     .unwrap(),
                 )
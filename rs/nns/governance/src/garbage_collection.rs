@@ -66,6 +66,7 @@ impl Governance {
                     // Check that this proposal can be purged.
                     if let Some(prop) = self.heap_data.proposals.get(prop_id) {
                         if prop.can_be_purged(now_seconds, voting_period_seconds) {
+                            self.proposal_index.remove(*prop_id, prop);
                             self.heap_data.proposals.remove(prop_id);
                         }
                     }
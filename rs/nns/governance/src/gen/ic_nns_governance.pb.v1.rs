@@ -121,6 +121,38 @@ pub struct NeuronStakeTransfer {
     #[prost(uint64, tag = "7")]
     pub memo: u64,
 }
+/// An ICRC-1 account: an owner and an optional 32-byte subaccount.
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Account {
+    #[prost(message, optional, tag = "1")]
+    pub owner: ::core::option::Option<::ic_base_types::PrincipalId>,
+    #[prost(bytes = "vec", optional, tag = "2")]
+    pub subaccount: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+}
+/// A portion of a neuron's maturity that is waiting to be minted to an
+/// account. Created by the `DisburseMaturity` command and finalized once
+/// `finalize_disbursement_timestamp_seconds` has passed.
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MaturityDisbursement {
+    /// The amount of maturity (in "e8s equivalent") that was taken out of the
+    /// neuron. The minted amount is this value after applying the maturity
+    /// modulation at finalization time.
+    #[prost(uint64, tag = "1")]
+    pub amount_e8s: u64,
+    /// When the disbursement was initiated.
+    #[prost(uint64, tag = "2")]
+    pub timestamp_of_disbursement_seconds: u64,
+    /// The earliest time at which the disbursement can be finalized.
+    #[prost(uint64, tag = "3")]
+    pub finalize_disbursement_timestamp_seconds: u64,
+    /// The account the ICP will be minted to.
+    #[prost(message, optional, tag = "4")]
+    pub account_to_disburse_to: ::core::option::Option<Account>,
+}
 /// This structure represents a neuron "at rest" in governance system of
 /// the Internet Computer IC.
 #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
@@ -246,6 +278,10 @@ pub struct Neuron {
     /// of the different states.
     #[prost(enumeration = "NeuronType", optional, tag = "22")]
     pub neuron_type: ::core::option::Option<i32>,
+    /// Maturity disbursements that have been initiated with the
+    /// `DisburseMaturity` command but not yet finalized, oldest first.
+    #[prost(message, repeated, tag = "23")]
+    pub maturity_disbursements_in_progress: ::prost::alloc::vec::Vec<MaturityDisbursement>,
     /// At any time, at most one of `when_dissolved` and
     /// `dissolve_delay` are specified.
     ///
@@ -664,7 +700,7 @@ pub struct ManageNeuron {
     pub neuron_id_or_subaccount: ::core::option::Option<manage_neuron::NeuronIdOrSubaccount>,
     #[prost(
         oneof = "manage_neuron::Command",
-        tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 13, 14, 15"
+        tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 13, 14, 15, 16"
     )]
     pub command: ::core::option::Option<manage_neuron::Command>,
 }
@@ -866,6 +902,23 @@ pub mod manage_neuron {
         #[prost(uint32, optional, tag = "1")]
         pub percentage_to_stake: ::core::option::Option<u32>,
     }
+    /// Disburse the maturity of a neuron to an ICRC-1 account. The chosen
+    /// percentage of the neuron's current maturity is removed from the neuron
+    /// right away, and the ICP is minted once the maturity disbursement delay
+    /// (see `NetworkEconomics`) has passed, with the maturity modulation at
+    /// that point in time applied.
+    #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct DisburseMaturity {
+        /// The percentage of maturity to disburse, from 1 to 100 (inclusive).
+        #[prost(uint32, tag = "1")]
+        pub percentage_to_disburse: u32,
+        /// The account to disburse the maturity to. If not set, the caller's
+        /// default account is used.
+        #[prost(message, optional, tag = "2")]
+        pub to_account: ::core::option::Option<super::Account>,
+    }
     /// Disburse a portion of this neuron's stake into another neuron.
     /// This allows to split a neuron but with a new dissolve delay
     /// and owned by someone else.
@@ -1014,6 +1067,8 @@ pub mod manage_neuron {
         Merge(Merge),
         #[prost(message, tag = "15")]
         StakeMaturity(StakeMaturity),
+        #[prost(message, tag = "16")]
+        DisburseMaturity(DisburseMaturity),
     }
 }
 /// The response of the ManageNeuron command
@@ -1025,7 +1080,7 @@ pub mod manage_neuron {
 pub struct ManageNeuronResponse {
     #[prost(
         oneof = "manage_neuron_response::Command",
-        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13"
+        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14"
     )]
     pub command: ::core::option::Option<manage_neuron_response::Command>,
 }
@@ -1072,6 +1127,15 @@ pub mod manage_neuron_response {
     #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct DisburseMaturityResponse {
+        /// The amount of maturity that was moved into the pending disbursement,
+        /// before maturity modulation is applied.
+        #[prost(uint64, optional, tag = "1")]
+        pub amount_disbursed_e8s: ::core::option::Option<u64>,
+    }
+    #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct FollowResponse {}
     #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
     #[allow(clippy::derive_partial_eq_without_eq)]
@@ -1158,6 +1222,8 @@ pub mod manage_neuron_response {
         Merge(MergeResponse),
         #[prost(message, tag = "13")]
         StakeMaturity(StakeMaturityResponse),
+        #[prost(message, tag = "14")]
+        DisburseMaturity(DisburseMaturityResponse),
     }
 }
 #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
@@ -1881,6 +1947,9 @@ pub struct NetworkEconomics {
     /// Global Neurons' Fund participation thresholds.
     #[prost(message, optional, tag = "11")]
     pub neurons_fund_economics: ::core::option::Option<NeuronsFundEconomics>,
+    /// How long a maturity disbursement stays pending before it is minted.
+    #[prost(uint64, optional, tag = "12")]
+    pub maturity_disbursement_delay_seconds: ::core::option::Option<u64>,
 }
 /// The thresholds specify the shape of the ideal matching function used by the Neurons' Fund to
 /// determine how much to contribute for a given direct participation amount. Note that the actual
@@ -2412,7 +2481,7 @@ pub mod governance {
         pub timestamp: u64,
         #[prost(
             oneof = "neuron_in_flight_command::Command",
-            tags = "2, 3, 5, 7, 8, 9, 10, 20, 21"
+            tags = "2, 3, 5, 7, 8, 9, 10, 20, 21, 22"
         )]
         pub command: ::core::option::Option<neuron_in_flight_command::Command>,
     }
@@ -2431,6 +2500,19 @@ pub mod governance {
         #[allow(clippy::derive_partial_eq_without_eq)]
         #[derive(Clone, PartialEq, ::prost::Message)]
         pub struct SyncCommand {}
+        /// Held while the ICP of a neuron's oldest due maturity disbursement is
+        /// being minted.
+        #[derive(
+            candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable,
+        )]
+        #[allow(clippy::derive_partial_eq_without_eq)]
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct FinalizeDisburseMaturity {
+            #[prost(uint64, tag = "1")]
+            pub amount_to_be_disbursed_e8s: u64,
+            #[prost(message, optional, tag = "2")]
+            pub to_account: ::core::option::Option<super::super::Account>,
+        }
         #[derive(
             candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable,
         )]
@@ -2455,6 +2537,8 @@ pub mod governance {
             Spawn(::ic_nns_common::pb::v1::NeuronId),
             #[prost(message, tag = "21")]
             SyncCommand(SyncCommand),
+            #[prost(message, tag = "22")]
+            FinalizeDisburseMaturity(FinalizeDisburseMaturity),
         }
     }
     /// Stores metrics that are too costly to compute each time metrics are
@@ -2737,6 +2821,40 @@ pub struct ListProposalInfo {
     /// request doesn't exceed the message size limit.
     #[prost(bool, optional, tag = "7")]
     pub omit_large_fields: ::core::option::Option<bool>,
+    /// If specified, only return proposals that are strictly later than the
+    /// specified proposal according to the proposal ID. Together with
+    /// `before_proposal`, this restricts the results to a window of IDs.
+    #[prost(message, optional, tag = "8")]
+    pub after_proposal: ::core::option::Option<::ic_nns_common::pb::v1::ProposalId>,
+    /// Include proposals made by a neuron in this list. If this list is
+    /// empty, no restriction is applied.
+    #[prost(message, repeated, tag = "9")]
+    pub include_proposers: ::prost::alloc::vec::Vec<::ic_nns_common::pb::v1::NeuronId>,
+    /// Include proposals whose action has a type in this list, e.g.,
+    /// "ACTION_MOTION" or "ACTION_EXECUTE_NNS_FUNCTION". If this list is empty,
+    /// no restriction is applied.
+    #[prost(string, repeated, tag = "10")]
+    pub include_action_types: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Include ExecuteNnsFunction proposals that execute a function in this
+    /// list (see \[NnsFunction\]). If this list is non-empty, proposals with
+    /// other actions are not returned. If this list is empty, no restriction
+    /// is applied.
+    #[prost(enumeration = "NnsFunction", repeated, tag = "11")]
+    pub include_nns_functions: ::prost::alloc::vec::Vec<i32>,
+    /// If specified, only return proposals created at or after this time.
+    #[prost(uint64, optional, tag = "12")]
+    pub created_at_or_after_timestamp_seconds: ::core::option::Option<u64>,
+    /// If specified, only return proposals created strictly before this time.
+    #[prost(uint64, optional, tag = "13")]
+    pub created_before_timestamp_seconds: ::core::option::Option<u64>,
+    /// If specified, only return proposals whose title contains this string,
+    /// ignoring case.
+    #[prost(string, optional, tag = "14")]
+    pub title_contains: ::core::option::Option<::prost::alloc::string::String>,
+    /// If true, return the earliest proposals first. By default, the most
+    /// recent proposals are returned first.
+    #[prost(bool, optional, tag = "15")]
+    pub ascending: ::core::option::Option<bool>,
 }
 #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -2762,6 +2880,34 @@ pub struct ListNeurons {
     /// neurons that the calling principal is authorized to read.
     #[prost(bool, tag = "2")]
     pub include_neurons_readable_by_caller: bool,
+    /// Limit on the number of neurons to return. If neither this nor
+    /// `start_after_neuron_id` is specified, the whole "requested list" is
+    /// returned, as for callers that predate pagination. Otherwise, if no
+    /// value or a value greater than 500 is specified, 500 will be used.
+    #[prost(uint32, optional, tag = "3")]
+    pub page_size: ::core::option::Option<u32>,
+    /// If specified, only neurons whose ID is strictly greater than this
+    /// one are returned. Neurons are returned in ascending order of ID, so
+    /// the `next_start_after_neuron_id` of a previous response can be passed
+    /// here to fetch the next page.
+    #[prost(fixed64, optional, tag = "4")]
+    pub start_after_neuron_id: ::core::option::Option<u64>,
+    /// Only return neurons that are in one of these states (see
+    /// \[NeuronState\]). If this list is empty, no restriction is applied.
+    #[prost(enumeration = "NeuronState", repeated, tag = "5")]
+    pub include_neuron_states: ::prost::alloc::vec::Vec<i32>,
+    /// If specified, only return neurons with a stake of at least this
+    /// amount.
+    #[prost(uint64, optional, tag = "6")]
+    pub min_stake_e8s: ::core::option::Option<u64>,
+    /// If specified, only return neurons with a stake of at most this amount.
+    #[prost(uint64, optional, tag = "7")]
+    pub max_stake_e8s: ::core::option::Option<u64>,
+    /// Whether neurons that are readable by the caller but have neither
+    /// stake nor maturity are included in the "requested list". Defaults to
+    /// true. Neurons listed explicitly in `neuron_ids` are always included.
+    #[prost(bool, optional, tag = "8")]
+    pub include_empty_neurons_readable_by_caller: ::core::option::Option<bool>,
 }
 /// A response to a `ListNeurons` request.
 ///
@@ -2780,6 +2926,11 @@ pub struct ListNeuronsResponse {
     /// `ManageNeuron` topic).
     #[prost(message, repeated, tag = "2")]
     pub full_neurons: ::prost::alloc::vec::Vec<Neuron>,
+    /// If there are more neurons in the "requested list" after the ones in
+    /// this response, this is the ID to pass as `start_after_neuron_id` to
+    /// get the next page.
+    #[prost(fixed64, optional, tag = "3")]
+    pub next_start_after_neuron_id: ::core::option::Option<u64>,
 }
 /// A response to "ListKnownNeurons"
 #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
@@ -23,7 +23,9 @@ use crate::{
         create_service_nervous_system::LedgerParameters,
         get_neurons_fund_audit_info_response,
         governance::{
-            neuron_in_flight_command::{Command as InFlightCommand, SyncCommand},
+            neuron_in_flight_command::{
+                Command as InFlightCommand, FinalizeDisburseMaturity, SyncCommand,
+            },
             GovernanceCachedMetrics, NeuronInFlightCommand,
         },
         governance_error::ErrorType,
@@ -33,7 +35,9 @@ use crate::{
             ClaimOrRefresh, Command, NeuronIdOrSubaccount,
         },
         manage_neuron_response,
-        manage_neuron_response::{MergeMaturityResponse, StakeMaturityResponse},
+        manage_neuron_response::{
+            DisburseMaturityResponse, MergeMaturityResponse, StakeMaturityResponse,
+        },
         neuron::Followees,
         neurons_fund_snapshot::NeuronsFundNeuronPortion as NeuronsFundNeuronPortionPb,
         proposal,
@@ -41,13 +45,13 @@ use crate::{
         reward_node_provider::{RewardMode, RewardToAccount},
         settle_neurons_fund_participation_request, settle_neurons_fund_participation_response,
         settle_neurons_fund_participation_response::NeuronsFundNeuron as NeuronsFundNeuronPb,
-        swap_background_information, Ballot, CreateServiceNervousSystem, ExecuteNnsFunction,
-        GetNeuronsFundAuditInfoRequest, GetNeuronsFundAuditInfoResponse,
+        swap_background_information, Account as AccountPb, Ballot, CreateServiceNervousSystem,
+        ExecuteNnsFunction, GetNeuronsFundAuditInfoRequest, GetNeuronsFundAuditInfoResponse,
         Governance as GovernanceProto, GovernanceError, KnownNeuron, ListKnownNeuronsResponse,
         ListNeurons, ListNeuronsResponse, ListProposalInfo, ListProposalInfoResponse, ManageNeuron,
-        ManageNeuronResponse, MostRecentMonthlyNodeProviderRewards, Motion, NetworkEconomics,
-        Neuron as NeuronProto, NeuronInfo, NeuronState, NeuronsFundAuditInfo, NeuronsFundData,
-        NeuronsFundEconomics as NeuronsFundNetworkEconomicsPb,
+        ManageNeuronResponse, MaturityDisbursement, MostRecentMonthlyNodeProviderRewards, Motion,
+        NetworkEconomics, Neuron as NeuronProto, NeuronInfo, NeuronState, NeuronsFundAuditInfo,
+        NeuronsFundData, NeuronsFundEconomics as NeuronsFundNetworkEconomicsPb,
         NeuronsFundParticipation as NeuronsFundParticipationPb,
         NeuronsFundSnapshot as NeuronsFundSnapshotPb, NnsFunction, NodeProvider, Proposal,
         ProposalData, ProposalInfo, ProposalRewardStatus, ProposalStatus, RestoreAgingSummary,
@@ -56,7 +60,10 @@ use crate::{
         Topic, UpdateNodeProvider, Vote, WaitForQuietState,
         XdrConversionRate as XdrConversionRatePb,
     },
-    proposals::create_service_nervous_system::ExecutedCreateServiceNervousSystemProposal,
+    proposals::{
+        create_service_nervous_system::ExecutedCreateServiceNervousSystemProposal,
+        proposal_index::{ProposalIndex, ProposalIndexQuery},
+    },
 };
 use async_trait::async_trait;
 use candid::{Decode, Encode};
@@ -105,7 +112,7 @@ use std::{
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     convert::{TryFrom, TryInto},
     fmt,
-    ops::RangeInclusive,
+    ops::{Bound, RangeBounds, RangeInclusive},
     str::FromStr,
     string::ToString,
 };
@@ -178,6 +185,10 @@ pub const MAX_NUMBER_OF_NEURONS: usize = 350_000;
 /// The maximum number results returned by the method `list_proposals`.
 pub const MAX_LIST_PROPOSAL_RESULTS: u32 = 100;
 
+/// The maximum number of neurons returned by the method `list_neurons` when
+/// the caller paginates.
+pub const MAX_LIST_NEURONS_RESULTS: u32 = 500;
+
 /// The number of e8s per ICP;
 const E8S_PER_ICP: u64 = TOKEN_SUBDIVIDABLE_BY;
 
@@ -215,6 +226,14 @@ const NODE_PROVIDER_REWARD_PERIOD_SECONDS: u64 = 2629800;
 
 const VALID_MATURITY_MODULATION_BASIS_POINTS_RANGE: RangeInclusive<i32> = -500..=500;
 
+/// The delay between initiating a maturity disbursement and minting it, used
+/// when `NetworkEconomics.maturity_disbursement_delay_seconds` is not set.
+pub const DEFAULT_MATURITY_DISBURSEMENT_DELAY_SECONDS: u64 = ONE_DAY_SECONDS * 7;
+
+/// The maximum number of maturity disbursements a neuron can have in progress
+/// at the same time.
+pub const MAX_NUM_MATURITY_DISBURSEMENTS_IN_PROGRESS: usize = 10;
+
 impl NetworkEconomics {
     /// The multiplier applied to minimum_icp_xdr_rate to convert the XDR unit to basis_points
     pub const ICP_XDR_RATE_TO_BASIS_POINT_MULTIPLIER: u64 = 100;
@@ -232,6 +251,7 @@ impl NetworkEconomics {
             transaction_fee_e8s: DEFAULT_TRANSFER_FEE.get_e8s(),
             max_proposals_to_keep_per_topic: 100,
             neurons_fund_economics: Some(NeuronsFundNetworkEconomicsPb::with_default_values()),
+            maturity_disbursement_delay_seconds: Some(DEFAULT_MATURITY_DISBURSEMENT_DELAY_SECONDS),
         }
     }
 }
@@ -482,6 +502,12 @@ impl ManageNeuronResponse {
         }
     }
 
+    pub fn disburse_maturity_response(response: DisburseMaturityResponse) -> Self {
+        ManageNeuronResponse {
+            command: Some(manage_neuron_response::Command::DisburseMaturity(response)),
+        }
+    }
+
     pub fn follow_response() -> Self {
         ManageNeuronResponse {
             command: Some(manage_neuron_response::Command::Follow(
@@ -1479,6 +1505,10 @@ pub struct Governance {
     /// Stores all neurons and related data.
     pub neuron_store: NeuronStore,
 
+    /// Indexes `heap_data.proposals` for `list_proposals`. Must be updated whenever a proposal
+    /// is added to or removed from `heap_data.proposals`.
+    pub(crate) proposal_index: ProposalIndex,
+
     /// Implementation of Environment to make unit testing easier.
     pub env: Box<dyn Environment>,
 
@@ -1620,6 +1650,21 @@ impl TryFrom<SettleNeuronsFundParticipationRequest>
     }
 }
 
+impl TryFrom<&AccountPb> for AccountIdentifier {
+    type Error = String;
+
+    fn try_from(account: &AccountPb) -> Result<Self, Self::Error> {
+        let owner = account.owner.ok_or("Account owner is missing")?;
+        let subaccount = account
+            .subaccount
+            .as_ref()
+            .map(|subaccount| Subaccount::try_from(subaccount.as_slice()))
+            .transpose()
+            .map_err(|_| "Account subaccount must be 32 bytes long".to_string())?;
+        Ok(AccountIdentifier::new(owner, subaccount))
+    }
+}
+
 impl XdrConversionRatePb {
     /// This constructor should be used only at canister creation, and not, e.g., after upgrades.
     /// The reason this function exists is because `Default::default` is already defined by prost.
@@ -1684,6 +1729,7 @@ impl Governance {
 
         // Step 3: Final assembly.
         Self {
+            proposal_index: ProposalIndex::new(&heap_governance_proto.proposals),
             heap_data: heap_governance_proto,
             neuron_store: NeuronStore::new(
                 // Neurons are converted from API type to internal type.
@@ -1714,6 +1760,7 @@ impl Governance {
             split_governance_proto(governance_proto);
 
         Self {
+            proposal_index: ProposalIndex::new(&heap_governance_proto.proposals),
             heap_data: heap_governance_proto,
             neuron_store: NeuronStore::new_restored((heap_neurons, topic_followee_map)),
             env,
@@ -2058,31 +2105,84 @@ impl Governance {
         caller: &PrincipalId,
     ) -> ListNeuronsResponse {
         let now = self.env.now();
-        let implicitly_requested_neurons = if req.include_neurons_readable_by_caller {
-            self.get_neuron_ids_by_principal(caller)
-        } else {
-            Vec::new()
+        let ListNeurons {
+            neuron_ids,
+            include_neurons_readable_by_caller,
+            page_size,
+            start_after_neuron_id,
+            include_neuron_states,
+            min_stake_e8s,
+            max_stake_e8s,
+            include_empty_neurons_readable_by_caller,
+        } = req;
+
+        // Callers that predate pagination set neither `page_size` nor
+        // `start_after_neuron_id`, and expect the whole requested list.
+        let page_size = match (page_size, start_after_neuron_id) {
+            (None, None) => usize::MAX,
+            (Some(page_size), _) if *page_size > 0 && *page_size <= MAX_LIST_NEURONS_RESULTS => {
+                *page_size as usize
+            }
+            _ => MAX_LIST_NEURONS_RESULTS as usize,
         };
-        let request_neuron_ids: Vec<NeuronId> = req
-            .neuron_ids
-            .iter()
+
+        // Sorting the requested list by ID is what makes `start_after_neuron_id` a stable
+        // cursor across calls.
+        let mut requested_list: BTreeSet<u64> = neuron_ids.iter().copied().collect();
+        if *include_neurons_readable_by_caller {
+            let include_empty_neurons = include_empty_neurons_readable_by_caller.unwrap_or(true);
+            requested_list.extend(
+                self.get_neuron_ids_by_principal(caller)
+                    .into_iter()
+                    .filter(|neuron_id| {
+                        include_empty_neurons
+                            || self
+                                .with_neuron(neuron_id, |neuron| neuron.is_funded())
+                                .unwrap_or(false)
+                    })
+                    .map(|neuron_id| neuron_id.id),
+            );
+        }
+
+        let matches_filters = |neuron_id: &NeuronId| {
+            self.with_neuron(neuron_id, |neuron| {
+                let stake_e8s = neuron.stake_e8s();
+                (include_neuron_states.is_empty()
+                    || include_neuron_states.contains(&(neuron.state(now) as i32)))
+                    && min_stake_e8s.map_or(true, |min_stake_e8s| stake_e8s >= min_stake_e8s)
+                    && max_stake_e8s.map_or(true, |max_stake_e8s| stake_e8s <= max_stake_e8s)
+            })
+            .unwrap_or(false)
+        };
+
+        let lower_bound = match start_after_neuron_id {
+            Some(start_after_neuron_id) => Bound::Excluded(*start_after_neuron_id),
+            None => Bound::Unbounded,
+        };
+        let mut matching_neuron_ids = requested_list
+            .range((lower_bound, Bound::Unbounded))
             .map(|id| NeuronId { id: *id })
-            .collect();
-        let requested_list = || {
-            request_neuron_ids
-                .iter()
-                .chain(implicitly_requested_neurons.iter())
+            .filter(matches_filters);
+        let page: Vec<NeuronId> = matching_neuron_ids.by_ref().take(page_size).collect();
+        let next_start_after_neuron_id = if matching_neuron_ids.next().is_some() {
+            page.last().map(|neuron_id| neuron_id.id)
+        } else {
+            None
         };
+
         ListNeuronsResponse {
-            neuron_infos: requested_list()
+            neuron_infos: page
+                .iter()
                 .filter_map(|id| {
                     self.with_neuron(id, |neuron| (id.id, neuron.get_neuron_info(now)))
                         .ok()
                 })
                 .collect(),
-            full_neurons: requested_list()
+            full_neurons: page
+                .iter()
                 .filter_map(|neuron_id| self.get_full_neuron(neuron_id, caller).ok())
                 .collect(),
+            next_start_after_neuron_id,
         }
     }
 
@@ -3055,6 +3155,150 @@ impl Governance {
         Ok(responses)
     }
 
+    /// Disburses the maturity of a neuron to an account.
+    ///
+    /// The chosen percentage of the neuron's maturity is removed from the
+    /// neuron right away and recorded as a maturity disbursement in progress.
+    /// The ICP is minted by `finalize_maturity_disbursements` once the maturity
+    /// disbursement delay has passed, with the maturity modulation of that day
+    /// applied.
+    ///
+    /// Pre-conditions:
+    /// - The neuron is controlled by `caller`
+    /// - The neuron is not in spawning state.
+    /// - The percentage to disburse is between 1 and 100 (inclusive).
+    /// - The neuron has fewer than `MAX_NUM_MATURITY_DISBURSEMENTS_IN_PROGRESS`
+    ///   disbursements in progress.
+    /// - The amount to disburse is worth more than the transaction fee, even
+    ///   with the worst possible maturity modulation.
+    pub fn disburse_maturity(
+        &mut self,
+        id: &NeuronId,
+        caller: &PrincipalId,
+        disburse_maturity: &manage_neuron::DisburseMaturity,
+    ) -> Result<DisburseMaturityResponse, GovernanceError> {
+        let now = self.env.now();
+        let (
+            neuron_state,
+            is_neuron_controlled_by_caller,
+            neuron_maturity_e8s_equivalent,
+            num_maturity_disbursements_in_progress,
+        ) = self.with_neuron(id, |neuron| {
+            (
+                neuron.state(now),
+                neuron.is_controlled_by(caller),
+                neuron.maturity_e8s_equivalent,
+                neuron.maturity_disbursements_in_progress.len(),
+            )
+        })?;
+
+        if neuron_state == NeuronState::Spawning {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                "Can't perform operation on neuron: Neuron is spawning.",
+            ));
+        }
+
+        if !is_neuron_controlled_by_caller {
+            return Err(GovernanceError::new(ErrorType::NotAuthorized));
+        }
+
+        let percentage_to_disburse = disburse_maturity.percentage_to_disburse;
+        if percentage_to_disburse > 100 || percentage_to_disburse == 0 {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                "The percentage of maturity to disburse must be a value between 1 and 100 (inclusive).",
+            ));
+        }
+
+        if num_maturity_disbursements_in_progress >= MAX_NUM_MATURITY_DISBURSEMENTS_IN_PROGRESS {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                format!(
+                    "The neuron already has {} maturity disbursements in progress, which is the maximum.",
+                    num_maturity_disbursements_in_progress
+                ),
+            ));
+        }
+
+        let to_account = disburse_maturity.to_account.clone().unwrap_or(AccountPb {
+            owner: Some(*caller),
+            subaccount: None,
+        });
+        AccountIdentifier::try_from(&to_account).map_err(|reason| {
+            GovernanceError::new_with_message(
+                ErrorType::InvalidCommand,
+                format!(
+                    "The account to disburse the maturity to is invalid: {}",
+                    reason
+                ),
+            )
+        })?;
+
+        let maturity_to_disburse_e8s =
+            neuron_maturity_e8s_equivalent.saturating_mul(percentage_to_disburse as u64) / 100;
+
+        // The maturity modulation is only known when the disbursement is finalized, so we make sure
+        // that the amount is worth a ledger transaction even in the worst case.
+        let worst_case_maturity_modulation = *VALID_MATURITY_MODULATION_BASIS_POINTS_RANGE.start();
+        let worst_case_amount_e8s =
+            apply_maturity_modulation(maturity_to_disburse_e8s, worst_case_maturity_modulation)
+                .map_err(|reason| {
+                    GovernanceError::new_with_message(
+                        ErrorType::PreconditionFailed,
+                        format!(
+                            "Could not apply maturity modulation to {} e8s: {}",
+                            maturity_to_disburse_e8s, reason
+                        ),
+                    )
+                })?;
+        let transaction_fee_e8s = self.transaction_fee();
+        if worst_case_amount_e8s < transaction_fee_e8s {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                format!(
+                    "Disbursing {}% of the neuron's maturity would result in {} e8s after the \
+                     worst-case maturity modulation, which is less than the transaction fee of {} e8s.",
+                    percentage_to_disburse, worst_case_amount_e8s, transaction_fee_e8s
+                ),
+            ));
+        }
+
+        let maturity_disbursement_delay_seconds = self
+            .economics()
+            .maturity_disbursement_delay_seconds
+            .unwrap_or(DEFAULT_MATURITY_DISBURSEMENT_DELAY_SECONDS);
+
+        let in_flight_command = NeuronInFlightCommand {
+            timestamp: now,
+            command: Some(InFlightCommand::SyncCommand(SyncCommand {})),
+        };
+
+        // Lock the neuron so that we're sure that we are not disbursing the maturity in the middle
+        // of another ongoing operation.
+        let _neuron_lock = self.lock_neuron_for_command(id.id, in_flight_command)?;
+
+        self.with_neuron_mut(id, |neuron| {
+            neuron.maturity_e8s_equivalent = neuron
+                .maturity_e8s_equivalent
+                .saturating_sub(maturity_to_disburse_e8s);
+            neuron
+                .maturity_disbursements_in_progress
+                .push(MaturityDisbursement {
+                    amount_e8s: maturity_to_disburse_e8s,
+                    timestamp_of_disbursement_seconds: now,
+                    finalize_disbursement_timestamp_seconds: now
+                        .saturating_add(maturity_disbursement_delay_seconds),
+                    account_to_disburse_to: Some(to_account),
+                });
+        })
+        .expect("Expected the neuron to exist");
+
+        Ok(DisburseMaturityResponse {
+            amount_disbursed_e8s: Some(maturity_to_disburse_e8s),
+        })
+    }
+
     /// Disburse part of the stake of a neuron into a new neuron, possibly
     /// owned by someone else and with a different dissolve delay.
     ///
@@ -3696,6 +3940,12 @@ impl Governance {
     /// retrieve dropped payloads by calling `get_proposal_info` for
     /// each proposal of interest.
     ///
+    /// - Proposals can further be filtered by proposer, action type, NNS
+    /// function, creation time and title. Except for the title, these
+    /// filters are served by `proposal_index`, so only matching proposals
+    /// are visited. If `ascending` is set to true, the oldest proposals are
+    /// returned first, and `after_proposal` can be used to paginate.
+    ///
     /// - If `omit_large_fields` is set to true, some "large fields" such as
     /// CreateServiceNervousSystem's logo and token_logo are omitted (set to
     /// none) from each proposal before returning. This is useful when these
@@ -3713,6 +3963,10 @@ impl Governance {
         let include_reward_status: HashSet<i32> =
             req.include_reward_status.iter().cloned().collect();
         let include_status: HashSet<i32> = req.include_status.iter().cloned().collect();
+        let title_contains = req
+            .title_contains
+            .as_ref()
+            .map(|title_contains| title_contains.to_lowercase());
         let now = self.env.now();
         let proposal_matches_request = |data: &ProposalData| -> bool {
             let topic = data.topic();
@@ -3732,6 +3986,16 @@ impl Governance {
             if !(include_status.is_empty() || include_status.contains(&(data.status() as i32))) {
                 return false;
             }
+            // Filter out proposals by title.
+            if let Some(title_contains) = &title_contains {
+                let title = data
+                    .proposal
+                    .as_ref()
+                    .and_then(|proposal| proposal.title.as_ref());
+                if !title.map_or(false, |title| title.to_lowercase().contains(title_contains)) {
+                    return false;
+                }
+            }
             // Filter out proposals by the visibility of the caller principal
             // when include_all_manage_neuron_proposals is false. When
             // include_all_manage_neuron_proposals is true the proposal is
@@ -3744,23 +4008,55 @@ impl Governance {
         } else {
             req.limit
         } as usize;
+        // Proposals are stored in a sorted map. Restrict the IDs to the
+        // window given by 'after_proposal' and 'before_proposal', if any.
+        let id_range = (
+            req.after_proposal
+                .map_or(Bound::Unbounded, |id| Bound::Excluded(id.id)),
+            req.before_proposal
+                .map_or(Bound::Unbounded, |id| Bound::Excluded(id.id)),
+        );
+        if let (Bound::Excluded(after), Bound::Excluded(before)) = id_range {
+            if after >= before {
+                return ListProposalInfoResponse::default();
+            }
+        }
         let proposals = &self.heap_data.proposals;
-        // Proposals are stored in a sorted map. If 'before_proposal'
-        // is provided, grab all proposals before that, else grab the
-        // whole range.
-        let proposals = if let Some(n) = req.before_proposal {
-            proposals.range(..(n.id))
+        // Use the index to only visit the proposals matching the indexed
+        // filters, if any were specified. Otherwise visit all proposals.
+        let proposer_ids = req
+            .include_proposers
+            .iter()
+            .map(|neuron_id| neuron_id.id)
+            .collect::<Vec<_>>();
+        let index_query = ProposalIndexQuery {
+            proposer_ids: &proposer_ids,
+            action_types: &req.include_action_types,
+            nns_functions: &req.include_nns_functions,
+            created_at_or_after_timestamp_seconds: req.created_at_or_after_timestamp_seconds,
+            created_before_timestamp_seconds: req.created_before_timestamp_seconds,
+        };
+        let proposal_ids: Box<dyn DoubleEndedIterator<Item = u64> + '_> =
+            match self.proposal_index.matching_proposal_ids(&index_query) {
+                Some(proposal_ids) => Box::new(
+                    proposal_ids
+                        .into_iter()
+                        .filter(move |id| id_range.contains(id)),
+                ),
+                None => Box::new(proposals.range(id_range).map(|(id, _)| *id)),
+            };
+        // Now order the IDs, filter, and restrict to 'limit'.
+        let proposal_ids: Box<dyn Iterator<Item = u64> + '_> = if req.ascending.unwrap_or(false) {
+            Box::new(proposal_ids)
         } else {
-            proposals.range(..)
+            Box::new(proposal_ids.rev())
         };
-        // Now reverse the range, filter, and restrict to 'limit'.
-        let proposals = proposals
-            .rev()
-            .filter(|(_, x)| proposal_matches_request(x))
+        let proposals = proposal_ids
+            .filter_map(|id| proposals.get(&id))
+            .filter(|x| proposal_matches_request(x))
             .take(limit);
 
         let proposal_info = proposals
-            .map(|(_, y)| y)
             .map(|pd| self.proposal_data_to_info(pd, &caller_neurons, now, true))
             .collect::<Vec<_>>();
 
@@ -4272,6 +4568,10 @@ impl Governance {
                     if ne.neurons_fund_economics.is_some() {
                         economics.neurons_fund_economics = ne.neurons_fund_economics
                     }
+                    if ne.maturity_disbursement_delay_seconds.is_some() {
+                        economics.maturity_disbursement_delay_seconds =
+                            ne.maturity_disbursement_delay_seconds
+                    }
                 } else {
                     // If for some reason, we don't have an
                     // 'economics' proto, use the proposed one.
@@ -4704,6 +5004,7 @@ impl Governance {
             data.proposal_timestamp_seconds + voting_period_seconds,
             self.closest_proposal_deadline_timestamp_seconds,
         );
+        self.proposal_index.add(pid, &data);
         self.heap_data.proposals.insert(pid, data);
         self.process_proposal(pid);
     }
@@ -6072,6 +6373,9 @@ impl Governance {
             Some(Command::StakeMaturity(s)) => self
                 .stake_maturity_of_neuron(&id, caller, s)
                 .map(|(response, _)| ManageNeuronResponse::stake_maturity_response(response)),
+            Some(Command::DisburseMaturity(d)) => self
+                .disburse_maturity(&id, caller, d)
+                .map(ManageNeuronResponse::disburse_maturity_response),
             Some(Command::Split(s)) => self
                 .split_neuron(&id, caller, s)
                 .await
@@ -6206,6 +6510,9 @@ impl Governance {
         // Try to spawn neurons (potentially multiple times per day).
         } else if self.can_spawn_neurons() {
             self.spawn_neurons().await;
+        // Try to finalize maturity disbursements (potentially multiple times per day).
+        } else if self.can_finalize_maturity_disbursements() {
+            self.finalize_maturity_disbursements().await;
         } else {
             // This is the lowest-priority async task. All other tasks should have their own
             // `else if`, like the ones above.
@@ -6482,6 +6789,152 @@ impl Governance {
         self.heap_data.spawning_neurons = Some(false);
     }
 
+    /// Lists the neurons whose oldest maturity disbursement is due and that are not locked. Neurons
+    /// whose lock was retained after a failed finalization are left out, so that they do not keep
+    /// the other periodic tasks from running.
+    fn list_neurons_ready_to_finalize_maturity_disbursement(&self) -> Vec<NeuronId> {
+        let now_seconds = self.env.now();
+        self.neuron_store
+            .list_neurons_ready_to_finalize_maturity_disbursement(now_seconds)
+            .into_iter()
+            .filter(|neuron_id| {
+                !self
+                    .heap_data
+                    .in_flight_commands
+                    .contains_key(&neuron_id.id)
+            })
+            .collect()
+    }
+
+    fn can_finalize_maturity_disbursements(&self) -> bool {
+        !self
+            .list_neurons_ready_to_finalize_maturity_disbursement()
+            .is_empty()
+    }
+
+    /// Mints the ICP of the oldest maturity disbursement of each neuron for which it is due,
+    /// modulated by the maturity modulation rate of the day.
+    ///
+    /// Each neuron is locked while its disbursement is being minted, and the disbursement is only
+    /// removed from the neuron once the ledger transfer succeeded. If the transfer fails, the lock
+    /// is retained (like for spawning), so that the disbursement is not minted twice.
+    async fn finalize_maturity_disbursements(&mut self) {
+        let maturity_modulation = match self.heap_data.cached_daily_maturity_modulation_basis_points
+        {
+            None => return,
+            Some(value) => value,
+        };
+
+        // Sanity check that the maturity modulation returned is within bounds.
+        if !VALID_MATURITY_MODULATION_BASIS_POINTS_RANGE.contains(&maturity_modulation) {
+            println!(
+                "{}Maturity modulation (in basis points) out-of-bounds. Should be in range [-500, 500], actually is: {}",
+                LOG_PREFIX, maturity_modulation
+            );
+            return;
+        }
+
+        let now_seconds = self.env.now();
+        for neuron_id in self.list_neurons_ready_to_finalize_maturity_disbursement() {
+            let disbursement = match self.with_neuron(&neuron_id, |neuron| {
+                neuron.maturity_disbursements_in_progress.first().cloned()
+            }) {
+                Ok(Some(disbursement)) => disbursement,
+                Ok(None) => continue,
+                Err(err) => {
+                    println!(
+                        "{}Could not read maturity disbursements of neuron {:?}: {}",
+                        LOG_PREFIX, neuron_id, err
+                    );
+                    continue;
+                }
+            };
+
+            let to_account = match disbursement
+                .account_to_disburse_to
+                .as_ref()
+                .ok_or_else(|| "Account is missing".to_string())
+                .and_then(AccountIdentifier::try_from)
+            {
+                Ok(to_account) => to_account,
+                Err(err) => {
+                    println!(
+                        "{}Invalid maturity disbursement {:?} for neuron {:?}: {}, skipping",
+                        LOG_PREFIX, disbursement, neuron_id, err
+                    );
+                    continue;
+                }
+            };
+
+            let amount_e8s = match apply_maturity_modulation(
+                disbursement.amount_e8s,
+                maturity_modulation,
+            ) {
+                Ok(amount_e8s) => amount_e8s,
+                Err(err) => {
+                    println!(
+                        "{}Could not apply modulation to {:?} for neuron {:?} due to {:?}, skipping",
+                        LOG_PREFIX, disbursement.amount_e8s, neuron_id, err
+                    );
+                    continue;
+                }
+            };
+
+            let in_flight_command = NeuronInFlightCommand {
+                timestamp: now_seconds,
+                command: Some(InFlightCommand::FinalizeDisburseMaturity(
+                    FinalizeDisburseMaturity {
+                        amount_to_be_disbursed_e8s: amount_e8s,
+                        to_account: disbursement.account_to_disburse_to.clone(),
+                    },
+                )),
+            };
+            let mut lock = match self.lock_neuron_for_command(neuron_id.id, in_flight_command) {
+                Ok(lock) => lock,
+                Err(error) => {
+                    println!(
+                        "{}Tried to finalize maturity disbursement but neuron was already locked: {:?}. Error: {:?}",
+                        LOG_PREFIX, neuron_id, error,
+                    );
+                    continue;
+                }
+            };
+
+            // Do the transfer, this is a minting transfer, from the governance canister's
+            // (which is also the minting canister) main account into the chosen account.
+            match self
+                .ledger
+                .transfer_funds(
+                    amount_e8s,
+                    0, // Minting transfer don't pay a fee.
+                    None,
+                    to_account,
+                    now_seconds,
+                )
+                .await
+            {
+                Ok(block_height) => {
+                    let remove_result = self.with_neuron_mut(&neuron_id, |neuron| {
+                        neuron.maturity_disbursements_in_progress.remove(0)
+                    });
+                    println!(
+                        "{}Finalized maturity disbursement {:?} of neuron {:?}, minted {} e8s at block {}. Result of removing it from the neuron: {:?}",
+                        LOG_PREFIX, disbursement, neuron_id, amount_e8s, block_height, remove_result,
+                    );
+                }
+                Err(error) => {
+                    // Retain the neuron lock, the neuron won't be able to undergo stake changing
+                    // operations, nor finalize this disbursement again, until this is fixed.
+                    lock.retain();
+                    println!(
+                        "{}Error finalizing maturity disbursement {:?} of neuron {:?}. Ledger update failed with err: {:?}.",
+                        LOG_PREFIX, disbursement, neuron_id, error,
+                    );
+                }
+            }
+        }
+    }
+
     /// Return `true` if rewards should be distributed, `false` otherwise
     fn should_distribute_rewards(&self) -> bool {
         let latest_distribution_nominal_end_timestamp_seconds =
@@ -0,0 +1,189 @@
+use crate::{
+    governance::{
+        tests::{MockEnvironment, StubCMC, StubIcpLedger},
+        Governance, MAX_NUM_MATURITY_DISBURSEMENTS_IN_PROGRESS,
+    },
+    pb::v1::{
+        governance_error::ErrorType, manage_neuron::DisburseMaturity,
+        manage_neuron_response::DisburseMaturityResponse, neuron, Account,
+        Governance as GovernanceProto, MaturityDisbursement, NetworkEconomics, Neuron,
+    },
+};
+use ic_base_types::PrincipalId;
+use ic_nervous_system_common::ONE_DAY_SECONDS;
+use ic_nns_common::pb::v1::NeuronId;
+use maplit::btreemap;
+
+const NOW_SECONDS: u64 = 1_700_000_000;
+
+fn governance_with_neuron(controller: PrincipalId, maturity_e8s_equivalent: u64) -> Governance {
+    let neuron = Neuron {
+        id: Some(NeuronId { id: 1 }),
+        controller: Some(controller),
+        cached_neuron_stake_e8s: 23,
+        account: b"a__4___8__12__16__20__24__28__32".to_vec(),
+        // One year
+        dissolve_state: Some(neuron::DissolveState::DissolveDelaySeconds(31557600)),
+        maturity_e8s_equivalent,
+        ..Default::default()
+    };
+
+    Governance::new(
+        GovernanceProto {
+            neurons: btreemap! {
+                1 => neuron
+            },
+            economics: Some(NetworkEconomics::with_default_values()),
+            ..GovernanceProto::default()
+        },
+        Box::new(MockEnvironment::new(vec![], NOW_SECONDS)),
+        Box::new(StubIcpLedger {}),
+        Box::new(StubCMC {}),
+    )
+}
+
+#[test]
+fn test_disburse_maturity() {
+    let principal_1 = PrincipalId::new_user_test_id(1);
+    let mut governance = governance_with_neuron(principal_1, 1_000_000_000);
+
+    let request = DisburseMaturity {
+        percentage_to_disburse: 40,
+        to_account: None,
+    };
+    let response = governance
+        .disburse_maturity(&NeuronId { id: 1 }, &principal_1, &request)
+        .expect("Expected call to succeed");
+
+    assert_eq!(
+        response,
+        DisburseMaturityResponse {
+            amount_disbursed_e8s: Some(400_000_000),
+        }
+    );
+    let (maturity_e8s_equivalent, maturity_disbursements_in_progress) = governance
+        .with_neuron(&NeuronId { id: 1 }, |neuron| {
+            (
+                neuron.maturity_e8s_equivalent,
+                neuron.maturity_disbursements_in_progress.clone(),
+            )
+        })
+        .unwrap();
+    assert_eq!(maturity_e8s_equivalent, 600_000_000);
+    // Without a `to_account`, the maturity is disbursed to the caller.
+    assert_eq!(
+        maturity_disbursements_in_progress,
+        vec![MaturityDisbursement {
+            amount_e8s: 400_000_000,
+            timestamp_of_disbursement_seconds: NOW_SECONDS,
+            finalize_disbursement_timestamp_seconds: NOW_SECONDS + 7 * ONE_DAY_SECONDS,
+            account_to_disburse_to: Some(Account {
+                owner: Some(principal_1),
+                subaccount: None,
+            }),
+        }]
+    );
+}
+
+fn assert_disburse_maturity_fails(
+    governance: &mut Governance,
+    caller: &PrincipalId,
+    request: DisburseMaturity,
+    expected_error_type: ErrorType,
+) {
+    let error = governance
+        .disburse_maturity(&NeuronId { id: 1 }, caller, &request)
+        .expect_err("Expected call to fail");
+    assert_eq!(error.error_type, expected_error_type as i32, "{:?}", error);
+}
+
+#[test]
+fn test_disburse_maturity_rejects_invalid_requests() {
+    let principal_1 = PrincipalId::new_user_test_id(1);
+    let principal_2 = PrincipalId::new_user_test_id(2);
+    let mut governance = governance_with_neuron(principal_1, 1_000_000_000);
+
+    // Not the controller.
+    assert_disburse_maturity_fails(
+        &mut governance,
+        &principal_2,
+        DisburseMaturity {
+            percentage_to_disburse: 40,
+            to_account: None,
+        },
+        ErrorType::NotAuthorized,
+    );
+    // Percentage out of range.
+    for percentage_to_disburse in [0, 101] {
+        assert_disburse_maturity_fails(
+            &mut governance,
+            &principal_1,
+            DisburseMaturity {
+                percentage_to_disburse,
+                to_account: None,
+            },
+            ErrorType::PreconditionFailed,
+        );
+    }
+    // Subaccount of the wrong length.
+    assert_disburse_maturity_fails(
+        &mut governance,
+        &principal_1,
+        DisburseMaturity {
+            percentage_to_disburse: 40,
+            to_account: Some(Account {
+                owner: Some(principal_2),
+                subaccount: Some(vec![1, 2, 3]),
+            }),
+        },
+        ErrorType::InvalidCommand,
+    );
+
+    // Nothing should have changed.
+    governance
+        .with_neuron(&NeuronId { id: 1 }, |neuron| {
+            assert_eq!(neuron.maturity_e8s_equivalent, 1_000_000_000);
+            assert_eq!(neuron.maturity_disbursements_in_progress, vec![]);
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_disburse_maturity_rejects_amounts_below_transaction_fee() {
+    let principal_1 = PrincipalId::new_user_test_id(1);
+    let transaction_fee_e8s = NetworkEconomics::with_default_values().transaction_fee_e8s;
+    // After the worst-case maturity modulation (-5%), this is just below the transaction fee.
+    let mut governance = governance_with_neuron(principal_1, transaction_fee_e8s);
+
+    assert_disburse_maturity_fails(
+        &mut governance,
+        &principal_1,
+        DisburseMaturity {
+            percentage_to_disburse: 100,
+            to_account: None,
+        },
+        ErrorType::PreconditionFailed,
+    );
+}
+
+#[test]
+fn test_disburse_maturity_limits_disbursements_in_progress() {
+    let principal_1 = PrincipalId::new_user_test_id(1);
+    let mut governance = governance_with_neuron(principal_1, 1_000_000_000);
+    let request = DisburseMaturity {
+        percentage_to_disburse: 1,
+        to_account: None,
+    };
+
+    for _ in 0..MAX_NUM_MATURITY_DISBURSEMENTS_IN_PROGRESS {
+        governance
+            .disburse_maturity(&NeuronId { id: 1 }, &principal_1, &request)
+            .expect("Expected call to succeed");
+    }
+    assert_disburse_maturity_fails(
+        &mut governance,
+        &principal_1,
+        request,
+        ErrorType::PreconditionFailed,
+    );
+}
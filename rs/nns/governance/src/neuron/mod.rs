@@ -293,6 +293,15 @@ impl Neuron {
             && self.staked_maturity_e8s_equivalent.unwrap_or(0) > 0
     }
 
+    /// Whether the oldest maturity disbursement in progress (if any) is due.
+    pub(crate) fn ready_to_finalize_maturity_disbursement(&self, now_seconds: u64) -> bool {
+        self.maturity_disbursements_in_progress
+            .first()
+            .map_or(false, |disbursement| {
+                disbursement.finalize_disbursement_timestamp_seconds <= now_seconds
+            })
+    }
+
     pub(crate) fn unstake_maturity(&mut self, now_seconds: u64) {
         if self.ready_to_unstake_maturity(now_seconds) {
             self.maturity_e8s_equivalent = self
@@ -682,7 +691,8 @@ impl Neuron {
     /// The exact criteria is subject to change. Currently, all of the following must hold:
     ///
     ///     1. Not seed or ect: NeuronType is not NeuronType::Seed or NeuronType::Ect
-    ///     2. Not funded: No stake, and no (unstaked) maturity.
+    ///     2. Not funded: No stake, no (unstaked) maturity, and no maturity disbursements in
+    ///        progress.
     ///     3. Dissolved sufficiently "long ago": Precisely, dissolved as of now - 2 weeks.
     ///     4. Member of the Neuron's Fund.
     ///
@@ -748,7 +758,7 @@ impl Neuron {
 
     pub fn is_funded(&self) -> bool {
         let amount_e8s = self.stake_e8s() + self.maturity_e8s_equivalent;
-        amount_e8s > 0
+        amount_e8s > 0 || !self.maturity_disbursements_in_progress.is_empty()
     }
 
     /// If not dissolving, returns None. Otherwise, returns Some Unix timestamp (seconds) when the
@@ -4,7 +4,8 @@ use crate::{
     pb::v1::{
         abridged_neuron::DissolveState as AbridgedNeuronDissolveState,
         neuron::{DissolveState as NeuronDissolveState, Followees},
-        AbridgedNeuron, BallotInfo, KnownNeuronData, Neuron as NeuronProto, NeuronStakeTransfer,
+        AbridgedNeuron, BallotInfo, KnownNeuronData, MaturityDisbursement, Neuron as NeuronProto,
+        NeuronStakeTransfer,
     },
 };
 use ic_base_types::PrincipalId;
@@ -99,6 +100,8 @@ pub struct Neuron {
     /// The type of the Neuron. See \[NeuronType\] for a description
     /// of the different states.
     pub neuron_type: Option<i32>,
+    /// Maturity disbursements that have been initiated but not yet finalized, oldest first.
+    pub maturity_disbursements_in_progress: Vec<MaturityDisbursement>,
 }
 
 impl Neuron {
@@ -156,6 +159,7 @@ impl From<Neuron> for NeuronProto {
             joined_community_fund_timestamp_seconds,
             known_neuron_data,
             neuron_type,
+            maturity_disbursements_in_progress,
         } = neuron;
 
         let id = Some(id);
@@ -188,6 +192,7 @@ impl From<Neuron> for NeuronProto {
             joined_community_fund_timestamp_seconds,
             known_neuron_data,
             neuron_type,
+            maturity_disbursements_in_progress,
         }
     }
 }
@@ -218,6 +223,7 @@ impl TryFrom<NeuronProto> for Neuron {
             joined_community_fund_timestamp_seconds,
             known_neuron_data,
             neuron_type,
+            maturity_disbursements_in_progress,
         } = proto;
 
         let id = id.ok_or("Neuron ID is missing")?;
@@ -250,6 +256,7 @@ impl TryFrom<NeuronProto> for Neuron {
             joined_community_fund_timestamp_seconds,
             known_neuron_data,
             neuron_type,
+            maturity_disbursements_in_progress,
         })
     }
 }
@@ -291,6 +298,7 @@ impl From<NeuronDissolveState> for AbridgedNeuronDissolveState {
 ///         hot_keys,
 ///         recent_ballots,
 ///         followees,
+///         maturity_disbursements_in_progress,
 ///
 ///         known_neuron_data,
 ///         transfer,
@@ -310,6 +318,7 @@ pub struct DecomposedNeuron {
     pub hot_keys: Vec<PrincipalId>,
     pub recent_ballots: Vec<BallotInfo>,
     pub followees: HashMap</* topic ID */ i32, Followees>,
+    pub maturity_disbursements_in_progress: Vec<MaturityDisbursement>,
 
     // Singletons
     pub known_neuron_data: Option<KnownNeuronData>,
@@ -341,6 +350,7 @@ impl TryFrom<Neuron> for DecomposedNeuron {
             joined_community_fund_timestamp_seconds,
             known_neuron_data,
             neuron_type,
+            maturity_disbursements_in_progress,
         } = source;
 
         let account = subaccount.to_vec();
@@ -377,6 +387,7 @@ impl TryFrom<Neuron> for DecomposedNeuron {
             hot_keys,
             recent_ballots,
             followees,
+            maturity_disbursements_in_progress,
 
             // Singletons
             known_neuron_data,
@@ -394,6 +405,7 @@ impl From<DecomposedNeuron> for Neuron {
             hot_keys,
             recent_ballots,
             followees,
+            maturity_disbursements_in_progress,
 
             known_neuron_data,
             transfer,
@@ -445,6 +457,7 @@ impl From<DecomposedNeuron> for Neuron {
             joined_community_fund_timestamp_seconds,
             known_neuron_data,
             neuron_type,
+            maturity_disbursements_in_progress,
         }
     }
 }
@@ -484,6 +497,8 @@ pub struct NeuronBuilder {
     staked_maturity_e8s_equivalent: Option<u64>,
     #[cfg(test)]
     known_neuron_data: Option<KnownNeuronData>,
+    #[cfg(test)]
+    maturity_disbursements_in_progress: Vec<MaturityDisbursement>,
 }
 
 impl NeuronBuilder {
@@ -522,6 +537,8 @@ impl NeuronBuilder {
             staked_maturity_e8s_equivalent: None,
             #[cfg(test)]
             known_neuron_data: None,
+            #[cfg(test)]
+            maturity_disbursements_in_progress: Vec::new(),
         }
     }
 
@@ -632,6 +649,15 @@ impl NeuronBuilder {
         self
     }
 
+    #[cfg(test)]
+    pub fn with_maturity_disbursements_in_progress(
+        mut self,
+        maturity_disbursements_in_progress: Vec<MaturityDisbursement>,
+    ) -> Self {
+        self.maturity_disbursements_in_progress = maturity_disbursements_in_progress;
+        self
+    }
+
     pub fn build(self) -> Neuron {
         let NeuronBuilder {
             id,
@@ -659,6 +685,8 @@ impl NeuronBuilder {
             staked_maturity_e8s_equivalent,
             #[cfg(test)]
             known_neuron_data,
+            #[cfg(test)]
+            maturity_disbursements_in_progress,
         } = self;
 
         let auto_stake_maturity = if auto_stake_maturity {
@@ -678,6 +706,8 @@ impl NeuronBuilder {
         let staked_maturity_e8s_equivalent = None;
         #[cfg(not(test))]
         let known_neuron_data = None;
+        #[cfg(not(test))]
+        let maturity_disbursements_in_progress = Vec::new();
 
         Neuron {
             id,
@@ -700,6 +730,7 @@ impl NeuronBuilder {
             joined_community_fund_timestamp_seconds,
             known_neuron_data,
             neuron_type,
+            maturity_disbursements_in_progress,
         }
     }
 }
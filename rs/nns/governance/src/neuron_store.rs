@@ -257,6 +257,8 @@ pub struct NeuronStore {
     ///   0 staked maturity), so no inactive neurons need to unstake maturity.
     /// - `list_ready_to_spawn_neuron_ids`: inactive neurons must have 0 maturity, and spawning
     ///   neurons must have maturity.
+    /// - `list_neurons_ready_to_finalize_maturity_disbursement`: neurons with maturity
+    ///   disbursements in progress are considered funded, and therefore not inactive.
     heap_neurons: BTreeMap<u64, Neuron>,
 
     /// Cached data structure that (for each topic) maps a followee to
@@ -696,6 +698,15 @@ impl NeuronStore {
         self.map_heap_neurons_filtered(filter, |neuron| neuron.id())
     }
 
+    /// List all neuron ids whose oldest maturity disbursement in progress is due.
+    pub fn list_neurons_ready_to_finalize_maturity_disbursement(
+        &self,
+        now_seconds: u64,
+    ) -> Vec<NeuronId> {
+        let filter = |neuron: &Neuron| neuron.ready_to_finalize_maturity_disbursement(now_seconds);
+        self.map_heap_neurons_filtered(filter, |neuron| neuron.id())
+    }
+
     /// List all neuron ids of known neurons
     pub fn list_known_neuron_ids(&self) -> Vec<NeuronId> {
         with_stable_neuron_indexes(|indexes| indexes.known_neuron().list_known_neuron_ids())
@@ -1,2 +1,3 @@
 pub mod create_service_nervous_system;
+pub(crate) mod proposal_index;
 pub mod proposal_submission;
@@ -0,0 +1,193 @@
+use crate::pb::v1::{proposal::Action, ProposalData};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Bound,
+};
+
+/// Secondary indexes over the proposals in `HeapGovernanceData::proposals`, used to serve the
+/// filters of `list_proposals` without scanning every proposal.
+///
+/// Only fields that never change after a proposal is made are indexed, so the index only needs
+/// to be updated when proposals are inserted or garbage collected.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ProposalIndex {
+    proposer_to_proposal_ids: BTreeMap<u64, BTreeSet<u64>>,
+    action_type_to_proposal_ids: BTreeMap<&'static str, BTreeSet<u64>>,
+    nns_function_to_proposal_ids: BTreeMap<i32, BTreeSet<u64>>,
+    creation_timestamp_and_proposal_ids: BTreeSet<(u64, u64)>,
+}
+
+/// The subset of the `ListProposalInfo` filters that can be served by `ProposalIndex`. Within
+/// each filter, proposals matching any of the given values are selected; proposals must match
+/// all non-empty filters.
+#[derive(Debug, Default)]
+pub(crate) struct ProposalIndexQuery<'a> {
+    pub proposer_ids: &'a [u64],
+    pub action_types: &'a [String],
+    pub nns_functions: &'a [i32],
+    pub created_at_or_after_timestamp_seconds: Option<u64>,
+    pub created_before_timestamp_seconds: Option<u64>,
+}
+
+impl ProposalIndexQuery<'_> {
+    fn is_empty(&self) -> bool {
+        self.proposer_ids.is_empty()
+            && self.action_types.is_empty()
+            && self.nns_functions.is_empty()
+            && self.created_at_or_after_timestamp_seconds.is_none()
+            && self.created_before_timestamp_seconds.is_none()
+    }
+}
+
+impl ProposalIndex {
+    pub fn new(proposals: &BTreeMap<u64, ProposalData>) -> Self {
+        let mut index = Self::default();
+        for (proposal_id, proposal_data) in proposals {
+            index.add(*proposal_id, proposal_data);
+        }
+        index
+    }
+
+    pub fn add(&mut self, proposal_id: u64, proposal_data: &ProposalData) {
+        if let Some(proposer) = &proposal_data.proposer {
+            self.proposer_to_proposal_ids
+                .entry(proposer.id)
+                .or_default()
+                .insert(proposal_id);
+        }
+        if let Some(action) = action(proposal_data) {
+            self.action_type_to_proposal_ids
+                .entry(action.as_str_name())
+                .or_default()
+                .insert(proposal_id);
+            if let Action::ExecuteNnsFunction(execute_nns_function) = action {
+                self.nns_function_to_proposal_ids
+                    .entry(execute_nns_function.nns_function)
+                    .or_default()
+                    .insert(proposal_id);
+            }
+        }
+        self.creation_timestamp_and_proposal_ids
+            .insert((proposal_data.proposal_timestamp_seconds, proposal_id));
+    }
+
+    pub fn remove(&mut self, proposal_id: u64, proposal_data: &ProposalData) {
+        if let Some(proposer) = &proposal_data.proposer {
+            remove_from_index(
+                &mut self.proposer_to_proposal_ids,
+                &proposer.id,
+                proposal_id,
+            );
+        }
+        if let Some(action) = action(proposal_data) {
+            remove_from_index(
+                &mut self.action_type_to_proposal_ids,
+                &action.as_str_name(),
+                proposal_id,
+            );
+            if let Action::ExecuteNnsFunction(execute_nns_function) = action {
+                remove_from_index(
+                    &mut self.nns_function_to_proposal_ids,
+                    &execute_nns_function.nns_function,
+                    proposal_id,
+                );
+            }
+        }
+        self.creation_timestamp_and_proposal_ids
+            .remove(&(proposal_data.proposal_timestamp_seconds, proposal_id));
+    }
+
+    /// Returns the IDs of the proposals matching `query`, or None if `query` does not restrict
+    /// the proposals at all, in which case every proposal matches.
+    pub fn matching_proposal_ids(&self, query: &ProposalIndexQuery) -> Option<BTreeSet<u64>> {
+        if query.is_empty() {
+            return None;
+        }
+
+        let mut candidates: Vec<BTreeSet<u64>> = vec![];
+        if !query.proposer_ids.is_empty() {
+            candidates.push(union_of(
+                &self.proposer_to_proposal_ids,
+                query.proposer_ids.iter(),
+            ));
+        }
+        if !query.action_types.is_empty() {
+            candidates.push(union_of(
+                &self.action_type_to_proposal_ids,
+                query.action_types.iter().map(String::as_str),
+            ));
+        }
+        if !query.nns_functions.is_empty() {
+            candidates.push(union_of(
+                &self.nns_function_to_proposal_ids,
+                query.nns_functions.iter(),
+            ));
+        }
+        if query.created_at_or_after_timestamp_seconds.is_some()
+            || query.created_before_timestamp_seconds.is_some()
+        {
+            let lower_bound = match query.created_at_or_after_timestamp_seconds {
+                Some(timestamp_seconds) => Bound::Included((timestamp_seconds, u64::MIN)),
+                None => Bound::Unbounded,
+            };
+            let upper_bound = match query.created_before_timestamp_seconds {
+                Some(timestamp_seconds) => Bound::Excluded((timestamp_seconds, u64::MIN)),
+                None => Bound::Unbounded,
+            };
+            candidates.push(
+                match (
+                    query.created_at_or_after_timestamp_seconds,
+                    query.created_before_timestamp_seconds,
+                ) {
+                    // An inverted time window matches nothing, but `range` would panic on it.
+                    (Some(start), Some(end)) if start > end => BTreeSet::new(),
+                    _ => self
+                        .creation_timestamp_and_proposal_ids
+                        .range((lower_bound, upper_bound))
+                        .map(|(_, proposal_id)| *proposal_id)
+                        .collect(),
+                },
+            );
+        }
+
+        // Intersect starting from the smallest set to keep the work proportional to it.
+        candidates.sort_by_key(BTreeSet::len);
+        let mut candidates = candidates.into_iter();
+        let smallest = candidates.next().unwrap_or_default();
+        Some(candidates.fold(smallest, |result, candidate| {
+            result.intersection(&candidate).copied().collect()
+        }))
+    }
+}
+
+fn action(proposal_data: &ProposalData) -> Option<&Action> {
+    proposal_data
+        .proposal
+        .as_ref()
+        .and_then(|proposal| proposal.action.as_ref())
+}
+
+fn union_of<'a, K, Q>(
+    index: &BTreeMap<K, BTreeSet<u64>>,
+    keys: impl Iterator<Item = &'a Q>,
+) -> BTreeSet<u64>
+where
+    K: Ord + std::borrow::Borrow<Q>,
+    Q: Ord + ?Sized + 'a,
+{
+    keys.filter_map(|key| index.get(key))
+        .flat_map(|proposal_ids| proposal_ids.iter().copied())
+        .collect()
+}
+
+fn remove_from_index<K: Ord>(index: &mut BTreeMap<K, BTreeSet<u64>>, key: &K, proposal_id: u64) {
+    if let Some(proposal_ids) = index.get_mut(key) {
+        proposal_ids.remove(&proposal_id);
+        if proposal_ids.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod proposal_index_tests;
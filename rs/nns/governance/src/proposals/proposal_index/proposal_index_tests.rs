@@ -0,0 +1,138 @@
+use crate::{
+    pb::v1::{proposal::Action, ExecuteNnsFunction, Motion, NnsFunction, Proposal, ProposalData},
+    proposals::proposal_index::{ProposalIndex, ProposalIndexQuery},
+};
+use ic_nns_common::pb::v1::{NeuronId, ProposalId};
+use maplit::{btreemap, btreeset};
+use std::collections::BTreeMap;
+
+fn proposal_data(
+    id: u64,
+    proposer: u64,
+    proposal_timestamp_seconds: u64,
+    action: Action,
+) -> ProposalData {
+    ProposalData {
+        id: Some(ProposalId { id }),
+        proposer: Some(NeuronId { id: proposer }),
+        proposal_timestamp_seconds,
+        proposal: Some(Proposal {
+            action: Some(action),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn motion() -> Action {
+    Action::Motion(Motion {
+        motion_text: "motion".to_string(),
+    })
+}
+
+fn execute_nns_function(nns_function: NnsFunction) -> Action {
+    Action::ExecuteNnsFunction(ExecuteNnsFunction {
+        nns_function: nns_function as i32,
+        payload: vec![],
+    })
+}
+
+fn test_proposals() -> BTreeMap<u64, ProposalData> {
+    btreemap! {
+        1 => proposal_data(1, 10, 100, motion()),
+        2 => proposal_data(2, 20, 200, execute_nns_function(NnsFunction::NnsCanisterUpgrade)),
+        3 => proposal_data(3, 10, 300, execute_nns_function(NnsFunction::HardResetNnsRootToVersion)),
+        4 => proposal_data(4, 20, 400, motion()),
+    }
+}
+
+#[test]
+fn test_empty_query_matches_everything() {
+    let index = ProposalIndex::new(&test_proposals());
+
+    assert_eq!(
+        index.matching_proposal_ids(&ProposalIndexQuery::default()),
+        None
+    );
+}
+
+#[test]
+fn test_single_filters() {
+    let index = ProposalIndex::new(&test_proposals());
+
+    assert_eq!(
+        index.matching_proposal_ids(&ProposalIndexQuery {
+            proposer_ids: &[10],
+            ..Default::default()
+        }),
+        Some(btreeset! {1, 3})
+    );
+    assert_eq!(
+        index.matching_proposal_ids(&ProposalIndexQuery {
+            action_types: &["ACTION_MOTION".to_string()],
+            ..Default::default()
+        }),
+        Some(btreeset! {1, 4})
+    );
+    assert_eq!(
+        index.matching_proposal_ids(&ProposalIndexQuery {
+            nns_functions: &[NnsFunction::NnsCanisterUpgrade as i32],
+            ..Default::default()
+        }),
+        Some(btreeset! {2})
+    );
+    assert_eq!(
+        index.matching_proposal_ids(&ProposalIndexQuery {
+            created_at_or_after_timestamp_seconds: Some(200),
+            created_before_timestamp_seconds: Some(400),
+            ..Default::default()
+        }),
+        Some(btreeset! {2, 3})
+    );
+    assert_eq!(
+        index.matching_proposal_ids(&ProposalIndexQuery {
+            created_at_or_after_timestamp_seconds: Some(400),
+            created_before_timestamp_seconds: Some(200),
+            ..Default::default()
+        }),
+        Some(btreeset! {})
+    );
+}
+
+#[test]
+fn test_values_within_a_filter_are_combined_and_filters_are_intersected() {
+    let index = ProposalIndex::new(&test_proposals());
+
+    assert_eq!(
+        index.matching_proposal_ids(&ProposalIndexQuery {
+            proposer_ids: &[10, 20],
+            action_types: &["ACTION_EXECUTE_NNS_FUNCTION".to_string()],
+            created_at_or_after_timestamp_seconds: Some(300),
+            ..Default::default()
+        }),
+        Some(btreeset! {3})
+    );
+}
+
+#[test]
+fn test_remove() {
+    let proposals = test_proposals();
+    let mut index = ProposalIndex::new(&proposals);
+
+    index.remove(1, &proposals[&1]);
+
+    assert_eq!(
+        index.matching_proposal_ids(&ProposalIndexQuery {
+            proposer_ids: &[10],
+            ..Default::default()
+        }),
+        Some(btreeset! {3})
+    );
+    assert_eq!(
+        index.matching_proposal_ids(&ProposalIndexQuery {
+            created_before_timestamp_seconds: Some(250),
+            ..Default::default()
+        }),
+        Some(btreeset! {2})
+    );
+}
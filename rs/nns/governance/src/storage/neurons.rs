@@ -2,7 +2,8 @@ use crate::{
     neuron::{DecomposedNeuron, Neuron},
     neuron_store::NeuronStoreError,
     pb::v1::{
-        neuron::Followees, AbridgedNeuron, BallotInfo, KnownNeuronData, NeuronStakeTransfer, Topic,
+        neuron::Followees, AbridgedNeuron, BallotInfo, KnownNeuronData, MaturityDisbursement,
+        NeuronStakeTransfer, Topic,
     },
     storage::validate_stable_btree_map,
 };
@@ -38,6 +39,7 @@ pub(crate) struct StableNeuronStoreBuilder<Memory> {
     pub hot_keys: Memory,
     pub recent_ballots: Memory,
     pub followees: Memory,
+    pub maturity_disbursements: Memory,
 
     // Singletons
     pub known_neuron_data: Memory,
@@ -56,6 +58,7 @@ where
             hot_keys,
             recent_ballots,
             followees,
+            maturity_disbursements,
 
             // Singletons
             known_neuron_data,
@@ -69,6 +72,7 @@ where
             hot_keys_map: StableBTreeMap::init(hot_keys),
             followees_map: StableBTreeMap::init(followees),
             recent_ballots_map: StableBTreeMap::init(recent_ballots),
+            maturity_disbursements_map: StableBTreeMap::init(maturity_disbursements),
 
             // Singletons
             known_neuron_data_map: StableBTreeMap::init(known_neuron_data),
@@ -87,6 +91,8 @@ where
     hot_keys_map: StableBTreeMap<(NeuronId, /* index */ u64), Principal, Memory>,
     recent_ballots_map: StableBTreeMap<(NeuronId, /* index */ u64), BallotInfo, Memory>,
     followees_map: StableBTreeMap<FolloweesKey, NeuronId, Memory>,
+    maturity_disbursements_map:
+        StableBTreeMap<(NeuronId, /* index */ u64), MaturityDisbursement, Memory>,
 
     // Singletons
     known_neuron_data_map: StableBTreeMap<NeuronId, KnownNeuronData, Memory>,
@@ -138,6 +144,7 @@ where
             hot_keys,
             recent_ballots,
             followees,
+            maturity_disbursements_in_progress,
 
             known_neuron_data,
             transfer,
@@ -176,6 +183,11 @@ where
         );
         update_repeated_field(neuron_id, recent_ballots, &mut self.recent_ballots_map);
         self.update_followees(neuron_id, followees);
+        update_repeated_field(
+            neuron_id,
+            maturity_disbursements_in_progress,
+            &mut self.maturity_disbursements_map,
+        );
 
         update_singleton_field(
             neuron_id,
@@ -215,6 +227,7 @@ where
             hot_keys,
             recent_ballots,
             followees,
+            maturity_disbursements_in_progress,
 
             known_neuron_data,
             transfer,
@@ -260,6 +273,13 @@ where
         if followees != old_neuron.followees {
             self.update_followees(neuron_id, followees);
         }
+        if maturity_disbursements_in_progress != old_neuron.maturity_disbursements_in_progress {
+            update_repeated_field(
+                neuron_id,
+                maturity_disbursements_in_progress,
+                &mut self.maturity_disbursements_map,
+            );
+        }
 
         if known_neuron_data != old_neuron.known_neuron_data {
             update_singleton_field(
@@ -293,6 +313,7 @@ where
         update_repeated_field(neuron_id, vec![], &mut self.hot_keys_map);
         update_repeated_field(neuron_id, vec![], &mut self.recent_ballots_map);
         self.update_followees(neuron_id, hashmap![]);
+        update_repeated_field(neuron_id, vec![], &mut self.maturity_disbursements_map);
 
         update_singleton_field(neuron_id, None, &mut self.known_neuron_data_map);
         update_singleton_field(neuron_id, None, &mut self.transfer_map);
@@ -335,6 +356,7 @@ where
         validate_stable_btree_map(&self.hot_keys_map);
         validate_stable_btree_map(&self.recent_ballots_map);
         validate_stable_btree_map(&self.followees_map);
+        validate_stable_btree_map(&self.maturity_disbursements_map);
         validate_stable_btree_map(&self.known_neuron_data_map);
         validate_stable_btree_map(&self.transfer_map);
     }
@@ -345,6 +367,8 @@ where
         let hot_keys = read_repeated_field(neuron_id, &self.hot_keys_map);
         let recent_ballots = read_repeated_field(neuron_id, &self.recent_ballots_map);
         let followees = self.read_followees(neuron_id);
+        let maturity_disbursements_in_progress =
+            read_repeated_field(neuron_id, &self.maturity_disbursements_map);
 
         let known_neuron_data = self.known_neuron_data_map.get(&neuron_id);
         let transfer = self.transfer_map.get(&neuron_id);
@@ -359,6 +383,7 @@ where
                 .collect(),
             recent_ballots,
             followees,
+            maturity_disbursements_in_progress,
 
             known_neuron_data,
             transfer,
@@ -472,6 +497,7 @@ pub(crate) fn new_heap_based() -> StableNeuronStore<VectorMemory> {
         hot_keys: VectorMemory::default(),
         recent_ballots: VectorMemory::default(),
         followees: VectorMemory::default(),
+        maturity_disbursements: VectorMemory::default(),
 
         // Singletons
         known_neuron_data: VectorMemory::default(),
@@ -537,6 +563,22 @@ impl Storable for KnownNeuronData {
     };
 }
 
+impl Storable for MaturityDisbursement {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::from(self.encode_to_vec())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Self::decode(&bytes[..]).expect("Unable to deserialize Neuron.")
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        // How this number was chosen: Similar to how MAX_SIZE was chosen for Neuron.
+        max_size: 256,
+        is_fixed_size: false,
+    };
+}
+
 impl Storable for NeuronStakeTransfer {
     fn to_bytes(&self) -> Cow<'_, [u8]> {
         Cow::from(self.encode_to_vec())
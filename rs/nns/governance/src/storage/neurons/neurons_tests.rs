@@ -2,7 +2,7 @@ use super::*;
 
 use crate::{
     neuron::{DissolveStateAndAge, NeuronBuilder},
-    pb::v1::{abridged_neuron::DissolveState, Vote},
+    pb::v1::{abridged_neuron::DissolveState, Account, Vote},
 };
 use ic_base_types::PrincipalId;
 use ic_nns_common::pb::v1::ProposalId;
@@ -68,6 +68,26 @@ fn create_model_neuron(id: u64) -> Neuron {
         block_height: 404,
         memo: 405,
     }))
+    .with_maturity_disbursements_in_progress(vec![
+        MaturityDisbursement {
+            amount_e8s: 500,
+            timestamp_of_disbursement_seconds: 123_456_789,
+            finalize_disbursement_timestamp_seconds: 124_061_589,
+            account_to_disburse_to: Some(Account {
+                owner: Some(PrincipalId::new_user_test_id(500)),
+                subaccount: None,
+            }),
+        },
+        MaturityDisbursement {
+            amount_e8s: 501,
+            timestamp_of_disbursement_seconds: 123_456_790,
+            finalize_disbursement_timestamp_seconds: 124_061_590,
+            account_to_disburse_to: Some(Account {
+                owner: Some(PrincipalId::new_user_test_id(501)),
+                subaccount: Some(vec![5; 32]),
+            }),
+        },
+    ])
     .build()
 }
 
@@ -93,6 +113,7 @@ fn new_red_herring_neuron(seed: u64) -> Neuron {
         proposal_id: Some(ProposalId { id: seed }),
         vote: Vote::No as i32,
     });
+    result.maturity_disbursements_in_progress[0].amount_e8s = seed;
 
     result.known_neuron_data.as_mut().unwrap().name = format!("Red Herring {}", seed,);
 
@@ -205,7 +226,8 @@ fn test_store_simplest_nontrivial_case() {
     // 5. Update existing neuron.
 
     // Derive neuron_5 from neuron_1 by adding entries to collections (to make
-    // sure the updating collections works).
+    // sure the updating collections works). Maturity disbursements are removed
+    // from the front instead, like when the oldest one is finalized.
     let neuron_5 = {
         let mut hot_keys = neuron_1.hot_keys.clone();
         hot_keys.push(PrincipalId::new_user_test_id(102));
@@ -227,6 +249,10 @@ fn test_store_simplest_nontrivial_case() {
             vote: Vote::Yes as i32,
         });
 
+        let mut maturity_disbursements_in_progress =
+            neuron_1.maturity_disbursements_in_progress.clone();
+        maturity_disbursements_in_progress.remove(0);
+
         let mut known_neuron_data = neuron_1.known_neuron_data.clone();
         known_neuron_data.as_mut().unwrap().name = "I changed my mind".to_string();
 
@@ -239,6 +265,7 @@ fn test_store_simplest_nontrivial_case() {
         neuron.hot_keys = hot_keys;
         neuron.followees = followees;
         neuron.recent_ballots = recent_ballots;
+        neuron.maturity_disbursements_in_progress = maturity_disbursements_in_progress;
 
         neuron.known_neuron_data = known_neuron_data;
         neuron.transfer = transfer;
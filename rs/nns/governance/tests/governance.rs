@@ -49,9 +49,10 @@ use ic_nns_governance::{
         },
         validate_proposal_title, Environment, Governance, HeapGrowthPotential,
         EXECUTE_NNS_FUNCTION_PAYLOAD_LISTING_BYTES_MAX, MAX_DISSOLVE_DELAY_SECONDS,
-        MAX_NEURON_AGE_FOR_AGE_BONUS, MAX_NUMBER_OF_PROPOSALS_WITH_BALLOTS,
-        MIN_DISSOLVE_DELAY_FOR_VOTE_ELIGIBILITY_SECONDS, PROPOSAL_MOTION_TEXT_BYTES_MAX,
-        REWARD_DISTRIBUTION_PERIOD_SECONDS, WAIT_FOR_QUIET_DEADLINE_INCREASE_SECONDS,
+        MAX_LIST_NEURONS_RESULTS, MAX_NEURON_AGE_FOR_AGE_BONUS,
+        MAX_NUMBER_OF_PROPOSALS_WITH_BALLOTS, MIN_DISSOLVE_DELAY_FOR_VOTE_ELIGIBILITY_SECONDS,
+        PROPOSAL_MOTION_TEXT_BYTES_MAX, REWARD_DISTRIBUTION_PERIOD_SECONDS,
+        WAIT_FOR_QUIET_DEADLINE_INCREASE_SECONDS,
     },
     governance_proto_builder::GovernanceProtoBuilder,
     init::GovernanceCanisterInitPayloadBuilder,
@@ -67,24 +68,24 @@ use ic_nns_governance::{
             configure::Operation,
             disburse::Amount,
             ChangeAutoStakeMaturity, ClaimOrRefresh, Command, Configure, Disburse,
-            DisburseToNeuron, IncreaseDissolveDelay, JoinCommunityFund, LeaveCommunityFund,
-            MergeMaturity, NeuronIdOrSubaccount, Spawn, Split, StartDissolving,
+            DisburseMaturity, DisburseToNeuron, IncreaseDissolveDelay, JoinCommunityFund,
+            LeaveCommunityFund, MergeMaturity, NeuronIdOrSubaccount, Spawn, Split, StartDissolving,
         },
-        manage_neuron_response::{self, Command as CommandResponse},
+        manage_neuron_response::{self, Command as CommandResponse, DisburseMaturityResponse},
         neuron::{self, DissolveState, Followees},
         neurons_fund_snapshot::NeuronsFundNeuronPortion,
         proposal::{self, Action, ActionDesc},
         reward_node_provider::{RewardMode, RewardToAccount, RewardToNeuron},
-        settle_neurons_fund_participation_request, swap_background_information,
+        settle_neurons_fund_participation_request, swap_background_information, Account,
         AddOrRemoveNodeProvider, ApproveGenesisKyc, Ballot, BallotChange, BallotInfo,
         BallotInfoChange, CreateServiceNervousSystem, Empty, ExecuteNnsFunction,
         Governance as GovernanceProto, GovernanceChange, GovernanceError,
         IdealMatchedParticipationFunction, KnownNeuron, KnownNeuronData, ListNeurons,
         ListNeuronsResponse, ListProposalInfo, ListProposalInfoResponse, ManageNeuron,
-        ManageNeuronResponse, MostRecentMonthlyNodeProviderRewards, Motion, NetworkEconomics,
-        Neuron, NeuronChange, NeuronState, NeuronType, NeuronsFundData, NeuronsFundParticipation,
-        NeuronsFundSnapshot, NnsFunction, NodeProvider, Proposal, ProposalChange, ProposalData,
-        ProposalDataChange,
+        ManageNeuronResponse, MaturityDisbursement, MostRecentMonthlyNodeProviderRewards, Motion,
+        NetworkEconomics, Neuron, NeuronChange, NeuronState, NeuronType, NeuronsFundData,
+        NeuronsFundParticipation, NeuronsFundSnapshot, NnsFunction, NodeProvider, Proposal,
+        ProposalChange, ProposalData, ProposalDataChange,
         ProposalRewardStatus::{self, AcceptVotes, ReadyToSettle},
         ProposalStatus::{self, Rejected},
         RewardEvent, RewardNodeProvider, RewardNodeProviders, SetDefaultFollowees,
@@ -5472,6 +5473,78 @@ fn test_neuron_spawn_with_subaccount() {
     assert_eq!(child_neuron.maturity_e8s_equivalent, 0);
 }
 
+/// Checks that:
+/// * Disbursing maturity takes it out of the neuron right away, and records a
+///   pending disbursement.
+/// * The ICP is only minted once the maturity disbursement delay has passed,
+///   with the maturity modulation applied.
+#[test]
+fn test_disburse_maturity() {
+    let from = *TEST_NEURON_1_OWNER_PRINCIPAL;
+    let (mut driver, mut gov, id, _) = governance_with_staked_neuron(
+        MIN_DISSOLVE_DELAY_FOR_VOTE_ELIGIBILITY_SECONDS,
+        1_000_000_000,
+        543212234,
+        from,
+        1234,
+    );
+    gov.with_neuron_mut(&id, |neuron| {
+        neuron.maturity_e8s_equivalent = 1_000_000_000;
+    })
+    .expect("Neuron did not exist");
+
+    let to_account = Account {
+        owner: Some(*TEST_NEURON_2_OWNER_PRINCIPAL),
+        subaccount: Some(vec![7; 32]),
+    };
+    let to_account_identifier =
+        AccountIdentifier::new(*TEST_NEURON_2_OWNER_PRINCIPAL, Some(Subaccount([7; 32])));
+    let response = gov
+        .disburse_maturity(
+            &id,
+            &from,
+            &DisburseMaturity {
+                percentage_to_disburse: 40,
+                to_account: Some(to_account.clone()),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        response,
+        DisburseMaturityResponse {
+            amount_disbursed_e8s: Some(400_000_000),
+        }
+    );
+
+    let disbursement_timestamp = driver.now();
+    let neuron = gov.get_full_neuron(&id, &from).unwrap();
+    assert_eq!(neuron.maturity_e8s_equivalent, 600_000_000);
+    assert_eq!(
+        neuron.maturity_disbursements_in_progress,
+        vec![MaturityDisbursement {
+            amount_e8s: 400_000_000,
+            timestamp_of_disbursement_seconds: disbursement_timestamp,
+            finalize_disbursement_timestamp_seconds: disbursement_timestamp + 7 * ONE_DAY_SECONDS,
+            account_to_disburse_to: Some(to_account),
+        }]
+    );
+
+    // Running periodic tasks shouldn't cause the ICP to be minted.
+    run_periodic_tasks_on_governance_often_enough_to_spawn(&mut gov);
+    let neuron = gov.get_full_neuron(&id, &from).unwrap();
+    assert_eq!(neuron.maturity_disbursements_in_progress.len(), 1);
+
+    // Advance the time by one week, should cause the ICP to be minted.
+    driver.advance_time_by(7 * ONE_DAY_SECONDS);
+    run_periodic_tasks_on_governance_often_enough_to_spawn(&mut gov);
+
+    // The fake CMC returns a maturity modulation of 1%.
+    driver.assert_account_contains(&to_account_identifier, 404_000_000);
+    let neuron = gov.get_full_neuron(&id, &from).unwrap();
+    assert_eq!(neuron.maturity_e8s_equivalent, 600_000_000);
+    assert_eq!(neuron.maturity_disbursements_in_progress, vec![]);
+}
+
 /// Checks that:
 /// * Specifying a percentage_to_spawn different from 100 lead to the proper fractional maturity
 /// to be spawned.
@@ -7496,6 +7569,114 @@ fn test_list_proposals() {
     );
 }
 
+/// Tests the filters on proposer, action, NNS function, creation time and
+/// title, as well as listing proposals in ascending order.
+#[test]
+fn test_list_proposals_search() {
+    let proposal_data =
+        |id: u64, proposer: u64, proposal_timestamp_seconds: u64, title: &str, action: Action| {
+            ProposalData {
+                id: Some(ProposalId { id }),
+                proposer: Some(NeuronId { id: proposer }),
+                proposal_timestamp_seconds,
+                proposal: Some(Proposal {
+                    title: Some(title.to_string()),
+                    action: Some(action),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }
+        };
+    let motion = || {
+        Action::Motion(Motion {
+            motion_text: "motion".to_string(),
+        })
+    };
+    let upgrade = || {
+        Action::ExecuteNnsFunction(ExecuteNnsFunction {
+            nns_function: NnsFunction::NnsCanisterUpgrade as i32,
+            payload: vec![],
+        })
+    };
+    let proto = GovernanceProto {
+        economics: Some(NetworkEconomics::with_default_values()),
+        proposals: btreemap! {
+            1 => proposal_data(1, 10, 1000, "Raise the bar", motion()),
+            2 => proposal_data(2, 20, 2000, "Upgrade governance", upgrade()),
+            3 => proposal_data(3, 10, 3000, "Upgrade registry", upgrade()),
+            4 => proposal_data(4, 20, 4000, "Lower the bar", motion()),
+        },
+        ..Default::default()
+    };
+    let driver = fake::FakeDriver::default();
+    let gov = Governance::new(
+        proto,
+        driver.get_fake_env(),
+        driver.get_fake_ledger(),
+        driver.get_fake_cmc(),
+    );
+    let list_proposal_ids =
+        |request: ListProposalInfo| proposal_ids(&gov.list_proposals(&principal(1), &request));
+
+    assert_eq!(
+        list_proposal_ids(ListProposalInfo {
+            include_proposers: vec![NeuronId { id: 10 }],
+            ..Default::default()
+        }),
+        vec![3, 1]
+    );
+    assert_eq!(
+        list_proposal_ids(ListProposalInfo {
+            include_action_types: vec!["ACTION_MOTION".to_string()],
+            ascending: Some(true),
+            ..Default::default()
+        }),
+        vec![1, 4]
+    );
+    assert_eq!(
+        list_proposal_ids(ListProposalInfo {
+            include_nns_functions: vec![NnsFunction::NnsCanisterUpgrade as i32],
+            created_at_or_after_timestamp_seconds: Some(2500),
+            ..Default::default()
+        }),
+        vec![3]
+    );
+    assert_eq!(
+        list_proposal_ids(ListProposalInfo {
+            created_before_timestamp_seconds: Some(3000),
+            ascending: Some(true),
+            ..Default::default()
+        }),
+        vec![1, 2]
+    );
+    assert_eq!(
+        list_proposal_ids(ListProposalInfo {
+            title_contains: Some("UPGRADE".to_string()),
+            ..Default::default()
+        }),
+        vec![3, 2]
+    );
+    // Paging in ascending order.
+    assert_eq!(
+        list_proposal_ids(ListProposalInfo {
+            limit: 2,
+            after_proposal: Some(ProposalId { id: 1 }),
+            ascending: Some(true),
+            ..Default::default()
+        }),
+        vec![2, 3]
+    );
+    // Filters that match nothing.
+    assert_eq!(
+        list_proposal_ids(ListProposalInfo {
+            include_proposers: vec![NeuronId { id: 10 }],
+            title_contains: Some("lower".to_string()),
+            ..Default::default()
+        }),
+        Vec::<u64>::new()
+    );
+}
+
 // A proposal with restricted voting is included only if the caller is allowed
 // to vote on the proposal.
 #[test]
@@ -8351,6 +8532,7 @@ fn test_list_neurons() {
         &ListNeurons {
             include_neurons_readable_by_caller: true,
             neuron_ids: vec![],
+            ..Default::default()
         },
         &p1,
     );
@@ -8372,6 +8554,7 @@ fn test_list_neurons() {
         &ListNeurons {
             include_neurons_readable_by_caller: true,
             neuron_ids: vec![42, 99],
+            ..Default::default()
         },
         &p4,
     );
@@ -8397,6 +8580,7 @@ fn test_list_neurons() {
         &ListNeurons {
             include_neurons_readable_by_caller: true,
             neuron_ids: vec![200],
+            ..Default::default()
         },
         &principal_with_no_neurons,
     );
@@ -8412,6 +8596,178 @@ fn test_list_neurons() {
     );
 }
 
+#[test]
+fn test_list_neurons_pagination_and_filters() {
+    let mut driver = fake::FakeDriver::default();
+    let controller = principal(1);
+    // Neuron n has a stake of n * 100 ICP, except for neuron 7 which is empty.
+    // Odd neurons are dissolving, even neurons are not.
+    let neurons = (1..=7)
+        .map(|id| {
+            let dissolve_state = if id % 2 == 1 {
+                DissolveState::WhenDissolvedTimestampSeconds(driver.now() + ONE_YEAR_SECONDS)
+            } else {
+                DissolveState::DissolveDelaySeconds(ONE_YEAR_SECONDS)
+            };
+            let neuron = Neuron {
+                id: Some(NeuronId { id }),
+                controller: Some(controller),
+                account: driver.random_byte_array().to_vec(),
+                cached_neuron_stake_e8s: if id == 7 { 0 } else { id * 100 * E8 },
+                dissolve_state: Some(dissolve_state),
+                ..Default::default()
+            };
+            (id, neuron)
+        })
+        .collect::<BTreeMap<u64, Neuron>>();
+    let gov = Governance::new(
+        GovernanceProto {
+            neurons,
+            ..Default::default()
+        },
+        driver.get_fake_env(),
+        driver.get_fake_ledger(),
+        driver.get_fake_cmc(),
+    );
+    let list_neuron_ids = |request: ListNeurons| {
+        let response = gov.list_neurons_by_principal(&request, &controller);
+        let full_neuron_ids = response
+            .full_neurons
+            .iter()
+            .map(|neuron| neuron.id.unwrap().id)
+            .collect::<Vec<u64>>();
+        assert_eq!(
+            response
+                .neuron_infos
+                .keys()
+                .copied()
+                .collect::<HashSet<u64>>(),
+            full_neuron_ids.iter().copied().collect::<HashSet<u64>>(),
+        );
+        (full_neuron_ids, response.next_start_after_neuron_id)
+    };
+
+    // Page through all the neurons readable by the caller.
+    let mut pages = vec![];
+    let mut start_after_neuron_id = None;
+    loop {
+        let (page, next_start_after_neuron_id) = list_neuron_ids(ListNeurons {
+            include_neurons_readable_by_caller: true,
+            page_size: Some(2),
+            start_after_neuron_id,
+            ..Default::default()
+        });
+        pages.push(page);
+        if next_start_after_neuron_id.is_none() {
+            break;
+        }
+        start_after_neuron_id = next_start_after_neuron_id;
+    }
+    assert_eq!(pages, vec![vec![1, 2], vec![3, 4], vec![5, 6], vec![7]]);
+
+    // Empty neurons can be excluded, unless they are requested explicitly.
+    assert_eq!(
+        list_neuron_ids(ListNeurons {
+            include_neurons_readable_by_caller: true,
+            include_empty_neurons_readable_by_caller: Some(false),
+            ..Default::default()
+        }),
+        (vec![1, 2, 3, 4, 5, 6], None)
+    );
+    assert_eq!(
+        list_neuron_ids(ListNeurons {
+            neuron_ids: vec![7],
+            include_neurons_readable_by_caller: true,
+            include_empty_neurons_readable_by_caller: Some(false),
+            ..Default::default()
+        }),
+        (vec![1, 2, 3, 4, 5, 6, 7], None)
+    );
+
+    // Filter by state and stake.
+    assert_eq!(
+        list_neuron_ids(ListNeurons {
+            include_neurons_readable_by_caller: true,
+            include_neuron_states: vec![NeuronState::Dissolving as i32],
+            min_stake_e8s: Some(200 * E8),
+            max_stake_e8s: Some(500 * E8),
+            ..Default::default()
+        }),
+        (vec![3, 5], None)
+    );
+    // The cursor applies after filtering.
+    assert_eq!(
+        list_neuron_ids(ListNeurons {
+            include_neurons_readable_by_caller: true,
+            include_neuron_states: vec![NeuronState::NotDissolving as i32],
+            page_size: Some(1),
+            start_after_neuron_id: Some(2),
+            ..Default::default()
+        }),
+        (vec![4], Some(4))
+    );
+}
+
+#[test]
+fn test_list_neurons_without_pagination_returns_all_neurons() {
+    let mut driver = fake::FakeDriver::default();
+    let controller = principal(1);
+    let num_neurons = MAX_LIST_NEURONS_RESULTS as u64 + 100;
+    let neurons = (1..=num_neurons)
+        .map(|id| {
+            let neuron = Neuron {
+                id: Some(NeuronId { id }),
+                controller: Some(controller),
+                account: driver.random_byte_array().to_vec(),
+                cached_neuron_stake_e8s: E8,
+                dissolve_state: Some(DissolveState::DissolveDelaySeconds(ONE_YEAR_SECONDS)),
+                ..Default::default()
+            };
+            (id, neuron)
+        })
+        .collect::<BTreeMap<u64, Neuron>>();
+    let gov = Governance::new(
+        GovernanceProto {
+            neurons,
+            ..Default::default()
+        },
+        driver.get_fake_env(),
+        driver.get_fake_ledger(),
+        driver.get_fake_cmc(),
+    );
+
+    // A caller that predates pagination gets every neuron it asked for.
+    let response = gov.list_neurons_by_principal(
+        &ListNeurons {
+            neuron_ids: vec![],
+            include_neurons_readable_by_caller: true,
+            ..Default::default()
+        },
+        &controller,
+    );
+    assert_eq!(response.full_neurons.len() as u64, num_neurons);
+    assert_eq!(response.neuron_infos.len() as u64, num_neurons);
+    assert_eq!(response.next_start_after_neuron_id, None);
+
+    // Callers that paginate get at most `MAX_LIST_NEURONS_RESULTS` neurons per page.
+    let response = gov.list_neurons_by_principal(
+        &ListNeurons {
+            include_neurons_readable_by_caller: true,
+            start_after_neuron_id: Some(0),
+            ..Default::default()
+        },
+        &controller,
+    );
+    assert_eq!(
+        response.full_neurons.len(),
+        MAX_LIST_NEURONS_RESULTS as usize
+    );
+    assert_eq!(
+        response.next_start_after_neuron_id,
+        Some(MAX_LIST_NEURONS_RESULTS as u64)
+    );
+}
+
 #[tokio::test]
 async fn test_max_number_of_proposals_with_ballots() {
     let mut fake_driver = fake::FakeDriver::default();
@@ -32,6 +32,7 @@ fn governance_mem_test() {
         include_status: vec![],
         include_all_manage_neuron_proposals: None,
         omit_large_fields: Some(false),
+        ..Default::default()
     };
 
     let proposals = query(
@@ -16,20 +16,26 @@ use ic_http_endpoints_public::{
     CallServiceBuilder, CanisterReadStateServiceBuilder, QueryServiceBuilder,
 };
 use ic_interfaces::{crypto::BasicSigner, ingress_pool::IngressPoolThrottler};
-use ic_management_canister_types::CanisterInstallMode;
+use ic_management_canister_types::{
+    CanisterHttpResponsePayload, CanisterInstallMode, HttpHeader, Payload, TransformArgs,
+};
 use ic_registry_proto_data_provider::ProtoRegistryDataProvider;
 use ic_registry_routing_table::{CanisterIdRange, RoutingTable, CANISTER_IDS_PER_SUBNET};
 use ic_registry_subnet_type::SubnetType;
 use ic_state_machine_tests::{
-    finalize_registry, IngressState, IngressStatus, StateMachine, StateMachineBuilder,
-    StateMachineConfig, SubmitIngressError, Time,
+    finalize_registry, IngressState, IngressStatus, PayloadBuilder, RejectCode, StateMachine,
+    StateMachineBuilder, StateMachineConfig, SubmitIngressError, Time,
 };
 use ic_types::{
     artifact::UnvalidatedArtifactMutation,
     artifact_kind::IngressArtifact,
+    canister_http::{
+        CanisterHttpMethod, CanisterHttpRequestContext, MAX_CANISTER_HTTP_RESPONSE_BYTES,
+    },
     crypto::{BasicSig, BasicSigOf, CryptoResult, Signable},
     messages::{
-        CertificateDelegation, MessageId as OtherMessageId, QueryResponseHash, ReplicaHealthStatus,
+        CallbackId, CertificateDelegation, MessageId as OtherMessageId, QueryResponseHash,
+        ReplicaHealthStatus,
     },
     CanisterId, NodeId, NumInstructions, PrincipalId, RegistryVersion, SubnetId,
 };
@@ -37,8 +43,8 @@ use ic_validator_ingress_message::StandaloneIngressSigVerifier;
 use itertools::Itertools;
 use pocket_ic::common::rest::{
     self, BinaryBlob, BlobCompression, DtsFlag, ExtendedSubnetConfigSet, RawAddCycles,
-    RawCanisterCall, RawEffectivePrincipal, RawMessageId, RawSetStableMemory,
-    SubnetInstructionConfig, SubnetKind, SubnetSpec, Topology,
+    RawCanisterCall, RawEffectivePrincipal, RawMessageId, RawMockCanisterHttpResponse,
+    RawSetStableMemory, SchnorrKeyId, SubnetInstructionConfig, SubnetKind, SubnetSpec, Topology,
 };
 use rand::rngs::StdRng;
 use rand::Rng;
@@ -65,6 +71,8 @@ pub const MAXIMUM_NUMBER_OF_SUBNETS_ON_MAINNET: u64 = 1024;
 
 pub struct PocketIc {
     subnets: Arc<RwLock<BTreeMap<SubnetId, Arc<StateMachine>>>>,
+    // The subnet IDs in the order in which the subnets were created.
+    subnet_seq: Vec<SubnetId>,
     routing_table: RoutingTable,
     /// Constant, created on initialization.
     pub topology: Topology,
@@ -77,8 +85,61 @@ pub struct PocketIc {
     subnet_configs: ExtendedSubnetConfigSet,
 }
 
+/// A copy of the full state of a PocketIC instance from which
+/// new instances can be created (see [PocketIc::checkpoint]).
+pub struct PocketIcCheckpoint {
+    subnet_configs: ExtendedSubnetConfigSet,
+    routing_table: RoutingTable,
+    topology: Topology,
+    randomness: StdRng,
+    // in the order in which the subnets were created
+    subnets: Vec<SubnetCheckpoint>,
+}
+
+struct SubnetCheckpoint {
+    subnet_id: SubnetId,
+    state_dir: TempDir,
+    time: Time,
+    nonce: u64,
+}
+
 impl PocketIc {
     pub fn new(runtime: Arc<Runtime>, subnet_configs: ExtendedSubnetConfigSet) -> Self {
+        Self::new_with_checkpoint(runtime, subnet_configs, None)
+    }
+
+    /// Creates a new instance whose subnets start from the states
+    /// saved in the given checkpoint. Returns an error if the subnets
+    /// of the new instance do not match those of the checkpoint.
+    pub fn from_checkpoint(
+        runtime: Arc<Runtime>,
+        checkpoint: &PocketIcCheckpoint,
+    ) -> Result<Self, String> {
+        let mut pocket_ic =
+            Self::new_with_checkpoint(runtime, checkpoint.subnet_configs.clone(), Some(checkpoint));
+        let checkpoint_subnet_seq: Vec<_> = checkpoint
+            .subnets
+            .iter()
+            .map(|subnet| subnet.subnet_id)
+            .collect();
+        if pocket_ic.subnet_seq != checkpoint_subnet_seq {
+            return Err("The subnet IDs do not match those of the checkpoint".to_string());
+        }
+        if pocket_ic.routing_table != checkpoint.routing_table {
+            return Err("The routing table does not match that of the checkpoint".to_string());
+        }
+        if pocket_ic.topology != checkpoint.topology {
+            return Err("The topology does not match that of the checkpoint".to_string());
+        }
+        pocket_ic.randomness = checkpoint.randomness.clone();
+        Ok(pocket_ic)
+    }
+
+    fn new_with_checkpoint(
+        runtime: Arc<Runtime>,
+        subnet_configs: ExtendedSubnetConfigSet,
+        checkpoint: Option<&PocketIcCheckpoint>,
+    ) -> Self {
         let fixed_range_subnets = subnet_configs.get_named();
         let flexible_subnets = {
             // note that for these, the subnet ids are currently ignored.
@@ -88,6 +149,7 @@ impl PocketIc {
                     spec.get_state_path(),
                     spec.get_instruction_config(),
                     spec.get_dts_flag(),
+                    spec.get_schnorr_keys(),
                 )
             });
             let app = subnet_configs.application.iter().map(|spec| {
@@ -96,6 +158,7 @@ impl PocketIc {
                     spec.get_state_path(),
                     spec.get_instruction_config(),
                     spec.get_dts_flag(),
+                    spec.get_schnorr_keys(),
                 )
             });
             sys.chain(app)
@@ -112,14 +175,29 @@ impl PocketIc {
 
         let ii_subnet_split = subnet_configs.ii.is_some();
 
-        for (subnet_kind, subnet_state_dir, instruction_config, dts_flag) in
-            fixed_range_subnets.into_iter().chain(flexible_subnets)
+        for (
+            subnet_seq_no,
+            (subnet_kind, subnet_state_dir, instruction_config, dts_flag, schnorr_keys),
+        ) in fixed_range_subnets
+            .into_iter()
+            .chain(flexible_subnets)
+            .enumerate()
         {
             let RangeConfig {
                 canister_id_ranges: ranges,
                 canister_allocation_range: alloc_range,
             } = get_range_config(subnet_kind, &mut range_gen, ii_subnet_split);
 
+            // A checkpoint takes precedence over the state directory from the subnet config.
+            let subnet_state_dir = match checkpoint {
+                Some(checkpoint) => Some(
+                    checkpoint.subnets[subnet_seq_no]
+                        .state_dir
+                        .path()
+                        .to_path_buf(),
+                ),
+                None => subnet_state_dir,
+            };
             let state_dir = if let Some(subnet_state_dir) = subnet_state_dir {
                 let tmp_dir = TempDir::new().expect("Failed to create temporary directory");
                 copy_dir(subnet_state_dir, tmp_dir.path()).expect("Failed to copy state directory");
@@ -135,6 +213,7 @@ impl PocketIc {
                 state_dir,
                 instruction_config,
                 dts_flag,
+                schnorr_keys,
             });
         }
 
@@ -142,6 +221,7 @@ impl PocketIc {
         let subnets: Arc<RwLock<BTreeMap<SubnetId, Arc<StateMachine>>>> =
             Arc::new(RwLock::new(BTreeMap::new()));
         let mut topology = Topology(HashMap::new());
+        let mut subnet_seq = vec![];
 
         // Create all StateMachines and the topology from the subnet config infos.
         for (
@@ -153,6 +233,7 @@ impl PocketIc {
                 state_dir,
                 instruction_config,
                 dts_flag,
+                schnorr_keys,
             },
         ) in subnet_config_info.into_iter().enumerate()
         {
@@ -195,6 +276,10 @@ impl PocketIc {
                 builder = builder.no_dts();
             };
 
+            for key in schnorr_keys {
+                builder = builder.with_schnorr_key(conv_schnorr_key_id(key));
+            }
+
             if subnet_kind == SubnetKind::NNS {
                 builder = builder.with_root_subnet_config();
                 if let Some(nns_subnet_id) = nns_subnet_id {
@@ -206,8 +291,16 @@ impl PocketIc {
                 builder = builder.with_state_dir(state_dir);
             }
 
+            let subnet_checkpoint = checkpoint.map(|checkpoint| &checkpoint.subnets[subnet_seq_no]);
+            if let Some(subnet_checkpoint) = subnet_checkpoint {
+                builder = builder
+                    .with_time(subnet_checkpoint.time)
+                    .with_nonce(subnet_checkpoint.nonce);
+            }
+
             let sm = builder.build_with_subnets(subnets.clone());
             let subnet_id = sm.get_subnet_id();
+            subnet_seq.push(subnet_id);
 
             // Store the actual NNS subnet ID if none was provided by the client.
             if let (SubnetKind::NNS, None) = (subnet_kind, nns_subnet_id) {
@@ -250,6 +343,7 @@ impl PocketIc {
 
         Self {
             subnets,
+            subnet_seq,
             routing_table,
             topology,
             randomness: StdRng::seed_from_u64(42),
@@ -257,6 +351,58 @@ impl PocketIc {
         }
     }
 
+    /// Saves the state of all subnets (and the configuration needed to
+    /// recreate the instance) so that new instances can be created from it.
+    /// This executes a round on every subnet to produce on-disk checkpoints.
+    pub fn checkpoint(&self) -> PocketIcCheckpoint {
+        let subnets = self.subnets.read().unwrap();
+        for subnet in subnets.values() {
+            let checkpoints_enabled = subnet.checkpoints_enabled();
+            subnet.set_checkpoints_enabled(true);
+            subnet.execute_round();
+            subnet.set_checkpoints_enabled(checkpoints_enabled);
+        }
+        let subnet_checkpoints = self
+            .subnet_seq
+            .iter()
+            .map(|subnet_id| {
+                let subnet = subnets.get(subnet_id).unwrap();
+                // Wait until the checkpoint has been fully written to disk.
+                subnet.state_manager.flush_tip_channel();
+                let height = *subnet
+                    .state_manager
+                    .checkpoint_heights()
+                    .last()
+                    .expect("Subnet has no checkpoint");
+                let checkpoint_layout = subnet
+                    .state_manager
+                    .state_layout()
+                    .checkpoint(height)
+                    .expect("Failed to access checkpoint");
+                let src = checkpoint_layout.raw_path();
+                let state_dir = TempDir::new().expect("Failed to create temporary directory");
+                let dst = state_dir
+                    .path()
+                    .join("checkpoints")
+                    .join(src.file_name().unwrap());
+                copy_dir(src, dst).expect("Failed to copy checkpoint");
+                SubnetCheckpoint {
+                    subnet_id: *subnet_id,
+                    state_dir,
+                    time: subnet.get_time(),
+                    nonce: subnet.get_nonce(),
+                }
+            })
+            .collect();
+        PocketIcCheckpoint {
+            subnet_configs: self.subnet_configs.clone(),
+            routing_table: self.routing_table.clone(),
+            topology: self.topology.clone(),
+            randomness: self.randomness.clone(),
+            subnets: subnet_checkpoints,
+        }
+    }
+
     fn try_route_canister(&self, canister_id: CanisterId) -> Option<Arc<StateMachine>> {
         let subnet_id = self.routing_table.route(canister_id.into());
         subnet_id.map(|subnet_id| self.get_subnet_with_id(subnet_id).unwrap())
@@ -378,6 +524,19 @@ fn conv_type(inp: rest::SubnetKind) -> SubnetType {
     }
 }
 
+fn conv_schnorr_key_id(key: SchnorrKeyId) -> ic_management_canister_types::SchnorrKeyId {
+    let algorithm = match key.algorithm {
+        rest::SchnorrAlgorithm::Bip340Secp256k1 => {
+            ic_management_canister_types::SchnorrAlgorithm::Bip340Secp256k1
+        }
+        rest::SchnorrAlgorithm::Ed25519 => ic_management_canister_types::SchnorrAlgorithm::Ed25519,
+    };
+    ic_management_canister_types::SchnorrKeyId {
+        algorithm,
+        name: key.name,
+    }
+}
+
 fn subnet_size(subnet: SubnetKind) -> u64 {
     use rest::SubnetKind::*;
     match subnet {
@@ -512,6 +671,7 @@ struct SubnetConfigInfo {
     pub state_dir: Option<TempDir>,
     pub instruction_config: SubnetInstructionConfig,
     pub dts_flag: DtsFlag,
+    pub schnorr_keys: Vec<SchnorrKeyId>,
 }
 
 // ---------------------------------------------------------------------------------------- //
@@ -592,6 +752,61 @@ impl Operation for Tick {
     }
 }
 
+/// Checkpoints of PocketIC instances by their labels.
+pub type Checkpoints = Arc<tokio::sync::RwLock<HashMap<String, Arc<PocketIcCheckpoint>>>>;
+
+pub struct CreateCheckpoint {
+    pub label: String,
+    pub checkpoints: Checkpoints,
+}
+
+impl Operation for CreateCheckpoint {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let checkpoint = pic.checkpoint();
+        self.checkpoints
+            .blocking_write()
+            .insert(self.label.clone(), Arc::new(checkpoint));
+        OpOut::NoOutput
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("checkpoint_{}", self.label))
+    }
+}
+
+/// Replaces the state of an instance by the state saved in a checkpoint.
+/// The checkpoint must have been created on an instance with the same topology.
+pub struct RestoreCheckpoint {
+    pub label: String,
+    pub checkpoints: Checkpoints,
+    pub runtime: Arc<Runtime>,
+}
+
+impl Operation for RestoreCheckpoint {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let Some(checkpoint) = self.checkpoints.blocking_read().get(&self.label).cloned() else {
+            return OpOut::Error(PocketIcError::CheckpointNotFound(self.label.clone()));
+        };
+        if checkpoint.topology != pic.topology {
+            return OpOut::Error(PocketIcError::CheckpointTopologyMismatch(format!(
+                "Checkpoint {} was created on an instance with a different topology",
+                self.label
+            )));
+        }
+        match PocketIc::from_checkpoint(self.runtime.clone(), &checkpoint) {
+            Ok(restored) => {
+                *pic = restored;
+                OpOut::NoOutput
+            }
+            Err(message) => OpOut::Error(PocketIcError::CheckpointTopologyMismatch(message)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("restore_{}", self.label))
+    }
+}
+
 #[derive(Clone, Debug, Copy)]
 pub struct AdvanceTimeAndTick(pub Duration);
 
@@ -1415,6 +1630,244 @@ impl Operation for GetSubnet {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct GetCanisterHttp;
+
+fn canister_http_method_to_rest(http_method: CanisterHttpMethod) -> rest::CanisterHttpMethod {
+    match http_method {
+        CanisterHttpMethod::GET => rest::CanisterHttpMethod::GET,
+        CanisterHttpMethod::POST => rest::CanisterHttpMethod::POST,
+        CanisterHttpMethod::HEAD => rest::CanisterHttpMethod::HEAD,
+    }
+}
+
+impl Operation for GetCanisterHttp {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let mut res = vec![];
+        for (subnet_id, subnet) in pic.subnets.read().unwrap().iter() {
+            for (callback_id, context) in subnet.canister_http_request_contexts() {
+                res.push(rest::CanisterHttpRequest {
+                    subnet_id: subnet_id.get().0,
+                    request_id: callback_id.get(),
+                    http_method: canister_http_method_to_rest(context.http_method),
+                    url: context.url,
+                    headers: context
+                        .headers
+                        .into_iter()
+                        .map(|h| rest::CanisterHttpHeader {
+                            name: h.name,
+                            value: h.value,
+                        })
+                        .collect(),
+                    body: context.body.unwrap_or_default(),
+                    max_response_bytes: context.max_response_bytes.map(|b| b.get()),
+                });
+            }
+        }
+        OpOut::CanisterHttp(res)
+    }
+
+    fn id(&self) -> OpId {
+        OpId("get_canister_http".into())
+    }
+}
+
+/// The outcome of a canister HTTP outcall as observed by a single replica.
+type CanisterHttpOutcome = Result<CanisterHttpResponsePayload, (RejectCode, String)>;
+
+#[derive(Clone, Debug)]
+pub struct MockCanisterHttp {
+    pub subnet_id: SubnetId,
+    pub request_id: CallbackId,
+    /// The (untransformed) responses observed by the replicas of the subnet.
+    /// A single response is taken to be observed by all replicas.
+    pub responses: Vec<CanisterHttpOutcome>,
+}
+
+impl TryFrom<RawMockCanisterHttpResponse> for MockCanisterHttp {
+    type Error = ConversionError;
+    fn try_from(
+        RawMockCanisterHttpResponse {
+            subnet_id,
+            request_id,
+            response,
+            additional_responses,
+        }: RawMockCanisterHttpResponse,
+    ) -> Result<Self, Self::Error> {
+        let subnet_id = match PrincipalId::try_from(subnet_id) {
+            Ok(subnet_id) => SubnetId::new(subnet_id),
+            Err(_) => {
+                return Err(ConversionError {
+                    message: "Bad subnet id".to_string(),
+                })
+            }
+        };
+        let responses = std::iter::once(response)
+            .chain(additional_responses)
+            .map(|response| match response {
+                rest::CanisterHttpResponse::CanisterHttpReply(reply) => {
+                    Ok(Ok(CanisterHttpResponsePayload {
+                        status: reply.status as u128,
+                        headers: reply
+                            .headers
+                            .into_iter()
+                            .map(|h| HttpHeader {
+                                name: h.name,
+                                value: h.value,
+                            })
+                            .collect(),
+                        body: reply.body,
+                    }))
+                }
+                rest::CanisterHttpResponse::CanisterHttpReject(reject) => {
+                    match RejectCode::try_from(reject.reject_code) {
+                        Ok(reject_code) => Ok(Err((reject_code, reject.message))),
+                        Err(_) => Err(ConversionError {
+                            message: format!("Bad reject code {}", reject.reject_code),
+                        }),
+                    }
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(MockCanisterHttp {
+            subnet_id,
+            request_id: CallbackId::from(request_id),
+            responses,
+        })
+    }
+}
+
+/// Applies the checks and the transform function that the replicas would apply
+/// to a response before agreeing on it.
+fn process_mock_canister_http_response(
+    subnet: &StateMachine,
+    context: &CanisterHttpRequestContext,
+    response: &CanisterHttpOutcome,
+) -> CanisterHttpOutcome {
+    let payload = response.clone()?;
+    let max_response_bytes = context
+        .max_response_bytes
+        .map(|b| b.get())
+        .unwrap_or(MAX_CANISTER_HTTP_RESPONSE_BYTES);
+    let response_size = payload.body.len()
+        + payload
+            .headers
+            .iter()
+            .map(|h| h.name.len() + h.value.len())
+            .sum::<usize>();
+    if response_size as u64 > max_response_bytes {
+        return Err((
+            RejectCode::SysFatal,
+            format!(
+                "Http body exceeds size limit of {} bytes.",
+                max_response_bytes
+            ),
+        ));
+    }
+    let Some(transform) = &context.transform else {
+        return Ok(payload);
+    };
+    let transform_args = TransformArgs {
+        response: payload,
+        context: transform.context.clone(),
+    };
+    match subnet.query_as(
+        CanisterId::ic_00().get(),
+        context.request.sender,
+        transform.method_name.clone(),
+        transform_args.encode(),
+    ) {
+        Ok(ic_state_machine_tests::WasmResult::Reply(bytes)) => {
+            CanisterHttpResponsePayload::decode(&bytes).map_err(|e| {
+                (
+                    RejectCode::SysFatal,
+                    format!("Failed to decode the transformed response: {}", e),
+                )
+            })
+        }
+        Ok(ic_state_machine_tests::WasmResult::Reject(msg)) => Err((
+            RejectCode::CanisterReject,
+            format!("Transform function rejected: {}", msg),
+        )),
+        Err(e) => Err((
+            RejectCode::SysFatal,
+            format!("Transform function failed: {}", e),
+        )),
+    }
+}
+
+impl Operation for MockCanisterHttp {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let Some(subnet) = pic.get_subnet_with_id(self.subnet_id) else {
+            return OpOut::Error(PocketIcError::SubnetNotFound(self.subnet_id.get().0));
+        };
+        let Some(context) = subnet
+            .canister_http_request_contexts()
+            .remove(&self.request_id)
+        else {
+            return OpOut::Error(PocketIcError::InvalidCanisterHttpRequestId((
+                self.subnet_id,
+                self.request_id.get(),
+            )));
+        };
+        let outcome = if let [response] = &self.responses[..] {
+            process_mock_canister_http_response(&subnet, &context, response)
+        } else {
+            let subnet_size = subnet.nodes.len();
+            if self.responses.len() != subnet_size {
+                return OpOut::Error(PocketIcError::InvalidMockCanisterHttpResponses((
+                    self.responses.len(),
+                    subnet_size,
+                )));
+            }
+            let outcomes: Vec<_> = self
+                .responses
+                .iter()
+                .map(|response| process_mock_canister_http_response(&subnet, &context, response))
+                .collect();
+            // The replicas agree on a (transformed) response
+            // if at least 2f+1 out of n = 3f+1 replicas observed it.
+            let faults_tolerated = (subnet_size - 1) / 3;
+            let threshold = 2 * faults_tolerated + 1;
+            outcomes
+                .iter()
+                .find(|outcome| outcomes.iter().filter(|o| o == outcome).count() >= threshold)
+                .cloned()
+                .unwrap_or_else(|| {
+                    Err((
+                        RejectCode::SysTransient,
+                        "No consensus could be reached. Replicas had different responses."
+                            .to_string(),
+                    ))
+                })
+        };
+        let payload = match outcome {
+            Ok(response) => PayloadBuilder::new().http_response(self.request_id, &response),
+            Err((reject_code, message)) => {
+                PayloadBuilder::new().http_response_failure(self.request_id, reject_code, message)
+            }
+        };
+        subnet.execute_payload(payload);
+        // Executing the payload advanced the time on this subnet only.
+        // Time is kept in sync across subnets.
+        let time = subnet.time();
+        for subnet in pic.subnets.read().unwrap().values() {
+            subnet.set_time(time);
+        }
+        OpOut::NoOutput
+    }
+
+    fn id(&self) -> OpId {
+        let mut hasher = Sha256::new();
+        hasher.write(format!("{:?}", self.responses).as_bytes());
+        let hash = Digest(hasher.finish());
+        OpId(format!(
+            "mock_canister_http({},{},{})",
+            self.subnet_id, self.request_id, hash
+        ))
+    }
+}
+
 /// Add cycles to a given canister.
 ///
 /// # Panics
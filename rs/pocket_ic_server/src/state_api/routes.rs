@@ -6,9 +6,10 @@
 ///
 use super::state::{ApiState, OpOut, PocketIcError, StateLabel, UpdateReply};
 use crate::pocket_ic::{
-    AddCycles, AwaitIngressMessage, CallRequest, ExecuteIngressMessage, GetCyclesBalance,
-    GetStableMemory, GetSubnet, GetTime, PubKey, Query, QueryRequest, ReadStateRequest,
-    SetStableMemory, SetTime, StatusRequest, SubmitIngressMessage, Tick,
+    AddCycles, AwaitIngressMessage, CallRequest, CreateCheckpoint, ExecuteIngressMessage,
+    GetCanisterHttp, GetCyclesBalance, GetStableMemory, GetSubnet, GetTime, MockCanisterHttp,
+    PubKey, Query, QueryRequest, ReadStateRequest, RestoreCheckpoint, SetStableMemory, SetTime,
+    StatusRequest, SubmitIngressMessage, Tick,
 };
 use crate::OpId;
 use crate::{pocket_ic::PocketIc, BlobStore, InstanceId, Operation};
@@ -35,8 +36,9 @@ use ic_http_endpoints_public::cors_layer;
 use ic_types::CanisterId;
 use pocket_ic::common::rest::{
     self, ApiResponse, ExtendedSubnetConfigSet, HttpGatewayConfig, HttpGatewayInfo, RawAddCycles,
-    RawCanisterCall, RawCanisterId, RawCanisterResult, RawCycles, RawMessageId, RawSetStableMemory,
-    RawStableMemory, RawSubmitIngressResult, RawSubnetId, RawTime, RawWasmResult,
+    RawCanisterCall, RawCanisterHttpRequest, RawCanisterId, RawCanisterResult, RawCheckpoint,
+    RawCycles, RawMessageId, RawMockCanisterHttpResponse, RawSetStableMemory, RawStableMemory,
+    RawSubmitIngressResult, RawSubnetId, RawTime, RawWasmResult,
 };
 use pocket_ic::WasmResult;
 use serde::Serialize;
@@ -71,6 +73,7 @@ where
         .directory_route("/get_stable_memory", post(handler_get_stable_memory))
         .directory_route("/get_subnet", post(handler_get_subnet))
         .directory_route("/pub_key", post(handler_pub_key))
+        .directory_route("/get_canister_http", get(handler_get_canister_http))
 }
 
 pub fn instance_update_routes<S>() -> ApiRouter<S>
@@ -95,6 +98,9 @@ where
         .directory_route("/add_cycles", post(handler_add_cycles))
         .directory_route("/set_stable_memory", post(handler_set_stable_memory))
         .directory_route("/tick", post(handler_tick))
+        .directory_route("/mock_canister_http", post(handler_mock_canister_http))
+        .directory_route("/checkpoint", post(handler_checkpoint))
+        .directory_route("/restore", post(handler_restore))
 }
 
 pub fn instance_api_v2_routes<S>() -> ApiRouter<S>
@@ -145,6 +151,10 @@ where
         // Returns an InstanceId.
         .api_route("/", post(create_instance))
         //
+        // Create a new IC instance from a checkpoint of another instance.
+        // Takes the label of the checkpoint and returns an InstanceId.
+        .api_route("/restore", post(restore_instance))
+        //
         // Deletes an instance.
         .directory_route("/:id", delete(delete_instance))
         //
@@ -414,6 +424,19 @@ impl TryFrom<OpOut> for RawSubmitIngressResult {
     }
 }
 
+impl TryFrom<OpOut> for Vec<RawCanisterHttpRequest> {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::CanisterHttp(canister_http_requests) => Ok(canister_http_requests
+                .into_iter()
+                .map(|r| r.into())
+                .collect()),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 struct ApiV2Error(String);
 
@@ -556,6 +579,17 @@ pub async fn handler_pub_key(
     (code, Json(res))
 }
 
+pub async fn handler_get_canister_http(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ApiResponse<Vec<RawCanisterHttpRequest>>>) {
+    let timeout = timeout_or_default(headers);
+    let op = GetCanisterHttp;
+    let (code, res) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(res))
+}
+
 pub async fn handler_status(
     State(AppState {
         api_state, runtime, ..
@@ -695,6 +729,13 @@ fn op_out_to_response(op_out: OpOut) -> Response {
             )),
         )
             .into_response(),
+        opout @ OpOut::CanisterHttp(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(
+                Vec::<RawCanisterHttpRequest>::try_from(opout).unwrap(),
+            )),
+        )
+            .into_response(),
         opout @ OpOut::MaybeSubnetId(_) => (
             StatusCode::OK,
             Json(ApiResponse::Success(
@@ -902,6 +943,60 @@ pub async fn handler_tick(
     (code, Json(res))
 }
 
+pub async fn handler_checkpoint(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(RawCheckpoint { label }): extract::Json<RawCheckpoint>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    let op = CreateCheckpoint {
+        label,
+        checkpoints: api_state.get_checkpoints(),
+    };
+    let (code, res) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(res))
+}
+
+pub async fn handler_restore(
+    State(AppState {
+        api_state, runtime, ..
+    }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(RawCheckpoint { label }): extract::Json<RawCheckpoint>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    let op = RestoreCheckpoint {
+        label,
+        checkpoints: api_state.get_checkpoints(),
+        runtime,
+    };
+    let (code, res) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(res))
+}
+
+pub async fn handler_mock_canister_http(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(raw_mock_canister_http_response): extract::Json<RawMockCanisterHttpResponse>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    match MockCanisterHttp::try_from(raw_mock_canister_http_response) {
+        Ok(mock_op) => {
+            let (code, res) = run_operation(api_state, instance_id, timeout, mock_op).await;
+            (code, Json(res))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
 // ----------------------------------------------------------------------------------------------------------------- //
 // Other handlers
 
@@ -969,6 +1064,51 @@ pub async fn create_instance(
     )
 }
 
+/// Create a new IC instance from a checkpoint created by the `checkpoint` endpoint
+/// of an instance. The new InstanceId will be returned.
+pub async fn restore_instance(
+    State(AppState {
+        api_state,
+        min_alive_until: _,
+        runtime,
+        blob_store: _,
+    }): State<AppState>,
+    extract::Json(RawCheckpoint { label }): extract::Json<RawCheckpoint>,
+) -> (StatusCode, Json<rest::CreateInstanceResponse>) {
+    let Some(checkpoint) = api_state.get_checkpoint(&label).await else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(rest::CreateInstanceResponse::Error {
+                message: format!("Checkpoint {} not found", label),
+            }),
+        );
+    };
+
+    let pocket_ic =
+        match tokio::task::spawn_blocking(move || PocketIc::from_checkpoint(runtime, &checkpoint))
+            .await
+            .expect("Failed to restore PocketIC instance from checkpoint")
+        {
+            Ok(pocket_ic) => pocket_ic,
+            Err(message) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(rest::CreateInstanceResponse::Error { message }),
+                )
+            }
+        };
+
+    let topology = pocket_ic.topology.clone();
+    let instance_id = api_state.add_instance(pocket_ic).await;
+    (
+        StatusCode::CREATED,
+        Json(rest::CreateInstanceResponse::Created {
+            instance_id,
+            topology,
+        }),
+    )
+}
+
 pub async fn list_instances(
     State(AppState { api_state, .. }): State<AppState>,
 ) -> Json<Vec<String>> {
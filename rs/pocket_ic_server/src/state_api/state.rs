@@ -1,13 +1,15 @@
 /// This module contains the core state of the PocketIc server.
 /// Axum handlers operate on a global state of type ApiState, whose
 /// interface guarantees consistency and determinism.
-use crate::pocket_ic::{AdvanceTimeAndTick, EffectivePrincipal, PocketIc};
+use crate::pocket_ic::{
+    AdvanceTimeAndTick, Checkpoints, EffectivePrincipal, PocketIc, PocketIcCheckpoint,
+};
 use crate::InstanceId;
 use crate::{OpId, Operation};
 use base64;
 use ic_http_endpoints_public::cors_layer;
 use ic_types::{CanisterId, SubnetId};
-use pocket_ic::common::rest::{HttpGatewayBackend, HttpGatewayConfig};
+use pocket_ic::common::rest::{CanisterHttpRequest, HttpGatewayBackend, HttpGatewayConfig};
 use pocket_ic::{ErrorCode, UserError, WasmResult};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -80,6 +82,8 @@ pub struct ApiState {
     port: Option<u16>,
     // status of HTTP gateway (true = running, false = stopped)
     http_gateways: Arc<RwLock<Vec<bool>>>,
+    // checkpoints of instances from which new instances can be created
+    checkpoints: Checkpoints,
 }
 
 #[derive(Default)]
@@ -143,6 +147,7 @@ impl PocketIcApiStateBuilder {
             sync_wait_time,
             port: self.port,
             http_gateways: Arc::new(RwLock::new(Vec::new())),
+            checkpoints: Arc::new(RwLock::new(HashMap::new())),
         })
     }
 }
@@ -161,6 +166,7 @@ pub enum OpOut {
     ApiV2Response((u16, BTreeMap<String, Vec<u8>>, Vec<u8>)),
     Pruned,
     MessageId((EffectivePrincipal, Vec<u8>)),
+    CanisterHttp(Vec<CanisterHttpRequest>),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
@@ -169,6 +175,10 @@ pub enum PocketIcError {
     BadIngressMessage(String),
     SubnetNotFound(candid::Principal),
     RequestRoutingError(String),
+    InvalidCanisterHttpRequestId((SubnetId, u64)),
+    InvalidMockCanisterHttpResponses((usize, usize)),
+    CheckpointNotFound(String),
+    CheckpointTopologyMismatch(String),
 }
 
 impl From<Result<ic_state_machine_tests::WasmResult, ic_state_machine_tests::UserError>> for OpOut {
@@ -226,6 +236,29 @@ impl std::fmt::Debug for OpOut {
             OpOut::Error(PocketIcError::RequestRoutingError(msg)) => {
                 write!(f, "RequestRoutingError({:?})", msg)
             }
+            OpOut::Error(PocketIcError::InvalidCanisterHttpRequestId((
+                subnet_id,
+                canister_http_request_id,
+            ))) => {
+                write!(
+                    f,
+                    "InvalidCanisterHttpRequestId({},{:?})",
+                    subnet_id, canister_http_request_id
+                )
+            }
+            OpOut::Error(PocketIcError::InvalidMockCanisterHttpResponses((actual, expected))) => {
+                write!(
+                    f,
+                    "InvalidMockCanisterHttpResponses(actual={},expected={})",
+                    actual, expected
+                )
+            }
+            OpOut::Error(PocketIcError::CheckpointNotFound(label)) => {
+                write!(f, "CheckpointNotFound({})", label)
+            }
+            OpOut::Error(PocketIcError::CheckpointTopologyMismatch(label)) => {
+                write!(f, "CheckpointTopologyMismatch({})", label)
+            }
             OpOut::Bytes(bytes) => write!(f, "Bytes({})", base64::encode(bytes)),
             OpOut::StableMemBytes(bytes) => write!(f, "StableMemory({})", base64::encode(bytes)),
             OpOut::MaybeSubnetId(Some(subnet_id)) => write!(f, "SubnetId({})", subnet_id),
@@ -248,6 +281,9 @@ impl std::fmt::Debug for OpOut {
                     hex::encode(message_id)
                 )
             }
+            OpOut::CanisterHttp(canister_http_requests) => {
+                write!(f, "CanisterHttp({:?})", canister_http_requests)
+            }
         }
     }
 }
@@ -335,6 +371,14 @@ impl ApiState {
         self.graph.clone()
     }
 
+    pub fn get_checkpoints(&self) -> Checkpoints {
+        self.checkpoints.clone()
+    }
+
+    pub async fn get_checkpoint(&self, label: &str) -> Option<Arc<PocketIcCheckpoint>> {
+        self.checkpoints.read().await.get(label).cloned()
+    }
+
     pub async fn add_instance(&self, instance: PocketIc) -> InstanceId {
         let mut instances = self.instances.write().await;
         let mut progress_threads = self.progress_threads.write().await;
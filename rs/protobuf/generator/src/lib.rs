@@ -342,6 +342,23 @@ fn build_state_proto(def: &Path, out: &Path) {
         ".state.queues.v1.Response",
         "#[derive(serde::Serialize, serde::Deserialize)]",
     );
+    config.type_attribute(
+        ".state.queues.v1.Request",
+        "#[derive(serde::Serialize, serde::Deserialize)]",
+    );
+    config.type_attribute(
+        ".state.queues.v1.RequestMetadata",
+        "#[derive(serde::Serialize, serde::Deserialize)]",
+    );
+    config.type_attribute(
+        ".state.ingress.v1.Ingress",
+        "#[derive(serde::Serialize, serde::Deserialize)]",
+    );
+    // Allows `state_tool` to dump and reload canister state as JSON.
+    config.type_attribute(
+        ".state.canister_state_bits.v1",
+        "#[derive(serde::Serialize, serde::Deserialize)]",
+    );
 
     let state_files = [
         def.join("state/ingress/v1/ingress.proto"),
@@ -365,10 +382,12 @@ fn build_types_proto(def: &Path, out: &Path) {
         ".types.v1.CatchUpPackage",
         ".types.v1.NiDkgId",
         ".types.v1.NodeId",
+        ".types.v1.NominalCycles",
         ".types.v1.PrincipalId",
         ".types.v1.SubnetId",
         ".types.v1.ThresholdSignature",
         ".types.v1.ThresholdSignatureShare",
+        ".types.v1.UserId",
     ] {
         config.type_attribute(path, "#[derive(serde::Serialize, serde::Deserialize)]");
     }
@@ -20,4 +20,11 @@ pub struct CanisterSnapshotBits {
     pub stable_memory_size: u64,
     #[prost(uint64, tag = "9")]
     pub wasm_memory_size: u64,
+    #[prost(message, repeated, tag = "10")]
+    pub exported_globals: ::prost::alloc::vec::Vec<super::super::canister_state_bits::v1::Global>,
+    #[prost(
+        enumeration = "super::super::canister_state_bits::v1::SnapshotSource",
+        tag = "11"
+    )]
+    pub source: i32,
 }
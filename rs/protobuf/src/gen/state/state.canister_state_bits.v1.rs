@@ -1,3 +1,4 @@
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CallContext {
@@ -18,6 +19,7 @@ pub struct CallContext {
 }
 /// Nested message and enum types in `CallContext`.
 pub mod call_context {
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct Ingress {
@@ -26,6 +28,7 @@ pub mod call_context {
         #[prost(bytes = "vec", tag = "2")]
         pub message_id: ::prost::alloc::vec::Vec<u8>,
     }
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct CanisterUpdateOrQuery {
@@ -38,9 +41,11 @@ pub mod call_context {
         pub deadline_seconds: u32,
     }
     /// System task is either a Heartbeat or a GlobalTimer.
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct SystemTask {}
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum CallOrigin {
@@ -56,6 +61,7 @@ pub mod call_context {
         SystemTask(SystemTask),
     }
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CallContextEntry {
@@ -64,6 +70,7 @@ pub struct CallContextEntry {
     #[prost(message, optional, tag = "2")]
     pub call_context: ::core::option::Option<CallContext>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WasmClosure {
@@ -73,6 +80,7 @@ pub struct WasmClosure {
     #[prost(uint64, tag = "2")]
     pub env: u64,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Callback {
@@ -99,6 +107,7 @@ pub struct Callback {
     #[prost(uint32, tag = "10")]
     pub deadline_seconds: u32,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CallbackEntry {
@@ -107,6 +116,7 @@ pub struct CallbackEntry {
     #[prost(message, optional, tag = "2")]
     pub callback: ::core::option::Option<Callback>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CallContextManager {
@@ -119,6 +129,7 @@ pub struct CallContextManager {
     #[prost(message, repeated, tag = "4")]
     pub callbacks: ::prost::alloc::vec::Vec<CallbackEntry>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CyclesAccount {
@@ -126,6 +137,7 @@ pub struct CyclesAccount {
     #[prost(bytes = "vec", tag = "1")]
     pub cycles_balance: ::prost::alloc::vec::Vec<u8>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Global {
@@ -134,6 +146,7 @@ pub struct Global {
 }
 /// Nested message and enum types in `Global`.
 pub mod global {
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Global {
@@ -149,6 +162,7 @@ pub mod global {
         V128(::prost::alloc::vec::Vec<u8>),
     }
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WasmMethod {
@@ -157,7 +171,19 @@ pub struct WasmMethod {
 }
 /// Nested message and enum types in `WasmMethod`.
 pub mod wasm_method {
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[derive(
+        serde::Serialize,
+        serde::Deserialize,
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration,
+    )]
     #[repr(i32)]
     pub enum SystemMethod {
         Unspecified = 0,
@@ -201,6 +227,7 @@ pub mod wasm_method {
             }
         }
     }
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum WasmMethod {
@@ -214,6 +241,7 @@ pub mod wasm_method {
         CompositeQuery(::prost::alloc::string::String),
     }
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WasmCustomSection {
@@ -224,6 +252,7 @@ pub struct WasmCustomSection {
     #[prost(bytes = "vec", optional, tag = "3")]
     pub hash: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WasmMetadata {
@@ -231,6 +260,7 @@ pub struct WasmMetadata {
     pub custom_sections:
         ::prost::alloc::collections::BTreeMap<::prost::alloc::string::String, WasmCustomSection>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ExecutionStateBits {
@@ -249,6 +279,7 @@ pub struct ExecutionStateBits {
     #[prost(enumeration = "NextScheduledMethod", optional, tag = "7")]
     pub next_scheduled_method: ::core::option::Option<i32>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StopCanisterContext {
@@ -257,6 +288,7 @@ pub struct StopCanisterContext {
 }
 /// Nested message and enum types in `StopCanisterContext`.
 pub mod stop_canister_context {
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct Ingress {
@@ -267,6 +299,7 @@ pub mod stop_canister_context {
         #[prost(uint64, optional, tag = "5")]
         pub call_id: ::core::option::Option<u64>,
     }
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct Canister {
@@ -284,6 +317,7 @@ pub mod stop_canister_context {
         #[prost(uint32, tag = "6")]
         pub deadline_seconds: u32,
     }
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Context {
@@ -293,12 +327,14 @@ pub mod stop_canister_context {
         Canister(Canister),
     }
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterStatusRunning {
     #[prost(message, optional, tag = "1")]
     pub call_context_manager: ::core::option::Option<CallContextManager>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterStatusStopping {
@@ -307,9 +343,11 @@ pub struct CanisterStatusStopping {
     #[prost(message, repeated, tag = "2")]
     pub stop_contexts: ::prost::alloc::vec::Vec<StopCanisterContext>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterStatusStopped {}
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ExecutionTask {
@@ -318,6 +356,7 @@ pub struct ExecutionTask {
 }
 /// Nested message and enum types in `ExecutionTask`.
 pub mod execution_task {
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct AbortedExecution {
@@ -331,6 +370,7 @@ pub mod execution_task {
     }
     /// Nested message and enum types in `AbortedExecution`.
     pub mod aborted_execution {
+        #[derive(serde::Serialize, serde::Deserialize)]
         #[allow(clippy::derive_partial_eq_without_eq)]
         #[derive(Clone, PartialEq, ::prost::Oneof)]
         pub enum Input {
@@ -344,6 +384,7 @@ pub mod execution_task {
             Task(i32),
         }
     }
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct AbortedInstallCode {
@@ -359,6 +400,7 @@ pub mod execution_task {
     }
     /// Nested message and enum types in `AbortedInstallCode`.
     pub mod aborted_install_code {
+        #[derive(serde::Serialize, serde::Deserialize)]
         #[allow(clippy::derive_partial_eq_without_eq)]
         #[derive(Clone, PartialEq, ::prost::Oneof)]
         pub enum Message {
@@ -368,7 +410,19 @@ pub mod execution_task {
             Ingress(super::super::super::super::ingress::v1::Ingress),
         }
     }
-    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[derive(
+        serde::Serialize,
+        serde::Deserialize,
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration,
+    )]
     #[repr(i32)]
     pub enum CanisterTask {
         Unspecified = 0,
@@ -397,6 +451,7 @@ pub mod execution_task {
             }
         }
     }
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Task {
@@ -406,6 +461,7 @@ pub mod execution_task {
         AbortedInstallCode(AbortedInstallCode),
     }
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ConsumedCyclesByUseCase {
@@ -414,12 +470,14 @@ pub struct ConsumedCyclesByUseCase {
     #[prost(message, optional, tag = "2")]
     pub cycles: ::core::option::Option<super::super::super::types::v1::NominalCycles>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterChangeFromUser {
     #[prost(message, optional, tag = "1")]
     pub user_id: ::core::option::Option<super::super::super::types::v1::PrincipalId>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterChangeFromCanister {
@@ -428,15 +486,18 @@ pub struct CanisterChangeFromCanister {
     #[prost(uint64, optional, tag = "2")]
     pub canister_version: ::core::option::Option<u64>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterCreation {
     #[prost(message, repeated, tag = "1")]
     pub controllers: ::prost::alloc::vec::Vec<super::super::super::types::v1::PrincipalId>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterCodeUninstall {}
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterCodeDeployment {
@@ -448,12 +509,14 @@ pub struct CanisterCodeDeployment {
     #[prost(bytes = "vec", tag = "2")]
     pub module_hash: ::prost::alloc::vec::Vec<u8>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterControllersChange {
     #[prost(message, repeated, tag = "1")]
     pub controllers: ::prost::alloc::vec::Vec<super::super::super::types::v1::PrincipalId>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterLoadSnapshot {
@@ -462,6 +525,7 @@ pub struct CanisterLoadSnapshot {
     #[prost(uint64, tag = "2")]
     pub taken_at_timestamp: u64,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterChange {
@@ -476,6 +540,7 @@ pub struct CanisterChange {
 }
 /// Nested message and enum types in `CanisterChange`.
 pub mod canister_change {
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum ChangeOrigin {
@@ -484,6 +549,7 @@ pub mod canister_change {
         #[prost(message, tag = "4")]
         CanisterChangeFromCanister(super::CanisterChangeFromCanister),
     }
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum ChangeDetails {
@@ -499,6 +565,7 @@ pub mod canister_change {
         CanisterLoadSnapshot(super::CanisterLoadSnapshot),
     }
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterHistory {
@@ -507,12 +574,14 @@ pub struct CanisterHistory {
     #[prost(uint64, tag = "2")]
     pub total_num_changes: u64,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Unsigned128 {
     #[prost(bytes = "vec", tag = "1")]
     pub raw: ::prost::alloc::vec::Vec<u8>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TotalQueryStats {
@@ -525,6 +594,7 @@ pub struct TotalQueryStats {
     #[prost(message, optional, tag = "4")]
     pub egress_payload_size: ::core::option::Option<Unsigned128>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WasmChunkData {
@@ -535,6 +605,7 @@ pub struct WasmChunkData {
     #[prost(uint64, tag = "3")]
     pub length: u64,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WasmChunkStoreMetadata {
@@ -543,6 +614,35 @@ pub struct WasmChunkStoreMetadata {
     #[prost(uint64, tag = "2")]
     pub size: u64,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LogVisibilityAllowedViewers {
+    #[prost(message, repeated, tag = "1")]
+    pub principals: ::prost::alloc::vec::Vec<super::super::super::types::v1::PrincipalId>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LogVisibilityV2 {
+    #[prost(oneof = "log_visibility_v2::LogVisibilityV2", tags = "1, 2, 3")]
+    pub log_visibility_v2: ::core::option::Option<log_visibility_v2::LogVisibilityV2>,
+}
+/// Nested message and enum types in `LogVisibilityV2`.
+pub mod log_visibility_v2 {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum LogVisibilityV2 {
+        #[prost(int32, tag = "1")]
+        Controllers(i32),
+        #[prost(int32, tag = "2")]
+        Public(i32),
+        #[prost(message, tag = "3")]
+        AllowedViewers(super::LogVisibilityAllowedViewers),
+    }
+}
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterLogRecord {
@@ -553,12 +653,23 @@ pub struct CanisterLogRecord {
     #[prost(bytes = "vec", tag = "3")]
     pub content: ::prost::alloc::vec::Vec<u8>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EnvironmentVariable {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub value: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SnapshotId {
     #[prost(bytes = "vec", tag = "1")]
     pub content: ::prost::alloc::vec::Vec<u8>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CanisterStateBits {
@@ -638,7 +749,8 @@ pub struct CanisterStateBits {
     /// Statistics on query execution for entire lifetime of canister.
     #[prost(message, optional, tag = "41")]
     pub total_query_stats: ::core::option::Option<TotalQueryStats>,
-    /// Log visibility for the canister.
+    /// Log visibility for the canister. Superseded by `log_visibility_v2`, kept
+    /// so that older replicas can still read the checkpoint.
     #[prost(enumeration = "LogVisibility", tag = "42")]
     pub log_visibility: i32,
     /// Log records of the canister.
@@ -656,11 +768,21 @@ pub struct CanisterStateBits {
     /// The next local snapshot ID.
     #[prost(uint64, tag = "46")]
     pub next_snapshot_id: u64,
+    /// Environment variables of the canister, sorted by name.
+    #[prost(message, repeated, tag = "48")]
+    pub environment_variables: ::prost::alloc::vec::Vec<EnvironmentVariable>,
+    /// Log visibility for the canister, including the allowed viewers.
+    #[prost(message, optional, tag = "49")]
+    pub log_visibility_v2: ::core::option::Option<LogVisibilityV2>,
+    /// Whether Wasm backtraces are attached to the errors of traps.
+    #[prost(bool, tag = "50")]
+    pub wasm_backtraces: bool,
     #[prost(oneof = "canister_state_bits::CanisterStatus", tags = "11, 12, 13")]
     pub canister_status: ::core::option::Option<canister_state_bits::CanisterStatus>,
 }
 /// Nested message and enum types in `CanisterStateBits`.
 pub mod canister_state_bits {
+    #[derive(serde::Serialize, serde::Deserialize)]
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum CanisterStatus {
@@ -672,7 +794,19 @@ pub mod canister_state_bits {
         Stopped(super::CanisterStatusStopped),
     }
 }
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ::prost::Enumeration,
+)]
 #[repr(i32)]
 pub enum CustomSectionType {
     Unspecified = 0,
@@ -701,7 +835,19 @@ impl CustomSectionType {
         }
     }
 }
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ::prost::Enumeration,
+)]
 #[repr(i32)]
 pub enum NextScheduledMethod {
     Unspecified = 0,
@@ -733,7 +879,19 @@ impl NextScheduledMethod {
         }
     }
 }
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ::prost::Enumeration,
+)]
 #[repr(i32)]
 pub enum CyclesUseCase {
     Unspecified = 0,
@@ -799,7 +957,19 @@ impl CyclesUseCase {
         }
     }
 }
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ::prost::Enumeration,
+)]
 #[repr(i32)]
 pub enum LogVisibility {
     Unspecified = 0,
@@ -828,3 +998,44 @@ impl LogVisibility {
         }
     }
 }
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ::prost::Enumeration,
+)]
+#[repr(i32)]
+pub enum SnapshotSource {
+    Unspecified = 0,
+    TakenFromCanister = 1,
+    MetadataUpload = 2,
+}
+impl SnapshotSource {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            SnapshotSource::Unspecified => "SNAPSHOT_SOURCE_UNSPECIFIED",
+            SnapshotSource::TakenFromCanister => "SNAPSHOT_SOURCE_TAKEN_FROM_CANISTER",
+            SnapshotSource::MetadataUpload => "SNAPSHOT_SOURCE_METADATA_UPLOAD",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "SNAPSHOT_SOURCE_UNSPECIFIED" => Some(Self::Unspecified),
+            "SNAPSHOT_SOURCE_TAKEN_FROM_CANISTER" => Some(Self::TakenFromCanister),
+            "SNAPSHOT_SOURCE_METADATA_UPLOAD" => Some(Self::MetadataUpload),
+            _ => None,
+        }
+    }
+}
@@ -121,6 +121,7 @@ pub struct IngressHistoryState {
     #[prost(uint64, tag = "3")]
     pub next_terminal_time: u64,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Ingress {
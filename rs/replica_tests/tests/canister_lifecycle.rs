@@ -5,8 +5,9 @@ use ic_config::Config;
 use ic_error_types::{ErrorCode, RejectCode};
 use ic_management_canister_types::{
     self as ic00, CanisterChange, CanisterIdRecord, CanisterInstallMode,
-    CanisterSettingsArgsBuilder, CanisterStatusResultV2, CanisterStatusType, EmptyBlob,
-    InstallCodeArgs, LogVisibility, Method, Payload, UpdateSettingsArgs, IC_00,
+    CanisterSettingsArgsBuilder, CanisterStatusResultV2, CanisterStatusType, CyclesBurned,
+    EmptyBlob, InstallCodeArgs, LogVisibility, MemoryMetrics, Method, Payload, UpdateSettingsArgs,
+    IC_00,
 };
 use ic_registry_provisional_whitelist::ProvisionalWhitelist;
 use ic_replica_tests as utils;
@@ -720,6 +721,20 @@ fn can_get_canister_information() {
                 0u128,
                 0u128,
                 Some(0),
+                vec![],
+                false,
+                MemoryMetrics::new(
+                    NumBytes::from(0),
+                    NumBytes::from(0),
+                    NumBytes::from(0),
+                    NumBytes::from(0),
+                    NumBytes::from(0),
+                    NumBytes::from((2 * size_of::<CanisterChange>() + 2 * size_of::<PrincipalId>()) as u64),
+                    NumBytes::from(0),
+                    NumBytes::from(0),
+                    NumBytes::from(0),
+                ),
+                CyclesBurned::default(),
             )
         );
 
@@ -777,7 +792,11 @@ fn can_get_canister_information() {
                     0u128,
                     0u128,
                     0u128,
-                    Some(0)
+                    Some(0),
+                    vec![],
+                    false,
+                    MemoryMetrics::default(),
+                    CyclesBurned::default(),
                 ),
                 CanisterStatusResultV2::decode(&res).unwrap(),
                 2 * BALANCE_EPSILON,
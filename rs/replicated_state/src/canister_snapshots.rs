@@ -1,10 +1,11 @@
+use ic_management_canister_types::SnapshotSource;
 use ic_types::{CanisterId, NumBytes, SnapshotId, Time};
 use ic_wasm_types::CanisterModule;
 
 use crate::{
-    canister_state::execution_state::Memory,
-    canister_state::system_state::wasm_chunk_store::WasmChunkStore, CanisterState, NumWasmPages,
-    PageMap,
+    canister_state::execution_state::{Global, Memory},
+    canister_state::system_state::wasm_chunk_store::WasmChunkStore,
+    CanisterState, NumWasmPages, PageMap,
 };
 
 use std::{
@@ -16,7 +17,7 @@ use std::{
 ///
 /// Additionally, keeps track of all the accumulated changes
 /// since the last flush to the disk.
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct CanisterSnapshots {
     pub(crate) snapshots: BTreeMap<SnapshotId, Arc<CanisterSnapshot>>,
     /// Snapshot operations are consumed by the `StateManager` in order to
@@ -57,6 +58,13 @@ impl CanisterSnapshots {
         self.snapshots.get(&snapshot_id)
     }
 
+    /// Returns a mutable reference of the canister snapshot identified by `snapshot_id`.
+    ///
+    /// The snapshot is cloned if it is shared with another copy of the state.
+    pub fn get_mut(&mut self, snapshot_id: SnapshotId) -> Option<&mut CanisterSnapshot> {
+        self.snapshots.get_mut(&snapshot_id).map(Arc::make_mut)
+    }
+
     /// Remove snapshot identified by `snapshot_id` from the collection of snapshots.
     ///
     /// Additionally, adds a new item to the `unflushed_changes`
@@ -167,10 +175,12 @@ impl From<&PageMemory> for Memory {
 }
 
 /// Contains all information related to a canister's execution state.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ExecutionStateSnapshot {
     /// The raw canister module.
     pub wasm_binary: CanisterModule,
+    /// The values of the exported globals.
+    pub exported_globals: Vec<Global>,
     /// Snapshot of stable memory.
     pub stable_memory: PageMemory,
     /// Snapshot of wasm memory.
@@ -178,10 +188,12 @@ pub struct ExecutionStateSnapshot {
 }
 
 /// Contains all information related to a canister snapshot.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CanisterSnapshot {
     /// Identifies the canister to which this snapshot belongs.
     canister_id: CanisterId,
+    /// Whether the snapshot was taken from the canister or uploaded.
+    source: SnapshotSource,
     /// The timestamp indicating the moment the snapshot was captured.
     taken_at_timestamp: Time,
     /// The canister version at the time of taking the snapshot.
@@ -198,8 +210,10 @@ pub struct CanisterSnapshot {
 }
 
 impl CanisterSnapshot {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         canister_id: CanisterId,
+        source: SnapshotSource,
         taken_at_timestamp: Time,
         canister_version: u64,
         certified_data: Vec<u8>,
@@ -209,6 +223,7 @@ impl CanisterSnapshot {
     ) -> CanisterSnapshot {
         Self {
             canister_id,
+            source,
             taken_at_timestamp,
             canister_version,
             certified_data,
@@ -225,12 +240,14 @@ impl CanisterSnapshot {
                 .as_ref()
                 .map(|execution_state| ExecutionStateSnapshot {
                     wasm_binary: execution_state.wasm_binary.binary.clone(),
+                    exported_globals: execution_state.exported_globals.clone(),
                     stable_memory: PageMemory::from(&execution_state.stable_memory),
                     wasm_memory: PageMemory::from(&execution_state.wasm_memory),
                 });
 
         Self {
             canister_id: canister.canister_id(),
+            source: SnapshotSource::TakenFromCanister,
             taken_at_timestamp,
             canister_version: canister.system_state.canister_version,
             certified_data: canister.system_state.certified_data.clone(),
@@ -244,6 +261,10 @@ impl CanisterSnapshot {
         self.canister_id
     }
 
+    pub fn source(&self) -> SnapshotSource {
+        self.source
+    }
+
     pub fn canister_version(&self) -> u64 {
         self.canister_version
     }
@@ -256,10 +277,20 @@ impl CanisterSnapshot {
         self.size
     }
 
+    /// Increases the memory accounted to this snapshot, e.g. after a new
+    /// chunk has been uploaded to its Wasm chunk store.
+    pub fn add_size(&mut self, bytes: NumBytes) {
+        self.size += bytes;
+    }
+
     pub fn execution_snapshot(&self) -> Option<&ExecutionStateSnapshot> {
         self.execution_snapshot.as_ref()
     }
 
+    pub fn execution_snapshot_mut(&mut self) -> Option<&mut ExecutionStateSnapshot> {
+        self.execution_snapshot.as_mut()
+    }
+
     pub fn stable_memory(&self) -> Option<&PageMemory> {
         self.execution_snapshot
             .as_ref()
@@ -278,10 +309,20 @@ impl CanisterSnapshot {
             .map(|exec| &exec.wasm_binary)
     }
 
+    pub fn exported_globals(&self) -> Option<&Vec<Global>> {
+        self.execution_snapshot
+            .as_ref()
+            .map(|exec| &exec.exported_globals)
+    }
+
     pub fn chunk_store(&self) -> &WasmChunkStore {
         &self.chunk_store
     }
 
+    pub fn chunk_store_mut(&mut self) -> &mut WasmChunkStore {
+        &mut self.chunk_store
+    }
+
     pub fn certified_data(&self) -> &Vec<u8> {
         &self.certified_data
     }
@@ -306,6 +347,7 @@ mod tests {
     fn test_push_and_remove_snapshot() {
         let execution_snapshot = ExecutionStateSnapshot {
             wasm_binary: CanisterModule::new(vec![1, 2, 3]),
+            exported_globals: vec![Global::I32(1)],
             stable_memory: PageMemory {
                 page_map: PageMap::new_for_testing(),
                 size: NumWasmPages::new(10),
@@ -318,6 +360,7 @@ mod tests {
         let canister_id = canister_test_id(0);
         let snapshot = CanisterSnapshot::new(
             canister_id,
+            SnapshotSource::TakenFromCanister,
             UNIX_EPOCH,
             0,
             vec![],
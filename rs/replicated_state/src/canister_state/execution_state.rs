@@ -141,6 +141,30 @@ impl TryFrom<pb::Global> for Global {
     }
 }
 
+impl From<&Global> for ic_management_canister_types::Global {
+    fn from(item: &Global) -> Self {
+        match item {
+            Global::I32(value) => Self::I32(*value),
+            Global::I64(value) => Self::I64(*value),
+            Global::F32(value) => Self::F32(*value),
+            Global::F64(value) => Self::F64(*value),
+            Global::V128(value) => Self::V128(*value),
+        }
+    }
+}
+
+impl From<&ic_management_canister_types::Global> for Global {
+    fn from(item: &ic_management_canister_types::Global) -> Self {
+        match item {
+            ic_management_canister_types::Global::I32(value) => Self::I32(*value),
+            ic_management_canister_types::Global::I64(value) => Self::I64(*value),
+            ic_management_canister_types::Global::F32(value) => Self::F32(*value),
+            ic_management_canister_types::Global::F64(value) => Self::F64(*value),
+            ic_management_canister_types::Global::V128(value) => Self::V128(*value),
+        }
+    }
+}
+
 /// A set of the functions that a Wasm module exports.
 ///
 /// Arc is used to make cheap clones of this during snapshots.
@@ -538,16 +562,34 @@ impl ExecutionState {
 
     /// Returns the memory currently used by the `ExecutionState`.
     pub fn memory_usage(&self) -> NumBytes {
-        // We use 8 bytes per global.
-        let globals_size_bytes = 8 * self.exported_globals.len() as u64;
-        let wasm_binary_size_bytes = self.wasm_binary.binary.len() as u64;
+        self.wasm_memory_usage()
+            + self.stable_memory_usage()
+            + self.globals_memory_usage()
+            + self.wasm_binary_memory_usage()
+            + self.metadata.memory_usage()
+    }
+
+    /// Returns the size of the Wasm memory in bytes.
+    pub fn wasm_memory_usage(&self) -> NumBytes {
         num_bytes_try_from(self.wasm_memory.size)
             .expect("could not convert from wasm memory number of pages to bytes")
-            + num_bytes_try_from(self.stable_memory.size)
-                .expect("could not convert from stable memory number of pages to bytes")
-            + NumBytes::from(globals_size_bytes)
-            + NumBytes::from(wasm_binary_size_bytes)
-            + self.metadata.memory_usage()
+    }
+
+    /// Returns the size of the stable memory in bytes.
+    pub fn stable_memory_usage(&self) -> NumBytes {
+        num_bytes_try_from(self.stable_memory.size)
+            .expect("could not convert from stable memory number of pages to bytes")
+    }
+
+    /// Returns the memory used by the exported globals in bytes.
+    pub fn globals_memory_usage(&self) -> NumBytes {
+        // We use 8 bytes per global.
+        NumBytes::from(8 * self.exported_globals.len() as u64)
+    }
+
+    /// Returns the size of the Wasm binary in bytes.
+    pub fn wasm_binary_memory_usage(&self) -> NumBytes {
+        NumBytes::from(self.wasm_binary.binary.len() as u64)
     }
 
     /// Returns the number of global variables in the Wasm module.
@@ -356,6 +356,14 @@ pub struct SystemState {
 
     /// Next local snapshot id.
     pub next_snapshot_id: u64,
+
+    /// Environment variables set by the controllers in the canister settings.
+    /// The canister reads them with the `ic0.env_var_*` system API calls.
+    pub environment_variables: BTreeMap<String, String>,
+
+    /// Whether a symbolized Wasm backtrace is attached to the error and the
+    /// canister log record when the canister traps.
+    pub wasm_backtraces: bool,
 }
 
 /// A wrapper around the different canister statuses.
@@ -720,6 +728,8 @@ impl SystemState {
             canister_log: Default::default(),
             wasm_memory_limit: None,
             next_snapshot_id: 0,
+            environment_variables: BTreeMap::new(),
+            wasm_backtraces: false,
         }
     }
 
@@ -747,6 +757,8 @@ impl SystemState {
         canister_log: CanisterLog,
         wasm_memory_limit: Option<NumBytes>,
         next_snapshot_id: u64,
+        environment_variables: BTreeMap<String, String>,
+        wasm_backtraces: bool,
     ) -> Self {
         Self {
             controllers,
@@ -773,6 +785,8 @@ impl SystemState {
             canister_log,
             wasm_memory_limit,
             next_snapshot_id,
+            environment_variables,
+            wasm_backtraces,
         }
     }
 
@@ -622,16 +622,45 @@ fn canister_state_log_visibility_round_trip() {
 
 #[test]
 fn compatibility_for_log_visibility() {
+    use ic_protobuf::state::canister_state_bits::v1 as pb;
+
     // If this fails, you are making a potentially incompatible change to `LogVisibility`.
     // See note [Handling changes to Enums in Replicated State] for how to proceed.
     assert_eq!(
         LogVisibility::iter()
-            .map(|x| x as i32)
+            .map(|x| pb::LogVisibility::from(&x) as i32)
             .collect::<Vec<i32>>(),
         [1, 2]
     );
 }
 
+#[test]
+fn canister_state_log_visibility_v2_round_trip() {
+    use ic_protobuf::state::canister_state_bits::v1 as pb;
+
+    let allowed_viewers =
+        LogVisibility::AllowedViewers(vec![user_test_id(1).get(), canister_test_id(2).get()]);
+    for initial in
+        LogVisibility::iter().chain([LogVisibility::AllowedViewers(vec![]), allowed_viewers])
+    {
+        let encoded = pb::LogVisibilityV2::from(&initial);
+        let round_trip = LogVisibility::try_from(encoded).unwrap();
+
+        assert_eq!(initial, round_trip);
+    }
+}
+
+#[test]
+fn log_visibility_allowed_viewers_falls_back_to_controllers_in_v1() {
+    use ic_protobuf::state::canister_state_bits::v1 as pb;
+
+    let allowed_viewers = LogVisibility::AllowedViewers(vec![user_test_id(1).get()]);
+    assert_eq!(
+        pb::LogVisibility::from(&allowed_viewers),
+        pb::LogVisibility::Controllers
+    );
+}
+
 #[test]
 fn canister_state_canister_log_record_round_trip() {
     use ic_protobuf::state::canister_state_bits::v1 as pb;
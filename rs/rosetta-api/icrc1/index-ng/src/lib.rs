@@ -1,12 +1,16 @@
 use candid::{CandidType, Deserialize, Nat, Principal};
 use icrc_ledger_types::icrc1::account::{Account, Subaccount};
-use icrc_ledger_types::icrc1::transfer::BlockIndex;
+use icrc_ledger_types::icrc1::transfer::{BlockIndex, Memo};
 use icrc_ledger_types::icrc3::blocks::GenericBlock;
 use icrc_ledger_types::icrc3::transactions::Transaction;
 
 /// The maximum number of blocks to return in a single [get_blocks] request.
 pub const DEFAULT_MAX_BLOCKS_PER_RESPONSE: u64 = 2000;
 
+/// The maximum number of blocks of an account examined by a single
+/// [search_account_transactions] request.
+pub const MAX_BLOCKS_SCANNED_PER_SEARCH: u64 = 10_000;
+
 #[derive(CandidType, Debug, Deserialize, Clone)]
 pub enum IndexArg {
     Init(InitArg),
@@ -68,6 +72,65 @@ pub struct GetAccountTransactionsError {
 pub type GetAccountTransactionsResult =
     Result<GetAccountTransactionsResponse, GetAccountTransactionsError>;
 
+#[derive(CandidType, Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub enum OperationKind {
+    Mint,
+    Burn,
+    Transfer,
+    Approve,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub enum MemoFilter {
+    Exact(Memo),
+    Prefix(Memo),
+}
+
+#[derive(CandidType, Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct TransactionFilter {
+    // Only return transactions whose block timestamp, in nanoseconds
+    // since the epoch, is greater than or equal to this value.
+    pub start_timestamp: Option<u64>,
+    // Only return transactions whose block timestamp, in nanoseconds
+    // since the epoch, is less than or equal to this value.
+    pub end_timestamp: Option<u64>,
+    // Only return transactions whose memo matches.
+    pub memo: Option<MemoFilter>,
+    // Only return transactions of this kind.
+    pub kind: Option<OperationKind>,
+    // Only return transactions that involve this account,
+    // e.g., as sender, receiver or spender.
+    pub counterparty: Option<Account>,
+}
+
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct SearchAccountTransactionsArgs {
+    pub account: Account,
+    // The txid of the last transaction seen by the client.
+    // If None then the results will start from the most recent
+    // txid. If set then the results will start from the next
+    // most recent txid after start (start won't be included).
+    pub start: Option<BlockIndex>,
+    // Maximum number of transactions to fetch.
+    pub max_results: Nat,
+    pub filter: TransactionFilter,
+}
+
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct SearchAccountTransactionsResponse {
+    pub balance: Nat,
+    pub transactions: Vec<TransactionWithId>,
+    // The txid of the oldest transaction the account has
+    pub oldest_tx_id: Option<BlockIndex>,
+    // The txid to use as start of the next request if there
+    // may be more matching transactions. None if the search
+    // is complete.
+    pub next_start: Option<BlockIndex>,
+}
+
+pub type SearchAccountTransactionsResult =
+    Result<SearchAccountTransactionsResponse, GetAccountTransactionsError>;
+
 #[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
 pub struct ListSubaccountsArgs {
     pub owner: Principal,
@@ -78,6 +141,47 @@ pub struct ListSubaccountsArgs {
     pub start: Option<Subaccount>,
 }
 
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct ListAllowancesArgs {
+    pub from_account: Account,
+    // The last spender seen by the client for the given account.
+    // This spender is excluded in the result.
+    // If None then the results will start from the first
+    // spender in natural order.
+    pub start: Option<Account>,
+    // Maximum number of allowances to fetch.
+    pub take: Option<u64>,
+}
+
+#[derive(CandidType, Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct AllowanceEntry {
+    pub from_account: Account,
+    pub to_spender: Account,
+    pub allowance: Nat,
+    pub expires_at: Option<u64>,
+}
+
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct ListSpendersArgs {
+    pub account: Account,
+    // The last spender seen by the client for the given account.
+    // This spender is excluded in the result.
+    // If None then the results will start from the first
+    // spender in natural order.
+    pub start: Option<Account>,
+    // Maximum number of spenders to fetch.
+    pub take: Option<u64>,
+}
+
+#[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
+pub struct ListAllowancesError {
+    pub message: String,
+}
+
+pub type ListAllowancesResult = Result<Vec<AllowanceEntry>, ListAllowancesError>;
+
+pub type ListSpendersResult = Result<Vec<Account>, ListAllowancesError>;
+
 #[derive(CandidType, Debug, Deserialize, PartialEq, Eq)]
 pub struct Status {
     pub num_blocks_synced: BlockIndex,
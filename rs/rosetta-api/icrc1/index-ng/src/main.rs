@@ -10,9 +10,13 @@ use ic_icrc1::blocks::{encoded_block_to_generic_block, generic_block_to_encoded_
 use ic_icrc1::endpoints::StandardRecord;
 use ic_icrc1::{Block, Operation};
 use ic_icrc1_index_ng::{
-    FeeCollectorRanges, GetAccountTransactionsArgs, GetAccountTransactionsResponse,
-    GetAccountTransactionsResult, GetBlocksMethod, IndexArg, InitArg, ListSubaccountsArgs, Log,
-    LogEntry, Status, TransactionWithId, UpgradeArg, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
+    AllowanceEntry, FeeCollectorRanges, GetAccountTransactionsArgs, GetAccountTransactionsError,
+    GetAccountTransactionsResponse, GetAccountTransactionsResult, GetBlocksMethod, IndexArg,
+    InitArg, ListAllowancesArgs, ListAllowancesError, ListAllowancesResult, ListSpendersArgs,
+    ListSpendersResult, ListSubaccountsArgs, Log, LogEntry, MemoFilter, OperationKind,
+    SearchAccountTransactionsArgs, SearchAccountTransactionsResponse,
+    SearchAccountTransactionsResult, Status, TransactionFilter, TransactionWithId, UpgradeArg,
+    DEFAULT_MAX_BLOCKS_PER_RESPONSE, MAX_BLOCKS_SCANNED_PER_SEARCH,
 };
 use ic_ledger_canister_core::runtime::total_memory_size_bytes;
 use ic_ledger_core::block::{BlockIndex as BlockIndex64, BlockType, EncodedBlock};
@@ -55,6 +59,8 @@ const BLOCK_LOG_INDEX_MEMORY_ID: MemoryId = MemoryId::new(1);
 const BLOCK_LOG_DATA_MEMORY_ID: MemoryId = MemoryId::new(2);
 const ACCOUNT_BLOCK_IDS_MEMORY_ID: MemoryId = MemoryId::new(3);
 const ACCOUNT_DATA_MEMORY_ID: MemoryId = MemoryId::new(4);
+const ALLOWANCES_MEMORY_ID: MemoryId = MemoryId::new(5);
+const ALLOWANCE_EXPIRATIONS_MEMORY_ID: MemoryId = MemoryId::new(6);
 
 const DEFAULT_RETRIEVE_BLOCKS_FROM_LEDGER_INTERVAL: Duration = Duration::from_secs(1);
 
@@ -77,6 +83,16 @@ type AccountBlockIdsMap = StableBTreeMap<AccountBlockIdsMapKey, (), VM>;
 type AccountDataMapKey = (AccountDataType, (Blob<29>, [u8; 32]));
 type AccountDataMap = StableBTreeMap<AccountDataMapKey, Tokens, VM>;
 
+// The key is the pair (approver, spender) where both accounts are
+// represented as principal of type Blob<29> and the effective subaccount
+type AllowancesMapKey = ((Blob<29>, [u8; 32]), (Blob<29>, [u8; 32]));
+type AllowancesMap = StableBTreeMap<AllowancesMapKey, StoredAllowance, VM>;
+
+// The key is the pair (expires_at, allowance key). An entry can be stale
+// if the allowance has been changed since, see [prune_expired_allowances].
+type AllowanceExpirationsMapKey = (u64, AllowancesMapKey);
+type AllowanceExpirationsMap = StableBTreeMap<AllowanceExpirationsMapKey, (), VM>;
+
 thread_local! {
     /// Static memory manager to manage the memory available for stable structures.
     static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> = RefCell::new(MemoryManager::init(DefaultMemoryImpl::default()));
@@ -104,6 +120,17 @@ thread_local! {
         RefCell::new(AccountDataMap::init(memory_manager.get(ACCOUNT_DATA_MEMORY_ID)))
     });
 
+    /// Map that contains the current allowances indexed by approver and spender.
+    static ALLOWANCES: RefCell<AllowancesMap> = with_memory_manager(|memory_manager| {
+        RefCell::new(AllowancesMap::init(memory_manager.get(ALLOWANCES_MEMORY_ID)))
+    });
+
+    /// Map that contains the expiration times of the allowances
+    /// used to prune expired allowances from [ALLOWANCES].
+    static ALLOWANCE_EXPIRATIONS: RefCell<AllowanceExpirationsMap> = with_memory_manager(|memory_manager| {
+        RefCell::new(AllowanceExpirationsMap::init(memory_manager.get(ALLOWANCE_EXPIRATIONS_MEMORY_ID)))
+    });
+
     /// Profiling data to understand cycles usage
     static PROFILING_DATA: RefCell<SpanStats> = RefCell::new(SpanStats::default());
 
@@ -136,6 +163,17 @@ struct State {
     /// index. Lower values will result in a more responsive UI, but higher costs due to increased
     /// cycle burn for the index, ledger and archive(s).
     retrieve_blocks_from_ledger_interval: Option<Duration>,
+
+    /// The number of blocks in the block log that have been processed to
+    /// build the [ALLOWANCES] map. This is smaller than the number of blocks
+    /// when the index is upgraded from a version that didn't track allowances.
+    #[serde(default)]
+    num_blocks_with_allowances_indexed: u64,
+
+    /// The timestamp of the last block processed to build the [ALLOWANCES]
+    /// map. Allowances that expire at or before this time are pruned.
+    #[serde(default)]
+    allowances_timestamp: u64,
 }
 
 impl State {
@@ -157,6 +195,8 @@ impl Default for State {
             fee_collectors: Default::default(),
             last_fee: None,
             retrieve_blocks_from_ledger_interval: None,
+            num_blocks_with_allowances_indexed: 0,
+            allowances_timestamp: 0,
         }
     }
 }
@@ -208,6 +248,26 @@ impl Storable for AccountDataType {
     };
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct StoredAllowance {
+    amount: Tokens,
+    expires_at: Option<u64>,
+}
+
+impl Storable for StoredAllowance {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        let mut buf = vec![];
+        ciborium::ser::into_writer(self, &mut buf).expect("failed to encode allowance");
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        ciborium::de::from_reader(&bytes[..]).expect("failed to decode allowance")
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
 // Ephemeral data that doesn't need to be saved between upgrades
 #[derive(Clone, Debug, Default)]
 struct Cache {
@@ -222,6 +282,27 @@ fn test_account_data_type_storable() {
     );
 }
 
+#[test]
+fn test_stored_allowance_storable() {
+    let allowance = StoredAllowance {
+        amount: Tokens::from(1_000_000u64),
+        expires_at: Some(42),
+    };
+    assert_eq!(allowance, StoredAllowance::from_bytes(allowance.to_bytes()));
+}
+
+#[test]
+fn test_check_allowances_indexed() {
+    with_blocks(|blocks| blocks.append(&vec![]).unwrap());
+    assert!(check_allowances_indexed().is_err());
+
+    mutate_state(|state| {
+        state.num_blocks_with_allowances_indexed = 1;
+        state.allowances_timestamp = 42;
+    });
+    assert_eq!(check_allowances_indexed(), Ok(42));
+}
+
 /// A helper function to access the scalar state.
 fn with_state<R>(f: impl FnOnce(&State) -> R) -> R {
     STATE.with(|cell| f(cell.borrow().get()))
@@ -259,6 +340,16 @@ fn with_account_data<R>(f: impl FnOnce(&mut AccountDataMap) -> R) -> R {
     ACCOUNT_DATA.with(|cell| f(&mut cell.borrow_mut()))
 }
 
+/// A helper function to access the allowances.
+fn with_allowances<R>(f: impl FnOnce(&mut AllowancesMap) -> R) -> R {
+    ALLOWANCES.with(|cell| f(&mut cell.borrow_mut()))
+}
+
+/// A helper function to access the allowance expirations.
+fn with_allowance_expirations<R>(f: impl FnOnce(&mut AllowanceExpirationsMap) -> R) -> R {
+    ALLOWANCE_EXPIRATIONS.with(|cell| f(&mut cell.borrow_mut()))
+}
+
 /// A helper function that returns a decoded block stored in the
 /// block log at the given index or None if there is no block at that index.
 /// This function can trap if the index at the given block cannot be decoded
@@ -288,11 +379,23 @@ fn change_balance(account: Account, f: impl FnOnce(Tokens) -> Tokens) {
 }
 
 fn balance_key(account: Account) -> (AccountDataType, (Blob<29>, [u8; 32])) {
+    (AccountDataType::Balance, account_key(account))
+}
+
+fn account_key(account: Account) -> (Blob<29>, [u8; 32]) {
     let owner = Blob::try_from(account.owner.as_slice()).unwrap();
-    (
-        AccountDataType::Balance,
-        (owner, *account.effective_subaccount()),
-    )
+    (owner, *account.effective_subaccount())
+}
+
+fn account_from_key((owner, subaccount): (Blob<29>, [u8; 32])) -> Account {
+    Account {
+        owner: Principal::from_slice(owner.as_slice()),
+        subaccount: Some(subaccount),
+    }
+}
+
+fn allowance_key(from: Account, spender: Account) -> AllowancesMapKey {
+    (account_key(from), account_key(spender))
 }
 
 #[init]
@@ -561,6 +664,7 @@ pub async fn build_index() -> Option<()> {
             state.is_build_index_running = false;
         });
     });
+    index_allowances_of_stored_blocks(with_state(|state| state.max_blocks_per_response));
     let num_indexed = match find_get_blocks_method().await {
         GetBlocksMethod::GetBlocks => fetch_blocks_via_get_blocks().await?,
         GetBlocksMethod::ICRC3GetBlocks => fetch_blocks_via_icrc3().await?,
@@ -702,6 +806,14 @@ fn append_block(block_index: BlockIndex64, block: GenericBlock) {
 
         // change the balance of the involved accounts
         process_balance_changes(block_index, &decoded_block);
+
+        // change the allowances of the involved accounts unless there are
+        // older blocks that still need to be processed, see
+        // [index_allowances_of_stored_blocks]
+        if with_state(|state| state.num_blocks_with_allowances_indexed) == block_index {
+            process_allowance_changes(block_index, &decoded_block);
+            mutate_state(|state| state.num_blocks_with_allowances_indexed = block_index + 1);
+        }
     });
 }
 
@@ -836,6 +948,143 @@ fn process_balance_changes(block_index: BlockIndex64, block: &Block<Tokens>) {
     );
 }
 
+fn process_allowance_changes(block_index: BlockIndex64, block: &Block<Tokens>) {
+    prune_expired_allowances(block.timestamp);
+    measure_span(
+        &PROFILING_DATA,
+        "append_blocks.process_allowance_changes",
+        move || match block.transaction.operation {
+            Operation::Mint { .. } => {}
+            Operation::Burn {
+                from,
+                spender: Some(spender),
+                amount,
+            } => decrease_allowance(block_index, from, spender, amount),
+            Operation::Burn { spender: None, .. } => {}
+            Operation::Transfer {
+                from,
+                spender: Some(spender),
+                amount,
+                fee,
+                ..
+            } => {
+                let fee = block.effective_fee.or(fee).unwrap_or_else(Tokens::zero);
+                let used_allowance = amount.checked_add(&fee).unwrap_or_else(|| {
+                    ic_cdk::trap(&format!(
+                        "token amount overflow while indexing block {block_index}"
+                    ))
+                });
+                decrease_allowance(block_index, from, spender, used_allowance);
+            }
+            Operation::Transfer { spender: None, .. } => {}
+            Operation::Approve {
+                from,
+                spender,
+                amount,
+                expires_at,
+                ..
+            } => {
+                let key = allowance_key(from, spender);
+                if amount.is_zero() {
+                    with_allowances(|allowances| allowances.remove(&key));
+                } else {
+                    with_allowances(|allowances| {
+                        allowances.insert(key, StoredAllowance { amount, expires_at })
+                    });
+                    if let Some(expires_at) = expires_at {
+                        with_allowance_expirations(|expirations| {
+                            expirations.insert((expires_at, allowance_key(from, spender)), ())
+                        });
+                    }
+                }
+            }
+        },
+    );
+}
+
+/// Removes the allowances that expire at or before `now` from [ALLOWANCES]
+/// and records `now` as the time up to which the allowances are indexed.
+fn prune_expired_allowances(now: u64) {
+    while let Some((expiration, ())) =
+        with_allowance_expirations(|expirations| expirations.first_key_value())
+    {
+        let (expires_at, key) = &expiration;
+        if *expires_at > now {
+            break;
+        }
+        with_allowance_expirations(|expirations| expirations.remove(&expiration));
+        // The allowance may have been changed after this expiration was recorded.
+        with_allowances(|allowances| {
+            if allowances
+                .get(key)
+                .is_some_and(|allowance| allowance.expires_at == Some(*expires_at))
+            {
+                allowances.remove(key);
+            }
+        });
+    }
+    mutate_state(|state| state.allowances_timestamp = state.allowances_timestamp.max(now));
+}
+
+fn decrease_allowance(block_index: BlockIndex64, from: Account, spender: Account, amount: Tokens) {
+    let key = allowance_key(from, spender);
+    with_allowances(|allowances| {
+        let Some(mut allowance) = allowances.get(&key) else {
+            log!(
+                P0,
+                "Block {} uses an allowance of {} for spender {} but no allowance is recorded",
+                block_index,
+                from,
+                spender
+            );
+            return;
+        };
+        match allowance.amount.checked_sub(&amount) {
+            Some(remaining) if !remaining.is_zero() => {
+                allowance.amount = remaining;
+                allowances.insert(key, allowance);
+            }
+            Some(_) => {
+                allowances.remove(&key);
+            }
+            None => {
+                log!(
+                    P0,
+                    "Block {} uses {} of the allowance of {} for spender {} but only {} is recorded",
+                    block_index,
+                    amount,
+                    from,
+                    spender,
+                    allowance.amount
+                );
+                allowances.remove(&key);
+            }
+        }
+    });
+}
+
+/// Processes at most `max_blocks` blocks of the block log that were appended
+/// before the index started tracking allowances. This is a no-op once the
+/// allowances have caught up with the block log.
+fn index_allowances_of_stored_blocks(max_blocks: u64) {
+    let start = with_state(|state| state.num_blocks_with_allowances_indexed);
+    let end = with_blocks(|blocks| blocks.len()).min(start.saturating_add(max_blocks));
+    if start >= end {
+        return;
+    }
+    for block_index in start..end {
+        let block = get_decoded_block(block_index).unwrap_or_else(|| {
+            trap(&format!(
+                "Block {} not found in the block log while indexing allowances",
+                block_index
+            ))
+        });
+        process_allowance_changes(block_index, &block);
+    }
+    mutate_state(|state| state.num_blocks_with_allowances_indexed = end);
+    log!(P1, "Indexed allowances of blocks [{}, {})", start, end);
+}
+
 fn debit(block_index: BlockIndex64, account: Account, amount: Tokens) {
     change_balance(account, |balance| {
         balance.checked_sub(&amount).unwrap_or_else(|| {
@@ -998,6 +1247,140 @@ fn get_account_transactions(arg: GetAccountTransactionsArgs) -> GetAccountTransa
     })
 }
 
+#[query]
+#[candid_method(query)]
+fn search_account_transactions(
+    arg: SearchAccountTransactionsArgs,
+) -> SearchAccountTransactionsResult {
+    let filter = arg.filter;
+    if let (Some(start_timestamp), Some(end_timestamp)) =
+        (filter.start_timestamp, filter.end_timestamp)
+    {
+        if start_timestamp > end_timestamp {
+            return Err(GetAccountTransactionsError {
+                message: format!(
+                    "start_timestamp {} is greater than end_timestamp {}",
+                    start_timestamp, end_timestamp
+                ),
+            });
+        }
+    }
+    let length = arg
+        .max_results
+        .0
+        .to_u64()
+        .ok_or_else(|| GetAccountTransactionsError {
+            message: "max_results must be a u64".to_string(),
+        })?
+        .min(with_state(|opts| opts.max_blocks_per_response))
+        .min(usize::MAX as u64) as usize;
+    let start = match arg.start {
+        Some(n) => n.0.to_u64().ok_or_else(|| GetAccountTransactionsError {
+            message: "start must be a u64".to_string(),
+        })?,
+        None => u64::MAX,
+    };
+    let key = account_block_ids_key(arg.account, start);
+    // The block ids of the account are iterated from the most recent one,
+    // so the scan stops at the first block older than start_timestamp.
+    let mut transactions = vec![];
+    let mut next_start = None;
+    let mut num_scanned = 0;
+    with_account_block_ids(|account_block_ids| {
+        for (k, _) in account_block_ids
+            .range(key..)
+            .take_while(|(k, _)| k.0 == key.0)
+            .filter(|(k, _)| k.1 .0 < start)
+        {
+            let id = k.1 .0;
+            if transactions.len() == length || num_scanned == MAX_BLOCKS_SCANNED_PER_SEARCH {
+                // there may be more matching transactions starting from the
+                // last block examined
+                next_start = Some(id + 1);
+                break;
+            }
+            num_scanned += 1;
+            let block = get_decoded_block(id).unwrap_or_else(|| {
+                trap(&format!(
+                    "Block {} not found in the block log, account blocks map is corrupted!",
+                    id
+                ))
+            });
+            if filter
+                .start_timestamp
+                .is_some_and(|start_timestamp| block.timestamp < start_timestamp)
+            {
+                break;
+            }
+            if block_matches_filter(&block, &filter) {
+                transactions.push(TransactionWithId {
+                    id: id.into(),
+                    transaction: block.into(),
+                });
+            }
+        }
+    });
+    let oldest_tx_id = get_oldest_tx_id(arg.account).map(|tx_id| tx_id.into());
+    let balance = get_balance(arg.account).into();
+    Ok(SearchAccountTransactionsResponse {
+        balance,
+        transactions,
+        oldest_tx_id,
+        next_start: next_start.map(|tx_id: BlockIndex64| tx_id.into()),
+    })
+}
+
+fn block_matches_filter(block: &Block<Tokens>, filter: &TransactionFilter) -> bool {
+    if filter
+        .end_timestamp
+        .is_some_and(|end_timestamp| block.timestamp > end_timestamp)
+    {
+        return false;
+    }
+    if let Some(memo_filter) = &filter.memo {
+        let Some(memo) = &block.transaction.memo else {
+            return false;
+        };
+        let matches = match memo_filter {
+            MemoFilter::Exact(expected) => memo == expected,
+            MemoFilter::Prefix(prefix) => memo.0.starts_with(&prefix.0),
+        };
+        if !matches {
+            return false;
+        }
+    }
+    if let Some(kind) = filter.kind {
+        let block_kind = match block.transaction.operation {
+            Operation::Mint { .. } => OperationKind::Mint,
+            Operation::Burn { .. } => OperationKind::Burn,
+            Operation::Transfer { .. } => OperationKind::Transfer,
+            Operation::Approve { .. } => OperationKind::Approve,
+        };
+        if kind != block_kind {
+            return false;
+        }
+    }
+    if let Some(counterparty) = filter.counterparty {
+        let involved = match block.transaction.operation {
+            Operation::Mint { to, .. } => vec![to],
+            Operation::Burn { from, spender, .. } => {
+                [Some(from), spender].into_iter().flatten().collect()
+            }
+            Operation::Transfer {
+                from, to, spender, ..
+            } => [Some(from), Some(to), spender]
+                .into_iter()
+                .flatten()
+                .collect(),
+            Operation::Approve { from, spender, .. } => vec![from, spender],
+        };
+        if !involved.contains(&counterparty) {
+            return false;
+        }
+    }
+    true
+}
+
 fn encoded_block_bytes_to_flat_transaction(
     block_index: BlockIndex64,
     block: Vec<u8>,
@@ -1069,6 +1452,91 @@ fn list_subaccounts(args: ListSubaccountsArgs) -> Vec<Subaccount> {
     })
 }
 
+#[query]
+#[candid_method(query)]
+fn list_allowances(args: ListAllowancesArgs) -> ListAllowancesResult {
+    let now = check_allowances_indexed()?;
+    let take = args
+        .take
+        .unwrap_or(DEFAULT_MAX_BLOCKS_PER_RESPONSE)
+        .min(DEFAULT_MAX_BLOCKS_PER_RESPONSE) as usize;
+    Ok(with_allowances(|allowances| {
+        allowances
+            .range(spenders_range(args.from_account, args.start))
+            .filter(|(_, allowance)| !is_expired(allowance, now))
+            .take(take)
+            .map(|((_, spender), allowance)| AllowanceEntry {
+                from_account: args.from_account,
+                to_spender: account_from_key(spender),
+                allowance: allowance.amount.into(),
+                expires_at: allowance.expires_at,
+            })
+            .collect()
+    }))
+}
+
+#[query]
+#[candid_method(query)]
+fn list_spenders(args: ListSpendersArgs) -> ListSpendersResult {
+    let now = check_allowances_indexed()?;
+    let take = args
+        .take
+        .unwrap_or(DEFAULT_MAX_BLOCKS_PER_RESPONSE)
+        .min(DEFAULT_MAX_BLOCKS_PER_RESPONSE) as usize;
+    Ok(with_allowances(|allowances| {
+        allowances
+            .range(spenders_range(args.account, args.start))
+            .filter(|(_, allowance)| !is_expired(allowance, now))
+            .take(take)
+            .map(|((_, spender), _)| account_from_key(spender))
+            .collect()
+    }))
+}
+
+/// Returns an error if the allowances of some blocks in the block log have
+/// not been indexed yet, see [index_allowances_of_stored_blocks]. Otherwise
+/// returns the timestamp of the latest block against which allowances expire.
+fn check_allowances_indexed() -> Result<u64, ListAllowancesError> {
+    let num_blocks = with_blocks(|blocks| blocks.len());
+    let (num_blocks_with_allowances_indexed, allowances_timestamp) = with_state(|state| {
+        (
+            state.num_blocks_with_allowances_indexed,
+            state.allowances_timestamp,
+        )
+    });
+    if num_blocks_with_allowances_indexed < num_blocks {
+        return Err(ListAllowancesError {
+            message: format!(
+                "Allowances are being indexed ({} of {} blocks processed), please try again later",
+                num_blocks_with_allowances_indexed, num_blocks
+            ),
+        });
+    }
+    Ok(allowances_timestamp)
+}
+
+/// Returns the range of keys in [ALLOWANCES] of the spenders of `from`
+/// that come after `start` (excluded) or all of them if `start` is None.
+fn spenders_range(
+    from: Account,
+    start: Option<Account>,
+) -> (
+    std::ops::Bound<AllowancesMapKey>,
+    std::ops::Bound<AllowancesMapKey>,
+) {
+    let min_spender = (Blob::try_from(&[][..]).unwrap(), [0; 32]);
+    let max_spender = (Blob::try_from(&[u8::MAX; 29][..]).unwrap(), [u8::MAX; 32]);
+    let start_bound = match start {
+        Some(spender) => Excluded((account_key(from), account_key(spender))),
+        None => Included((account_key(from), min_spender)),
+    };
+    (start_bound, Included((account_key(from), max_spender)))
+}
+
+fn is_expired(allowance: &StoredAllowance, now: u64) -> bool {
+    matches!(allowance.expires_at, Some(expires_at) if expires_at <= now)
+}
+
 #[query(hidden = true)]
 fn http_request(req: HttpRequest) -> HttpResponse {
     if req.path() == "/metrics" {
@@ -7,9 +7,12 @@ use candid::{Decode, Encode, Nat, Principal};
 use ic_agent::identity::Identity;
 use ic_base_types::{CanisterId, PrincipalId};
 use ic_icrc1_index_ng::{
-    FeeCollectorRanges, GetAccountTransactionsArgs, GetAccountTransactionsResponse,
+    AllowanceEntry, FeeCollectorRanges, GetAccountTransactionsArgs, GetAccountTransactionsResponse,
     GetAccountTransactionsResult, GetBlocksResponse, IndexArg, InitArg as IndexInitArg,
-    ListSubaccountsArgs, TransactionWithId, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
+    ListAllowancesArgs, ListAllowancesResult, ListSpendersArgs, ListSpendersResult,
+    ListSubaccountsArgs, MemoFilter, OperationKind, SearchAccountTransactionsArgs,
+    SearchAccountTransactionsResponse, SearchAccountTransactionsResult, TransactionFilter,
+    TransactionWithId, DEFAULT_MAX_BLOCKS_PER_RESPONSE,
 };
 use ic_icrc1_ledger::{ChangeFeeCollector, LedgerArgument, UpgradeArgs as LedgerUpgradeArgs};
 use ic_icrc1_test_utils::{
@@ -17,8 +20,9 @@ use ic_icrc1_test_utils::{
 };
 use ic_state_machine_tests::StateMachine;
 use icrc_ledger_types::icrc1::account::{Account, Subaccount};
-use icrc_ledger_types::icrc1::transfer::{BlockIndex, TransferArg, TransferError};
+use icrc_ledger_types::icrc1::transfer::{BlockIndex, Memo, TransferArg, TransferError};
 use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
+use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
 use icrc_ledger_types::icrc3::blocks::GetBlocksRequest;
 use icrc_ledger_types::icrc3::transactions::{Mint, Transaction, Transfer};
 use num_traits::cast::ToPrimitive;
@@ -253,6 +257,48 @@ fn approve(
         .unwrap()
 }
 
+fn transfer_from(
+    env: &StateMachine,
+    ledger_id: CanisterId,
+    from: Account,
+    to: Account,
+    spender: Account,
+    amount: u64,
+) -> BlockIndex {
+    let arg = TransferFromArgs {
+        spender_subaccount: spender.subaccount,
+        from,
+        to,
+        amount: amount.into(),
+        fee: None,
+        memo: None,
+        created_at_time: None,
+    };
+    let req = Encode!(&arg).expect("Failed to encode TransferFromArgs");
+    let res = env
+        .execute_ingress_as(
+            PrincipalId(spender.owner),
+            ledger_id,
+            "icrc2_transfer_from",
+            req,
+        )
+        .unwrap_or_else(|e| {
+            panic!(
+                "Failed to transfer_from tokens. spender:{} arg:{:?} error:{}",
+                spender, arg, e
+            )
+        })
+        .bytes();
+    Decode!(&res, Result<BlockIndex, TransferFromError>)
+        .expect("Failed to decode Result<BlockIndex, TransferFromError>")
+        .unwrap_or_else(|e| {
+            panic!(
+                "Failed to transfer_from. spender:{} arg:{:?} error:{:?}",
+                spender, arg, e
+            )
+        })
+}
+
 // Same as get_account_transactions but with the old index interface.
 fn old_get_account_transactions(
     env: &StateMachine,
@@ -297,6 +343,29 @@ fn get_account_transactions(
         .expect("Failed to perform GetAccountTransactionsArgs")
 }
 
+fn search_account_transactions(
+    env: &StateMachine,
+    index_id: CanisterId,
+    account: Account,
+    start: Option<u64>,
+    max_results: u64,
+    filter: TransactionFilter,
+) -> SearchAccountTransactionsResult {
+    let req = SearchAccountTransactionsArgs {
+        account,
+        start: start.map(|n| n.into()),
+        max_results: max_results.into(),
+        filter,
+    };
+    let req = Encode!(&req).expect("Failed to encode SearchAccountTransactionsArgs");
+    let res = env
+        .query(index_id, "search_account_transactions", req)
+        .expect("Failed to search_account_transactions")
+        .bytes();
+    Decode!(&res, SearchAccountTransactionsResult)
+        .expect("Failed to decode SearchAccountTransactionsResult")
+}
+
 fn list_subaccounts(
     env: &StateMachine,
     index: CanisterId,
@@ -321,6 +390,57 @@ fn list_subaccounts(
     .expect("failed to decode list_subaccounts response")
 }
 
+fn list_allowances(
+    env: &StateMachine,
+    index: CanisterId,
+    from_account: Account,
+    start: Option<Account>,
+    take: Option<u64>,
+) -> Vec<AllowanceEntry> {
+    Decode!(
+        &env.query(
+            index,
+            "list_allowances",
+            Encode!(&ListAllowancesArgs {
+                from_account,
+                start,
+                take,
+            })
+            .unwrap()
+        )
+        .expect("failed to list_allowances")
+        .bytes(),
+        ListAllowancesResult
+    )
+    .expect("failed to decode list_allowances response")
+    .expect("failed to list allowances")
+}
+
+fn list_spenders(
+    env: &StateMachine,
+    index: CanisterId,
+    account: Account,
+    start: Option<Account>,
+) -> Vec<Account> {
+    Decode!(
+        &env.query(
+            index,
+            "list_spenders",
+            Encode!(&ListSpendersArgs {
+                account,
+                start,
+                take: None,
+            })
+            .unwrap()
+        )
+        .expect("failed to list_spenders")
+        .bytes(),
+        ListSpendersResult
+    )
+    .expect("failed to decode list_spenders response")
+    .expect("failed to list spenders")
+}
+
 fn get_fee_collectors_ranges(env: &StateMachine, index: CanisterId) -> FeeCollectorRanges {
     Decode!(
         &env.execute_ingress(index, "get_fee_collectors_ranges", Encode!(&()).unwrap())
@@ -1332,6 +1452,336 @@ fn test_principal_subaccounts() {
     assert!(subaccounts.contains(&account(2, 1).subaccount.unwrap()));
 }
 
+#[test]
+fn test_search_account_transactions() {
+    let account_1 = account(1, 0);
+    let account_2 = account(2, 0);
+    let account_3 = account(3, 0);
+    let env = &StateMachine::new();
+    let minter = minter_identity().sender().unwrap();
+    let ledger_id = install_ledger(
+        env,
+        vec![(account_1, 1_000_000_000)],
+        default_archive_options(),
+        None,
+        minter,
+    );
+    let index_id = install_index_ng(env, index_init_arg_without_interval(ledger_id));
+
+    let transfer_with_memo = |to: Account, memo: &[u8]| {
+        env.advance_time(Duration::from_secs(1));
+        icrc1_transfer(
+            env,
+            ledger_id,
+            PrincipalId(account_1.owner),
+            TransferArg {
+                from_subaccount: account_1.subaccount,
+                to,
+                fee: None,
+                created_at_time: None,
+                memo: Some(Memo::from(memo.to_vec())),
+                amount: Nat::from(1_000u64),
+            },
+        )
+    };
+    // block 0 is the mint to account_1
+    transfer_with_memo(account_2, b"invoice-1"); // block 1
+    transfer_with_memo(account_3, b"invoice-2"); // block 2
+    env.advance_time(Duration::from_secs(1));
+    approve(env, ledger_id, account_1, account_3, 10_000); // block 3
+    transfer_with_memo(account_2, b"refund"); // block 4
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+
+    let search = |start: Option<u64>, max_results: u64, filter: TransactionFilter| {
+        search_account_transactions(env, index_id, account_1, start, max_results, filter)
+            .expect("Failed to search account transactions")
+    };
+    let ids = |res: &SearchAccountTransactionsResponse| -> Vec<u64> {
+        res.transactions
+            .iter()
+            .map(|tx| tx.id.0.to_u64().unwrap())
+            .collect()
+    };
+
+    // No filter returns all the transactions of the account.
+    let res = search(None, u64::MAX, TransactionFilter::default());
+    assert_eq!(ids(&res), vec![4, 3, 2, 1, 0]);
+    assert_eq!(res.next_start, None);
+    assert_eq!(res.oldest_tx_id, Some(Nat::from(0u64)));
+    let timestamps: Vec<u64> = res
+        .transactions
+        .iter()
+        .rev()
+        .map(|tx| tx.transaction.timestamp)
+        .collect();
+
+    // Filter by operation kind.
+    let kind_filter = |kind| TransactionFilter {
+        kind: Some(kind),
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(&search(
+            None,
+            u64::MAX,
+            kind_filter(OperationKind::Transfer)
+        )),
+        vec![4, 2, 1]
+    );
+    assert_eq!(
+        ids(&search(None, u64::MAX, kind_filter(OperationKind::Approve))),
+        vec![3]
+    );
+    assert_eq!(
+        ids(&search(None, u64::MAX, kind_filter(OperationKind::Mint))),
+        vec![0]
+    );
+    assert!(search(None, u64::MAX, kind_filter(OperationKind::Burn))
+        .transactions
+        .is_empty());
+
+    // Filter by memo.
+    let memo_filter = |memo| TransactionFilter {
+        memo: Some(memo),
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(&search(
+            None,
+            u64::MAX,
+            memo_filter(MemoFilter::Prefix(Memo::from(b"invoice".to_vec())))
+        )),
+        vec![2, 1]
+    );
+    assert_eq!(
+        ids(&search(
+            None,
+            u64::MAX,
+            memo_filter(MemoFilter::Exact(Memo::from(b"invoice-2".to_vec())))
+        )),
+        vec![2]
+    );
+    assert!(search(
+        None,
+        u64::MAX,
+        memo_filter(MemoFilter::Exact(Memo::from(b"invoice".to_vec())))
+    )
+    .transactions
+    .is_empty());
+
+    // Filter by counterparty.
+    let counterparty_filter = |counterparty| TransactionFilter {
+        counterparty: Some(counterparty),
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(&search(None, u64::MAX, counterparty_filter(account_2))),
+        vec![4, 1]
+    );
+    assert_eq!(
+        ids(&search(None, u64::MAX, counterparty_filter(account_3))),
+        vec![3, 2]
+    );
+
+    // Filter by time range. timestamps[i] is the timestamp of block i.
+    let time_filter = TransactionFilter {
+        start_timestamp: Some(timestamps[1]),
+        end_timestamp: Some(timestamps[3]),
+        ..Default::default()
+    };
+    assert_eq!(ids(&search(None, u64::MAX, time_filter)), vec![3, 2, 1]);
+
+    // Paginate through the transfers one at a time.
+    let res = search(None, 1, kind_filter(OperationKind::Transfer));
+    assert_eq!(ids(&res), vec![4]);
+    let next_start = res.next_start.unwrap().0.to_u64().unwrap();
+    let res = search(Some(next_start), 1, kind_filter(OperationKind::Transfer));
+    assert_eq!(ids(&res), vec![2]);
+    let next_start = res.next_start.unwrap().0.to_u64().unwrap();
+    let res = search(Some(next_start), 1, kind_filter(OperationKind::Transfer));
+    assert_eq!(ids(&res), vec![1]);
+    let next_start = res.next_start.unwrap().0.to_u64().unwrap();
+    let res = search(Some(next_start), 1, kind_filter(OperationKind::Transfer));
+    assert!(res.transactions.is_empty());
+    assert_eq!(res.next_start, None);
+
+    // An empty time range is rejected.
+    assert!(search_account_transactions(
+        env,
+        index_id,
+        account_1,
+        None,
+        u64::MAX,
+        TransactionFilter {
+            start_timestamp: Some(timestamps[3]),
+            end_timestamp: Some(timestamps[1]),
+            ..Default::default()
+        },
+    )
+    .is_err());
+}
+
+#[test]
+fn test_list_allowances() {
+    let from = account(1, 0);
+    let spender_1 = account(2, 0);
+    let spender_2 = account(3, 0);
+    let spender_3 = account(4, 0);
+    let env = &StateMachine::new();
+    let minter = minter_identity().sender().unwrap();
+    let ledger_id = install_ledger(
+        env,
+        vec![(from, 1_000_000_000)],
+        default_archive_options(),
+        None,
+        minter,
+    );
+    let index_id = install_index_ng(env, index_init_arg_without_interval(ledger_id));
+
+    approve(env, ledger_id, from, spender_1, 100_000);
+    approve(env, ledger_id, from, spender_2, 200_000);
+    let expires_at = (env.time() + Duration::from_secs(3_600))
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    icrc2_approve(
+        env,
+        ledger_id,
+        PrincipalId(from.owner),
+        ApproveArgs {
+            from_subaccount: from.subaccount,
+            spender: spender_3,
+            amount: Nat::from(300_000u64),
+            expected_allowance: None,
+            expires_at: Some(expires_at),
+            fee: None,
+            memo: None,
+            created_at_time: None,
+        },
+    );
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+
+    let allowance_entry =
+        |spender: Account, allowance: u64, expires_at: Option<u64>| AllowanceEntry {
+            from_account: from,
+            to_spender: spender,
+            allowance: Nat::from(allowance),
+            expires_at,
+        };
+    assert_eq!(
+        list_allowances(env, index_id, from, None, None),
+        vec![
+            allowance_entry(spender_1, 100_000, None),
+            allowance_entry(spender_2, 200_000, None),
+            allowance_entry(spender_3, 300_000, Some(expires_at)),
+        ]
+    );
+    assert_eq!(
+        list_spenders(env, index_id, from, None),
+        vec![spender_1, spender_2, spender_3]
+    );
+
+    // Pagination starts after the previous spender.
+    assert_eq!(
+        list_allowances(env, index_id, from, Some(spender_1), Some(1)),
+        vec![allowance_entry(spender_2, 200_000, None)]
+    );
+    assert_eq!(
+        list_spenders(env, index_id, from, Some(spender_2)),
+        vec![spender_3]
+    );
+
+    // A transfer_from consumes the amount and the fee from the allowance
+    // and using the whole allowance removes it.
+    transfer_from(env, ledger_id, from, spender_1, spender_1, 50_000);
+    transfer_from(env, ledger_id, from, spender_2, spender_2, 200_000 - FEE);
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+    assert_eq!(
+        list_allowances(env, index_id, from, None, None),
+        vec![
+            allowance_entry(spender_1, 50_000 - FEE, None),
+            allowance_entry(spender_3, 300_000, Some(expires_at)),
+        ]
+    );
+
+    // Approving zero tokens revokes the allowance.
+    approve(env, ledger_id, from, spender_1, 0);
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+    assert_eq!(
+        list_allowances(env, index_id, from, None, None),
+        vec![allowance_entry(spender_3, 300_000, Some(expires_at))]
+    );
+
+    // Allowances expire against the timestamp of the latest indexed block,
+    // so an expired allowance is only pruned once a newer block is indexed.
+    env.advance_time(Duration::from_secs(3_600));
+    env.tick();
+    assert_eq!(
+        list_allowances(env, index_id, from, None, None),
+        vec![allowance_entry(spender_3, 300_000, Some(expires_at))]
+    );
+    approve(env, ledger_id, from, spender_2, 400_000);
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+    assert_eq!(
+        list_allowances(env, index_id, from, None, None),
+        vec![allowance_entry(spender_2, 400_000, None)]
+    );
+    assert_eq!(list_spenders(env, index_id, from, None), vec![spender_2]);
+    assert_eq!(list_spenders(env, index_id, spender_1, None), vec![]);
+}
+
+#[test]
+fn test_list_allowances_renewed_before_expiration() {
+    let from = account(1, 0);
+    let spender = account(2, 0);
+    let env = &StateMachine::new();
+    let minter = minter_identity().sender().unwrap();
+    let ledger_id = install_ledger(
+        env,
+        vec![(from, 1_000_000_000)],
+        default_archive_options(),
+        None,
+        minter,
+    );
+    let index_id = install_index_ng(env, index_init_arg_without_interval(ledger_id));
+
+    let expires_at = (env.time() + Duration::from_secs(3_600))
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    icrc2_approve(
+        env,
+        ledger_id,
+        PrincipalId(from.owner),
+        ApproveArgs {
+            from_subaccount: from.subaccount,
+            spender,
+            amount: Nat::from(100_000u64),
+            expected_allowance: None,
+            expires_at: Some(expires_at),
+            fee: None,
+            memo: None,
+            created_at_time: None,
+        },
+    );
+    // Replacing the allowance by one without expiration keeps it
+    // after the original expiration time has passed.
+    approve(env, ledger_id, from, spender, 200_000);
+    env.advance_time(Duration::from_secs(3_600));
+    transfer(env, ledger_id, from, account(3, 0), 1_000);
+    wait_until_sync_is_completed(env, index_id, ledger_id);
+
+    assert_eq!(
+        list_allowances(env, index_id, from, None, None),
+        vec![AllowanceEntry {
+            from_account: from,
+            to_spender: spender,
+            allowance: Nat::from(200_000u64),
+            expires_at: None,
+        }]
+    );
+}
+
 mod metrics {
     use crate::index_wasm;
     use candid::Principal;
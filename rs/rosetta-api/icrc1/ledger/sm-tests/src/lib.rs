@@ -21,6 +21,7 @@ use icrc_ledger_types::icrc2::allowance::{Allowance, AllowanceArgs};
 use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
 use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
 use icrc_ledger_types::icrc21::errors::Icrc21Error;
+use icrc_ledger_types::icrc21::lib::convert_tokens_to_string_representation;
 use icrc_ledger_types::icrc21::requests::ConsentMessageMetadata;
 use icrc_ledger_types::icrc21::requests::{
     ConsentMessageRequest, ConsentMessageSpec, DisplayMessageType,
@@ -871,7 +872,7 @@ where
         standards.push(standard.name);
     }
     standards.sort();
-    assert_eq!(standards, vec!["ICRC-1", "ICRC-2", "ICRC-3"]);
+    assert_eq!(standards, vec!["ICRC-1", "ICRC-2", "ICRC-21", "ICRC-3"]);
 }
 
 pub fn test_total_supply<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
@@ -3338,11 +3339,18 @@ pub fn test_icrc1_test_suite<T: candid::CandidType>(
     }
 }
 
-pub fn test_icrc21_standard<T>(ledger_wasm: Vec<u8>, encode_init_args: fn(InitArgs) -> T)
-where
+/// Checks the ICRC-21 consent messages of a ledger. Amounts are expected to be
+/// rendered using `decimals` if given, and in the smallest unit of the token
+/// otherwise.
+pub fn test_icrc21_standard<T>(
+    ledger_wasm: Vec<u8>,
+    encode_init_args: fn(InitArgs) -> T,
+    decimals: Option<u8>,
+) where
     T: CandidType,
 {
     fn check_consent_message(
+        decimals: Option<u8>,
         sender: Option<Account>,
         receiver: Option<Account>,
         spender: Option<Account>,
@@ -3356,6 +3364,10 @@ where
         expires_at: Option<u64>,
         expected_allowance: Option<Nat>,
     ) {
+        let format_tokens = |tokens: Nat| match decimals {
+            Some(decimals) => convert_tokens_to_string_representation(tokens, decimals),
+            None => tokens.to_string(),
+        };
         let message = match consent_message {
             ConsentMessage::GenericDisplayMessage(message) => message,
             ConsentMessage::LineDisplayMessage { pages } => pages
@@ -3402,21 +3414,21 @@ where
         }
         if let Some(amount) = amount {
             assert!(
-                message.contains(&amount.to_string()),
+                message.contains(&format_tokens(amount)),
                 "Message: {}",
                 message
             );
         }
         if let Some(fee_payed) = fee_payed {
             assert!(
-                message.contains(&fee_payed.to_string()),
+                message.contains(&format_tokens(fee_payed)),
                 "Message: {}",
                 message
             );
         }
         if let Some(fee_set) = fee_set {
             assert!(
-                message.contains(&format!("{}", fee_set)),
+                message.contains(&format_tokens(fee_set)),
                 "Message: {}",
                 message
             );
@@ -3433,7 +3445,7 @@ where
         }
         if let Some(expected_allowance) = expected_allowance {
             assert!(
-                message.contains(&expected_allowance.to_string()),
+                message.contains(&format_tokens(expected_allowance)),
                 "Message: {}",
                 message
             );
@@ -3485,6 +3497,7 @@ where
         ConsentMessage::GenericDisplayMessage { .. }
     ));
     check_consent_message(
+        decimals,
         Some(sender),
         Some(receiver),
         None,
@@ -3530,6 +3543,7 @@ where
         _ => panic!("Expected LineDisplayMessage"),
     }
     check_consent_message(
+        decimals,
         Some(sender),
         Some(receiver),
         None,
@@ -3592,6 +3606,7 @@ where
         ConsentMessage::GenericDisplayMessage { .. }
     ));
     check_consent_message(
+        decimals,
         Some(sender),
         Some(receiver),
         None,
@@ -3628,6 +3643,7 @@ where
     };
     let consent_info = icrc21_consent_message(&env, canister_id, caller.0, args).unwrap();
     check_consent_message(
+        decimals,
         Some(sender),
         None,
         Some(approve_args.spender),
@@ -3657,6 +3673,7 @@ where
     };
     let consent_info = icrc21_consent_message(&env, canister_id, caller.0, args).unwrap();
     check_consent_message(
+        decimals,
         Some(sender),
         None,
         Some(approve_args.spender),
@@ -3692,6 +3709,7 @@ where
     };
     let consent_info = icrc21_consent_message(&env, canister_id, spender.owner, args).unwrap();
     check_consent_message(
+        decimals,
         Some(transfer_from_args.from),
         Some(transfer_from_args.to),
         Some(spender),
@@ -3721,6 +3739,7 @@ where
     };
     let consent_info = icrc21_consent_message(&env, canister_id, spender.owner, args).unwrap();
     check_consent_message(
+        decimals,
         Some(transfer_from_args.from),
         Some(transfer_from_args.to),
         Some(spender),
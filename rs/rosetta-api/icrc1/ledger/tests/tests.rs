@@ -292,6 +292,15 @@ fn test_approval_trimming() {
     ic_icrc1_ledger_sm_tests::test_approval_trimming(ledger_wasm(), encode_init_args);
 }
 
+#[test]
+fn test_icrc21_standard() {
+    ic_icrc1_ledger_sm_tests::test_icrc21_standard(
+        ledger_wasm(),
+        encode_init_args,
+        Some(ic_icrc1_ledger_sm_tests::DECIMAL_PLACES),
+    );
+}
+
 #[test]
 fn test_archive_controllers() {
     ic_icrc1_ledger_sm_tests::test_archive_controllers(ledger_wasm());
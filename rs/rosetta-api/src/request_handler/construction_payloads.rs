@@ -423,6 +423,7 @@ fn handle_list_neurons(
     let args = ic_nns_governance::pb::v1::ListNeurons {
         neuron_ids: vec![],
         include_neurons_readable_by_caller: true,
+        ..Default::default()
     };
     let update = HttpCanisterUpdate {
         canister_id: Blob(ic_nns_constants::GOVERNANCE_CANISTER_ID.get().to_vec()),
@@ -146,7 +146,9 @@ pub(crate) fn generate_sns_init_payload(
         .map_err(|err| format!("Invalid configuration in {:?}: {}", path, err))
 }
 
-fn read_create_service_nervous_system_from_init_yaml(
+/// Reads the SNS init config file at `path`, and converts it into the
+/// CreateServiceNervousSystem proposal action it describes.
+pub fn read_create_service_nervous_system_from_init_yaml(
     path: &Path,
 ) -> Result<ic_nns_governance::pb::v1::CreateServiceNervousSystem, String> {
     let contents = std::fs::read_to_string(path)
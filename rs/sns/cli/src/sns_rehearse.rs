@@ -0,0 +1,489 @@
+//! Rehearses the launch of an SNS on a local PocketIC instance.
+//!
+//! This binary depends on the (testonly) nervous system integration test
+//! helpers, so it is kept separate from the `sns` binary. See the README for
+//! how to run it.
+
+use candid::Principal;
+use clap::Parser;
+use ic_base_types::{CanisterId, PrincipalId};
+use ic_ledger_core::Tokens;
+use ic_nervous_system_common::ledger::compute_distribution_subaccount_bytes;
+use ic_nervous_system_integration_tests::pocket_ic_helpers::{
+    add_wasms_to_sns_wasm, install_nns_canisters, nns,
+    sns::{
+        self,
+        swap::{await_swap_finalization_status, SwapFinalizationStatus},
+    },
+    STARTING_CYCLES_PER_CANISTER,
+};
+use ic_nns_constants::ROOT_CANISTER_ID;
+use ic_nns_governance::pb::v1::CreateServiceNervousSystem;
+use ic_sns_cli::read_create_service_nervous_system_from_init_yaml;
+use ic_sns_governance::governance::TREASURY_SUBACCOUNT_NONCE;
+use ic_sns_swap::{pb::v1::Lifecycle, swap::principal_to_subaccount};
+use icp_ledger::{AccountIdentifier, DEFAULT_TRANSFER_FEE};
+use icrc_ledger_types::icrc1::{account::Account, transfer::TransferArg};
+use pocket_ic::{CanisterSettings, PocketIc, PocketIcBuilder};
+use std::path::PathBuf;
+
+/// The first test user ID used for simulated direct swap participants.
+const FIRST_PARTICIPANT_TEST_ID: u64 = 1000;
+
+#[derive(Debug, Parser)]
+#[clap(
+    name = "sns-rehearse",
+    about = "Rehearse the launch of an SNS on a local PocketIC instance, from the NNS proposal to swap finalization.",
+    version
+)]
+struct RehearseArgs {
+    /// Path to a configuration file specifying the SNS to be created.
+    #[clap(default_value = "sns_init.yaml", value_parser = clap::value_parser!(std::path::PathBuf))]
+    init_config_file: PathBuf,
+
+    /// Rehearse with the NNS and SNS canister versions built from this
+    /// branch, rather than the versions currently running on mainnet.
+    ///
+    /// Either way, the canister WASMs are loaded from the paths given in the
+    /// `*_WASM_PATH` environment variables (e.g.
+    /// MAINNET_GOVERNANCE_CANISTER_WASM_PATH), and the PocketIC server binary
+    /// from POCKET_IC_BIN. `bazel run` sets all of them.
+    #[clap(long)]
+    with_tip_of_master_versions: bool,
+}
+
+fn main() {
+    let args = match RehearseArgs::try_parse_from(std::env::args()) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    rehearse(args)
+}
+
+/// Rehearses the launch of the SNS described by the init config file on a
+/// local PocketIC instance: the NNS and SNS-W are installed, the
+/// CreateServiceNervousSystem proposal is adopted, the swap is filled by
+/// simulated direct participants, and, once the swap is finalized, the
+/// resulting neurons, ledger balances and dapp controllers are printed.
+fn rehearse(args: RehearseArgs) {
+    let RehearseArgs {
+        init_config_file,
+        with_tip_of_master_versions,
+    } = args;
+
+    // Step 0: Load configuration. `bazel run` executes the binary from its
+    // runfiles directory, so relative paths are resolved against the
+    // directory where the command was invoked.
+    let init_config_file = match std::env::var_os("BUILD_WORKING_DIRECTORY") {
+        Some(working_directory) => PathBuf::from(working_directory).join(init_config_file),
+        None => init_config_file,
+    };
+    let create_service_nervous_system = read_create_service_nervous_system_from_init_yaml(
+        &init_config_file,
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    });
+    eprintln!("Loaded configuration.");
+
+    // Step 1: Spin up the NNS (including SNS-W) on PocketIC.
+    eprintln!("Installing the NNS on PocketIC...");
+    let pocket_ic = PocketIcBuilder::new()
+        .with_nns_subnet()
+        .with_sns_subnet()
+        .build();
+    let with_mainnet_versions = !with_tip_of_master_versions;
+    install_nns_canisters(&pocket_ic, vec![], with_mainnet_versions, None);
+    if let Err(err) = add_wasms_to_sns_wasm(&pocket_ic, with_mainnet_versions) {
+        exit_with_error(&format!(
+            "Failed to publish the SNS WASMs to SNS-W: {}",
+            err
+        ));
+    }
+
+    // Step 2: Create the dapp canisters, handing control over to NNS root
+    // (alongside the fallback controllers), just like on mainnet.
+    create_dapp_canisters(&pocket_ic, &create_service_nervous_system);
+
+    // Step 3: Make the CreateServiceNervousSystem proposal, and wait until
+    // the swap opens.
+    eprintln!("Submitting the CreateServiceNervousSystem proposal...");
+    let sns_instance_label = create_service_nervous_system
+        .name
+        .clone()
+        .unwrap_or_default();
+    let (deployed_sns, nns_proposal_id) = nns::governance::propose_to_deploy_sns_and_wait(
+        &pocket_ic,
+        create_service_nervous_system.clone(),
+        &sns_instance_label,
+    );
+    eprintln!(
+        "Proposal {} was adopted and the SNS was deployed.",
+        nns_proposal_id.id
+    );
+    let sns_root_canister_id = deployed_sns.root_canister_id.unwrap();
+    let sns_governance_canister_id = deployed_sns.governance_canister_id.unwrap();
+    let sns_ledger_canister_id = deployed_sns.ledger_canister_id.unwrap();
+    let swap_canister_id = deployed_sns.swap_canister_id.unwrap();
+    if let Err(err) = sns::swap::await_swap_lifecycle(&pocket_ic, swap_canister_id, Lifecycle::Open)
+    {
+        exit_with_error(&err);
+    }
+
+    // Step 4: Simulate direct participation until the swap is filled up,
+    // and wait for it to be finalized.
+    eprintln!("Participating in the swap...");
+    participate_until_swap_is_full(&pocket_ic, swap_canister_id, &create_service_nervous_system);
+    if let Err(err) =
+        sns::swap::await_swap_lifecycle(&pocket_ic, swap_canister_id, Lifecycle::Committed)
+    {
+        exit_with_error(&err);
+    }
+    if let Err(err) = await_swap_finalization_status(
+        &pocket_ic,
+        swap_canister_id,
+        SwapFinalizationStatus::Committed,
+    ) {
+        exit_with_error(&err);
+    }
+
+    // Step 5: Report the resulting state of the SNS.
+    println!();
+    println!("🚀 The swap was committed and finalized.");
+    println!();
+    println!("SNS canisters:");
+    println!("  root:       {}", sns_root_canister_id);
+    println!("  governance: {}", sns_governance_canister_id);
+    println!("  ledger:     {}", sns_ledger_canister_id);
+    println!("  swap:       {}", swap_canister_id);
+    if let Some(index_canister_id) = deployed_sns.index_canister_id {
+        println!("  index:      {}", index_canister_id);
+    }
+
+    println!();
+    println!("SNS neurons:");
+    let now_seconds = pocket_ic
+        .get_time()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    for neuron in sns::governance::list_neurons(&pocket_ic, sns_governance_canister_id).neurons {
+        let id = neuron
+            .id
+            .as_ref()
+            .map(|id| id.to_string())
+            .unwrap_or_default();
+        let controllers = neuron
+            .permissions
+            .iter()
+            .filter_map(|permission| permission.principal)
+            .map(|principal| principal.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "  {}: stake {} e8s, dissolve delay {} seconds, principals [{}]",
+            id,
+            neuron.stake_e8s(),
+            neuron.dissolve_delay_seconds(now_seconds),
+            controllers,
+        );
+    }
+
+    println!();
+    println!("SNS ledger balances:");
+    println!(
+        "  total supply: {} e8s",
+        sns::ledger::icrc1_total_supply(&pocket_ic, sns_ledger_canister_id)
+    );
+    let treasury_account = Account {
+        owner: sns_governance_canister_id.0,
+        subaccount: Some(compute_distribution_subaccount_bytes(
+            sns_governance_canister_id,
+            TREASURY_SUBACCOUNT_NONCE,
+        )),
+    };
+    println!(
+        "  treasury: {} e8s",
+        sns::ledger::icrc1_balance_of(&pocket_ic, sns_ledger_canister_id, treasury_account)
+    );
+    let swap_account = Account {
+        owner: swap_canister_id.0,
+        subaccount: None,
+    };
+    println!(
+        "  swap: {} e8s",
+        sns::ledger::icrc1_balance_of(&pocket_ic, sns_ledger_canister_id, swap_account)
+    );
+
+    println!();
+    println!("Dapp canister controllers:");
+    let dapp_canister_ids = sns::root::list_sns_canisters(&pocket_ic, sns_root_canister_id).dapps;
+    for dapp_canister_id in dapp_canister_ids {
+        let controllers = pocket_ic
+            .canister_status(dapp_canister_id.0, Some(sns_root_canister_id.0))
+            .map(|status| {
+                status
+                    .settings
+                    .controllers
+                    .into_iter()
+                    .map(|controller| controller.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_else(|err| format!("<unable to read canister status: {:?}>", err));
+        println!("  {}: [{}]", dapp_canister_id, controllers);
+    }
+}
+
+fn create_dapp_canisters(
+    pocket_ic: &PocketIc,
+    create_service_nervous_system: &CreateServiceNervousSystem,
+) {
+    let controllers = std::iter::once(ROOT_CANISTER_ID.get())
+        .chain(
+            create_service_nervous_system
+                .fallback_controller_principal_ids
+                .iter()
+                .copied(),
+        )
+        .map(Principal::from)
+        .collect::<Vec<_>>();
+    for canister in &create_service_nervous_system.dapp_canisters {
+        let dapp_canister_id = CanisterId::unchecked_from_principal(canister.id.unwrap());
+        let settings = Some(CanisterSettings {
+            controllers: Some(controllers.clone()),
+            ..Default::default()
+        });
+        if let Err(err) =
+            pocket_ic.create_canister_with_id(None, settings, dapp_canister_id.get().0)
+        {
+            exit_with_error(&format!(
+                "Unable to create dapp canister {}: {}",
+                dapp_canister_id, err
+            ));
+        }
+        pocket_ic.add_cycles(dapp_canister_id.get().0, STARTING_CYCLES_PER_CANISTER);
+    }
+}
+
+/// Returns the amounts (in e8s) that simulated direct participants need to
+/// commit in order to reach the maximum direct participation of the swap, with
+/// at least `minimum_participants` participants, and each of them committing
+/// between `minimum_participant_e8s` and `maximum_participant_e8s`. The
+/// remainder of the division is spread over the first participants, one e8
+/// each.
+///
+/// The smallest participant count that satisfies the other constraints is
+/// used, as it maximizes the amount per participant. If even that amount is
+/// below `minimum_participant_e8s`, the swap cannot be filled up, and an error
+/// is returned.
+fn direct_participation_amounts_e8s(
+    maximum_direct_participation_e8s: u64,
+    minimum_participant_e8s: u64,
+    maximum_participant_e8s: u64,
+    minimum_participants: u64,
+) -> Result<Vec<u64>, String> {
+    let participant_count = minimum_participants
+        .max(maximum_direct_participation_e8s.div_ceil(maximum_participant_e8s))
+        .max(1);
+    let per_participant_e8s = maximum_direct_participation_e8s / participant_count;
+    if per_participant_e8s < minimum_participant_e8s {
+        return Err(format!(
+            "The swap cannot be filled up: at least {} participants are needed, but then \
+             each of them can commit at most {} ICP e8s of the maximum direct participation \
+             of {} ICP e8s, which is less than the minimum participation of {} ICP e8s.",
+            participant_count,
+            per_participant_e8s,
+            maximum_direct_participation_e8s,
+            minimum_participant_e8s,
+        ));
+    }
+    let remainder_e8s = maximum_direct_participation_e8s % participant_count;
+    Ok((0..participant_count)
+        .map(|i| per_participant_e8s + u64::from(i < remainder_e8s))
+        .collect())
+}
+
+/// Makes enough direct participations to fill up the swap (see
+/// `direct_participation_amounts_e8s`).
+fn participate_until_swap_is_full(
+    pocket_ic: &PocketIc,
+    swap_canister_id: PrincipalId,
+    create_service_nervous_system: &CreateServiceNervousSystem,
+) {
+    let swap_parameters = create_service_nervous_system
+        .swap_parameters
+        .as_ref()
+        .unwrap();
+    let maximum_direct_participation_e8s = swap_parameters
+        .maximum_direct_participation_icp
+        .as_ref()
+        .and_then(|tokens| tokens.e8s)
+        .unwrap();
+    let minimum_participant_e8s = swap_parameters
+        .minimum_participant_icp
+        .as_ref()
+        .and_then(|tokens| tokens.e8s)
+        .unwrap();
+    let maximum_participant_e8s = swap_parameters
+        .maximum_participant_icp
+        .as_ref()
+        .and_then(|tokens| tokens.e8s)
+        .unwrap();
+    let amounts_e8s = direct_participation_amounts_e8s(
+        maximum_direct_participation_e8s,
+        minimum_participant_e8s,
+        maximum_participant_e8s,
+        swap_parameters.minimum_participants.unwrap(),
+    )
+    .unwrap_or_else(|err| exit_with_error(&err));
+
+    for (i, amount_e8s) in (0..).zip(amounts_e8s) {
+        let participant = PrincipalId::new_user_test_id(FIRST_PARTICIPANT_TEST_ID + i);
+        let amount = Tokens::from_e8s(amount_e8s);
+        nns::ledger::mint_icp(
+            pocket_ic,
+            AccountIdentifier::new(participant, None),
+            amount.saturating_add(DEFAULT_TRANSFER_FEE),
+        );
+        let transfer_result = nns::ledger::icrc1_transfer(
+            pocket_ic,
+            participant,
+            TransferArg {
+                from_subaccount: None,
+                to: Account {
+                    owner: swap_canister_id.0,
+                    subaccount: Some(principal_to_subaccount(&participant)),
+                },
+                fee: None,
+                created_at_time: None,
+                memo: None,
+                amount: amount.get_e8s().into(),
+            },
+        );
+        if let Err(err) = transfer_result {
+            exit_with_error(&format!(
+                "Participant {} could not transfer ICP to the swap: {:?}",
+                participant, err
+            ));
+        }
+        let response = sns::swap::refresh_buyer_tokens(
+            pocket_ic,
+            swap_canister_id,
+            participant,
+            swap_parameters.confirmation_text.clone(),
+        );
+        match response {
+            Ok(response) => eprintln!(
+                "Participant {} committed {} ICP e8s.",
+                participant, response.icp_accepted_participation_e8s
+            ),
+            Err(err) => exit_with_error(&format!(
+                "Participation of {} was not accepted by the swap: {}",
+                participant, err
+            )),
+        }
+    }
+}
+
+fn exit_with_error(err: &str) -> ! {
+    eprintln!("{}", err);
+    println!();
+    println!("💔 The rehearsal failed. Look up slightly for diagnostics.");
+    std::process::exit(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_nervous_system_common::E8;
+
+    #[test]
+    fn test_direct_participation_amounts_fill_up_the_swap() {
+        for (
+            maximum_direct_participation_e8s,
+            minimum_participant_e8s,
+            maximum_participant_e8s,
+            minimum_participants,
+        ) in [
+            // The minimum number of participants is the binding constraint.
+            (1_000 * E8, E8, 1_000 * E8, 10),
+            // The maximum participation per participant is the binding
+            // constraint, and the division leaves a remainder.
+            (1_000 * E8 + 7, E8, 30 * E8, 2),
+            // Each participant commits exactly the minimum participation.
+            (1_000 * E8, 200 * E8, 1_000 * E8, 5),
+            (1, 1, 1, 1),
+        ] {
+            let amounts_e8s = direct_participation_amounts_e8s(
+                maximum_direct_participation_e8s,
+                minimum_participant_e8s,
+                maximum_participant_e8s,
+                minimum_participants,
+            )
+            .unwrap();
+            assert_eq!(
+                amounts_e8s.iter().sum::<u64>(),
+                maximum_direct_participation_e8s
+            );
+            assert!(amounts_e8s.len() as u64 >= minimum_participants);
+            assert!(amounts_e8s.iter().all(|amount_e8s| {
+                minimum_participant_e8s <= *amount_e8s && *amount_e8s <= maximum_participant_e8s
+            }));
+        }
+    }
+
+    #[test]
+    fn test_direct_participation_amounts_spread_the_remainder() {
+        assert_eq!(
+            direct_participation_amounts_e8s(11, 1, 4, 3),
+            Ok(vec![4, 4, 3])
+        );
+        assert_eq!(
+            direct_participation_amounts_e8s(12, 2, 10, 5),
+            Ok(vec![3, 3, 2, 2, 2])
+        );
+    }
+
+    #[test]
+    fn test_direct_participation_amounts_respect_the_minimum_participation() {
+        // 10 participants cannot commit at least 200 ICP each without
+        // exceeding the maximum direct participation of 1000 ICP.
+        let err =
+            direct_participation_amounts_e8s(1_000 * E8, 200 * E8, 1_000 * E8, 10).unwrap_err();
+        assert!(err.contains("cannot be filled up"), "{}", err);
+
+        // The participants needed to stay below the maximum participation
+        // cannot reach the minimum participation either.
+        let err =
+            direct_participation_amounts_e8s(1_000 * E8, 95 * E8, 100 * E8 - 1, 1).unwrap_err();
+        assert!(err.contains("cannot be filled up"), "{}", err);
+
+        // With a lower minimum, the same number of participants is fine.
+        assert_eq!(
+            direct_participation_amounts_e8s(1_000 * E8, 100 * E8, 1_000 * E8, 10),
+            Ok(vec![100 * E8; 10])
+        );
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = RehearseArgs::try_parse_from(["sns-rehearse"]).unwrap();
+        assert_eq!(args.init_config_file, PathBuf::from("sns_init.yaml"));
+        assert!(!args.with_tip_of_master_versions);
+
+        let args = RehearseArgs::try_parse_from([
+            "sns-rehearse",
+            "my_sns.yaml",
+            "--with-tip-of-master-versions",
+        ])
+        .unwrap();
+        assert_eq!(args.init_config_file, PathBuf::from("my_sns.yaml"));
+        assert!(args.with_tip_of_master_versions);
+    }
+}
@@ -48,8 +48,9 @@ use ic_sns_governance::{
         GetRunningSnsVersionRequest, GetRunningSnsVersionResponse,
         GetSnsInitializationParametersRequest, GetSnsInitializationParametersResponse,
         Governance as GovernanceProto, ListNervousSystemFunctionsResponse, ListNeurons,
-        ListNeuronsResponse, ListProposals, ListProposalsResponse, ManageNeuron,
-        ManageNeuronResponse, NervousSystemParameters, RewardEvent, SetMode, SetModeResponse,
+        ListNeuronsResponse, ListProposals, ListProposalsResponse, ListTopicsRequest,
+        ListTopicsResponse, ManageNeuron, ManageNeuronResponse, NervousSystemParameters,
+        RewardEvent, SetMode, SetModeResponse,
     },
     types::{Environment, HeapGrowthPotential},
 };
@@ -471,6 +472,20 @@ fn list_proposals_(list_proposals: ListProposals) -> ListProposalsResponse {
     governance().list_proposals(&list_proposals, &caller())
 }
 
+/// Returns the topics of this SNS, together with the nervous system functions
+/// that belong to each of them.
+#[export_name = "canister_query list_topics"]
+fn list_topics() {
+    log!(INFO, "list_topics");
+    over(candid_one, list_topics_)
+}
+
+/// Internal method for calling list_topics.
+#[candid_method(query, rename = "list_topics")]
+fn list_topics_(_request: ListTopicsRequest) -> ListTopicsResponse {
+    governance().list_topics()
+}
+
 /// Returns the current list of available NervousSystemFunctions.
 #[export_name = "canister_query list_nervous_system_functions"]
 fn list_nervous_system_functions() {
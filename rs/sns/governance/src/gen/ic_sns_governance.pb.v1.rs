@@ -138,6 +138,13 @@ pub struct Neuron {
     /// entry\[i\].timestamp_of_disbursement_seconds <= entry\[i+1\].timestamp_of_disbursement_seconds
     #[prost(message, repeated, tag = "18")]
     pub disburse_maturity_in_progress: ::prost::alloc::vec::Vec<DisburseMaturityInProgress>,
+    /// The neuron's followees, specified as a map of topics to followees neuron IDs.
+    /// The map's keys are the integer values of the `Topic` enum, as Protobuf does not
+    /// support enum keys in maps. Topic following applies to proposals of all functions
+    /// in the topic, except for functions for which the neuron has followees in
+    /// `followees`.
+    #[prost(btree_map = "int32, message", tag = "19")]
+    pub topic_followees: ::prost::alloc::collections::BTreeMap<i32, neuron::Followees>,
     /// The neuron's dissolve state, specifying whether the neuron is dissolving,
     /// non-dissolving, or dissolved.
     ///
@@ -260,6 +267,11 @@ pub mod nervous_system_function {
         /// <method_name>(proposal_data: ProposalData) -> Result<String, String>
         #[prost(string, optional, tag = "5")]
         pub validator_method_name: ::core::option::Option<::prost::alloc::string::String>,
+        /// The topic of proposals that execute this function. Proposals of
+        /// functions without a topic can only be followed by function (or
+        /// with the catch-all following).
+        #[prost(enumeration = "super::Topic", optional, tag = "6")]
+        pub topic: ::core::option::Option<i32>,
     }
     #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
     #[allow(clippy::derive_partial_eq_without_eq)]
@@ -400,6 +412,51 @@ pub mod transfer_sns_treasury_funds {
         }
     }
 }
+/// A proposal to grant an ICRC-2 allowance on SNS treasury funds to (optionally
+/// a Subaccount of) the spender principal. Unlike TransferSnsTreasuryFunds, the
+/// funds stay in the treasury until the spender actually transfers them.
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ApproveTreasuryAllowance {
+    /// Whether to approve the allowance on the NNS ledger (in ICP) or on the SNS
+    /// ledger (in SNS tokens).
+    #[prost(enumeration = "transfer_sns_treasury_funds::TransferFrom", tag = "1")]
+    pub from_treasury: i32,
+    /// The amount the spender is allowed to transfer, in e8s. This replaces any
+    /// allowance previously granted to the same spender.
+    #[prost(uint64, optional, tag = "2")]
+    pub amount_e8s: ::core::option::Option<u64>,
+    /// The principal that is allowed to spend the funds.
+    #[prost(message, optional, tag = "3")]
+    pub spender_principal: ::core::option::Option<::ic_base_types::PrincipalId>,
+    /// An (optional) Subaccount of the spender principal.
+    #[prost(message, optional, tag = "4")]
+    pub spender_subaccount: ::core::option::Option<Subaccount>,
+    /// When the allowance expires, in seconds since the Unix epoch. If not set,
+    /// the allowance does not expire.
+    #[prost(uint64, optional, tag = "5")]
+    pub expires_at_timestamp_seconds: ::core::option::Option<u64>,
+    /// An optional memo to use for the approval.
+    #[prost(uint64, optional, tag = "6")]
+    pub memo: ::core::option::Option<u64>,
+}
+/// A proposal to revoke an ICRC-2 allowance previously granted from the SNS
+/// treasury via ApproveTreasuryAllowance.
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RevokeTreasuryAllowance {
+    /// The treasury whose allowance is revoked.
+    #[prost(enumeration = "transfer_sns_treasury_funds::TransferFrom", tag = "1")]
+    pub from_treasury: i32,
+    /// The principal whose allowance is revoked.
+    #[prost(message, optional, tag = "2")]
+    pub spender_principal: ::core::option::Option<::ic_base_types::PrincipalId>,
+    /// An (optional) Subaccount of the spender principal.
+    #[prost(message, optional, tag = "3")]
+    pub spender_subaccount: ::core::option::Option<Subaccount>,
+}
 /// A proposal function that changes the ledger's parameters.
 /// Fields with None values will remain unchanged.
 #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
@@ -537,7 +594,7 @@ pub struct Proposal {
     /// of this mapping.
     #[prost(
         oneof = "proposal::Action",
-        tags = "4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18"
+        tags = "4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20"
     )]
     pub action: ::core::option::Option<proposal::Action>,
 }
@@ -643,6 +700,17 @@ pub mod proposal {
         /// Id = 14.
         #[prost(message, tag = "18")]
         ManageDappCanisterSettings(super::ManageDappCanisterSettings),
+        /// Grant an ICRC-2 allowance on SNS treasury funds (ICP or SNS token) to an
+        /// account.
+        ///
+        /// Id = 15.
+        #[prost(message, tag = "19")]
+        ApproveTreasuryAllowance(super::ApproveTreasuryAllowance),
+        /// Revoke an ICRC-2 allowance previously granted from the SNS treasury.
+        ///
+        /// Id = 16.
+        #[prost(message, tag = "20")]
+        RevokeTreasuryAllowance(super::RevokeTreasuryAllowance),
     }
 }
 #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
@@ -866,6 +934,8 @@ pub struct ProposalData {
     /// Id 8 - ManageSnsMetadata proposals.
     /// Id 9 - TransferSnsTreasuryFunds proposals.
     /// Id 13 - ManageLedgerParameters proposals.
+    /// Id 15 - ApproveTreasuryAllowance proposals.
+    /// Id 16 - RevokeTreasuryAllowance proposals.
     #[prost(uint64, tag = "1")]
     pub action: u64,
     /// This is stored here temporarily. It is also stored on the map
@@ -989,7 +1059,7 @@ pub struct ProposalData {
         ::core::option::Option<::ic_nervous_system_proto::pb::v1::Percentage>,
     /// In general, this holds data retrieved at proposal submission/creation time and used later
     /// during execution. This varies based on the action of the proposal.
-    #[prost(oneof = "proposal_data::ActionAuxiliary", tags = "22, 23")]
+    #[prost(oneof = "proposal_data::ActionAuxiliary", tags = "22, 23, 24")]
     pub action_auxiliary: ::core::option::Option<proposal_data::ActionAuxiliary>,
 }
 /// Nested message and enum types in `ProposalData`.
@@ -1018,6 +1088,10 @@ pub mod proposal_data {
         TransferSnsTreasuryFunds(TransferSnsTreasuryFundsActionAuxiliary),
         #[prost(message, tag = "23")]
         MintSnsTokens(MintSnsTokensActionAuxiliary),
+        /// The allowance is valued like a transfer of the same amount, so that it
+        /// counts towards the same treasury limits.
+        #[prost(message, tag = "24")]
+        ApproveTreasuryAllowance(TransferSnsTreasuryFundsActionAuxiliary),
     }
 }
 #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
@@ -1497,7 +1571,7 @@ pub mod governance {
         pub timestamp: u64,
         #[prost(
             oneof = "neuron_in_flight_command::Command",
-            tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 20"
+            tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 20"
         )]
         pub command: ::core::option::Option<neuron_in_flight_command::Command>,
     }
@@ -1542,6 +1616,8 @@ pub mod governance {
             RegisterVote(super::super::manage_neuron::RegisterVote),
             #[prost(message, tag = "13")]
             FinalizeDisburseMaturity(super::super::manage_neuron::FinalizeDisburseMaturity),
+            #[prost(message, tag = "14")]
+            MergeNeurons(super::super::manage_neuron::MergeNeurons),
             #[prost(message, tag = "20")]
             SyncCommand(SyncCommand),
         }
@@ -1835,7 +1911,7 @@ pub struct ManageNeuron {
     pub subaccount: ::prost::alloc::vec::Vec<u8>,
     #[prost(
         oneof = "manage_neuron::Command",
-        tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13"
+        tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15"
     )]
     pub command: ::core::option::Option<manage_neuron::Command>,
 }
@@ -1965,6 +2041,23 @@ pub mod manage_neuron {
         #[prost(uint64, tag = "2")]
         pub memo: u64,
     }
+    /// The operation that merges another neuron (called 'source neuron') into the
+    /// managed neuron (called 'target neuron').
+    /// Specifically, the source neuron's stake (minus the transaction fee) is transferred
+    /// to the target neuron, and the source neuron's maturity and staked maturity are
+    /// moved to the target neuron. The target neuron's age becomes the stake-weighted
+    /// average of both neurons' ages, its dissolve delay becomes the larger of the two,
+    /// and it adopts the source neuron's followees for any function for which it does not
+    /// define followees itself. Both neurons must be non-dissolving, must not be vesting,
+    /// must have identical permissions, and must not be involved in open proposals.
+    #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct MergeNeurons {
+        /// The ID of the neuron whose stake is merged into the managed neuron.
+        #[prost(message, optional, tag = "1")]
+        pub source_neuron_id: ::core::option::Option<super::NeuronId>,
+    }
     /// The operation that merges a given percentage of a neuron's maturity (if applicable
     /// to the nervous system) to the neuron's stake.
     #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
@@ -2046,6 +2139,36 @@ pub mod manage_neuron {
         #[prost(message, repeated, tag = "2")]
         pub followees: ::prost::alloc::vec::Vec<super::NeuronId>,
     }
+    /// The operation that sets the followees of a neuron for a set of topics.
+    /// For each of the given topics, the neuron's current followees on that
+    /// topic are replaced with the given list (not added). If the provided
+    /// followee list is empty, the follow relation for the topic is removed.
+    /// Setting the followees on a topic also removes the neuron's followees on
+    /// the individual functions in that topic, so that the topic following
+    /// applies to all of them.
+    #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct SetFollowing {
+        /// The follow relations to set. Each topic can be specified at most once.
+        #[prost(message, repeated, tag = "1")]
+        pub topic_following: ::prost::alloc::vec::Vec<set_following::FolloweesForTopic>,
+    }
+    /// Nested message and enum types in `SetFollowing`.
+    pub mod set_following {
+        /// The followees of a neuron on a given topic.
+        #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+        #[allow(clippy::derive_partial_eq_without_eq)]
+        #[derive(Clone, PartialEq, ::prost::Message)]
+        pub struct FolloweesForTopic {
+            /// The list of followee neurons, specified by their neuron ID.
+            #[prost(message, repeated, tag = "1")]
+            pub followees: ::prost::alloc::vec::Vec<super::super::NeuronId>,
+            /// The topic for which this follow relation is relevant.
+            #[prost(enumeration = "super::super::Topic", optional, tag = "2")]
+            pub topic: ::core::option::Option<i32>,
+        }
+    }
     /// The operation that registers a given vote from the neuron for a given
     /// proposal (a directly cast vote as opposed to a vote that is cast as
     /// a result of a follow relation).
@@ -2166,6 +2289,10 @@ pub mod manage_neuron {
         RemoveNeuronPermissions(RemoveNeuronPermissions),
         #[prost(message, tag = "13")]
         StakeMaturity(StakeMaturity),
+        #[prost(message, tag = "14")]
+        MergeNeurons(MergeNeurons),
+        #[prost(message, tag = "15")]
+        SetFollowing(SetFollowing),
     }
 }
 /// The response of a ManageNeuron command.
@@ -2176,7 +2303,7 @@ pub mod manage_neuron {
 pub struct ManageNeuronResponse {
     #[prost(
         oneof = "manage_neuron_response::Command",
-        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13"
+        tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15"
     )]
     pub command: ::core::option::Option<manage_neuron_response::Command>,
 }
@@ -2238,6 +2365,11 @@ pub mod manage_neuron_response {
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct FollowResponse {}
+    /// The response to the ManageNeuron command 'set_following'.
+    #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct SetFollowingResponse {}
     /// The response to the ManageNeuron command 'make_proposal'.
     #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
     #[allow(clippy::derive_partial_eq_without_eq)]
@@ -2261,6 +2393,18 @@ pub mod manage_neuron_response {
         #[prost(message, optional, tag = "1")]
         pub created_neuron_id: ::core::option::Option<super::NeuronId>,
     }
+    /// The response to the ManageNeuron command 'merge_neurons'.
+    #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct MergeNeuronsResponse {
+        /// The source neuron after the merge.
+        #[prost(message, optional, tag = "1")]
+        pub source_neuron: ::core::option::Option<super::Neuron>,
+        /// The target neuron after the merge.
+        #[prost(message, optional, tag = "2")]
+        pub target_neuron: ::core::option::Option<super::Neuron>,
+    }
     /// The response to the ManageNeuron command 'claim_or_refresh'.
     #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
     #[allow(clippy::derive_partial_eq_without_eq)]
@@ -2311,6 +2455,10 @@ pub mod manage_neuron_response {
         RemoveNeuronPermission(RemoveNeuronPermissionsResponse),
         #[prost(message, tag = "13")]
         StakeMaturity(StakeMaturityResponse),
+        #[prost(message, tag = "14")]
+        MergeNeurons(MergeNeuronsResponse),
+        #[prost(message, tag = "15")]
+        SetFollowing(SetFollowingResponse),
     }
 }
 /// An operation that attempts to get a neuron by a given neuron ID.
@@ -2486,6 +2634,43 @@ pub struct ListNervousSystemFunctionsResponse {
 #[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListTopicsRequest {}
+/// Information about a topic, and the functions whose proposals belong to it.
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TopicInfo {
+    /// The topic.
+    #[prost(enumeration = "Topic", optional, tag = "1")]
+    pub topic: ::core::option::Option<i32>,
+    /// A short, human-readable name of the topic.
+    #[prost(string, optional, tag = "2")]
+    pub name: ::core::option::Option<::prost::alloc::string::String>,
+    /// A description of the proposals that belong to the topic.
+    #[prost(string, optional, tag = "3")]
+    pub description: ::core::option::Option<::prost::alloc::string::String>,
+    /// The native functions whose proposals belong to the topic.
+    #[prost(message, repeated, tag = "4")]
+    pub native_functions: ::prost::alloc::vec::Vec<NervousSystemFunction>,
+    /// The generic (user-defined) functions whose proposals belong to the topic.
+    #[prost(message, repeated, tag = "5")]
+    pub custom_functions: ::prost::alloc::vec::Vec<NervousSystemFunction>,
+}
+/// The response to the list_topics query.
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListTopicsResponse {
+    /// All topics, along with the functions that belong to them.
+    #[prost(message, repeated, tag = "1")]
+    pub topics: ::prost::alloc::vec::Vec<TopicInfo>,
+    /// The generic (user-defined) functions that do not belong to any topic.
+    #[prost(message, repeated, tag = "2")]
+    pub uncategorized_functions: ::prost::alloc::vec::Vec<NervousSystemFunction>,
+}
+#[derive(candid::CandidType, candid::Deserialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SetMode {
     #[prost(enumeration = "governance::Mode", tag = "1")]
     pub mode: i32,
@@ -2835,6 +3020,80 @@ impl Vote {
         }
     }
 }
+/// The topics that proposals are grouped into. Neurons can follow other neurons on
+/// a topic, which applies to proposals of all functions in that topic, including
+/// generic functions that are added later on.
+#[derive(
+    candid::CandidType,
+    candid::Deserialize,
+    comparable::Comparable,
+    strum_macros::EnumIter,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    ::prost::Enumeration,
+)]
+#[repr(i32)]
+pub enum Topic {
+    /// This exists because proto3 defaults to the 0 value on enums.
+    /// This is not a valid topic.
+    Unspecified = 0,
+    /// Proposals that change the DAO's settings, e.g., its nervous system
+    /// parameters, metadata, or ledger parameters.
+    DaoCommunitySettings = 1,
+    /// Proposals that upgrade the SNS framework canisters.
+    SnsFrameworkManagement = 2,
+    /// Proposals that manage the dapp's canisters, e.g., upgrading them,
+    /// registering them, or changing their settings.
+    DappCanisterManagement = 3,
+    /// Proposals that execute generic functions implementing the dapp's
+    /// business logic.
+    ApplicationBusinessLogic = 4,
+    /// Motion proposals.
+    Governance = 5,
+    /// Proposals that move or mint the DAO's tokens.
+    TreasuryAssetManagement = 6,
+    /// Proposals that could critically affect the dapp, e.g., adding or removing
+    /// generic functions, or deregistering dapp canisters.
+    CriticalDappOperations = 7,
+}
+impl Topic {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Topic::Unspecified => "TOPIC_UNSPECIFIED",
+            Topic::DaoCommunitySettings => "TOPIC_DAO_COMMUNITY_SETTINGS",
+            Topic::SnsFrameworkManagement => "TOPIC_SNS_FRAMEWORK_MANAGEMENT",
+            Topic::DappCanisterManagement => "TOPIC_DAPP_CANISTER_MANAGEMENT",
+            Topic::ApplicationBusinessLogic => "TOPIC_APPLICATION_BUSINESS_LOGIC",
+            Topic::Governance => "TOPIC_GOVERNANCE",
+            Topic::TreasuryAssetManagement => "TOPIC_TREASURY_ASSET_MANAGEMENT",
+            Topic::CriticalDappOperations => "TOPIC_CRITICAL_DAPP_OPERATIONS",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "TOPIC_UNSPECIFIED" => Some(Self::Unspecified),
+            "TOPIC_DAO_COMMUNITY_SETTINGS" => Some(Self::DaoCommunitySettings),
+            "TOPIC_SNS_FRAMEWORK_MANAGEMENT" => Some(Self::SnsFrameworkManagement),
+            "TOPIC_DAPP_CANISTER_MANAGEMENT" => Some(Self::DappCanisterManagement),
+            "TOPIC_APPLICATION_BUSINESS_LOGIC" => Some(Self::ApplicationBusinessLogic),
+            "TOPIC_GOVERNANCE" => Some(Self::Governance),
+            "TOPIC_TREASURY_ASSET_MANAGEMENT" => Some(Self::TreasuryAssetManagement),
+            "TOPIC_CRITICAL_DAPP_OPERATIONS" => Some(Self::CriticalDappOperations),
+            _ => None,
+        }
+    }
+}
 #[derive(
     candid::CandidType,
     candid::Deserialize,
@@ -35,15 +35,16 @@ use crate::{
                 RemoveNeuronPermissions,
             },
             manage_neuron_response::{
-                DisburseMaturityResponse, MergeMaturityResponse, StakeMaturityResponse,
+                DisburseMaturityResponse, MergeMaturityResponse, MergeNeuronsResponse,
+                StakeMaturityResponse,
             },
             neuron::{DissolveState, Followees},
             proposal::Action,
             proposal_data::ActionAuxiliary as ActionAuxiliaryPb,
             transfer_sns_treasury_funds::TransferFrom,
-            Account as AccountProto, Ballot, ClaimSwapNeuronsError, ClaimSwapNeuronsRequest,
-            ClaimSwapNeuronsResponse, ClaimedSwapNeuronStatus, DefaultFollowees,
-            DeregisterDappCanisters, DisburseMaturityInProgress, Empty,
+            Account as AccountProto, ApproveTreasuryAllowance, Ballot, ClaimSwapNeuronsError,
+            ClaimSwapNeuronsRequest, ClaimSwapNeuronsResponse, ClaimedSwapNeuronStatus,
+            DefaultFollowees, DeregisterDappCanisters, DisburseMaturityInProgress, Empty,
             ExecuteGenericNervousSystemFunction, FailStuckUpgradeInProgressRequest,
             FailStuckUpgradeInProgressResponse, GetMaturityModulationRequest,
             GetMaturityModulationResponse, GetMetadataRequest, GetMetadataResponse, GetMode,
@@ -51,11 +52,12 @@ use crate::{
             GetSnsInitializationParametersRequest, GetSnsInitializationParametersResponse,
             Governance as GovernanceProto, GovernanceError, ListNervousSystemFunctionsResponse,
             ListNeurons, ListNeuronsResponse, ListProposals, ListProposalsResponse,
-            ManageDappCanisterSettings, ManageLedgerParameters, ManageNeuron, ManageNeuronResponse,
-            ManageSnsMetadata, MintSnsTokens, NervousSystemFunction, NervousSystemParameters,
-            Neuron, NeuronId, NeuronPermission, NeuronPermissionList, NeuronPermissionType,
-            Proposal, ProposalData, ProposalDecisionStatus, ProposalId, ProposalRewardStatus,
-            RegisterDappCanisters, RewardEvent, Tally, TransferSnsTreasuryFunds,
+            ListTopicsResponse, ManageDappCanisterSettings, ManageLedgerParameters, ManageNeuron,
+            ManageNeuronResponse, ManageSnsMetadata, MintSnsTokens, NervousSystemFunction,
+            NervousSystemParameters, Neuron, NeuronId, NeuronPermission, NeuronPermissionList,
+            NeuronPermissionType, Proposal, ProposalData, ProposalDecisionStatus, ProposalId,
+            ProposalRewardStatus, RegisterDappCanisters, RevokeTreasuryAllowance, RewardEvent,
+            Subaccount as SubaccountProto, Tally, Topic, TopicInfo, TransferSnsTreasuryFunds,
             UpgradeSnsControlledCanister, UpgradeSnsToNextVersion, Vote, WaitForQuietState,
         },
     },
@@ -70,8 +72,8 @@ use crate::{
         UpgradeSnsParams,
     },
     types::{
-        function_id_to_proposal_criticality, is_registered_function_id, Environment,
-        HeapGrowthPotential, LedgerUpdateLock,
+        function_id_to_proposal_criticality, function_id_to_topic, is_registered_function_id,
+        Environment, HeapGrowthPotential, LedgerUpdateLock,
     },
 };
 use candid::{Decode, Encode};
@@ -101,7 +103,10 @@ use ic_protobuf::types::v1::CanisterInstallMode as CanisterInstallModeProto;
 use ic_sns_governance_proposal_criticality::ProposalCriticality;
 use ic_sns_governance_token_valuation::Valuation;
 use icp_ledger::DEFAULT_TRANSFER_FEE as NNS_DEFAULT_TRANSFER_FEE;
-use icrc_ledger_types::icrc1::account::{Account, Subaccount};
+use icrc_ledger_types::{
+    icrc1::account::{Account, Subaccount},
+    icrc2::approve::{ApproveArgs, ApproveError},
+};
 use lazy_static::lazy_static;
 use maplit::hashset;
 use rust_decimal::Decimal;
@@ -289,6 +294,62 @@ impl GovernanceProto {
         }
     }
 
+    /// Builds an index that maps topics to (followee) neuron IDs to these neuron's followers.
+    /// The resulting index is a map
+    /// Topic -> (followee's neuron ID) -> set of followers' neuron IDs.
+    ///
+    /// The index is built from the `neurons` in the `Governance` struct, which map followers
+    /// (the neuron ID) to a set of followees per topic.
+    pub fn build_topic_follower_index(
+        &self,
+        neurons: &BTreeMap<String, Neuron>,
+    ) -> BTreeMap<i32, BTreeMap<String, BTreeSet<NeuronId>>> {
+        let mut topic_follower_index = BTreeMap::new();
+        for neuron in neurons.values() {
+            GovernanceProto::add_neuron_to_topic_follower_index(&mut topic_follower_index, neuron);
+        }
+        topic_follower_index
+    }
+
+    /// Adds a neuron to the topic_follower_index.
+    pub fn add_neuron_to_topic_follower_index(
+        index: &mut BTreeMap<i32, BTreeMap<String, BTreeSet<NeuronId>>>,
+        neuron: &Neuron,
+    ) {
+        let neuron_id = neuron.id.as_ref().expect("Neuron must have a NeuronId");
+        for (topic, followees) in neuron.topic_followees.iter() {
+            let followee_index = index.entry(*topic).or_default();
+            for followee in followees.followees.iter() {
+                followee_index
+                    .entry(followee.to_string())
+                    .or_default()
+                    .insert(neuron_id.clone());
+            }
+        }
+    }
+
+    /// Removes a neuron from the topic_follower_index.
+    pub fn remove_neuron_from_topic_follower_index(
+        index: &mut BTreeMap<i32, BTreeMap<String, BTreeSet<NeuronId>>>,
+        neuron: &Neuron,
+    ) {
+        let neuron_id = neuron.id.as_ref().expect("Neuron must have a NeuronId");
+        for (topic, followees) in neuron.topic_followees.iter() {
+            let Some(followee_index) = index.get_mut(topic) else {
+                continue;
+            };
+            for followee in followees.followees.iter() {
+                let nid = followee.to_string();
+                if let Some(followee_set) = followee_index.get_mut(&nid) {
+                    followee_set.remove(neuron_id);
+                    if followee_set.is_empty() {
+                        followee_index.remove(&nid);
+                    }
+                }
+            }
+        }
+    }
+
     /// Iterate through one neuron and add all the principals that have some permission on this
     /// neuron to the index that maps principalIDs to a set of neurons for which the principal
     /// has some permissions.
@@ -671,6 +732,16 @@ pub struct Governance {
     /// Function ID -> (followee's neuron ID) -> set of followers' neuron IDs.
     pub function_followee_index: BTreeMap<u64, BTreeMap<String, BTreeSet<NeuronId>>>,
 
+    /// Cached data structure that (for each topic) maps a followee to the set
+    /// of its followers. It is the inverse of the mapping from follower to
+    /// topic followees that is stored in each (follower) neuron.
+    ///
+    /// This is a cached index and will be removed and recreated when the state
+    /// is saved and restored.
+    ///
+    /// Topic -> (followee's neuron ID) -> set of followers' neuron IDs.
+    pub topic_follower_index: BTreeMap<i32, BTreeMap<String, BTreeSet<NeuronId>>>,
+
     /// Maps Principals to the Neuron IDs of all Neurons for which this principal
     /// has some permissions, i.e., all neurons that have this principal associated
     /// with a NeuronPermissionType for the Neuron.
@@ -744,6 +815,7 @@ impl Governance {
             nns_ledger,
             cmc,
             function_followee_index: BTreeMap::new(),
+            topic_follower_index: BTreeMap::new(),
             principal_to_neuron_ids_index: BTreeMap::new(),
             closest_proposal_deadline_timestamp_seconds: 0,
             latest_gc_timestamp_seconds: 0,
@@ -802,6 +874,7 @@ impl Governance {
         self.function_followee_index = self
             .proto
             .build_function_followee_index(&self.proto.neurons);
+        self.topic_follower_index = self.proto.build_topic_follower_index(&self.proto.neurons);
         self.principal_to_neuron_ids_index = self
             .proto
             .build_principal_to_neuron_ids_index(&self.proto.neurons);
@@ -912,7 +985,8 @@ impl Governance {
     }
 
     /// Adds a neuron to the list of neurons and updates the indices
-    /// `principal_to_neuron_ids_index` and `function_followee_index`.
+    /// `principal_to_neuron_ids_index`, `function_followee_index` and
+    /// `topic_follower_index`.
     ///
     /// Preconditions:
     /// - the heap can still grow
@@ -952,13 +1026,19 @@ impl Governance {
             &neuron,
         );
 
+        GovernanceProto::add_neuron_to_topic_follower_index(
+            &mut self.topic_follower_index,
+            &neuron,
+        );
+
         self.proto.neurons.insert(neuron_id.to_string(), neuron);
 
         Ok(())
     }
 
     /// Removes a neuron from the list of neurons and updates the indices
-    /// `principal_to_neuron_ids_index` and `function_followee_index`.
+    /// `principal_to_neuron_ids_index`, `function_followee_index` and
+    /// `topic_follower_index`.
     ///
     /// Preconditions:
     /// - the given `neuron_id` exists in `self.proto.neurons`
@@ -987,6 +1067,11 @@ impl Governance {
             &neuron,
         );
 
+        GovernanceProto::remove_neuron_from_topic_follower_index(
+            &mut self.topic_follower_index,
+            &neuron,
+        );
+
         self.proto.neurons.remove(&neuron_id.to_string());
 
         Ok(())
@@ -1313,6 +1398,7 @@ impl Governance {
             created_timestamp_seconds: creation_timestamp_seconds,
             aging_since_timestamp_seconds: parent_neuron.aging_since_timestamp_seconds,
             followees: parent_neuron.followees.clone(),
+            topic_followees: parent_neuron.topic_followees.clone(),
             maturity_e8s_equivalent: 0,
             dissolve_state: parent_neuron.dissolve_state.clone(),
             voting_power_percentage_multiplier: parent_neuron.voting_power_percentage_multiplier,
@@ -1379,6 +1465,279 @@ impl Governance {
         Ok(child_nid)
     }
 
+    /// Merges a (source) neuron into another (target) neuron.
+    ///
+    /// The source neuron's stake, minus the management fees (which are burned)
+    /// and the transfer fee, is transferred to the target neuron. The source
+    /// neuron's maturity and staked maturity are moved to the target neuron.
+    /// The target neuron's age becomes the stake-weighted average of the two
+    /// neurons' ages, and its dissolve delay becomes the larger of the two
+    /// neurons' dissolve delays. The target neuron also adopts the source
+    /// neuron's followees for functions for which it has no followees of its own.
+    /// The source neuron keeps its dissolve delay and, if any stake was
+    /// transferred, its age is reset.
+    ///
+    /// On success returns the source and target neurons after the merge.
+    ///
+    /// Preconditions:
+    /// - Both neurons exist and are distinct
+    /// - The caller is authorized to perform this neuron operation on the
+    ///   source neuron (NeuronPermissionType::Disburse) and on the target
+    ///   neuron (NeuronPermissionType::ConfigureDissolveState)
+    /// - Both neurons have identical permissions
+    /// - Both neurons have the same voting power percentage multiplier
+    /// - Both neurons are non-dissolving and neither of them is vesting
+    /// - Neither neuron is the proposer of an open proposal
+    /// - Neither neuron's id is in the list of neurons with ongoing operations
+    pub async fn merge_neurons(
+        &mut self,
+        id: &NeuronId,
+        caller: &PrincipalId,
+        merge: &manage_neuron::MergeNeurons,
+    ) -> Result<MergeNeuronsResponse, GovernanceError> {
+        let now = self.env.now();
+        let transaction_fee_e8s = self.transaction_fee_e8s_or_panic();
+
+        let source_nid = merge.source_neuron_id.as_ref().ok_or_else(|| {
+            GovernanceError::new_with_message(
+                ErrorType::InvalidCommand,
+                "MergeNeurons lacks a value in its source_neuron_id field.",
+            )
+        })?;
+        if source_nid == id {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::InvalidCommand,
+                "Cannot merge a neuron into itself.",
+            ));
+        }
+
+        // Get the neurons and clone to appease the borrow checker.
+        // We'll get mutable references when we need to change them later.
+        let source_neuron = self.get_neuron_result(source_nid)?.clone();
+        let target_neuron = self.get_neuron_result(id)?.clone();
+
+        source_neuron.check_authorized(caller, NeuronPermissionType::Disburse)?;
+        target_neuron.check_authorized(caller, NeuronPermissionType::ConfigureDissolveState)?;
+
+        Self::validate_neurons_can_be_merged(&source_neuron, &target_neuron, now)?;
+
+        for neuron_id in [source_nid, id] {
+            if self.is_proposer_of_open_proposal(neuron_id) {
+                return Err(GovernanceError::new_with_message(
+                    ErrorType::PreconditionFailed,
+                    format!(
+                        "Neuron {} is the proposer of an open proposal and cannot be merged.",
+                        neuron_id
+                    ),
+                ));
+            }
+        }
+
+        // The target neuron is already locked by manage_neuron, the source
+        // neuron must be locked for the duration of the merge as well.
+        let in_flight_command = NeuronInFlightCommand {
+            timestamp: now,
+            command: Some(InFlightCommand::MergeNeurons(merge.clone())),
+        };
+        let _source_lock = self.lock_neuron_for_command(source_nid, in_flight_command)?;
+
+        let from_subaccount = source_neuron.subaccount()?;
+        let to_subaccount = target_neuron.subaccount()?;
+
+        // Burn the source neuron's management fees, but only if the value
+        // exceeds the cost of a transaction fee, as the ledger doesn't support
+        // burn transfers for an amount less than the transaction fee.
+        let fees_amount_e8s = source_neuron.neuron_fees_e8s;
+        if fees_amount_e8s > transaction_fee_e8s {
+            let _result = self
+                .ledger
+                .transfer_funds(
+                    fees_amount_e8s,
+                    0, // Burning transfers don't pay a fee.
+                    Some(from_subaccount),
+                    self.governance_minting_account(),
+                    now,
+                )
+                .await?;
+        }
+
+        let source_neuron = self
+            .get_neuron_result_mut(source_nid)
+            .expect("Expected the source neuron to exist");
+        source_neuron.cached_neuron_stake_e8s = source_neuron
+            .cached_neuron_stake_e8s
+            .saturating_sub(fees_amount_e8s);
+        source_neuron.neuron_fees_e8s = 0;
+
+        // Transfer the source neuron's stake to the target neuron, if there is
+        // enough stake left to pay for the transaction fee.
+        let amount_to_transfer_e8s = source_neuron
+            .cached_neuron_stake_e8s
+            .saturating_sub(transaction_fee_e8s);
+        if amount_to_transfer_e8s > 0 {
+            let _block_height: u64 = self
+                .ledger
+                .transfer_funds(
+                    amount_to_transfer_e8s,
+                    transaction_fee_e8s,
+                    Some(from_subaccount),
+                    self.neuron_account_id(to_subaccount),
+                    self.env.random_u64(),
+                )
+                .await?;
+        }
+
+        // Update the state of the source neuron. Its stake is now gone, and it
+        // takes its maturity with it to the target neuron.
+        let source_neuron = self
+            .get_neuron_result_mut(source_nid)
+            .expect("Expected the source neuron to exist");
+        let source_age_seconds = source_neuron.age_seconds(now);
+        let source_dissolve_delay_seconds = source_neuron.dissolve_delay_seconds(now);
+        if amount_to_transfer_e8s > 0 {
+            source_neuron.cached_neuron_stake_e8s = source_neuron
+                .cached_neuron_stake_e8s
+                .saturating_sub(amount_to_transfer_e8s + transaction_fee_e8s);
+            source_neuron.aging_since_timestamp_seconds = now;
+        }
+        let maturity_e8s = std::mem::take(&mut source_neuron.maturity_e8s_equivalent);
+        let staked_maturity_e8s = source_neuron.staked_maturity_e8s_equivalent.take();
+        let source_followees = source_neuron.followees.clone();
+        let source_topic_followees = source_neuron.topic_followees.clone();
+        let source_neuron = source_neuron.clone();
+
+        // Update the state of the target neuron.
+        let target_neuron = self
+            .get_neuron_result_mut(id)
+            .expect("Expected the target neuron to exist");
+        let target_stake_e8s = target_neuron.cached_neuron_stake_e8s;
+        let new_target_stake_e8s = target_stake_e8s.saturating_add(amount_to_transfer_e8s);
+        if new_target_stake_e8s > 0 {
+            let new_age_seconds = (target_neuron.age_seconds(now) as u128
+                * target_stake_e8s as u128
+                + source_age_seconds as u128 * amount_to_transfer_e8s as u128)
+                / new_target_stake_e8s as u128;
+            target_neuron.aging_since_timestamp_seconds =
+                now.saturating_sub(new_age_seconds as u64);
+        }
+        target_neuron.cached_neuron_stake_e8s = new_target_stake_e8s;
+        target_neuron.dissolve_state = Some(DissolveState::DissolveDelaySeconds(
+            target_neuron
+                .dissolve_delay_seconds(now)
+                .max(source_dissolve_delay_seconds),
+        ));
+        target_neuron.maturity_e8s_equivalent = target_neuron
+            .maturity_e8s_equivalent
+            .saturating_add(maturity_e8s);
+        if let Some(staked_maturity_e8s) = staked_maturity_e8s {
+            target_neuron.staked_maturity_e8s_equivalent = Some(
+                target_neuron
+                    .staked_maturity_e8s_equivalent
+                    .unwrap_or_default()
+                    .saturating_add(staked_maturity_e8s),
+            );
+        }
+        for (function_id, followees) in source_followees {
+            target_neuron
+                .followees
+                .entry(function_id)
+                .or_insert(followees);
+        }
+        for (topic, followees) in source_topic_followees {
+            target_neuron
+                .topic_followees
+                .entry(topic)
+                .or_insert(followees);
+        }
+        let target_neuron = target_neuron.clone();
+
+        // Adding a neuron to the followee indices is idempotent for the followees
+        // that were already present, so this only records the adopted ones.
+        GovernanceProto::add_neuron_to_function_followee_index(
+            &mut self.function_followee_index,
+            &self.proto.id_to_nervous_system_functions,
+            &target_neuron,
+        );
+        GovernanceProto::add_neuron_to_topic_follower_index(
+            &mut self.topic_follower_index,
+            &target_neuron,
+        );
+
+        Ok(MergeNeuronsResponse {
+            source_neuron: Some(source_neuron),
+            target_neuron: Some(target_neuron),
+        })
+    }
+
+    /// Returns an error if the given source neuron cannot be merged into the
+    /// given target neuron at time `now`.
+    fn validate_neurons_can_be_merged(
+        source_neuron: &Neuron,
+        target_neuron: &Neuron,
+        now: u64,
+    ) -> Result<(), GovernanceError> {
+        for neuron in [source_neuron, target_neuron] {
+            let nid = neuron.id.as_ref().expect("Neurons must have an id");
+            let state = neuron.state(now);
+            if state != NeuronState::NotDissolving {
+                return Err(GovernanceError::new_with_message(
+                    ErrorType::PreconditionFailed,
+                    format!(
+                        "Neuron {} must be non-dissolving to be merged. It is in state {:?}",
+                        nid, state
+                    ),
+                ));
+            }
+            if neuron.is_vesting(now) {
+                return Err(GovernanceError::new_with_message(
+                    ErrorType::PreconditionFailed,
+                    format!("Neuron {} is vesting and cannot be merged.", nid),
+                ));
+            }
+        }
+
+        let permissions_by_principal = |neuron: &Neuron| {
+            neuron
+                .permissions
+                .iter()
+                .map(|permission| {
+                    let permission_types = permission
+                        .permission_type
+                        .iter()
+                        .copied()
+                        .collect::<BTreeSet<_>>();
+                    (permission.principal, permission_types)
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        if permissions_by_principal(source_neuron) != permissions_by_principal(target_neuron) {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                "Only neurons with identical permissions can be merged.",
+            ));
+        }
+
+        if source_neuron.voting_power_percentage_multiplier
+            != target_neuron.voting_power_percentage_multiplier
+        {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                "Only neurons with the same voting power percentage multiplier can be merged.",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the given neuron is the proposer of a proposal that
+    /// has not been decided yet.
+    fn is_proposer_of_open_proposal(&self, neuron_id: &NeuronId) -> bool {
+        self.proto.proposals.values().any(|proposal_data| {
+            proposal_data.status() == ProposalDecisionStatus::Open
+                && proposal_data.proposer.as_ref() == Some(neuron_id)
+        })
+    }
+
     /// Merges the maturity of a neuron into the neuron's cached stake.
     ///
     /// This method allows a neuron controller to merge the currently
@@ -1855,6 +2214,51 @@ impl Governance {
         }
     }
 
+    /// Returns the topics of this SNS, together with the native and custom
+    /// nervous system functions that belong to each of them. Custom functions
+    /// that have not been assigned a topic are listed separately.
+    pub fn list_topics(&self) -> ListTopicsResponse {
+        let custom_functions = self
+            .proto
+            .id_to_nervous_system_functions
+            .values()
+            .filter(|&f| f != &*NERVOUS_SYSTEM_FUNCTION_DELETION_MARKER)
+            .map(|f| {
+                let topic = function_id_to_topic(f.id, &self.proto.id_to_nervous_system_functions);
+                (topic, f.clone())
+            })
+            .collect::<Vec<_>>();
+
+        let topics = Topic::iter()
+            .filter(|topic| *topic != Topic::Unspecified)
+            .map(|topic| TopicInfo {
+                topic: Some(topic as i32),
+                name: Some(topic.name().to_string()),
+                description: Some(topic.description().to_string()),
+                native_functions: Action::iter()
+                    .filter(|action| action.native_topic() == Some(topic))
+                    .map(NervousSystemFunction::from)
+                    .collect(),
+                custom_functions: custom_functions
+                    .iter()
+                    .filter(|(function_topic, _)| *function_topic == Some(topic))
+                    .map(|(_, f)| f.clone())
+                    .collect(),
+            })
+            .collect();
+
+        let uncategorized_functions = custom_functions
+            .into_iter()
+            .filter(|(topic, _)| topic.is_none())
+            .map(|(_, f)| f)
+            .collect();
+
+        ListTopicsResponse {
+            topics,
+            uncategorized_functions,
+        }
+    }
+
     /// Returns the proposal IDs for all proposals that have reward status ReadyToSettle
     fn ready_to_be_settled_proposal_ids(&self) -> impl Iterator<Item = ProposalId> + '_ {
         let now = self.env.now();
@@ -2072,6 +2476,18 @@ impl Governance {
                 self.perform_manage_dapp_canister_settings(manage_dapp_canister_settings)
                     .await
             }
+            Action::ApproveTreasuryAllowance(approve) => {
+                let valuation =
+                    get_action_auxiliary(&self.proto.proposals, ProposalId { id: proposal_id })
+                        .and_then(|action_auxiliary| {
+                            action_auxiliary.unwrap_approve_treasury_allowance_or_err()
+                        });
+                self.perform_approve_treasury_allowance(proposal_id, valuation, &approve)
+                    .await
+            }
+            Action::RevokeTreasuryAllowance(revoke) => {
+                self.perform_revoke_treasury_allowance(&revoke).await
+            }
             // This should not be possible, because Proposal validation is performed when
             // a proposal is first made.
             Action::Unspecified(_) => Err(GovernanceError::new_with_message(
@@ -2587,7 +3003,8 @@ impl Governance {
         }
 
         transfer_sns_treasury_funds_amount_is_small_enough_at_execution_time_or_err(
-            transfer,
+            transfer.from_treasury(),
+            transfer.amount_e8s,
             valuation?,
             self.proto.proposals.values(),
             self.env.now(),
@@ -2652,6 +3069,168 @@ impl Governance {
         }
     }
 
+    async fn perform_approve_treasury_allowance(
+        &self,
+        proposal_id: u64, // This is just to control concurrency.
+        valuation: Result<Valuation, GovernanceError>,
+        approve: &ApproveTreasuryAllowance,
+    ) -> Result<(), GovernanceError> {
+        // Only execute one proposal of this type at a time.
+        thread_local! {
+            static IN_PROGRESS_PROPOSAL_ID: RefCell<Option<u64>> = const { RefCell::new(None) };
+        }
+        let release_on_drop = acquire(&IN_PROGRESS_PROPOSAL_ID, proposal_id);
+        if let Err(already_in_progress_proposal_id) = release_on_drop {
+            return Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                format!(
+                    "Another ApproveTreasuryAllowance proposal (ID = {}) is already in progress.",
+                    already_in_progress_proposal_id,
+                ),
+            ));
+        }
+
+        let amount_e8s = approve.amount_e8s.ok_or_else(|| {
+            GovernanceError::new_with_message(
+                ErrorType::InvalidProposal,
+                "Expected ApproveTreasuryAllowance to have an amount_e8s",
+            )
+        })?;
+
+        transfer_sns_treasury_funds_amount_is_small_enough_at_execution_time_or_err(
+            approve.from_treasury(),
+            amount_e8s,
+            valuation?,
+            self.proto.proposals.values(),
+            self.env.now(),
+        )?;
+
+        self.set_treasury_allowance(
+            approve.from_treasury(),
+            approve.spender_principal,
+            &approve.spender_subaccount,
+            amount_e8s,
+            approve
+                .expires_at_timestamp_seconds
+                .map(|seconds| seconds.saturating_mul(1_000_000_000)),
+            approve.memo,
+        )
+        .await
+    }
+
+    async fn perform_revoke_treasury_allowance(
+        &self,
+        revoke: &RevokeTreasuryAllowance,
+    ) -> Result<(), GovernanceError> {
+        // ICRC-2 has no dedicated revoke operation; approving an amount of zero has that effect.
+        self.set_treasury_allowance(
+            revoke.from_treasury(),
+            revoke.spender_principal,
+            &revoke.spender_subaccount,
+            0,
+            None,
+            None,
+        )
+        .await
+    }
+
+    /// Sets the ICRC-2 allowance of the spender on one of the treasury accounts, replacing any
+    /// previous allowance. The approval fee is paid from the treasury.
+    async fn set_treasury_allowance(
+        &self,
+        from_treasury: TransferFrom,
+        spender_principal: Option<PrincipalId>,
+        spender_subaccount: &Option<SubaccountProto>,
+        amount_e8s: u64,
+        expires_at_timestamp_nanoseconds: Option<u64>,
+        memo: Option<u64>,
+    ) -> Result<(), GovernanceError> {
+        let spender = Account {
+            owner: spender_principal
+                .ok_or_else(|| {
+                    GovernanceError::new_with_message(
+                        ErrorType::InvalidProposal,
+                        "Expected the allowance to have a spender principal",
+                    )
+                })?
+                .0,
+            subaccount: spender_subaccount
+                .as_ref()
+                .map(|s| bytes_to_subaccount(&s.subaccount[..]))
+                .transpose()?,
+        };
+
+        let (ledger_canister_id, from_subaccount, fee_e8s, treasury_name) = match from_treasury {
+            TransferFrom::IcpTreasury => (
+                self.nns_ledger.canister_id(),
+                None,
+                NNS_DEFAULT_TRANSFER_FEE.get_e8s(),
+                "ICP",
+            ),
+            TransferFrom::SnsTokenTreasury => (
+                self.ledger.canister_id(),
+                // See ic_sns_init::distributions::FractionalDeveloperVotingPower.insert_treasury_accounts
+                Some(compute_distribution_subaccount_bytes(
+                    self.env.canister_id().get(),
+                    TREASURY_SUBACCOUNT_NONCE,
+                )),
+                self.transaction_fee_e8s_or_panic(),
+                "SNS Token",
+            ),
+            TransferFrom::Unspecified => {
+                return Err(GovernanceError::new_with_message(
+                    ErrorType::PreconditionFailed,
+                    "Invalid 'from_treasury' in allowance.",
+                ))
+            }
+        };
+
+        let approve_args = ApproveArgs {
+            from_subaccount,
+            spender,
+            amount: candid::Nat::from(amount_e8s),
+            expected_allowance: None,
+            expires_at: expires_at_timestamp_nanoseconds,
+            fee: Some(candid::Nat::from(fee_e8s)),
+            memo: memo.map(Into::into),
+            created_at_time: None,
+        };
+        let approve_args = candid::encode_one(approve_args).map_err(|err| {
+            GovernanceError::new_with_message(
+                ErrorType::External,
+                format!("Error encoding icrc2_approve request: {}", err),
+            )
+        })?;
+
+        let result = self
+            .env
+            .call_canister(ledger_canister_id, "icrc2_approve", approve_args)
+            .await
+            .map_err(|err| {
+                GovernanceError::new_with_message(
+                    ErrorType::External,
+                    format!("Canister method call icrc2_approve failed: {:?}", err),
+                )
+            })?;
+        candid::decode_one::<Result<candid::Nat, ApproveError>>(&result)
+            .map_err(|err| {
+                GovernanceError::new_with_message(
+                    ErrorType::External,
+                    format!("Error decoding icrc2_approve response: {}", err),
+                )
+            })?
+            .map(|_block_index| ())
+            .map_err(|err| {
+                GovernanceError::new_with_message(
+                    ErrorType::External,
+                    format!(
+                        "Error setting allowance on the {} treasury: {}",
+                        treasury_name, err
+                    ),
+                )
+            })
+    }
+
     async fn perform_mint_sns_tokens(
         &mut self,
         mint: MintSnsTokens,
@@ -3205,13 +3784,16 @@ impl Governance {
             .neuron_fees_e8s += proposal_data.reject_cost_e8s;
 
         let function_id = u64::from(action);
+        let topic = function_id_to_topic(function_id, &self.proto.id_to_nervous_system_functions);
         // Cast a 'yes'-vote for the proposer, including following.
         Governance::cast_vote_and_cascade_follow(
             &proposal_id,
             proposer_id,
             Vote::Yes,
             function_id,
+            topic,
             &self.function_followee_index,
+            &self.topic_follower_index,
             &self.proto.neurons,
             now_seconds,
             &mut proposal_data.ballots,
@@ -3224,9 +3806,9 @@ impl Governance {
     }
 
     /// Registers the vote `vote_of_neuron` for the neuron `voting_neuron_id`
-    /// and cascades voting according to the following relationship given in
-    /// function_followee_index that (for each action) maps a followee to
-    /// the set of followers.
+    /// and cascades voting according to the following relationships given in
+    /// function_followee_index and topic_follower_index that (for each action,
+    /// resp. for each topic) map a followee to the set of followers.
     ///
     /// This method should only be called with `vote_of_neuron` being `yes`
     /// or `no`.
@@ -3238,7 +3820,9 @@ impl Governance {
         voting_neuron_id: &NeuronId,
         vote_of_neuron: Vote,
         function_id: u64,
+        topic: Option<Topic>,
         function_followee_index: &BTreeMap<u64, BTreeMap<String, BTreeSet<NeuronId>>>,
+        topic_follower_index: &BTreeMap<i32, BTreeMap<String, BTreeSet<NeuronId>>>,
         neurons: &BTreeMap<String, Neuron>,
         // As of Dec, 2023 (52eec5c), the next parameter is only used to populate Ballots. In
         // particular, this has no impact on how the implications of following are deduced.
@@ -3251,8 +3835,9 @@ impl Governance {
         // This identifies which other neurons might get "triggered" to vote by
         // filling in the current neuron's ballot.
         //
-        // By default, followers on the specific function_id are reconsidered,
-        // as well as followers have have general "catch-all" following. As an
+        // By default, followers on the specific function_id and on the topic
+        // of the function are reconsidered, as well as followers have have
+        // general "catch-all" following. As an
         // optimization, catch-all followers are not considered when the
         // proposal is not Critical.
         //
@@ -3261,6 +3846,12 @@ impl Governance {
         // a follower of Bob, because the proposal is Critical.
         let neuron_id_to_follower_neuron_ids = {
             let mut members = vec![];
+
+            if let Some(member) = topic.and_then(|topic| topic_follower_index.get(&(topic as i32)))
+            {
+                members.push(member);
+            }
+
             let mut push_member = |function_id| {
                 if let Some(member) = function_followee_index.get(&function_id) {
                     members.push(member);
@@ -3357,7 +3948,8 @@ impl Governance {
                     }
                 };
 
-                let follower_vote = follower_neuron.would_follow_ballots(function_id, ballots);
+                let follower_vote =
+                    follower_neuron.would_follow_ballots(function_id, topic, ballots);
                 if follower_vote != Vote::Unspecified {
                     // follower_neuron would be swayed by its followees!
                     //
@@ -3453,12 +4045,15 @@ impl Governance {
 
         // Update ballots.
         let function_id = u64::from(action);
+        let topic = function_id_to_topic(function_id, &self.proto.id_to_nervous_system_functions);
         Governance::cast_vote_and_cascade_follow(
             proposal_id,
             neuron_id,
             vote,
             function_id,
+            topic,
             &self.function_followee_index,
+            &self.topic_follower_index,
             &self.proto.neurons,
             now_seconds,
             &mut proposal.ballots,
@@ -3576,6 +4171,125 @@ impl Governance {
         }
     }
 
+    /// Sets the followees of a given neuron for each of the specified topics.
+    ///
+    /// For each topic, if the list of followees is empty, the neuron stops
+    /// following on that topic. Otherwise, the current list of followees for
+    /// the topic is replaced with the provided list. Any legacy (per-function)
+    /// followees for functions that belong to one of the specified topics are
+    /// removed, so that the topic following takes effect for all of them.
+    ///
+    /// Preconditions:
+    /// - the follower neuron exists
+    /// - the caller has the permission to change followers (same authorization
+    ///   as voting required, i.e., permission `Vote`)
+    /// - each topic is specified, known, and appears at most once
+    /// - no list of followees is too long (does not exceed max_followees_per_function
+    ///   as defined in the nervous system parameters)
+    fn set_following(
+        &mut self,
+        id: &NeuronId,
+        caller: &PrincipalId,
+        set_following: &manage_neuron::SetFollowing,
+    ) -> Result<(), GovernanceError> {
+        let neuron = self.get_neuron_result(id)?;
+
+        // Check that the caller is authorized to change followers (same authorization
+        // as voting required).
+        neuron.check_authorized(caller, NeuronPermissionType::Vote)?;
+
+        let max_followees_per_function = self
+            .proto
+            .parameters
+            .as_ref()
+            .expect("NervousSystemParameters not present")
+            .max_followees_per_function
+            .expect("NervousSystemParameters must have max_followees_per_function");
+
+        let mut topic_to_followees = BTreeMap::new();
+        for followees_for_topic in &set_following.topic_following {
+            let topic = followees_for_topic
+                .topic
+                .and_then(|topic| Topic::try_from(topic).ok())
+                .filter(|topic| *topic != Topic::Unspecified)
+                .ok_or_else(|| {
+                    GovernanceError::new_with_message(
+                        ErrorType::InvalidCommand,
+                        format!("Invalid topic: {:?}", followees_for_topic.topic),
+                    )
+                })?;
+
+            // Check that the list of followees is not too long. Allowing
+            // neurons to follow too many neurons allows a memory exhaustion
+            // attack on the governance canister.
+            if followees_for_topic.followees.len() > max_followees_per_function as usize {
+                return Err(GovernanceError::new_with_message(
+                    ErrorType::InvalidCommand,
+                    format!("Too many followees for topic {:?}.", topic),
+                ));
+            }
+
+            if topic_to_followees
+                .insert(topic, followees_for_topic.followees.clone())
+                .is_some()
+            {
+                return Err(GovernanceError::new_with_message(
+                    ErrorType::InvalidCommand,
+                    format!("Topic {:?} was specified more than once.", topic),
+                ));
+            }
+        }
+
+        // The function ids whose legacy followees are superseded by the new
+        // topic following.
+        let superseded_function_ids = Action::native_function_ids()
+            .into_iter()
+            .chain(self.proto.id_to_nervous_system_functions.keys().copied())
+            .filter(|function_id| {
+                function_id_to_topic(*function_id, &self.proto.id_to_nervous_system_functions)
+                    .map_or(false, |topic| topic_to_followees.contains_key(&topic))
+            })
+            .collect::<Vec<_>>();
+
+        // Remove the neuron from the follower indices before changing its
+        // followees, and re-add it afterwards.
+        let mut neuron = self.get_neuron_result(id)?.clone();
+        GovernanceProto::remove_neuron_from_function_followee_index(
+            &mut self.function_followee_index,
+            &neuron,
+        );
+        GovernanceProto::remove_neuron_from_topic_follower_index(
+            &mut self.topic_follower_index,
+            &neuron,
+        );
+
+        for function_id in superseded_function_ids {
+            neuron.followees.remove(&function_id);
+        }
+        for (topic, followees) in topic_to_followees {
+            if followees.is_empty() {
+                neuron.topic_followees.remove(&(topic as i32));
+            } else {
+                neuron
+                    .topic_followees
+                    .insert(topic as i32, Followees { followees });
+            }
+        }
+
+        GovernanceProto::add_neuron_to_function_followee_index(
+            &mut self.function_followee_index,
+            &self.proto.id_to_nervous_system_functions,
+            &neuron,
+        );
+        GovernanceProto::add_neuron_to_topic_follower_index(
+            &mut self.topic_follower_index,
+            &neuron,
+        );
+        self.proto.neurons.insert(id.to_string(), neuron);
+
+        Ok(())
+    }
+
     /// Configures a given neuron (specified by the given neuron id).
     /// Specifically, this allows to stop and start dissolving a neuron
     /// as well as to increase a neuron's dissolve delay.
@@ -3759,6 +4473,7 @@ impl Governance {
             created_timestamp_seconds: now,
             aging_since_timestamp_seconds: now,
             followees: self.default_followees_or_panic().followees,
+            topic_followees: BTreeMap::new(),
             maturity_e8s_equivalent: 0,
             dissolve_state: Some(DissolveState::DissolveDelaySeconds(0)),
             // A neuron created through the `claim_or_refresh` ManageNeuron command will
@@ -3911,6 +4626,7 @@ impl Governance {
                 created_timestamp_seconds: now,
                 aging_since_timestamp_seconds: now,
                 followees: neuron_parameter.construct_followees(),
+                topic_followees: BTreeMap::new(),
                 maturity_e8s_equivalent: 0,
                 dissolve_state: Some(DissolveState::DissolveDelaySeconds(
                     neuron_parameter.get_dissolve_delay_seconds_or_panic(),
@@ -4211,9 +4927,16 @@ impl Governance {
                 .split_neuron(&neuron_id, caller, s)
                 .await
                 .map(ManageNeuronResponse::split_response),
+            C::MergeNeurons(m) => self
+                .merge_neurons(&neuron_id, caller, m)
+                .await
+                .map(ManageNeuronResponse::merge_neurons_response),
             C::Follow(f) => self
                 .follow(&neuron_id, caller, f)
                 .map(|_| ManageNeuronResponse::follow_response()),
+            C::SetFollowing(f) => self
+                .set_following(&neuron_id, caller, f)
+                .map(|_| ManageNeuronResponse::set_following_response()),
             C::MakeProposal(p) => self
                 .make_proposal(&neuron_id, caller, p)
                 .await
@@ -4274,7 +4997,9 @@ impl Governance {
             },
             Disburse(_) => err("Disburse"),
             Split(_) => err("Split"),
+            MergeNeurons(_) => err("MergeNeurons"),
             Follow(_)
+            | SetFollowing(_)
             | MakeProposal(_)
             | RegisterVote(_)
             | ClaimOrRefresh(_)
@@ -6076,6 +6801,7 @@ mod tests {
                         target_method_name: Some("test_method".to_string()),
                         validator_canister_id: Some(CanisterId::from_u64(1).get()),
                         validator_method_name: Some("test_validator_method".to_string()),
+                        topic: None,
                     },
                 )),
             },
@@ -8551,6 +9277,7 @@ mod tests {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from(100).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    topic: None,
                 },
             )),
         };
@@ -9534,6 +10261,7 @@ mod tests {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from(1).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    topic: None,
                 },
             )),
         };
@@ -9556,6 +10284,7 @@ mod tests {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(invalid_canister_target.get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    topic: None,
                 },
             )),
         };
@@ -9779,7 +10508,9 @@ mod tests {
                     &voting_neuron_id,
                     vote_of_neuron,
                     function_id,
+                    None,
                     &function_followee_index,
+                    &BTreeMap::new(),
                     &neurons,
                     now_seconds,
                     &mut ballots,
@@ -2,7 +2,7 @@ use crate::{
     pb::v1::{
         governance_error::ErrorType, manage_neuron, neuron::DissolveState, proposal::Action,
         Ballot, Empty, GovernanceError, Neuron, NeuronId, NeuronPermission, NeuronPermissionList,
-        NeuronPermissionType, Vote,
+        NeuronPermissionType, Topic, Vote,
     },
     types::function_id_to_proposal_criticality,
 };
@@ -235,11 +235,13 @@ impl Neuron {
 
     /// Given the specified `ballots`, determine how the neuron would
     /// vote on a proposal of `action` based on which neurons this
-    /// neuron follows on this action (or on the default action if this
-    /// neuron doesn't specify any followees for `action`).
+    /// neuron follows on this action (or on the action's `topic` if this
+    /// neuron doesn't specify any followees for `action`, or on the default
+    /// action if this neuron doesn't specify any followees for either).
     pub(crate) fn would_follow_ballots(
         &self,
         function_id: u64,
+        topic: Option<Topic>,
         ballots: &BTreeMap<String, Ballot>,
     ) -> Vote {
         // Step 1: Who are the relevant followees?
@@ -256,8 +258,20 @@ impl Neuron {
 
         let mut followee_neuron_ids = get_followee_neuron_ids(function_id);
 
+        // If this Neuron does not have followees specifically for the function, then fall back to
+        // the following on the function's topic.
+        if followee_neuron_ids.is_empty() {
+            if let Some(topic) = topic {
+                followee_neuron_ids = self
+                    .topic_followees
+                    .get(&(topic as i32))
+                    .map(|followees_message| &followees_message.followees)
+                    .unwrap_or(&empty);
+            }
+        }
+
         // If the function is not critical, and this Neuron does not have followees specifically for
-        // the function, then fall back to the "catch-all" following.
+        // the function or its topic, then fall back to the "catch-all" following.
         if followee_neuron_ids.is_empty() {
             use ProposalCriticality::{Critical, Normal};
             match function_id_to_proposal_criticality(function_id) {
@@ -17,12 +17,13 @@ use crate::{
             TransferSnsTreasuryFundsActionAuxiliary,
         },
         transfer_sns_treasury_funds::TransferFrom,
-        DeregisterDappCanisters, ExecuteGenericNervousSystemFunction, Governance, GovernanceError,
-        LogVisibility, ManageDappCanisterSettings, ManageLedgerParameters, ManageSnsMetadata,
-        MintSnsTokens, Motion, NervousSystemFunction, NervousSystemParameters, Proposal,
-        ProposalData, ProposalDecisionStatus, ProposalId, ProposalRewardStatus,
-        RegisterDappCanisters, Tally, TransferSnsTreasuryFunds, UpgradeSnsControlledCanister,
-        UpgradeSnsToNextVersion, Valuation as ValuationPb, Vote,
+        ApproveTreasuryAllowance, DeregisterDappCanisters, ExecuteGenericNervousSystemFunction,
+        Governance, GovernanceError, LogVisibility, ManageDappCanisterSettings,
+        ManageLedgerParameters, ManageSnsMetadata, MintSnsTokens, Motion, NervousSystemFunction,
+        NervousSystemParameters, Proposal, ProposalData, ProposalDecisionStatus, ProposalId,
+        ProposalRewardStatus, RegisterDappCanisters, RevokeTreasuryAllowance, Subaccount, Tally,
+        Topic, TransferSnsTreasuryFunds, UpgradeSnsControlledCanister, UpgradeSnsToNextVersion,
+        Valuation as ValuationPb, Vote,
     },
     sns_upgrade::{get_upgrade_params, UpgradeSnsParams},
     types::Environment,
@@ -175,6 +176,7 @@ pub(crate) fn get_action_auxiliary(
 pub(crate) enum ActionAuxiliary {
     TransferSnsTreasuryFunds(Valuation),
     MintSnsTokens(Valuation),
+    ApproveTreasuryAllowance(Valuation),
     None,
 }
 
@@ -193,6 +195,21 @@ impl ActionAuxiliary {
             )),
         }
     }
+
+    pub fn unwrap_approve_treasury_allowance_or_err(self) -> Result<Valuation, GovernanceError> {
+        match self {
+            Self::ApproveTreasuryAllowance(valuation) => Ok(valuation),
+
+            wrong => Err(GovernanceError::new_with_message(
+                ErrorType::InconsistentInternalData,
+                format!(
+                    "Missing supporting information. Specifically, \
+                     no treasury valuation factors: {:#?}",
+                    wrong,
+                ),
+            )),
+        }
+    }
 }
 
 /// Most proposal actions have no auxiliary data. In those cases, we would have
@@ -217,6 +234,14 @@ impl TryFrom<ActionAuxiliary> for Option<ActionAuxiliaryPb> {
                     valuation: Some(ValuationPb::try_from(valuation)?),
                 },
             )),
+
+            ActionAuxiliary::ApproveTreasuryAllowance(valuation) => {
+                Some(ActionAuxiliaryPb::ApproveTreasuryAllowance(
+                    proposal_data::TransferSnsTreasuryFundsActionAuxiliary {
+                        valuation: Some(ValuationPb::try_from(valuation)?),
+                    },
+                ))
+            }
         };
 
         Ok(result)
@@ -247,6 +272,14 @@ impl TryFrom<&Option<ActionAuxiliaryPb>> for ActionAuxiliary {
 
                 ActionAuxiliary::MintSnsTokens(valuation)
             }
+            Some(ActionAuxiliaryPb::ApproveTreasuryAllowance(action_auxiliary)) => {
+                let TransferSnsTreasuryFundsActionAuxiliary { valuation } = action_auxiliary;
+
+                let valuation = Valuation::try_from(valuation.as_ref().unwrap_or_default())
+                    .map_err(|err| format!("Invalid ActionAuxiliaryPb {:?}: {}", src, err))?;
+
+                ActionAuxiliary::ApproveTreasuryAllowance(valuation)
+            }
         };
 
         Ok(result)
@@ -444,6 +477,20 @@ pub(crate) async fn validate_and_render_action(
         proposal::Action::ManageDappCanisterSettings(manage_dapp_canister_settings) => {
             validate_and_render_manage_dapp_canister_settings(manage_dapp_canister_settings)
         }
+        proposal::Action::ApproveTreasuryAllowance(approve) => {
+            return validate_and_render_approve_treasury_allowance(
+                approve,
+                sns_transfer_fee_e8s,
+                env,
+                swap_canister_id,
+                sns_ledger_canister_id,
+                proposals,
+            )
+            .await;
+        }
+        proposal::Action::RevokeTreasuryAllowance(revoke) => {
+            validate_and_render_revoke_treasury_allowance(revoke)
+        }
     }
     .map(|rendering| (rendering, ActionAuxiliary::None))
 }
@@ -668,7 +715,8 @@ impl TreasuryAccount for Token {
     }
 }
 
-/// Currently, two Actions implement this: TransferSnsTreasuryFunds, and MintSnsTokens.
+/// Currently, three Actions implement this: TransferSnsTreasuryFunds, ApproveTreasuryAllowance,
+/// and MintSnsTokens.
 ///
 /// The thing that they have in common here is that we want to limit the 7-day amount total of these
 /// proposals.
@@ -986,6 +1034,269 @@ impl TokenProposalAction for MintSnsTokens {
     }
 }
 
+/// Validates and renders an ApproveTreasuryAllowance proposal. An allowance lets the spender move
+/// up to the approved amount out of the treasury, so it is subject to the same 7-day limit as
+/// TransferSnsTreasuryFunds.
+///
+/// Returns ActionAuxiliary::ApproveTreasuryAllowance.
+async fn validate_and_render_approve_treasury_allowance(
+    approve: &ApproveTreasuryAllowance,
+    sns_transfer_fee_e8s: u64,
+    env: &dyn Environment,
+    swap_canister_id: CanisterId,
+    sns_ledger_canister_id: CanisterId,
+    proposals: impl Iterator<Item = &ProposalData>,
+) -> Result<
+    (
+        String, // Rendering.
+        ActionAuxiliary,
+    ),
+    String,
+> {
+    let mut defects = vec![];
+
+    // Validate amount. This requires calling CMC and the swap canister; hence, await.
+    let valuation = treasury_valuation_if_proposal_amount_is_small_enough_or_err(
+        env,
+        sns_ledger_canister_id,
+        swap_canister_id,
+        proposals,
+        approve,
+    )
+    .await;
+    let valuation = match valuation {
+        Ok(ok) => Some(ok),
+        Err(err) => {
+            defects.push(err);
+            None
+        }
+    };
+
+    locally_validate_and_render_approve_treasury_allowance(
+        approve,
+        sns_transfer_fee_e8s,
+        env.now(),
+        defects,
+    )
+    .and_then(|rendering| match valuation {
+        Some(valuation) => Ok((
+            rendering,
+            ActionAuxiliary::ApproveTreasuryAllowance(valuation),
+        )),
+
+        // As in validate_and_render_transfer_sns_treasury_funds, this cannot happen, because a
+        // missing valuation means that defects was nonempty, in which case no rendering is
+        // generated.
+        None => Err(
+            "There seems to be a bug in the amount validator. Somehow, no valuation, \
+             even though a rendering was generated."
+                .to_string(),
+        ),
+    })
+}
+
+/// Performs all the validation on an ApproveTreasuryAllowance that does not require fetching
+/// information from other canisters.
+fn locally_validate_and_render_approve_treasury_allowance(
+    approve: &ApproveTreasuryAllowance,
+    sns_transfer_fee_e8s: u64,
+    now_timestamp_seconds: u64,
+    mut defects: Vec<String>,
+) -> Result<String, String> {
+    let (from, unit) = match approve.from_treasury() {
+        TransferFrom::IcpTreasury => ("ICP Treasury (ICP Ledger)", "ICP"),
+        TransferFrom::SnsTokenTreasury => ("SNS Token Treasury (SNS Ledger)", "SNS Tokens"),
+        TransferFrom::Unspecified => {
+            defects.push(
+                "Must specify a treasury from which to approve the allowance (ICP/SNS Token)."
+                    .to_string(),
+            );
+            ("", "")
+        }
+    };
+
+    // An allowance smaller than the fee could never be used to make a transfer.
+    let minimum_transaction = match approve.from_treasury() {
+        TransferFrom::IcpTreasury => NNS_DEFAULT_TRANSFER_FEE.get_e8s(),
+        TransferFrom::SnsTokenTreasury => sns_transfer_fee_e8s,
+        TransferFrom::Unspecified => 0,
+    };
+    if approve.amount_e8s.is_none() {
+        defects.push("Must specify an amount_e8s to approve.".to_string());
+    } else if approve.amount_e8s() < minimum_transaction {
+        defects.push(format!(
+            "For allowances from {}, the fee and minimum transaction is {} e8s",
+            from, minimum_transaction
+        ))
+    }
+
+    if let Some(expires_at_timestamp_seconds) = approve.expires_at_timestamp_seconds {
+        if expires_at_timestamp_seconds <= now_timestamp_seconds {
+            defects.push(format!(
+                "expires_at_timestamp_seconds ({}) must be in the future.",
+                expires_at_timestamp_seconds
+            ));
+        }
+    }
+
+    let spender_account = validate_and_render_spender_account(
+        approve.spender_principal,
+        &approve.spender_subaccount,
+        &mut defects,
+    );
+
+    // Generate final report.
+    if !defects.is_empty() {
+        return Err(format!(
+            "ApproveTreasuryAllowance proposal was invalid for the following reason(s):\n{}",
+            defects.join("\n"),
+        ));
+    }
+
+    let display_amount_tokens = i2d(approve.amount_e8s()) / i2d(E8);
+    let expires_at = match approve.expires_at_timestamp_seconds {
+        Some(expires_at_timestamp_seconds) => expires_at_timestamp_seconds.to_string(),
+        None => "Never".to_string(),
+    };
+    Ok(format!(
+        r"# Proposal to approve an allowance on SNS Treasury funds:
+## Source treasury: {from}
+## Amount: {display_amount_tokens:.8} {unit}
+## Amount (e8s): {amount_e8s}
+## Spender account: {spender_account}
+## Expires at (seconds since the Unix epoch): {expires_at}
+## Memo: {memo}",
+        amount_e8s = approve.amount_e8s(),
+        memo = approve.memo()
+    ))
+}
+
+impl TokenProposalAction for ApproveTreasuryAllowance {
+    fn token(&self) -> Result<Token, String> {
+        match self.from_treasury() {
+            TransferFrom::IcpTreasury => Ok(Token::Icp),
+            TransferFrom::SnsTokenTreasury => Ok(Token::SnsToken),
+            TransferFrom::Unspecified => Err(format!(
+                "Invalid ApproveTreasuryAllowance: \
+                 The `from_treasury` field does not hold a known treasury: {:#?}",
+                self,
+            )),
+        }
+    }
+
+    fn proposal_amount_tokens(&self) -> Result<Decimal, String> {
+        let amount_e8s = self
+            .amount_e8s
+            // This Err only occurs when self is invalid.
+            .ok_or_else(|| "The `amount_e8s` field is not populated.".to_string())?;
+
+        denominations_to_tokens(amount_e8s, E8)
+            // This Err will not be generated, because we are dividing a u64 (amount_e8s) by a
+            // positive number (E8).
+            .ok_or_else(|| {
+                format!(
+                    "Unable to convert proposal amount {} e8s to tokens.",
+                    amount_e8s,
+                )
+            })
+    }
+
+    fn recent_amount_total_tokens<'a>(
+        &self,
+        proposals: impl Iterator<Item = &'a ProposalData>,
+        now_timestamp_seconds: u64,
+    ) -> Result<Decimal, String> {
+        total_treasury_transfer_amount_tokens(
+            proposals,
+            self.from_treasury(),
+            now_timestamp_seconds - 7 * ONE_DAY_SECONDS,
+        )
+    }
+
+    fn recent_amount_total_upper_bound_tokens(valuation: &Valuation) -> Result<Decimal, String> {
+        transfer_sns_treasury_funds_7_day_total_upper_bound_tokens(*valuation)
+            // Err is most likely a bug.
+            .map_err(|treasury_limit_error| {
+                format!("Unable to validate amount: {:?}", treasury_limit_error,)
+            })
+    }
+}
+
+/// Validates and renders a RevokeTreasuryAllowance proposal. Revoking an allowance can only
+/// reduce what can leave the treasury, so no valuation is needed.
+fn validate_and_render_revoke_treasury_allowance(
+    revoke: &RevokeTreasuryAllowance,
+) -> Result<String, String> {
+    let mut defects = vec![];
+
+    let from = match revoke.from_treasury() {
+        TransferFrom::IcpTreasury => "ICP Treasury (ICP Ledger)",
+        TransferFrom::SnsTokenTreasury => "SNS Token Treasury (SNS Ledger)",
+        TransferFrom::Unspecified => {
+            defects.push(
+                "Must specify a treasury from which to revoke the allowance (ICP/SNS Token)."
+                    .to_string(),
+            );
+            ""
+        }
+    };
+
+    let spender_account = validate_and_render_spender_account(
+        revoke.spender_principal,
+        &revoke.spender_subaccount,
+        &mut defects,
+    );
+
+    if !defects.is_empty() {
+        return Err(format!(
+            "RevokeTreasuryAllowance proposal was invalid for the following reason(s):\n{}",
+            defects.join("\n"),
+        ));
+    }
+
+    Ok(format!(
+        r"# Proposal to revoke an allowance on SNS Treasury funds:
+## Source treasury: {from}
+## Spender account: {spender_account}"
+    ))
+}
+
+/// Renders the spender of an ApproveTreasuryAllowance or RevokeTreasuryAllowance proposal, which
+/// must be a non-anonymous principal, optionally with a valid subaccount. Problems are added to
+/// `defects`.
+fn validate_and_render_spender_account(
+    spender_principal: Option<PrincipalId>,
+    spender_subaccount: &Option<Subaccount>,
+    defects: &mut Vec<String>,
+) -> String {
+    let spender_principal = if let Some(spender_principal) = spender_principal {
+        if spender_principal == PrincipalId::new_anonymous() {
+            defects.push("spender_principal must not be anonymous.".to_string());
+        }
+        spender_principal
+    } else {
+        defects.push("Must specify a spender_principal.".to_string());
+        PrincipalId::new_anonymous()
+    };
+
+    let subaccount = match spender_subaccount {
+        None => None,
+        Some(s) => match bytes_to_subaccount(&s.subaccount[..]) {
+            Ok(s) => Some(s),
+            Err(e) => {
+                defects.push(e.error_message);
+                return "".to_string();
+            }
+        },
+    };
+
+    Account {
+        owner: spender_principal.0,
+        subaccount,
+    }
+    .to_string()
+}
+
 /// Validates and renders a proposal with action UpgradeSnsControlledCanister.
 fn validate_and_render_upgrade_sns_controlled_canister(
     upgrade: &UpgradeSnsControlledCanister,
@@ -1228,6 +1539,7 @@ impl TryFrom<&NervousSystemFunction> for ValidGenericNervousSystemFunction {
                 target_method_name,
                 validator_canister_id,
                 validator_method_name,
+                topic,
             })) => {
                 // Validate the target_canister_id field.
                 let target_canister_id =
@@ -1251,6 +1563,16 @@ impl TryFrom<&NervousSystemFunction> for ValidGenericNervousSystemFunction {
                     defects.push("validator_method_name was empty.".to_string());
                 }
 
+                // Validate the (optional) topic field.
+                if let Some(topic) = topic {
+                    match Topic::try_from(*topic) {
+                        Ok(Topic::Unspecified) | Err(_) => {
+                            defects.push(format!("topic {} is not a valid topic.", topic));
+                        }
+                        Ok(_) => (),
+                    }
+                }
+
                 if !defects.is_empty() {
                     return Err(format!(
                         "ExecuteNervousSystemFunction was invalid for the following reason(s):\n{}",
@@ -2080,8 +2402,8 @@ impl ProposalData {
         }
 
         // At this point, we can let go of most proposals. The only special case is
-        // TransferSnsTreasuryFunds and MintSnsTokens (the common thread between these is that these
-        // affect the value of the treasury). We want to hang onto those for at least 7 days after
+        // TransferSnsTreasuryFunds, ApproveTreasuryAllowance, and MintSnsTokens (the common thread
+        // between these is that these affect the value of the treasury). We want to hang onto those for at least 7 days after
         // they have been successfully executed. This is because they are still needed for the
         // purposes of limiting amounts.
         let Some(proposal) = &self.proposal else {
@@ -2089,7 +2411,10 @@ impl ProposalData {
             return true;
         };
         let retention_duration_seconds = match &proposal.action {
-            Some(Action::TransferSnsTreasuryFunds(_)) => {
+            // Allowances count towards the same limit as transfers, so they are retained for as
+            // long.
+            Some(Action::TransferSnsTreasuryFunds(_))
+            | Some(Action::ApproveTreasuryAllowance(_)) => {
                 EXECUTED_TRANSFER_SNS_TREASURY_FUNDS_PROPOSAL_RETENTION_DURATION_SECONDS
             }
             Some(Action::MintSnsTokens(_)) => {
@@ -2207,8 +2532,11 @@ impl ProposalRewardStatus {
     }
 }
 
+/// Used when executing TransferSnsTreasuryFunds and ApproveTreasuryAllowance proposals, which share
+/// the same 7 day upper bound. `from_treasury` and `amount_e8s` come from the proposal's action.
 pub(crate) fn transfer_sns_treasury_funds_amount_is_small_enough_at_execution_time_or_err<'a>(
-    transfer: &TransferSnsTreasuryFunds,
+    from_treasury: TransferFrom,
+    amount_e8s: u64,
     valuation: Valuation,
     proposals: impl Iterator<Item = &'a ProposalData>,
     now_timestamp_seconds: u64,
@@ -2232,7 +2560,7 @@ pub(crate) fn transfer_sns_treasury_funds_amount_is_small_enough_at_execution_ti
     // TransferSnsTreasuryFunds proposal between now and then.
     let spent_tokens = total_treasury_transfer_amount_tokens(
         proposals,
-        transfer.from_treasury(),
+        from_treasury,
         now_timestamp_seconds - 7 * ONE_DAY_SECONDS,
     )
     .map_err(|message| {
@@ -2240,7 +2568,7 @@ pub(crate) fn transfer_sns_treasury_funds_amount_is_small_enough_at_execution_ti
     })?;
 
     let remainder_tokens = allowance_tokens - spent_tokens;
-    let transfer_amount_tokens = denominations_to_tokens(transfer.amount_e8s, E8)
+    let transfer_amount_tokens = denominations_to_tokens(amount_e8s, E8)
         // This Err cannot be provoked, because we are dividing a u64 (amount_e8s) by a positive
         // integer (E8).
         .ok_or_else(|| {
@@ -2248,7 +2576,7 @@ pub(crate) fn transfer_sns_treasury_funds_amount_is_small_enough_at_execution_ti
                 ErrorType::UnreachableCode,
                 format!(
                     "Unable to convert proposals amount {} e8s to tokens.",
-                    transfer.amount_e8s,
+                    amount_e8s,
                 ),
             )
         })?;
@@ -2271,7 +2599,8 @@ pub(crate) fn transfer_sns_treasury_funds_amount_is_small_enough_at_execution_ti
 }
 
 /// Returns the total amount (in e8s) that was transfered from the treasury via
-/// TransferSnsTreasuryFunds proposals, or None if there was an overflow.
+/// TransferSnsTreasuryFunds proposals, or made available for transfer via ApproveTreasuryAllowance
+/// proposals, or None if there was an overflow.
 ///
 /// Arguments:
 /// * `proposals` - Self-explanatory.
@@ -2289,8 +2618,13 @@ fn total_treasury_transfer_amount_tokens<'a>(
     min_executed_timestamp_seconds: u64,
 ) -> Result<Decimal, String> {
     let filter_proposal_action_amount_e8s = |action: &Action| {
-        let transfer = match action {
-            Action::TransferSnsTreasuryFunds(ok) => ok,
+        let (from_treasury, amount_e8s) = match action {
+            Action::TransferSnsTreasuryFunds(transfer) => {
+                (transfer.from_treasury, transfer.amount_e8s)
+            }
+            Action::ApproveTreasuryAllowance(approve) => {
+                (approve.from_treasury, approve.amount_e8s?)
+            }
             // Skip other types of proposals.
             _ => return None,
         };
@@ -2298,12 +2632,12 @@ fn total_treasury_transfer_amount_tokens<'a>(
         let is_proposal_token_relevant =
             // Very confusingly, the from_treasury field specifies which token
             // the proposal is about.
-            TransferFrom::try_from(transfer.from_treasury) == Ok(filter_from_treasury);
+            TransferFrom::try_from(from_treasury) == Ok(filter_from_treasury);
         if !is_proposal_token_relevant {
             return None;
         }
 
-        Some(transfer.amount_e8s)
+        Some(amount_e8s)
     };
 
     total_proposal_amounts_tokens(
@@ -2776,6 +3110,7 @@ mod tests {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from_u64(1).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    topic: None,
                 },
             )),
         };
@@ -2973,6 +3308,7 @@ mod tests {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from_u64(1).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    topic: None,
                 },
             )),
         };
@@ -3019,6 +3355,7 @@ mod tests {
                         target_method_name: Some("test_method".to_string()),
                         validator_canister_id: Some(CanisterId::from_u64(i as u64).get()),
                         validator_method_name: Some("test_validator_method".to_string()),
+                        topic: None,
                     },
                 )),
             };
@@ -3035,6 +3372,7 @@ mod tests {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from_u64(u64::MAX).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    topic: None,
                 },
             )),
         };
@@ -3436,6 +3774,7 @@ Version {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from(1).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    topic: None,
                 },
             )),
         };
@@ -3457,6 +3796,7 @@ Version {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from(1).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    topic: None,
                 },
             )),
         };
@@ -3476,6 +3816,7 @@ Version {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::ic_00().get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    topic: None,
                 },
             )),
         };
@@ -4423,6 +4764,7 @@ Version {
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(canister_id.get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    topic: None,
                 },
             )),
         };
@@ -4475,6 +4817,7 @@ NervousSystemFunction {
                 validator_method_name: Some(
                     "test_validator_method",
                 ),
+                topic: None,
             },
         ),
     ),
@@ -286,3 +286,144 @@ fn test_total_treasury_transfer_amount_tokens() {
         Ok((Decimal::from(u64::MAX) + Decimal::from(1)) / Decimal::from(E8)),
     );
 }
+
+#[test]
+fn test_total_treasury_transfer_amount_tokens_includes_approved_allowances() {
+    let min_executed_timestamp_seconds = 123_456_789;
+
+    let executed_proposal = |action: Action| ProposalData {
+        proposal: Some(Proposal {
+            action: Some(action),
+            ..Default::default()
+        }),
+        executed_timestamp_seconds: min_executed_timestamp_seconds,
+        ..Default::default()
+    };
+    let approve_treasury_allowance = ApproveTreasuryAllowance {
+        from_treasury: TransferFrom::IcpTreasury as i32,
+        amount_e8s: Some(300),
+        spender_principal: Some(PrincipalId::new_user_test_id(42)),
+        ..Default::default()
+    };
+
+    let proposals = vec![
+        executed_proposal(Action::TransferSnsTreasuryFunds(TransferSnsTreasuryFunds {
+            from_treasury: TransferFrom::IcpTreasury as i32,
+            amount_e8s: 20,
+            ..Default::default()
+        })),
+        executed_proposal(Action::ApproveTreasuryAllowance(
+            approve_treasury_allowance.clone(),
+        )),
+        // Skip because wrong type of token (SNS instead of ICP).
+        executed_proposal(Action::ApproveTreasuryAllowance(ApproveTreasuryAllowance {
+            from_treasury: TransferFrom::SnsTokenTreasury as i32,
+            amount_e8s: Some(4000),
+            ..approve_treasury_allowance.clone()
+        })),
+        // Skip because revoking an allowance does not take anything from the treasury.
+        executed_proposal(Action::RevokeTreasuryAllowance(RevokeTreasuryAllowance {
+            from_treasury: TransferFrom::IcpTreasury as i32,
+            spender_principal: Some(PrincipalId::new_user_test_id(42)),
+            spender_subaccount: None,
+        })),
+    ];
+
+    assert_eq!(
+        total_treasury_transfer_amount_tokens(
+            proposals.iter(),
+            TransferFrom::IcpTreasury,
+            min_executed_timestamp_seconds,
+        ),
+        Ok(Decimal::from(320) / Decimal::from(E8)),
+    );
+
+    // Like transfers, executed allowances are retained for 7 days, because they count towards the
+    // limit.
+    let settled_proposal = ProposalData {
+        decided_timestamp_seconds: min_executed_timestamp_seconds - 5,
+        reward_event_end_timestamp_seconds: Some(min_executed_timestamp_seconds),
+        is_eligible_for_rewards: true,
+        ..proposals[1].clone()
+    };
+    assert!(!settled_proposal.can_be_purged(min_executed_timestamp_seconds + 10));
+    assert!(
+        settled_proposal.can_be_purged(min_executed_timestamp_seconds + 7 * ONE_DAY_SECONDS + 10)
+    );
+}
+
+#[test]
+fn test_locally_validate_and_render_approve_treasury_allowance() {
+    let now_timestamp_seconds = 123_456_789;
+    let sns_transfer_fee_e8s = 10_000;
+
+    let approve_treasury_allowance = ApproveTreasuryAllowance {
+        from_treasury: TransferFrom::SnsTokenTreasury as i32,
+        amount_e8s: Some(1_000_000),
+        spender_principal: Some(PrincipalId::new_user_test_id(42)),
+        spender_subaccount: None,
+        expires_at_timestamp_seconds: Some(now_timestamp_seconds + ONE_DAY_SECONDS),
+        memo: Some(7),
+    };
+
+    let rendering = locally_validate_and_render_approve_treasury_allowance(
+        &approve_treasury_allowance,
+        sns_transfer_fee_e8s,
+        now_timestamp_seconds,
+        vec![],
+    )
+    .unwrap();
+    assert!(
+        rendering.contains("## Amount (e8s): 1000000"),
+        "{}",
+        rendering
+    );
+    assert!(rendering.contains("## Memo: 7"), "{}", rendering);
+
+    let invalid_approve_treasury_allowances = vec![
+        ApproveTreasuryAllowance {
+            from_treasury: TransferFrom::Unspecified as i32,
+            ..approve_treasury_allowance.clone()
+        },
+        ApproveTreasuryAllowance {
+            amount_e8s: None,
+            ..approve_treasury_allowance.clone()
+        },
+        ApproveTreasuryAllowance {
+            amount_e8s: Some(sns_transfer_fee_e8s - 1),
+            ..approve_treasury_allowance.clone()
+        },
+        ApproveTreasuryAllowance {
+            spender_principal: None,
+            ..approve_treasury_allowance.clone()
+        },
+        ApproveTreasuryAllowance {
+            spender_principal: Some(PrincipalId::new_anonymous()),
+            ..approve_treasury_allowance.clone()
+        },
+        ApproveTreasuryAllowance {
+            spender_subaccount: Some(Subaccount {
+                subaccount: vec![1, 2, 3],
+            }),
+            ..approve_treasury_allowance.clone()
+        },
+        ApproveTreasuryAllowance {
+            expires_at_timestamp_seconds: Some(now_timestamp_seconds),
+            ..approve_treasury_allowance.clone()
+        },
+    ];
+    for invalid_approve_treasury_allowance in invalid_approve_treasury_allowances {
+        let result = locally_validate_and_render_approve_treasury_allowance(
+            &invalid_approve_treasury_allowance,
+            sns_transfer_fee_e8s,
+            now_timestamp_seconds,
+            vec![],
+        );
+        assert!(
+            result.is_err(),
+            "{:?} {:?}",
+            invalid_approve_treasury_allowance,
+            result
+        );
+    }
+}
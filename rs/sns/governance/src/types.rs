@@ -18,19 +18,20 @@ use crate::{
             governance_error::ErrorType,
             manage_neuron,
             manage_neuron_response::{
-                self, DisburseMaturityResponse, MergeMaturityResponse, StakeMaturityResponse,
+                self, DisburseMaturityResponse, MergeMaturityResponse, MergeNeuronsResponse,
+                StakeMaturityResponse,
             },
             nervous_system_function::FunctionType,
             neuron::Followees,
             proposal::Action,
-            ClaimSwapNeuronsError, ClaimSwapNeuronsResponse, ClaimedSwapNeuronStatus,
-            DefaultFollowees, DeregisterDappCanisters, Empty, ExecuteGenericNervousSystemFunction,
-            GovernanceError, ManageDappCanisterSettings, ManageLedgerParameters,
-            ManageNeuronResponse, ManageSnsMetadata, MintSnsTokens, Motion, NervousSystemFunction,
-            NervousSystemParameters, Neuron, NeuronId, NeuronPermission, NeuronPermissionList,
-            NeuronPermissionType, ProposalId, RegisterDappCanisters, RewardEvent,
-            TransferSnsTreasuryFunds, UpgradeSnsControlledCanister, UpgradeSnsToNextVersion, Vote,
-            VotingRewardsParameters,
+            ApproveTreasuryAllowance, ClaimSwapNeuronsError, ClaimSwapNeuronsResponse,
+            ClaimedSwapNeuronStatus, DefaultFollowees, DeregisterDappCanisters, Empty,
+            ExecuteGenericNervousSystemFunction, GovernanceError, ManageDappCanisterSettings,
+            ManageLedgerParameters, ManageNeuronResponse, ManageSnsMetadata, MintSnsTokens, Motion,
+            NervousSystemFunction, NervousSystemParameters, Neuron, NeuronId, NeuronPermission,
+            NeuronPermissionList, NeuronPermissionType, ProposalId, RegisterDappCanisters,
+            RevokeTreasuryAllowance, RewardEvent, Topic, TransferSnsTreasuryFunds,
+            UpgradeSnsControlledCanister, UpgradeSnsToNextVersion, Vote, VotingRewardsParameters,
         },
     },
     proposal::ValidGenericNervousSystemFunction,
@@ -115,6 +116,12 @@ pub mod native_action_ids {
 
     /// ManageDappCanisterSettings Action.
     pub const MANAGE_DAPP_CANISTER_SETTINGS: u64 = 14;
+
+    /// ApproveTreasuryAllowance Action.
+    pub const APPROVE_TREASURY_ALLOWANCE: u64 = 15;
+
+    /// RevokeTreasuryAllowance Action.
+    pub const REVOKE_TREASURY_ALLOWANCE: u64 = 16;
 }
 
 impl governance::Mode {
@@ -143,6 +150,7 @@ impl governance::Mode {
         use manage_neuron::Command as C;
         let ok = match command {
             C::Follow(_)
+            | C::SetFollowing(_)
             | C::MakeProposal(_)
             | C::RegisterVote(_)
             | C::AddNeuronPermissions(_)
@@ -240,6 +248,15 @@ impl governance::Mode {
                 )
             )),
 
+            Action::ApproveTreasuryAllowance(_) => Err(GovernanceError::new_with_message(
+                ErrorType::PreconditionFailed,
+                format!(
+                    "ApproveTreasuryAllowance proposals are not allowed while \
+                        governance is in PreInitializationSwap mode: {:#?}",
+                    action
+                )
+            )),
+
             _ => Ok(()),
         }
     }
@@ -304,6 +321,8 @@ impl From<&manage_neuron::Command> for neuron_in_flight_command::Command {
             S::AddNeuronPermissions   (x) => D::AddNeuronPermissions   (x),
             S::RemoveNeuronPermissions(x) => D::RemoveNeuronPermissions(x),
             S::StakeMaturity          (_) => D::SyncCommand(SyncCommand{}),
+            S::MergeNeurons           (x) => D::MergeNeurons           (x),
+            S::SetFollowing           (_) => D::SyncCommand(SyncCommand{}),
         }
     }
 }
@@ -1130,6 +1149,26 @@ impl From<Action> for NervousSystemFunction {
                 ),
                 function_type: Some(FunctionType::NativeNervousSystemFunction(Empty {})),
             },
+            Action::ApproveTreasuryAllowance(_) => NervousSystemFunction {
+                id: native_action_ids::APPROVE_TREASURY_ALLOWANCE,
+                name: "Approve treasury allowance".to_string(),
+                description: Some(
+                    "Proposal to allow a spender to transfer funds from an SNS Governance \
+                     controlled treasury account"
+                        .to_string(),
+                ),
+                function_type: Some(FunctionType::NativeNervousSystemFunction(Empty {})),
+            },
+            Action::RevokeTreasuryAllowance(_) => NervousSystemFunction {
+                id: native_action_ids::REVOKE_TREASURY_ALLOWANCE,
+                name: "Revoke treasury allowance".to_string(),
+                description: Some(
+                    "Proposal to revoke an allowance previously granted on an SNS Governance \
+                     controlled treasury account"
+                        .to_string(),
+                ),
+                function_type: Some(FunctionType::NativeNervousSystemFunction(Empty {})),
+            },
         }
     }
 }
@@ -1198,6 +1237,8 @@ impl manage_neuron::Command {
             manage_neuron::Command::AddNeuronPermissions(_) => "AddNeuronPermissions",
             manage_neuron::Command::RemoveNeuronPermissions(_) => "RemoveNeuronPermissions",
             manage_neuron::Command::StakeMaturity(_) => "StakeMaturity",
+            manage_neuron::Command::MergeNeurons(_) => "MergeNeurons",
+            manage_neuron::Command::SetFollowing(_) => "SetFollowing",
         }
         .to_string()
     }
@@ -1278,6 +1319,12 @@ impl ManageNeuronResponse {
         }
     }
 
+    pub fn merge_neurons_response(response: MergeNeuronsResponse) -> Self {
+        ManageNeuronResponse {
+            command: Some(manage_neuron_response::Command::MergeNeurons(response)),
+        }
+    }
+
     pub fn follow_response() -> Self {
         ManageNeuronResponse {
             command: Some(manage_neuron_response::Command::Follow(
@@ -1286,6 +1333,14 @@ impl ManageNeuronResponse {
         }
     }
 
+    pub fn set_following_response() -> Self {
+        ManageNeuronResponse {
+            command: Some(manage_neuron_response::Command::SetFollowing(
+                manage_neuron_response::SetFollowingResponse {},
+            )),
+        }
+    }
+
     pub fn make_proposal_response(proposal_id: ProposalId) -> Self {
         let proposal_id = Some(proposal_id);
         ManageNeuronResponse {
@@ -1569,9 +1624,10 @@ impl Action {
     fn proposal_criticality(&self) -> ProposalCriticality {
         use Action::*;
         match self {
-            DeregisterDappCanisters(_) | TransferSnsTreasuryFunds(_) | MintSnsTokens(_) => {
-                ProposalCriticality::Critical
-            }
+            DeregisterDappCanisters(_)
+            | TransferSnsTreasuryFunds(_)
+            | MintSnsTokens(_)
+            | ApproveTreasuryAllowance(_) => ProposalCriticality::Critical,
 
             Unspecified(_)
             | ManageNervousSystemParameters(_)
@@ -1584,7 +1640,38 @@ impl Action {
             | ManageSnsMetadata(_)
             | ManageLedgerParameters(_)
             | RegisterDappCanisters(_)
-            | ManageDappCanisterSettings(_) => ProposalCriticality::Normal,
+            | ManageDappCanisterSettings(_)
+            | RevokeTreasuryAllowance(_) => ProposalCriticality::Normal,
+        }
+    }
+
+    /// Returns the topic of this action, if it is a native action. Generic actions do not
+    /// have a topic of their own, as their topic is defined by the function they execute.
+    pub(crate) fn native_topic(&self) -> Option<Topic> {
+        use Action::*;
+        match self {
+            ManageNervousSystemParameters(_) | ManageSnsMetadata(_) | ManageLedgerParameters(_) => {
+                Some(Topic::DaoCommunitySettings)
+            }
+
+            UpgradeSnsToNextVersion(_) => Some(Topic::SnsFrameworkManagement),
+
+            UpgradeSnsControlledCanister(_)
+            | RegisterDappCanisters(_)
+            | ManageDappCanisterSettings(_) => Some(Topic::DappCanisterManagement),
+
+            Motion(_) => Some(Topic::Governance),
+
+            TransferSnsTreasuryFunds(_)
+            | MintSnsTokens(_)
+            | ApproveTreasuryAllowance(_)
+            | RevokeTreasuryAllowance(_) => Some(Topic::TreasuryAssetManagement),
+
+            AddGenericNervousSystemFunction(_)
+            | RemoveGenericNervousSystemFunction(_)
+            | DeregisterDappCanisters(_) => Some(Topic::CriticalDappOperations),
+
+            Unspecified(_) | ExecuteGenericNervousSystemFunction(_) => None,
         }
     }
 }
@@ -1628,6 +1715,79 @@ pub(crate) fn function_id_to_proposal_criticality(function_id: u64) -> ProposalC
     ProposalCriticality::Normal
 }
 
+/// Returns the topic of proposals of the function with the given ID, if it has one.
+///
+/// Native functions always have a topic (except for the "catch-all" pseudo function).
+/// Generic functions only have a topic if one was specified when they were added.
+pub(crate) fn function_id_to_topic(
+    function_id: u64,
+    id_to_nervous_system_functions: &BTreeMap<u64, NervousSystemFunction>,
+) -> Option<Topic> {
+    lazy_static! {
+        static ref NATIVE_FUNCTION_ID_TO_TOPIC: HashMap</* function_id */ u64, Topic> =
+            Action::iter()
+                .filter_map(|action| Some((u64::from(&action), action.native_topic()?)))
+                .collect();
+    }
+
+    if let Some(topic) = NATIVE_FUNCTION_ID_TO_TOPIC.get(&function_id) {
+        return Some(*topic);
+    }
+
+    match id_to_nervous_system_functions
+        .get(&function_id)?
+        .function_type
+        .as_ref()?
+    {
+        FunctionType::GenericNervousSystemFunction(function) => {
+            Some(function.topic()).filter(|topic| *topic != Topic::Unspecified)
+        }
+        FunctionType::NativeNervousSystemFunction(_) => None,
+    }
+}
+
+impl Topic {
+    /// A short, human-readable name of the topic.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topic::Unspecified => "Unspecified",
+            Topic::DaoCommunitySettings => "DAO community settings",
+            Topic::SnsFrameworkManagement => "SNS framework management",
+            Topic::DappCanisterManagement => "Dapp canister management",
+            Topic::ApplicationBusinessLogic => "Application business logic",
+            Topic::Governance => "Governance",
+            Topic::TreasuryAssetManagement => "Treasury & asset management",
+            Topic::CriticalDappOperations => "Critical dapp operations",
+        }
+    }
+
+    /// A description of the proposals that belong to the topic.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Topic::Unspecified => "Proposals that do not belong to any topic.",
+            Topic::DaoCommunitySettings => {
+                "Proposals to set the DAO's parameters, metadata and ledger settings."
+            }
+            Topic::SnsFrameworkManagement => {
+                "Proposals to upgrade the canisters of the SNS framework."
+            }
+            Topic::DappCanisterManagement => {
+                "Proposals to upgrade, register and configure the dapp's canisters."
+            }
+            Topic::ApplicationBusinessLogic => {
+                "Proposals that execute the dapp's custom (generic) functions."
+            }
+            Topic::Governance => "Motion proposals, e.g., on the DAO's direction.",
+            Topic::TreasuryAssetManagement => {
+                "Proposals to transfer, mint, or approve the transfer of the DAO's tokens."
+            }
+            Topic::CriticalDappOperations => {
+                "Proposals to add or remove custom functions, or to deregister dapp canisters."
+            }
+        }
+    }
+}
+
 impl UpgradeSnsControlledCanister {
     /// Returns a clone of self, except that "large blob fields" are replaced
     /// with a (UTF-8 encoded) textual summary of their contents. See
@@ -1767,6 +1927,8 @@ impl From<&Action> for u64 {
             Action::ManageDappCanisterSettings(_) => {
                 native_action_ids::MANAGE_DAPP_CANISTER_SETTINGS
             }
+            Action::ApproveTreasuryAllowance(_) => native_action_ids::APPROVE_TREASURY_ALLOWANCE,
+            Action::RevokeTreasuryAllowance(_) => native_action_ids::REVOKE_TREASURY_ALLOWANCE,
         }
     }
 }
@@ -2306,6 +2468,18 @@ impl From<MintSnsTokens> for Action {
     }
 }
 
+impl From<ApproveTreasuryAllowance> for Action {
+    fn from(approve_treasury_allowance: ApproveTreasuryAllowance) -> Action {
+        Action::ApproveTreasuryAllowance(approve_treasury_allowance)
+    }
+}
+
+impl From<RevokeTreasuryAllowance> for Action {
+    fn from(revoke_treasury_allowance: RevokeTreasuryAllowance) -> Action {
+        Action::RevokeTreasuryAllowance(revoke_treasury_allowance)
+    }
+}
+
 impl UpgradeSnsControlledCanister {
     // Gets the install mode if it is set, otherwise defaults to Upgrade.
     // This function is not called `mode_or_default` because `or_default` usually
@@ -2826,6 +3000,7 @@ pub(crate) mod tests {
             #[rustfmt::skip]
             let allowed_in_pre_initialization_swap = vec! [
                 Command::Follow                  (Default::default()),
+                Command::SetFollowing            (Default::default()),
                 Command::MakeProposal            (Default::default()),
                 Command::RegisterVote            (Default::default()),
                 Command::AddNeuronPermissions    (Default::default()),
@@ -2976,6 +3151,7 @@ pub(crate) mod tests {
                         target_method_name: Some("Foo".to_string()),
                         validator_canister_id: Some(*target_canister_id),
                         validator_method_name: Some("Bar".to_string()),
+                        topic: None,
                     })),
                 }
             }
@@ -16,7 +16,7 @@ use ic_sns_governance::{
         },
         manage_neuron_response::{
             self, AddNeuronPermissionsResponse, FollowResponse, MergeMaturityResponse,
-            RegisterVoteResponse, RemoveNeuronPermissionsResponse,
+            RegisterVoteResponse, RemoveNeuronPermissionsResponse, SetFollowingResponse,
         },
         neuron::{DissolveState, Followees},
         proposal::Action,
@@ -770,6 +770,27 @@ impl GovernanceCanisterFixture {
         }
     }
 
+    pub fn set_following(
+        &mut self,
+        target_neuron: &NeuronId,
+        topic_following: Vec<manage_neuron::set_following::FolloweesForTopic>,
+        caller: PrincipalId,
+    ) -> Result<SetFollowingResponse, GovernanceError> {
+        let response = self.manage_neuron(
+            target_neuron,
+            manage_neuron::Command::SetFollowing(manage_neuron::SetFollowing { topic_following }),
+            caller,
+        );
+
+        match response.command.unwrap() {
+            manage_neuron_response::Command::SetFollowing(set_following_response) => {
+                Ok(set_following_response)
+            }
+            manage_neuron_response::Command::Error(governance_error) => Err(governance_error),
+            _ => panic!("Unexpected command response when setting topic following"),
+        }
+    }
+
     pub fn vote(
         &mut self,
         target_neuron: &NeuronId,
@@ -22,30 +22,33 @@ use ic_sns_governance::{
             claim_swap_neurons_response::{ClaimSwapNeuronsResult, ClaimedSwapNeurons, SwapNeuron},
             governance_error::ErrorType,
             manage_neuron::{
-                self, claim_or_refresh, configure::Operation, AddNeuronPermissions, ClaimOrRefresh,
-                Configure, Disburse, DisburseMaturity, Follow, IncreaseDissolveDelay,
-                MergeMaturity, RegisterVote, RemoveNeuronPermissions, Split, StakeMaturity,
+                self, claim_or_refresh, configure::Operation, set_following::FolloweesForTopic,
+                AddNeuronPermissions, ClaimOrRefresh, Configure, Disburse, DisburseMaturity,
+                Follow, IncreaseDissolveDelay, MergeMaturity, MergeNeurons, RegisterVote,
+                RemoveNeuronPermissions, SetFollowing, Split, StakeMaturity,
             },
             manage_neuron_response::{
                 Command as CommandResponse, DisburseMaturityResponse, MergeMaturityResponse,
                 RegisterVoteResponse, StakeMaturityResponse,
             },
+            nervous_system_function::{FunctionType, GenericNervousSystemFunction},
             neuron,
             neuron::{DissolveState, Followees},
             proposal::Action,
             Account as AccountProto, AddMaturityRequest, Ballot, ClaimSwapNeuronsError,
             ClaimSwapNeuronsRequest, ClaimSwapNeuronsResponse, ClaimedSwapNeuronStatus,
-            DeregisterDappCanisters, Empty, GovernanceError, ManageNeuronResponse,
-            MintTokensRequest, MintTokensResponse, Motion, NervousSystemParameters, Neuron,
-            NeuronId, NeuronPermission, NeuronPermissionList, NeuronPermissionType, Proposal,
-            ProposalData, ProposalId, RegisterDappCanisters, Vote, WaitForQuietState,
+            DeregisterDappCanisters, Empty, GovernanceError, ListTopicsResponse,
+            ManageNeuronResponse, MintTokensRequest, MintTokensResponse, Motion,
+            NervousSystemFunction, NervousSystemParameters, Neuron, NeuronId, NeuronPermission,
+            NeuronPermissionList, NeuronPermissionType, Proposal, ProposalData, ProposalId,
+            RegisterDappCanisters, Topic, Vote, WaitForQuietState,
         },
     },
     types::native_action_ids,
 };
 use maplit::btreemap;
 use pretty_assertions::assert_eq;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use strum::IntoEnumIterator;
 
 pub mod fixtures;
@@ -179,6 +182,274 @@ async fn test_disburse_fails_when_state_is_not_dissolving() {
     assert_eq!(error.error_type, ErrorType::PreconditionFailed as i32);
 }
 
+/// Tests the happy path of `ManageNeuron::MergeNeurons`, i.e., that the source neuron's stake,
+/// maturity, age and followees are combined into the target neuron.
+#[test]
+fn test_merge_neurons_succeeds() {
+    let start_time = 1_000 * ONE_DAY_SECONDS;
+    let user_principal = PrincipalId::new_user_test_id(1000);
+    let target_neuron_id = neuron_id(user_principal, /*memo*/ 0);
+    let source_neuron_id = neuron_id(user_principal, /*memo*/ 1);
+    let target_followee = neuron_id(PrincipalId::new_user_test_id(1001), /*memo*/ 0);
+    let source_followee = neuron_id(PrincipalId::new_user_test_id(1002), /*memo*/ 0);
+    let followees = |followee: &NeuronId| Followees {
+        followees: vec![followee.clone()],
+    };
+
+    let mut canister_fixture = GovernanceCanisterFixtureBuilder::new()
+        .set_start_time(start_time)
+        .add_neuron(
+            NeuronBuilder::new(
+                target_neuron_id.clone(),
+                10 * E8,
+                NeuronPermission::all(&user_principal),
+            )
+            .set_dissolve_delay(6 * ONE_MONTH_SECONDS)
+            .set_aging_since_timestamp(start_time - 10 * ONE_DAY_SECONDS)
+            .set_maturity(100)
+            .add_followees(native_action_ids::MOTION, followees(&target_followee)),
+        )
+        .add_neuron(
+            NeuronBuilder::new(
+                source_neuron_id.clone(),
+                5 * E8,
+                NeuronPermission::all(&user_principal),
+            )
+            .set_dissolve_delay(12 * ONE_MONTH_SECONDS)
+            .set_aging_since_timestamp(start_time - 100 * ONE_DAY_SECONDS)
+            .set_maturity(200)
+            .add_followees(native_action_ids::MOTION, followees(&source_followee))
+            .add_followees(
+                native_action_ids::UPGRADE_SNS_CONTROLLER_CANISTER,
+                followees(&source_followee),
+            ),
+        )
+        .create();
+
+    let transaction_fee_e8s = canister_fixture
+        .get_nervous_system_parameters()
+        .transaction_fee_e8s
+        .unwrap();
+
+    let manage_neuron_response = canister_fixture.manage_neuron(
+        &target_neuron_id,
+        manage_neuron::Command::MergeNeurons(MergeNeurons {
+            source_neuron_id: Some(source_neuron_id.clone()),
+        }),
+        user_principal,
+    );
+    let response = match manage_neuron_response.command.unwrap() {
+        CommandResponse::MergeNeurons(response) => response,
+        CommandResponse::Error(error) => {
+            panic!("Unexpected error when merging the neurons: {}", error)
+        }
+        _ => panic!("Unexpected command response when merging the neurons"),
+    };
+
+    let source_neuron = canister_fixture.get_neuron(&source_neuron_id);
+    let target_neuron = canister_fixture.get_neuron(&target_neuron_id);
+    assert_eq!(response.source_neuron, Some(source_neuron.clone()));
+    assert_eq!(response.target_neuron, Some(target_neuron.clone()));
+
+    // The stake was moved, minus the transaction fee, both in governance and on the ledger.
+    let transferred_e8s = 5 * E8 - transaction_fee_e8s;
+    assert_eq!(source_neuron.cached_neuron_stake_e8s, 0);
+    assert_eq!(
+        target_neuron.cached_neuron_stake_e8s,
+        10 * E8 + transferred_e8s
+    );
+    assert_eq!(
+        canister_fixture.get_account_balance(
+            &canister_fixture.get_neuron_account_id(&source_neuron_id),
+            TargetLedger::Sns
+        ),
+        0
+    );
+    assert_eq!(
+        canister_fixture.get_account_balance(
+            &canister_fixture.get_neuron_account_id(&target_neuron_id),
+            TargetLedger::Sns
+        ),
+        10 * E8 + transferred_e8s
+    );
+
+    // The maturity was moved.
+    assert_eq!(source_neuron.maturity_e8s_equivalent, 0);
+    assert_eq!(target_neuron.maturity_e8s_equivalent, 300);
+
+    // The target neuron's age is the stake-weighted average, and its dissolve delay the maximum.
+    let expected_age_seconds = (10 * ONE_DAY_SECONDS as u128 * 10 * E8 as u128
+        + 100 * ONE_DAY_SECONDS as u128 * transferred_e8s as u128)
+        / (10 * E8 + transferred_e8s) as u128;
+    assert_eq!(
+        target_neuron.age_seconds(start_time),
+        expected_age_seconds as u64
+    );
+    assert_eq!(
+        target_neuron.dissolve_state,
+        Some(DissolveState::DissolveDelaySeconds(12 * ONE_MONTH_SECONDS))
+    );
+    assert_eq!(source_neuron.age_seconds(start_time), 0);
+    assert_eq!(
+        source_neuron.dissolve_state,
+        Some(DissolveState::DissolveDelaySeconds(12 * ONE_MONTH_SECONDS))
+    );
+
+    // The target neuron keeps its own followees and adopts the missing ones.
+    assert_eq!(
+        target_neuron.followees,
+        btreemap! {
+            native_action_ids::MOTION => followees(&target_followee),
+            native_action_ids::UPGRADE_SNS_CONTROLLER_CANISTER => followees(&source_followee),
+        }
+    );
+}
+
+/// Tests that `ManageNeuron::MergeNeurons` fails if its preconditions are not met.
+#[test]
+fn test_merge_neurons_fails_when_preconditions_are_not_met() {
+    let user_principal = PrincipalId::new_user_test_id(1000);
+    let other_principal = PrincipalId::new_user_test_id(1001);
+    let target_neuron_id = neuron_id(user_principal, /*memo*/ 0);
+    let dissolving_neuron_id = neuron_id(user_principal, /*memo*/ 1);
+    let shared_neuron_id = neuron_id(user_principal, /*memo*/ 2);
+    let other_neuron_id = neuron_id(other_principal, /*memo*/ 0);
+
+    let mut canister_fixture = GovernanceCanisterFixtureBuilder::new()
+        .add_neuron(
+            NeuronBuilder::new(
+                target_neuron_id.clone(),
+                10 * E8,
+                NeuronPermission::all(&user_principal),
+            )
+            .set_dissolve_delay(12 * ONE_MONTH_SECONDS),
+        )
+        .add_neuron(
+            NeuronBuilder::new(
+                dissolving_neuron_id.clone(),
+                E8,
+                NeuronPermission::all(&user_principal),
+            )
+            .set_dissolve_state(Some(DissolveState::WhenDissolvedTimestampSeconds(u64::MAX))),
+        )
+        .add_neuron(
+            NeuronBuilder::new(
+                shared_neuron_id.clone(),
+                E8,
+                NeuronPermission::all(&user_principal),
+            )
+            .set_dissolve_delay(12 * ONE_MONTH_SECONDS)
+            .add_neuron_permission(NeuronPermission {
+                principal: Some(other_principal),
+                permission_type: vec![NeuronPermissionType::Vote as i32],
+            }),
+        )
+        .add_neuron(
+            NeuronBuilder::new(
+                other_neuron_id.clone(),
+                E8,
+                NeuronPermission::all(&other_principal),
+            )
+            .set_dissolve_delay(12 * ONE_MONTH_SECONDS),
+        )
+        .create();
+
+    let mut merge_into_target = |source_neuron_id: &NeuronId| -> GovernanceError {
+        let manage_neuron_response = canister_fixture.manage_neuron(
+            &target_neuron_id,
+            manage_neuron::Command::MergeNeurons(MergeNeurons {
+                source_neuron_id: Some(source_neuron_id.clone()),
+            }),
+            user_principal,
+        );
+        match manage_neuron_response.command.unwrap() {
+            CommandResponse::Error(error) => error,
+            response => panic!("Expected the merge to fail, got {:?}", response),
+        }
+    };
+
+    let error = merge_into_target(&target_neuron_id);
+    assert_eq!(error.error_type, ErrorType::InvalidCommand as i32);
+
+    let error = merge_into_target(&dissolving_neuron_id);
+    assert_eq!(error.error_type, ErrorType::PreconditionFailed as i32);
+    assert!(error.error_message.contains("non-dissolving"), "{}", error);
+
+    let error = merge_into_target(&shared_neuron_id);
+    assert_eq!(error.error_type, ErrorType::PreconditionFailed as i32);
+    assert!(error.error_message.contains("permissions"), "{}", error);
+
+    let error = merge_into_target(&other_neuron_id);
+    assert_eq!(error.error_type, ErrorType::NotAuthorized as i32);
+
+    // None of the failed merges changed the neurons.
+    assert_eq!(
+        canister_fixture
+            .get_neuron(&target_neuron_id)
+            .cached_neuron_stake_e8s,
+        10 * E8
+    );
+    for neuron_id in [dissolving_neuron_id, shared_neuron_id, other_neuron_id] {
+        assert_eq!(
+            canister_fixture
+                .get_neuron(&neuron_id)
+                .cached_neuron_stake_e8s,
+            E8
+        );
+    }
+}
+
+/// Tests that `ManageNeuron::MergeNeurons` fails if one of the neurons has an open proposal.
+#[test]
+fn test_merge_neurons_fails_when_neuron_is_proposer_of_open_proposal() {
+    let user_principal = PrincipalId::new_user_test_id(1000);
+    let target_neuron_id = neuron_id(user_principal, /*memo*/ 0);
+    let source_neuron_id = neuron_id(user_principal, /*memo*/ 1);
+
+    let mut canister_fixture = GovernanceCanisterFixtureBuilder::new()
+        .add_neuron(
+            NeuronBuilder::new(
+                target_neuron_id.clone(),
+                10 * E8,
+                NeuronPermission::all(&user_principal),
+            )
+            .set_dissolve_delay(12 * ONE_MONTH_SECONDS),
+        )
+        .add_neuron(
+            NeuronBuilder::new(
+                source_neuron_id.clone(),
+                10 * E8,
+                NeuronPermission::all(&user_principal),
+            )
+            .set_dissolve_delay(12 * ONE_MONTH_SECONDS),
+        )
+        .create();
+
+    canister_fixture
+        .make_default_proposal(
+            &source_neuron_id,
+            Motion {
+                motion_text: "Test merging the proposer".to_string(),
+            },
+            user_principal,
+        )
+        .unwrap();
+
+    let manage_neuron_response = canister_fixture.manage_neuron(
+        &target_neuron_id,
+        manage_neuron::Command::MergeNeurons(MergeNeurons {
+            source_neuron_id: Some(source_neuron_id.clone()),
+        }),
+        user_principal,
+    );
+    let error = match manage_neuron_response.command.unwrap() {
+        CommandResponse::Error(error) => error,
+        response => panic!("Expected the merge to fail, got {:?}", response),
+    };
+    assert_eq!(error.error_type, ErrorType::PreconditionFailed as i32);
+    assert!(error.error_message.contains("open proposal"), "{}", error);
+}
+
 struct DisburseMaturityTestEnvironment {
     pub gov_fixture: GovernanceCanisterFixture,
     pub neuron_id: NeuronId,
@@ -751,10 +1022,18 @@ fn test_vesting_neuron_manage_neuron_operations() {
         ),
         (Command::Disburse(Disburse::default()), err("Disburse")),
         (Command::Split(Split::default()), err("Split")),
+        (
+            Command::MergeNeurons(MergeNeurons::default()),
+            err("MergeNeurons"),
+        ),
         (
             Command::Follow(Follow::default()),
             ManageNeuronResponse::follow_response(),
         ),
+        (
+            Command::SetFollowing(SetFollowing::default()),
+            ManageNeuronResponse::set_following_response(),
+        ),
         (
             Command::MakeProposal(proposal),
             ManageNeuronResponse::make_proposal_response(ProposalId::from(1)),
@@ -2445,6 +2724,521 @@ fn test_neurons_can_follow_themselves() {
         .is_err());
 }
 
+#[test]
+fn test_set_following_by_topic_cascades_votes_on_functions_of_the_topic() {
+    let followee_principal_id = PrincipalId::new_user_test_id(1000);
+    let followee_neuron_id = neuron_id(followee_principal_id, /*memo*/ 0);
+
+    let follower_principal_id = PrincipalId::new_user_test_id(1001);
+    let follower_neuron_id = neuron_id(follower_principal_id, /*memo*/ 0);
+
+    let proposer_principal_id = PrincipalId::new_user_test_id(1002);
+    let proposer_neuron_id = neuron_id(proposer_principal_id, /*memo*/ 0);
+
+    let mut canister_fixture = GovernanceCanisterFixtureBuilder::new()
+        .add_neuron(
+            NeuronBuilder::new(
+                followee_neuron_id.clone(),
+                E8,
+                NeuronPermission::all(&followee_principal_id),
+            )
+            .set_dissolve_delay(15778801),
+        )
+        .add_neuron(
+            NeuronBuilder::new(
+                follower_neuron_id.clone(),
+                E8,
+                NeuronPermission::all(&follower_principal_id),
+            )
+            .set_dissolve_delay(15778801),
+        )
+        .add_neuron(
+            NeuronBuilder::new(
+                proposer_neuron_id.clone(),
+                E8,
+                NeuronPermission::all(&proposer_principal_id),
+            )
+            .set_dissolve_delay(15778801),
+        )
+        .create();
+
+    // The follower neuron follows the followee neuron on the Governance topic.
+    canister_fixture
+        .set_following(
+            &follower_neuron_id,
+            vec![FolloweesForTopic {
+                followees: vec![followee_neuron_id.clone()],
+                topic: Some(Topic::Governance as i32),
+            }],
+            follower_principal_id,
+        )
+        .unwrap();
+
+    let follower_neuron = canister_fixture.get_neuron(&follower_neuron_id);
+    assert_eq!(
+        follower_neuron.topic_followees,
+        btreemap! {
+            Topic::Governance as i32 => neuron::Followees {
+                followees: vec![followee_neuron_id.clone()]
+            },
+        }
+    );
+
+    // A Motion belongs to the Governance topic, so the follower neuron follows the vote of
+    // the followee neuron.
+    let (proposal_id, _) = canister_fixture
+        .make_default_proposal(
+            &proposer_neuron_id,
+            Motion {
+                motion_text: "Test topic following".to_string(),
+            },
+            proposer_principal_id,
+        )
+        .unwrap();
+    canister_fixture
+        .vote(
+            &followee_neuron_id,
+            proposal_id,
+            Vote::No,
+            followee_principal_id,
+        )
+        .unwrap();
+
+    let proposal_data = canister_fixture.get_proposal_or_panic(proposal_id);
+    let follower_ballot = proposal_data
+        .ballots
+        .get(&follower_neuron_id.to_string())
+        .expect("Expected the follower neuron to have a ballot");
+    assert_eq!(follower_ballot.vote, Vote::No as i32);
+
+    // RegisterDappCanisters belongs to a different topic, so the vote of the followee neuron
+    // does not cascade to the follower neuron.
+    let (proposal_id, _) = canister_fixture
+        .make_default_proposal(
+            &proposer_neuron_id,
+            RegisterDappCanisters {
+                canister_ids: vec![PrincipalId::new_user_test_id(1)],
+            },
+            proposer_principal_id,
+        )
+        .unwrap();
+    canister_fixture
+        .vote(
+            &followee_neuron_id,
+            proposal_id,
+            Vote::No,
+            followee_principal_id,
+        )
+        .unwrap();
+
+    let proposal_data = canister_fixture.get_proposal_or_panic(proposal_id);
+    let follower_ballot = proposal_data
+        .ballots
+        .get(&follower_neuron_id.to_string())
+        .expect("Expected the follower neuron to have a ballot");
+    assert_eq!(follower_ballot.vote, Vote::Unspecified as i32);
+}
+
+#[test]
+fn test_function_followees_take_precedence_over_topic_followees_in_vote_cascades() {
+    let function_followee_principal_id = PrincipalId::new_user_test_id(1000);
+    let function_followee_neuron_id = neuron_id(function_followee_principal_id, /*memo*/ 0);
+
+    let topic_followee_principal_id = PrincipalId::new_user_test_id(1001);
+    let topic_followee_neuron_id = neuron_id(topic_followee_principal_id, /*memo*/ 0);
+
+    let follower_principal_id = PrincipalId::new_user_test_id(1002);
+    let follower_neuron_id = neuron_id(follower_principal_id, /*memo*/ 0);
+
+    let proposer_principal_id = PrincipalId::new_user_test_id(1003);
+    let proposer_neuron_id = neuron_id(proposer_principal_id, /*memo*/ 0);
+
+    let mut canister_fixture = GovernanceCanisterFixtureBuilder::new()
+        .add_neuron(
+            NeuronBuilder::new(
+                function_followee_neuron_id.clone(),
+                E8,
+                NeuronPermission::all(&function_followee_principal_id),
+            )
+            .set_dissolve_delay(15778801),
+        )
+        .add_neuron(
+            NeuronBuilder::new(
+                topic_followee_neuron_id.clone(),
+                E8,
+                NeuronPermission::all(&topic_followee_principal_id),
+            )
+            .set_dissolve_delay(15778801),
+        )
+        .add_neuron(
+            NeuronBuilder::new(
+                follower_neuron_id.clone(),
+                E8,
+                NeuronPermission::all(&follower_principal_id),
+            )
+            .set_dissolve_delay(15778801)
+            // RegisterDappCanisters does not belong to the Governance topic, so this legacy
+            // following is kept by SetFollowing below.
+            .add_followees(
+                native_action_ids::REGISTER_DAPP_CANISTERS,
+                Followees {
+                    followees: vec![function_followee_neuron_id.clone()],
+                },
+            ),
+        )
+        .add_neuron(
+            NeuronBuilder::new(
+                proposer_neuron_id.clone(),
+                E8,
+                NeuronPermission::all(&proposer_principal_id),
+            )
+            .set_dissolve_delay(15778801),
+        )
+        .create();
+
+    // The follower neuron follows one neuron on the Governance topic, and another neuron
+    // specifically on Motion proposals (which belong to the Governance topic).
+    canister_fixture
+        .set_following(
+            &follower_neuron_id,
+            vec![FolloweesForTopic {
+                followees: vec![topic_followee_neuron_id.clone()],
+                topic: Some(Topic::Governance as i32),
+            }],
+            follower_principal_id,
+        )
+        .unwrap();
+    canister_fixture
+        .follow(
+            &follower_neuron_id,
+            native_action_ids::MOTION,
+            vec![function_followee_neuron_id.clone()],
+            follower_principal_id,
+        )
+        .unwrap();
+
+    let follower_vote = |canister_fixture: &mut GovernanceCanisterFixture, proposal_id| {
+        canister_fixture
+            .get_proposal_or_panic(proposal_id)
+            .ballots
+            .get(&follower_neuron_id.to_string())
+            .expect("Expected the follower neuron to have a ballot")
+            .vote
+    };
+
+    // On Motion proposals, the function followee determines the follower's vote; the vote of
+    // the topic followee is ignored.
+    let (proposal_id, _) = canister_fixture
+        .make_default_proposal(
+            &proposer_neuron_id,
+            Motion {
+                motion_text: "Test function and topic following".to_string(),
+            },
+            proposer_principal_id,
+        )
+        .unwrap();
+    canister_fixture
+        .vote(
+            &topic_followee_neuron_id,
+            proposal_id,
+            Vote::No,
+            topic_followee_principal_id,
+        )
+        .unwrap();
+    assert_eq!(
+        follower_vote(&mut canister_fixture, proposal_id),
+        Vote::Unspecified as i32
+    );
+    canister_fixture
+        .vote(
+            &function_followee_neuron_id,
+            proposal_id,
+            Vote::Yes,
+            function_followee_principal_id,
+        )
+        .unwrap();
+    assert_eq!(
+        follower_vote(&mut canister_fixture, proposal_id),
+        Vote::Yes as i32
+    );
+
+    // The legacy following on a function of another topic still cascades.
+    let (proposal_id, _) = canister_fixture
+        .make_default_proposal(
+            &proposer_neuron_id,
+            RegisterDappCanisters {
+                canister_ids: vec![PrincipalId::new_user_test_id(1)],
+            },
+            proposer_principal_id,
+        )
+        .unwrap();
+    canister_fixture
+        .vote(
+            &function_followee_neuron_id,
+            proposal_id,
+            Vote::No,
+            function_followee_principal_id,
+        )
+        .unwrap();
+    assert_eq!(
+        follower_vote(&mut canister_fixture, proposal_id),
+        Vote::No as i32
+    );
+}
+
+#[test]
+fn test_set_following_replaces_legacy_followees_of_the_topic() {
+    let user_principal = PrincipalId::new_user_test_id(1000);
+    let neuron_id = neuron_id(user_principal, /*memo*/ 0);
+    let old_followee_neuron_id = NeuronId::new_test_neuron_id(1);
+    let new_followee_neuron_id = NeuronId::new_test_neuron_id(2);
+
+    let old_followees = Followees {
+        followees: vec![old_followee_neuron_id.clone()],
+    };
+    let mut canister_fixture = GovernanceCanisterFixtureBuilder::new()
+        .add_neuron(
+            NeuronBuilder::new(
+                neuron_id.clone(),
+                E8,
+                NeuronPermission::all(&user_principal),
+            )
+            .add_followees(native_action_ids::MOTION, old_followees.clone())
+            .add_followees(
+                native_action_ids::UPGRADE_SNS_CONTROLLER_CANISTER,
+                old_followees.clone(),
+            ),
+        )
+        .create();
+
+    canister_fixture
+        .set_following(
+            &neuron_id,
+            vec![FolloweesForTopic {
+                followees: vec![new_followee_neuron_id.clone()],
+                topic: Some(Topic::Governance as i32),
+            }],
+            user_principal,
+        )
+        .unwrap();
+
+    // The legacy followees on Motion (which belongs to the Governance topic) are replaced,
+    // while those on functions of other topics are kept.
+    let neuron = canister_fixture.get_neuron(&neuron_id);
+    assert_eq!(
+        neuron.followees,
+        btreemap! {
+            native_action_ids::UPGRADE_SNS_CONTROLLER_CANISTER => old_followees,
+        }
+    );
+    assert_eq!(
+        neuron.topic_followees,
+        btreemap! {
+            Topic::Governance as i32 => Followees {
+                followees: vec![new_followee_neuron_id.clone()]
+            },
+        }
+    );
+    assert_eq!(
+        canister_fixture
+            .governance
+            .topic_follower_index
+            .get(&(Topic::Governance as i32))
+            .and_then(|followee_index| followee_index.get(&new_followee_neuron_id.to_string())),
+        Some(&BTreeSet::from([neuron_id.clone()]))
+    );
+
+    // Setting an empty list of followees stops following on the topic.
+    canister_fixture
+        .set_following(
+            &neuron_id,
+            vec![FolloweesForTopic {
+                followees: vec![],
+                topic: Some(Topic::Governance as i32),
+            }],
+            user_principal,
+        )
+        .unwrap();
+
+    let neuron = canister_fixture.get_neuron(&neuron_id);
+    assert_eq!(neuron.topic_followees, btreemap! {});
+    assert_eq!(
+        canister_fixture
+            .governance
+            .topic_follower_index
+            .get(&(Topic::Governance as i32))
+            .and_then(|followee_index| followee_index.get(&new_followee_neuron_id.to_string())),
+        None
+    );
+}
+
+#[test]
+fn test_set_following_fails_when_preconditions_are_not_met() {
+    let user_principal = PrincipalId::new_user_test_id(1000);
+    let neuron_id = neuron_id(user_principal, /*memo*/ 0);
+    let followee_neuron_id = NeuronId::new_test_neuron_id(1);
+
+    let mut canister_fixture = GovernanceCanisterFixtureBuilder::new()
+        .add_neuron(NeuronBuilder::new(
+            neuron_id.clone(),
+            E8,
+            NeuronPermission::all(&user_principal),
+        ))
+        .create();
+
+    let max_followees_per_function = canister_fixture
+        .get_nervous_system_parameters()
+        .max_followees_per_function
+        .unwrap();
+
+    let followees_for_topic =
+        |topic: Option<i32>, followees: Vec<NeuronId>| FolloweesForTopic { followees, topic };
+
+    for (topic_following, caller, expected_error_type) in [
+        (
+            vec![followees_for_topic(None, vec![followee_neuron_id.clone()])],
+            user_principal,
+            ErrorType::InvalidCommand,
+        ),
+        (
+            vec![followees_for_topic(
+                Some(Topic::Unspecified as i32),
+                vec![followee_neuron_id.clone()],
+            )],
+            user_principal,
+            ErrorType::InvalidCommand,
+        ),
+        (
+            vec![followees_for_topic(
+                Some(1000),
+                vec![followee_neuron_id.clone()],
+            )],
+            user_principal,
+            ErrorType::InvalidCommand,
+        ),
+        (
+            vec![
+                followees_for_topic(
+                    Some(Topic::Governance as i32),
+                    vec![followee_neuron_id.clone()],
+                ),
+                followees_for_topic(Some(Topic::Governance as i32), vec![]),
+            ],
+            user_principal,
+            ErrorType::InvalidCommand,
+        ),
+        (
+            vec![followees_for_topic(
+                Some(Topic::Governance as i32),
+                (0..=max_followees_per_function)
+                    .map(NeuronId::new_test_neuron_id)
+                    .collect(),
+            )],
+            user_principal,
+            ErrorType::InvalidCommand,
+        ),
+        (
+            vec![followees_for_topic(
+                Some(Topic::Governance as i32),
+                vec![followee_neuron_id.clone()],
+            )],
+            PrincipalId::new_user_test_id(1001),
+            ErrorType::NotAuthorized,
+        ),
+    ] {
+        let error = canister_fixture
+            .set_following(&neuron_id, topic_following.clone(), caller)
+            .unwrap_err();
+        assert_eq!(
+            error.error_type, expected_error_type as i32,
+            "{:?}: {:?}",
+            topic_following, error
+        );
+    }
+
+    // None of the failed attempts changed the neuron's following.
+    let neuron = canister_fixture.get_neuron(&neuron_id);
+    assert_eq!(neuron.topic_followees, btreemap! {});
+}
+
+#[test]
+fn test_list_topics_groups_functions_by_topic() {
+    let mut canister_fixture = GovernanceCanisterFixtureBuilder::new().create();
+
+    let generic_function = |id: u64, topic: Option<Topic>| NervousSystemFunction {
+        id,
+        name: format!("Function {}", id),
+        description: None,
+        function_type: Some(FunctionType::GenericNervousSystemFunction(
+            GenericNervousSystemFunction {
+                target_canister_id: Some(CanisterId::from_u64(id).get()),
+                target_method_name: Some("method".to_string()),
+                validator_canister_id: Some(CanisterId::from_u64(id).get()),
+                validator_method_name: Some("validate_method".to_string()),
+                topic: topic.map(|topic| topic as i32),
+            },
+        )),
+    };
+    let categorized_function = generic_function(1000, Some(Topic::ApplicationBusinessLogic));
+    let uncategorized_function = generic_function(1001, None);
+    canister_fixture
+        .governance
+        .proto
+        .id_to_nervous_system_functions
+        .extend([
+            (categorized_function.id, categorized_function.clone()),
+            (uncategorized_function.id, uncategorized_function.clone()),
+        ]);
+
+    let ListTopicsResponse {
+        topics,
+        uncategorized_functions,
+    } = canister_fixture.governance.list_topics();
+
+    assert_eq!(uncategorized_functions, vec![uncategorized_function]);
+    assert_eq!(
+        topics
+            .iter()
+            .map(|topic_info| topic_info.topic.unwrap())
+            .collect::<Vec<_>>(),
+        Topic::iter()
+            .filter(|topic| *topic != Topic::Unspecified)
+            .map(|topic| topic as i32)
+            .collect::<Vec<_>>()
+    );
+
+    // Every native function (except for the pseudo function Unspecified and
+    // ExecuteGenericNervousSystemFunction) belongs to exactly one topic.
+    let mut native_function_ids = topics
+        .iter()
+        .flat_map(|topic_info| topic_info.native_functions.iter().map(|f| f.id))
+        .collect::<Vec<_>>();
+    native_function_ids.sort();
+    let mut expected_native_function_ids = Action::native_function_ids()
+        .into_iter()
+        .filter(|id| {
+            ![
+                native_action_ids::UNSPECIFIED,
+                native_action_ids::EXECUTE_GENERIC_NERVOUS_SYSTEM_FUNCTION,
+            ]
+            .contains(id)
+        })
+        .collect::<Vec<_>>();
+    expected_native_function_ids.sort();
+    assert_eq!(native_function_ids, expected_native_function_ids);
+
+    for topic_info in topics {
+        let topic = Topic::try_from(topic_info.topic.unwrap()).unwrap();
+        assert_eq!(topic_info.name, Some(topic.name().to_string()));
+        let expected_custom_functions = if topic == Topic::ApplicationBusinessLogic {
+            vec![categorized_function.clone()]
+        } else {
+            vec![]
+        };
+        assert_eq!(topic_info.custom_functions, expected_custom_functions);
+    }
+}
+
 // Same as the previous test, but wait_for_quiet_state is None.
 #[test]
 fn test_register_vote_happy_no_wait_for_quiet() {
@@ -167,6 +167,7 @@ fn generate_generic_nervous_system_functions(
                     target_method_name: Some("test_method".to_string()),
                     validator_canister_id: Some(CanisterId::from_u64(id).get()),
                     validator_method_name: Some("test_validator_method".to_string()),
+                    topic: None,
                 },
             )),
             ..Default::default()
@@ -18,7 +18,7 @@ use ic_nervous_system_common::{
 };
 use ic_nervous_system_runtime::DfnRuntime;
 use ic_sns_swap::{
-    clients::RealSnsRootClient,
+    clients::{RealIcrc2LedgerClient, RealSnsRootClient},
     logs::{ERROR, INFO},
     memory::UPGRADES_MEMORY,
     pb::v1::{
@@ -32,9 +32,9 @@ use ic_sns_swap::{
         ListCommunityFundParticipantsResponse, ListDirectParticipantsRequest,
         ListDirectParticipantsResponse, ListSnsNeuronRecipesRequest, ListSnsNeuronRecipesResponse,
         NewSaleTicketRequest, NewSaleTicketResponse, NotifyPaymentFailureRequest,
-        NotifyPaymentFailureResponse, OpenRequest, OpenResponse, RefreshBuyerTokensRequest,
-        RefreshBuyerTokensResponse, RestoreDappControllersRequest, RestoreDappControllersResponse,
-        Swap,
+        NotifyPaymentFailureResponse, OpenRequest, OpenResponse, ParticipateWithApprovalRequest,
+        ParticipateWithApprovalResponse, RefreshBuyerTokensRequest, RefreshBuyerTokensResponse,
+        RestoreDappControllersRequest, RestoreDappControllersResponse, Swap,
     },
 };
 use ic_stable_structures::{writer::Writer, Memory};
@@ -182,6 +182,30 @@ async fn refresh_buyer_tokens_(arg: RefreshBuyerTokensRequest) -> RefreshBuyerTo
     }
 }
 
+/// See `Swap.participate_with_approval`.
+#[export_name = "canister_update participate_with_approval"]
+fn participate_with_approval() {
+    over_async(candid_one, participate_with_approval_)
+}
+
+/// See `Swap.participate_with_approval`.
+#[candid_method(update, rename = "participate_with_approval")]
+async fn participate_with_approval_(
+    request: ParticipateWithApprovalRequest,
+) -> ParticipateWithApprovalResponse {
+    log!(INFO, "participate_with_approval");
+    let mut icp_ledger = RealIcrc2LedgerClient::new(swap().init_or_panic().icp_ledger_or_panic());
+    swap_mut()
+        .participate_with_approval(
+            caller(),
+            &request,
+            id(),
+            dfn_core::api::time_nanos(),
+            &mut icp_ledger,
+        )
+        .await
+}
+
 fn now_fn(_: bool) -> u64 {
     now_seconds()
 }
@@ -539,6 +563,11 @@ fn canister_post_upgrade() {
             err
         )
     });
+
+    // The canister is stopped before upgrades, so no participate_with_approval call can be
+    // in progress. Entries left behind by calls that trapped are released here rather than
+    // waiting for them to expire (see `Swap::purge_expired_pending_participations`).
+    swap_mut().pending_participations.clear();
 }
 
 /// Resources to serve for a given http_request
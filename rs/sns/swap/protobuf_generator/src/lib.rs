@@ -74,6 +74,7 @@ pub fn generate_prost_files(proto: ProtoPaths<'_>, out: &Path) {
         "Swap.purge_old_tickets_next_principal",
         "ICRC1Account.subaccount",
         "NewSaleTicketRequest.subaccount",
+        "ParticipateWithApprovalRequest.from_subaccount",
     ];
     for field in option_blob_fields {
         config.field_attribute(
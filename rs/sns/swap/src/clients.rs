@@ -3,11 +3,16 @@ use crate::pb::v1::{
     SettleNeuronsFundParticipationRequest, SettleNeuronsFundParticipationResponse,
 };
 use async_trait::async_trait;
+use candid::Nat;
 use ic_base_types::CanisterId;
 use ic_sns_governance::pb::v1::{
     ClaimSwapNeuronsRequest, ClaimSwapNeuronsResponse, ManageNeuron, ManageNeuronResponse, SetMode,
     SetModeResponse,
 };
+use icrc_ledger_types::{
+    icrc1::transfer::{TransferArg, TransferError},
+    icrc2::transfer_from::{TransferFromArgs, TransferFromError},
+};
 
 #[async_trait]
 pub trait SnsRootClient {
@@ -148,3 +153,57 @@ impl NnsGovernanceClient for RealNnsGovernanceClient {
         .map_err(CanisterCallError::from)
     }
 }
+
+#[async_trait]
+pub trait Icrc2LedgerClient {
+    async fn icrc1_transfer(
+        &mut self,
+        request: TransferArg,
+    ) -> Result<Result<Nat, TransferError>, CanisterCallError>;
+
+    async fn icrc2_transfer_from(
+        &mut self,
+        request: TransferFromArgs,
+    ) -> Result<Result<Nat, TransferFromError>, CanisterCallError>;
+}
+
+pub struct RealIcrc2LedgerClient {
+    canister_id: CanisterId,
+}
+
+impl RealIcrc2LedgerClient {
+    pub fn new(canister_id: CanisterId) -> Self {
+        Self { canister_id }
+    }
+}
+
+#[async_trait]
+impl Icrc2LedgerClient for RealIcrc2LedgerClient {
+    async fn icrc1_transfer(
+        &mut self,
+        request: TransferArg,
+    ) -> Result<Result<Nat, TransferError>, CanisterCallError> {
+        dfn_core::api::call(
+            self.canister_id,
+            "icrc1_transfer",
+            dfn_candid::candid_one,
+            request,
+        )
+        .await
+        .map_err(CanisterCallError::from)
+    }
+
+    async fn icrc2_transfer_from(
+        &mut self,
+        request: TransferFromArgs,
+    ) -> Result<Result<Nat, TransferFromError>, CanisterCallError> {
+        dfn_core::api::call(
+            self.canister_id,
+            "icrc2_transfer_from",
+            dfn_candid::candid_one,
+            request,
+        )
+        .await
+        .map_err(CanisterCallError::from)
+    }
+}
@@ -225,6 +225,19 @@ pub struct Swap {
     /// Amount of contributions from the Neurons' Fund committed to this SNS so far.
     #[prost(uint64, optional, tag = "20")]
     pub neurons_fund_participation_icp_e8s: ::core::option::Option<u64>,
+    /// Participations made via participate_with_approval whose
+    /// icrc2_transfer_from call is still outstanding. The key is the textual
+    /// representation of the buyer's principal. The reserved amounts are no
+    /// longer available to other participants. Each buyer can have at most one
+    /// such participation at a time.
+    ///
+    /// Entries are removed as soon as the call returns. An entry that is left
+    /// behind because the callback trapped is removed once it is older than
+    /// the transaction window of the ICP ledger (see
+    /// `PENDING_PARTICIPATION_EXPIRY_SECONDS`).
+    #[prost(btree_map = "string, message", tag = "22")]
+    pub pending_participations:
+        ::prost::alloc::collections::BTreeMap<::prost::alloc::string::String, PendingParticipation>,
 }
 /// The initialisation data of the canister. Always specified on
 /// canister creation, and cannot be modified afterwards.
@@ -2053,6 +2066,178 @@ pub mod new_sale_ticket_response {
         Err(Err),
     }
 }
+/// A participation made via participate_with_approval whose
+/// icrc2_transfer_from call is still outstanding.
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PendingParticipation {
+    /// The amount reserved for the buyer. Set to 0 once the ICP ledger has
+    /// responded, while any refund is in progress.
+    #[prost(uint64, tag = "1")]
+    pub amount_icp_e8s: u64,
+    /// The `created_at_time` of the icrc2_transfer_from call, in nanoseconds
+    /// since the UNIX epoch.
+    #[prost(uint64, tag = "2")]
+    pub created_at_time_nanos: u64,
+}
+/// Request struct for the method `participate_with_approval`.
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ParticipateWithApprovalRequest {
+    /// The amount of ICP e8s by which the caller wants to increase their
+    /// participation. Before calling this method, the caller must approve
+    /// (via `icrc2_approve` on the ICP ledger) the swap canister to transfer
+    /// this amount plus the ledger's transfer fee.
+    #[prost(uint64, tag = "1")]
+    pub amount_icp_e8s: u64,
+    /// The subaccount of the caller from which the ICP is transferred. If not
+    /// set, the default subaccount is used.
+    #[prost(bytes = "vec", optional, tag = "2")]
+    #[serde(deserialize_with = "ic_utils::deserialize::deserialize_option_blob")]
+    pub from_subaccount: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    /// Must match the confirmation text set during SNS initialization, if any.
+    #[prost(string, optional, tag = "3")]
+    pub confirmation_text: ::core::option::Option<::prost::alloc::string::String>,
+    /// The ISO 3166-1 alpha-2 code of the country of the caller. Required if the
+    /// swap restricts participation from some countries.
+    #[prost(string, optional, tag = "4")]
+    pub country_code: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Response struct for the method `participate_with_approval`
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ParticipateWithApprovalResponse {
+    #[prost(oneof = "participate_with_approval_response::Result", tags = "1, 2")]
+    pub result: ::core::option::Option<participate_with_approval_response::Result>,
+}
+/// Nested message and enum types in `ParticipateWithApprovalResponse`.
+pub mod participate_with_approval_response {
+    /// Request was completed successfully.
+    #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Ok {
+        /// The total participation of the caller after this request.
+        #[prost(uint64, tag = "1")]
+        pub icp_accepted_participation_e8s: u64,
+        /// The ICP ledger block in which the participation was transferred.
+        #[prost(uint64, tag = "2")]
+        pub transfer_block_index: u64,
+    }
+    /// Request was not successful.
+    #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Err {
+        #[prost(enumeration = "err::Type", tag = "1")]
+        pub error_type: i32,
+        /// When `error_type` is `INVALID_USER_AMOUNT` then this field
+        /// describes the minimum and maximum amounts.
+        #[prost(message, optional, tag = "2")]
+        pub invalid_user_amount:
+            ::core::option::Option<super::new_sale_ticket_response::err::InvalidUserAmount>,
+        /// A human-readable description of the error.
+        #[prost(string, optional, tag = "3")]
+        pub description: ::core::option::Option<::prost::alloc::string::String>,
+    }
+    /// Nested message and enum types in `Err`.
+    pub mod err {
+        #[derive(
+            candid::CandidType,
+            candid::Deserialize,
+            serde::Serialize,
+            comparable::Comparable,
+            Clone,
+            Copy,
+            Debug,
+            PartialEq,
+            Eq,
+            Hash,
+            PartialOrd,
+            Ord,
+            ::prost::Enumeration,
+        )]
+        #[repr(i32)]
+        pub enum Type {
+            Unspecified = 0,
+            SaleNotOpen = 1,
+            SaleClosed = 2,
+            /// The amount requested by the user is not within the Swap parameters.
+            ///
+            /// When this is the `error_type`, then the field invalid_user_amount
+            /// is set and describes minimum and maximum amounts.
+            InvalidUserAmount = 3,
+            /// The specified subaccount is not a valid subaccount
+            /// (length != 32 bytes).
+            InvalidSubaccount = 4,
+            /// The specified principal is forbidden from participating.
+            InvalidPrincipal = 5,
+            /// The confirmation text does not match the one set during SNS
+            /// initialization.
+            InvalidConfirmationText = 6,
+            /// Participation from the country of the caller is restricted, or the
+            /// country was not specified even though the swap restricts some
+            /// countries.
+            RestrictedCountry = 7,
+            /// The ICP could not be transferred from the caller, e.g., because the
+            /// allowance or the balance was insufficient. No ICP was transferred.
+            TransferFromFailed = 8,
+            /// The ICP was transferred, but could not be counted towards the
+            /// caller's participation, because the swap state changed in the
+            /// meantime. The ICP can be reclaimed via `error_refund_icp` once the
+            /// swap is over.
+            ParticipationNotAccepted = 9,
+        }
+        impl Type {
+            /// String value of the enum field names used in the ProtoBuf definition.
+            ///
+            /// The values are not transformed in any way and thus are considered stable
+            /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+            pub fn as_str_name(&self) -> &'static str {
+                match self {
+                    Type::Unspecified => "TYPE_UNSPECIFIED",
+                    Type::SaleNotOpen => "TYPE_SALE_NOT_OPEN",
+                    Type::SaleClosed => "TYPE_SALE_CLOSED",
+                    Type::InvalidUserAmount => "TYPE_INVALID_USER_AMOUNT",
+                    Type::InvalidSubaccount => "TYPE_INVALID_SUBACCOUNT",
+                    Type::InvalidPrincipal => "TYPE_INVALID_PRINCIPAL",
+                    Type::InvalidConfirmationText => "TYPE_INVALID_CONFIRMATION_TEXT",
+                    Type::RestrictedCountry => "TYPE_RESTRICTED_COUNTRY",
+                    Type::TransferFromFailed => "TYPE_TRANSFER_FROM_FAILED",
+                    Type::ParticipationNotAccepted => "TYPE_PARTICIPATION_NOT_ACCEPTED",
+                }
+            }
+            /// Creates an enum from field names used in the ProtoBuf definition.
+            pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+                match value {
+                    "TYPE_UNSPECIFIED" => Some(Self::Unspecified),
+                    "TYPE_SALE_NOT_OPEN" => Some(Self::SaleNotOpen),
+                    "TYPE_SALE_CLOSED" => Some(Self::SaleClosed),
+                    "TYPE_INVALID_USER_AMOUNT" => Some(Self::InvalidUserAmount),
+                    "TYPE_INVALID_SUBACCOUNT" => Some(Self::InvalidSubaccount),
+                    "TYPE_INVALID_PRINCIPAL" => Some(Self::InvalidPrincipal),
+                    "TYPE_INVALID_CONFIRMATION_TEXT" => Some(Self::InvalidConfirmationText),
+                    "TYPE_RESTRICTED_COUNTRY" => Some(Self::RestrictedCountry),
+                    "TYPE_TRANSFER_FROM_FAILED" => Some(Self::TransferFromFailed),
+                    "TYPE_PARTICIPATION_NOT_ACCEPTED" => Some(Self::ParticipationNotAccepted),
+                    _ => None,
+                }
+            }
+        }
+    }
+    #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Result {
+        #[prost(message, tag = "1")]
+        Ok(Ok),
+        #[prost(message, tag = "2")]
+        Err(Err),
+    }
+}
 /// Request struct for the method `list_direct_participants`. This method
 /// paginates over all direct participants in the decentralization swap.
 /// Direct participants are participants who did not participate via the
@@ -1,12 +1,12 @@
 use crate::pb::v1::Params;
 use crate::{
-    clients::{NnsGovernanceClient, SnsGovernanceClient, SnsRootClient},
+    clients::{Icrc2LedgerClient, NnsGovernanceClient, SnsGovernanceClient, SnsRootClient},
     environment::CanisterEnvironment,
     logs::{ERROR, INFO},
     memory,
     pb::v1::{
-        get_open_ticket_response, new_sale_ticket_response, restore_dapp_controllers_response,
-        set_dapp_controllers_call_result, set_mode_call_result,
+        get_open_ticket_response, new_sale_ticket_response, participate_with_approval_response,
+        restore_dapp_controllers_response, set_dapp_controllers_call_result, set_mode_call_result,
         set_mode_call_result::SetModeResult,
         settle_neurons_fund_participation_request, settle_neurons_fund_participation_response,
         sns_neuron_recipe::{ClaimedStatus, Investor, NeuronAttributes},
@@ -21,7 +21,8 @@ use crate::{
         ListDirectParticipantsResponse, ListSnsNeuronRecipesRequest, ListSnsNeuronRecipesResponse,
         NeuronBasketConstructionParameters, NeuronId as SaleNeuronId, NewSaleTicketRequest,
         NewSaleTicketResponse, NotifyPaymentFailureResponse, OpenRequest, OpenResponse,
-        Participant, RefreshBuyerTokensResponse, RestoreDappControllersResponse,
+        Participant, ParticipateWithApprovalRequest, ParticipateWithApprovalResponse,
+        PendingParticipation, RefreshBuyerTokensResponse, RestoreDappControllersResponse,
         SetDappControllersCallResult, SetDappControllersRequest, SetDappControllersResponse,
         SetModeCallResult, SettleNeuronsFundParticipationRequest,
         SettleNeuronsFundParticipationResponse, SettleNeuronsFundParticipationResult,
@@ -29,6 +30,7 @@ use crate::{
     },
     types::{NeuronsFundNeuron, ScheduledVestingEvent, TransferResult},
 };
+use candid::Nat;
 use dfn_core::CanisterId;
 use ic_base_types::PrincipalId;
 use ic_canister_log::log;
@@ -45,7 +47,13 @@ use ic_sns_governance::pb::v1::{
 use ic_stable_structures::storable::Bound;
 use ic_stable_structures::{storable::Blob, GrowFailed, Storable};
 use icp_ledger::DEFAULT_TRANSFER_FEE;
-use icrc_ledger_types::icrc1::account::{Account, Subaccount};
+use icrc_ledger_types::{
+    icrc1::{
+        account::{Account, Subaccount},
+        transfer::TransferArg,
+    },
+    icrc2::transfer_from::{TransferFromArgs, TransferFromError},
+};
 use itertools::{Either, Itertools};
 use maplit::btreemap;
 use prost::Message;
@@ -98,6 +106,14 @@ pub const FIRST_PRINCIPAL_BYTES: [u8; PrincipalId::MAX_LENGTH_IN_BYTES] =
 /// 2. Avoid having the SNS Governance canister hit the instruction limit per message.
 pub const CLAIM_SWAP_NEURONS_BATCH_SIZE: usize = 500;
 
+/// The age after which an entry of `Swap.pending_participations` is removed. The ICP ledger
+/// rejects transactions whose `created_at_time` is older than its transaction window of one
+/// day, so by then the `icrc2_transfer_from` call of the participation has completed, and the
+/// entry was only left behind because the callback trapped. Any ICP that was transferred is in
+/// the buyer's subaccount and can be accounted for with `refresh_buyer_tokens`. The extra hour
+/// accounts for clock drift.
+pub const PENDING_PARTICIPATION_EXPIRY_SECONDS: u64 = 25 * 60 * 60;
+
 impl From<(Option<i32>, String)> for CanisterCallError {
     fn from((code, description): (Option<i32>, String)) -> Self {
         Self { code, description }
@@ -338,6 +354,7 @@ mod swap_participation {
         logs::ERROR,
         swap::{Lifecycle, Swap},
     };
+    use ic_base_types::PrincipalId;
     use ic_canister_log::log;
 
     impl Swap {
@@ -353,6 +370,20 @@ mod swap_participation {
             }
         }
 
+        /// Participations via `participate_with_approval` transfer ICP to the buyer's
+        /// subaccount, which must not be accounted for by another call while the
+        /// transfer is still in progress.
+        pub fn validate_no_pending_participation(&self, buyer: &PrincipalId) -> Result<(), String> {
+            if self.pending_participations.contains_key(&buyer.to_string()) {
+                Err(format!(
+                    "A participation of {} via participate_with_approval is in progress.",
+                    buyer
+                ))
+            } else {
+                Ok(())
+            }
+        }
+
         pub fn validate_lifecycle_is_open(&self) -> Result<(), String> {
             let lifecycle: Lifecycle = self.lifecycle();
             if lifecycle == Lifecycle::Open {
@@ -393,6 +424,34 @@ mod swap_participation {
                 (None, None) => Ok(()),
             }
         }
+
+        /// Validate the (self-declared) country of the caller who wishes to participate in the
+        /// swap against the countries restricted in the SnsInitPayload structure. If some
+        /// countries are restricted, the caller must declare their country.
+        pub fn validate_country_code(&self, country_code: Option<&str>) -> Result<(), String> {
+            let restricted_countries = match self.init_or_panic().restricted_countries.as_ref() {
+                Some(countries) if !countries.iso_codes.is_empty() => countries,
+                _ => return Ok(()),
+            };
+            let Some(country_code) = country_code else {
+                return Err(
+                    "This swap restricts participation from some countries, but no value \
+                    was provided for `country_code`."
+                        .to_string(),
+                );
+            };
+            if restricted_countries
+                .iso_codes
+                .iter()
+                .any(|iso_code| iso_code.eq_ignore_ascii_case(country_code))
+            {
+                return Err(format!(
+                    "Participation from country {} is restricted in this swap.",
+                    country_code
+                ));
+            }
+            Ok(())
+        }
     }
 
     pub fn context_before_awaiting_icp_ledger_response(err: String) -> String {
@@ -431,6 +490,7 @@ impl Swap {
             auto_finalize_swap_response: None,
             direct_participation_icp_e8s: None,
             neurons_fund_participation_icp_e8s: None,
+            pending_participations: Default::default(), // Btree map
         };
         if init.validate_swap_init_for_one_proposal_flow().is_ok() {
             // Automatically fill out the fields that the (legacy) open request
@@ -529,10 +589,47 @@ impl Swap {
             .expect("Expected params.max_direct_participation_icp_e8s to be set")
     }
 
-    /// The amount of ICP e8s currently available for direct participation.
+    /// The amount of ICP e8s reserved for participations whose ICP transfer is in progress
+    /// (see `participate_with_approval`).
+    pub fn pending_direct_participation_e8s(&self) -> u64 {
+        self.pending_participations
+            .values()
+            .fold(0_u64, |sum, pending_participation| {
+                sum.saturating_add(pending_participation.amount_icp_e8s)
+            })
+    }
+
+    /// Removes the entries of `pending_participations` that are older than
+    /// `PENDING_PARTICIPATION_EXPIRY_SECONDS`. Returns the number of removed entries.
+    pub fn purge_expired_pending_participations(&mut self, now_nanos: u64) -> usize {
+        let expiry_nanos = PENDING_PARTICIPATION_EXPIRY_SECONDS.saturating_mul(1_000_000_000);
+        let num_pending_participations = self.pending_participations.len();
+        self.pending_participations
+            .retain(|buyer, pending_participation| {
+                let age_nanos =
+                    now_nanos.saturating_sub(pending_participation.created_at_time_nanos);
+                if age_nanos < expiry_nanos {
+                    return true;
+                }
+                log!(
+                    ERROR,
+                    "Removing the pending participation of {} ICP e8s by {} created at {} ns, \
+                     as its call to the ICP ledger did not complete.",
+                    pending_participation.amount_icp_e8s,
+                    buyer,
+                    pending_participation.created_at_time_nanos,
+                );
+                false
+            });
+        num_pending_participations - self.pending_participations.len()
+    }
+
+    /// The amount of ICP e8s currently available for direct participation, i.e., not yet
+    /// committed to or reserved for participants.
     pub fn available_direct_participation_e8s(&self) -> u64 {
         let max_direct_participation_e8s = self.max_direct_participation_e8s();
         let current_direct_participation_e8s = self.current_direct_participation_e8s();
+        let pending_direct_participation_e8s = self.pending_direct_participation_e8s();
         max_direct_participation_e8s
             .checked_sub(current_direct_participation_e8s)
             .unwrap_or_else(|| {
@@ -543,6 +640,8 @@ impl Swap {
                 );
                 0
             })
+            // Reservations never exceed the available amount when they are made.
+            .saturating_sub(pending_direct_participation_e8s)
     }
 
     /// Update derived fields:
@@ -1031,6 +1130,11 @@ impl Swap {
             MAX_NUMBER_OF_PRINCIPALS_TO_INSPECT,
         );
 
+        // Release the reservations of participate_with_approval calls whose callback trapped.
+        self.purge_expired_pending_participations(
+            heartbeat_start_seconds.saturating_mul(1_000_000_000),
+        );
+
         // Automatically transition the state. Only one state transition per heartbeat.
 
         // Auto-open the swap
@@ -1138,11 +1242,13 @@ impl Swap {
     ) -> Result<RefreshBuyerTokensResponse, String> {
         use swap_participation::*;
 
-        // These two checks need to be repeated after awaiting the response from the ICP ledger.
+        // These checks need to be repeated after awaiting the response from the ICP ledger.
         self.validate_lifecycle_is_open()
             .map_err(context_before_awaiting_icp_ledger_response)?;
         self.validate_possibility_of_direct_participation()
             .map_err(context_before_awaiting_icp_ledger_response)?;
+        self.validate_no_pending_participation(&buyer)
+            .map_err(context_before_awaiting_icp_ledger_response)?;
 
         // User input validation doesn't expire after await, so this check doesn't need repetition.
         self.validate_confirmation_text(confirmation_text)?;
@@ -1167,6 +1273,8 @@ impl Swap {
             .map_err(context_after_awaiting_icp_ledger_response)?;
         self.validate_possibility_of_direct_participation()
             .map_err(context_after_awaiting_icp_ledger_response)?;
+        self.validate_no_pending_participation(&buyer)
+            .map_err(context_after_awaiting_icp_ledger_response)?;
 
         // Once swap is OPEN, the Swap.params field is set. In light of validation performed
         // above, we should be able to `expect` this value without a panic.
@@ -1248,25 +1356,7 @@ impl Swap {
             // If there exists no ticket for the buyer, the payment flow will simply ignore the ticket
         }
 
-        // Append to a new buyer to the BUYERS_LIST_INDEX
-        let is_preexisting_buyer = self.buyers.contains_key(&buyer.to_string());
-        if !is_preexisting_buyer {
-            insert_buyer_into_buyers_list_index(buyer)
-                .map_err(|grow_failed| {
-                    format!(
-                        "Failed to add buyer {} to state, the canister's stable memory could not grow: {}",
-                        buyer, grow_failed
-                    )
-                })?;
-        }
-
-        self.buyers
-            .entry(buyer.to_string())
-            .or_insert_with(|| BuyerState::new(0))
-            .set_amount_icp_e8s(new_balance_e8s);
-        // We compute the current participation amounts once and store the result in Swap's state,
-        // for efficiency reasons.
-        self.update_total_participation_amounts();
+        self.set_buyer_participation_e8s(buyer, new_balance_e8s)?;
 
         log!(
             INFO,
@@ -1289,6 +1379,409 @@ impl Swap {
         })
     }
 
+    /// Increases the participation of `buyer` by (at most) `request.amount_icp_e8s`, pulling
+    /// the ICP from the buyer's account via `icrc2_transfer_from`. Before calling this, the
+    /// buyer must have approved the swap canister to transfer that amount plus the ICP
+    /// ledger's transfer fee.
+    ///
+    /// All participation constraints (lifecycle, minimum and maximum participation,
+    /// confirmation text, and restricted countries) are checked before any ICP is
+    /// transferred, so an invalid request never moves funds. The ICP is transferred to the
+    /// buyer's subaccount of the swap canister, i.e., the same account that
+    /// `refresh_buyer_token_e8s` inspects, so the usual sweeping and refunding of
+    /// participations applies to it.
+    ///
+    /// The amount is reserved while the call to the ICP ledger is outstanding, so concurrent
+    /// participations cannot exceed the maximum direct participation, and a buyer can only
+    /// have one such call in progress at a time. Any transferred ICP that cannot be
+    /// accepted once the ICP ledger has responded (e.g., because the swap was closed in the
+    /// meantime) is refunded right away.
+    ///
+    /// `now_nanos` is used as the `created_at_time` of the ledger transactions, so that the
+    /// ICP ledger deduplicates them.
+    pub async fn participate_with_approval(
+        &mut self,
+        buyer: PrincipalId,
+        request: &ParticipateWithApprovalRequest,
+        this_canister: CanisterId,
+        now_nanos: u64,
+        icp_ledger: &mut impl Icrc2LedgerClient,
+    ) -> ParticipateWithApprovalResponse {
+        let (from, amount_icp_e8s) =
+            match self.begin_participation_with_approval(buyer, request, now_nanos) {
+                Ok(participation) => participation,
+                Err(response) => return response,
+            };
+
+        let transfer_from_args = TransferFromArgs {
+            spender_subaccount: None,
+            from,
+            to: Account {
+                owner: this_canister.get().0,
+                subaccount: Some(principal_to_subaccount(&buyer)),
+            },
+            amount: Nat::from(amount_icp_e8s),
+            fee: None,
+            memo: None,
+            created_at_time: Some(now_nanos),
+        };
+        let transfer_from_result = icp_ledger.icrc2_transfer_from(transfer_from_args).await;
+
+        self.complete_participation_with_approval(
+            buyer,
+            from,
+            amount_icp_e8s,
+            transfer_from_result,
+            now_nanos,
+            icp_ledger,
+        )
+        .await
+    }
+
+    /// The part of `participate_with_approval` that runs before calling the ICP ledger.
+    ///
+    /// Validates the request and reserves the amount to be transferred for `buyer`. Returns
+    /// the account to transfer the ICP from and the reserved amount. The reservation must
+    /// be released by calling `complete_participation_with_approval`. If that never happens
+    /// because the callback traps, it expires after `PENDING_PARTICIPATION_EXPIRY_SECONDS`.
+    fn begin_participation_with_approval(
+        &mut self,
+        buyer: PrincipalId,
+        request: &ParticipateWithApprovalRequest,
+        now_nanos: u64,
+    ) -> Result<(Account, u64), ParticipateWithApprovalResponse> {
+        // Return an error if we are not in Lifecycle::Open.
+        if self.lifecycle().is_before_open() {
+            return Err(ParticipateWithApprovalResponse::err_sale_not_open());
+        }
+        if self.lifecycle().is_after_open() {
+            return Err(ParticipateWithApprovalResponse::err_sale_closed());
+        }
+        if self.lifecycle() != Lifecycle::Open {
+            // It must be that we are in Lifecycle::Unspecified, but this also
+            // accounts for cases that might have been overlooked.
+            log!(
+                ERROR,
+                "We are not in Lifecycle::Open. Swap:\n{:#?}",
+                SwapDigest::new(self),
+            );
+            return Err(ParticipateWithApprovalResponse::err_sale_not_open());
+        }
+
+        if buyer.is_anonymous() {
+            return Err(ParticipateWithApprovalResponse::err_invalid_principal());
+        }
+
+        // subaccounts must be 32 bytes
+        let from_subaccount = match request
+            .from_subaccount
+            .as_ref()
+            .map(|subaccount| Subaccount::try_from(subaccount.as_slice()))
+            .transpose()
+        {
+            Ok(from_subaccount) => from_subaccount,
+            Err(_) => return Err(ParticipateWithApprovalResponse::err_invalid_subaccount()),
+        };
+
+        if let Err(err) = self.validate_confirmation_text(request.confirmation_text.clone()) {
+            return Err(ParticipateWithApprovalResponse::err_invalid_confirmation_text(err));
+        }
+        if let Err(err) = self.validate_country_code(request.country_code.as_deref()) {
+            return Err(ParticipateWithApprovalResponse::err_restricted_country(err));
+        }
+
+        self.purge_expired_pending_participations(now_nanos);
+        if self.pending_participations.contains_key(&buyer.to_string()) {
+            return Err(
+                ParticipateWithApprovalResponse::err_participation_not_accepted(format!(
+                    "Another participation of {} is already in progress.",
+                    buyer
+                )),
+            );
+        }
+
+        let params = self
+            .params
+            .as_ref()
+            .expect("Expected params to be set because lifecycle is OPEN");
+        let old_balance_e8s = self
+            .buyers
+            .get(&buyer.to_string())
+            .map_or(0, |buyer_state| buyer_state.amount_icp_e8s());
+        let amount_icp_e8s = match compute_participation_increment(
+            self.current_direct_participation_e8s()
+                .saturating_add(self.pending_direct_participation_e8s()),
+            self.max_direct_participation_e8s(),
+            params.min_participant_icp_e8s,
+            params.max_participant_icp_e8s,
+            old_balance_e8s,
+            request.amount_icp_e8s,
+        ) {
+            Ok(amount_icp_e8s) => amount_icp_e8s,
+            Err((min, max)) => {
+                return Err(ParticipateWithApprovalResponse::err_invalid_user_amount(
+                    min, max,
+                ))
+            }
+        };
+
+        self.pending_participations.insert(
+            buyer.to_string(),
+            PendingParticipation {
+                amount_icp_e8s,
+                created_at_time_nanos: now_nanos,
+            },
+        );
+
+        let from = Account {
+            owner: buyer.0,
+            subaccount: from_subaccount,
+        };
+        Ok((from, amount_icp_e8s))
+    }
+
+    /// The part of `participate_with_approval` that runs after the ICP ledger has responded
+    /// to the `icrc2_transfer_from` call for the participation reserved by
+    /// `begin_participation_with_approval`.
+    ///
+    /// Releases the reservation, and if the transfer succeeded, accepts as much of
+    /// `amount_icp_e8s` as the current state of the swap allows. The rest is refunded to
+    /// `from` right away.
+    async fn complete_participation_with_approval(
+        &mut self,
+        buyer: PrincipalId,
+        from: Account,
+        amount_icp_e8s: u64,
+        transfer_from_result: Result<Result<Nat, TransferFromError>, CanisterCallError>,
+        now_nanos: u64,
+        icp_ledger: &mut impl Icrc2LedgerClient,
+    ) -> ParticipateWithApprovalResponse {
+        // The reserved amount is released, but the buyer stays locked until any refund is
+        // done, as the ICP to be refunded is in the buyer's subaccount in the meantime.
+        match self.pending_participations.get_mut(&buyer.to_string()) {
+            Some(pending_participation) => pending_participation.amount_icp_e8s = 0,
+            None => log!(
+                ERROR,
+                "No pending participation of {} found after awaiting ICP ledger response.",
+                buyer,
+            ),
+        }
+
+        let response = self
+            .accept_participation_with_approval(
+                buyer,
+                from,
+                amount_icp_e8s,
+                transfer_from_result,
+                now_nanos,
+                icp_ledger,
+            )
+            .await;
+
+        self.pending_participations.remove(&buyer.to_string());
+        response
+    }
+
+    /// See `complete_participation_with_approval`.
+    async fn accept_participation_with_approval(
+        &mut self,
+        buyer: PrincipalId,
+        from: Account,
+        amount_icp_e8s: u64,
+        transfer_from_result: Result<Result<Nat, TransferFromError>, CanisterCallError>,
+        now_nanos: u64,
+        icp_ledger: &mut impl Icrc2LedgerClient,
+    ) -> ParticipateWithApprovalResponse {
+        let transfer_block_index = match transfer_from_result {
+            Ok(Ok(block_index)) => u64::try_from(block_index.0).unwrap_or(u64::MAX),
+            Ok(Err(err)) => {
+                return ParticipateWithApprovalResponse::err_transfer_from_failed(format!(
+                    "icrc2_transfer_from of {} ICP e8s failed: {}",
+                    amount_icp_e8s, err
+                ))
+            }
+            Err(err) => {
+                // It is unknown whether the transfer happened. If it did, the ICP is in the
+                // buyer's subaccount and can be accounted for via refresh_buyer_tokens.
+                return ParticipateWithApprovalResponse::err_transfer_from_failed(format!(
+                    "Error calling icrc2_transfer_from of the ICP ledger: {:?}. If the transfer \
+                    nevertheless happened, call refresh_buyer_tokens to have it accepted.",
+                    err
+                ));
+            }
+        };
+
+        // The ICP is now in the buyer's subaccount. Whatever cannot be accepted is refunded.
+        let old_balance_e8s = self
+            .buyers
+            .get(&buyer.to_string())
+            .map_or(0, |buyer_state| buyer_state.amount_icp_e8s());
+        let accepted = self.accept_participation_increment(buyer, old_balance_e8s, amount_icp_e8s);
+        let accepted_increment_e8s = accepted
+            .as_ref()
+            .map_or(0, |new_balance_e8s| new_balance_e8s - old_balance_e8s);
+        let refund_e8s = amount_icp_e8s.saturating_sub(accepted_increment_e8s);
+        let refund_result = if refund_e8s > 0 {
+            self.refund_participation_excess(buyer, from, refund_e8s, now_nanos, icp_ledger)
+                .await
+        } else {
+            "nothing to refund".to_string()
+        };
+
+        let new_balance_e8s = match accepted {
+            Ok(new_balance_e8s) => new_balance_e8s,
+            Err(err) => {
+                return ParticipateWithApprovalResponse::err_participation_not_accepted(format!(
+                    "{} Refund of the transferred {} ICP e8s: {}",
+                    err, amount_icp_e8s, refund_result
+                ));
+            }
+        };
+
+        log!(
+            INFO,
+            "Participate_with_approval for buyer {}; old e8s {}; new e8s {}; block {}; \
+            refund: {}",
+            buyer,
+            old_balance_e8s,
+            new_balance_e8s,
+            transfer_block_index,
+            refund_result,
+        );
+
+        ParticipateWithApprovalResponse::ok(new_balance_e8s, transfer_block_index)
+    }
+
+    /// Increases the participation of `buyer` from `old_balance_e8s` by as much of
+    /// `increment_e8s` as the swap can accept after awaiting the ICP ledger response, which
+    /// might be less than `increment_e8s` (or nothing) if the state of the swap changed in
+    /// the meantime. Returns the new participation of `buyer`.
+    fn accept_participation_increment(
+        &mut self,
+        buyer: PrincipalId,
+        old_balance_e8s: u64,
+        increment_e8s: u64,
+    ) -> Result<u64, String> {
+        use swap_participation::*;
+
+        self.validate_lifecycle_is_open()
+            .map_err(context_after_awaiting_icp_ledger_response)?;
+        self.validate_possibility_of_direct_participation()
+            .map_err(context_after_awaiting_icp_ledger_response)?;
+
+        let params = self.params.as_ref().expect("Expected params to be set");
+        let min_participant_icp_e8s = params.min_participant_icp_e8s;
+        let max_participant_icp_e8s = params.max_participant_icp_e8s;
+        let new_balance_e8s = old_balance_e8s
+            .saturating_add(increment_e8s.min(self.available_direct_participation_e8s()))
+            .min(max_participant_icp_e8s);
+        if new_balance_e8s <= old_balance_e8s || new_balance_e8s < min_participant_icp_e8s {
+            return Err(format!(
+                "The participation of {} could not be increased from {} ICP e8s \
+                (after awaiting ICP ledger response).",
+                buyer, old_balance_e8s,
+            ));
+        }
+        self.set_buyer_participation_e8s(buyer, new_balance_e8s)?;
+
+        Ok(new_balance_e8s)
+    }
+
+    /// Transfers `refund_e8s` (minus the transfer fee) from the subaccount of `buyer` back
+    /// to `to`. Returns a description of the outcome. If the refund fails, the ICP stays in
+    /// the buyer's subaccount, and can be reclaimed via `error_refund_icp` once the swap is
+    /// over.
+    async fn refund_participation_excess(
+        &self,
+        buyer: PrincipalId,
+        to: Account,
+        refund_e8s: u64,
+        now_nanos: u64,
+        icp_ledger: &mut impl Icrc2LedgerClient,
+    ) -> String {
+        let fee_e8s = DEFAULT_TRANSFER_FEE.get_e8s();
+        if refund_e8s <= fee_e8s {
+            return format!(
+                "{} ICP e8s do not cover the transfer fee, so they were not refunded",
+                refund_e8s
+            );
+        }
+        let amount_e8s = refund_e8s - fee_e8s;
+        let transfer_arg = TransferArg {
+            from_subaccount: Some(principal_to_subaccount(&buyer)),
+            to,
+            fee: Some(Nat::from(fee_e8s)),
+            created_at_time: Some(now_nanos),
+            memo: None,
+            amount: Nat::from(amount_e8s),
+        };
+        match icp_ledger.icrc1_transfer(transfer_arg).await {
+            Ok(Ok(block_index)) => {
+                format!(
+                    "{} ICP e8s were refunded in block {}",
+                    amount_e8s, block_index
+                )
+            }
+            Ok(Err(err)) => {
+                log!(
+                    ERROR,
+                    "Failed to refund {} ICP e8s to {}: {}",
+                    amount_e8s,
+                    buyer,
+                    err,
+                );
+                format!(
+                    "refunding {} ICP e8s failed ({}); use error_refund_icp once the swap is over",
+                    amount_e8s, err
+                )
+            }
+            Err(err) => {
+                log!(
+                    ERROR,
+                    "Error calling icrc1_transfer to refund {} ICP e8s to {}: {:?}",
+                    amount_e8s,
+                    buyer,
+                    err,
+                );
+                format!(
+                    "calling icrc1_transfer to refund {} ICP e8s failed ({:?}); use \
+                    error_refund_icp once the swap is over",
+                    amount_e8s, err
+                )
+            }
+        }
+    }
+
+    /// Sets the participation of `buyer` to `new_balance_e8s`, adding `buyer` to the
+    /// buyers list index if they are a new participant.
+    fn set_buyer_participation_e8s(
+        &mut self,
+        buyer: PrincipalId,
+        new_balance_e8s: u64,
+    ) -> Result<(), String> {
+        // Append to a new buyer to the BUYERS_LIST_INDEX
+        let is_preexisting_buyer = self.buyers.contains_key(&buyer.to_string());
+        if !is_preexisting_buyer {
+            insert_buyer_into_buyers_list_index(buyer)
+                .map_err(|grow_failed| {
+                    format!(
+                        "Failed to add buyer {} to state, the canister's stable memory could not grow: {}",
+                        buyer, grow_failed
+                    )
+                })?;
+        }
+
+        self.buyers
+            .entry(buyer.to_string())
+            .or_insert_with(|| BuyerState::new(0))
+            .set_amount_icp_e8s(new_balance_e8s);
+        // We compute the current participation amounts once and store the result in Swap's state,
+        // for efficiency reasons.
+        self.update_total_participation_amounts();
+
+        Ok(())
+    }
+
     /*
 
     Transfers OUT.
@@ -3632,6 +4125,118 @@ impl NewSaleTicketResponse {
     }
 }
 
+impl ParticipateWithApprovalResponse {
+    pub fn ok(icp_accepted_participation_e8s: u64, transfer_block_index: u64) -> Self {
+        Self {
+            result: Some(participate_with_approval_response::Result::Ok(
+                participate_with_approval_response::Ok {
+                    icp_accepted_participation_e8s,
+                    transfer_block_index,
+                },
+            )),
+        }
+    }
+
+    pub fn err(err: participate_with_approval_response::Err) -> Self {
+        Self {
+            result: Some(participate_with_approval_response::Result::Err(err)),
+        }
+    }
+
+    fn err_with_type(
+        error_type: participate_with_approval_response::err::Type,
+        description: Option<String>,
+    ) -> Self {
+        Self::err(participate_with_approval_response::Err {
+            error_type: error_type as i32,
+            invalid_user_amount: None,
+            description,
+        })
+    }
+
+    pub fn err_sale_not_open() -> Self {
+        Self::err_with_type(
+            participate_with_approval_response::err::Type::SaleNotOpen,
+            None,
+        )
+    }
+
+    pub fn err_sale_closed() -> Self {
+        Self::err_with_type(
+            participate_with_approval_response::err::Type::SaleClosed,
+            None,
+        )
+    }
+
+    pub fn err_invalid_principal() -> Self {
+        Self::err_with_type(
+            participate_with_approval_response::err::Type::InvalidPrincipal,
+            None,
+        )
+    }
+
+    pub fn err_invalid_subaccount() -> Self {
+        Self::err_with_type(
+            participate_with_approval_response::err::Type::InvalidSubaccount,
+            None,
+        )
+    }
+
+    pub fn err_invalid_confirmation_text(description: String) -> Self {
+        Self::err_with_type(
+            participate_with_approval_response::err::Type::InvalidConfirmationText,
+            Some(description),
+        )
+    }
+
+    pub fn err_restricted_country(description: String) -> Self {
+        Self::err_with_type(
+            participate_with_approval_response::err::Type::RestrictedCountry,
+            Some(description),
+        )
+    }
+
+    pub fn err_transfer_from_failed(description: String) -> Self {
+        Self::err_with_type(
+            participate_with_approval_response::err::Type::TransferFromFailed,
+            Some(description),
+        )
+    }
+
+    pub fn err_participation_not_accepted(description: String) -> Self {
+        Self::err_with_type(
+            participate_with_approval_response::err::Type::ParticipationNotAccepted,
+            Some(description),
+        )
+    }
+
+    pub fn err_invalid_user_amount(
+        min_amount_icp_e8s_included: u64,
+        max_amount_icp_e8s_included: u64,
+    ) -> Self {
+        Self::err(participate_with_approval_response::Err {
+            error_type: participate_with_approval_response::err::Type::InvalidUserAmount as i32,
+            invalid_user_amount: Some(new_sale_ticket_response::err::InvalidUserAmount {
+                min_amount_icp_e8s_included,
+                max_amount_icp_e8s_included,
+            }),
+            description: None,
+        })
+    }
+
+    // panics if self.result is not set
+    pub fn icp_accepted_participation_e8s(
+        &self,
+    ) -> Result<u64, participate_with_approval_response::Err> {
+        match self.result.as_ref().unwrap() {
+            participate_with_approval_response::Result::Ok(ok) => {
+                Ok(ok.icp_accepted_participation_e8s)
+            }
+            participate_with_approval_response::Result::Err(err) => Err(err.clone()),
+        }
+    }
+}
+
 fn insert_buyer_into_buyers_list_index(buyer_principal_id: PrincipalId) -> Result<(), GrowFailed> {
     memory::BUYERS_LIST_INDEX
         .with(|buyer_list| buyer_list.borrow_mut().push(&buyer_principal_id.into()))
@@ -3673,6 +4278,7 @@ impl<'a> fmt::Debug for SwapDigest<'a> {
             neuron_recipes,
             direct_participation_icp_e8s,
             neurons_fund_participation_icp_e8s,
+            pending_participations,
         } = self.swap;
 
         formatter
@@ -3722,6 +4328,10 @@ impl<'a> fmt::Debug for SwapDigest<'a> {
                 "neurons_fund_participation_icp_e8s",
                 neurons_fund_participation_icp_e8s,
             )
+            .field(
+                "pending_participations",
+                &format!("<len={}>", pending_participations.len()),
+            )
             .finish()
     }
 }
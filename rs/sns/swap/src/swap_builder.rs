@@ -202,6 +202,11 @@ impl SwapBuilder {
         self
     }
 
+    pub fn with_restricted_countries(mut self, restricted_countries: Countries) -> Self {
+        self.restricted_countries = Some(restricted_countries);
+        self
+    }
+
     pub fn with_neuron_recipes(mut self, neuron_recipes: Vec<SnsNeuronRecipe>) -> Self {
         self.neuron_recipes = neuron_recipes;
         self
@@ -1,4 +1,6 @@
 use async_trait::async_trait;
+use candid::Nat;
+use futures::channel::{mpsc, oneshot};
 use ic_base_types::CanisterId;
 use ic_ledger_core::Tokens;
 use ic_nervous_system_common::{ledger::ICRC1Ledger, NervousSystemError};
@@ -9,17 +11,23 @@ use ic_sns_governance::pb::v1::{
     SetModeResponse,
 };
 use ic_sns_swap::{
-    clients::{NnsGovernanceClient, SnsGovernanceClient, SnsRootClient},
+    clients::{Icrc2LedgerClient, NnsGovernanceClient, SnsGovernanceClient, SnsRootClient},
     environment::CanisterClients,
     pb::v1::{
         CanisterCallError, SetDappControllersRequest, SetDappControllersResponse,
         SettleNeuronsFundParticipationRequest, SettleNeuronsFundParticipationResponse,
     },
 };
-use icrc_ledger_types::icrc1::account::{Account, Subaccount};
+use icrc_ledger_types::{
+    icrc1::{
+        account::{Account, Subaccount},
+        transfer::{TransferArg, TransferError},
+    },
+    icrc2::transfer_from::{TransferFromArgs, TransferFromError},
+};
 use std::{
     collections::VecDeque,
-    sync::{Arc, Mutex},
+    sync::{atomic, atomic::Ordering as AtomicOrdering, Arc, Mutex},
 };
 
 /// Expect that no SNS root calls will be made. Explode otherwise.
@@ -250,6 +258,124 @@ impl NnsGovernanceClient for SpyNnsGovernanceClient {
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub enum Icrc2LedgerClientCall {
+    Icrc1Transfer(TransferArg),
+    Icrc2TransferFrom(TransferFromArgs),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Icrc2LedgerClientReply {
+    Icrc1Transfer(Result<Nat, TransferError>),
+    Icrc2TransferFrom(Result<Nat, TransferFromError>),
+    CanisterCallError(CanisterCallError),
+}
+
+/// Icrc2LedgerClient that lets the test spy on the calls made. Replies are
+/// popped from the back of `replies`.
+#[derive(Default, Debug)]
+pub struct SpyIcrc2LedgerClient {
+    pub observed_calls: Vec<Icrc2LedgerClientCall>,
+    pub replies: Vec<Icrc2LedgerClientReply>,
+}
+
+impl SpyIcrc2LedgerClient {
+    pub fn new(replies: Vec<Icrc2LedgerClientReply>) -> Self {
+        SpyIcrc2LedgerClient {
+            observed_calls: vec![],
+            replies,
+        }
+    }
+
+    fn pop_reply(&mut self) -> Icrc2LedgerClientReply {
+        self.replies
+            .pop()
+            .expect("Expected there to be a reply in the Icrc2LedgerClient queue")
+    }
+}
+
+#[async_trait]
+impl Icrc2LedgerClient for SpyIcrc2LedgerClient {
+    async fn icrc1_transfer(
+        &mut self,
+        request: TransferArg,
+    ) -> Result<Result<Nat, TransferError>, CanisterCallError> {
+        self.observed_calls
+            .push(Icrc2LedgerClientCall::Icrc1Transfer(request));
+        match self.pop_reply() {
+            Icrc2LedgerClientReply::Icrc1Transfer(reply) => Ok(reply),
+            Icrc2LedgerClientReply::CanisterCallError(err) => Err(err),
+            reply => panic!("Unexpected reply to icrc1_transfer: {:?}", reply),
+        }
+    }
+
+    async fn icrc2_transfer_from(
+        &mut self,
+        request: TransferFromArgs,
+    ) -> Result<Result<Nat, TransferFromError>, CanisterCallError> {
+        self.observed_calls
+            .push(Icrc2LedgerClientCall::Icrc2TransferFrom(request));
+        match self.pop_reply() {
+            Icrc2LedgerClientReply::Icrc2TransferFrom(reply) => Ok(reply),
+            Icrc2LedgerClientReply::CanisterCallError(err) => Err(err),
+            reply => panic!("Unexpected reply to icrc2_transfer_from: {:?}", reply),
+        }
+    }
+}
+
+pub type Icrc2LedgerClientControlMessage = (Icrc2LedgerClientCall, oneshot::Sender<()>);
+
+/// Icrc2LedgerClient to test interleavings of canister method calls. Each call is sent over
+/// `observer` before it is forwarded to the underlying client, and blocks until the receiver
+/// side of the channel tells it to continue via the accompanying one-shot channel.
+pub struct InterleavingIcrc2LedgerClient {
+    pub underlying: SpyIcrc2LedgerClient,
+    observer: mpsc::UnboundedSender<Icrc2LedgerClientControlMessage>,
+}
+
+impl InterleavingIcrc2LedgerClient {
+    pub fn new(
+        underlying: SpyIcrc2LedgerClient,
+        observer: mpsc::UnboundedSender<Icrc2LedgerClientControlMessage>,
+    ) -> Self {
+        InterleavingIcrc2LedgerClient {
+            underlying,
+            observer,
+        }
+    }
+
+    /// Notifies the observer of `call`, and blocks until it receives a message to continue.
+    async fn notify(&self, call: Icrc2LedgerClientCall) {
+        let (tx, rx) = oneshot::channel::<()>();
+        atomic::fence(AtomicOrdering::SeqCst);
+        self.observer.unbounded_send((call, tx)).unwrap();
+        rx.await
+            .expect("Expected the observer to let the blocked call continue");
+        atomic::fence(AtomicOrdering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl Icrc2LedgerClient for InterleavingIcrc2LedgerClient {
+    async fn icrc1_transfer(
+        &mut self,
+        request: TransferArg,
+    ) -> Result<Result<Nat, TransferError>, CanisterCallError> {
+        self.notify(Icrc2LedgerClientCall::Icrc1Transfer(request.clone()))
+            .await;
+        self.underlying.icrc1_transfer(request).await
+    }
+
+    async fn icrc2_transfer_from(
+        &mut self,
+        request: TransferFromArgs,
+    ) -> Result<Result<Nat, TransferFromError>, CanisterCallError> {
+        self.notify(Icrc2LedgerClientCall::Icrc2TransferFrom(request.clone()))
+            .await;
+        self.underlying.icrc2_transfer_from(request).await
+    }
+}
+
 /// Expectation of one call on the mock Ledger.
 #[derive(Debug, Clone, Copy)]
 pub enum LedgerExpect {
@@ -4,10 +4,11 @@ use crate::common::{
     create_generic_sns_neuron_recipes, create_successful_swap_neuron_basket_for_neurons_fund,
     create_successful_swap_neuron_basket_for_one_direct_participant,
     doubles::{
-        spy_clients, spy_clients_exploding_root, ExplodingSnsRootClient, LedgerExpect,
+        spy_clients, spy_clients_exploding_root, ExplodingSnsRootClient, Icrc2LedgerClientCall,
+        Icrc2LedgerClientReply, InterleavingIcrc2LedgerClient, LedgerExpect,
         NnsGovernanceClientCall, NnsGovernanceClientReply, SnsGovernanceClientCall,
-        SnsGovernanceClientReply, SnsRootClientCall, SnsRootClientReply, SpyNnsGovernanceClient,
-        SpySnsGovernanceClient, SpySnsRootClient,
+        SnsGovernanceClientReply, SnsRootClientCall, SnsRootClientReply, SpyIcrc2LedgerClient,
+        SpyNnsGovernanceClient, SpySnsGovernanceClient, SpySnsRootClient,
     },
     extract_canister_call_error, extract_set_dapp_controller_response,
     get_account_balance_mock_ledger, get_snapshot_of_buyers_index_list, get_sns_balance,
@@ -16,7 +17,7 @@ use crate::common::{
     successful_set_mode_call_result, sweep, try_error_refund_err, try_error_refund_ok,
     verify_direct_participant_icp_balances, verify_direct_participant_sns_balances,
 };
-use candid::Principal;
+use candid::{Nat, Principal};
 use error_refund_icp_response::err::Type::Precondition;
 use futures::{channel::mpsc, future::FutureExt, StreamExt};
 use ic_base_types::{CanisterId, PrincipalId};
@@ -55,11 +56,15 @@ use ic_sns_swap::{
     swap::{
         apportion_approximately_equally, principal_to_subaccount, CLAIM_SWAP_NEURONS_BATCH_SIZE,
         FIRST_PRINCIPAL_BYTES, NEURON_BASKET_MEMO_RANGE_START,
+        PENDING_PARTICIPATION_EXPIRY_SECONDS,
     },
     swap_builder::SwapBuilder,
 };
 use icp_ledger::DEFAULT_TRANSFER_FEE;
-use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::{
+    icrc1::{account::Account, transfer::TransferArg},
+    icrc2::transfer_from::{TransferFromArgs, TransferFromError},
+};
 use maplit::btreemap;
 use rust_decimal_macros::dec;
 use std::{
@@ -209,6 +214,7 @@ fn create_generic_committed_swap() -> Swap {
         auto_finalize_swap_response: None,
         direct_participation_icp_e8s: Some(50 * E8),
         neurons_fund_participation_icp_e8s: None,
+        pending_participations: Default::default(),
     }
 }
 
@@ -5959,3 +5965,532 @@ async fn test_finalize_swap_abort_sets_amount_transferred_and_fees_correctly() {
         },
     );
 }
+
+fn participate_with_approval_swap() -> Swap {
+    SwapBuilder::new()
+        .with_sns_governance_canister_id(SNS_GOVERNANCE_CANISTER_ID)
+        .with_lifecycle(Open)
+        .with_swap_start_due(Some(START_TIMESTAMP_SECONDS), Some(END_TIMESTAMP_SECONDS))
+        .with_min_participants(1)
+        .with_min_max_participant_icp(2 * E8, 40 * E8)
+        .with_min_max_direct_participation(5 * E8, 50 * E8)
+        .with_sns_tokens(100_000 * E8)
+        .with_neuron_basket_count(3)
+        .without_confirmation_text()
+        .build()
+}
+
+fn participate_with_approval_request(amount_icp_e8s: u64) -> ParticipateWithApprovalRequest {
+    ParticipateWithApprovalRequest {
+        amount_icp_e8s,
+        from_subaccount: None,
+        confirmation_text: None,
+        country_code: None,
+    }
+}
+
+const PARTICIPATE_WITH_APPROVAL_TIME_NANOS: u64 = (START_TIMESTAMP_SECONDS + 10) * 1_000_000_000;
+
+fn participate_with_approval_transfer_from_args(
+    buyer: PrincipalId,
+    amount_icp_e8s: u64,
+) -> TransferFromArgs {
+    TransferFromArgs {
+        spender_subaccount: None,
+        from: Account {
+            owner: buyer.0,
+            subaccount: None,
+        },
+        to: Account {
+            owner: SWAP_CANISTER_ID.get().0,
+            subaccount: Some(principal_to_subaccount(&buyer)),
+        },
+        amount: Nat::from(amount_icp_e8s),
+        fee: None,
+        memo: None,
+        created_at_time: Some(PARTICIPATE_WITH_APPROVAL_TIME_NANOS),
+    }
+}
+
+fn participate_with_approval_error_type(
+    response: &ParticipateWithApprovalResponse,
+) -> participate_with_approval_response::err::Type {
+    let err = response.icp_accepted_participation_e8s().unwrap_err();
+    participate_with_approval_response::err::Type::try_from(err.error_type).unwrap()
+}
+
+#[tokio::test]
+async fn test_participate_with_approval_happy() {
+    let user = PrincipalId::new_user_test_id(1);
+    let mut swap = participate_with_approval_swap();
+    let mut icp_ledger =
+        SpyIcrc2LedgerClient::new(vec![Icrc2LedgerClientReply::Icrc2TransferFrom(Ok(
+            Nat::from(42_u64),
+        ))]);
+
+    let response = swap
+        .participate_with_approval(
+            user,
+            &participate_with_approval_request(10 * E8),
+            SWAP_CANISTER_ID,
+            PARTICIPATE_WITH_APPROVAL_TIME_NANOS,
+            &mut icp_ledger,
+        )
+        .await;
+
+    assert_eq!(
+        response,
+        ParticipateWithApprovalResponse::ok(10 * E8, 42),
+        "{:#?}",
+        response
+    );
+    assert_eq!(
+        icp_ledger.observed_calls,
+        vec![Icrc2LedgerClientCall::Icrc2TransferFrom(
+            participate_with_approval_transfer_from_args(user, 10 * E8)
+        )]
+    );
+    check_final_conditions(&mut swap, &user, &(10 * E8), &(10 * E8));
+    assert_eq!(get_snapshot_of_buyers_index_list(), vec![user]);
+}
+
+#[tokio::test]
+async fn test_participate_with_approval_rejects_invalid_requests_before_transfer() {
+    use participate_with_approval_response::err::Type;
+
+    let user = PrincipalId::new_user_test_id(1);
+    let confirmation_text = "Please confirm that 2+2=4".to_string();
+    let mut swap = SwapBuilder::new()
+        .with_lifecycle(Open)
+        .with_min_max_participant_icp(2 * E8, 40 * E8)
+        .with_min_max_direct_participation(5 * E8, 50 * E8)
+        .with_confirmation_text(confirmation_text.clone())
+        .with_restricted_countries(Countries {
+            iso_codes: vec!["CH".to_string()],
+        })
+        .build();
+    let valid_request = ParticipateWithApprovalRequest {
+        confirmation_text: Some(confirmation_text),
+        country_code: Some("US".to_string()),
+        ..participate_with_approval_request(10 * E8)
+    };
+
+    let cases = vec![
+        (
+            PrincipalId::new_anonymous(),
+            valid_request.clone(),
+            Type::InvalidPrincipal,
+        ),
+        (
+            user,
+            ParticipateWithApprovalRequest {
+                from_subaccount: Some(vec![0; 31]),
+                ..valid_request.clone()
+            },
+            Type::InvalidSubaccount,
+        ),
+        (
+            user,
+            ParticipateWithApprovalRequest {
+                confirmation_text: Some("Please confirm that 2+2=5".to_string()),
+                ..valid_request.clone()
+            },
+            Type::InvalidConfirmationText,
+        ),
+        (
+            user,
+            ParticipateWithApprovalRequest {
+                country_code: None,
+                ..valid_request.clone()
+            },
+            Type::RestrictedCountry,
+        ),
+        (
+            user,
+            ParticipateWithApprovalRequest {
+                country_code: Some("ch".to_string()),
+                ..valid_request.clone()
+            },
+            Type::RestrictedCountry,
+        ),
+        (
+            user,
+            ParticipateWithApprovalRequest {
+                amount_icp_e8s: E8,
+                ..valid_request.clone()
+            },
+            Type::InvalidUserAmount,
+        ),
+    ];
+
+    for (buyer, request, expected_error_type) in cases {
+        // No reply is queued, so any call to the ledger would panic.
+        let mut icp_ledger = SpyIcrc2LedgerClient::default();
+        let response = swap
+            .participate_with_approval(
+                buyer,
+                &request,
+                SWAP_CANISTER_ID,
+                PARTICIPATE_WITH_APPROVAL_TIME_NANOS,
+                &mut icp_ledger,
+            )
+            .await;
+        assert_eq!(
+            participate_with_approval_error_type(&response),
+            expected_error_type,
+            "{:#?}",
+            request
+        );
+        assert_eq!(icp_ledger.observed_calls, vec![]);
+    }
+    assert!(swap.buyers.is_empty());
+    assert!(swap.pending_participations.is_empty());
+
+    // The swap is closed.
+    swap.lifecycle = Committed as i32;
+    let mut icp_ledger = SpyIcrc2LedgerClient::default();
+    let response = swap
+        .participate_with_approval(
+            user,
+            &valid_request,
+            SWAP_CANISTER_ID,
+            PARTICIPATE_WITH_APPROVAL_TIME_NANOS,
+            &mut icp_ledger,
+        )
+        .await;
+    assert_eq!(response, ParticipateWithApprovalResponse::err_sale_closed());
+    assert_eq!(icp_ledger.observed_calls, vec![]);
+}
+
+#[tokio::test]
+async fn test_participate_with_approval_transfer_from_failure_leaves_state_unchanged() {
+    let user = PrincipalId::new_user_test_id(1);
+    let mut swap = participate_with_approval_swap();
+    let mut icp_ledger = SpyIcrc2LedgerClient::new(vec![
+        Icrc2LedgerClientReply::CanisterCallError(CanisterCallError {
+            code: Some(0),
+            description: "UNEXPECTED ERROR".to_string(),
+        }),
+        Icrc2LedgerClientReply::Icrc2TransferFrom(Err(TransferFromError::InsufficientAllowance {
+            allowance: Nat::from(E8),
+        })),
+    ]);
+
+    // Replies are popped from the back.
+    for _ in 0..2 {
+        let response = swap
+            .participate_with_approval(
+                user,
+                &participate_with_approval_request(10 * E8),
+                SWAP_CANISTER_ID,
+                PARTICIPATE_WITH_APPROVAL_TIME_NANOS,
+                &mut icp_ledger,
+            )
+            .await;
+        assert_eq!(
+            participate_with_approval_error_type(&response),
+            participate_with_approval_response::err::Type::TransferFromFailed,
+        );
+    }
+
+    // No refunds are attempted, and the reservation is released.
+    assert_eq!(icp_ledger.observed_calls.len(), 2);
+    assert!(swap.buyers.is_empty());
+    assert!(swap.pending_participations.is_empty());
+    assert_eq!(swap.current_direct_participation_e8s(), 0);
+    assert_eq!(swap.available_direct_participation_e8s(), 50 * E8);
+}
+
+/// Test that a reservation left behind by a participate_with_approval call whose callback
+/// trapped (so that it was never released) expires instead of locking the buyer and the
+/// reserved amount forever.
+#[tokio::test]
+async fn test_participate_with_approval_pending_participation_expires() {
+    use participate_with_approval_response::err::Type;
+
+    let user = PrincipalId::new_user_test_id(1);
+    let mut swap = participate_with_approval_swap();
+    swap.pending_participations.insert(
+        user.to_string(),
+        PendingParticipation {
+            amount_icp_e8s: 30 * E8,
+            created_at_time_nanos: PARTICIPATE_WITH_APPROVAL_TIME_NANOS,
+        },
+    );
+    let expiry_time_nanos =
+        PARTICIPATE_WITH_APPROVAL_TIME_NANOS + PENDING_PARTICIPATION_EXPIRY_SECONDS * 1_000_000_000;
+
+    // Step 1: Until the reservation expires, the buyer stays locked and the reserved amount
+    // is unavailable to others.
+    assert_eq!(
+        swap.purge_expired_pending_participations(expiry_time_nanos - 1),
+        0
+    );
+    assert_eq!(swap.available_direct_participation_e8s(), 20 * E8);
+    let mut icp_ledger = SpyIcrc2LedgerClient::default();
+    let response = swap
+        .participate_with_approval(
+            user,
+            &participate_with_approval_request(10 * E8),
+            SWAP_CANISTER_ID,
+            expiry_time_nanos - 1,
+            &mut icp_ledger,
+        )
+        .await;
+    assert_eq!(
+        participate_with_approval_error_type(&response),
+        Type::ParticipationNotAccepted
+    );
+    assert_eq!(icp_ledger.observed_calls, vec![]);
+
+    // Step 2: Once it has expired, the next participation releases it and goes through.
+    let mut icp_ledger =
+        SpyIcrc2LedgerClient::new(vec![Icrc2LedgerClientReply::Icrc2TransferFrom(Ok(
+            Nat::from(42_u64),
+        ))]);
+    let response = swap
+        .participate_with_approval(
+            user,
+            &participate_with_approval_request(10 * E8),
+            SWAP_CANISTER_ID,
+            expiry_time_nanos,
+            &mut icp_ledger,
+        )
+        .await;
+    assert_eq!(
+        response,
+        ParticipateWithApprovalResponse::ok(10 * E8, 42),
+        "{:#?}",
+        response
+    );
+    assert_eq!(
+        icp_ledger.observed_calls,
+        vec![Icrc2LedgerClientCall::Icrc2TransferFrom(TransferFromArgs {
+            created_at_time: Some(expiry_time_nanos),
+            ..participate_with_approval_transfer_from_args(user, 10 * E8)
+        })]
+    );
+    assert!(swap.pending_participations.is_empty());
+    assert_eq!(swap.available_direct_participation_e8s(), 40 * E8);
+}
+
+#[test]
+fn test_purge_expired_pending_participations() {
+    let mut swap = participate_with_approval_swap();
+    for (i, created_at_time_nanos) in [0, 100, 200].into_iter().enumerate() {
+        swap.pending_participations.insert(
+            PrincipalId::new_user_test_id(i as u64).to_string(),
+            PendingParticipation {
+                amount_icp_e8s: E8,
+                created_at_time_nanos,
+            },
+        );
+    }
+    let expiry_nanos = PENDING_PARTICIPATION_EXPIRY_SECONDS * 1_000_000_000;
+
+    assert_eq!(
+        swap.purge_expired_pending_participations(expiry_nanos - 1),
+        0
+    );
+    assert_eq!(
+        swap.purge_expired_pending_participations(expiry_nanos + 100),
+        2
+    );
+    assert_eq!(
+        swap.pending_participations.keys().collect::<Vec<_>>(),
+        vec![&PrincipalId::new_user_test_id(2).to_string()]
+    );
+    assert_eq!(swap.pending_direct_participation_e8s(), E8);
+}
+
+/// Test that overlapping calls to participate_with_approval cannot oversubscribe the swap,
+/// and that a buyer cannot have two of them in progress at the same time. Use the
+/// InterleavingIcrc2LedgerClient to have one thread block on the icrc2_transfer_from call,
+/// and make other calls in the meantime.
+#[test]
+fn test_participate_with_approval_reserves_participation_while_transfer_is_in_progress() {
+    let user1 = PrincipalId::new_user_test_id(1);
+    let user2 = PrincipalId::new_user_test_id(2);
+
+    // See test_finalize_swap_rejects_concurrent_calls for why a raw pointer is used.
+    let mut boxed_swap = Box::pin(participate_with_approval_swap());
+    let raw_ptr_swap = unsafe { Pin::get_unchecked_mut(boxed_swap.as_mut()) as *mut Swap };
+
+    #[allow(clippy::disallowed_methods)]
+    let (sender_channel, mut receiver_channel) = mpsc::unbounded();
+    let mut icp_ledger = InterleavingIcrc2LedgerClient::new(
+        SpyIcrc2LedgerClient::new(vec![Icrc2LedgerClientReply::Icrc2TransferFrom(Ok(
+            Nat::from(1_u64),
+        ))]),
+        sender_channel,
+    );
+
+    // Step 1: Have user1 participate with 30 ICP, and block on the ICP ledger call.
+    let thread_handle = thread::spawn(move || {
+        let response = tokio_test::block_on(boxed_swap.participate_with_approval(
+            user1,
+            &participate_with_approval_request(30 * E8),
+            SWAP_CANISTER_ID,
+            PARTICIPATE_WITH_APPROVAL_TIME_NANOS,
+            &mut icp_ledger,
+        ));
+        (boxed_swap, response)
+    });
+    let (call, continue_sender) =
+        tokio_test::block_on(async { receiver_channel.next().await.unwrap() });
+    assert_eq!(
+        call,
+        Icrc2LedgerClientCall::Icrc2TransferFrom(participate_with_approval_transfer_from_args(
+            user1,
+            30 * E8
+        ))
+    );
+
+    // Step 2: Make overlapping calls while the transfer of user1 is in progress.
+    atomic::fence(AtomicOrdering::SeqCst);
+    unsafe {
+        let swap = &mut *raw_ptr_swap;
+        assert_eq!(swap.pending_direct_participation_e8s(), 30 * E8);
+        assert_eq!(swap.current_direct_participation_e8s(), 0);
+        assert_eq!(swap.available_direct_participation_e8s(), 20 * E8);
+
+        // user1 cannot participate again, neither via participate_with_approval, ...
+        let mut icp_ledger = SpyIcrc2LedgerClient::default();
+        let response = swap
+            .participate_with_approval(
+                user1,
+                &participate_with_approval_request(10 * E8),
+                SWAP_CANISTER_ID,
+                PARTICIPATE_WITH_APPROVAL_TIME_NANOS,
+                &mut icp_ledger,
+            )
+            .now_or_never()
+            .unwrap();
+        assert_eq!(
+            participate_with_approval_error_type(&response),
+            participate_with_approval_response::err::Type::ParticipationNotAccepted,
+        );
+        assert_eq!(icp_ledger.observed_calls, vec![]);
+
+        // ... nor via refresh_buyer_tokens. The ICP ledger is not even called.
+        let result = swap
+            .refresh_buyer_token_e8s(user1, None, SWAP_CANISTER_ID, &mock_stub(vec![]))
+            .now_or_never()
+            .unwrap();
+        assert!(result.unwrap_err().contains("in progress"));
+
+        // user2 can only get what is not reserved for user1.
+        let mut icp_ledger =
+            SpyIcrc2LedgerClient::new(vec![Icrc2LedgerClientReply::Icrc2TransferFrom(Ok(
+                Nat::from(2_u64),
+            ))]);
+        let response = swap
+            .participate_with_approval(
+                user2,
+                &participate_with_approval_request(30 * E8),
+                SWAP_CANISTER_ID,
+                PARTICIPATE_WITH_APPROVAL_TIME_NANOS,
+                &mut icp_ledger,
+            )
+            .now_or_never()
+            .unwrap();
+        assert_eq!(response, ParticipateWithApprovalResponse::ok(20 * E8, 2));
+        assert_eq!(
+            icp_ledger.observed_calls,
+            vec![Icrc2LedgerClientCall::Icrc2TransferFrom(
+                participate_with_approval_transfer_from_args(user2, 20 * E8)
+            )]
+        );
+        assert_eq!(swap.current_direct_participation_e8s(), 20 * E8);
+        assert_eq!(swap.available_direct_participation_e8s(), 0);
+    }
+
+    // Step 3: Let the participation of user1 complete.
+    continue_sender.send(()).unwrap();
+    let (swap, response) = thread_handle
+        .join()
+        .expect("Expected the spawned thread to succeed");
+    assert_eq!(response, ParticipateWithApprovalResponse::ok(30 * E8, 1));
+    assert!(swap.pending_participations.is_empty());
+    assert_eq!(swap.current_direct_participation_e8s(), 50 * E8);
+    assert_eq!(
+        swap.buyers
+            .iter()
+            .map(|(buyer, state)| (buyer.clone(), state.amount_icp_e8s()))
+            .collect::<BTreeMap<_, _>>(),
+        btreemap! {
+            user1.to_string() => 30 * E8,
+            user2.to_string() => 20 * E8,
+        }
+    );
+}
+
+/// Test that ICP transferred via participate_with_approval is refunded right away if the
+/// swap is closed while the icrc2_transfer_from call is in progress.
+#[test]
+fn test_participate_with_approval_refunds_icp_that_cannot_be_accepted() {
+    let user = PrincipalId::new_user_test_id(1);
+
+    // See test_finalize_swap_rejects_concurrent_calls for why a raw pointer is used.
+    let mut boxed_swap = Box::pin(participate_with_approval_swap());
+    let raw_ptr_swap = unsafe { Pin::get_unchecked_mut(boxed_swap.as_mut()) as *mut Swap };
+
+    #[allow(clippy::disallowed_methods)]
+    let (sender_channel, mut receiver_channel) = mpsc::unbounded();
+    let mut icp_ledger = InterleavingIcrc2LedgerClient::new(
+        // Replies are popped from the back.
+        SpyIcrc2LedgerClient::new(vec![
+            Icrc2LedgerClientReply::Icrc1Transfer(Ok(Nat::from(2_u64))),
+            Icrc2LedgerClientReply::Icrc2TransferFrom(Ok(Nat::from(1_u64))),
+        ]),
+        sender_channel,
+    );
+
+    let thread_handle = thread::spawn(move || {
+        let response = tokio_test::block_on(boxed_swap.participate_with_approval(
+            user,
+            &participate_with_approval_request(10 * E8),
+            SWAP_CANISTER_ID,
+            PARTICIPATE_WITH_APPROVAL_TIME_NANOS,
+            &mut icp_ledger,
+        ));
+        (boxed_swap, icp_ledger, response)
+    });
+
+    // Close the swap while the transfer is in progress.
+    let (_, continue_sender) =
+        tokio_test::block_on(async { receiver_channel.next().await.unwrap() });
+    atomic::fence(AtomicOrdering::SeqCst);
+    unsafe {
+        (*raw_ptr_swap).lifecycle = Committed as i32;
+    }
+    continue_sender.send(()).unwrap();
+
+    // Let the refund go through.
+    let (call, continue_sender) =
+        tokio_test::block_on(async { receiver_channel.next().await.unwrap() });
+    continue_sender.send(()).unwrap();
+    let expected_refund = TransferArg {
+        from_subaccount: Some(principal_to_subaccount(&user)),
+        to: Account {
+            owner: user.0,
+            subaccount: None,
+        },
+        fee: Some(Nat::from(DEFAULT_TRANSFER_FEE.get_e8s())),
+        created_at_time: Some(PARTICIPATE_WITH_APPROVAL_TIME_NANOS),
+        memo: None,
+        amount: Nat::from(10 * E8 - DEFAULT_TRANSFER_FEE.get_e8s()),
+    };
+    assert_eq!(call, Icrc2LedgerClientCall::Icrc1Transfer(expected_refund));
+
+    let (swap, icp_ledger, response) = thread_handle
+        .join()
+        .expect("Expected the spawned thread to succeed");
+    assert_eq!(
+        participate_with_approval_error_type(&response),
+        participate_with_approval_response::err::Type::ParticipationNotAccepted,
+    );
+    assert_eq!(icp_ledger.underlying.observed_calls.len(), 2);
+    assert!(swap.buyers.is_empty());
+    assert!(swap.pending_participations.is_empty());
+}
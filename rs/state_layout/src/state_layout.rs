@@ -4,7 +4,7 @@ use crate::utils::do_copy;
 use ic_base_types::{NumBytes, NumSeconds};
 use ic_config::flag_status::FlagStatus;
 use ic_logger::{error, info, warn, ReplicaLogger};
-use ic_management_canister_types::LogVisibility;
+use ic_management_canister_types::{LogVisibility, SnapshotSource};
 use ic_metrics::{buckets::decimal_buckets, MetricsRegistry};
 use ic_protobuf::{
     proxy::{try_from_option_field, ProxyDecodeError},
@@ -168,16 +168,20 @@ pub struct CanisterStateBits {
     pub canister_log: CanisterLog,
     pub wasm_memory_limit: Option<NumBytes>,
     pub next_snapshot_id: u64,
+    pub environment_variables: BTreeMap<String, String>,
+    pub wasm_backtraces: bool,
 }
 
 /// This struct contains bits of the `CanisterSnapshot` that are not already
 /// covered somewhere else and are too small to be serialized separately.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub struct CanisterSnapshotBits {
     /// The ID of the canister snapshot.
     pub snapshot_id: SnapshotId,
     /// Identifies the canister to which this snapshot belongs.
     pub canister_id: CanisterId,
+    /// Whether the snapshot was taken from the canister or uploaded.
+    pub source: SnapshotSource,
     /// The timestamp indicating the moment the snapshot was captured.
     pub taken_at_timestamp: Time,
     /// The canister version at the time of taking the snapshot.
@@ -192,6 +196,8 @@ pub struct CanisterSnapshotBits {
     pub stable_memory_size: NumWasmPages,
     /// The size of the wasm memory in pages.
     pub wasm_memory_size: NumWasmPages,
+    /// The values of the exported globals.
+    pub exported_globals: Vec<Global>,
 }
 
 #[derive(Clone)]
@@ -1910,6 +1916,7 @@ impl From<CanisterStateBits> for pb_canister_state_bits::CanisterStateBits {
             total_query_stats: Some((&item.total_query_stats).into()),
             log_visibility: pb_canister_state_bits::LogVisibility::from(&item.log_visibility)
                 .into(),
+            log_visibility_v2: Some((&item.log_visibility).into()),
             canister_log_records: item
                 .canister_log
                 .records()
@@ -1919,6 +1926,12 @@ impl From<CanisterStateBits> for pb_canister_state_bits::CanisterStateBits {
             next_canister_log_record_idx: item.canister_log.next_idx(),
             wasm_memory_limit: item.wasm_memory_limit.map(|v| v.get()),
             next_snapshot_id: item.next_snapshot_id,
+            environment_variables: item
+                .environment_variables
+                .into_iter()
+                .map(|(name, value)| pb_canister_state_bits::EnvironmentVariable { name, value })
+                .collect(),
+            wasm_backtraces: item.wasm_backtraces,
         }
     }
 }
@@ -1992,6 +2005,22 @@ impl TryFrom<pb_canister_state_bits::CanisterStateBits> for CanisterStateBits {
             );
         }
 
+        // Checkpoints written by older replicas only have the legacy field.
+        let log_visibility = match value.log_visibility_v2 {
+            Some(log_visibility_v2) => LogVisibility::try_from(log_visibility_v2)?,
+            None => LogVisibility::from(
+                pb_canister_state_bits::LogVisibility::try_from(value.log_visibility).map_err(
+                    |_| ProxyDecodeError::ValueOutOfRange {
+                        typ: "LogVisibility",
+                        err: format!(
+                            "Unexpected value of log visibility: {}",
+                            value.log_visibility
+                        ),
+                    },
+                )?,
+            ),
+        };
+
         Ok(Self {
             controllers,
             last_full_execution_round: value.last_full_execution_round.into(),
@@ -2051,17 +2080,7 @@ impl TryFrom<pb_canister_state_bits::CanisterStateBits> for CanisterStateBits {
                 "CanisterStateBits::total_query_stats",
             )
             .unwrap_or_default(),
-            log_visibility: LogVisibility::from(
-                pb_canister_state_bits::LogVisibility::try_from(value.log_visibility).map_err(
-                    |_| ProxyDecodeError::ValueOutOfRange {
-                        typ: "LogVisibility",
-                        err: format!(
-                            "Unexpected value of log visibility: {}",
-                            value.log_visibility
-                        ),
-                    },
-                )?,
-            ),
+            log_visibility,
             canister_log: CanisterLog::new(
                 value.next_canister_log_record_idx,
                 value
@@ -2072,6 +2091,12 @@ impl TryFrom<pb_canister_state_bits::CanisterStateBits> for CanisterStateBits {
             ),
             wasm_memory_limit: value.wasm_memory_limit.map(NumBytes::from),
             next_snapshot_id: value.next_snapshot_id,
+            environment_variables: value
+                .environment_variables
+                .into_iter()
+                .map(|variable| (variable.name, variable.value))
+                .collect(),
+            wasm_backtraces: value.wasm_backtraces,
         })
     }
 }
@@ -2152,6 +2177,12 @@ impl From<&CanisterSnapshotBits> for pb_canister_snapshot_bits::CanisterSnapshot
             wasm_chunk_store_metadata: Some((&item.wasm_chunk_store_metadata).into()),
             stable_memory_size: item.stable_memory_size.get() as u64,
             wasm_memory_size: item.wasm_memory_size.get() as u64,
+            exported_globals: item
+                .exported_globals
+                .iter()
+                .map(|global| global.into())
+                .collect(),
+            source: pb_canister_state_bits::SnapshotSource::from(item.source).into(),
         }
     }
 }
@@ -2176,9 +2207,21 @@ impl TryFrom<pb_canister_snapshot_bits::CanisterSnapshotBits> for CanisterSnapsh
             }
             None => None,
         };
+        let mut exported_globals = Vec::with_capacity(item.exported_globals.len());
+        for global in item.exported_globals.into_iter() {
+            exported_globals.push(global.try_into()?);
+        }
+        let source =
+            pb_canister_state_bits::SnapshotSource::try_from(item.source).map_err(|_| {
+                ProxyDecodeError::ValueOutOfRange {
+                    typ: "SnapshotSource",
+                    err: format!("Unexpected value of snapshot source: {}", item.source),
+                }
+            })?;
         Ok(Self {
             snapshot_id: SnapshotId::from((canister_id, item.snapshot_id)),
             canister_id,
+            source: SnapshotSource::from(source),
             taken_at_timestamp: Time::from_nanos_since_unix_epoch(item.taken_at_timestamp),
             canister_version: item.canister_version,
             binary_hash,
@@ -2190,6 +2233,7 @@ impl TryFrom<pb_canister_snapshot_bits::CanisterSnapshotBits> for CanisterSnapsh
             .unwrap_or_default(),
             stable_memory_size: NumWasmPages::from(item.stable_memory_size as usize),
             wasm_memory_size: NumWasmPages::from(item.wasm_memory_size as usize),
+            exported_globals,
         })
     }
 }
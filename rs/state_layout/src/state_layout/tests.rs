@@ -2,7 +2,7 @@ use super::*;
 
 use ic_management_canister_types::{
     CanisterChange, CanisterChangeDetails, CanisterChangeOrigin, CanisterInstallMode,
-    LogVisibility, IC_00,
+    LogVisibility, SnapshotSource, IC_00,
 };
 use ic_replicated_state::{
     canister_state::system_state::CanisterHistory,
@@ -55,6 +55,8 @@ fn default_canister_state_bits() -> CanisterStateBits {
         canister_log: Default::default(),
         wasm_memory_limit: None,
         next_snapshot_id: 0,
+        environment_variables: BTreeMap::new(),
+        wasm_backtraces: false,
     }
 }
 
@@ -201,6 +203,7 @@ fn test_canister_snapshots_decode() {
     let canister_snapshot_bits = CanisterSnapshotBits {
         snapshot_id: SnapshotId::from((canister_id, 5)),
         canister_id,
+        source: SnapshotSource::MetadataUpload,
         taken_at_timestamp: UNIX_EPOCH,
         canister_version: 3,
         binary_hash: Some(WasmHash::from(&CanisterModule::new(vec![2, 3, 4]))),
@@ -208,6 +211,7 @@ fn test_canister_snapshots_decode() {
         wasm_chunk_store_metadata: WasmChunkStoreMetadata::default(),
         stable_memory_size: NumWasmPages::new(10),
         wasm_memory_size: NumWasmPages::new(10),
+        exported_globals: vec![Global::I32(1), Global::I64(2), Global::V128(3)],
     };
 
     let pb_bits = pb_canister_snapshot_bits::CanisterSnapshotBits::from(&canister_snapshot_bits);
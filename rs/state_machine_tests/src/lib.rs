@@ -38,7 +38,7 @@ use ic_management_canister_types::{
 pub use ic_management_canister_types::{
     CanisterHttpResponsePayload, CanisterInstallMode, CanisterSettingsArgs, CanisterStatusResultV2,
     CanisterStatusType, ECDSAPublicKeyResponse, EcdsaCurve, EcdsaKeyId, HttpHeader, HttpMethod,
-    SignWithECDSAReply, UpdateSettingsArgs,
+    SchnorrAlgorithm, SchnorrKeyId, SignWithECDSAReply, SignWithSchnorrReply, UpdateSettingsArgs,
 };
 use ic_messaging::SyncMessageRouting;
 use ic_metrics::MetricsRegistry;
@@ -70,7 +70,9 @@ use ic_registry_subnet_features::{
 };
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::canister_state::system_state::CyclesUseCase;
-use ic_replicated_state::metadata_state::subnet_call_context_manager::SignWithEcdsaContext;
+use ic_replicated_state::metadata_state::subnet_call_context_manager::{
+    SignWithEcdsaContext, SignWithThresholdContext, ThresholdArguments,
+};
 use ic_replicated_state::page_map::Buffer;
 use ic_replicated_state::{
     canister_state::{NumWasmPages, WASM_PAGE_SIZE_IN_BYTES},
@@ -219,7 +221,7 @@ pub fn finalize_registry(
 fn make_nodes_registry(
     subnet_id: SubnetId,
     subnet_type: SubnetType,
-    ecdsa_keys: &[EcdsaKeyId],
+    idkg_keys: &[MasterPublicKeyId],
     features: SubnetFeatures,
     registry_data_provider: Arc<ProtoRegistryDataProvider>,
     nodes: &Vec<StateMachineNode>,
@@ -235,10 +237,10 @@ fn make_nodes_registry(
             raw: subnet_id.get_ref().to_vec(),
         }),
     };
-    for key_id in ecdsa_keys {
+    for key_id in idkg_keys {
         registry_data_provider
             .add(
-                &make_chain_key_signing_subnet_list_key(&MasterPublicKeyId::Ecdsa(key_id.clone())),
+                &make_chain_key_signing_subnet_list_key(key_id),
                 registry_version,
                 Some(ChainKeySigningSubnetList {
                     subnets: vec![subnet_id_proto.clone()],
@@ -297,10 +299,10 @@ fn make_nodes_registry(
         .with_max_ingress_messages_per_block(max_ingress_messages_per_block)
         .with_max_block_payload_size(max_block_payload_size)
         .with_chain_key_config(ChainKeyConfig {
-            key_configs: ecdsa_keys
+            key_configs: idkg_keys
                 .iter()
-                .map(|ecdsa_key| KeyConfig {
-                    key_id: MasterPublicKeyId::Ecdsa(ecdsa_key.clone()),
+                .map(|key_id| KeyConfig {
+                    key_id: key_id.clone(),
                     pre_signatures_to_create_in_advance: 1,
                     max_queue_size: DEFAULT_ECDSA_MAX_QUEUE_SIZE,
                 })
@@ -577,6 +579,8 @@ pub struct StateMachine {
     public_key_der: Vec<u8>,
     secret_key: SecretKeyBytes,
     ecdsa_secret_key: PrivateKey,
+    schnorr_bip340_secret_key: PrivateKey,
+    schnorr_ed25519_secret_key: ic_crypto_ed25519::PrivateKey,
     registry_data_provider: Arc<ProtoRegistryDataProvider>,
     pub registry_client: Arc<FakeRegistryClient>,
     pub state_manager: Arc<StateManagerImpl>,
@@ -630,6 +634,7 @@ pub struct StateMachineBuilder {
     use_cost_scaling_flag: bool,
     enable_canister_snapshots: bool,
     ecdsa_keys: Vec<EcdsaKeyId>,
+    schnorr_keys: Vec<SchnorrKeyId>,
     features: SubnetFeatures,
     runtime: Option<Arc<Runtime>>,
     registry_data_provider: Arc<ProtoRegistryDataProvider>,
@@ -659,6 +664,7 @@ impl StateMachineBuilder {
                 curve: EcdsaCurve::Secp256k1,
                 name: "master_ecdsa_public_key".to_string(),
             }],
+            schnorr_keys: vec![],
             features: SubnetFeatures {
                 http_requests: true,
                 ..SubnetFeatures::default()
@@ -684,7 +690,7 @@ impl StateMachineBuilder {
         Self { state_dir, ..self }
     }
 
-    fn with_nonce(self, nonce: u64) -> Self {
+    pub fn with_nonce(self, nonce: u64) -> Self {
         Self { nonce, ..self }
     }
 
@@ -784,6 +790,17 @@ impl StateMachineBuilder {
         }
     }
 
+    /// Makes the subnet hold the given threshold Schnorr key and
+    /// answer `sign_with_schnorr` requests for it in every round.
+    pub fn with_schnorr_key(self, key: SchnorrKeyId) -> Self {
+        let mut schnorr_keys = self.schnorr_keys;
+        schnorr_keys.push(key);
+        Self {
+            schnorr_keys,
+            ..self
+        }
+    }
+
     pub fn with_features(self, features: SubnetFeatures) -> Self {
         Self { features, ..self }
     }
@@ -834,6 +851,7 @@ impl StateMachineBuilder {
             self.use_cost_scaling_flag,
             self.enable_canister_snapshots,
             self.ecdsa_keys,
+            self.schnorr_keys,
             self.features,
             self.runtime.unwrap_or_else(|| {
                 tokio::runtime::Builder::new_current_thread()
@@ -1037,6 +1055,9 @@ impl StateMachine {
             ));
         }
 
+        // Push responses to Schnorr management canister calls into `PayloadBuilder`.
+        self.push_sign_with_schnorr_responses(&state, &mut payload);
+
         // Finally execute the payload.
         self.execute_payload(payload);
     }
@@ -1063,6 +1084,7 @@ impl StateMachine {
         use_cost_scaling_flag: bool,
         enable_canister_snapshots: bool,
         ecdsa_keys: Vec<EcdsaKeyId>,
+        schnorr_keys: Vec<SchnorrKeyId>,
         features: SubnetFeatures,
         runtime: Arc<Runtime>,
         registry_data_provider: Arc<ProtoRegistryDataProvider>,
@@ -1090,10 +1112,16 @@ impl StateMachine {
         let public_key_der = threshold_sig_public_key_to_der(public_key).unwrap();
         let subnet_id =
             subnet_id.unwrap_or(PrincipalId::new_self_authenticating(&public_key_der).into());
+        let idkg_keys: Vec<MasterPublicKeyId> = ecdsa_keys
+            .iter()
+            .cloned()
+            .map(MasterPublicKeyId::Ecdsa)
+            .chain(schnorr_keys.iter().cloned().map(MasterPublicKeyId::Schnorr))
+            .collect();
         let registry_client = make_nodes_registry(
             subnet_id,
             subnet_type,
-            &ecdsa_keys,
+            &idkg_keys,
             features,
             registry_data_provider.clone(),
             &nodes,
@@ -1116,6 +1144,11 @@ impl StateMachine {
             hypervisor_config.canister_snapshots = FlagStatus::Enabled;
         }
 
+        if !schnorr_keys.is_empty() {
+            hypervisor_config.ic00_schnorr_public_key = FlagStatus::Enabled;
+            hypervisor_config.ic00_sign_with_schnorr = FlagStatus::Enabled;
+        }
+
         // We are not interested in ingress signature validation.
         let malicious_flags = MaliciousFlags {
             maliciously_disable_ingress_validation: true,
@@ -1192,7 +1225,18 @@ impl StateMachine {
 
         let mut idkg_subnet_public_keys = BTreeMap::new();
 
-        //TODO: support schnorr keys
+        // Deterministic keys for threshold Schnorr. Please do not use these private keys anywhere.
+        let schnorr_bip340_secret_key = PrivateKey::deserialize_sec1(
+            &hex::decode("b2cc4c036233ed1d6fb9f2b4bd8ea32271c0af3503c9b7467dac353a071e8f46")
+                .unwrap(),
+        )
+        .unwrap();
+        let schnorr_ed25519_secret_key = ic_crypto_ed25519::PrivateKey::deserialize_raw(
+            &hex::decode("c41b7c10375e4fbee32b9ff9639005c4b08cf2c343a6c4d3293e030987ab18ea")
+                .unwrap(),
+        )
+        .unwrap();
+
         for ecdsa_key in ecdsa_keys {
             idkg_subnet_public_keys.insert(
                 MasterPublicKeyId::Ecdsa(ecdsa_key),
@@ -1214,6 +1258,24 @@ impl StateMachine {
             },
         );
 
+        for schnorr_key in schnorr_keys {
+            let master_public_key = match schnorr_key.algorithm {
+                SchnorrAlgorithm::Bip340Secp256k1 => MasterPublicKey {
+                    algorithm_id: AlgorithmId::ThresholdSchnorrBip340,
+                    public_key: schnorr_bip340_secret_key.public_key().serialize_sec1(true),
+                },
+                SchnorrAlgorithm::Ed25519 => MasterPublicKey {
+                    algorithm_id: AlgorithmId::ThresholdEd25519,
+                    public_key: schnorr_ed25519_secret_key
+                        .public_key()
+                        .serialize_raw()
+                        .to_vec(),
+                },
+            };
+            idkg_subnet_public_keys
+                .insert(MasterPublicKeyId::Schnorr(schnorr_key), master_public_key);
+        }
+
         let time_source = FastForwardTimeSource::new();
         time_source.set_time(time).unwrap();
         let consensus_time = Arc::new(PocketConsensusTime::new(time));
@@ -1243,6 +1305,8 @@ impl StateMachine {
             public_key,
             public_key_der,
             ecdsa_secret_key,
+            schnorr_bip340_secret_key,
+            schnorr_ed25519_secret_key,
             registry_data_provider,
             registry_client: registry_client.clone(),
             state_manager,
@@ -1384,6 +1448,19 @@ impl StateMachine {
             .store(enabled, core::sync::atomic::Ordering::Relaxed)
     }
 
+    /// Returns whether the state machine creates an on-disk checkpoint
+    /// for each new state it creates (see [set_checkpoints_enabled]).
+    pub fn checkpoints_enabled(&self) -> bool {
+        self.checkpoints_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Returns the nonce of the most recent ingress message built by this state machine.
+    /// Passing it to [StateMachineBuilder::with_nonce] avoids duplicate message IDs
+    /// when building a state machine from a copy of this state machine's state.
+    pub fn get_nonce(&self) -> u64 {
+        self.nonce.load(Ordering::Relaxed)
+    }
+
     /// Returns the latest state.
     pub fn get_latest_state(&self) -> Arc<ReplicatedState> {
         self.state_manager.get_latest_state().take()
@@ -1554,9 +1631,56 @@ impl StateMachine {
                 MsgPayload::Data(reply.encode()),
             ));
         }
+        self.push_sign_with_schnorr_responses(&state, &mut payload);
         self.execute_payload(payload);
     }
 
+    /// Signs the messages of all pending `sign_with_schnorr` requests
+    /// with keys derived from the subnet's master keys.
+    fn push_sign_with_schnorr_responses(
+        &self,
+        state: &ReplicatedState,
+        payload: &mut PayloadBuilder,
+    ) {
+        let sign_with_threshold_contexts = &state
+            .metadata
+            .subnet_call_context_manager
+            .sign_with_threshold_contexts;
+        for (callback, context) in sign_with_threshold_contexts {
+            let ThresholdArguments::Schnorr(args) = &context.args else {
+                continue;
+            };
+            let signature = match args.key_id.algorithm {
+                SchnorrAlgorithm::Bip340Secp256k1 => sign_message_with_bip340_derived_key(
+                    &self.schnorr_bip340_secret_key,
+                    &args.message,
+                    DerivationPath::new(
+                        threshold_derivation_path(context)
+                            .map(DerivationIndex)
+                            .collect(),
+                    ),
+                    &context.pseudo_random_id,
+                ),
+                SchnorrAlgorithm::Ed25519 => sign_message_with_ed25519_derived_key(
+                    &self.schnorr_ed25519_secret_key,
+                    &args.message,
+                    ic_crypto_ed25519::DerivationPath::new(
+                        threshold_derivation_path(context)
+                            .map(ic_crypto_ed25519::DerivationIndex)
+                            .collect(),
+                    ),
+                ),
+            };
+
+            let reply = SignWithSchnorrReply { signature };
+
+            payload.consensus_responses.push(ConsensusResponse::new(
+                *callback,
+                MsgPayload::Data(reply.encode()),
+            ));
+        }
+    }
+
     /// Makes the state machine tick until there are no more messages in the system.
     /// This method is useful if you need to wait for asynchronous canister communication to
     /// complete.
@@ -2792,6 +2916,61 @@ fn sign_prehashed_message_with_derived_key(
     signature.to_vec()
 }
 
+/// The derivation path used for a threshold signing request: the caller
+/// followed by the derivation path provided by the caller.
+fn threshold_derivation_path(
+    context: &SignWithThresholdContext,
+) -> impl Iterator<Item = Vec<u8>> + '_ {
+    std::iter::once(context.request.sender.get().as_slice().to_vec())
+        .chain(context.derivation_path.iter().cloned())
+}
+
+fn sign_message_with_bip340_derived_key(
+    secret_key: &PrivateKey,
+    message: &[u8],
+    derivation_path: DerivationPath,
+    aux_rand: &[u8; 32],
+) -> Vec<u8> {
+    const CHAIN_CODE: &[u8] = &[0; 32];
+
+    let derived_public_key_bytes = derivation_path
+        .public_key_derivation(&secret_key.public_key().serialize_sec1(true), CHAIN_CODE)
+        .expect("couldn't derive bip340 public key");
+    let derived_private_key_bytes = derivation_path
+        .private_key_derivation(&secret_key.serialize_sec1(), CHAIN_CODE)
+        .expect("couldn't derive bip340 private key");
+
+    let signing_key =
+        k256::schnorr::SigningKey::from_bytes(&derived_private_key_bytes.derived_private_key)
+            .expect("couldn't deserialize bip340 private key");
+    let signature = signing_key
+        .sign_raw(message, aux_rand)
+        .expect("failed to sign");
+
+    // BIP340 public keys are the x-coordinate of the SEC1 encoded point.
+    let verifying_key =
+        k256::schnorr::VerifyingKey::from_bytes(&derived_public_key_bytes.derived_public_key[1..])
+            .expect("couldn't deserialize bip340 public key");
+    assert!(verifying_key.verify_raw(message, &signature).is_ok());
+    signature.to_bytes().to_vec()
+}
+
+fn sign_message_with_ed25519_derived_key(
+    secret_key: &ic_crypto_ed25519::PrivateKey,
+    message: &[u8],
+    derivation_path: ic_crypto_ed25519::DerivationPath,
+) -> Vec<u8> {
+    let (derived_private_key, _chain_code) = secret_key.derive_subkey(&derivation_path);
+    let (derived_public_key, _chain_code) = secret_key.public_key().derive_subkey(&derivation_path);
+
+    let signature = derived_private_key.sign_message(message);
+
+    assert!(derived_public_key
+        .verify_signature(message, &signature)
+        .is_ok());
+    signature.to_vec()
+}
+
 #[derive(Clone)]
 pub struct PayloadBuilder {
     expiry_time: Time,
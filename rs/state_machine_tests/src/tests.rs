@@ -33,6 +33,53 @@ proptest! {
 
         prop_assert!(derived_public_key.verify_signature_prehashed(&message_hash, &signature));
     }
+
+    #[test]
+    fn test_bip340_derivation_prop(
+        derivation_path_bytes in pvec(pvec(any::<u8>(), 1..10), 1..10),
+        message in pvec(any::<u8>(), 0..100),
+        aux_rand in any::<[u8; 32]>(),
+    ) {
+        const CHAIN_CODE: &[u8] = &[0; 32];
+        let secret_key = PrivateKey::generate();
+        let derivation_path = DerivationPath::new(
+            derivation_path_bytes
+                .into_iter()
+                .map(DerivationIndex)
+                .collect(),
+        );
+
+        let signature = crate::sign_message_with_bip340_derived_key(&secret_key, &message, derivation_path.clone(), &aux_rand);
+
+        let derived_public_key_bytes = derivation_path
+            .public_key_derivation(&secret_key.public_key().serialize_sec1(true), CHAIN_CODE)
+            .expect("couldn't derive bip340 public key");
+        let derived_public_key =
+            k256::schnorr::VerifyingKey::from_bytes(&derived_public_key_bytes.derived_public_key[1..]).unwrap();
+        let signature = k256::schnorr::Signature::try_from(signature.as_slice()).unwrap();
+
+        prop_assert!(derived_public_key.verify_raw(&message, &signature).is_ok());
+    }
+
+    #[test]
+    fn test_ed25519_derivation_prop(
+        derivation_path_bytes in pvec(pvec(any::<u8>(), 1..10), 1..10),
+        message in pvec(any::<u8>(), 0..100),
+    ) {
+        let secret_key = ic_crypto_ed25519::PrivateKey::generate();
+        let derivation_path = ic_crypto_ed25519::DerivationPath::new(
+            derivation_path_bytes
+                .into_iter()
+                .map(ic_crypto_ed25519::DerivationIndex)
+                .collect(),
+        );
+
+        let signature = crate::sign_message_with_ed25519_derived_key(&secret_key, &message, derivation_path.clone());
+
+        let (derived_public_key, _chain_code) = secret_key.public_key().derive_subkey(&derivation_path);
+
+        prop_assert!(derived_public_key.verify_signature(&message, &signature).is_ok());
+    }
 }
 
 #[test]
@@ -413,6 +413,8 @@ pub fn load_canister_state<P: ReadPolicy>(
         canister_state_bits.canister_log,
         canister_state_bits.wasm_memory_limit,
         canister_state_bits.next_snapshot_id,
+        canister_state_bits.environment_variables,
+        canister_state_bits.wasm_backtraces,
     );
 
     let canister_state = CanisterState {
@@ -876,10 +876,12 @@ fn serialize_canister_to_tip(
                 .metadata()
                 .clone(),
             total_query_stats: canister_state.scheduler_state.total_query_stats.clone(),
-            log_visibility: canister_state.system_state.log_visibility,
+            log_visibility: canister_state.system_state.log_visibility.clone(),
             canister_log: canister_state.system_state.canister_log.clone(),
             wasm_memory_limit: canister_state.system_state.wasm_memory_limit,
             next_snapshot_id: canister_state.system_state.next_snapshot_id,
+            environment_variables: canister_state.system_state.environment_variables.clone(),
+            wasm_backtraces: canister_state.system_state.wasm_backtraces,
         }
         .into(),
     )?;
@@ -3,9 +3,12 @@ pub mod cdiff;
 pub mod chash;
 pub mod convert_ids;
 pub mod decode;
+pub mod extract_canister;
 pub mod import_state;
+pub mod inject_canister;
 pub mod list;
 pub mod manifest;
+pub mod report;
 pub mod split;
 pub mod split_manifest;
 mod utils;
@@ -0,0 +1,123 @@
+//! Extracts the artifacts of a single canister from a checkpoint.
+
+use crate::commands::utils::CANISTER_JSON_FILE;
+use ic_replicated_state::page_map::{PageMap, StorageLayout, TestPageAllocatorFileDescriptorImpl};
+use ic_state_layout::{
+    CanisterLayout, CompleteCheckpointLayout, PageMapLayout, ReadOnly, WriteOnly,
+};
+use ic_types::{CanisterId, Height, PrincipalId};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Writes the state of `canister_id` in the checkpoint at `path` to the
+/// `output` directory.
+///
+/// The output directory mirrors the layout of a canister directory within a
+/// checkpoint, except that the system state is stored as JSON
+/// (`canister.json`) instead of protobuf, and each memory (heap, stable memory
+/// and chunk store) is a single flat `.bin` image, with all overlays applied.
+pub fn do_extract_canister(
+    path: PathBuf,
+    canister_id: PrincipalId,
+    output: PathBuf,
+) -> Result<(), String> {
+    let canister_id = CanisterId::unchecked_from_principal(canister_id);
+    let cp_layout = CompleteCheckpointLayout::new_untracked(path.clone(), Height::new(0))
+        .map_err(|e| format!("failed to create checkpoint layout: {}", e))?;
+    let canister_ids = cp_layout
+        .canister_ids()
+        .map_err(|e| format!("failed to enumerate canisters: {}", e))?;
+    if !canister_ids.contains(&canister_id) {
+        return Err(format!(
+            "canister {} not found in checkpoint {}",
+            canister_id,
+            path.display()
+        ));
+    }
+
+    let canister_layout = cp_layout
+        .canister(&canister_id)
+        .map_err(|e| format!("failed to access canister {}: {}", canister_id, e))?;
+    let output_layout = CanisterLayout::<WriteOnly>::new(output.clone())
+        .map_err(|e| format!("failed to create output directory: {}", e))?;
+
+    let canister_state_bits = canister_layout
+        .canister()
+        .deserialize()
+        .map_err(|e| format!("failed to read canister state: {}", e))?;
+    let json = serde_json::to_string_pretty(&canister_state_bits)
+        .map_err(|e| format!("failed to encode canister state as JSON: {}", e))?;
+    let json_path = output.join(CANISTER_JSON_FILE);
+    std::fs::write(&json_path, json)
+        .map_err(|e| format!("failed to write {}: {}", json_path.display(), e))?;
+
+    let queues = canister_layout
+        .queues()
+        .deserialize()
+        .map_err(|e| format!("failed to read canister queues: {}", e))?;
+    output_layout
+        .queues()
+        .serialize(queues)
+        .map_err(|e| format!("failed to write canister queues: {}", e))?;
+
+    if canister_layout.wasm().raw_path().exists() {
+        let wasm = canister_layout
+            .wasm()
+            .deserialize(None)
+            .map_err(|e| format!("failed to read Wasm module: {}", e))?;
+        output_layout
+            .wasm()
+            .serialize(&wasm)
+            .map_err(|e| format!("failed to write Wasm module: {}", e))?;
+    }
+
+    let fd_factory = Arc::new(TestPageAllocatorFileDescriptorImpl::new());
+    for (layout, output_layout) in [
+        (canister_layout.vmemory_0(), output_layout.vmemory_0()),
+        (
+            canister_layout.stable_memory(),
+            output_layout.stable_memory(),
+        ),
+        (
+            canister_layout.wasm_chunk_store(),
+            output_layout.wasm_chunk_store(),
+        ),
+    ] {
+        output_layout
+            .delete_files()
+            .map_err(|e| format!("failed to clean up {}: {}", output.display(), e))?;
+        write_memory_image(&layout, &output_layout.base(), fd_factory.clone())?;
+    }
+
+    println!(
+        "Extracted canister {} from {} to {}",
+        canister_id,
+        path.display(),
+        output.display()
+    );
+
+    Ok(())
+}
+
+/// Reassembles the page map stored at `layout` (base file plus overlays) and
+/// writes it to `dst` as a single flat image. Nothing is written for an empty
+/// page map.
+fn write_memory_image(
+    layout: &PageMapLayout<ReadOnly>,
+    dst: &Path,
+    fd_factory: Arc<TestPageAllocatorFileDescriptorImpl>,
+) -> Result<(), String> {
+    let page_map = PageMap::open(layout, Height::new(0), fd_factory)
+        .map_err(|e| format!("failed to open {}: {}", layout.base().display(), e))?;
+    if page_map.num_host_pages() == 0 {
+        return Ok(());
+    }
+
+    let write_error = |e: std::io::Error| format!("failed to write {}: {}", dst.display(), e);
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(dst).map_err(write_error)?);
+    for (_, page) in page_map.host_pages_iter() {
+        writer.write_all(page).map_err(write_error)?;
+    }
+    writer.flush().map_err(write_error)
+}
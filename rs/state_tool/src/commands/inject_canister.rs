@@ -0,0 +1,345 @@
+//! Writes the artifacts of a single canister back into a checkpoint.
+
+use crate::commands::utils::CANISTER_JSON_FILE;
+use ic_protobuf::state::canister_state_bits::v1 as pb_canister;
+use ic_replicated_state::page_map::StorageLayout;
+use ic_state_layout::{
+    CanisterLayout, CanisterStateBits, CheckpointLayout, ReadOnly, WriteOnly, CHECKPOINTS_DIR,
+};
+use ic_types::{CanisterId, Height, PrincipalId};
+use std::convert::TryFrom;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// Replaces the state of `canister_id` in the checkpoint at `path` with the
+/// artifacts in the `input` directory, as produced by `extract-canister`. If
+/// the canister does not exist in the checkpoint, it is created.
+///
+/// Note that this modifies the checkpoint in place, so its manifest no longer
+/// matches. The result is meant to be loaded by tools such as `drun` or
+/// PocketIC, not by a replica. Read-only checkpoints and checkpoints within
+/// the state root of a replica are therefore rejected; copy them first.
+pub fn do_inject_canister(
+    path: PathBuf,
+    canister_id: PrincipalId,
+    input: PathBuf,
+) -> Result<(), String> {
+    check_checkpoint_is_writable(&path)?;
+    let canister_id = CanisterId::unchecked_from_principal(canister_id);
+    let input_layout = CanisterLayout::<ReadOnly>::new(input.clone())
+        .map_err(|e| format!("failed to access input directory: {}", e))?;
+
+    let json_path = input.join(CANISTER_JSON_FILE);
+    let json = std::fs::read_to_string(&json_path)
+        .map_err(|e| format!("failed to read {}: {}", json_path.display(), e))?;
+    let canister_state_bits: pb_canister::CanisterStateBits = serde_json::from_str(&json)
+        .map_err(|e| format!("failed to decode {}: {}", json_path.display(), e))?;
+    // Reject a system state that the replica would fail to load.
+    CanisterStateBits::try_from(canister_state_bits.clone())
+        .map_err(|e| format!("invalid canister state in {}: {}", json_path.display(), e))?;
+    let queues = input_layout
+        .queues()
+        .deserialize()
+        .map_err(|e| format!("failed to read canister queues: {}", e))?;
+
+    let cp_layout = CheckpointLayout::<WriteOnly>::new_untracked(path.clone(), Height::new(0))
+        .map_err(|e| format!("failed to create checkpoint layout: {}", e))?;
+    let canister_layout = cp_layout
+        .canister(&canister_id)
+        .map_err(|e| format!("failed to access canister {}: {}", canister_id, e))?;
+
+    canister_layout
+        .canister()
+        .serialize(canister_state_bits)
+        .map_err(|e| format!("failed to write canister state: {}", e))?;
+    canister_layout
+        .queues()
+        .serialize(queues)
+        .map_err(|e| format!("failed to write canister queues: {}", e))?;
+
+    canister_layout
+        .wasm()
+        .try_delete_file()
+        .map_err(|e| format!("failed to remove Wasm module: {}", e))?;
+    if input_layout.wasm().raw_path().exists() {
+        let wasm = input_layout
+            .wasm()
+            .deserialize(None)
+            .map_err(|e| format!("failed to read Wasm module: {}", e))?;
+        canister_layout
+            .wasm()
+            .serialize(&wasm)
+            .map_err(|e| format!("failed to write Wasm module: {}", e))?;
+    }
+
+    for (input_layout, layout) in [
+        (input_layout.vmemory_0(), canister_layout.vmemory_0()),
+        (
+            input_layout.stable_memory(),
+            canister_layout.stable_memory(),
+        ),
+        (
+            input_layout.wasm_chunk_store(),
+            canister_layout.wasm_chunk_store(),
+        ),
+    ] {
+        // Drop the existing base file and overlays; the image becomes the new base file.
+        layout
+            .delete_files()
+            .map_err(|e| format!("failed to remove {}: {}", layout.base().display(), e))?;
+        let image = input_layout.base();
+        if image.exists() {
+            std::fs::copy(&image, layout.base()).map_err(|e| {
+                format!(
+                    "failed to copy {} to {}: {}",
+                    image.display(),
+                    layout.base().display(),
+                    e
+                )
+            })?;
+        }
+    }
+
+    println!(
+        "Injected canister {} from {} into {}",
+        canister_id,
+        input.display(),
+        path.display()
+    );
+
+    Ok(())
+}
+
+/// Returns an error if the checkpoint at `path` must not be modified, i.e. if
+/// it is read-only or if it lives in the `checkpoints` directory of a state
+/// root, where a replica would load it and verify it against its manifest.
+fn check_checkpoint_is_writable(path: &Path) -> Result<(), String> {
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| format!("failed to access checkpoint {}: {}", path.display(), e))?;
+    if metadata.permissions().readonly() {
+        return Err(format!(
+            "checkpoint {} is read-only; copy it to a writable location and inject into the copy",
+            path.display()
+        ));
+    }
+
+    let canonical_path = path
+        .canonicalize()
+        .map_err(|e| format!("failed to resolve {}: {}", path.display(), e))?;
+    if canonical_path.parent().and_then(Path::file_name) == Some(OsStr::new(CHECKPOINTS_DIR)) {
+        return Err(format!(
+            "checkpoint {} belongs to a replica state root and injecting a canister would \
+             invalidate its manifest; copy it out of the state root and inject into the copy",
+            path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::extract_canister::do_extract_canister;
+    use ic_protobuf::state::queues::v1 as pb_queues;
+    use ic_replicated_state::page_map::PAGE_SIZE;
+    use ic_state_layout::CompleteCheckpointLayout;
+    use ic_types::Cycles;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn canister_state_bits(cycles: u128) -> pb_canister::CanisterStateBits {
+        pb_canister::CanisterStateBits {
+            cycles_balance: Some(Cycles::new(cycles).into()),
+            canister_status: Some(pb_canister::canister_state_bits::CanisterStatus::Stopped(
+                pb_canister::CanisterStatusStopped {},
+            )),
+            time_of_last_allocation_charge_nanos: Some(0),
+            canister_version: 7,
+            certified_data: vec![1, 2, 3],
+            ..Default::default()
+        }
+    }
+
+    /// Writes a checkpoint at `path` holding a single canister with the given
+    /// system state, a Wasm module and a two page heap.
+    fn write_checkpoint(
+        path: &Path,
+        canister_id: CanisterId,
+        canister_state_bits: pb_canister::CanisterStateBits,
+        wasm: &[u8],
+        heap: &[u8],
+    ) {
+        let cp_layout =
+            CheckpointLayout::<WriteOnly>::new_untracked(path.to_path_buf(), Height::new(0))
+                .unwrap();
+        let canister_layout = cp_layout.canister(&canister_id).unwrap();
+        canister_layout
+            .canister()
+            .serialize(canister_state_bits)
+            .unwrap();
+        canister_layout
+            .queues()
+            .serialize(pb_queues::CanisterQueues {
+                next_input_queue: 1,
+                ..Default::default()
+            })
+            .unwrap();
+        std::fs::write(canister_layout.wasm().raw_path(), wasm).unwrap();
+        std::fs::write(canister_layout.vmemory_0().base(), heap).unwrap();
+    }
+
+    #[test]
+    fn extract_and_inject_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("source");
+        let target = tmp.path().join("target");
+        let extracted = tmp.path().join("extracted");
+        let canister_id = CanisterId::from_u64(42);
+        let other_canister_id = CanisterId::from_u64(43);
+
+        let heap = vec![0xab; 2 * PAGE_SIZE];
+        write_checkpoint(
+            &source,
+            canister_id,
+            canister_state_bits(1_000),
+            b"\0asm source",
+            &heap,
+        );
+        write_checkpoint(
+            &target,
+            canister_id,
+            canister_state_bits(5),
+            b"\0asm target",
+            &[0xcd; PAGE_SIZE],
+        );
+        write_checkpoint(
+            &target,
+            other_canister_id,
+            canister_state_bits(9),
+            b"\0asm other",
+            &[0xef; PAGE_SIZE],
+        );
+
+        do_extract_canister(source.clone(), canister_id.get(), extracted.clone()).unwrap();
+        do_inject_canister(target.clone(), canister_id.get(), extracted).unwrap();
+
+        let source_layout = CompleteCheckpointLayout::new_untracked(source, Height::new(0))
+            .unwrap()
+            .canister(&canister_id)
+            .unwrap();
+        let target_cp_layout =
+            CompleteCheckpointLayout::new_untracked(target, Height::new(0)).unwrap();
+        let target_layout = target_cp_layout.canister(&canister_id).unwrap();
+
+        assert_eq!(
+            target_layout.canister().deserialize().unwrap(),
+            source_layout.canister().deserialize().unwrap()
+        );
+        assert_eq!(
+            target_layout.queues().deserialize().unwrap(),
+            source_layout.queues().deserialize().unwrap()
+        );
+        assert_eq!(
+            std::fs::read(target_layout.wasm().raw_path()).unwrap(),
+            b"\0asm source"
+        );
+        assert_eq!(
+            std::fs::read(target_layout.vmemory_0().base()).unwrap(),
+            heap
+        );
+        assert!(!target_layout.stable_memory().base().exists());
+
+        // The other canister in the target checkpoint is left untouched.
+        let other_layout = target_cp_layout.canister(&other_canister_id).unwrap();
+        assert_eq!(
+            other_layout.canister().deserialize().unwrap(),
+            canister_state_bits(9)
+        );
+        assert_eq!(
+            std::fs::read(other_layout.wasm().raw_path()).unwrap(),
+            b"\0asm other"
+        );
+    }
+
+    #[test]
+    fn inject_into_read_only_checkpoint_fails() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("source");
+        let target = tmp.path().join("target");
+        let extracted = tmp.path().join("extracted");
+        let canister_id = CanisterId::from_u64(42);
+        write_checkpoint(
+            &source,
+            canister_id,
+            canister_state_bits(1_000),
+            b"\0asm source",
+            &[0xab; PAGE_SIZE],
+        );
+        write_checkpoint(
+            &target,
+            canister_id,
+            canister_state_bits(5),
+            b"\0asm target",
+            &[0xcd; PAGE_SIZE],
+        );
+        do_extract_canister(source, canister_id.get(), extracted.clone()).unwrap();
+
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o555)).unwrap();
+        let result = do_inject_canister(target.clone(), canister_id.get(), extracted);
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let err = result.unwrap_err();
+        assert!(err.contains("is read-only"), "unexpected error: {}", err);
+        let target_layout = CompleteCheckpointLayout::new_untracked(target, Height::new(0))
+            .unwrap()
+            .canister(&canister_id)
+            .unwrap();
+        assert_eq!(
+            target_layout.canister().deserialize().unwrap(),
+            canister_state_bits(5)
+        );
+    }
+
+    #[test]
+    fn inject_into_state_root_checkpoint_fails() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("source");
+        let target = tmp
+            .path()
+            .join("state")
+            .join(CHECKPOINTS_DIR)
+            .join("000000000000000a");
+        let extracted = tmp.path().join("extracted");
+        let canister_id = CanisterId::from_u64(42);
+        write_checkpoint(
+            &source,
+            canister_id,
+            canister_state_bits(1_000),
+            b"\0asm source",
+            &[0xab; PAGE_SIZE],
+        );
+        write_checkpoint(
+            &target,
+            canister_id,
+            canister_state_bits(5),
+            b"\0asm target",
+            &[0xcd; PAGE_SIZE],
+        );
+        do_extract_canister(source, canister_id.get(), extracted.clone()).unwrap();
+
+        let err = do_inject_canister(target.clone(), canister_id.get(), extracted).unwrap_err();
+        assert!(
+            err.contains("would invalidate its manifest"),
+            "unexpected error: {}",
+            err
+        );
+        let target_layout = CompleteCheckpointLayout::new_untracked(target, Height::new(0))
+            .unwrap()
+            .canister(&canister_id)
+            .unwrap();
+        assert_eq!(
+            target_layout.canister().deserialize().unwrap(),
+            canister_state_bits(5)
+        );
+    }
+}
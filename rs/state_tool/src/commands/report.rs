@@ -0,0 +1,402 @@
+//! Reports how the bytes of a checkpoint are distributed across canisters and
+//! subnet metadata.
+
+use clap::ArgEnum;
+use ic_replicated_state::page_map::{PageMap, TestPageAllocatorFileDescriptorImpl, PAGE_SIZE};
+use ic_state_layout::{
+    CanisterLayout, CompleteCheckpointLayout, PageMapLayout, ReadOnly, CANISTER_STATES_DIR,
+};
+use ic_types::Height;
+use prost::Message;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Output formats supported by the `report` command.
+#[derive(Clone, Copy, Debug, ArgEnum)]
+pub enum ReportFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+/// Columns the per-canister rows can be sorted by. Sizes are sorted in
+/// descending order, canister IDs in ascending order.
+#[derive(Clone, Copy, Debug, ArgEnum)]
+pub enum SortBy {
+    CanisterId,
+    Total,
+    Wasm,
+    Heap,
+    StableMemory,
+    WasmChunkStore,
+    OverlayFiles,
+    OverlayBytes,
+    Queues,
+    CanisterHistory,
+}
+
+/// Size breakdown of a single canister. Also used for the subnet-wide files
+/// (only `total_bytes` is set) and for the sum over the whole checkpoint.
+#[derive(Default, Serialize)]
+struct CanisterReport {
+    canister_id: String,
+    /// Total size of all files in the canister directory.
+    total_bytes: u64,
+    /// Size of the Wasm module.
+    wasm_bytes: u64,
+    /// Logical size of the Wasm heap, with all overlays applied.
+    heap_bytes: u64,
+    /// Logical size of the stable memory, with all overlays applied.
+    stable_memory_bytes: u64,
+    /// Logical size of the Wasm chunk store, with all overlays applied.
+    wasm_chunk_store_bytes: u64,
+    /// Number of overlay files across heap, stable memory and chunk store.
+    overlay_files: u64,
+    /// Total size of those overlay files.
+    overlay_bytes: u64,
+    /// Size of the encoded canister queues.
+    queues_bytes: u64,
+    /// Size of the encoded canister history.
+    canister_history_bytes: u64,
+}
+
+impl CanisterReport {
+    fn sort_key(&self, sort_by: SortBy) -> u64 {
+        match sort_by {
+            SortBy::CanisterId => 0,
+            SortBy::Total => self.total_bytes,
+            SortBy::Wasm => self.wasm_bytes,
+            SortBy::Heap => self.heap_bytes,
+            SortBy::StableMemory => self.stable_memory_bytes,
+            SortBy::WasmChunkStore => self.wasm_chunk_store_bytes,
+            SortBy::OverlayFiles => self.overlay_files,
+            SortBy::OverlayBytes => self.overlay_bytes,
+            SortBy::Queues => self.queues_bytes,
+            SortBy::CanisterHistory => self.canister_history_bytes,
+        }
+    }
+
+    fn add(&mut self, other: &CanisterReport) {
+        self.total_bytes += other.total_bytes;
+        self.wasm_bytes += other.wasm_bytes;
+        self.heap_bytes += other.heap_bytes;
+        self.stable_memory_bytes += other.stable_memory_bytes;
+        self.wasm_chunk_store_bytes += other.wasm_chunk_store_bytes;
+        self.overlay_files += other.overlay_files;
+        self.overlay_bytes += other.overlay_bytes;
+        self.queues_bytes += other.queues_bytes;
+        self.canister_history_bytes += other.canister_history_bytes;
+    }
+}
+
+/// Sizes of the subnet-wide state files.
+#[derive(Serialize)]
+struct SubnetReport {
+    /// Total size of all files outside of the canister directories.
+    total_bytes: u64,
+    system_metadata_bytes: u64,
+    ingress_history_bytes: u64,
+    subnet_queues_bytes: u64,
+    stats_bytes: u64,
+    /// Size of the remaining subnet-wide files, e.g. the split marker.
+    other_bytes: u64,
+}
+
+impl SubnetReport {
+    /// Returns the subnet-wide files as a row of the per-canister table.
+    fn as_row(&self) -> CanisterReport {
+        CanisterReport {
+            canister_id: "subnet".to_string(),
+            total_bytes: self.total_bytes,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Report {
+    canisters: Vec<CanisterReport>,
+    /// Sum over all canisters and the subnet-wide files, i.e. the size of the
+    /// whole checkpoint.
+    total: CanisterReport,
+    subnet: SubnetReport,
+}
+
+/// Prints the per-canister and aggregate size breakdown of the checkpoint at
+/// `path`.
+///
+/// Canister snapshots are not included, as they are not persisted in
+/// checkpoints yet.
+pub fn do_report(path: PathBuf, format: ReportFormat, sort_by: SortBy) -> Result<(), String> {
+    let report = build_report(path, sort_by)?;
+    match format {
+        ReportFormat::Table => print_table(&report),
+        ReportFormat::Csv => print_csv(&report),
+        ReportFormat::Json => serde_json::to_writer_pretty(std::io::stdout(), &report)
+            .map_err(|e| format!("failed to write JSON report: {}", e)),
+    }
+}
+
+fn build_report(path: PathBuf, sort_by: SortBy) -> Result<Report, String> {
+    let cp_layout = CompleteCheckpointLayout::new_untracked(path.clone(), Height::new(0))
+        .map_err(|e| format!("failed to create checkpoint layout: {}", e))?;
+    let canister_ids = cp_layout
+        .canister_ids()
+        .map_err(|e| format!("failed to enumerate canisters: {}", e))?;
+
+    let fd_factory = Arc::new(TestPageAllocatorFileDescriptorImpl::new());
+    let mut canisters = Vec::with_capacity(canister_ids.len());
+    for canister_id in canister_ids {
+        let canister_layout = cp_layout
+            .canister(&canister_id)
+            .map_err(|e| format!("failed to access canister {}: {}", canister_id, e))?;
+        let mut report = canister_report(&canister_layout, fd_factory.clone())?;
+        report.canister_id = canister_id.to_string();
+        canisters.push(report);
+    }
+    match sort_by {
+        SortBy::CanisterId => canisters.sort_by(|a, b| a.canister_id.cmp(&b.canister_id)),
+        _ => canisters.sort_by_key(|c| std::cmp::Reverse(c.sort_key(sort_by))),
+    }
+
+    let mut subnet = SubnetReport {
+        total_bytes: dir_size(&path, Some(CANISTER_STATES_DIR))?,
+        system_metadata_bytes: file_size(cp_layout.system_metadata().raw_path())?,
+        ingress_history_bytes: file_size(cp_layout.ingress_history().raw_path())?,
+        subnet_queues_bytes: file_size(cp_layout.subnet_queues().raw_path())?,
+        stats_bytes: file_size(cp_layout.stats().raw_path())?,
+        other_bytes: 0,
+    };
+    subnet.other_bytes = subnet.total_bytes
+        - subnet.system_metadata_bytes
+        - subnet.ingress_history_bytes
+        - subnet.subnet_queues_bytes
+        - subnet.stats_bytes;
+
+    let mut total = CanisterReport {
+        canister_id: "total".to_string(),
+        ..Default::default()
+    };
+    for canister in &canisters {
+        total.add(canister);
+    }
+    total.add(&subnet.as_row());
+
+    Ok(Report {
+        canisters,
+        total,
+        subnet,
+    })
+}
+
+/// Computes the size breakdown of a single canister. The canister ID is left
+/// for the caller to fill in.
+fn canister_report(
+    canister_layout: &CanisterLayout<ReadOnly>,
+    fd_factory: Arc<TestPageAllocatorFileDescriptorImpl>,
+) -> Result<CanisterReport, String> {
+    let mut report = CanisterReport::default();
+
+    let canister_dir = canister_layout.raw_path();
+    let entries = std::fs::read_dir(&canister_dir)
+        .map_err(|e| format!("failed to list {}: {}", canister_dir.display(), e))?;
+    for entry in entries {
+        let entry =
+            entry.map_err(|e| format!("failed to list {}: {}", canister_dir.display(), e))?;
+        report.total_bytes += file_size(&entry.path())?;
+    }
+
+    report.wasm_bytes = file_size(canister_layout.wasm().raw_path())?;
+    report.queues_bytes = file_size(canister_layout.queues().raw_path())?;
+    report.canister_history_bytes = canister_layout
+        .canister()
+        .deserialize()
+        .map_err(|e| format!("failed to read canister state: {}", e))?
+        .canister_history
+        .map_or(0, |history| history.encoded_len() as u64);
+
+    let mut page_map_size = |layout: PageMapLayout<ReadOnly>| -> Result<u64, String> {
+        let overlays = layout
+            .existing_overlays()
+            .map_err(|e| format!("failed to list overlays: {}", e))?;
+        report.overlay_files += overlays.len() as u64;
+        for overlay in &overlays {
+            report.overlay_bytes += file_size(overlay)?;
+        }
+
+        let page_map = PageMap::open(&layout, Height::new(0), fd_factory.clone())
+            .map_err(|e| format!("failed to open page map: {}", e))?;
+        Ok((page_map.num_host_pages() * PAGE_SIZE) as u64)
+    };
+    let heap_bytes = page_map_size(canister_layout.vmemory_0())?;
+    let stable_memory_bytes = page_map_size(canister_layout.stable_memory())?;
+    let wasm_chunk_store_bytes = page_map_size(canister_layout.wasm_chunk_store())?;
+    report.heap_bytes = heap_bytes;
+    report.stable_memory_bytes = stable_memory_bytes;
+    report.wasm_chunk_store_bytes = wasm_chunk_store_bytes;
+
+    Ok(report)
+}
+
+/// Returns the total size of all files under the directory at `path`,
+/// skipping the top-level subdirectory named `skip`, if any.
+fn dir_size(path: &Path, skip: Option<&str>) -> Result<u64, String> {
+    let mut size = 0;
+    let entries =
+        std::fs::read_dir(path).map_err(|e| format!("failed to list {}: {}", path.display(), e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("failed to list {}: {}", path.display(), e))?;
+        if skip.is_some_and(|skip| entry.file_name() == skip) {
+            continue;
+        }
+        let file_type = entry
+            .file_type()
+            .map_err(|e| format!("failed to stat {}: {}", entry.path().display(), e))?;
+        if file_type.is_dir() {
+            size += dir_size(&entry.path(), None)?;
+        } else {
+            size += file_size(&entry.path())?;
+        }
+    }
+    Ok(size)
+}
+
+/// Returns the size of the file at `path`, or 0 if it does not exist.
+fn file_size(path: &Path) -> Result<u64, String> {
+    match std::fs::metadata(path) {
+        Ok(metadata) => Ok(metadata.len()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(format!("failed to stat {}: {}", path.display(), e)),
+    }
+}
+
+fn print_table(report: &Report) -> Result<(), String> {
+    println!(
+        "{:<30} {:>15} {:>15} {:>15} {:>15} {:>15} {:>10} {:>15} {:>12} {:>12}",
+        "CANISTER",
+        "TOTAL",
+        "WASM",
+        "HEAP",
+        "STABLE",
+        "CHUNK_STORE",
+        "OVERLAYS",
+        "OVERLAY_BYTES",
+        "QUEUES",
+        "HISTORY"
+    );
+    let subnet_row = report.subnet.as_row();
+    for row in report.canisters.iter().chain([&subnet_row, &report.total]) {
+        println!(
+            "{:<30} {:>15} {:>15} {:>15} {:>15} {:>15} {:>10} {:>15} {:>12} {:>12}",
+            row.canister_id,
+            row.total_bytes,
+            row.wasm_bytes,
+            row.heap_bytes,
+            row.stable_memory_bytes,
+            row.wasm_chunk_store_bytes,
+            row.overlay_files,
+            row.overlay_bytes,
+            row.queues_bytes,
+            row.canister_history_bytes
+        );
+    }
+
+    println!();
+    println!("{:<30} {:>15}", "SUBNET FILE", "BYTES");
+    for (name, bytes) in [
+        ("system_metadata", report.subnet.system_metadata_bytes),
+        ("ingress_history", report.subnet.ingress_history_bytes),
+        ("subnet_queues", report.subnet.subnet_queues_bytes),
+        ("stats", report.subnet.stats_bytes),
+        ("other", report.subnet.other_bytes),
+    ] {
+        println!("{:<30} {:>15}", name, bytes);
+    }
+
+    Ok(())
+}
+
+/// Prints one row per canister, a `subnet` row holding the size of all
+/// subnet-wide files and the aggregate row.
+fn print_csv(report: &Report) -> Result<(), String> {
+    let mut writer = csv::Writer::from_writer(std::io::stdout());
+    let subnet_row = report.subnet.as_row();
+    for row in report.canisters.iter().chain([&subnet_row, &report.total]) {
+        writer
+            .serialize(row)
+            .map_err(|e| format!("failed to write CSV report: {}", e))?;
+    }
+    writer
+        .flush()
+        .map_err(|e| format!("failed to write CSV report: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_protobuf::state::canister_state_bits::v1 as pb_canister;
+    use ic_state_layout::{CheckpointLayout, WriteOnly};
+    use ic_types::CanisterId;
+
+    #[test]
+    fn report_covers_canisters_and_subnet_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("checkpoint");
+        let cp_layout =
+            CheckpointLayout::<WriteOnly>::new_untracked(path.clone(), Height::new(0)).unwrap();
+
+        let canister_1 = cp_layout.canister(&CanisterId::from_u64(1)).unwrap();
+        canister_1
+            .canister()
+            .serialize(pb_canister::CanisterStateBits {
+                certified_data: vec![7; 10],
+                ..Default::default()
+            })
+            .unwrap();
+        let canister_state_bytes = file_size(canister_1.canister().raw_path()).unwrap();
+        assert!(canister_state_bytes > 0);
+        std::fs::write(canister_1.wasm().raw_path(), vec![0; 100]).unwrap();
+        std::fs::write(canister_1.vmemory_0().base(), vec![1; 2 * PAGE_SIZE]).unwrap();
+
+        let canister_2 = cp_layout.canister(&CanisterId::from_u64(2)).unwrap();
+        std::fs::write(canister_2.wasm().raw_path(), vec![0; 10]).unwrap();
+        std::fs::write(canister_2.stable_memory().base(), vec![1; PAGE_SIZE]).unwrap();
+
+        std::fs::write(cp_layout.system_metadata().raw_path(), vec![0; 50]).unwrap();
+        std::fs::write(cp_layout.split_marker().raw_path(), vec![0; 7]).unwrap();
+
+        let report = build_report(path, SortBy::CanisterId).unwrap();
+
+        assert_eq!(report.canisters.len(), 2);
+        let report_1 = &report.canisters[0];
+        assert_eq!(report_1.canister_id, CanisterId::from_u64(1).to_string());
+        assert_eq!(
+            report_1.total_bytes,
+            canister_state_bytes + 100 + 2 * PAGE_SIZE as u64
+        );
+        assert_eq!(report_1.wasm_bytes, 100);
+        assert_eq!(report_1.heap_bytes, 2 * PAGE_SIZE as u64);
+        assert_eq!(report_1.stable_memory_bytes, 0);
+        let report_2 = &report.canisters[1];
+        assert_eq!(report_2.canister_id, CanisterId::from_u64(2).to_string());
+        assert_eq!(report_2.total_bytes, 10 + PAGE_SIZE as u64);
+        assert_eq!(report_2.wasm_bytes, 10);
+        assert_eq!(report_2.heap_bytes, 0);
+        assert_eq!(report_2.stable_memory_bytes, PAGE_SIZE as u64);
+
+        assert_eq!(report.subnet.total_bytes, 57);
+        assert_eq!(report.subnet.system_metadata_bytes, 50);
+        assert_eq!(report.subnet.ingress_history_bytes, 0);
+        assert_eq!(report.subnet.other_bytes, 7);
+
+        // The aggregate row accounts for every file in the checkpoint.
+        assert_eq!(
+            report.total.total_bytes,
+            report_1.total_bytes + report_2.total_bytes + 57
+        );
+        assert_eq!(report.total.wasm_bytes, 110);
+        assert_eq!(report.total.heap_bytes, 2 * PAGE_SIZE as u64);
+        assert_eq!(report.total.stable_memory_bytes, PAGE_SIZE as u64);
+    }
+}
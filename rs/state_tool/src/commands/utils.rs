@@ -6,6 +6,10 @@ use ic_metrics::MetricsRegistry;
 use ic_state_layout::StateLayout;
 use std::path::PathBuf;
 
+/// Name of the file holding a canister's system state (the contents of
+/// `canister.pbuf`) as JSON, in the directories written by `extract-canister`.
+pub const CANISTER_JSON_FILE: &str = "canister.json";
+
 /// Loads the location of the state root from the given `replica` configuration
 /// file.
 pub fn locate_state_root(config_path: PathBuf) -> Result<StateLayout, String> {
@@ -2,12 +2,14 @@
 //!
 //! A command-line tool to manage Internet Computer replicated states (decode
 //! persisted state files, diff checkpoints, compute partial state hashes and
-//! checkpoint manifests, import state trees).
+//! checkpoint manifests, import state trees, extract and inject canisters,
+//! report state sizes).
 
 use clap::Parser;
 use ic_registry_routing_table::CanisterIdRange;
 use ic_registry_subnet_type::SubnetType;
 use ic_state_tool::commands;
+use ic_state_tool::commands::report::{ReportFormat, SortBy};
 use ic_types::{PrincipalId, Time};
 use std::path::PathBuf;
 
@@ -43,6 +45,41 @@ enum Opt {
         height: u64,
     },
 
+    /// Extracts the Wasm module, memories, system state and queues of a
+    /// canister from a checkpoint into a directory.
+    #[clap(name = "extract-canister")]
+    ExtractCanister {
+        /// Path to a checkpoint.
+        #[clap(long = "state")]
+        path: PathBuf,
+
+        /// The canister to extract.
+        #[clap(long = "canister_id")]
+        canister_id: PrincipalId,
+
+        /// Directory to write the canister artifacts to.
+        #[clap(long = "output")]
+        output: PathBuf,
+    },
+
+    /// Writes canister artifacts produced by `extract-canister` back into a
+    /// checkpoint, replacing the canister's existing state.
+    #[clap(name = "inject-canister")]
+    InjectCanister {
+        /// Path to a writable copy of a checkpoint, outside of any replica
+        /// state root.
+        #[clap(long = "state")]
+        path: PathBuf,
+
+        /// The canister to inject.
+        #[clap(long = "canister_id")]
+        canister_id: PrincipalId,
+
+        /// Directory holding the canister artifacts.
+        #[clap(long = "input")]
+        input: PathBuf,
+    },
+
     /// Computes manifest of a checkpoint.
     #[clap(name = "manifest")]
     Manifest {
@@ -51,6 +88,23 @@ enum Opt {
         path: PathBuf,
     },
 
+    /// Reports the sizes of canister memories, overlays, queues and histories,
+    /// as well as of subnet-wide state files, of a checkpoint.
+    #[clap(name = "report")]
+    Report {
+        /// Path to a checkpoint.
+        #[clap(long = "state")]
+        path: PathBuf,
+
+        /// Output format.
+        #[clap(arg_enum, long = "format", default_value = "table")]
+        format: ReportFormat,
+
+        /// Column to sort canisters by.
+        #[clap(arg_enum, long = "sort_by", default_value = "total")]
+        sort_by: SortBy,
+    },
+
     /// Verifies whether the textual representation
     /// of a manifest matches its root hash.
     #[clap(name = "verify_manifest")]
@@ -159,7 +213,22 @@ fn main() {
             config,
             height,
         } => commands::import_state::do_import(state, config, height),
+        Opt::ExtractCanister {
+            path,
+            canister_id,
+            output,
+        } => commands::extract_canister::do_extract_canister(path, canister_id, output),
+        Opt::InjectCanister {
+            path,
+            canister_id,
+            input,
+        } => commands::inject_canister::do_inject_canister(path, canister_id, input),
         Opt::Manifest { path } => commands::manifest::do_compute_manifest(path),
+        Opt::Report {
+            path,
+            format,
+            sort_by,
+        } => commands::report::do_report(path, format, sort_by),
         Opt::VerifyManifest { file } => commands::verify_manifest::do_verify_manifest(&file),
         Opt::ListStates { config } => commands::list::do_list(config),
         Opt::Decode { file } => commands::decode::do_decode(file),
@@ -16,6 +16,9 @@ use ic_interfaces::execution_environment::{
     TrapCode::{self, CyclesAmountTooBigFor64Bit},
 };
 use ic_logger::{error, ReplicaLogger};
+use ic_management_canister_types::{
+    EcdsaCurve, EcdsaKeyId, MasterPublicKeyId, SchnorrAlgorithm, SchnorrKeyId,
+};
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{
     canister_state::WASM_PAGE_SIZE_IN_BYTES, memory_required_to_push_request, Memory, NumWasmPages,
@@ -1498,6 +1501,11 @@ impl SystemApiImpl {
             ApiType::Init { .. } | ApiType::PreUpgrade { .. }
         )
     }
+
+    /// Returns true if the canister enabled Wasm backtraces in its settings.
+    pub fn wasm_backtraces_enabled(&self) -> bool {
+        self.sandbox_safe_system_state.wasm_backtraces
+    }
 }
 
 impl SystemApi for SystemApiImpl {
@@ -2656,9 +2664,10 @@ impl SystemApi for SystemApiImpl {
         let resulting_size = current_size.saturating_add(additional_pages);
         if let StableMemoryApi::Stable32 = stable_memory_api {
             if current_size > MAX_32_BIT_STABLE_MEMORY_IN_PAGES {
-                return Err(HypervisorError::Trapped(
-                    TrapCode::StableMemoryTooBigFor32Bit,
-                ));
+                return Err(HypervisorError::Trapped {
+                    trap_code: TrapCode::StableMemoryTooBigFor32Bit,
+                    backtrace: None,
+                });
             }
             if resulting_size > MAX_32_BIT_STABLE_MEMORY_IN_PAGES {
                 return Ok(StableGrowOutcome::Failure);
@@ -2700,7 +2709,10 @@ impl SystemApi for SystemApiImpl {
                 .ic0_canister_cycle_balance_helper("ic0_canister_cycle_balance")?
                 .into_parts();
             if high_amount != 0 {
-                return Err(HypervisorError::Trapped(CyclesAmountTooBigFor64Bit));
+                return Err(HypervisorError::Trapped {
+                    trap_code: CyclesAmountTooBigFor64Bit,
+                    backtrace: None,
+                });
             }
             Ok(low_amount)
         };
@@ -2730,7 +2742,10 @@ impl SystemApi for SystemApiImpl {
                 .ic0_msg_cycles_available_helper("ic0_msg_cycles_available")?
                 .into_parts();
             if high_amount != 0 {
-                return Err(HypervisorError::Trapped(CyclesAmountTooBigFor64Bit));
+                return Err(HypervisorError::Trapped {
+                    trap_code: CyclesAmountTooBigFor64Bit,
+                    backtrace: None,
+                });
             }
             Ok(low_amount)
         };
@@ -2755,7 +2770,10 @@ impl SystemApi for SystemApiImpl {
                 .ic0_msg_cycles_refunded_helper("ic0_msg_cycles_refunded")?
                 .into_parts();
             if high_amount != 0 {
-                return Err(HypervisorError::Trapped(CyclesAmountTooBigFor64Bit));
+                return Err(HypervisorError::Trapped {
+                    trap_code: CyclesAmountTooBigFor64Bit,
+                    backtrace: None,
+                });
             }
             Ok(low_amount)
         };
@@ -3084,7 +3102,10 @@ impl SystemApi for SystemApiImpl {
             let msg = valid_subslice("trap", src, size, heap)
                 .map(|bytes| String::from_utf8_lossy(bytes).to_string())
                 .unwrap_or_else(|_| "(trap message out of memory bounds)".to_string());
-            CalledTrap(msg)
+            CalledTrap {
+                message: msg,
+                backtrace: None,
+            }
         };
         trace_syscall!(self, Trap, src, size, summarize(heap, src, size));
         Err(result)
@@ -3263,6 +3284,381 @@ impl SystemApi for SystemApiImpl {
         trace_syscall!(self, CyclesBurn128, result, amount);
         result
     }
+
+    fn ic0_cost_call(
+        &self,
+        method_name_size: u64,
+        payload_size: u64,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let cost = self.sandbox_safe_system_state.call_cost(NumBytes::from(
+            method_name_size.saturating_add(payload_size),
+        ));
+        let result = copy_cycles_to_heap(cost, dst, heap, "ic0_cost_call");
+        trace_syscall!(self, CostCall, result, method_name_size, payload_size);
+        result
+    }
+
+    fn ic0_cost_create_canister(&self, dst: usize, heap: &mut [u8]) -> HypervisorResult<()> {
+        let cost = self.sandbox_safe_system_state.create_canister_cost();
+        let result = copy_cycles_to_heap(cost, dst, heap, "ic0_cost_create_canister");
+        trace_syscall!(self, CostCreateCanister, result);
+        result
+    }
+
+    fn ic0_cost_http_request(
+        &self,
+        request_size: u64,
+        max_res_bytes: u64,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let cost = self
+            .sandbox_safe_system_state
+            .http_request_cost(NumBytes::from(request_size), NumBytes::from(max_res_bytes));
+        let result = copy_cycles_to_heap(cost, dst, heap, "ic0_cost_http_request");
+        trace_syscall!(self, CostHttpRequest, result, request_size, max_res_bytes);
+        result
+    }
+
+    fn ic0_cost_sign_with_ecdsa(
+        &self,
+        src: usize,
+        size: usize,
+        curve: u32,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32> {
+        let method_name = "ic0_cost_sign_with_ecdsa";
+        let result = match key_name(method_name, src, size, heap) {
+            Err(err) => Err(err),
+            Ok(name) => match curve {
+                0 => self.copy_sign_with_threshold_key_cost(
+                    MasterPublicKeyId::Ecdsa(EcdsaKeyId {
+                        curve: EcdsaCurve::Secp256k1,
+                        name,
+                    }),
+                    dst,
+                    heap,
+                    method_name,
+                ),
+                _ => Ok(CostSignReturnCode::UnknownCurveOrAlgorithm as u32),
+            },
+        };
+        trace_syscall!(self, CostSignWithEcdsa, result, src, size, curve);
+        result
+    }
+
+    fn ic0_cost_sign_with_schnorr(
+        &self,
+        src: usize,
+        size: usize,
+        algorithm: u32,
+        dst: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<u32> {
+        let method_name = "ic0_cost_sign_with_schnorr";
+        let result = match key_name(method_name, src, size, heap) {
+            Err(err) => Err(err),
+            Ok(name) => {
+                let algorithm = match algorithm {
+                    0 => Some(SchnorrAlgorithm::Bip340Secp256k1),
+                    1 => Some(SchnorrAlgorithm::Ed25519),
+                    _ => None,
+                };
+                match algorithm {
+                    Some(algorithm) => self.copy_sign_with_threshold_key_cost(
+                        MasterPublicKeyId::Schnorr(SchnorrKeyId { algorithm, name }),
+                        dst,
+                        heap,
+                        method_name,
+                    ),
+                    None => Ok(CostSignReturnCode::UnknownCurveOrAlgorithm as u32),
+                }
+            }
+        };
+        trace_syscall!(self, CostSignWithSchnorr, result, src, size, algorithm);
+        result
+    }
+
+    fn ic0_env_var_count(&self) -> HypervisorResult<usize> {
+        let result = match &self.api_type {
+            ApiType::Start { .. } => Err(self.error_for("ic0_env_var_count")),
+            ApiType::Init { .. }
+            | ApiType::SystemTask { .. }
+            | ApiType::Cleanup { .. }
+            | ApiType::Update { .. }
+            | ApiType::ReplicatedQuery { .. }
+            | ApiType::NonReplicatedQuery { .. }
+            | ApiType::PreUpgrade { .. }
+            | ApiType::ReplyCallback { .. }
+            | ApiType::RejectCallback { .. }
+            | ApiType::InspectMessage { .. } => {
+                Ok(self.sandbox_safe_system_state.environment_variables.len())
+            }
+        };
+        trace_syscall!(self, EnvVarCount, result);
+        result
+    }
+
+    fn ic0_env_var_name_size(&self, index: usize) -> HypervisorResult<usize> {
+        let result = match &self.api_type {
+            ApiType::Start { .. } => Err(self.error_for("ic0_env_var_name_size")),
+            ApiType::Init { .. }
+            | ApiType::SystemTask { .. }
+            | ApiType::Cleanup { .. }
+            | ApiType::Update { .. }
+            | ApiType::ReplicatedQuery { .. }
+            | ApiType::NonReplicatedQuery { .. }
+            | ApiType::PreUpgrade { .. }
+            | ApiType::ReplyCallback { .. }
+            | ApiType::RejectCallback { .. }
+            | ApiType::InspectMessage { .. } => self
+                .env_var_name("ic0_env_var_name_size", index)
+                .map(|name| name.len()),
+        };
+        trace_syscall!(self, EnvVarNameSize, result, index);
+        result
+    }
+
+    fn ic0_env_var_name_copy(
+        &self,
+        index: usize,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let result = match &self.api_type {
+            ApiType::Start { .. } => Err(self.error_for("ic0_env_var_name_copy")),
+            ApiType::Init { .. }
+            | ApiType::SystemTask { .. }
+            | ApiType::Cleanup { .. }
+            | ApiType::Update { .. }
+            | ApiType::ReplicatedQuery { .. }
+            | ApiType::NonReplicatedQuery { .. }
+            | ApiType::PreUpgrade { .. }
+            | ApiType::ReplyCallback { .. }
+            | ApiType::RejectCallback { .. }
+            | ApiType::InspectMessage { .. } => {
+                let name = self.env_var_name("ic0_env_var_name_copy", index)?;
+                valid_subslice("ic0.env_var_name_copy heap", dst, size, heap)?;
+                let slice =
+                    valid_subslice("ic0.env_var_name_copy name", offset, size, name.as_bytes())?;
+                deterministic_copy_from_slice(&mut heap[dst..dst + size], slice);
+                Ok(())
+            }
+        };
+        trace_syscall!(
+            self,
+            EnvVarNameCopy,
+            result,
+            index,
+            dst,
+            offset,
+            size,
+            summarize(heap, dst, size)
+        );
+        result
+    }
+
+    fn ic0_env_var_name_exists(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<u32> {
+        let result = match &self.api_type {
+            ApiType::Start { .. } => Err(self.error_for("ic0_env_var_name_exists")),
+            ApiType::Init { .. }
+            | ApiType::SystemTask { .. }
+            | ApiType::Cleanup { .. }
+            | ApiType::Update { .. }
+            | ApiType::ReplicatedQuery { .. }
+            | ApiType::NonReplicatedQuery { .. }
+            | ApiType::PreUpgrade { .. }
+            | ApiType::ReplyCallback { .. }
+            | ApiType::RejectCallback { .. }
+            | ApiType::InspectMessage { .. } => {
+                let name = valid_subslice("ic0.env_var_name_exists", name_src, name_size, heap)?;
+                let exists = std::str::from_utf8(name).map_or(false, |name| {
+                    self.sandbox_safe_system_state
+                        .environment_variables
+                        .contains_key(name)
+                });
+                Ok(exists as u32)
+            }
+        };
+        trace_syscall!(
+            self,
+            EnvVarNameExists,
+            result,
+            summarize(heap, name_src, name_size)
+        );
+        result
+    }
+
+    fn ic0_env_var_value_size(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<usize> {
+        let result = match &self.api_type {
+            ApiType::Start { .. } => Err(self.error_for("ic0_env_var_value_size")),
+            ApiType::Init { .. }
+            | ApiType::SystemTask { .. }
+            | ApiType::Cleanup { .. }
+            | ApiType::Update { .. }
+            | ApiType::ReplicatedQuery { .. }
+            | ApiType::NonReplicatedQuery { .. }
+            | ApiType::PreUpgrade { .. }
+            | ApiType::ReplyCallback { .. }
+            | ApiType::RejectCallback { .. }
+            | ApiType::InspectMessage { .. } => self
+                .env_var_value("ic0_env_var_value_size", name_src, name_size, heap)
+                .map(|value| value.len()),
+        };
+        trace_syscall!(
+            self,
+            EnvVarValueSize,
+            result,
+            summarize(heap, name_src, name_size)
+        );
+        result
+    }
+
+    fn ic0_env_var_value_copy(
+        &self,
+        name_src: usize,
+        name_size: usize,
+        dst: usize,
+        offset: usize,
+        size: usize,
+        heap: &mut [u8],
+    ) -> HypervisorResult<()> {
+        let result = match &self.api_type {
+            ApiType::Start { .. } => Err(self.error_for("ic0_env_var_value_copy")),
+            ApiType::Init { .. }
+            | ApiType::SystemTask { .. }
+            | ApiType::Cleanup { .. }
+            | ApiType::Update { .. }
+            | ApiType::ReplicatedQuery { .. }
+            | ApiType::NonReplicatedQuery { .. }
+            | ApiType::PreUpgrade { .. }
+            | ApiType::ReplyCallback { .. }
+            | ApiType::RejectCallback { .. }
+            | ApiType::InspectMessage { .. } => {
+                let value =
+                    self.env_var_value("ic0_env_var_value_copy", name_src, name_size, heap)?;
+                valid_subslice("ic0.env_var_value_copy heap", dst, size, heap)?;
+                let slice = valid_subslice(
+                    "ic0.env_var_value_copy value",
+                    offset,
+                    size,
+                    value.as_bytes(),
+                )?;
+                deterministic_copy_from_slice(&mut heap[dst..dst + size], slice);
+                Ok(())
+            }
+        };
+        trace_syscall!(
+            self,
+            EnvVarValueCopy,
+            result,
+            dst,
+            offset,
+            size,
+            summarize(heap, dst, size)
+        );
+        result
+    }
+}
+
+/// Return codes of `ic0.cost_sign_with_ecdsa` and `ic0.cost_sign_with_schnorr`.
+enum CostSignReturnCode {
+    Success = 0,
+    UnknownCurveOrAlgorithm = 1,
+    UnknownKey = 2,
+}
+
+/// Reads the key name passed to one of the `ic0.cost_sign_with_*` calls.
+/// A name that is not valid UTF-8 cannot belong to any key, so it is mapped
+/// to an empty name.
+fn key_name(method_name: &str, src: usize, size: usize, heap: &[u8]) -> HypervisorResult<String> {
+    let bytes = valid_subslice(method_name, src, size, heap)?;
+    Ok(std::str::from_utf8(bytes).unwrap_or_default().to_string())
+}
+
+impl SystemApiImpl {
+    /// Returns the name of the environment variable at `index`. The variables
+    /// are sorted by name.
+    fn env_var_name(&self, method_name: &str, index: usize) -> HypervisorResult<&str> {
+        let variables = &self.sandbox_safe_system_state.environment_variables;
+        match variables.keys().nth(index) {
+            Some(name) => Ok(name.as_str()),
+            None => Err(HypervisorError::UserContractViolation {
+                error: format!(
+                    "{} failed because the index {} is out of bounds for {} environment variables.",
+                    method_name,
+                    index,
+                    variables.len()
+                ),
+                suggestion: "Use ic0.env_var_count to get the number of environment variables."
+                    .to_string(),
+                doc_link: "".to_string(),
+            }),
+        }
+    }
+
+    /// Returns the value of the environment variable with the name given by
+    /// heap[name_src..name_src+name_size].
+    fn env_var_value(
+        &self,
+        method_name: &str,
+        name_src: usize,
+        name_size: usize,
+        heap: &[u8],
+    ) -> HypervisorResult<&str> {
+        let name = valid_subslice(method_name, name_src, name_size, heap)?;
+        let variables = &self.sandbox_safe_system_state.environment_variables;
+        match std::str::from_utf8(name)
+            .ok()
+            .and_then(|name| variables.get(name))
+        {
+            Some(value) => Ok(value.as_str()),
+            None => Err(HypervisorError::UserContractViolation {
+                error: format!(
+                    "{} failed because the environment variable '{}' does not exist.",
+                    method_name,
+                    String::from_utf8_lossy(name)
+                ),
+                suggestion: "Use ic0.env_var_name_exists to check whether the variable is set."
+                    .to_string(),
+                doc_link: "".to_string(),
+            }),
+        }
+    }
+
+    fn copy_sign_with_threshold_key_cost(
+        &self,
+        key_id: MasterPublicKeyId,
+        dst: usize,
+        heap: &mut [u8],
+        method_name: &str,
+    ) -> HypervisorResult<u32> {
+        match self
+            .sandbox_safe_system_state
+            .sign_with_threshold_key_cost(&key_id)
+        {
+            Some(cost) => {
+                copy_cycles_to_heap(cost, dst, heap, method_name)?;
+                Ok(CostSignReturnCode::Success as u32)
+            }
+            None => Ok(CostSignReturnCode::UnknownKey as u32),
+        }
+    }
 }
 
 /// The default implementation of the `OutOfInstructionHandler` trait.
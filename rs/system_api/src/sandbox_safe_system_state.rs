@@ -11,8 +11,8 @@ use ic_interfaces::execution_environment::{HypervisorError, HypervisorResult};
 use ic_logger::{info, ReplicaLogger};
 use ic_management_canister_types::{
     CreateCanisterArgs, InstallChunkedCodeArgs, InstallCodeArgsV2, LoadCanisterSnapshotArgs,
-    Method as Ic00Method, Payload, ProvisionalCreateCanisterWithCyclesArgs, UninstallCodeArgs,
-    UpdateSettingsArgs, IC_00,
+    MasterPublicKeyId, Method as Ic00Method, Payload, ProvisionalCreateCanisterWithCyclesArgs,
+    UninstallCodeArgs, UpdateSettingsArgs, IC_00,
 };
 use ic_nns_constants::CYCLES_MINTING_CANISTER_ID;
 use ic_registry_subnet_type::SubnetType;
@@ -260,7 +260,11 @@ impl SystemStateChanges {
             | Ok(Ic00Method::ClearChunkStore)
             | Ok(Ic00Method::TakeCanisterSnapshot)
             | Ok(Ic00Method::ListCanisterSnapshots)
-            | Ok(Ic00Method::DeleteCanisterSnapshot) => Ok(None),
+            | Ok(Ic00Method::DeleteCanisterSnapshot)
+            | Ok(Ic00Method::ReadCanisterSnapshotMetadata)
+            | Ok(Ic00Method::ReadCanisterSnapshotData)
+            | Ok(Ic00Method::UploadCanisterSnapshotMetadata)
+            | Ok(Ic00Method::UploadCanisterSnapshotData) => Ok(None),
             Err(_) => Err(UserError::new(
                 ErrorCode::CanisterMethodNotFound,
                 format!("Management canister has no method '{}'", msg.method_name),
@@ -555,6 +559,9 @@ pub struct SandboxSafeSystemState {
     pub(super) status: CanisterStatusView,
     pub(super) subnet_type: SubnetType,
     pub(super) subnet_size: usize,
+    /// The size of the subnet that signs with each chain key, as used for
+    /// computing signature fees.
+    idkg_signing_subnet_sizes: BTreeMap<MasterPublicKeyId, usize>,
     dirty_page_overhead: NumInstructions,
     freeze_threshold: NumSeconds,
     memory_allocation: MemoryAllocation,
@@ -575,6 +582,8 @@ pub struct SandboxSafeSystemState {
     global_timer: CanisterTimer,
     canister_version: u64,
     controllers: BTreeSet<PrincipalId>,
+    pub(super) environment_variables: BTreeMap<String, String>,
+    pub(super) wasm_backtraces: bool,
     pub(super) request_metadata: RequestMetadata,
     caller: Option<PrincipalId>,
 }
@@ -601,10 +610,13 @@ impl SandboxSafeSystemState {
         ic00_available_request_slots: usize,
         ic00_aliases: BTreeSet<CanisterId>,
         subnet_size: usize,
+        idkg_signing_subnet_sizes: BTreeMap<MasterPublicKeyId, usize>,
         dirty_page_overhead: NumInstructions,
         global_timer: CanisterTimer,
         canister_version: u64,
         controllers: BTreeSet<PrincipalId>,
+        environment_variables: BTreeMap<String, String>,
+        wasm_backtraces: bool,
         request_metadata: RequestMetadata,
         caller: Option<PrincipalId>,
         next_canister_log_record_idx: u64,
@@ -614,6 +626,7 @@ impl SandboxSafeSystemState {
             status,
             subnet_type: cycles_account_manager.subnet_type(),
             subnet_size,
+            idkg_signing_subnet_sizes,
             dirty_page_overhead,
             freeze_threshold,
             memory_allocation,
@@ -638,6 +651,8 @@ impl SandboxSafeSystemState {
             global_timer,
             canister_version,
             controllers,
+            environment_variables,
+            wasm_backtraces,
             request_metadata,
             caller,
         }
@@ -695,6 +710,18 @@ impl SandboxSafeSystemState {
         let subnet_size = network_topology
             .get_subnet_size(&cycles_account_manager.get_subnet_id())
             .unwrap_or(SMALL_APP_SUBNET_MAX_SIZE);
+        // Signing requests are routed to the first subnet enabled to sign
+        // with the key, see `routing::resolve_destination`.
+        let idkg_signing_subnet_sizes = network_topology
+            .idkg_signing_subnets
+            .iter()
+            .filter_map(|(key_id, subnets)| {
+                let subnet_size = network_topology
+                    .get_subnet_size(subnets.first()?)
+                    .unwrap_or(SMALL_APP_SUBNET_MAX_SIZE);
+                Some((key_id.clone(), subnet_size))
+            })
+            .collect();
 
         Self::new_internal(
             system_state.canister_id,
@@ -716,10 +743,13 @@ impl SandboxSafeSystemState {
             ic00_available_request_slots,
             ic00_aliases,
             subnet_size,
+            idkg_signing_subnet_sizes,
             dirty_page_overhead,
             system_state.global_timer,
             system_state.canister_version,
             system_state.controllers.clone(),
+            system_state.environment_variables.clone(),
+            system_state.wasm_backtraces,
             request_metadata,
             caller,
             system_state.canister_log.next_idx(),
@@ -919,6 +949,48 @@ impl SandboxSafeSystemState {
         amount_to_accept
     }
 
+    /// Returns the cycles withdrawn when sending a request with a payload
+    /// (method name and argument) of the given size.
+    pub(super) fn call_cost(&self, payload_size: NumBytes) -> Cycles {
+        self.cycles_account_manager
+            .xnet_call_total_fee(payload_size, self.subnet_size)
+    }
+
+    pub(super) fn create_canister_cost(&self) -> Cycles {
+        self.cycles_account_manager
+            .canister_creation_fee(self.subnet_size)
+    }
+
+    pub(super) fn http_request_cost(
+        &self,
+        request_size: NumBytes,
+        response_size_limit: NumBytes,
+    ) -> Cycles {
+        self.cycles_account_manager.http_request_fee(
+            request_size,
+            Some(response_size_limit),
+            self.subnet_size,
+        )
+    }
+
+    /// Returns the fee for a signature with the given key, or `None` if no
+    /// subnet is enabled to sign with it. The fee depends on the size of the
+    /// signing subnet rather than on the size of this subnet.
+    pub(super) fn sign_with_threshold_key_cost(
+        &self,
+        key_id: &MasterPublicKeyId,
+    ) -> Option<Cycles> {
+        let subnet_size = *self.idkg_signing_subnet_sizes.get(key_id)?;
+        Some(match key_id {
+            MasterPublicKeyId::Ecdsa(_) => {
+                self.cycles_account_manager.ecdsa_signature_fee(subnet_size)
+            }
+            MasterPublicKeyId::Schnorr(_) => self
+                .cycles_account_manager
+                .schnorr_signature_fee(subnet_size),
+        })
+    }
+
     pub fn prepayment_for_response_execution(&self) -> Cycles {
         self.cycles_account_manager
             .prepayment_for_response_execution(self.subnet_size)
@@ -1384,10 +1456,13 @@ mod tests {
             0,
             BTreeSet::new(),
             SMALL_APP_SUBNET_MAX_SIZE,
+            BTreeMap::new(),
             SchedulerConfig::application_subnet().dirty_page_overhead,
             CanisterTimer::Inactive,
             0,
             BTreeSet::new(),
+            BTreeMap::new(),
+            false,
             RequestMetadata::new(0, Time::from_nanos_since_unix_epoch(0)),
             None,
             0,
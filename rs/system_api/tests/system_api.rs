@@ -10,10 +10,13 @@ use ic_interfaces::execution_environment::{
     PerformanceCounterType, SubnetAvailableMemory, SystemApi, SystemApiCallId, TrapCode,
 };
 use ic_logger::replica_logger::no_op_logger;
-use ic_management_canister_types::DataSize;
+use ic_management_canister_types::{
+    DataSize, EcdsaCurve, EcdsaKeyId, MasterPublicKeyId, SchnorrAlgorithm, SchnorrKeyId,
+};
 use ic_registry_subnet_type::SubnetType;
 use ic_replicated_state::{
-    testing::CanisterQueuesTesting, CallOrigin, Memory, NetworkTopology, SystemState,
+    testing::CanisterQueuesTesting, CallOrigin, Memory, NetworkTopology, SubnetTopology,
+    SystemState,
 };
 use ic_system_api::{
     sandbox_safe_system_state::SandboxSafeSystemState, ApiType, DefaultOutOfInstructionsHandler,
@@ -22,7 +25,7 @@ use ic_system_api::{
 use ic_test_utilities::cycles_account_manager::CyclesAccountManagerBuilder;
 use ic_test_utilities_state::SystemStateBuilder;
 use ic_test_utilities_types::{
-    ids::{call_context_test_id, canister_test_id, subnet_test_id, user_test_id},
+    ids::{call_context_test_id, canister_test_id, node_test_id, subnet_test_id, user_test_id},
     messages::RequestBuilder,
 };
 use ic_types::{
@@ -30,7 +33,7 @@ use ic_types::{
     methods::{Callback, WasmClosure},
     time,
     time::UNIX_EPOCH,
-    CanisterTimer, CountBytes, Cycles, NumInstructions, PrincipalId, Time,
+    CanisterTimer, CountBytes, Cycles, NumBytes, NumInstructions, PrincipalId, Time,
     MAX_ALLOWED_CANISTER_LOG_BUFFER_SIZE,
 };
 use maplit::btreemap;
@@ -255,6 +258,17 @@ fn is_supported(api_type: SystemApiCallId, context: &str) -> bool {
         SystemApiCallId::MsgCyclesAccept => vec!["U", "Rt", "Ry"],
         SystemApiCallId::MsgCyclesAccept128 => vec!["U", "Rt", "Ry"],
         SystemApiCallId::CyclesBurn128 => vec!["I", "G", "U", "Ry", "Rt", "C", "T"],
+        SystemApiCallId::CostCall => vec!["*", "s"],
+        SystemApiCallId::CostCreateCanister => vec!["*", "s"],
+        SystemApiCallId::CostHttpRequest => vec!["*", "s"],
+        SystemApiCallId::CostSignWithEcdsa => vec!["*", "s"],
+        SystemApiCallId::CostSignWithSchnorr => vec!["*", "s"],
+        SystemApiCallId::EnvVarCount => vec!["*"],
+        SystemApiCallId::EnvVarNameSize => vec!["*"],
+        SystemApiCallId::EnvVarNameCopy => vec!["*"],
+        SystemApiCallId::EnvVarNameExists => vec!["*"],
+        SystemApiCallId::EnvVarValueSize => vec!["*"],
+        SystemApiCallId::EnvVarValueCopy => vec!["*"],
         SystemApiCallId::CanisterSelfSize => vec!["*"],
         SystemApiCallId::CanisterSelfCopy => vec!["*"],
         SystemApiCallId::CanisterCycleBalance => vec!["*"],
@@ -303,7 +317,8 @@ fn api_availability_test(
     api_type_enum: SystemApiCallId,
     context: &str,
 ) {
-    let system_state = get_system_state();
+    let mut system_state = get_system_state();
+    system_state.environment_variables = btreemap! {"X".to_string() => "value".to_string()};
     match api_type_enum {
         SystemApiCallId::MsgCallerSize => {
             assert_api_availability(
@@ -772,6 +787,116 @@ fn api_availability_test(
                 context,
             );
         }
+        SystemApiCallId::CostCall => {
+            assert_api_availability(
+                |api| api.ic0_cost_call(0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostCreateCanister => {
+            assert_api_availability(
+                |api| api.ic0_cost_create_canister(0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostHttpRequest => {
+            assert_api_availability(
+                |api| api.ic0_cost_http_request(0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostSignWithEcdsa => {
+            assert_api_availability(
+                |api| api.ic0_cost_sign_with_ecdsa(0, 0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::CostSignWithSchnorr => {
+            assert_api_availability(
+                |api| api.ic0_cost_sign_with_schnorr(0, 0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarCount => {
+            assert_api_availability(
+                |api| api.ic0_env_var_count(),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarNameSize => {
+            assert_api_availability(
+                |api| api.ic0_env_var_name_size(0),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarNameCopy => {
+            assert_api_availability(
+                |api| api.ic0_env_var_name_copy(0, 0, 0, 0, &mut [42; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarNameExists => {
+            assert_api_availability(
+                |api| api.ic0_env_var_name_exists(0, 1, &[b'X'; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarValueSize => {
+            assert_api_availability(
+                |api| api.ic0_env_var_value_size(0, 1, &[b'X'; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
+        SystemApiCallId::EnvVarValueCopy => {
+            assert_api_availability(
+                |api| api.ic0_env_var_value_copy(0, 1, 0, 0, 0, &mut [b'X'; 128]),
+                api_type,
+                &system_state,
+                cycles_account_manager,
+                api_type_enum,
+                context,
+            );
+        }
         // stable API is tested separately
         SystemApiCallId::StableGrow
         | SystemApiCallId::StableRead
@@ -1032,9 +1157,10 @@ fn test_canister_cycle_balance() {
     // Check ic0_canister_cycle_balance.
     assert_eq!(
         api.ic0_canister_cycle_balance(),
-        Err(HypervisorError::Trapped(
-            TrapCode::CyclesAmountTooBigFor64Bit
-        ))
+        Err(HypervisorError::Trapped {
+            trap_code: TrapCode::CyclesAmountTooBigFor64Bit,
+            backtrace: None
+        })
     );
 
     let mut heap = vec![0; 16];
@@ -1066,9 +1192,10 @@ fn test_msg_cycles_available_traps() {
 
     assert_eq!(
         api.ic0_msg_cycles_available(),
-        Err(HypervisorError::Trapped(
-            TrapCode::CyclesAmountTooBigFor64Bit
-        ))
+        Err(HypervisorError::Trapped {
+            trap_code: TrapCode::CyclesAmountTooBigFor64Bit,
+            backtrace: None
+        })
     );
 
     let mut heap = vec![0; 16];
@@ -1090,9 +1217,10 @@ fn test_msg_cycles_refunded_traps() {
 
     assert_eq!(
         api.ic0_msg_cycles_refunded(),
-        Err(HypervisorError::Trapped(
-            TrapCode::CyclesAmountTooBigFor64Bit
-        ))
+        Err(HypervisorError::Trapped {
+            trap_code: TrapCode::CyclesAmountTooBigFor64Bit,
+            backtrace: None
+        })
     );
 
     let mut heap = vec![0; 16];
@@ -1699,6 +1827,161 @@ fn test_ic0_cycles_burn() {
     assert_eq!(Cycles::new(0), Cycles::from(&heap));
 }
 
+#[test]
+fn test_ic0_cost_call_create_canister_and_http_request() {
+    let cycles_account_manager = CyclesAccountManagerBuilder::new().build();
+    let api = get_system_api(
+        ApiTypeBuilder::build_update_api(),
+        &SystemStateBuilder::default().build(),
+        cycles_account_manager,
+    );
+
+    let mut heap = vec![0; 16];
+    api.ic0_cost_call(10, 1_000, 0, &mut heap).unwrap();
+    assert_eq!(
+        cycles_account_manager
+            .xnet_call_total_fee(NumBytes::from(1_010), SMALL_APP_SUBNET_MAX_SIZE),
+        Cycles::from(&heap)
+    );
+
+    let mut heap = vec![0; 16];
+    api.ic0_cost_create_canister(0, &mut heap).unwrap();
+    assert_eq!(
+        cycles_account_manager.canister_creation_fee(SMALL_APP_SUBNET_MAX_SIZE),
+        Cycles::from(&heap)
+    );
+
+    let mut heap = vec![0; 16];
+    api.ic0_cost_http_request(100, 2_000, 0, &mut heap).unwrap();
+    assert_eq!(
+        cycles_account_manager.http_request_fee(
+            NumBytes::from(100),
+            Some(NumBytes::from(2_000)),
+            SMALL_APP_SUBNET_MAX_SIZE
+        ),
+        Cycles::from(&heap)
+    );
+}
+
+#[test]
+fn test_ic0_cost_sign_with_threshold_keys() {
+    let signing_subnet_id = subnet_test_id(2);
+    let signing_subnet_size = 34;
+    let ecdsa_key_id = MasterPublicKeyId::Ecdsa(EcdsaKeyId {
+        curve: EcdsaCurve::Secp256k1,
+        name: "ecdsa_key".to_string(),
+    });
+    let schnorr_key_id = MasterPublicKeyId::Schnorr(SchnorrKeyId {
+        algorithm: SchnorrAlgorithm::Ed25519,
+        name: "schnorr_key".to_string(),
+    });
+    let mut network_topology = NetworkTopology::default();
+    network_topology.subnets.insert(
+        signing_subnet_id,
+        SubnetTopology {
+            nodes: (0..signing_subnet_size).map(node_test_id).collect(),
+            ..Default::default()
+        },
+    );
+    network_topology.idkg_signing_subnets = btreemap! {
+        ecdsa_key_id => vec![signing_subnet_id],
+        schnorr_key_id => vec![signing_subnet_id],
+    };
+    let cycles_account_manager = CyclesAccountManagerBuilder::new().build();
+    let mut heap = vec![0; 64];
+    heap[..9].copy_from_slice(b"ecdsa_key");
+    heap[16..27].copy_from_slice(b"schnorr_key");
+    let api = get_system_api_with_network_topology(
+        ApiTypeBuilder::build_update_api(),
+        &SystemStateBuilder::default().build(),
+        cycles_account_manager,
+        &network_topology,
+    );
+
+    // The fees are scaled by the size of the signing subnet.
+    assert_eq!(
+        api.ic0_cost_sign_with_ecdsa(0, 9, 0, 32, &mut heap)
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        cycles_account_manager.ecdsa_signature_fee(signing_subnet_size as usize),
+        Cycles::from(&heap[32..48].to_vec())
+    );
+    assert_eq!(
+        api.ic0_cost_sign_with_schnorr(16, 11, 1, 48, &mut heap)
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        cycles_account_manager.schnorr_signature_fee(signing_subnet_size as usize),
+        Cycles::from(&heap[48..64].to_vec())
+    );
+
+    // Unknown curve or algorithm.
+    assert_eq!(
+        api.ic0_cost_sign_with_ecdsa(0, 9, 1, 32, &mut heap)
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        api.ic0_cost_sign_with_schnorr(16, 11, 2, 48, &mut heap)
+            .unwrap(),
+        1
+    );
+
+    // No subnet signs with the key.
+    heap[32..].fill(0);
+    assert_eq!(
+        api.ic0_cost_sign_with_ecdsa(16, 11, 0, 32, &mut heap)
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        api.ic0_cost_sign_with_schnorr(16, 11, 0, 48, &mut heap)
+            .unwrap(),
+        2
+    );
+    assert_eq!(heap[32..], [0; 32]);
+}
+
+#[test]
+fn test_ic0_env_vars() {
+    let mut system_state = SystemStateBuilder::default().build();
+    system_state.environment_variables = btreemap! {
+        "ENDPOINT".to_string() => "https://staging.example.com".to_string(),
+        "MODE".to_string() => "staging".to_string(),
+    };
+    let api = get_system_api(
+        ApiTypeBuilder::build_update_api(),
+        &system_state,
+        CyclesAccountManagerBuilder::new().build(),
+    );
+
+    assert_eq!(api.ic0_env_var_count().unwrap(), 2);
+
+    // The variables are sorted by name.
+    let mut heap = vec![0; 64];
+    assert_eq!(api.ic0_env_var_name_size(1).unwrap(), 4);
+    api.ic0_env_var_name_copy(1, 0, 0, 4, &mut heap).unwrap();
+    assert_eq!(&heap[..4], b"MODE");
+    api.ic0_env_var_name_copy(0, 4, 2, 6, &mut heap).unwrap();
+    assert_eq!(&heap[4..10], b"DPOINT");
+    assert!(api.ic0_env_var_name_size(2).is_err());
+    assert!(api.ic0_env_var_name_copy(0, 0, 5, 4, &mut heap).is_err());
+
+    assert_eq!(api.ic0_env_var_name_exists(0, 4, &heap).unwrap(), 1);
+    assert_eq!(api.ic0_env_var_name_exists(0, 3, &heap).unwrap(), 0);
+    assert_eq!(api.ic0_env_var_value_size(0, 4, &heap).unwrap(), 7);
+    api.ic0_env_var_value_copy(0, 4, 16, 0, 7, &mut heap)
+        .unwrap();
+    assert_eq!(&heap[16..23], b"staging");
+    assert!(api.ic0_env_var_value_size(0, 3, &heap).is_err());
+    assert!(api
+        .ic0_env_var_value_copy(0, 4, 16, 1, 7, &mut heap)
+        .is_err());
+}
+
 const CANISTER_LOGGING_IS_ENABLED: bool = true;
 
 #[test]
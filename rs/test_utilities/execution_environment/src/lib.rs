@@ -23,8 +23,8 @@ use ic_logger::{replica_logger::no_op_logger, ReplicaLogger};
 use ic_management_canister_types::{
     CanisterIdRecord, CanisterInstallMode, CanisterInstallModeV2, CanisterSettingsArgs,
     CanisterSettingsArgsBuilder, CanisterStatusType, CanisterUpgradeOptions, EmptyBlob,
-    InstallCodeArgs, InstallCodeArgsV2, LogVisibility, MasterPublicKeyId, Method, Payload,
-    ProvisionalCreateCanisterWithCyclesArgs, UpdateSettingsArgs,
+    EnvironmentVariable, InstallCodeArgs, InstallCodeArgsV2, LogVisibility, MasterPublicKeyId,
+    Method, Payload, ProvisionalCreateCanisterWithCyclesArgs, UpdateSettingsArgs,
 };
 use ic_metrics::MetricsRegistry;
 use ic_registry_provisional_whitelist::ProvisionalWhitelist;
@@ -697,6 +697,23 @@ impl ExecutionTest {
         self.subnet_message(Method::UpdateSettings, payload)
     }
 
+    /// Replaces the environment variables of the canister.
+    pub fn set_environment_variables(
+        &mut self,
+        canister_id: CanisterId,
+        environment_variables: Vec<EnvironmentVariable>,
+    ) -> Result<WasmResult, UserError> {
+        let payload = UpdateSettingsArgs {
+            canister_id: canister_id.into(),
+            settings: CanisterSettingsArgsBuilder::new()
+                .with_environment_variables(environment_variables)
+                .build(),
+            sender_canister_version: None,
+        }
+        .encode();
+        self.subnet_message(Method::UpdateSettings, payload)
+    }
+
     /// Installs the given Wasm binary in the given canister.
     pub fn install_canister(
         &mut self,
@@ -1976,6 +1993,11 @@ impl ExecutionTestBuilder {
         self
     }
 
+    pub fn with_execution_profiling(mut self, status: FlagStatus) -> Self {
+        self.execution_config.embedders_config.execution_profiling = status;
+        self
+    }
+
     pub fn with_ic00_compute_initial_i_dkg_dealings(mut self, status: FlagStatus) -> Self {
         self.execution_config.ic00_compute_initial_i_dkg_dealings = status;
         self
@@ -47,6 +47,9 @@ const WASM_HASH_LENGTH: usize = 32;
 /// See https://github.com/bitcoin/bips/blob/master/bip-0032.mediawiki#serialization-format
 /// for details
 const MAXIMUM_DERIVATION_PATH_LENGTH: usize = 255;
+/// The maximum number of bytes that can be read or uploaded in a single
+/// `read_canister_snapshot_data` or `upload_canister_snapshot_data` call.
+pub const MAX_SNAPSHOT_DATA_SLICE_SIZE: u64 = 2_000_000;
 
 /// Limit the amount of work for skipping unneeded data on the wire when parsing Candid.
 /// The value of 10_000 follows the Candid recommendation.
@@ -115,6 +118,12 @@ pub enum Method {
     LoadCanisterSnapshot,
     ListCanisterSnapshots,
     DeleteCanisterSnapshot,
+
+    // Support for downloading and uploading canister snapshots.
+    ReadCanisterSnapshotMetadata,
+    ReadCanisterSnapshotData,
+    UploadCanisterSnapshotMetadata,
+    UploadCanisterSnapshotData,
 }
 
 fn candid_error_to_user_error(err: candid::Error) -> UserError {
@@ -760,21 +769,32 @@ impl Payload<'_> for UninstallCodeArgs {}
 /// variant {
 ///    controllers;
 ///    public;
+///    allowed_viewers: vec principal;
 /// }
 /// ```
-#[derive(Default, Clone, Copy, CandidType, Deserialize, Debug, PartialEq, Eq, EnumIter)]
+#[derive(Default, Clone, CandidType, Deserialize, Debug, PartialEq, Eq, EnumIter)]
 pub enum LogVisibility {
     #[default]
     #[serde(rename = "controllers")]
-    Controllers = 1,
+    Controllers,
     #[serde(rename = "public")]
-    Public = 2,
+    Public,
+    /// The logs are visible to the controllers and the listed principals.
+    ///
+    /// Not part of the legacy (v1) encoding, hence excluded from `iter()`.
+    #[serde(rename = "allowed_viewers")]
+    #[strum(disabled)]
+    AllowedViewers(Vec<PrincipalId>),
 }
 
+/// The legacy encoding cannot represent allowed viewers, so they fall back to
+/// the more restrictive `Controllers`.
 impl From<&LogVisibility> for pb_canister_state_bits::LogVisibility {
     fn from(item: &LogVisibility) -> Self {
         match item {
-            LogVisibility::Controllers => pb_canister_state_bits::LogVisibility::Controllers,
+            LogVisibility::Controllers | LogVisibility::AllowedViewers(_) => {
+                pb_canister_state_bits::LogVisibility::Controllers
+            }
             LogVisibility::Public => pb_canister_state_bits::LogVisibility::Public,
         }
     }
@@ -790,6 +810,68 @@ impl From<pb_canister_state_bits::LogVisibility> for LogVisibility {
     }
 }
 
+impl From<&LogVisibility> for pb_canister_state_bits::LogVisibilityV2 {
+    fn from(item: &LogVisibility) -> Self {
+        use pb_canister_state_bits::log_visibility_v2::LogVisibilityV2;
+
+        let log_visibility_v2 = match item {
+            LogVisibility::Controllers => LogVisibilityV2::Controllers(1),
+            LogVisibility::Public => LogVisibilityV2::Public(2),
+            LogVisibility::AllowedViewers(principals) => LogVisibilityV2::AllowedViewers(
+                pb_canister_state_bits::LogVisibilityAllowedViewers {
+                    principals: principals.iter().map(|p| (*p).into()).collect(),
+                },
+            ),
+        };
+        Self {
+            log_visibility_v2: Some(log_visibility_v2),
+        }
+    }
+}
+
+impl TryFrom<pb_canister_state_bits::LogVisibilityV2> for LogVisibility {
+    type Error = ProxyDecodeError;
+
+    fn try_from(item: pb_canister_state_bits::LogVisibilityV2) -> Result<Self, Self::Error> {
+        use pb_canister_state_bits::log_visibility_v2::LogVisibilityV2;
+
+        match try_from_option_field(item.log_visibility_v2, "LogVisibilityV2::log_visibility_v2")? {
+            LogVisibilityV2::Controllers(_) => Ok(Self::Controllers),
+            LogVisibilityV2::Public(_) => Ok(Self::Public),
+            LogVisibilityV2::AllowedViewers(allowed_viewers) => {
+                let principals = allowed_viewers
+                    .principals
+                    .into_iter()
+                    .map(PrincipalId::try_from)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Self::AllowedViewers(principals))
+            }
+        }
+    }
+}
+
+/// An environment variable of a canister.
+/// ```text
+/// record {
+///     name : text;
+///     value : text;
+/// }
+/// ```
+#[derive(Clone, CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub struct EnvironmentVariable {
+    pub name: String,
+    pub value: String,
+}
+
+impl EnvironmentVariable {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+}
+
 /// Struct used for encoding/decoding
 /// `(record {
 ///     controller : principal;
@@ -799,6 +881,8 @@ impl From<pb_canister_state_bits::LogVisibility> for LogVisibility {
 ///     reserved_cycles_limit: nat;
 ///     log_visibility: log_visibility;
 ///     wasm_memory_limit: nat;
+///     environment_variables: vec environment_variable;
+///     wasm_backtraces: bool;
 /// })`
 #[derive(CandidType, Clone, Deserialize, Debug, Eq, PartialEq)]
 pub struct DefiniteCanisterSettingsArgs {
@@ -810,9 +894,12 @@ pub struct DefiniteCanisterSettingsArgs {
     reserved_cycles_limit: candid::Nat,
     log_visibility: LogVisibility,
     wasm_memory_limit: candid::Nat,
+    environment_variables: Vec<EnvironmentVariable>,
+    wasm_backtraces: bool,
 }
 
 impl DefiniteCanisterSettingsArgs {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         controller: PrincipalId,
         controllers: Vec<PrincipalId>,
@@ -822,6 +909,8 @@ impl DefiniteCanisterSettingsArgs {
         reserved_cycles_limit: Option<u128>,
         log_visibility: LogVisibility,
         wasm_memory_limit: Option<u64>,
+        environment_variables: Vec<EnvironmentVariable>,
+        wasm_backtraces: bool,
     ) -> Self {
         let memory_allocation = candid::Nat::from(memory_allocation.unwrap_or(0));
         let reserved_cycles_limit = candid::Nat::from(reserved_cycles_limit.unwrap_or(0));
@@ -835,6 +924,8 @@ impl DefiniteCanisterSettingsArgs {
             reserved_cycles_limit,
             log_visibility,
             wasm_memory_limit,
+            environment_variables,
+            wasm_backtraces,
         }
     }
 
@@ -847,7 +938,15 @@ impl DefiniteCanisterSettingsArgs {
     }
 
     pub fn log_visibility(&self) -> LogVisibility {
-        self.log_visibility
+        self.log_visibility.clone()
+    }
+
+    pub fn environment_variables(&self) -> &[EnvironmentVariable] {
+        &self.environment_variables
+    }
+
+    pub fn wasm_backtraces(&self) -> bool {
+        self.wasm_backtraces
     }
 }
 
@@ -917,6 +1016,219 @@ pub struct QueryStats {
     response_payload_bytes_total: candid::Nat,
 }
 
+/// Struct used for encoding/decoding
+/// `(record {
+///     wasm_memory_size : nat;
+///     stable_memory_size : nat;
+///     global_memory_size : nat;
+///     wasm_binary_size : nat;
+///     custom_sections_size : nat;
+///     canister_history_size : nat;
+///     wasm_chunk_store_size : nat;
+///     snapshots_size : nat;
+///     message_memory_size : nat;
+/// })`
+#[derive(Clone, CandidType, Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct MemoryMetrics {
+    wasm_memory_size: candid::Nat,
+    stable_memory_size: candid::Nat,
+    global_memory_size: candid::Nat,
+    wasm_binary_size: candid::Nat,
+    custom_sections_size: candid::Nat,
+    canister_history_size: candid::Nat,
+    wasm_chunk_store_size: candid::Nat,
+    snapshots_size: candid::Nat,
+    message_memory_size: candid::Nat,
+}
+
+impl MemoryMetrics {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        wasm_memory_size: NumBytes,
+        stable_memory_size: NumBytes,
+        global_memory_size: NumBytes,
+        wasm_binary_size: NumBytes,
+        custom_sections_size: NumBytes,
+        canister_history_size: NumBytes,
+        wasm_chunk_store_size: NumBytes,
+        snapshots_size: NumBytes,
+        message_memory_size: NumBytes,
+    ) -> Self {
+        Self {
+            wasm_memory_size: candid::Nat::from(wasm_memory_size.get()),
+            stable_memory_size: candid::Nat::from(stable_memory_size.get()),
+            global_memory_size: candid::Nat::from(global_memory_size.get()),
+            wasm_binary_size: candid::Nat::from(wasm_binary_size.get()),
+            custom_sections_size: candid::Nat::from(custom_sections_size.get()),
+            canister_history_size: candid::Nat::from(canister_history_size.get()),
+            wasm_chunk_store_size: candid::Nat::from(wasm_chunk_store_size.get()),
+            snapshots_size: candid::Nat::from(snapshots_size.get()),
+            message_memory_size: candid::Nat::from(message_memory_size.get()),
+        }
+    }
+
+    pub fn wasm_memory_size(&self) -> NumBytes {
+        NumBytes::from(self.wasm_memory_size.0.to_u64().unwrap_or(u64::MAX))
+    }
+
+    pub fn stable_memory_size(&self) -> NumBytes {
+        NumBytes::from(self.stable_memory_size.0.to_u64().unwrap_or(u64::MAX))
+    }
+
+    pub fn global_memory_size(&self) -> NumBytes {
+        NumBytes::from(self.global_memory_size.0.to_u64().unwrap_or(u64::MAX))
+    }
+
+    pub fn wasm_binary_size(&self) -> NumBytes {
+        NumBytes::from(self.wasm_binary_size.0.to_u64().unwrap_or(u64::MAX))
+    }
+
+    pub fn custom_sections_size(&self) -> NumBytes {
+        NumBytes::from(self.custom_sections_size.0.to_u64().unwrap_or(u64::MAX))
+    }
+
+    pub fn canister_history_size(&self) -> NumBytes {
+        NumBytes::from(self.canister_history_size.0.to_u64().unwrap_or(u64::MAX))
+    }
+
+    pub fn wasm_chunk_store_size(&self) -> NumBytes {
+        NumBytes::from(self.wasm_chunk_store_size.0.to_u64().unwrap_or(u64::MAX))
+    }
+
+    pub fn snapshots_size(&self) -> NumBytes {
+        NumBytes::from(self.snapshots_size.0.to_u64().unwrap_or(u64::MAX))
+    }
+
+    pub fn message_memory_size(&self) -> NumBytes {
+        NumBytes::from(self.message_memory_size.0.to_u64().unwrap_or(u64::MAX))
+    }
+}
+
+/// Cycles burned by a canister since its creation, broken down by use case.
+///
+/// Struct used for encoding/decoding
+/// `(record {
+///     memory : nat;
+///     compute_allocation : nat;
+///     ingress_induction : nat;
+///     instructions : nat;
+///     request_and_response_transmission : nat;
+///     http_outcalls : nat;
+///     ecdsa_outcalls : nat;
+///     schnorr_outcalls : nat;
+///     uninstall : nat;
+///     canister_creation : nat;
+///     deleted_canisters : nat;
+///     non_consumed : nat;
+///     burned_cycles : nat;
+/// })`
+#[derive(Clone, CandidType, Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct CyclesBurned {
+    memory: candid::Nat,
+    compute_allocation: candid::Nat,
+    ingress_induction: candid::Nat,
+    instructions: candid::Nat,
+    request_and_response_transmission: candid::Nat,
+    http_outcalls: candid::Nat,
+    ecdsa_outcalls: candid::Nat,
+    schnorr_outcalls: candid::Nat,
+    uninstall: candid::Nat,
+    canister_creation: candid::Nat,
+    deleted_canisters: candid::Nat,
+    non_consumed: candid::Nat,
+    burned_cycles: candid::Nat,
+}
+
+impl CyclesBurned {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        memory: u128,
+        compute_allocation: u128,
+        ingress_induction: u128,
+        instructions: u128,
+        request_and_response_transmission: u128,
+        http_outcalls: u128,
+        ecdsa_outcalls: u128,
+        schnorr_outcalls: u128,
+        uninstall: u128,
+        canister_creation: u128,
+        deleted_canisters: u128,
+        non_consumed: u128,
+        burned_cycles: u128,
+    ) -> Self {
+        Self {
+            memory: candid::Nat::from(memory),
+            compute_allocation: candid::Nat::from(compute_allocation),
+            ingress_induction: candid::Nat::from(ingress_induction),
+            instructions: candid::Nat::from(instructions),
+            request_and_response_transmission: candid::Nat::from(request_and_response_transmission),
+            http_outcalls: candid::Nat::from(http_outcalls),
+            ecdsa_outcalls: candid::Nat::from(ecdsa_outcalls),
+            schnorr_outcalls: candid::Nat::from(schnorr_outcalls),
+            uninstall: candid::Nat::from(uninstall),
+            canister_creation: candid::Nat::from(canister_creation),
+            deleted_canisters: candid::Nat::from(deleted_canisters),
+            non_consumed: candid::Nat::from(non_consumed),
+            burned_cycles: candid::Nat::from(burned_cycles),
+        }
+    }
+
+    pub fn memory(&self) -> u128 {
+        self.memory.0.to_u128().unwrap_or(u128::MAX)
+    }
+
+    pub fn compute_allocation(&self) -> u128 {
+        self.compute_allocation.0.to_u128().unwrap_or(u128::MAX)
+    }
+
+    pub fn ingress_induction(&self) -> u128 {
+        self.ingress_induction.0.to_u128().unwrap_or(u128::MAX)
+    }
+
+    pub fn instructions(&self) -> u128 {
+        self.instructions.0.to_u128().unwrap_or(u128::MAX)
+    }
+
+    pub fn request_and_response_transmission(&self) -> u128 {
+        self.request_and_response_transmission
+            .0
+            .to_u128()
+            .unwrap_or(u128::MAX)
+    }
+
+    pub fn http_outcalls(&self) -> u128 {
+        self.http_outcalls.0.to_u128().unwrap_or(u128::MAX)
+    }
+
+    pub fn ecdsa_outcalls(&self) -> u128 {
+        self.ecdsa_outcalls.0.to_u128().unwrap_or(u128::MAX)
+    }
+
+    pub fn schnorr_outcalls(&self) -> u128 {
+        self.schnorr_outcalls.0.to_u128().unwrap_or(u128::MAX)
+    }
+
+    pub fn uninstall(&self) -> u128 {
+        self.uninstall.0.to_u128().unwrap_or(u128::MAX)
+    }
+
+    pub fn canister_creation(&self) -> u128 {
+        self.canister_creation.0.to_u128().unwrap_or(u128::MAX)
+    }
+
+    pub fn deleted_canisters(&self) -> u128 {
+        self.deleted_canisters.0.to_u128().unwrap_or(u128::MAX)
+    }
+
+    pub fn non_consumed(&self) -> u128 {
+        self.non_consumed.0.to_u128().unwrap_or(u128::MAX)
+    }
+
+    pub fn burned_cycles(&self) -> u128 {
+        self.burned_cycles.0.to_u128().unwrap_or(u128::MAX)
+    }
+}
+
 /// Struct used for encoding/decoding
 /// `(record {
 ///     status : variant { running; stopping; stopped };
@@ -933,7 +1245,9 @@ pub struct QueryStats {
 ///         num_instructions: nat;
 ///         ingress_payload_size: nat;
 ///         egress_payload_size: nat;
-///     }
+///     };
+///     memory_metrics: memory_metrics;
+///     cycles_burned: cycles_burned;
 /// })`
 #[derive(CandidType, Debug, Deserialize, Eq, PartialEq)]
 pub struct CanisterStatusResultV2 {
@@ -949,6 +1263,8 @@ pub struct CanisterStatusResultV2 {
     idle_cycles_burned_per_day: candid::Nat,
     reserved_cycles: candid::Nat,
     query_stats: QueryStats,
+    memory_metrics: MemoryMetrics,
+    cycles_burned: CyclesBurned,
 }
 
 impl CanisterStatusResultV2 {
@@ -972,6 +1288,10 @@ impl CanisterStatusResultV2 {
         query_ingress_payload_size: u128,
         query_egress_payload_size: u128,
         wasm_memory_limit: Option<u64>,
+        environment_variables: Vec<EnvironmentVariable>,
+        wasm_backtraces: bool,
+        memory_metrics: MemoryMetrics,
+        cycles_burned: CyclesBurned,
     ) -> Self {
         Self {
             status,
@@ -991,6 +1311,8 @@ impl CanisterStatusResultV2 {
                 reserved_cycles_limit,
                 log_visibility,
                 wasm_memory_limit,
+                environment_variables,
+                wasm_backtraces,
             ),
             freezing_threshold: candid::Nat::from(freezing_threshold),
             idle_cycles_burned_per_day: candid::Nat::from(idle_cycles_burned_per_day),
@@ -1001,6 +1323,8 @@ impl CanisterStatusResultV2 {
                 request_payload_bytes_total: candid::Nat::from(query_ingress_payload_size),
                 response_payload_bytes_total: candid::Nat::from(query_egress_payload_size),
             },
+            memory_metrics,
+            cycles_burned,
         }
     }
 
@@ -1051,6 +1375,14 @@ impl CanisterStatusResultV2 {
     pub fn settings(&self) -> DefiniteCanisterSettingsArgs {
         self.settings.clone()
     }
+
+    pub fn memory_metrics(&self) -> &MemoryMetrics {
+        &self.memory_metrics
+    }
+
+    pub fn cycles_burned(&self) -> &CyclesBurned {
+        &self.cycles_burned
+    }
 }
 
 /// Indicates whether the canister is running, stopping, or stopped.
@@ -1640,6 +1972,8 @@ impl DataSize for PrincipalId {
 ///     reserved_cycles_limit: opt nat;
 ///     log_visibility : opt log_visibility;
 ///     wasm_memory_limit: opt nat;
+///     environment_variables: opt vec environment_variable;
+///     wasm_backtraces: opt bool;
 /// })`
 #[derive(Default, Clone, CandidType, Deserialize, Debug, PartialEq, Eq)]
 pub struct CanisterSettingsArgs {
@@ -1652,6 +1986,8 @@ pub struct CanisterSettingsArgs {
     pub reserved_cycles_limit: Option<candid::Nat>,
     pub log_visibility: Option<LogVisibility>,
     pub wasm_memory_limit: Option<candid::Nat>,
+    pub environment_variables: Option<Vec<EnvironmentVariable>>,
+    pub wasm_backtraces: Option<bool>,
 }
 
 impl Payload<'_> for CanisterSettingsArgs {}
@@ -1669,6 +2005,8 @@ impl CanisterSettingsArgs {
             reserved_cycles_limit: None,
             log_visibility: None,
             wasm_memory_limit: None,
+            environment_variables: None,
+            wasm_backtraces: None,
         }
     }
 
@@ -1687,6 +2025,8 @@ pub struct CanisterSettingsArgsBuilder {
     reserved_cycles_limit: Option<candid::Nat>,
     log_visibility: Option<LogVisibility>,
     wasm_memory_limit: Option<candid::Nat>,
+    environment_variables: Option<Vec<EnvironmentVariable>>,
+    wasm_backtraces: Option<bool>,
 }
 
 #[allow(dead_code)]
@@ -1705,6 +2045,8 @@ impl CanisterSettingsArgsBuilder {
             reserved_cycles_limit: self.reserved_cycles_limit,
             log_visibility: self.log_visibility,
             wasm_memory_limit: self.wasm_memory_limit,
+            environment_variables: self.environment_variables,
+            wasm_backtraces: self.wasm_backtraces,
         }
     }
 
@@ -1788,6 +2130,26 @@ impl CanisterSettingsArgsBuilder {
             ..self
         }
     }
+
+    /// Replaces all environment variables of the canister.
+    pub fn with_environment_variables(
+        self,
+        environment_variables: Vec<EnvironmentVariable>,
+    ) -> Self {
+        Self {
+            environment_variables: Some(environment_variables),
+            ..self
+        }
+    }
+
+    /// Enables or disables symbolized Wasm backtraces in the errors and the
+    /// canister log when the canister traps.
+    pub fn with_wasm_backtraces(self, wasm_backtraces: bool) -> Self {
+        Self {
+            wasm_backtraces: Some(wasm_backtraces),
+            ..self
+        }
+    }
 }
 
 /// Struct used for encoding/decoding
@@ -2637,15 +2999,60 @@ pub struct NodeMetricsHistoryResponse {
 
 impl Payload<'_> for NodeMetricsHistoryResponse {}
 
+/// `CandidType` for `FetchCanisterLogsRange`
+/// ```text
+/// record {
+///     start: nat64;
+///     end: nat64;
+/// }
+/// ```
+/// The range includes `start` and excludes `end`.
+#[derive(Default, Clone, Copy, CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub struct FetchCanisterLogsRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl FetchCanisterLogsRange {
+    pub fn new(start: u64, end: u64) -> Self {
+        Self { start, end }
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        self.start <= value && value < self.end
+    }
+}
+
+/// `CandidType` for `FetchCanisterLogsFilter`
+/// ```text
+/// variant {
+///     substring: text;
+///     regex: text;
+/// }
+/// ```
+#[derive(Clone, CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub enum FetchCanisterLogsFilter {
+    #[serde(rename = "substring")]
+    Substring(String),
+    #[serde(rename = "regex")]
+    Regex(String),
+}
+
 /// `CandidType` for `FetchCanisterLogsRequest`
 /// ```text
 /// record {
 ///     canister_id: principal;
+///     idx_range: opt fetch_canister_logs_range;
+///     timestamp_nanos_range: opt fetch_canister_logs_range;
+///     content_filter: opt fetch_canister_logs_filter;
 /// }
 /// ```
 #[derive(Default, Clone, CandidType, Deserialize, Debug)]
 pub struct FetchCanisterLogsRequest {
     pub canister_id: PrincipalId,
+    pub idx_range: Option<FetchCanisterLogsRange>,
+    pub timestamp_nanos_range: Option<FetchCanisterLogsRange>,
+    pub content_filter: Option<FetchCanisterLogsFilter>,
 }
 
 impl Payload<'_> for FetchCanisterLogsRequest {}
@@ -2654,9 +3061,28 @@ impl FetchCanisterLogsRequest {
     pub fn new(canister_id: CanisterId) -> Self {
         Self {
             canister_id: canister_id.into(),
+            ..Default::default()
         }
     }
 
+    /// Only returns the records whose index is in the given range.
+    pub fn with_idx_range(mut self, start: u64, end: u64) -> Self {
+        self.idx_range = Some(FetchCanisterLogsRange::new(start, end));
+        self
+    }
+
+    /// Only returns the records whose timestamp is in the given range.
+    pub fn with_timestamp_nanos_range(mut self, start: u64, end: u64) -> Self {
+        self.timestamp_nanos_range = Some(FetchCanisterLogsRange::new(start, end));
+        self
+    }
+
+    /// Only returns the records whose content matches the given filter.
+    pub fn with_content_filter(mut self, content_filter: FetchCanisterLogsFilter) -> Self {
+        self.content_filter = Some(content_filter);
+        self
+    }
+
     pub fn get_canister_id(&self) -> CanisterId {
         CanisterId::unchecked_from_principal(self.canister_id)
     }
@@ -3169,6 +3595,417 @@ impl ListCanisterSnapshotArgs {
 
 impl Payload<'_> for ListCanisterSnapshotArgs {}
 
+/// Validates that the given bytes represent a well-formed snapshot ID.
+fn validate_snapshot_id(snapshot_id: &[u8]) -> Result<(), UserError> {
+    SnapshotId::try_from(&snapshot_id.to_vec())
+        .map(|_| ())
+        .map_err(|err| {
+            UserError::new(
+                ErrorCode::InvalidManagementPayload,
+                format!("Payload deserialization error: {err:?}"),
+            )
+        })
+}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     canister_id: principal;
+///     snapshot_id: blob;
+/// })`
+#[derive(Default, Clone, CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub struct ReadCanisterSnapshotMetadataArgs {
+    pub canister_id: PrincipalId,
+    #[serde(with = "serde_bytes")]
+    pub snapshot_id: Vec<u8>,
+}
+
+impl ReadCanisterSnapshotMetadataArgs {
+    pub fn new(canister_id: CanisterId, snapshot_id: SnapshotId) -> Self {
+        Self {
+            canister_id: canister_id.get(),
+            snapshot_id: snapshot_id.to_vec(),
+        }
+    }
+
+    pub fn get_canister_id(&self) -> CanisterId {
+        CanisterId::unchecked_from_principal(self.canister_id)
+    }
+
+    pub fn get_snapshot_id(&self) -> SnapshotId {
+        SnapshotId::try_from(&self.snapshot_id).unwrap()
+    }
+}
+
+impl<'a> Payload<'a> for ReadCanisterSnapshotMetadataArgs {
+    fn decode(blob: &'a [u8]) -> Result<Self, UserError> {
+        let args = Decode!([decoder_config()]; blob, Self).map_err(candid_error_to_user_error)?;
+        validate_snapshot_id(&args.snapshot_id)?;
+        Ok(args)
+    }
+}
+
+/// Describes how a canister snapshot was created.
+/// ```text
+/// variant {
+///     taken_from_canister;
+///     metadata_upload;
+/// }
+/// ```
+#[derive(Default, Clone, Copy, CandidType, Deserialize, Debug, PartialEq, Eq, EnumIter)]
+pub enum SnapshotSource {
+    #[default]
+    #[serde(rename = "taken_from_canister")]
+    TakenFromCanister = 1,
+    #[serde(rename = "metadata_upload")]
+    MetadataUpload = 2,
+}
+
+impl From<SnapshotSource> for pb_canister_state_bits::SnapshotSource {
+    fn from(item: SnapshotSource) -> Self {
+        match item {
+            SnapshotSource::TakenFromCanister => {
+                pb_canister_state_bits::SnapshotSource::TakenFromCanister
+            }
+            SnapshotSource::MetadataUpload => {
+                pb_canister_state_bits::SnapshotSource::MetadataUpload
+            }
+        }
+    }
+}
+
+impl From<pb_canister_state_bits::SnapshotSource> for SnapshotSource {
+    fn from(item: pb_canister_state_bits::SnapshotSource) -> Self {
+        match item {
+            pb_canister_state_bits::SnapshotSource::Unspecified => Self::default(),
+            pb_canister_state_bits::SnapshotSource::TakenFromCanister => Self::TakenFromCanister,
+            pb_canister_state_bits::SnapshotSource::MetadataUpload => Self::MetadataUpload,
+        }
+    }
+}
+
+/// The value of an exported Wasm global as stored in a canister snapshot.
+/// ```text
+/// variant {
+///     i32 : int32;
+///     i64 : int64;
+///     f32 : float32;
+///     f64 : float64;
+///     v128 : nat;
+/// }
+/// ```
+#[derive(Copy, Clone, CandidType, Deserialize, Debug, PartialEq)]
+pub enum Global {
+    #[serde(rename = "i32")]
+    I32(i32),
+    #[serde(rename = "i64")]
+    I64(i64),
+    #[serde(rename = "f32")]
+    F32(f32),
+    #[serde(rename = "f64")]
+    F64(f64),
+    #[serde(rename = "v128")]
+    V128(u128),
+}
+
+/// Struct to be returned when reading the metadata of a canister snapshot.
+/// `(record {
+///     source: variant { taken_from_canister; metadata_upload };
+///     taken_at_timestamp: nat64;
+///     wasm_module_size: nat64;
+///     exported_globals: vec global;
+///     wasm_memory_size: nat64;
+///     stable_memory_size: nat64;
+///     wasm_chunk_store: vec record { hash: blob };
+///     canister_version: nat64;
+///     certified_data: blob;
+/// })`
+#[derive(Clone, CandidType, Deserialize, Debug, PartialEq)]
+pub struct ReadCanisterSnapshotMetadataResponse {
+    pub source: SnapshotSource,
+    pub taken_at_timestamp: u64,
+    pub wasm_module_size: u64,
+    pub exported_globals: Vec<Global>,
+    pub wasm_memory_size: u64,
+    pub stable_memory_size: u64,
+    pub wasm_chunk_store: Vec<ChunkHash>,
+    pub canister_version: u64,
+    #[serde(with = "serde_bytes")]
+    pub certified_data: Vec<u8>,
+}
+
+impl Payload<'_> for ReadCanisterSnapshotMetadataResponse {}
+
+/// Selects the part of a canister snapshot to be read.
+/// ```text
+/// variant {
+///     wasm_module : record { offset : nat64; size : nat64 };
+///     main_memory : record { offset : nat64; size : nat64 };
+///     stable_memory : record { offset : nat64; size : nat64 };
+///     wasm_chunk : record { hash : blob };
+/// }
+/// ```
+#[derive(Clone, CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub enum CanisterSnapshotDataKind {
+    #[serde(rename = "wasm_module")]
+    WasmModule { offset: u64, size: u64 },
+    #[serde(rename = "main_memory")]
+    MainMemory { offset: u64, size: u64 },
+    #[serde(rename = "stable_memory")]
+    StableMemory { offset: u64, size: u64 },
+    #[serde(rename = "wasm_chunk")]
+    WasmChunk {
+        #[serde(with = "serde_bytes")]
+        hash: Vec<u8>,
+    },
+}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     canister_id: principal;
+///     snapshot_id: blob;
+///     kind: canister_snapshot_data_kind;
+/// })`
+#[derive(Clone, CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub struct ReadCanisterSnapshotDataArgs {
+    pub canister_id: PrincipalId,
+    #[serde(with = "serde_bytes")]
+    pub snapshot_id: Vec<u8>,
+    pub kind: CanisterSnapshotDataKind,
+}
+
+impl ReadCanisterSnapshotDataArgs {
+    pub fn new(
+        canister_id: CanisterId,
+        snapshot_id: SnapshotId,
+        kind: CanisterSnapshotDataKind,
+    ) -> Self {
+        Self {
+            canister_id: canister_id.get(),
+            snapshot_id: snapshot_id.to_vec(),
+            kind,
+        }
+    }
+
+    pub fn get_canister_id(&self) -> CanisterId {
+        CanisterId::unchecked_from_principal(self.canister_id)
+    }
+
+    pub fn get_snapshot_id(&self) -> SnapshotId {
+        SnapshotId::try_from(&self.snapshot_id).unwrap()
+    }
+}
+
+impl<'a> Payload<'a> for ReadCanisterSnapshotDataArgs {
+    fn decode(blob: &'a [u8]) -> Result<Self, UserError> {
+        let args = Decode!([decoder_config()]; blob, Self).map_err(candid_error_to_user_error)?;
+        validate_snapshot_id(&args.snapshot_id)?;
+        match &args.kind {
+            CanisterSnapshotDataKind::WasmModule { size, .. }
+            | CanisterSnapshotDataKind::MainMemory { size, .. }
+            | CanisterSnapshotDataKind::StableMemory { size, .. } => {
+                if *size > MAX_SNAPSHOT_DATA_SLICE_SIZE {
+                    return Err(UserError::new(
+                        ErrorCode::InvalidManagementPayload,
+                        format!(
+                            "Requested slice size {} exceeds the maximum of {} bytes",
+                            size, MAX_SNAPSHOT_DATA_SLICE_SIZE
+                        ),
+                    ));
+                }
+            }
+            CanisterSnapshotDataKind::WasmChunk { .. } => {}
+        }
+        Ok(args)
+    }
+}
+
+/// Struct to be returned when reading data of a canister snapshot.
+/// `(record {
+///     chunk: blob;
+/// })`
+#[derive(Default, Clone, CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub struct ReadCanisterSnapshotDataResponse {
+    #[serde(with = "serde_bytes")]
+    pub chunk: Vec<u8>,
+}
+
+impl Payload<'_> for ReadCanisterSnapshotDataResponse {}
+
+impl ReadCanisterSnapshotDataResponse {
+    pub fn new(chunk: Vec<u8>) -> Self {
+        Self { chunk }
+    }
+}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     canister_id: principal;
+///     replace_snapshot: opt blob;
+///     wasm_module_size: nat64;
+///     exported_globals: vec global;
+///     wasm_memory_size: nat64;
+///     stable_memory_size: nat64;
+///     certified_data: blob;
+/// })`
+#[derive(Clone, CandidType, Deserialize, Debug, PartialEq)]
+pub struct UploadCanisterSnapshotMetadataArgs {
+    pub canister_id: PrincipalId,
+    pub replace_snapshot: Option<serde_bytes::ByteBuf>,
+    pub wasm_module_size: u64,
+    pub exported_globals: Vec<Global>,
+    pub wasm_memory_size: u64,
+    pub stable_memory_size: u64,
+    #[serde(with = "serde_bytes")]
+    pub certified_data: Vec<u8>,
+}
+
+impl UploadCanisterSnapshotMetadataArgs {
+    pub fn new(
+        canister_id: CanisterId,
+        replace_snapshot: Option<SnapshotId>,
+        wasm_module_size: u64,
+        exported_globals: Vec<Global>,
+        wasm_memory_size: u64,
+        stable_memory_size: u64,
+        certified_data: Vec<u8>,
+    ) -> Self {
+        Self {
+            canister_id: canister_id.get(),
+            replace_snapshot: replace_snapshot
+                .map(|snapshot_id| ByteBuf::from(snapshot_id.to_vec())),
+            wasm_module_size,
+            exported_globals,
+            wasm_memory_size,
+            stable_memory_size,
+            certified_data,
+        }
+    }
+
+    pub fn get_canister_id(&self) -> CanisterId {
+        CanisterId::unchecked_from_principal(self.canister_id)
+    }
+
+    pub fn replace_snapshot(&self) -> Option<SnapshotId> {
+        self.replace_snapshot
+            .as_ref()
+            .map(|bytes| SnapshotId::try_from(&bytes.clone().into_vec()).unwrap())
+    }
+}
+
+impl<'a> Payload<'a> for UploadCanisterSnapshotMetadataArgs {
+    fn decode(blob: &'a [u8]) -> Result<Self, UserError> {
+        let args = Decode!([decoder_config()]; blob, Self).map_err(candid_error_to_user_error)?;
+        if let Some(replace_snapshot) = &args.replace_snapshot {
+            validate_snapshot_id(replace_snapshot)?;
+        }
+        Ok(args)
+    }
+}
+
+/// Struct to be returned when uploading the metadata of a canister snapshot.
+/// `(record {
+///     snapshot_id: blob;
+/// })`
+#[derive(Default, Clone, CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub struct UploadCanisterSnapshotMetadataResponse {
+    #[serde(with = "serde_bytes")]
+    pub snapshot_id: Vec<u8>,
+}
+
+impl Payload<'_> for UploadCanisterSnapshotMetadataResponse {}
+
+impl UploadCanisterSnapshotMetadataResponse {
+    pub fn new(snapshot_id: &SnapshotId) -> Self {
+        Self {
+            snapshot_id: snapshot_id.to_vec(),
+        }
+    }
+
+    pub fn get_snapshot_id(&self) -> SnapshotId {
+        SnapshotId::try_from(&self.snapshot_id).unwrap()
+    }
+}
+
+/// Selects the part of a canister snapshot to be written.
+/// ```text
+/// variant {
+///     wasm_module : record { offset : nat64 };
+///     main_memory : record { offset : nat64 };
+///     stable_memory : record { offset : nat64 };
+///     wasm_chunk;
+/// }
+/// ```
+#[derive(Clone, CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub enum CanisterSnapshotDataOffset {
+    #[serde(rename = "wasm_module")]
+    WasmModule { offset: u64 },
+    #[serde(rename = "main_memory")]
+    MainMemory { offset: u64 },
+    #[serde(rename = "stable_memory")]
+    StableMemory { offset: u64 },
+    #[serde(rename = "wasm_chunk")]
+    WasmChunk,
+}
+
+/// Struct used for encoding/decoding
+/// `(record {
+///     canister_id: principal;
+///     snapshot_id: blob;
+///     kind: canister_snapshot_data_offset;
+///     chunk: blob;
+/// })`
+#[derive(Clone, CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub struct UploadCanisterSnapshotDataArgs {
+    pub canister_id: PrincipalId,
+    #[serde(with = "serde_bytes")]
+    pub snapshot_id: Vec<u8>,
+    pub kind: CanisterSnapshotDataOffset,
+    #[serde(with = "serde_bytes")]
+    pub chunk: Vec<u8>,
+}
+
+impl UploadCanisterSnapshotDataArgs {
+    pub fn new(
+        canister_id: CanisterId,
+        snapshot_id: SnapshotId,
+        kind: CanisterSnapshotDataOffset,
+        chunk: Vec<u8>,
+    ) -> Self {
+        Self {
+            canister_id: canister_id.get(),
+            snapshot_id: snapshot_id.to_vec(),
+            kind,
+            chunk,
+        }
+    }
+
+    pub fn get_canister_id(&self) -> CanisterId {
+        CanisterId::unchecked_from_principal(self.canister_id)
+    }
+
+    pub fn get_snapshot_id(&self) -> SnapshotId {
+        SnapshotId::try_from(&self.snapshot_id).unwrap()
+    }
+}
+
+impl<'a> Payload<'a> for UploadCanisterSnapshotDataArgs {
+    fn decode(blob: &'a [u8]) -> Result<Self, UserError> {
+        let args = Decode!([decoder_config()]; blob, Self).map_err(candid_error_to_user_error)?;
+        validate_snapshot_id(&args.snapshot_id)?;
+        if args.chunk.len() as u64 > MAX_SNAPSHOT_DATA_SLICE_SIZE {
+            return Err(UserError::new(
+                ErrorCode::InvalidManagementPayload,
+                format!(
+                    "Uploaded slice size {} exceeds the maximum of {} bytes",
+                    args.chunk.len(),
+                    MAX_SNAPSHOT_DATA_SLICE_SIZE
+                ),
+            ));
+        }
+        Ok(args)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3219,6 +4056,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn memory_metrics_and_cycles_burned_getters_saturate() {
+        let too_large = candid::Nat::from(u128::MAX) + candid::Nat::from(1_u8);
+        let memory_metrics = MemoryMetrics {
+            wasm_memory_size: too_large.clone(),
+            ..Default::default()
+        };
+        assert_eq!(memory_metrics.wasm_memory_size(), NumBytes::from(u64::MAX));
+        assert_eq!(memory_metrics.stable_memory_size(), NumBytes::from(0));
+
+        let cycles_burned = CyclesBurned {
+            burned_cycles: too_large,
+            ..Default::default()
+        };
+        assert_eq!(cycles_burned.burned_cycles(), u128::MAX);
+        assert_eq!(cycles_burned.memory(), 0);
+    }
+
     #[test]
     fn verify_max_bounded_controllers_length() {
         const TEST_START: usize = 5;
@@ -3371,6 +4226,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn snapshot_source_round_trip() {
+        for source in SnapshotSource::iter() {
+            let encoded = pb_canister_state_bits::SnapshotSource::from(source);
+            assert_eq!(SnapshotSource::from(encoded), source);
+        }
+    }
+
+    #[test]
+    fn read_canister_snapshot_data_args_rejects_large_slices() {
+        let snapshot_id = SnapshotId::from((CanisterId::from_u64(1), 0));
+        let args = ReadCanisterSnapshotDataArgs::new(
+            CanisterId::from_u64(1),
+            snapshot_id,
+            CanisterSnapshotDataKind::MainMemory {
+                offset: 0,
+                size: MAX_SNAPSHOT_DATA_SLICE_SIZE,
+            },
+        );
+        assert_eq!(
+            ReadCanisterSnapshotDataArgs::decode(&args.encode()),
+            Ok(args)
+        );
+
+        let args = ReadCanisterSnapshotDataArgs::new(
+            CanisterId::from_u64(1),
+            snapshot_id,
+            CanisterSnapshotDataKind::MainMemory {
+                offset: 0,
+                size: MAX_SNAPSHOT_DATA_SLICE_SIZE + 1,
+            },
+        );
+        let err = ReadCanisterSnapshotDataArgs::decode(&args.encode()).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+    }
+
+    #[test]
+    fn upload_canister_snapshot_data_args_rejects_large_chunks() {
+        let snapshot_id = SnapshotId::from((CanisterId::from_u64(1), 0));
+        let args = UploadCanisterSnapshotDataArgs::new(
+            CanisterId::from_u64(1),
+            snapshot_id,
+            CanisterSnapshotDataOffset::StableMemory { offset: 0 },
+            vec![0; MAX_SNAPSHOT_DATA_SLICE_SIZE as usize + 1],
+        );
+        let err = UploadCanisterSnapshotDataArgs::decode(&args.encode()).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidManagementPayload);
+    }
+
     #[test]
     fn verify_max_derivation_path_length() {
         for i in 0..=MAXIMUM_DERIVATION_PATH_LENGTH {
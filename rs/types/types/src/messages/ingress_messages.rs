@@ -519,7 +519,11 @@ pub fn extract_effective_canister_id(
         | Ok(Method::TakeCanisterSnapshot)
         | Ok(Method::LoadCanisterSnapshot)
         | Ok(Method::ListCanisterSnapshots)
-        | Ok(Method::DeleteCanisterSnapshot) => Err(ParseIngressError::UnknownSubnetMethod),
+        | Ok(Method::DeleteCanisterSnapshot)
+        | Ok(Method::ReadCanisterSnapshotMetadata)
+        | Ok(Method::ReadCanisterSnapshotData)
+        | Ok(Method::UploadCanisterSnapshotMetadata)
+        | Ok(Method::UploadCanisterSnapshotData) => Err(ParseIngressError::UnknownSubnetMethod),
 
         Ok(Method::CreateCanister)
         | Ok(Method::SetupInitialDKG)
@@ -181,7 +181,11 @@ impl Request {
             | Ok(Method::TakeCanisterSnapshot)
             | Ok(Method::LoadCanisterSnapshot)
             | Ok(Method::ListCanisterSnapshots)
-            | Ok(Method::DeleteCanisterSnapshot) => None,
+            | Ok(Method::DeleteCanisterSnapshot)
+            | Ok(Method::ReadCanisterSnapshotMetadata)
+            | Ok(Method::ReadCanisterSnapshotData)
+            | Ok(Method::UploadCanisterSnapshotMetadata)
+            | Ok(Method::UploadCanisterSnapshotData) => None,
             Ok(Method::CreateCanister)
             | Ok(Method::SetupInitialDKG)
             | Ok(Method::HttpRequest)